@@ -155,3 +155,167 @@ fn bench_byteorder(b: &mut test::Bencher) {
     });
     b.bytes = 2 * NITER as u64;
 }
+
+/// A WebAssembly function's local declarations: 1M (count, type) pairs, with counts of 1 to 3 bytes
+fn wasm_locals() -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(4_000_000);
+    let mut count = [0u8; 3];
+    for i in 0..1_000_000u32 {
+        let len = scroll::Uleb128::write_u128(&mut count, &mut 0, u128::from(i * 7919 % 0x20_0000)).unwrap();
+        bytes.extend_from_slice(&count[..len]);
+        bytes.push(0x7f);
+    }
+    bytes
+}
+
+#[bench]
+fn bench_gread_uleb128(b: &mut test::Bencher) {
+    use std::convert::TryFrom;
+    let bytes = wasm_locals();
+    b.iter(|| {
+        let data = black_box(&bytes[..]);
+        let offset = &mut 0;
+        while *offset < data.len() {
+            let count: scroll::Uleb128 = data.gread(offset).unwrap();
+            let _: u32 = black_box(u32::try_from(count).unwrap());
+            let _: u8 = black_box(data.gread(offset).unwrap());
+        }
+    });
+    b.bytes = bytes.len() as u64;
+}
+
+#[bench]
+fn bench_gread_leb128_u32(b: &mut test::Bencher) {
+    use scroll::PreadExt;
+    let bytes = wasm_locals();
+    b.iter(|| {
+        let data = black_box(&bytes[..]);
+        let offset = &mut 0;
+        while *offset < data.len() {
+            let _: u32 = black_box(data.gread_leb128_u32(offset).unwrap());
+            let _: u8 = black_box(data.gread(offset).unwrap());
+        }
+    });
+    b.bytes = bytes.len() as u64;
+}
+
+/// An index column: 1M ULEB128 values packed back to back, of 1 to 3 bytes
+fn packed_uleb128s() -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(3_000_000);
+    let mut value = [0u8; 3];
+    for i in 0..1_000_000u32 {
+        let len = scroll::Uleb128::write_u128(&mut value, &mut 0, u128::from(i * 7919 % 0x20_0000)).unwrap();
+        bytes.extend_from_slice(&value[..len]);
+    }
+    bytes
+}
+
+#[bench]
+fn bench_gread_uleb128_packed(b: &mut test::Bencher) {
+    let bytes = packed_uleb128s();
+    b.iter(|| {
+        let data = black_box(&bytes[..]);
+        let mut values = Vec::new();
+        let offset = &mut 0;
+        while *offset < data.len() {
+            values.push(data.gread::<scroll::Uleb128>(offset).unwrap().value());
+        }
+        black_box(values)
+    });
+    b.bytes = bytes.len() as u64;
+}
+
+#[bench]
+fn bench_decode_uleb128_all(b: &mut test::Bencher) {
+    let bytes = packed_uleb128s();
+    b.iter(|| black_box(scroll::leb128::decode_uleb128_all(black_box(&bytes[..]), 0).unwrap()));
+    b.bytes = bytes.len() as u64;
+}
+
+fn offset_table() -> Vec<u32> {
+    (0..1_000_000u32).map(|i| i.wrapping_mul(24)).collect()
+}
+
+#[bench]
+fn bench_gwrite_offset_table(b: &mut test::Bencher) {
+    use scroll::{Pwrite, BE};
+    let table = offset_table();
+    let mut bytes = vec![0u8; table.len() * 4];
+    b.iter(|| {
+        let offset = &mut 0;
+        for entry in black_box(&table[..]) {
+            bytes.gwrite_with(*entry, offset, BE).unwrap();
+        }
+        black_box(&bytes);
+    });
+    b.bytes = bytes.len() as u64;
+}
+
+#[bench]
+fn bench_gwrite_slice_with_offset_table(b: &mut test::Bencher) {
+    use scroll::{PwriteExt, BE};
+    let table = offset_table();
+    let mut bytes = vec![0u8; table.len() * 4];
+    b.iter(|| {
+        bytes.gwrite_slice_with(&mut 0, black_box(&table[..]), BE).unwrap();
+        black_box(&bytes);
+    });
+    b.bytes = bytes.len() as u64;
+}
+
+#[bench]
+fn bench_gwrite_slice_with_offset_table_native(b: &mut test::Bencher) {
+    use scroll::{PwriteExt, NATIVE};
+    let table = offset_table();
+    let mut bytes = vec![0u8; table.len() * 4];
+    b.iter(|| {
+        bytes.gwrite_slice_with(&mut 0, black_box(&table[..]), NATIVE).unwrap();
+        black_box(&bytes);
+    });
+    b.bytes = bytes.len() as u64;
+}
+
+// only `addr` is read, as when scanning a table for one field of each record
+#[allow(dead_code)]
+#[derive(scroll_derive::Pread, scroll_derive::PreadView)]
+struct TableRecord {
+    name: [u8; 16],
+    kind: u16,
+    flags: u16,
+    #[scroll(ctx = "scroll::BE")]
+    addr: u64,
+    size: u32,
+    checksum: u32,
+    links: [u32; 4],
+}
+
+fn record_table() -> Vec<u8> {
+    use scroll::Pwrite;
+    let mut table = vec![0u8; 100_000 * TableRecordView::FIXED_SIZE];
+    for (i, record) in table.chunks_mut(TableRecordView::FIXED_SIZE).enumerate() {
+        record.pwrite_with(i as u64 * 0x1000, 20, scroll::BE).unwrap();
+    }
+    table
+}
+
+#[bench]
+fn bench_pread_view_one_field(b: &mut test::Bencher) {
+    let table = record_table();
+    b.iter(|| {
+        black_box(&table[..]).chunks(TableRecordView::FIXED_SIZE)
+            .map(|bytes| TableRecordView::new(bytes, LE).unwrap().addr().unwrap())
+            .sum::<u64>()
+    });
+    b.bytes = table.len() as u64;
+}
+
+#[bench]
+fn bench_pread_struct_one_field(b: &mut test::Bencher) {
+    let table = record_table();
+    b.iter(|| {
+        black_box(&table[..]).chunks(TableRecordView::FIXED_SIZE)
+            .map(|bytes| bytes.pread_with::<TableRecord>(0, LE).unwrap().addr)
+            .sum::<u64>()
+    });
+    b.bytes = table.len() as u64;
+}
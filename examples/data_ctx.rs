@@ -12,7 +12,7 @@ impl<'a> ctx::TryFromCtx<'a, Endian> for Data<'a> {
                      -> Result<(Self, usize), Self::Error> {
         let name = src.pread::<&'a str>(0)?;
         let id = src.pread_with(name.len()+1, endian)?;
-        Ok((Data { name: name, id: id }, name.len()+4))
+        Ok((Data { name, id }, name.len()+4))
     }
 }
 
@@ -1,44 +1,789 @@
 #![recursion_limit="1024"]
 
 extern crate proc_macro;
-use proc_macro2;
-use quote::quote;
+use quote::{quote, quote_spanned};
+use syn::parse_quote;
+use syn::spanned::Spanned;
 
 use proc_macro::TokenStream;
+use std::convert::TryFrom;
 
-fn impl_struct(name: &syn::Ident, fields: &syn::FieldsNamed) -> proc_macro2::TokenStream {
+/// The `#[scroll(..)]` attributes of a struct
+#[derive(Default)]
+struct StructAttrs {
+    /// `default_ctx = "BE"`: the endianness `DefaultCtx` returns
+    default_ctx: Option<syn::Ident>,
+    /// `magic = b"..."`: bytes which precede the first field, and aren't stored
+    magic: Option<syn::LitByteStr>,
+    /// `verify_writes`: check the `eq` and `assert` attributes of the fields before writing them, too
+    verify_writes: bool,
+    /// `ctx = "MyCtx"`: the context the struct is read and written with, instead of `Endian`
+    ctx: Option<syn::Type>,
+    /// `ctx_bound = "C: Copy + HasEndian"`: makes the context a type parameter of the impls, with this bound
+    ctx_bound: Option<syn::WherePredicate>,
+    /// `size = 0x60`: the number of bytes the struct takes up, past its last field
+    size: Option<usize>,
+    /// `zero_gaps`: write zeroes to the gaps before fields at explicit offsets, and before the struct's size
+    zero_gaps: bool,
+    /// `bits = "u16"`: the integer the fields of a bitfield struct are packed into
+    bits: Option<syn::Type>,
+    /// `bit_order = "LsbFirst"`: the first field of a bitfield struct is in the least significant bits, instead of the most
+    lsb_first: bool,
+    /// `endian = "BE"`: the byte order of a bitfield struct's integer, whatever the context
+    endian: Option<syn::Ident>,
+    /// `debug_offsets`: also derive a `dump` of the struct's fields, with where each is
+    debug_offsets: bool,
+}
+
+/// The `#[scroll(..)]` attributes of a field
+#[derive(Default)]
+struct FieldAttrs {
+    /// `eq = 0x1234`: the value the field must hold
+    eq: Option<syn::Lit>,
+    /// `assert = "self.version <= 4"`: a condition the field must meet, along with its source text
+    assert: Option<(syn::Expr, String)>,
+    /// `error = "invalid magic"`: the message of the `Error::BadInput` a failed `eq` or `assert` is, instead of the default error
+    error: Option<syn::LitStr>,
+    /// `parse_with = "path::to::fn"`: reads the field instead of `gread_with`
+    parse_with: Option<syn::Path>,
+    /// `write_with = "path::to::fn"`: writes the field instead of `gwrite_with`
+    write_with: Option<syn::Path>,
+    /// `if = "self.version >= 2"`: the condition under which a field is present
+    condition: Option<syn::Expr>,
+    /// `optional_trailing`: an `Option` field which is absent when the input ends right before it
+    optional_trailing: bool,
+    /// `ctx = "scroll::BE"`: the context of the field, instead of the struct's
+    ctx: Option<syn::Expr>,
+    /// `leb128`: an integer field stored as a ULEB128, or an SLEB128 if it is signed, instead of with the context
+    leb128: bool,
+    /// `offset = 0x40`: where the field is from the start of the struct, instead of right after the field before it
+    offset: Option<usize>,
+    /// `bits = 3`: the width of a field of a bitfield struct
+    bits: Option<u32>,
+}
+
+/// The items of every `#[scroll(..)]` attribute in `attrs`
+fn scroll_items(attrs: &[syn::Attribute]) -> Vec<syn::NestedMeta> {
+    attrs.iter()
+        .filter(|attr| attr.path.segments.len() == 1 && attr.path.segments[0].ident == "scroll")
+        .flat_map(|attr| match attr.parse_meta() {
+            Ok(syn::Meta::List(list)) => list.nested.into_iter(),
+            _ => panic!("malformed scroll attribute, expected e.g. `#[scroll(magic = b\"\\x7fELF\")]`"),
+        })
+        .collect()
+}
+
+fn struct_attrs(attrs: &[syn::Attribute]) -> StructAttrs {
+    let mut parsed = StructAttrs::default();
+    for item in scroll_items(attrs) {
+        match item {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue { ref ident, ref lit, .. })) if ident == "default_ctx" => {
+                match *lit {
+                    syn::Lit::Str(ref lit) if ["BE", "LE", "NATIVE", "NETWORK"].contains(&&lit.value()[..]) => {
+                        parsed.default_ctx = Some(syn::Ident::new(&lit.value(), lit.span()))
+                    },
+                    _ => panic!("default_ctx must be one of \"BE\", \"LE\", \"NATIVE\" or \"NETWORK\""),
+                }
+            },
+            syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue { ref ident, ref lit, .. })) if ident == "magic" => {
+                match *lit {
+                    syn::Lit::ByteStr(ref lit) => parsed.magic = Some(lit.clone()),
+                    _ => panic!("magic must be a byte string, e.g. `b\"\\x7fELF\"`"),
+                }
+            },
+            syn::NestedMeta::Meta(syn::Meta::Word(ref ident)) if ident == "verify_writes" => parsed.verify_writes = true,
+            syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue { ref ident, ref lit, .. })) if ident == "ctx" => {
+                match *lit {
+                    syn::Lit::Str(ref lit) => {
+                        parsed.ctx = Some(lit.parse().unwrap_or_else(|_| panic!("ctx must be a type, found `{}`", lit.value())))
+                    },
+                    _ => panic!("ctx must be a string, e.g. `ctx = \"MyCtx\"`"),
+                }
+            },
+            syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue { ref ident, ref lit, .. })) if ident == "ctx_bound" => {
+                match *lit {
+                    syn::Lit::Str(ref lit) => {
+                        parsed.ctx_bound = Some(lit.parse().unwrap_or_else(|_| panic!("ctx_bound must be a bound, found `{}`", lit.value())))
+                    },
+                    _ => panic!("ctx_bound must be a string, e.g. `ctx_bound = \"C: Copy + HasEndian\"`"),
+                }
+            },
+            syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue { ref ident, ref lit, .. })) if ident == "size" => {
+                match *lit {
+                    syn::Lit::Int(ref lit) => parsed.size = Some(int_value(lit, "size")),
+                    _ => panic!("size must be an integer, e.g. `size = 0x60`"),
+                }
+            },
+            syn::NestedMeta::Meta(syn::Meta::Word(ref ident)) if ident == "zero_gaps" => parsed.zero_gaps = true,
+            syn::NestedMeta::Meta(syn::Meta::Word(ref ident)) if ident == "debug_offsets" => parsed.debug_offsets = true,
+            syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue { ref ident, ref lit, .. })) if ident == "bits" => {
+                match *lit {
+                    syn::Lit::Str(ref lit) if ["u8", "u16", "u32", "u64", "u128"].contains(&&lit.value()[..]) => {
+                        parsed.bits = Some(lit.parse().unwrap())
+                    },
+                    _ => panic!("the bits of a struct must be one of \"u8\", \"u16\", \"u32\", \"u64\" or \"u128\""),
+                }
+            },
+            syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue { ref ident, ref lit, .. })) if ident == "bit_order" => {
+                match *lit {
+                    syn::Lit::Str(ref lit) if lit.value() == "MsbFirst" || lit.value() == "LsbFirst" => {
+                        parsed.lsb_first = lit.value() == "LsbFirst"
+                    },
+                    _ => panic!("bit_order must be \"MsbFirst\" or \"LsbFirst\""),
+                }
+            },
+            syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue { ref ident, ref lit, .. })) if ident == "endian" => {
+                match *lit {
+                    syn::Lit::Str(ref lit) if ["BE", "LE", "NATIVE", "NETWORK"].contains(&&lit.value()[..]) => {
+                        parsed.endian = Some(syn::Ident::new(&lit.value(), lit.span()))
+                    },
+                    _ => panic!("endian must be one of \"BE\", \"LE\", \"NATIVE\" or \"NETWORK\""),
+                }
+            },
+            _ => panic!("unknown scroll attribute on a struct, expected `default_ctx`, `magic`, `verify_writes`, `ctx`, `ctx_bound`, `size`, `zero_gaps`, `bits`, `bit_order`, `endian` or `debug_offsets`"),
+        }
+    }
+    if parsed.bits.is_none() && (parsed.lsb_first || parsed.endian.is_some()) {
+        panic!("bit_order and endian are for bitfield structs, which need their `bits`, e.g. `#[scroll(bits = \"u16\")]`")
+    }
+    if parsed.bits.is_some() && (parsed.magic.is_some() || parsed.ctx.is_some() || parsed.size.is_some() || parsed.zero_gaps || parsed.debug_offsets) {
+        panic!("a bitfield struct cannot have magic, ctx, size, zero_gaps or debug_offsets")
+    }
+    if parsed.ctx.is_some() && parsed.default_ctx.is_some() {
+        panic!("default_ctx gives an `Endian` context, so it cannot be used with a custom ctx")
+    }
+    if parsed.ctx_bound.is_some() && ctx_param(&parsed).is_none() {
+        panic!("ctx_bound needs the context's type parameter, e.g. `ctx = \"C\", ctx_bound = \"C: Copy + HasEndian\"`")
+    }
+    parsed
+}
+
+fn field_attrs(attrs: &[syn::Attribute]) -> FieldAttrs {
+    let mut parsed = FieldAttrs::default();
+    for item in scroll_items(attrs) {
+        match item {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue { ref ident, ref lit, .. })) if ident == "eq" => {
+                parsed.eq = Some(lit.clone())
+            },
+            syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue { ref ident, ref lit, .. })) if ident == "assert" => {
+                match *lit {
+                    syn::Lit::Str(ref lit) => {
+                        let expr = syn::parse_str(&lit.value()).unwrap_or_else(|_| panic!("assert must be an expression, found `{}`", lit.value()));
+                        parsed.assert = Some((expr, lit.value()))
+                    },
+                    _ => panic!("assert must be a string, e.g. `assert = \"self.version <= 4\"`"),
+                }
+            },
+            syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue { ref ident, ref lit, .. })) if ident == "error" => {
+                match *lit {
+                    syn::Lit::Str(ref lit) => parsed.error = Some(lit.clone()),
+                    _ => panic!("error must be a string, e.g. `error = \"invalid magic\"`"),
+                }
+            },
+            syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue { ref ident, ref lit, .. })) if ident == "parse_with" || ident == "write_with" => {
+                let path = match *lit {
+                    syn::Lit::Str(ref lit) => lit.parse().unwrap_or_else(|_| panic!("{} must be a path to a function, found `{}`", ident, lit.value())),
+                    _ => panic!("{} must be a string, e.g. `{} = \"path::to::fn\"`", ident, ident),
+                };
+                if ident == "parse_with" {
+                    parsed.parse_with = Some(path)
+                } else {
+                    parsed.write_with = Some(path)
+                }
+            },
+            syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue { ref ident, ref lit, .. })) if ident == "if" => {
+                match *lit {
+                    syn::Lit::Str(ref lit) => {
+                        parsed.condition = Some(syn::parse_str(&lit.value()).unwrap_or_else(|_| panic!("if must be an expression, found `{}`", lit.value())))
+                    },
+                    _ => panic!("if must be a string, e.g. `if = \"self.version >= 2\"`"),
+                }
+            },
+            syn::NestedMeta::Meta(syn::Meta::Word(ref ident)) if ident == "optional_trailing" => parsed.optional_trailing = true,
+            syn::NestedMeta::Meta(syn::Meta::Word(ref ident)) if ident == "leb128" => parsed.leb128 = true,
+            syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue { ref ident, ref lit, .. })) if ident == "ctx" => {
+                match *lit {
+                    syn::Lit::Str(ref lit) => {
+                        parsed.ctx = Some(lit.parse().unwrap_or_else(|_| panic!("ctx must be an expression, found `{}`", lit.value())))
+                    },
+                    _ => panic!("ctx must be a string, e.g. `ctx = \"scroll::ctx::StrCtx::Delimiter(0)\"`"),
+                }
+            },
+            syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue { ref ident, ref lit, .. })) if ident == "offset" => {
+                match *lit {
+                    syn::Lit::Int(ref lit) => parsed.offset = Some(int_value(lit, "offset")),
+                    _ => panic!("offset must be an integer, e.g. `offset = 0x40`"),
+                }
+            },
+            syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue { ref ident, ref lit, .. })) if ident == "bits" => {
+                match *lit {
+                    syn::Lit::Int(ref lit) if lit.value() > 0 => parsed.bits = Some(int_value(lit, "bits")),
+                    _ => panic!("the bits of a field must be a positive integer, e.g. `bits = 3`"),
+                }
+            },
+            _ => panic!("unknown scroll attribute on a field, expected `eq`, `assert`, `error`, `parse_with`, `write_with`, `if`, `optional_trailing`, `ctx`, `leb128`, `offset` or `bits`"),
+        }
+    }
+    if parsed.offset.is_some() && parsed.optional_trailing {
+        panic!("an optional_trailing field is wherever the input ends, so it cannot have an offset")
+    }
+    if parsed.condition.is_some() && parsed.optional_trailing {
+        panic!("a field cannot be both conditional and optional_trailing")
+    }
+    if parsed.error.is_some() && parsed.eq.is_none() && parsed.assert.is_none() {
+        panic!("error is the message of a failed `eq` or `assert`, which the field doesn't have")
+    }
+    if parsed.leb128 && (parsed.parse_with.is_some() || parsed.write_with.is_some() || parsed.ctx.is_some()) {
+        panic!("a leb128 field cannot also have parse_with, write_with or ctx")
+    }
+    parsed
+}
+
+/// The value of the integer `lit` given for `name`, which must fit in a `T`
+fn int_value<T: TryFrom<u64>>(lit: &syn::LitInt, name: &str) -> T {
+    T::try_from(lit.value()).unwrap_or_else(|_| panic!("the {} `{}` is too large", name, lit.value()))
+}
+
+/// Checks that the explicit offsets of the fields come after the magic, increase, and are within the struct's size
+fn check_layout(fields: &syn::FieldsNamed, attrs: &StructAttrs) {
+    if let Some(f) = fields.named.iter().find(|f| field_attrs(&f.attrs).bits.is_some()) {
+        panic!("field `{}` has bits, but the struct doesn't, e.g. `#[scroll(bits = \"u16\")]`", f.ident.as_ref().unwrap())
+    }
+    let mut last = attrs.magic.as_ref().map_or(0, |magic| magic.value().len());
+    for f in fields.named.iter() {
+        if let Some(offset) = field_attrs(&f.attrs).offset {
+            if offset < last {
+                panic!("the offset of field `{}` must come after the magic and the explicit offsets before it", f.ident.as_ref().unwrap())
+            }
+            last = offset;
+        }
+    }
+    match attrs.size {
+        Some(size) if size < last => panic!("the struct's size must come after the explicit offsets of its fields"),
+        _ => (),
+    }
+    if attrs.zero_gaps && attrs.size.is_none() && fields.named.iter().all(|f| field_attrs(&f.attrs).offset.is_none()) {
+        panic!("zero_gaps needs fields with explicit offsets, or the struct's size")
+    }
+}
+
+/// Whether the `leb128` field of type `ty` is signed, i.e., an SLEB128 rather than a ULEB128
+fn leb128_signed(ty: &syn::Type) -> bool {
+    let ident = match *ty {
+        syn::Type::Path(ref path) if path.qself.is_none() => path.path.segments.last().map(|segment| segment.value().ident.to_string()),
+        _ => None,
+    };
+    match ident.as_deref() {
+        Some("u8") | Some("u16") | Some("u32") | Some("u64") | Some("u128") | Some("usize") => false,
+        Some("i8") | Some("i16") | Some("i32") | Some("i64") | Some("i128") | Some("isize") => true,
+        _ => panic!("leb128 fields must be primitive integers"),
+    }
+}
+
+/// The error for the field `ident`, holding `value` at `at`, failing its `eq` or `assert` check, whose source text is `text`
+fn check_failed(error: &Option<syn::LitStr>, ident: &Option<syn::Ident>, text: &str, value: proc_macro2::TokenStream, at: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    match *error {
+        Some(ref msg) => quote! {
+            ::scroll::Error::BadInput { size: ::scroll::export::mem::size_of_val(&#value), msg: #msg }
+        },
+        None => quote! {
+            ::scroll::export::field_mismatch(stringify!(#ident), #text, &#value, #at)
+        },
+    }
+}
+
+/// The `T` of an `Option<T>` field, which `optional_trailing` requires; an `if` field which isn't an `Option` is
+/// defaulted instead when its condition fails
+fn option_inner(ty: &syn::Type) -> Option<&syn::Type> {
+    if let syn::Type::Path(syn::TypePath { qself: None, ref path }) = *ty {
+        let last = path.segments.last().unwrap().into_value();
+        if last.ident == "Option" {
+            if let syn::PathArguments::AngleBracketed(ref args) = last.arguments {
+                if let Some(syn::GenericArgument::Type(ref inner)) = args.args.first().map(|arg| arg.into_value()) {
+                    return Some(inner);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// The type a field is read or written as, and whether it is wrapped in an `Option` by its attributes
+fn field_type<'a>(ty: &'a syn::Type, field: &FieldAttrs) -> (&'a syn::Type, bool) {
+    if field.condition.is_none() && !field.optional_trailing {
+        return (ty, false);
+    }
+    match option_inner(ty) {
+        Some(inner) => (inner, true),
+        None if field.optional_trailing => panic!("fields with `optional_trailing` must be an `Option<T>`"),
+        None => (ty, false),
+    }
+}
+
+/// The local a field is read into before the struct is built
+fn local(ident: &Option<syn::Ident>) -> proc_macro2::Ident {
+    proc_macro2::Ident::new(&format!("__scroll_{}", ident.as_ref().unwrap()), proc_macro2::Span::call_site())
+}
+
+/// `tokens` with every `self.field` replaced by the local the field was read into, so that an `if` can refer to the
+/// fields before it
+fn replace_self_fields(tokens: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let tokens: Vec<_> = tokens.into_iter().collect();
+    let mut replaced = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+    while i < tokens.len() {
+        match (&tokens[i], tokens.get(i + 1), tokens.get(i + 2)) {
+            (proc_macro2::TokenTree::Ident(this), Some(proc_macro2::TokenTree::Punct(dot)), Some(proc_macro2::TokenTree::Ident(field)))
+                if this == "self" && dot.as_char() == '.' => {
+                replaced.push(proc_macro2::TokenTree::Ident(local(&Some(field.clone()))));
+                i += 3;
+                continue;
+            },
+            (proc_macro2::TokenTree::Group(group), _, _) => {
+                let mut group_replaced = proc_macro2::Group::new(group.delimiter(), replace_self_fields(group.stream()));
+                group_replaced.set_span(group.span());
+                replaced.push(proc_macro2::TokenTree::Group(group_replaced));
+            },
+            (token, _, _) => replaced.push(token.clone()),
+        }
+        i += 1;
+    }
+    replaced.into_iter().collect()
+}
+
+/// `tokens` with every `self` replaced by `this`, so that an `assert` can be checked before there is a `self`
+fn replace_self(tokens: proc_macro2::TokenStream, this: &proc_macro2::Ident) -> proc_macro2::TokenStream {
+    tokens.into_iter().map(|token| match token {
+        proc_macro2::TokenTree::Ident(ref ident) if ident == "self" => proc_macro2::TokenTree::Ident(this.clone()),
+        proc_macro2::TokenTree::Group(ref group) => {
+            let mut replaced = proc_macro2::Group::new(group.delimiter(), replace_self(group.stream(), this));
+            replaced.set_span(group.span());
+            proc_macro2::TokenTree::Group(replaced)
+        },
+        token => token,
+    }).collect()
+}
+
+/// The source text of `tokens`, for error messages
+fn source_text<T: quote::ToTokens>(tokens: &T) -> String {
+    quote!(#tokens).to_string()
+}
+
+/// The context type the struct is read and written with
+fn ctx_type(attrs: &StructAttrs) -> syn::Type {
+    match attrs.ctx {
+        Some(ref ctx) => ctx.clone(),
+        None => parse_quote!(::scroll::Endian),
+    }
+}
+
+/// The type parameter the context is, when the struct has a `ctx_bound`
+fn ctx_param(attrs: &StructAttrs) -> Option<syn::Ident> {
+    match (&attrs.ctx, &attrs.ctx_bound) {
+        (Some(syn::Type::Path(syn::TypePath { qself: None, ref path })), Some(_)) if path.segments.len() == 1 => {
+            Some(path.segments[0].ident.clone())
+        },
+        _ => None,
+    }
+}
+
+/// `generics` with the context's type parameter and its `ctx_bound` added, if it has one
+fn with_ctx_param(mut generics: syn::Generics, attrs: &StructAttrs) -> syn::Generics {
+    if let (Some(param), Some(bound)) = (ctx_param(attrs), attrs.ctx_bound.as_ref()) {
+        generics.params.push(parse_quote!(#param));
+        generics.make_where_clause().predicates.push(bound.clone());
+    }
+    generics
+}
+
+/// `generics` with `bound(T)` added to the where clause for each of its type parameters `T`
+fn bounded(generics: &syn::Generics, bound: impl Fn(&syn::Ident) -> syn::WherePredicate) -> syn::Generics {
+    let mut bounded = generics.clone();
+    let predicates: Vec<_> = generics.type_params().map(|param| bound(&param.ident)).collect();
+    bounded.make_where_clause().predicates.extend(predicates);
+    bounded
+}
+
+/// The generics of the `TryFromCtx` impl for a struct with `generics`, and the lifetime of the source: the struct's own
+/// lifetime if it borrows, or a fresh one otherwise
+fn source_generics(generics: &syn::Generics, ctx: &syn::Type) -> (syn::Generics, syn::Lifetime) {
+    let mut lifetimes = generics.lifetimes();
+    let (mut with_source, lifetime) = match (lifetimes.next(), lifetimes.next()) {
+        (None, _) => {
+            let mut with_source = generics.clone();
+            with_source.params.insert(0, parse_quote!('a));
+            (with_source, parse_quote!('a))
+        },
+        (Some(def), None) => (generics.clone(), def.lifetime.clone()),
+        (Some(_), Some(_)) => panic!("Pread can only be derived for structs with at most one lifetime, which the source must outlive"),
+    };
+    let predicates: Vec<syn::WherePredicate> = generics.type_params().map(|param| {
+        let ident = &param.ident;
+        parse_quote!(#ident: ::scroll::ctx::TryFromCtx<#lifetime, #ctx, Error = ::scroll::Error>)
+    }).collect();
+    with_source.make_where_clause().predicates.extend(predicates);
+    (with_source, lifetime)
+}
+
+/// The statements reading a struct from `src` at `offset`, ending with `Ok((data, *offset))`; with `dumped`, each field
+/// read is also shown on `__scroll_dumper`, with its offset from `__scroll_base`, as a `debug_offsets` dump lays the
+/// struct out
+fn read_body(name: &syn::Ident, fields: &syn::FieldsNamed, attrs: &StructAttrs, dumped: bool) -> proc_macro2::TokenStream {
+    let this = proc_macro2::Ident::new("__scroll_self", proc_macro2::Span::call_site());
+    let mut asserts = Vec::new();
+    let mut trailing = false;
     let items: Vec<_> = fields.named.iter().map(|f| {
         let ident = &f.ident;
-        let ty = &f.ty;
-        match *ty {
-            syn::Type::Array(ref array) => {
-                match array.len {
-                    syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(ref int), ..}) => {
-                        let size = int.value();
-                        quote! {
-                            #ident: { let mut __tmp: #ty = [0; #size as usize]; src.gread_inout_with(offset, &mut __tmp, ctx)?; __tmp }
-                        }
+        let field = field_attrs(&f.attrs);
+        let (ty, optional) = field_type(&f.ty, &field);
+        if trailing && !field.optional_trailing {
+            panic!("only optional_trailing fields can follow an optional_trailing field")
+        }
+        trailing = field.optional_trailing;
+        let local = local(ident);
+        let at = proc_macro2::Ident::new(&format!("__scroll_{}_offset", ident.as_ref().unwrap()), proc_macro2::Span::call_site());
+        let ctx = match field.ctx {
+            Some(ref ctx) => replace_self_fields(quote!(#ctx)),
+            None => quote!(ctx),
+        };
+        let read = match field.parse_with {
+            Some(ref path) => {
+                let span = path.segments.last().unwrap().value().ident.span();
+                quote_spanned! { span=>
+                    #path(src, offset, &ctx)?
+                }
+            },
+            None if field.leb128 => {
+                let read = if leb128_signed(ty) { quote!(read_sleb128) } else { quote!(read_uleb128) };
+                quote_spanned! { ty.span()=>
+                    ::scroll::export::#read::<#ty>(src, offset)?
+                }
+            },
+            None => match *ty {
+                syn::Type::Array(ref array) => {
+                    match array.len {
+                        syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(ref int), ..}) => {
+                            let size = int.value();
+                            quote_spanned! { ty.span()=>
+                                { let mut __tmp: #ty = [0; #size as usize]; src.gread_inout_with(offset, &mut __tmp, #ctx)?; __tmp }
+                            }
+                        },
+                        _ => panic!("Pread derive with bad array constexpr")
+                    }
+                },
+                _ => match (&attrs.ctx, &field.ctx) {
+                    (Some(struct_ctx), None) => quote_spanned! { ty.span()=>
+                        ::scroll::export::gread_field::<#ty, #struct_ctx>(src, offset, ctx)?
                     },
-                    _ => panic!("Pread derive with bad array constexpr")
+                    _ => quote_spanned! { ty.span()=>
+                        src.gread_with::<#ty>(offset, #ctx)?
+                    },
+                }
+            }
+        };
+        let eq = field.eq.as_ref().map(|lit| {
+            let failed = check_failed(&field.error, ident, &source_text(lit), quote!(#local), quote!(#at));
+            quote! {
+                if #local != #lit {
+                    return Err(#failed);
+                }
+            }
+        });
+        if let Some((ref expr, ref text)) = field.assert {
+            let expr = replace_self(quote!(#expr), &this);
+            let failed = check_failed(&field.error, ident, text, quote!(#this.#ident), quote!(#at));
+            asserts.push(quote! {
+                if !(#expr) {
+                    return Err(#failed);
+                }
+            });
+        }
+        let read = if dumped {
+            let ctx = match field.ctx {
+                Some(ref ctx) => replace_self_fields(quote!(#ctx)),
+                None => quote!(ctx),
+            };
+            quote! {{
+                let __scroll_start = *offset;
+                let __scroll_value = #read;
+                let __scroll_src = src.get(__scroll_start..*offset).unwrap_or_default();
+                __scroll_dumper.field(stringify!(#ident), __scroll_base + __scroll_start, __scroll_src.len());
+                __scroll_dumper.nested(|__scroll_dumper| {
+                    ::scroll::DumpWith::dump_with(&__scroll_value, __scroll_dumper, __scroll_src, __scroll_base + __scroll_start, #ctx)
+                });
+                __scroll_value
+            }}
+        } else {
+            read
+        };
+        let read = match field.condition {
+            Some(condition) => {
+                let condition = replace_self_fields(quote!(#condition));
+                if optional {
+                    quote! { if #condition { Some(#read) } else { None } }
+                } else {
+                    quote! { if #condition { #read } else { Default::default() } }
                 }
             },
-            _ => {
-                quote! {
-                    #ident: src.gread_with::<#ty>(offset, ctx)?
+            // running out of input right before the field is fine, but not in the middle of it
+            None if field.optional_trailing => quote! { if *offset == src.len() { None } else { Some(#read) } },
+            None => read,
+        };
+        let seek = field.offset.map(|to| quote! {
+            ::scroll::export::read_gap(src, offset, #to)?;
+        });
+        let ty = &f.ty;
+        quote! {
+            #seek
+            let #at = *offset;
+            let #local: #ty = #read;
+            #eq
+        }
+    }).collect();
+    let fields: Vec<_> = fields.named.iter().map(|f| {
+        let ident = &f.ident;
+        let local = local(ident);
+        quote! { #ident: #local }
+    }).collect();
+    let magic = attrs.magic.as_ref().map(|magic| {
+        let text = source_text(magic);
+        let shown = if dumped {
+            Some(quote! {
+                __scroll_dumper.field("magic", __scroll_base, __scroll_magic.len());
+                __scroll_dumper.value(__scroll_magic);
+            })
+        } else {
+            None
+        };
+        quote! {
+            let __scroll_magic: &[u8] = src.gread_with(offset, #magic.len())?;
+            #shown
+            if __scroll_magic != &#magic[..] {
+                return Err(::scroll::export::field_mismatch("magic", #text, &__scroll_magic, 0));
+            }
+        }
+    });
+    let size = attrs.size.map(|size| quote! {
+        ::scroll::export::read_gap(src, offset, #size)?;
+    });
+    let asserts = if asserts.is_empty() {
+        None
+    } else {
+        Some(quote! {
+            let #this = &data;
+            #(#asserts)*
+        })
+    };
+
+    quote! {
+        let offset = &mut 0;
+        #magic
+        #(#items)*
+        #size
+        let data = #name { #(#fields,)* };
+        #asserts
+        Ok((data, *offset))
+    }
+}
+
+fn impl_struct(name: &syn::Ident, generics: &syn::Generics, fields: &syn::FieldsNamed, attrs: &StructAttrs) -> proc_macro2::TokenStream {
+    check_layout(fields, attrs);
+    let body = read_body(name, fields, attrs, false);
+
+    let ctx = ctx_type(attrs);
+    let (with_source, lifetime) = source_generics(generics, &ctx);
+    let with_source = with_ctx_param(with_source, attrs);
+    let (impl_generics, _, where_clause) = with_source.split_for_impl();
+    let (_, ty_generics, _) = generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics ::scroll::ctx::TryFromCtx<#lifetime, #ctx> for #name #ty_generics #where_clause {
+            type Error = ::scroll::Error;
+            #[inline]
+            fn try_from_ctx(src: &#lifetime [u8], ctx: #ctx) -> ::scroll::export::result::Result<(Self, usize), Self::Error> {
+                use ::scroll::Pread;
+                #body
+            }
+        }
+    }
+}
+
+/// Derives `DumpWith` for a struct with `debug_offsets`, which reads it as `TryFromCtx` does, showing each field as it
+/// goes, and a `dump` function displaying it
+fn impl_dump(name: &syn::Ident, generics: &syn::Generics, fields: &syn::FieldsNamed, attrs: &StructAttrs) -> proc_macro2::TokenStream {
+    let body = read_body(name, fields, attrs, true);
+
+    let ctx = ctx_type(attrs);
+    let (with_source, lifetime) = source_generics(generics, &ctx);
+    let dumpable = with_ctx_param(bounded(&with_source, |param| parse_quote!(#param: ::scroll::DumpWith<#lifetime, #ctx>)), attrs);
+    let (impl_generics_dump, _, where_clause_dump) = dumpable.split_for_impl();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let mut params: Vec<proc_macro2::TokenStream> = Vec::new();
+    if generics.lifetimes().next().is_none() {
+        params.push(quote!(#lifetime));
+    }
+    params.extend(ctx_param(attrs).map(|param| quote!(#param)));
+    let params = if params.is_empty() { None } else { Some(quote!(<#(#params),*>)) };
+    let bound = ctx_param(attrs).and(attrs.ctx_bound.as_ref()).map(|bound| quote!(#bound,));
+
+    quote! {
+        impl #impl_generics_dump ::scroll::DumpWith<#lifetime, #ctx> for #name #ty_generics #where_clause_dump {
+            fn dump_with(&self, dumper: &mut ::scroll::Dumper, src: &#lifetime [u8], offset: usize, ctx: #ctx) {
+                let _ = <Self as ::scroll::DumpWith<#lifetime, #ctx>>::dump_from(src, dumper, offset, ctx);
+            }
+            fn dump_from(src: &#lifetime [u8], __scroll_dumper: &mut ::scroll::Dumper, __scroll_base: usize, ctx: #ctx) -> ::scroll::Result<(Self, usize)> {
+                use ::scroll::Pread;
+                let read = (|| -> ::scroll::Result<(Self, usize)> { #body })();
+                if let Err(ref e) = read {
+                    __scroll_dumper.error(e);
                 }
+                read
+            }
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Reads the struct at `offset` in `src` with `ctx`, and displays each field on a line of its own, with its
+            /// offset, its size, and its value; a read which fails shows the fields before the failure, and the error
+            pub fn dump #params(src: &#lifetime [u8], offset: usize, ctx: #ctx) -> ::scroll::OffsetDump<#lifetime, Self, #ctx>
+                where #bound Self: ::scroll::DumpWith<#lifetime, #ctx> + ::scroll::ctx::TryFromCtx<#lifetime, #ctx, Error = ::scroll::Error>
+            {
+                ::scroll::OffsetDump::new(src, offset, ctx)
             }
         }
+    }
+}
+
+/// A field of a bitfield struct: its name, its type, its shift and mask within the struct's integer, and whether it is a `bool`
+struct BitField<'a> {
+    ident: &'a syn::Ident,
+    ty: &'a syn::Type,
+    shift: u32,
+    mask: proc_macro2::Literal,
+    boolean: bool,
+}
+
+/// The fields of the bitfield struct `name`, packed into `bits` bits
+fn bit_fields<'a>(name: &syn::Ident, fields: &'a syn::FieldsNamed, bits: &syn::Type, lsb_first: bool) -> Vec<BitField<'a>> {
+    let width: u32 = match &source_text(bits)[..] {
+        "u8" => 8,
+        "u16" => 16,
+        "u32" => 32,
+        "u64" => 64,
+        _ => 128,
+    };
+    let mut used = 0;
+    let fields: Vec<_> = fields.named.iter().map(|f| {
+        let ident = f.ident.as_ref().unwrap();
+        let field = field_attrs(&f.attrs);
+        let field_bits = field.bits.unwrap_or_else(|| panic!("field `{}` of a bitfield struct needs its bits, e.g. `#[scroll(bits = 3)]`", ident));
+        if field.eq.is_some() || field.assert.is_some() || field.parse_with.is_some() || field.write_with.is_some()
+            || field.condition.is_some() || field.optional_trailing || field.ctx.is_some() || field.leb128 || field.offset.is_some() {
+            panic!("field `{}` of a bitfield struct can only have bits", ident)
+        }
+        let (boolean, max_bits) = match &source_text(&f.ty)[..] {
+            "bool" => (true, 1),
+            "u8" => (false, 8),
+            "u16" => (false, 16),
+            "u32" => (false, 32),
+            "u64" => (false, 64),
+            "u128" => (false, 128),
+            _ => panic!("field `{}` of a bitfield struct must be an unsigned integer or a bool", ident),
+        };
+        if field_bits > max_bits {
+            panic!("field `{}` has {} bits, more than its type holds", ident, field_bits)
+        }
+        let shift = if lsb_first { used } else { width.saturating_sub(used + field_bits) };
+        used += field_bits;
+        let mask = if field_bits == 128 { u128::MAX } else { (1 << field_bits) - 1 };
+        BitField { ident, ty: &f.ty, shift, mask: proc_macro2::Literal::u128_suffixed(mask), boolean }
     }).collect();
+    if used != width {
+        panic!("the fields of `{}` have {} bits, but it packs them into the {} bits of a {}", name, used, width, source_text(bits))
+    }
+    fields
+}
+
+/// The byte order a bitfield struct's integer is read and written in
+fn bit_endian(attrs: &StructAttrs) -> proc_macro2::TokenStream {
+    match attrs.endian {
+        Some(ref endian) => quote! { { let _ = ctx; ::scroll::#endian } },
+        None => quote!(ctx),
+    }
+}
 
+fn impl_bitfield_read(name: &syn::Ident, generics: &syn::Generics, fields: &syn::FieldsNamed, attrs: &StructAttrs) -> proc_macro2::TokenStream {
+    let bits = attrs.bits.as_ref().unwrap();
+    let items: Vec<_> = bit_fields(name, fields, bits, attrs.lsb_first).into_iter().map(|field| {
+        let BitField { ident, ty, shift, mask, boolean } = field;
+        if boolean {
+            quote! { #ident: (__scroll_bits >> #shift) & 1 != 0 }
+        } else {
+            quote! { #ident: ((__scroll_bits >> #shift) & (#mask as #bits)) as #ty }
+        }
+    }).collect();
+    let endian = bit_endian(attrs);
+    let (with_source, lifetime) = source_generics(generics, &parse_quote!(::scroll::Endian));
+    let (impl_generics, _, where_clause) = with_source.split_for_impl();
+    let (_, ty_generics, _) = generics.split_for_impl();
     quote! {
-        impl<'a> ::scroll::ctx::TryFromCtx<'a, ::scroll::Endian> for #name where #name: 'a {
+        impl #impl_generics ::scroll::ctx::TryFromCtx<#lifetime, ::scroll::Endian> for #name #ty_generics #where_clause {
             type Error = ::scroll::Error;
             #[inline]
-            fn try_from_ctx(src: &'a [u8], ctx: ::scroll::Endian) -> ::scroll::export::result::Result<(Self, usize), Self::Error> {
+            fn try_from_ctx(src: &#lifetime [u8], ctx: ::scroll::Endian) -> ::scroll::export::result::Result<(Self, usize), Self::Error> {
                 use ::scroll::Pread;
-                let offset = &mut 0;
-                let data  = #name { #(#items,)* };
-                Ok((data, *offset))
+                let __scroll_bits: #bits = src.pread_with(0, #endian)?;
+                Ok((#name { #(#items,)* }, ::scroll::export::mem::size_of::<#bits>()))
+            }
+        }
+    }
+}
+
+fn impl_bitfield_write(name: &syn::Ident, generics: &syn::Generics, fields: &syn::FieldsNamed, attrs: &StructAttrs) -> proc_macro2::TokenStream {
+    let bits = attrs.bits.as_ref().unwrap();
+    let items: Vec<_> = bit_fields(name, fields, bits, attrs.lsb_first).into_iter().map(|field| {
+        let BitField { ident, shift, mask, .. } = field;
+        quote! {
+            if self.#ident as u128 > #mask {
+                return Err(::scroll::Error::Invalid { offset: 0, msg: concat!("field `", stringify!(#ident), "` is too large for its bits") });
+            }
+            __scroll_bits |= (self.#ident as #bits) << #shift;
+        }
+    }).collect();
+    let endian = bit_endian(attrs);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let mut with_self = generics.clone();
+    with_self.params.insert(0, parse_quote!('__scroll_self));
+    let (impl_generics_self, _, _) = with_self.split_for_impl();
+    quote! {
+        impl #impl_generics_self ::scroll::ctx::TryIntoCtx<::scroll::Endian> for &'__scroll_self #name #ty_generics #where_clause {
+            type Error = ::scroll::Error;
+            #[inline]
+            fn try_into_ctx(self, dst: &mut [u8], ctx: ::scroll::Endian) -> ::scroll::export::result::Result<usize, Self::Error> {
+                use ::scroll::Pwrite;
+                let mut __scroll_bits: #bits = 0;
+                #(#items)*
+                dst.pwrite_with(__scroll_bits, 0, #endian)
+            }
+        }
+
+        impl #impl_generics ::scroll::ctx::TryIntoCtx<::scroll::Endian> for #name #ty_generics #where_clause {
+            type Error = ::scroll::Error;
+            #[inline]
+            fn try_into_ctx(self, dst: &mut [u8], ctx: ::scroll::Endian) -> ::scroll::export::result::Result<usize, Self::Error> {
+                (&self).try_into_ctx(dst, ctx)
+            }
+        }
+    }
+}
+
+fn impl_default_ctx(name: &syn::Ident, generics: &syn::Generics, endian: &syn::Ident) -> proc_macro2::TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        impl #impl_generics ::scroll::ctx::DefaultCtx for #name #ty_generics #where_clause {
+            type Ctx = ::scroll::Endian;
+            #[inline]
+            fn default_ctx() -> ::scroll::Endian {
+                ::scroll::#endian
             }
         }
     }
@@ -50,7 +795,19 @@ fn impl_try_from_ctx(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
         syn::Data::Struct(ref data) => {
             match data.fields {
                 syn::Fields::Named(ref fields) => {
-                    impl_struct(name, fields)
+                    let attrs = struct_attrs(&ast.attrs);
+                    let try_from_ctx = if attrs.bits.is_some() {
+                        impl_bitfield_read(name, &ast.generics, fields, &attrs)
+                    } else {
+                        impl_struct(name, &ast.generics, fields, &attrs)
+                    };
+                    let dump = if attrs.debug_offsets { Some(impl_dump(name, &ast.generics, fields, &attrs)) } else { None };
+                    let default_ctx = attrs.default_ctx.map(|endian| impl_default_ctx(name, &ast.generics, &endian));
+                    quote! {
+                        #try_from_ctx
+                        #dump
+                        #default_ctx
+                    }
                 },
                 _ => {
                     panic!("Pread can only be derived for a regular struct with public fields")
@@ -61,49 +818,249 @@ fn impl_try_from_ctx(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
     }
 }
 
-#[proc_macro_derive(Pread)]
+/// Derives `TryFromCtx<Endian>`, configured by `#[scroll(..)]` attributes:
+///
+/// * `#[scroll(default_ctx = "BE")]` (or `"LE"`, `"NATIVE"`, `"NETWORK"`) on the struct also derives `DefaultCtx`, so the
+///   struct can be read with `pread_into`
+/// * `#[scroll(ctx = "MyCtx")]` on the struct derives `TryFromCtx<MyCtx>` instead, for formats whose context carries more
+///   than the byte order; fields are read with the struct's context, so most need their own `ctx`, e.g. `ctx = "ctx.endian"`.
+///   With `#[scroll(ctx = "C", ctx_bound = "C: Copy + HasEndian")]`, the context is a type parameter of the impl instead
+/// * `#[scroll(magic = b"...")]` on the struct reads and checks those bytes before the first field; they aren't stored
+/// * `#[scroll(eq = 0x1234)]` on a field checks it holds that value
+/// * `#[scroll(assert = "self.version <= 4")]` on a field checks the condition once the whole struct is read
+/// * `#[scroll(error = "invalid magic")]` next to a field's `eq` or `assert` makes a failed check an `Error::BadInput`
+///   with that message, whose size is the field's
+/// * `#[scroll(parse_with = "path::to::fn")]` on a field reads it with that function instead, which takes the source, the
+///   offset to advance and the context, like `fn(&'a [u8], &mut usize, &Endian) -> Result<T, E>`
+/// * `#[scroll(if = "self.version >= 2")]` on a field only reads it when the condition holds, and otherwise makes it
+///   `None` if it is an `Option<T>`, or `Default::default()`; the condition can refer to the fields before it, and to `ctx`
+/// * `#[scroll(optional_trailing)]` on `Option<T>` fields at the end of the struct makes them `None` when the input ends
+///   right before them; an input ending in the middle of one is still an error
+/// * `#[scroll(ctx = "scroll::ctx::StrCtx::Delimiter(0)")]` on a field reads it with that context instead of the struct's;
+///   like `if`, it can refer to the fields before it, e.g., `ctx = "self.len as usize"` for a `&'a [u8]`
+/// * `#[scroll(leb128)]` on an integer field reads it as a ULEB128, or an SLEB128 if the type is signed, taking however
+///   many bytes the encoding does; a value too large for the field's type is an `Error::Invalid`
+/// * `#[scroll(offset = 0x40)]` on a field reads it that far from the start of the struct, skipping the gap before it,
+///   and the fields after it follow it as usual; explicit offsets must increase, and fields running past one are an
+///   `Error::Invalid`
+/// * `#[scroll(size = 0x60)]` on the struct makes it take up that many bytes, so reading it skips any gap after its last
+///   field, and `SizeWith` and `ConstSize` are that size
+/// * `#[scroll(bits = "u16")]` on the struct makes it a bitfield: the integer is read once, and each field, which carries
+///   its width like `#[scroll(bits = 3)]`, is extracted from it; the first field is in the most significant bits, or the
+///   least with `bit_order = "LsbFirst"`, and the integer is in the context's byte order, or the one of `endian = "BE"`.
+///   The fields must be unsigned integers or `bool`s, and their widths must add up to the integer's. Writing composes
+///   them back, and a field too large for its width is an `Error::Invalid`
+/// * `#[scroll(debug_offsets)]` on the struct also derives `DumpWith` and a `dump(src, offset, ctx)` function, whose
+///   `Display` reads the struct at `offset` in `src` and shows each field on a line of its own: its offset in hex, its
+///   size, and its value, in hex for integers, escaped for strings, and as fields of their own, indented, for nested
+///   structs with `debug_offsets`. The fields are read by the same code `TryFromCtx` is, so the offsets and sizes are the
+///   ones they were read from, whatever their attributes; fields which weren't read aren't shown, and a read which fails
+///   shows the fields before the failure, then the error
+///
+/// Structs may be generic, in which case each type parameter must be readable with the struct's context, and may borrow
+/// from the source through a single lifetime.
+///
+/// A failed check is an error naming the field, what was expected, and what was found at which offset.
+///
+/// ```rust
+/// use scroll_derive::Pread;
+/// use scroll::{Pread, LE};
+///
+/// #[derive(Pread)]
+/// #[scroll(magic = b"MZ")]
+/// struct Header {
+///     #[scroll(assert = "self.pages > 0")]
+///     pages: u16,
+///     #[scroll(eq = 0)]
+///     reserved: u16,
+/// }
+///
+/// let header: Header = b"MZ\x03\x00\x00\x00".pread_with(0, LE).unwrap();
+/// assert_eq!(header.pages, 3);
+/// assert!(b"MZ\x03\x00\x01\x00".pread_with::<Header>(0, LE).is_err());
+/// ```
+///
+/// Malformed attributes are compile errors:
+/// ```compile_fail
+/// # use scroll_derive::Pread;
+/// #[derive(Pread)]
+/// #[scroll(magic = "MZ")]
+/// struct Header { pages: u16 }
+/// ```
+/// ```compile_fail
+/// # use scroll_derive::Pread;
+/// #[derive(Pread)]
+/// struct Header { #[scroll(assert = "self.pages >")] pages: u16 }
+/// ```
+/// ```compile_fail
+/// # use scroll_derive::Pread;
+/// #[derive(Pread)]
+/// struct Header { #[scroll(equals = 1)] pages: u16 }
+/// ```
+/// ```compile_fail
+/// # use scroll_derive::Pread;
+/// #[derive(Pread)]
+/// #[scroll(bits = "u8")]
+/// struct VersionIhl { #[scroll(bits = 4)] version: u8, #[scroll(bits = 3)] ihl: u8 }
+/// ```
+///
+/// So is a field which can't be read with the struct's context, which is an unsatisfied `TryFromCtx` bound on its type:
+/// ```compile_fail
+/// # use scroll_derive::Pread;
+/// #[derive(Clone, Copy)]
+/// struct FormatCtx { version: u16, endian: scroll::Endian }
+///
+/// #[derive(Pread)]
+/// #[scroll(ctx = "FormatCtx")]
+/// struct Header { pages: u16 }
+/// ```
+#[proc_macro_derive(Pread, attributes(scroll))]
 pub fn derive_pread(input: TokenStream) -> TokenStream {
     let ast: syn::DeriveInput = syn::parse(input).unwrap();
     let gen = impl_try_from_ctx(&ast);
     gen.into()
 }
 
-fn impl_try_into_ctx(name: &syn::Ident, fields: &syn::FieldsNamed) -> proc_macro2::TokenStream {
+fn impl_try_into_ctx(name: &syn::Ident, generics: &syn::Generics, fields: &syn::FieldsNamed, attrs: &StructAttrs) -> proc_macro2::TokenStream {
+    check_layout(fields, attrs);
+    let zero_gaps = attrs.zero_gaps;
     let items: Vec<_> = fields.named.iter().map(|f| {
         let ident = &f.ident;
-        let ty = &f.ty;
-        match *ty {
-            syn::Type::Array(_) => {
-                quote! {
-                    for i in 0..self.#ident.len() {
-                        dst.gwrite_with(&self.#ident[i], offset, ctx)?;
+        let field = field_attrs(&f.attrs);
+        let (ty, optional) = field_type(&f.ty, &field);
+        let (ty, value) = if optional {
+            (ty, quote! { (*__scroll_value) })
+        } else {
+            (&f.ty, quote! { self.#ident })
+        };
+        let ctx = match field.ctx {
+            Some(ref ctx) => quote!(#ctx),
+            None => quote!(ctx),
+        };
+        let write = match field.write_with {
+            Some(ref path) => {
+                let span = path.segments.last().unwrap().value().ident.span();
+                quote_spanned! { span=>
+                    #path(&#value, dst, offset, &ctx)?;
+                }
+            },
+            None if field.leb128 => {
+                let write = if leb128_signed(ty) { quote!(write_sleb128) } else { quote!(write_uleb128) };
+                quote_spanned! { ty.span()=>
+                    ::scroll::export::#write(dst, offset, #value)?;
+                }
+            },
+            None => match *ty {
+                syn::Type::Array(_) => {
+                    quote_spanned! { ty.span()=>
+                        for i in 0..#value.len() {
+                            dst.gwrite_with(&#value[i], offset, #ctx)?;
+                        }
                     }
+                },
+                _ => match (&attrs.ctx, &field.ctx) {
+                    (Some(struct_ctx), None) => quote_spanned! { ty.span()=>
+                        ::scroll::export::gwrite_field::<&#ty, #struct_ctx>(dst, &#value, offset, ctx)?;
+                    },
+                    _ => quote_spanned! { ty.span()=>
+                        dst.gwrite_with(&#value, offset, #ctx)?;
+                    },
+                }
+            }
+        };
+        let write = match field.condition {
+            Some(ref condition) if !optional => quote! {
+                if #condition {
+                    #write
                 }
             },
-            _ => {
-                quote! {
-                    dst.gwrite_with(&self.#ident, offset, ctx)?
+            Some(ref condition) => quote! {
+                if #condition {
+                    match self.#ident {
+                        Some(ref __scroll_value) => { #write },
+                        None => return Err(::scroll::Error::BadInput {
+                            size: *offset,
+                            msg: concat!("field `", stringify!(#ident), "` is None, but its condition holds"),
+                        }),
+                    }
+                }
+            },
+            None if field.optional_trailing => quote! {
+                if let Some(ref __scroll_value) = self.#ident {
+                    #write
+                }
+            },
+            None => write,
+        };
+        let write = match field.offset {
+            Some(to) => quote! {
+                ::scroll::export::write_gap(dst, offset, #to, #zero_gaps)?;
+                #write
+            },
+            None => write,
+        };
+        if !attrs.verify_writes {
+            return write;
+        }
+        let eq = field.eq.as_ref().map(|lit| {
+            let failed = check_failed(&field.error, ident, &source_text(lit), quote!(self.#ident), quote!(*offset));
+            quote! {
+                if self.#ident != #lit {
+                    return Err(#failed);
                 }
             }
+        });
+        let assert = field.assert.as_ref().map(|(expr, text)| {
+            let failed = check_failed(&field.error, ident, text, quote!(self.#ident), quote!(*offset));
+            quote! {
+                if !(#expr) {
+                    return Err(#failed);
+                }
+            }
+        });
+        quote! {
+            #eq
+            #assert
+            #write
         }
     }).collect();
+    let magic = attrs.magic.as_ref().map(|magic| {
+        quote! {
+            dst.gwrite(&#magic[..], offset)?;
+        }
+    });
+    let size = attrs.size.map(|size| quote! {
+        ::scroll::export::write_gap(dst, offset, #size, #zero_gaps)?;
+    });
+
+    let ctx = ctx_type(attrs);
+    let bounded = with_ctx_param(bounded(generics, |param| parse_quote! {
+        for<'__scroll> &'__scroll #param: ::scroll::ctx::TryIntoCtx<#ctx, Error = ::scroll::Error>
+    }), attrs);
+    let (_, ty_generics, _) = generics.split_for_impl();
+    let (impl_generics, _, where_clause) = bounded.split_for_impl();
+    let mut with_self = bounded.clone();
+    with_self.params.insert(0, parse_quote!('__scroll_self));
+    let (impl_generics_self, _, _) = with_self.split_for_impl();
 
     quote! {
-        impl<'a> ::scroll::ctx::TryIntoCtx<::scroll::Endian> for &'a #name {
+        impl #impl_generics_self ::scroll::ctx::TryIntoCtx<#ctx> for &'__scroll_self #name #ty_generics #where_clause {
             type Error = ::scroll::Error;
             #[inline]
-            fn try_into_ctx(self, dst: &mut [u8], ctx: ::scroll::Endian) -> ::scroll::export::result::Result<usize, Self::Error> {
+            fn try_into_ctx(self, dst: &mut [u8], ctx: #ctx) -> ::scroll::export::result::Result<usize, Self::Error> {
                 use ::scroll::Pwrite;
                 let offset = &mut 0;
-                #(#items;)*;
+                #magic
+                #(#items)*
+                #size
                 Ok(*offset)
             }
         }
 
-        impl ::scroll::ctx::TryIntoCtx<::scroll::Endian> for #name {
+        impl #impl_generics ::scroll::ctx::TryIntoCtx<#ctx> for #name #ty_generics #where_clause {
             type Error = ::scroll::Error;
             #[inline]
-            fn try_into_ctx(self, dst: &mut [u8], ctx: ::scroll::Endian) -> ::scroll::export::result::Result<usize, Self::Error> {
+            fn try_into_ctx(self, dst: &mut [u8], ctx: #ctx) -> ::scroll::export::result::Result<usize, Self::Error> {
                 (&self).try_into_ctx(dst, ctx)
             }
         }
@@ -116,7 +1073,12 @@ fn impl_pwrite(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
         syn::Data::Struct(ref data) => {
             match data.fields {
                 syn::Fields::Named(ref fields) => {
-                    impl_try_into_ctx(name, fields)
+                    let attrs = struct_attrs(&ast.attrs);
+                    if attrs.bits.is_some() {
+                        impl_bitfield_write(name, &ast.generics, fields, &attrs)
+                    } else {
+                        impl_try_into_ctx(name, &ast.generics, fields, &attrs)
+                    }
                 },
                 _ => {
                     panic!("Pwrite can only be derived for a regular struct with public fields")
@@ -127,24 +1089,64 @@ fn impl_pwrite(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
     }
 }
 
-#[proc_macro_derive(Pwrite)]
+/// Derives `TryIntoCtx<Endian>`, writing the `magic` bytes of the struct, if any, before the first field
+///
+/// A field with `#[scroll(write_with = "path::to::fn")]` is written with that function instead, which takes the field,
+/// the destination, the offset to advance and the context, like `fn(&T, &mut [u8], &mut usize, &Endian) -> Result<usize, E>`.
+///
+/// An `if` field is written when its condition holds, and must be `Some` then if it is an `Option`; an `optional_trailing`
+/// field is written when it is `Some`.
+///
+/// A field with a `ctx` attribute is written with that context, where `self` is the struct being written, and a `leb128`
+/// field in its shortest encoding. The gaps before fields with an `offset`, and before the struct's `size`, are left as
+/// they are in the destination, or zeroed with `#[scroll(zero_gaps)]` on the struct. A struct `ctx` (and `ctx_bound`) derives `TryIntoCtx` for that context, like `Pread`.
+///
+/// With `#[scroll(verify_writes)]` on the struct, the `eq` and `assert` attributes of each field are checked before it is
+/// written. A `default_ctx` attribute is accepted, but its `DefaultCtx` is derived by `Pread`.
+#[proc_macro_derive(Pwrite, attributes(scroll))]
 pub fn derive_pwrite(input: TokenStream) -> TokenStream {
     let ast: syn::DeriveInput = syn::parse(input).unwrap();
     let gen = impl_pwrite(&ast);
     gen.into()
 }
 
-fn size_with(name: &syn::Ident, fields: &syn::FieldsNamed) -> proc_macro2::TokenStream {
-    let items: Vec<_> = fields.named.iter().map(|f| {
+fn size_with(name: &syn::Ident, generics: &syn::Generics, fields: &syn::FieldsNamed, attrs: &StructAttrs) -> proc_macro2::TokenStream {
+    let magic = attrs.magic.as_ref().map_or(0, |magic| magic.value().len());
+    let items: Vec<_> = fields.named.iter().filter(|_| attrs.size.is_none() && attrs.bits.is_none()).map(|f| {
+        let field = field_attrs(&f.attrs);
+        if field.offset.is_some() {
+            panic!("SizeWith needs the struct's size when its fields have explicit offsets, e.g. `#[scroll(size = 0x60)]`")
+        }
+        if field.leb128 {
+            panic!("SizeWith cannot be derived for a struct with leb128 fields, as their size depends on their value")
+        }
+        let unsized_by = if field.condition.is_some() {
+            Some("it is only present when its `if` holds")
+        } else if field.optional_trailing {
+            Some("it is optional_trailing")
+        } else if field.parse_with.is_some() {
+            Some("it is read with parse_with")
+        } else {
+            None
+        };
+        if let Some(reason) = unsized_by {
+            panic!("SizeWith cannot be derived for `{}`: field `{}` has no fixed size, as {}", name, f.ident.as_ref().unwrap(), reason)
+        }
+        // fields of `Endian` structs are sized with the struct's context, as their overrides may refer to `self`; a custom
+        // context needs them
+        let ctx = match field.ctx {
+            Some(ref ctx) if attrs.ctx.is_some() => quote!(&{ #[allow(unused_variables)] let ctx = *ctx; #ctx }),
+            _ => quote!(ctx),
+        };
         let ty = &f.ty;
         match *ty {
             syn::Type::Array(ref array) => {
                 let elem = &array.elem;
                 match array.len {
                     syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(ref int), ..}) => {
-                        let size = int.value() as usize;
+                        let size: usize = int_value(int, "array length");
                         quote! {
-                            (#size * <#elem>::size_with(ctx))
+                            (#size * <#elem>::size_with(#ctx))
                         }
                     },
                     _ => panic!("Pread derive with bad array constexpr")
@@ -152,16 +1154,25 @@ fn size_with(name: &syn::Ident, fields: &syn::FieldsNamed) -> proc_macro2::Token
             },
             _ => {
                 quote! {
-                    <#ty>::size_with(ctx)
+                    <#ty>::size_with(#ctx)
                 }
             }
         }
     }).collect();
+    let size = match (attrs.size, &attrs.bits) {
+        (Some(size), _) => quote!(#size),
+        (None, Some(bits)) => quote!(::scroll::export::mem::size_of::<#bits>()),
+        (None, None) => quote!(#magic #(+ #items)*),
+    };
+    let ctx = ctx_type(attrs);
+    let bounded = with_ctx_param(bounded(generics, |param| parse_quote!(#param: ::scroll::ctx::SizeWith<#ctx>)), attrs);
+    let (impl_generics, _, where_clause) = bounded.split_for_impl();
+    let (_, ty_generics, _) = generics.split_for_impl();
     quote! {
-        impl ::scroll::ctx::SizeWith<::scroll::Endian> for #name {
+        impl #impl_generics ::scroll::ctx::SizeWith<#ctx> for #name #ty_generics #where_clause {
             #[inline]
-            fn size_with(ctx: &::scroll::Endian) -> usize {
-                0 #(+ #items)*
+            fn size_with(ctx: &#ctx) -> usize {
+                #size
             }
         }
     }
@@ -173,7 +1184,7 @@ fn impl_size_with(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
         syn::Data::Struct(ref data) => {
             match data.fields {
                 syn::Fields::Named(ref fields) => {
-                    size_with(name, fields)
+                    size_with(name, &ast.generics, fields, &struct_attrs(&ast.attrs))
                 },
                 _ => {
                     panic!("SizeWith can only be derived for a regular struct with public fields")
@@ -184,13 +1195,338 @@ fn impl_size_with(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
     }
 }
 
-#[proc_macro_derive(SizeWith)]
+/// Derives `SizeWith` for a struct: its size is its `magic` bytes, if any, plus the sizes of its fields with the
+/// context, or the struct's own `size` when it has one
+///
+/// ```rust
+/// use scroll_derive::SizeWith;
+/// use scroll::{ctx::SizeWith, LE};
+///
+/// #[derive(SizeWith)]
+/// #[scroll(size = 0x60)]
+/// struct Header {
+///     kind: u32,
+///     len: u64,
+/// }
+///
+/// assert_eq!(Header::size_with(&LE), 0x60);
+/// ```
+///
+/// A field whose attributes make its size vary is an error, unless the struct has its own `size`.
+///
+/// ```compile_fail
+/// # use scroll_derive::{Pread, SizeWith};
+/// #[derive(Pread, SizeWith)]
+/// struct Entry { flags: u8, #[scroll(if = "self.flags & 1 != 0")] extra: u32 }
+/// ```
+/// ```compile_fail
+/// # use scroll_derive::SizeWith;
+/// #[derive(SizeWith)]
+/// struct Entry { len: u16, #[scroll(parse_with = "read_extra")] extra: u32 }
+/// ```
+#[proc_macro_derive(SizeWith, attributes(scroll))]
 pub fn derive_sizewith(input: TokenStream) -> TokenStream {
     let ast: syn::DeriveInput = syn::parse(input).unwrap();
     let gen = impl_size_with(&ast);
     gen.into()
 }
 
+fn const_size(name: &syn::Ident, generics: &syn::Generics, fields: &syn::FieldsNamed, attrs: &StructAttrs) -> proc_macro2::TokenStream {
+    let magic = attrs.magic.as_ref().map_or(0, |magic| magic.value().len());
+    let items: Vec<_> = fields.named.iter().filter(|_| attrs.size.is_none() && attrs.bits.is_none()).map(|f| {
+        let ident = f.ident.as_ref().unwrap();
+        let field = field_attrs(&f.attrs);
+        let unsized_by = if field.offset.is_some() {
+            Some("it has an explicit offset, so the struct needs its size, e.g. `#[scroll(size = 0x60)]`")
+        } else if field.condition.is_some() {
+            Some("it is only present when its `if` holds")
+        } else if field.optional_trailing {
+            Some("it is optional_trailing")
+        } else if field.leb128 {
+            Some("it is leb128, whose size depends on its value")
+        } else if field.parse_with.is_some() {
+            Some("it is read with parse_with")
+        } else {
+            None
+        };
+        if let Some(reason) = unsized_by {
+            panic!("ConstSize cannot be derived for `{}`: field `{}` has no fixed size, as {}", name, ident, reason)
+        }
+        let ty = &f.ty;
+        quote_spanned! { ty.span()=>
+            <#ty as ::scroll::ctx::ConstSize>::SIZE
+        }
+    }).collect();
+    let size = match (attrs.size, &attrs.bits) {
+        (Some(size), _) => quote!(#size),
+        (None, Some(bits)) => quote!(<#bits as ::scroll::ctx::ConstSize>::SIZE),
+        (None, None) => quote!(#magic #(+ #items)*),
+    };
+    let bounded = bounded(generics, |param| parse_quote!(#param: ::scroll::ctx::ConstSize));
+    let (impl_generics, ty_generics, where_clause) = bounded.split_for_impl();
+    quote! {
+        impl #impl_generics ::scroll::ctx::ConstSize for #name #ty_generics #where_clause {
+            const SIZE: usize = #size;
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// The number of bytes the struct is encoded in
+            pub const SIZE: usize = <Self as ::scroll::ctx::ConstSize>::SIZE;
+        }
+    }
+}
+
+fn impl_const_size(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
+    let name = &ast.ident;
+    match ast.data {
+        syn::Data::Struct(ref data) => {
+            match data.fields {
+                syn::Fields::Named(ref fields) => {
+                    const_size(name, &ast.generics, fields, &struct_attrs(&ast.attrs))
+                },
+                _ => {
+                    panic!("ConstSize can only be derived for a regular struct with public fields")
+                }
+            }
+        },
+        _ => panic!("ConstSize can only be derived for structs")
+    }
+}
+
+/// Derives `ConstSize`, and an inherent `SIZE` constant, for a struct whose fields are all `ConstSize`: its size is its
+/// `magic` bytes, if any, plus the sizes of its fields, as `Pread` lays them out
+///
+/// A field whose type isn't `ConstSize` is an error at that field, as is one whose attributes make its size vary.
+///
+/// ```rust
+/// use scroll_derive::{Pread, ConstSize};
+/// use scroll::{Pread, LE};
+///
+/// #[derive(Pread, ConstSize)]
+/// #[scroll(magic = b"\x7fELF")]
+/// struct Ident {
+///     class: u8,
+///     data: u8,
+///     version: u8,
+///     pad: [u8; 9],
+/// }
+///
+/// const _: () = assert!(Ident::SIZE == 16);
+/// let table = [0x7f, b'E', b'L', b'F', 2, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+/// let offset = &mut 0;
+/// let _: Ident = table.gread_with(offset, LE).unwrap();
+/// assert_eq!(*offset, Ident::SIZE);
+/// ```
+///
+/// ```compile_fail
+/// # use scroll_derive::{Pread, ConstSize};
+/// #[derive(Pread, ConstSize)]
+/// struct Entry<'a> { len: u32, #[scroll(ctx = "self.len as usize")] name: &'a [u8] }
+/// ```
+/// ```compile_fail
+/// # use scroll_derive::{Pread, ConstSize};
+/// #[derive(Pread, ConstSize)]
+/// struct Entry { #[scroll(leb128)] len: u64 }
+/// ```
+#[proc_macro_derive(ConstSize, attributes(scroll))]
+pub fn derive_const_size(input: TokenStream) -> TokenStream {
+    let ast: syn::DeriveInput = syn::parse(input).unwrap();
+    let gen = impl_const_size(&ast);
+    gen.into()
+}
+
+/// Whether `tokens` refer to `self`, i.e., to the fields read before them
+fn mentions_self(tokens: proc_macro2::TokenStream) -> bool {
+    tokens.into_iter().any(|token| match token {
+        proc_macro2::TokenTree::Ident(ref ident) => ident == "self",
+        proc_macro2::TokenTree::Group(ref group) => mentions_self(group.stream()),
+        _ => false,
+    })
+}
+
+fn view(name: &syn::Ident, vis: &syn::Visibility, generics: &syn::Generics, fields: &syn::FieldsNamed, attrs: &StructAttrs) -> proc_macro2::TokenStream {
+    if attrs.bits.is_some() {
+        panic!("PreadView cannot be derived for a bitfield struct, whose fields share their bytes")
+    }
+    if generics.type_params().next().is_some() || attrs.ctx_bound.is_some() {
+        panic!("PreadView cannot be derived for a struct with type parameters")
+    }
+    check_layout(fields, attrs);
+    let lifetime: syn::Lifetime = match generics.lifetimes().next() {
+        Some(def) => def.lifetime.clone(),
+        None => parse_quote!('a),
+    };
+    let ctx = ctx_type(attrs);
+    let view = syn::Ident::new(&format!("{}View", name), name.span());
+    let magic_len = attrs.magic.as_ref().map_or(0, |magic| magic.value().len());
+    // the offset of each field of the fixed-size prefix, laid out as `Pread` and `ConstSize` do
+    let mut offset = quote!(#magic_len);
+    let mut fixed = true;
+    let accessors: Vec<_> = fields.named.iter().filter_map(|f| {
+        let field = field_attrs(&f.attrs);
+        if let Some(to) = field.offset {
+            offset = quote!(#to);
+        }
+        let varies = field.condition.is_some() || field.optional_trailing || field.leb128 || field.parse_with.is_some()
+            || field.ctx.as_ref().is_some_and(|ctx| mentions_self(quote!(#ctx)));
+        fixed = fixed && !varies;
+        if !fixed {
+            return None;
+        }
+        let ident = &f.ident;
+        if ["new", "as_bytes", "FIXED_SIZE"].iter().any(|own| ident.as_ref().unwrap() == own) {
+            panic!("PreadView cannot make an accessor for the field `{}`, which the view has already; rename the field", ident.as_ref().unwrap())
+        }
+        let ty = &f.ty;
+        let at = offset.clone();
+        offset = quote!(#offset + <#ty as ::scroll::ctx::ConstSize>::SIZE);
+        let field_ctx = match field.ctx {
+            Some(ref ctx) => quote!(#ctx),
+            None => quote!(ctx),
+        };
+        let read = match *ty {
+            syn::Type::Array(ref array) => match array.len {
+                syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(ref int), ..}) => {
+                    let size = int.value();
+                    quote_spanned! { ty.span()=>
+                        let mut __tmp: #ty = [0; #size as usize];
+                        self.bytes.gread_inout_with(&mut (#at), &mut __tmp, #field_ctx)?;
+                        Ok(__tmp)
+                    }
+                },
+                _ => panic!("Pread derive with bad array constexpr")
+            },
+            _ => match (&attrs.ctx, &field.ctx) {
+                (Some(struct_ctx), None) => quote_spanned! { ty.span()=>
+                    ::scroll::export::gread_field::<#ty, #struct_ctx>(self.bytes, &mut (#at), ctx)
+                },
+                _ => quote_spanned! { ty.span()=>
+                    self.bytes.pread_with::<#ty>(#at, #field_ctx)
+                },
+            },
+        };
+        let doc = format!("Reads `{}` from the bytes of the view", ident.as_ref().unwrap());
+        Some(quote! {
+            #[doc = #doc]
+            #[inline]
+            pub fn #ident(&self) -> ::scroll::Result<#ty> {
+                use ::scroll::Pread;
+                #[allow(unused_variables)]
+                let ctx = self.ctx;
+                #read
+            }
+        })
+    }).collect();
+    let size = match attrs.size {
+        Some(size) if fixed => quote!(#size),
+        _ => offset,
+    };
+    let magic = attrs.magic.as_ref().map(|magic| {
+        let text = source_text(magic);
+        quote! {
+            let __scroll_magic = &bytes[..#magic_len];
+            if __scroll_magic != &#magic[..] {
+                return Err(::scroll::export::field_mismatch("magic", #text, &__scroll_magic, 0));
+            }
+        }
+    });
+    let doc = format!("The unparsed bytes of a `{}`, whose fields are read one at a time, when they are asked for", name);
+    quote! {
+        #[doc = #doc]
+        #[derive(Clone, Copy)]
+        #vis struct #view<#lifetime> {
+            bytes: &#lifetime [u8],
+            ctx: #ctx,
+        }
+
+        impl<#lifetime> #view<#lifetime> {
+            /// The number of bytes a view needs: those of the fields with a fixed size and offset
+            pub const FIXED_SIZE: usize = #size;
+
+            /// A view of `bytes`, whose fields are read with `ctx`; only the size of `bytes`, and the magic, are checked
+            #[inline]
+            pub fn new(bytes: &#lifetime [u8], ctx: #ctx) -> ::scroll::Result<Self> {
+                if bytes.len() < Self::FIXED_SIZE {
+                    return Err(::scroll::Error::TooBig { size: Self::FIXED_SIZE, len: bytes.len() });
+                }
+                #magic
+                Ok(#view { bytes, ctx })
+            }
+
+            /// The bytes of the view
+            #[inline]
+            pub fn as_bytes(&self) -> &#lifetime [u8] {
+                self.bytes
+            }
+
+            #(#accessors)*
+        }
+    }
+}
+
+fn impl_view(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
+    let name = &ast.ident;
+    match ast.data {
+        syn::Data::Struct(ref data) => {
+            match data.fields {
+                syn::Fields::Named(ref fields) => {
+                    view(name, &ast.vis, &ast.generics, fields, &struct_attrs(&ast.attrs))
+                },
+                _ => {
+                    panic!("PreadView can only be derived for a regular struct with public fields")
+                }
+            }
+        },
+        _ => panic!("PreadView can only be derived for structs")
+    }
+}
+
+/// Derives a view of a struct's unparsed bytes, `<Name>View<'a>`, with a method per field which reads just that field
+/// when it is called, at the offset `Pread` would read it from
+///
+/// Making a view with `new` only checks that there are enough bytes, and the `magic`, so scanning one field of each of
+/// many records doesn't pay for parsing the rest; `eq` and `assert` aren't checked. Fields are read with the struct's
+/// context, or their own `ctx`, and need to be `ConstSize` to find where the next one starts. The first field whose
+/// size or presence depends on the input, as with `if`, `optional_trailing`, `leb128`, `parse_with` or a `ctx`
+/// referring to `self`, ends the fixed-size prefix the view covers, and it and the fields after it have no methods.
+/// A field in the prefix named like the view's own `new`, `as_bytes` or `FIXED_SIZE` is a compile error.
+///
+/// ```rust
+/// use scroll_derive::{Pread, PreadView};
+/// use scroll::{Pread, LE};
+///
+/// #[derive(Pread, PreadView)]
+/// #[scroll(magic = b"SY")]
+/// struct Symbol<'a> {
+///     value: u64,
+///     #[scroll(offset = 12)]
+///     section: u16,
+///     len: u8,
+///     #[scroll(ctx = "self.len as usize")]
+///     name: &'a [u8],
+/// }
+///
+/// let bytes = b"SY\x10\0\0\0\0\0\0\0\0\0\x02\0\x04main";
+/// let view = SymbolView::new(bytes, LE).unwrap();
+/// assert_eq!(SymbolView::FIXED_SIZE, 15);
+/// assert_eq!((view.value().unwrap(), view.section().unwrap(), view.len().unwrap()), (0x10, 2, 4));
+/// let symbol: Symbol = bytes.pread_with(0, LE).unwrap();
+/// assert_eq!(symbol.name, b"main");
+/// assert!(SymbolView::new(&bytes[..14], LE).is_err());
+/// assert!(SymbolView::new(b"MZ\x10\0\0\0\0\0\0\0\0\0\x02\0\x04", LE).is_err());
+/// ```
+/// ```compile_fail
+/// # use scroll_derive::PreadView;
+/// #[derive(PreadView)]
+/// struct Entry { new: u32 }
+/// ```
+#[proc_macro_derive(PreadView, attributes(scroll))]
+pub fn derive_pread_view(input: TokenStream) -> TokenStream {
+    let ast: syn::DeriveInput = syn::parse(input).unwrap();
+    let gen = impl_view(&ast);
+    gen.into()
+}
+
 fn impl_cread_struct(name: &syn::Ident, fields: &syn::FieldsNamed) -> proc_macro2::TokenStream {
     let items: Vec<_> = fields.named.iter().map(|f| {
         let ident = &f.ident;
@@ -244,6 +1580,19 @@ fn impl_from_ctx(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
         syn::Data::Struct(ref data) => {
             match data.fields {
                 syn::Fields::Named(ref fields) => {
+                    let attrs = struct_attrs(&ast.attrs);
+                    if attrs.magic.is_some() {
+                        panic!("IOread cannot check or write magic bytes, as it is infallible")
+                    }
+                    if attrs.ctx.is_some() {
+                        panic!("IOread only supports an `Endian` context")
+                    }
+                    if attrs.size.is_some() || attrs.bits.is_some() {
+                        panic!("IOread does not support a struct size or bits")
+                    }
+                    if fields.named.iter().any(|f| { let field = field_attrs(&f.attrs); field.parse_with.is_some() || field.write_with.is_some() || field.leb128 || field.offset.is_some() }) {
+                        panic!("IOread does not support parse_with, write_with, leb128 or offset")
+                    }
                     impl_cread_struct(name, fields)
                 },
                 _ => {
@@ -294,7 +1643,6 @@ fn impl_into_ctx(name: &syn::Ident, fields: &syn::FieldsNamed) -> proc_macro2::T
                 use ::scroll::Cwrite;
                 let offset = &mut 0;
                 #(#items;)*;
-                ()
             }
         }
 
@@ -313,6 +1661,19 @@ fn impl_iowrite(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
         syn::Data::Struct(ref data) => {
             match data.fields {
                 syn::Fields::Named(ref fields) => {
+                    let attrs = struct_attrs(&ast.attrs);
+                    if attrs.magic.is_some() {
+                        panic!("IOwrite cannot check or write magic bytes, as it is infallible")
+                    }
+                    if attrs.ctx.is_some() {
+                        panic!("IOwrite only supports an `Endian` context")
+                    }
+                    if attrs.size.is_some() || attrs.bits.is_some() {
+                        panic!("IOwrite does not support a struct size or bits")
+                    }
+                    if fields.named.iter().any(|f| { let field = field_attrs(&f.attrs); field.parse_with.is_some() || field.write_with.is_some() || field.leb128 || field.offset.is_some() }) {
+                        panic!("IOwrite does not support parse_with, write_with, leb128 or offset")
+                    }
                     impl_into_ctx(name, fields)
                 },
                 _ => {
@@ -1,4 +1,4 @@
-use scroll_derive::{Pread, Pwrite, SizeWith, IOread, IOwrite};
+use scroll_derive::{Pread, Pwrite, SizeWith, ConstSize, PreadView, IOread, IOwrite};
 use scroll::{Pread, Pwrite, Cread, Cwrite, LE};
 
 use scroll::ctx::SizeWith;
@@ -157,3 +157,743 @@ fn test_nested_struct() {
     assert_eq!(read, size);
     assert_eq!(b, b2);
 }
+
+#[derive(Debug, PartialEq, Pread, Pwrite)]
+#[scroll(default_ctx = "BE")]
+struct BeHeader {
+    magic: u16,
+    len: u32,
+}
+
+#[test]
+fn test_default_ctx() {
+    let bytes = [0xca, 0xfe, 0, 0, 0, 0x10];
+    let header = bytes.pread_into::<BeHeader>(0).unwrap();
+    assert_eq!(header, BeHeader { magic: 0xcafe, len: 0x10 });
+    let offset = &mut 0;
+    let header: BeHeader = bytes.gread_into(offset).unwrap();
+    assert_eq!(*offset, 6);
+
+    let mut out = [0u8; 6];
+    assert_eq!(out.pwrite_into(header, 0).unwrap(), 6);
+    assert_eq!(out, bytes);
+}
+
+#[derive(Debug, PartialEq, Pread, Pwrite, SizeWith)]
+#[scroll(magic = b"\x7fELF", verify_writes)]
+struct ElfIdent {
+    #[scroll(assert = "self.class == 1 || self.class == 2")]
+    class: u8,
+    data: u8,
+    #[scroll(eq = 1)]
+    version: u8,
+}
+
+#[test]
+fn test_magic_and_asserts() {
+    let bytes = [0x7f, b'E', b'L', b'F', 2, 1, 1];
+    let ident: ElfIdent = bytes.pread_with(0, LE).unwrap();
+    assert_eq!(ident, ElfIdent { class: 2, data: 1, version: 1 });
+    assert_eq!(ElfIdent::size_with(&LE), 7);
+
+    let mut out = [0u8; 7];
+    assert_eq!(out.pwrite_with(&ident, 0, LE).unwrap(), 7);
+    assert_eq!(out, bytes);
+
+    let err = [0x7f, b'E', b'L', b'G', 2, 1, 1].pread_with::<ElfIdent>(0, LE).unwrap_err();
+    assert_eq!(err.to_string(), r#"field `magic` at offset 0: expected b"\x7fELF", found [127, 69, 76, 71]"#);
+    let err = [0x7f, b'E', b'L', b'F', 2, 1, 0].pread_with::<ElfIdent>(0, LE).unwrap_err();
+    assert_eq!(err.to_string(), "field `version` at offset 6: expected 1, found 0");
+    let err = [0x7f, b'E', b'L', b'F', 3, 1, 1].pread_with::<ElfIdent>(0, LE).unwrap_err();
+    assert_eq!(err.to_string(), "field `class` at offset 4: expected self.class == 1 || self.class == 2, found 3");
+    assert!([0x7f, b'E', b'L'].pread_with::<ElfIdent>(0, LE).is_err());
+
+    let err = out.pwrite_with(ElfIdent { class: 1, data: 1, version: 2 }, 0, LE).unwrap_err();
+    assert_eq!(err.to_string(), "field `version` at offset 6: expected 1, found 2");
+    let err = out.pwrite_with(ElfIdent { class: 0, data: 1, version: 1 }, 0, LE).unwrap_err();
+    assert_eq!(err.to_string(), "field `class` at offset 4: expected self.class == 1 || self.class == 2, found 0");
+}
+
+#[derive(Debug, PartialEq, Pread, Pwrite)]
+#[scroll(verify_writes)]
+struct Chunk {
+    #[scroll(assert = "self.magic == 0xdead", error = "invalid magic")]
+    magic: u16,
+    #[scroll(eq = 1, error = "unsupported version")]
+    version: u8,
+}
+
+#[test]
+fn test_assert_errors() {
+    use scroll::BE;
+    let bytes = [0xde, 0xad, 0x01];
+    assert_eq!(bytes.pread_with::<Chunk>(0, BE).unwrap(), Chunk { magic: 0xdead, version: 1 });
+
+    let err = [0xbe, 0xef, 0x01].pread_with::<Chunk>(0, BE).unwrap_err();
+    assert!(matches!(err, scroll::Error::BadInput { size: 2, msg: "invalid magic" }), "{:?}", err);
+    let err = [0xde, 0xad, 0x02].pread_with::<Chunk>(0, BE).unwrap_err();
+    assert!(matches!(err, scroll::Error::BadInput { size: 1, msg: "unsupported version" }), "{:?}", err);
+
+    let mut out = [0u8; 3];
+    let err = out.pwrite_with(Chunk { magic: 0, version: 1 }, 0, BE).unwrap_err();
+    assert!(matches!(err, scroll::Error::BadInput { msg: "invalid magic", .. }), "{:?}", err);
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Date {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+mod date {
+    use super::Date;
+    use scroll::{Endian, Pread, Pwrite};
+
+    /// A date packed into 3 bytes: 7 bits of years since 1980, 4 bits of month, 5 bits of day, and a spare byte
+    pub fn parse(src: &[u8], offset: &mut usize, ctx: &Endian) -> scroll::Result<Date> {
+        let packed: u16 = src.gread_with(offset, *ctx)?;
+        let _spare: u8 = src.gread(offset)?;
+        Ok(Date { year: 1980 + (packed >> 9), month: (packed >> 5 & 0xf) as u8, day: (packed & 0x1f) as u8 })
+    }
+
+    pub fn write(date: &Date, dst: &mut [u8], offset: &mut usize, ctx: &Endian) -> scroll::Result<usize> {
+        let packed = (date.year - 1980) << 9 | u16::from(date.month) << 5 | u16::from(date.day);
+        Ok(dst.gwrite_with(packed, offset, *ctx)? + dst.gwrite(0u8, offset)?)
+    }
+}
+
+#[derive(Debug, PartialEq, Pread, Pwrite)]
+struct Entry {
+    id: u32,
+    flags: u16,
+    #[scroll(parse_with = "date::parse", write_with = "date::write")]
+    modified: Date,
+    size: u64,
+}
+
+#[test]
+fn test_parse_with() {
+    let entry = Entry { id: 7, flags: 0x8001, modified: Date { year: 2024, month: 5, day: 17 }, size: 4096 };
+    let mut bytes = [0u8; 17];
+    assert_eq!(bytes.pwrite_with(&entry, 0, LE).unwrap(), 17);
+    assert_eq!(&bytes[6..9], &[0xb1, 0x58, 0x00]);
+    let offset = &mut 0;
+    let read: Entry = bytes.gread_with(offset, LE).unwrap();
+    assert_eq!(read, entry);
+    assert_eq!(*offset, 17);
+    assert!(bytes[..8].pread_with::<Entry>(0, LE).is_err());
+}
+
+#[derive(Debug, PartialEq, Pread, Pwrite)]
+struct VersionedHeader {
+    version: u16,
+    flags: u16,
+    #[scroll(if = "self.version >= 2")]
+    checksum: Option<u32>,
+    #[scroll(if = "self.version >= 2 && ctx == scroll::LE")]
+    timestamp: Option<u64>,
+    #[scroll(optional_trailing)]
+    comment_offset: Option<u32>,
+    #[scroll(optional_trailing)]
+    comment_len: Option<u16>,
+}
+
+#[test]
+fn test_optional_fields() {
+    let v1 = [1, 0, 0xff, 0];
+    let offset = &mut 0;
+    let header: VersionedHeader = v1.gread_with(offset, LE).unwrap();
+    assert_eq!(header, VersionedHeader { version: 1, flags: 0xff, checksum: None, timestamp: None, comment_offset: None, comment_len: None });
+    assert_eq!(*offset, 4);
+    let mut out = [0u8; 24];
+    assert_eq!(out.pwrite_with(&header, 0, LE).unwrap(), 4);
+    assert_eq!(&out[..4], &v1);
+
+    let mut v2 = vec![2, 0, 0, 0, 0xef, 0xbe, 0xad, 0xde, 1, 0, 0, 0, 0, 0, 0, 0];
+    let header: VersionedHeader = v2.pread_with(0, LE).unwrap();
+    assert_eq!(header.checksum, Some(0xdeadbeef));
+    assert_eq!(header.timestamp, Some(1));
+    assert_eq!(header.comment_offset, None);
+    assert_eq!(out.pwrite_with(&header, 0, LE).unwrap(), 16);
+
+    v2.extend_from_slice(&[0x40, 0, 0, 0]);
+    let offset = &mut 0;
+    let header: VersionedHeader = v2.gread_with(offset, LE).unwrap();
+    assert_eq!((header.comment_offset, header.comment_len), (Some(0x40), None));
+    assert_eq!(*offset, 20);
+    v2.extend_from_slice(&[0x10, 0]);
+    let offset = &mut 0;
+    let header: VersionedHeader = v2.gread_with(offset, LE).unwrap();
+    assert_eq!((header.comment_offset, header.comment_len), (Some(0x40), Some(0x10)));
+    assert_eq!(*offset, 22);
+    assert_eq!(out.pwrite_with(&header, 0, LE).unwrap(), 22);
+    assert_eq!(&out[..22], &v2[..]);
+
+    // truncated in the middle of a trailing field, or of a required one
+    assert!(v2[..21].pread_with::<VersionedHeader>(0, LE).is_err());
+    assert!(v2[..18].pread_with::<VersionedHeader>(0, LE).is_err());
+    assert!(v2[..10].pread_with::<VersionedHeader>(0, LE).is_err());
+
+    // a present field has to be written
+    let header = VersionedHeader { checksum: None, ..header };
+    assert!(out.pwrite_with(&header, 0, LE).is_err());
+}
+
+#[derive(Debug, PartialEq, Pread, Pwrite)]
+struct Record {
+    flags: u8,
+    #[scroll(if = "self.flags & 0x01 != 0")]
+    checksum: u32,
+    #[scroll(if = "self.flags & 0x02 != 0")]
+    tag: [u8; 2],
+    len: u16,
+}
+
+#[test]
+fn test_conditional_fields() {
+    let with = [0x03, 0xef, 0xbe, 0xad, 0xde, b'o', b'k', 0x10, 0x00];
+    let offset = &mut 0;
+    let record: Record = with.gread_with(offset, LE).unwrap();
+    assert_eq!(record, Record { flags: 3, checksum: 0xdeadbeef, tag: *b"ok", len: 0x10 });
+    assert_eq!(*offset, 9);
+    let mut out = [0u8; 9];
+    assert_eq!(out.pwrite_with(&record, 0, LE).unwrap(), 9);
+    assert_eq!(out, with);
+
+    let without = [0x02, b'o', b'k', 0x10, 0x00];
+    let offset = &mut 0;
+    let record: Record = without.gread_with(offset, LE).unwrap();
+    assert_eq!(record, Record { flags: 2, checksum: 0, tag: *b"ok", len: 0x10 });
+    assert_eq!(*offset, 5);
+    assert_eq!(out.pwrite_with(&record, 0, LE).unwrap(), 5);
+    assert_eq!(&out[..5], &without);
+
+    let record: Record = [0x00, 0x10, 0x00].pread_with(0, LE).unwrap();
+    assert_eq!(record, Record { flags: 0, checksum: 0, tag: [0; 2], len: 0x10 });
+}
+
+#[derive(Debug, PartialEq, Pread)]
+struct Borrowed<'a> {
+    count: u32,
+    #[scroll(ctx = "self.count as usize")]
+    payload: &'a [u8],
+    #[scroll(ctx = "scroll::ctx::StrCtx::Delimiter(0)")]
+    name: &'a str,
+}
+
+#[derive(Debug, PartialEq, Pread, Pwrite, SizeWith, ConstSize)]
+struct Pair<T> {
+    a: T,
+    b: T,
+}
+
+#[derive(Debug, PartialEq, Pread)]
+struct Tagged<'a, T> {
+    #[scroll(ctx = "scroll::ctx::StrCtx::Length(3)")]
+    tag: &'a str,
+    pair: Pair<T>,
+}
+
+#[test]
+fn test_generics_and_lifetimes() {
+    let bytes = b"\x03\x00\x00\x00abcname\x00rest";
+    let offset = &mut 0;
+    let borrowed: Borrowed = bytes.gread_with(offset, LE).unwrap();
+    assert_eq!(borrowed, Borrowed { count: 3, payload: b"abc", name: "name" });
+    assert_eq!(*offset, 12);
+    assert_eq!(borrowed.payload.as_ptr(), bytes[4..].as_ptr());
+
+    let bytes = [0x01, 0x02, 0x03, 0x04];
+    let pair: Pair<u8> = bytes.pread_with(0, LE).unwrap();
+    assert_eq!(pair, Pair { a: 1, b: 2 });
+    let pair: Pair<u16> = bytes.pread_with(0, LE).unwrap();
+    assert_eq!(pair, Pair { a: 0x0201, b: 0x0403 });
+    assert_eq!(Pair::<u16>::size_with(&LE), 4);
+    let mut out = [0u8; 4];
+    assert_eq!(out.pwrite_with(pair, 0, scroll::BE).unwrap(), 4);
+    assert_eq!(out, [0x02, 0x01, 0x04, 0x03]);
+
+    let bytes = b"abc\x01\x00\x02\x00";
+    let tagged: Tagged<i16> = bytes.pread_with(0, LE).unwrap();
+    assert_eq!(tagged, Tagged { tag: "abc", pair: Pair { a: 1, b: 2 } });
+    let tagged: Tagged<Pair<u8>> = bytes.pread_with(0, LE).unwrap();
+    assert_eq!(tagged.pair.b, Pair { a: 2, b: 0 });
+}
+
+#[derive(Debug, PartialEq, Pread, Pwrite)]
+struct Varints {
+    tag: u32,
+    #[scroll(leb128)]
+    len: u64,
+    #[scroll(leb128)]
+    delta: i32,
+    flags: u16,
+    #[scroll(leb128)]
+    small: u8,
+}
+
+#[test]
+fn test_leb128_fields() {
+    let bytes = [0x01, 0x00, 0x00, 0x00, 0xe5, 0x8e, 0x26, 0xc0, 0xbb, 0x78, 0x34, 0x12, 0x7f];
+    let offset = &mut 0;
+    let varints: Varints = bytes.gread_with(offset, LE).unwrap();
+    assert_eq!(varints, Varints { tag: 1, len: 624485, delta: -123456, flags: 0x1234, small: 0x7f });
+    assert_eq!(*offset, bytes.len());
+    let mut out = [0u8; 13];
+    assert_eq!(out.pwrite_with(varints, 0, LE).unwrap(), bytes.len());
+    assert_eq!(out, bytes);
+
+    // 300 doesn't fit in `small`
+    let too_big = [0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xac, 0x02];
+    assert!(matches!(too_big.pread_with::<Varints>(0, LE), Err(scroll::Error::Invalid { offset: 8, .. })));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FormatCtx {
+    version: u16,
+    endian: scroll::Endian,
+}
+
+trait HasEndian {
+    fn endian(&self) -> scroll::Endian;
+}
+
+impl HasEndian for FormatCtx {
+    fn endian(&self) -> scroll::Endian {
+        self.endian
+    }
+}
+
+impl HasEndian for scroll::Endian {
+    fn endian(&self) -> scroll::Endian {
+        *self
+    }
+}
+
+#[derive(Debug, PartialEq, Pread, Pwrite, SizeWith)]
+#[scroll(ctx = "FormatCtx")]
+struct RecordHeader {
+    #[scroll(ctx = "ctx.endian")]
+    kind: u16,
+    #[scroll(ctx = "ctx.endian")]
+    len: u32,
+}
+
+#[derive(Debug, PartialEq, Pread, Pwrite)]
+#[scroll(ctx = "FormatCtx")]
+struct VersionedRecord {
+    header: RecordHeader,
+    #[scroll(if = "ctx.version >= 2", ctx = "ctx.endian")]
+    checksum: Option<u32>,
+}
+
+#[derive(Debug, PartialEq, Pread, Pwrite)]
+#[scroll(ctx = "C", ctx_bound = "C: Copy + HasEndian")]
+struct Span {
+    #[scroll(ctx = "ctx.endian()")]
+    start: u32,
+    #[scroll(ctx = "ctx.endian()")]
+    end: u32,
+}
+
+#[test]
+fn test_struct_ctx() {
+    let v1 = FormatCtx { version: 1, endian: scroll::BE };
+    let v2 = FormatCtx { version: 2, ..v1 };
+    let bytes = [0x00, 0x07, 0x00, 0x00, 0x00, 0x04, 0xca, 0xfe, 0xba, 0xbe];
+    let header = RecordHeader { kind: 7, len: 4 };
+    assert_eq!(RecordHeader::size_with(&v1), 6);
+
+    let offset = &mut 0;
+    let record: VersionedRecord = bytes.gread_with(offset, v1).unwrap();
+    assert_eq!(record, VersionedRecord { header: RecordHeader { kind: 7, len: 4 }, checksum: None });
+    assert_eq!(*offset, 6);
+    let offset = &mut 0;
+    let record: VersionedRecord = bytes.gread_with(offset, v2).unwrap();
+    assert_eq!(record, VersionedRecord { header, checksum: Some(0xcafebabe) });
+    assert_eq!(*offset, 10);
+
+    let mut out = [0u8; 10];
+    assert_eq!(out.pwrite_with(&record, 0, v2).unwrap(), 10);
+    assert_eq!(out, bytes);
+
+    let span: Span = bytes.pread_with(2, v1).unwrap();
+    assert_eq!(span, Span { start: 4, end: 0xcafebabe });
+    let span: Span = bytes.pread_with(2, LE).unwrap();
+    assert_eq!(span, Span { start: 0x0400_0000, end: 0xbebafeca });
+    assert_eq!(out.pwrite_with(span, 2, scroll::BE).unwrap(), 8);
+    assert_eq!(&out[2..], &[0x04, 0x00, 0x00, 0x00, 0xbe, 0xba, 0xfe, 0xca]);
+}
+
+#[derive(Debug, PartialEq, Pread, Pwrite, ConstSize)]
+struct SectionEntry {
+    name: [u8; 8],
+    #[scroll(ctx = "scroll::BE")]
+    addr: u64,
+    size: u32,
+    reserved: [u8; 4],
+}
+
+#[derive(Debug, PartialEq, Pread, ConstSize)]
+#[scroll(magic = b"SECT")]
+struct SectionTable {
+    count: u16,
+    first: SectionEntry,
+    sizes: [u16; 2],
+    tail: Pair<u16>,
+}
+
+const _: () = assert!(SectionEntry::SIZE == 24);
+
+#[test]
+fn test_const_size() {
+    let mut bytes = [0u8; 64];
+    bytes[..4].copy_from_slice(b"SECT");
+    bytes[4] = 1;
+    bytes[6..11].copy_from_slice(b".text");
+    bytes[21] = 0x10;
+    let offset = &mut 0;
+    let table: SectionTable = bytes.gread_with(offset, LE).unwrap();
+    assert_eq!(*offset, SectionTable::SIZE);
+    assert_eq!(SectionTable::SIZE, 4 + 2 + SectionEntry::SIZE + 4 + Pair::<u16>::SIZE);
+    assert_eq!(table.first.addr, 0x10);
+    assert_eq!(Pair::<u32>::size_with(&LE), Pair::<u32>::SIZE);
+
+    let offset = &mut 0;
+    let _: SectionEntry = bytes.gread_with(offset, LE).unwrap();
+    assert_eq!(*offset, SectionEntry::SIZE);
+    let table: [u8; SectionEntry::SIZE * 2] = [0; SectionEntry::SIZE * 2];
+    let entries: Vec<SectionEntry> = table.chunks(SectionEntry::SIZE).map(|chunk| chunk.pread_with(0, LE).unwrap()).collect();
+    assert_eq!(entries.len(), 2);
+}
+
+/// The fields of a FAT boot sector we care about, at their offsets in the BIOS parameter block
+#[derive(Debug, PartialEq, Pread, Pwrite, SizeWith, ConstSize)]
+#[scroll(size = 0x200)]
+struct BootSector {
+    jump: [u8; 3],
+    #[scroll(offset = 0x0b)]
+    bytes_per_sector: u16,
+    sectors_per_cluster: u8,
+    reserved_sectors: u16,
+    fats: u8,
+    #[scroll(offset = 0x27)]
+    volume_id: u32,
+    label: [u8; 11],
+    #[scroll(offset = 0x1fe, eq = 0xaa55)]
+    signature: u16,
+}
+
+#[derive(Debug, PartialEq, Pread, Pwrite)]
+#[scroll(size = 0x10, zero_gaps)]
+struct ZeroedGaps {
+    #[scroll(offset = 4)]
+    a: u16,
+    #[scroll(offset = 8)]
+    b: u16,
+}
+
+#[derive(Debug, Pread, Pwrite)]
+struct Overlapping {
+    a: u32,
+    #[scroll(offset = 2)]
+    b: u16,
+}
+
+#[test]
+fn test_explicit_offsets() {
+    let mut sector = [0u8; 0x200];
+    sector[..3].copy_from_slice(&[0xeb, 0x3c, 0x90]);
+    sector[3..0x0b].copy_from_slice(b"MSDOS5.0");
+    sector[0x0b..0x11].copy_from_slice(&[0x00, 0x02, 0x04, 0x01, 0x00, 0x02]);
+    sector[0x11..0x27].iter_mut().enumerate().for_each(|(i, b)| *b = i as u8 + 1);
+    sector[0x27..0x2b].copy_from_slice(&0x1234_5678u32.to_le_bytes());
+    sector[0x2b..0x36].copy_from_slice(b"NO NAME    ");
+    sector[0x36..0x3e].copy_from_slice(b"FAT16   ");
+    sector[0x3e..0x1fe].iter_mut().for_each(|b| *b = 0xf4);
+    sector[0x1fe..].copy_from_slice(&[0x55, 0xaa]);
+
+    let offset = &mut 0;
+    let boot: BootSector = sector.gread_with(offset, LE).unwrap();
+    assert_eq!(*offset, 0x200);
+    assert_eq!(BootSector::SIZE, 0x200);
+    assert_eq!(BootSector::size_with(&LE), 0x200);
+    assert_eq!((boot.bytes_per_sector, boot.sectors_per_cluster, boot.reserved_sectors, boot.fats), (512, 4, 1, 2));
+    assert_eq!(boot.volume_id, 0x1234_5678);
+    assert_eq!(&boot.label, b"NO NAME    ");
+
+    // the gaps are left as they are, so writing over the original round trips
+    let mut written = sector;
+    assert_eq!(written.pwrite_with(&boot, 0, LE).unwrap(), 0x200);
+    assert_eq!(written[..], sector[..]);
+    let mut blank = [0u8; 0x200];
+    blank.pwrite_with(&boot, 0, LE).unwrap();
+    assert_eq!(blank.pread_with::<BootSector>(0, LE).unwrap(), boot);
+    assert_eq!(blank[3..0x0b], [0; 8]);
+    assert!(matches!(sector[..0x1ff].pread_with::<BootSector>(0, LE), Err(scroll::Error::Incomplete { needed: 1 })));
+    assert!(matches!(blank[..0x100].pwrite_with(&boot, 0, LE), Err(scroll::Error::TooBig { size: 0x1fe, len: 0x100 })));
+
+    let mut bytes = [0xffu8; 0x10];
+    bytes.pwrite_with(ZeroedGaps { a: 1, b: 2 }, 0, LE).unwrap();
+    assert_eq!(bytes, [0, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(bytes.pread_with::<ZeroedGaps>(0, LE).unwrap(), ZeroedGaps { a: 1, b: 2 });
+
+    // sequential fields running into an explicit offset are an error, rather than overlapping
+    assert!(matches!(bytes.pread_with::<Overlapping>(0, LE), Err(scroll::Error::Invalid { offset: 2, .. })));
+    assert!(matches!(bytes.pwrite_with(Overlapping { a: 0, b: 0 }, 0, LE), Err(scroll::Error::Invalid { offset: 2, .. })));
+}
+
+/// The first two bytes of an IPv4 header
+#[derive(Debug, PartialEq, Pread, Pwrite, SizeWith, ConstSize)]
+#[scroll(bits = "u16", endian = "BE")]
+struct Ipv4VersionTos {
+    #[scroll(bits = 4)]
+    version: u8,
+    #[scroll(bits = 4)]
+    ihl: u8,
+    #[scroll(bits = 6)]
+    dscp: u8,
+    #[scroll(bits = 2)]
+    ecn: u8,
+}
+
+/// The flags and fragment offset of an IPv4 header
+#[derive(Debug, PartialEq, Pread, Pwrite)]
+#[scroll(bits = "u16", endian = "NETWORK")]
+struct Ipv4Fragment {
+    #[scroll(bits = 1)]
+    reserved: bool,
+    #[scroll(bits = 1)]
+    dont_fragment: bool,
+    #[scroll(bits = 1)]
+    more_fragments: bool,
+    #[scroll(bits = 13)]
+    offset: u16,
+}
+
+/// A control register numbered from its least significant bit, in the context's byte order
+#[derive(Debug, PartialEq, Pread, Pwrite)]
+#[scroll(bits = "u32", bit_order = "LsbFirst")]
+struct Control {
+    #[scroll(bits = 1)]
+    enable: bool,
+    #[scroll(bits = 3)]
+    mode: u8,
+    #[scroll(bits = 12)]
+    divisor: u16,
+    #[scroll(bits = 16)]
+    reserved: u32,
+}
+
+#[test]
+fn test_bitfields() {
+    let header = [0x45, 0xb8, 0x40, 0x12];
+    let offset = &mut 0;
+    let tos: Ipv4VersionTos = header.gread_with(offset, LE).unwrap();
+    assert_eq!(tos, Ipv4VersionTos { version: 4, ihl: 5, dscp: 46, ecn: 0 });
+    let fragment: Ipv4Fragment = header.gread_with(offset, LE).unwrap();
+    assert_eq!(fragment, Ipv4Fragment { reserved: false, dont_fragment: true, more_fragments: false, offset: 0x12 });
+    assert_eq!(*offset, 4);
+    assert_eq!(Ipv4VersionTos::SIZE, 2);
+    assert_eq!(Ipv4VersionTos::size_with(&LE), 2);
+
+    let mut written = [0u8; 4];
+    let offset = &mut 0;
+    written.gwrite_with(&tos, offset, LE).unwrap();
+    written.gwrite_with(&fragment, offset, LE).unwrap();
+    assert_eq!(written, header);
+
+    let bytes = 0x0000_a5cbu32.to_le_bytes();
+    let control: Control = bytes.pread_with(0, LE).unwrap();
+    assert_eq!(control, Control { enable: true, mode: 0b101, divisor: 0xa5c, reserved: 0 });
+    let mut written = [0u8; 4];
+    written.pwrite_with(&control, 0, scroll::BE).unwrap();
+    assert_eq!(u32::from_be_bytes(written), 0x0000_a5cb);
+
+    let too_wide = Ipv4VersionTos { version: 4, ihl: 16, dscp: 0, ecn: 0 };
+    assert!(matches!(written.pwrite_with(&too_wide, 0, LE), Err(scroll::Error::Invalid { offset: 0, .. })));
+    assert!(header[..1].pread_with::<Ipv4VersionTos>(0, LE).is_err());
+}
+
+#[derive(Debug, PartialEq, Pread, PreadView)]
+struct TableRecord {
+    name: [u8; 16],
+    kind: u16,
+    flags: u16,
+    #[scroll(ctx = "scroll::BE")]
+    addr: u64,
+    size: u32,
+    checksum: u32,
+    links: [u32; 4],
+}
+
+#[derive(Debug, PartialEq, Pread, PreadView)]
+#[scroll(ctx = "FormatCtx", magic = b"T")]
+struct TaggedSpan {
+    #[scroll(ctx = "ctx.endian")]
+    tag: u16,
+    #[scroll(offset = 4, ctx = "ctx.endian")]
+    start: u32,
+}
+
+#[test]
+fn test_pread_view() {
+    const RECORDS: usize = 1000;
+    let size = TableRecordView::FIXED_SIZE;
+    assert_eq!(size, 52);
+    let mut table = vec![0u8; RECORDS * size];
+    for (i, record) in table.chunks_mut(size).enumerate() {
+        record[..8].copy_from_slice(b"section.");
+        record.pwrite_with(i as u16, 16, LE).unwrap();
+        record.pwrite_with(i as u64 * 0x1000, 20, scroll::BE).unwrap();
+        record.pwrite_with(i as u32, 48, LE).unwrap();
+    }
+
+    let view = TableRecordView::new(&table[size..], LE).unwrap();
+    let record: TableRecord = table.pread_with(size, LE).unwrap();
+    assert_eq!(view.name().unwrap(), record.name);
+    assert_eq!(view.kind().unwrap(), record.kind);
+    assert_eq!(view.flags().unwrap(), record.flags);
+    assert_eq!(view.addr().unwrap(), 0x1000);
+    assert_eq!(view.size().unwrap(), record.size);
+    assert_eq!(view.checksum().unwrap(), record.checksum);
+    assert_eq!(view.links().unwrap(), [0, 0, 0, 1]);
+    assert_eq!(view.as_bytes().len(), (RECORDS - 1) * size);
+    assert!(matches!(TableRecordView::new(&table[..51], LE), Err(scroll::Error::TooBig { size: 52, len: 51 })));
+
+    // one field of each record, read through a view or by parsing the whole record; see benches/bench.rs for how long
+    let viewed: u64 = table.chunks(size).map(|bytes| TableRecordView::new(bytes, LE).unwrap().addr().unwrap()).sum();
+    let parsed: u64 = table.chunks(size).map(|bytes| bytes.pread_with::<TableRecord>(0, LE).unwrap().addr).sum();
+    assert_eq!(viewed, parsed);
+    assert_eq!(viewed, (0..RECORDS as u64).sum::<u64>() * 0x1000);
+
+    let ctx = FormatCtx { version: 1, endian: scroll::BE };
+    let bytes = b"T\x00\x07\xff\x00\x00\x00\x09";
+    let view = TaggedSpanView::new(bytes, ctx).unwrap();
+    assert_eq!((view.tag().unwrap(), view.start().unwrap()), (7, 9));
+    assert_eq!(bytes.pread_with::<TaggedSpan>(0, ctx).unwrap(), TaggedSpan { tag: 7, start: 9 });
+    assert!(TaggedSpanView::new(b"X\x00\x07\xff\x00\x00\x00\x09", ctx).is_err());
+    assert!(TaggedSpanView::new(&bytes[..7], ctx).is_err());
+}
+
+#[derive(Debug, PartialEq, Pread, Pwrite)]
+#[scroll(ctx = "C", ctx_bound = "C: Copy + HasEndian", debug_offsets)]
+struct DumpedSpan {
+    #[scroll(ctx = "ctx.endian()")]
+    start: u32,
+    #[scroll(ctx = "ctx.endian()")]
+    end: u32,
+}
+
+#[derive(Debug, PartialEq, Pread, Pwrite)]
+#[scroll(ctx = "FormatCtx", magic = b"DMP\0", size = 0x30, debug_offsets)]
+struct DumpedHeader {
+    #[scroll(ctx = "ctx.endian")]
+    version: u16,
+    #[scroll(leb128)]
+    count: u32,
+    #[scroll(parse_with = "read_name", ctx = "scroll::ctx::StrCtx::Delimiter(0)")]
+    name: String,
+    span: DumpedSpan,
+    #[scroll(if = "self.version >= 2", ctx = "ctx.endian")]
+    checksum: Option<i32>,
+    #[scroll(offset = 0x20, ctx = "ctx.endian")]
+    tags: [u16; 3],
+    #[scroll(ctx = "ctx.endian")]
+    delta: i16,
+}
+
+/// Borrows its name, so it can be read but not written
+#[derive(Pread)]
+#[scroll(debug_offsets)]
+struct DumpedRecord<'a> {
+    #[scroll(ctx = "scroll::ctx::StrCtx::Delimiter(0)")]
+    name: &'a str,
+    #[scroll(leb128)]
+    len: u64,
+}
+
+fn read_name(src: &[u8], offset: &mut usize, _: &FormatCtx) -> scroll::Result<String> {
+    src.gread_with::<&str>(offset, scroll::ctx::StrCtx::Delimiter(0)).map(String::from)
+}
+
+#[test]
+fn test_debug_offsets() {
+    let ctx = FormatCtx { version: 2, endian: scroll::BE };
+    let header = DumpedHeader {
+        version: 2,
+        count: 300,
+        name: "a\tb".to_string(),
+        span: DumpedSpan { start: 0x10, end: 0x20 },
+        checksum: Some(-2),
+        tags: [1, 2, 0xffff],
+        delta: -5,
+    };
+    let mut bytes = [0u8; 0x130];
+    assert_eq!(bytes.pwrite_with(&header, 0x100, ctx).unwrap(), 0x30);
+    assert_eq!(bytes.pread_with::<DumpedHeader>(0x100, ctx).unwrap(), header);
+    assert_eq!(DumpedHeader::dump(&bytes, 0x100, ctx).to_string(), "\
+0x0100    4  magic: [0x44, 0x4d, 0x50, 0x0]
+0x0104    2  version: 0x2
+0x0106    2  count: 0x12c
+0x0108    4  name: \"a\\tb\"
+0x010c    8  span:
+0x010c    4    start: 0x10
+0x0110    4    end: 0x20
+0x0114    4  checksum: -0x2
+0x0120    6  tags: [0x1, 0x2, 0xffff]
+0x0126    2  delta: -0x5");
+    assert_eq!(DumpedSpan::dump(&bytes, 0x10c, scroll::BE).to_string(), "0x010c    4  start: 0x10\n0x0110    4  end: 0x20");
+
+    // absent fields have no line, and the offsets after a shorter leb128 move up to meet it
+    let header = DumpedHeader { version: 1, count: 7, checksum: None, ..header };
+    let mut bytes = [0u8; 0x30];
+    bytes.pwrite_with(&header, 0, ctx).unwrap();
+    assert_eq!(DumpedHeader::dump(&bytes, 0, ctx).to_string(), "\
+0x0000    4  magic: [0x44, 0x4d, 0x50, 0x0]
+0x0004    2  version: 0x1
+0x0006    1  count: 0x7
+0x0007    4  name: \"a\\tb\"
+0x000b    8  span:
+0x000b    4    start: 0x10
+0x000f    4    end: 0x20
+0x0020    6  tags: [0x1, 0x2, 0xffff]
+0x0026    2  delta: -0x5");
+
+    // the offsets are the ones read: a non-canonical leb128, 300 in three bytes, takes all three
+    let mut bytes = [0u8; 0x30];
+    bytes[..6].copy_from_slice(b"DMP\0\x00\x01");
+    bytes[6..9].copy_from_slice(&[0xac, 0x82, 0x00]);
+    bytes[9..11].copy_from_slice(b"x\0");
+    assert_eq!(bytes.pread_with::<DumpedHeader>(0, ctx).unwrap().count, 300);
+    assert_eq!(DumpedHeader::dump(&bytes, 0, ctx).to_string(), "\
+0x0000    4  magic: [0x44, 0x4d, 0x50, 0x0]
+0x0004    2  version: 0x1
+0x0006    3  count: 0x12c
+0x0009    2  name: \"x\"
+0x000b    8  span:
+0x000b    4    start: 0x0
+0x000f    4    end: 0x0
+0x0020    6  tags: [0x0, 0x0, 0x0]
+0x0026    2  delta: 0x0");
+
+    // a read which fails shows how far it got
+    assert_eq!(DumpedHeader::dump(&bytes[..0x10], 0, ctx).to_string(), "\
+0x0000    4  magic: [0x44, 0x4d, 0x50, 0x0]
+0x0004    2  version: 0x1
+0x0006    3  count: 0x12c
+0x0009    2  name: \"x\"
+             <incomplete input, 3 more bytes needed>");
+    bytes[0] = b'X';
+    assert_eq!(DumpedHeader::dump(&bytes, 0, ctx).to_string(), "\
+0x0000    4  magic: [0x58, 0x4d, 0x50, 0x0]
+             <field `magic` at offset 0: expected b\"DMP\\0\", found [88, 77, 80, 0]>");
+    assert_eq!(DumpedHeader::dump(&bytes, 0x31, ctx).to_string(), "             <bad offset 49>");
+
+    // fields which can't be written are shown all the same
+    let bytes = b"..ab\0\x80\x01";
+    let record: DumpedRecord = bytes.pread_with(2, LE).unwrap();
+    assert_eq!((record.name, record.len), ("ab", 0x80));
+    assert_eq!(DumpedRecord::dump(bytes, 2, LE).to_string(), "0x0002    3  name: \"ab\"\n0x0005    2  len: 0x80");
+}
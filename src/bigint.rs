@@ -0,0 +1,293 @@
+use num_bigint::{BigInt, BigUint, Sign};
+
+use crate::ctx::{TryFromCtx, TryIntoCtx};
+use crate::endian::Endian;
+use crate::error;
+
+/// How the sign of a `BigInt` is stored
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SignConvention {
+    /// Negative numbers are stored in two's complement, like the builtin signed integers
+    TwosComplement,
+    /// The top bit of the most significant byte is the sign, and the remaining bits are the magnitude
+    SignMagnitude,
+}
+
+/// The context for reading and writing a `BigUint` or a `BigInt` as a fixed-width field of `len` bytes; requires the
+/// `bigint` feature
+///
+/// When writing a value which is shorter than the field, it is padded at its most significant end (sign extended in
+/// two's complement) if `pad` is set, and is an error otherwise. A value which is longer than the field is always
+/// an error.
+///
+/// A `(len, endian)` tuple can be used as the context as well, which is two's complement and padded.
+///
+/// # Example
+/// ```rust
+/// use num_bigint::{BigInt, BigUint};
+/// use scroll::{Pread, Pwrite, BE, LE, BigIntCtx, SignConvention};
+///
+/// let bytes = [0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+/// let n: BigUint = bytes.pread_with(0, (9, BE)).unwrap();
+/// assert_eq!(n, BigUint::from(1u8) << 64);
+///
+/// let ctx = BigIntCtx { sign: SignConvention::SignMagnitude, ..BigIntCtx::new(4, LE) };
+/// let mut out = [0u8; 4];
+/// out.pwrite_with(BigInt::from(-2), 0, ctx).unwrap();
+/// assert_eq!(out, [0x02, 0x00, 0x00, 0x80]);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BigIntCtx {
+    /// The width of the field in bytes
+    pub len: usize,
+    /// The byte order of the field
+    pub endian: Endian,
+    /// How the sign of a `BigInt` is stored; ignored for a `BigUint`
+    pub sign: SignConvention,
+    /// Whether a value shorter than the field is padded when written, rather than being an error
+    pub pad: bool,
+}
+
+impl BigIntCtx {
+    /// A two's complement, padded field of `len` bytes
+    pub fn new(len: usize, endian: Endian) -> Self {
+        BigIntCtx { len, endian, sign: SignConvention::TwosComplement, pad: true }
+    }
+
+    /// The field, in big endian byte order
+    fn read_be(&self, src: &[u8]) -> error::Result<Vec<u8>> {
+        if self.len > src.len() {
+            return Err(error::Error::Incomplete { needed: self.len - src.len() });
+        }
+        let mut field = src[..self.len].to_vec();
+        if self.endian.is_little() {
+            field.reverse();
+        }
+        Ok(field)
+    }
+
+    /// Writes the minimal big endian `bytes` of a value as the field, filling the most significant bytes with `fill`
+    fn write_be<'d>(&self, bytes: &[u8], fill: u8, dst: &'d mut [u8]) -> error::Result<&'d mut [u8]> {
+        if bytes.len() > self.len {
+            return Err(error::Error::TooBig { size: bytes.len(), len: self.len });
+        }
+        if bytes.len() < self.len && !self.pad {
+            return Err(error::Error::BadInput { size: bytes.len(), msg: "big integer is shorter than its field" });
+        }
+        if self.len > dst.len() {
+            return Err(error::Error::TooBig { size: self.len, len: dst.len() });
+        }
+        let field = &mut dst[..self.len];
+        let (padding, value) = field.split_at_mut(self.len - bytes.len());
+        for b in padding {
+            *b = fill;
+        }
+        value.copy_from_slice(bytes);
+        Ok(field)
+    }
+
+    fn finish(&self, field: &mut [u8]) -> usize {
+        if self.endian.is_little() {
+            field.reverse();
+        }
+        self.len
+    }
+}
+
+impl From<(usize, Endian)> for BigIntCtx {
+    fn from((len, endian): (usize, Endian)) -> Self {
+        BigIntCtx::new(len, endian)
+    }
+}
+
+/// The big endian magnitude of `n`, without any leading zeros
+fn magnitude(n: &BigUint) -> Vec<u8> {
+    if n.bits() == 0 {
+        Vec::new()
+    } else {
+        n.to_bytes_be()
+    }
+}
+
+impl<'a> TryFromCtx<'a, BigIntCtx> for BigUint {
+    type Error = error::Error;
+    #[inline]
+    fn try_from_ctx(src: &'a [u8], ctx: BigIntCtx) -> error::Result<(Self, usize)> {
+        Ok((BigUint::from_bytes_be(&ctx.read_be(src)?), ctx.len))
+    }
+}
+
+impl<'a> TryFromCtx<'a, BigIntCtx> for BigInt {
+    type Error = error::Error;
+    #[inline]
+    fn try_from_ctx(src: &'a [u8], ctx: BigIntCtx) -> error::Result<(Self, usize)> {
+        let mut field = ctx.read_be(src)?;
+        let n = match ctx.sign {
+            SignConvention::TwosComplement => BigInt::from_signed_bytes_be(&field),
+            SignConvention::SignMagnitude => {
+                let negative = field.first().is_some_and(|msb| msb & 0x80 != 0);
+                if let Some(msb) = field.first_mut() {
+                    *msb &= 0x7f;
+                }
+                let sign = if negative { Sign::Minus } else { Sign::Plus };
+                BigInt::from_biguint(sign, BigUint::from_bytes_be(&field))
+            }
+        };
+        Ok((n, ctx.len))
+    }
+}
+
+impl TryIntoCtx<BigIntCtx> for &BigUint {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], ctx: BigIntCtx) -> error::Result<usize> {
+        let field = ctx.write_be(&magnitude(self), 0, dst)?;
+        Ok(ctx.finish(field))
+    }
+}
+
+impl TryIntoCtx<BigIntCtx> for &BigInt {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], ctx: BigIntCtx) -> error::Result<usize> {
+        let negative = self.sign() == Sign::Minus;
+        let field = match ctx.sign {
+            SignConvention::TwosComplement => {
+                let bytes = if self.sign() == Sign::NoSign { Vec::new() } else { self.to_signed_bytes_be() };
+                ctx.write_be(&bytes, if negative { 0xff } else { 0 }, dst)?
+            },
+            SignConvention::SignMagnitude => {
+                let mut bytes = magnitude(self.magnitude());
+                // the top bit is taken by the sign
+                if bytes.first().is_some_and(|msb| msb & 0x80 != 0) {
+                    bytes.insert(0, 0);
+                }
+                let field = ctx.write_be(&bytes, 0, dst)?;
+                if negative {
+                    field[0] |= 0x80;
+                }
+                field
+            }
+        };
+        Ok(ctx.finish(field))
+    }
+}
+
+macro_rules! bigint_impl {
+    ($ty:ty) => {
+        impl<'a> TryFromCtx<'a, (usize, Endian)> for $ty {
+            type Error = error::Error;
+            #[inline]
+            fn try_from_ctx(src: &'a [u8], ctx: (usize, Endian)) -> error::Result<(Self, usize)> {
+                TryFromCtx::try_from_ctx(src, BigIntCtx::from(ctx))
+            }
+        }
+
+        impl TryIntoCtx<BigIntCtx> for $ty {
+            type Error = error::Error;
+            #[inline]
+            fn try_into_ctx(self, dst: &mut [u8], ctx: BigIntCtx) -> error::Result<usize> {
+                (&self).try_into_ctx(dst, ctx)
+            }
+        }
+
+        impl TryIntoCtx<(usize, Endian)> for &$ty {
+            type Error = error::Error;
+            #[inline]
+            fn try_into_ctx(self, dst: &mut [u8], ctx: (usize, Endian)) -> error::Result<usize> {
+                self.try_into_ctx(dst, BigIntCtx::from(ctx))
+            }
+        }
+
+        impl TryIntoCtx<(usize, Endian)> for $ty {
+            type Error = error::Error;
+            #[inline]
+            fn try_into_ctx(self, dst: &mut [u8], ctx: (usize, Endian)) -> error::Result<usize> {
+                (&self).try_into_ctx(dst, BigIntCtx::from(ctx))
+            }
+        }
+    }
+}
+
+bigint_impl!(BigUint);
+bigint_impl!(BigInt);
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::{BigInt, BigUint};
+    use crate::{Pread, Pwrite, Error, BE, LE};
+    use super::{BigIntCtx, SignConvention};
+
+    /// A 2048-bit odd modulus with its top bit set, as in an RSA public key
+    fn modulus() -> Vec<u8> {
+        let mut bytes: Vec<u8> = (0..256u32).map(|i| (i.wrapping_mul(167) ^ 0x5a) as u8).collect();
+        bytes[0] |= 0x80;
+        bytes[255] |= 1;
+        bytes
+    }
+
+    #[test]
+    fn modulus_round_trip() {
+        let bytes = modulus();
+        let n: BigUint = bytes.pread_with(0, (256, BE)).unwrap();
+        assert_eq!(n.bits(), 2048);
+        assert_eq!(&n % 2u8, BigUint::from(1u8));
+
+        let mut out = vec![0u8; 256];
+        assert_eq!(out.pwrite_with(&n, 0, (256, BE)).unwrap(), 256);
+        assert_eq!(out, bytes);
+
+        assert_eq!(out.pwrite_with(&n, 0, (256, LE)).unwrap(), 256);
+        assert!(out.iter().rev().eq(bytes.iter()));
+        assert_eq!(out.pread_with::<BigUint>(0, (256, LE)).unwrap(), n);
+
+        assert!(matches!(out.pwrite_with(&n, 0, (255, BE)), Err(Error::TooBig { size: 256, len: 255 })));
+        assert!(matches!(bytes.pread_with::<BigUint>(1, (256, BE)), Err(Error::Incomplete { needed: 1 })));
+    }
+
+    #[test]
+    fn zero() {
+        let mut out = [0xffu8; 4];
+        assert_eq!(out.pwrite_with(BigUint::from(0u8), 0, (4, BE)).unwrap(), 4);
+        assert_eq!(out, [0; 4]);
+        assert_eq!(out.pread_with::<BigUint>(0, (4, BE)).unwrap(), BigUint::from(0u8));
+
+        for &sign in &[SignConvention::TwosComplement, SignConvention::SignMagnitude] {
+            let ctx = BigIntCtx { sign, ..BigIntCtx::new(4, LE) };
+            out = [0xff; 4];
+            out.pwrite_with(BigInt::from(0), 0, ctx).unwrap();
+            assert_eq!(out, [0; 4]);
+            assert_eq!(out.pread_with::<BigInt>(0, ctx).unwrap(), BigInt::from(0));
+        }
+        // negative zero
+        let ctx = BigIntCtx { sign: SignConvention::SignMagnitude, ..BigIntCtx::new(2, BE) };
+        assert_eq!([0x80u8, 0].pread_with::<BigInt>(0, ctx).unwrap(), BigInt::from(0));
+    }
+
+    #[test]
+    fn signed() {
+        let mut out = [0u8; 3];
+        out.pwrite_with(BigInt::from(-129), 0, (3, BE)).unwrap();
+        assert_eq!(out, [0xff, 0xff, 0x7f]);
+        assert_eq!(out.pread_with::<BigInt>(0, (3, BE)).unwrap(), BigInt::from(-129));
+
+        let ctx = BigIntCtx { sign: SignConvention::SignMagnitude, ..BigIntCtx::new(3, BE) };
+        out.pwrite_with(BigInt::from(-129), 0, ctx).unwrap();
+        assert_eq!(out, [0x80, 0x00, 0x81]);
+        assert_eq!(out.pread_with::<BigInt>(0, ctx).unwrap(), BigInt::from(-129));
+
+        // the sign bit needs a byte of its own
+        let ctx = BigIntCtx { len: 1, ..ctx };
+        assert!(out.pwrite_with(BigInt::from(128), 0, ctx).is_err());
+        assert!(out.pwrite_with(BigInt::from(-128), 0, BigIntCtx::new(1, BE)).is_ok());
+    }
+
+    #[test]
+    fn unpadded() {
+        let ctx = BigIntCtx { pad: false, ..BigIntCtx::new(2, BE) };
+        let mut out = [0u8; 2];
+        assert!(matches!(out.pwrite_with(BigUint::from(1u8), 0, ctx), Err(Error::BadInput { .. })));
+        assert_eq!(out.pwrite_with(BigUint::from(0x100u16), 0, ctx).unwrap(), 2);
+        assert_eq!(out, [1, 0]);
+    }
+}
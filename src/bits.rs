@@ -0,0 +1,259 @@
+use crate::ctx::{BitOrder, SizeWith, TryFromCtx, TryIntoCtx};
+use crate::error;
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use crate::prelude::*;
+
+/// Where a [BitWriter](struct.BitWriter.html) puts its bytes: a `[u8]`, which fails to take any past its end, or, with
+/// the `alloc` feature, a `Vec<u8>`, which grows to take them
+pub trait BitSink {
+    /// The `len` bytes at `offset`, making room for them if the sink can, or an `Error::TooBig` if it can't
+    fn bytes_at(&mut self, offset: usize, len: usize) -> error::Result<&mut [u8]>;
+}
+
+impl BitSink for [u8] {
+    #[inline]
+    fn bytes_at(&mut self, offset: usize, len: usize) -> error::Result<&mut [u8]> {
+        let room = self.len().saturating_sub(offset);
+        if len > room {
+            return Err(error::Error::TooBig { size: len, len: room });
+        }
+        Ok(&mut self[offset..offset + len])
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl BitSink for Vec<u8> {
+    fn bytes_at(&mut self, offset: usize, len: usize) -> error::Result<&mut [u8]> {
+        let end = offset.checked_add(len).ok_or(error::Error::TooBig { size: len, len: usize::MAX - offset })?;
+        if end > self.len() {
+            crate::buffer::try_reserve(self, end - self.len())?;
+            self.resize(end, 0);
+        }
+        Ok(&mut self[offset..end])
+    }
+}
+
+/// The bit at `position` in a stream of bits in `order`: its byte, and its mask in that byte
+#[inline]
+fn locate(position: usize, order: BitOrder) -> (usize, u8) {
+    (position / 8, order.mask(position))
+}
+
+/// Writes values a given number of bits wide into a [BitSink](trait.BitSink.html), packed back to back across bytes, as
+/// codecs and compressed formats store them
+///
+/// With `BitOrder::LsbFirst`, as in DEFLATE, the bits fill each byte from its least significant bit, and a value's
+/// least significant bit goes first; with `BitOrder::MsbFirst`, as in MPEG and H.264 streams, they fill each byte from
+/// its most significant bit, and a value's most significant bit goes first. Bytes are written as the bits reach them, so
+/// the sink always holds the stream so far; [finish](#method.finish) zeroes the unused bits of the last byte.
+///
+/// Once [aligned](#method.align_to_byte), whole values can be written with [gwrite_with](#method.gwrite_with) and
+/// [write_bytes](#method.write_bytes), between runs of bits. A write which doesn't fit in a `[u8]` sink is an
+/// `Error::TooBig`, and writes nothing.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx::BitOrder, BitCursor, BitWriter, BE};
+///
+/// // a final DEFLATE block with the fixed Huffman codes, holding nothing but its end-of-block code
+/// let mut bytes = [0xffu8; 8];
+/// let mut writer = BitWriter::new(&mut bytes[..], BitOrder::LsbFirst);
+/// writer.write_bits(1, 1).unwrap();
+/// writer.write_bits(0b01, 2).unwrap();
+/// writer.write_bits(0, 7).unwrap();
+/// assert_eq!(writer.bit_position(), 10);
+/// writer.align_to_byte(false).unwrap();
+/// writer.gwrite_with(0xcafeu16, BE).unwrap();
+/// assert_eq!(writer.finish(), 4);
+/// assert_eq!(bytes[..4], [0x03, 0x00, 0xca, 0xfe]);
+///
+/// let mut cursor = BitCursor::new(&bytes, BitOrder::LsbFirst);
+/// assert_eq!((cursor.read_bits(1).unwrap(), cursor.read_bits(2).unwrap()), (1, 0b01));
+/// ```
+#[derive(Debug)]
+pub struct BitWriter<'a, S: BitSink + ?Sized = [u8]> {
+    sink: &'a mut S,
+    position: usize,
+    order: BitOrder,
+}
+
+impl<'a, S: BitSink + ?Sized> BitWriter<'a, S> {
+    /// Writes bits in `order` from the start of `sink`
+    #[inline]
+    pub fn new(sink: &'a mut S, order: BitOrder) -> Self {
+        BitWriter { sink, position: 0, order }
+    }
+    /// The number of bits written
+    #[inline]
+    pub fn bit_position(&self) -> usize {
+        self.position
+    }
+    /// Whether the next bit starts a byte
+    #[inline]
+    pub fn is_aligned(&self) -> bool {
+        self.position.is_multiple_of(8)
+    }
+    /// The bytes at the byte position, whole bytes from here on, which the stream must be aligned to
+    fn aligned_bytes(&mut self, len: usize) -> error::Result<&mut [u8]> {
+        if !self.is_aligned() {
+            return Err(error::Error::Invalid { offset: self.position / 8, msg: "bit stream is not byte aligned" });
+        }
+        self.sink.bytes_at(self.position / 8, len)
+    }
+    /// Writes the low `width` bits of `value`, up to 64 of them; a `value` with bits set above them is an
+    /// `Error::Invalid`, and a `width` over 64 an `Error::BadInput`
+    pub fn write_bits(&mut self, value: u64, width: u32) -> error::Result<()> {
+        if width > 64 {
+            return Err(error::Error::BadInput { size: width as usize, msg: "a bit field is at most 64 bits wide" });
+        }
+        if width < 64 && value >> width != 0 {
+            return Err(error::Error::Invalid { offset: self.position / 8, msg: "value is too large for its width" });
+        }
+        let start = self.position / 8;
+        let end = (self.position + width as usize).div_ceil(8);
+        let bytes = self.sink.bytes_at(start, end - start)?;
+        for i in 0..width {
+            let bit = match self.order {
+                BitOrder::LsbFirst => value >> i & 1,
+                BitOrder::MsbFirst => value >> (width - 1 - i) & 1,
+            };
+            let (byte, mask) = locate(self.position + i as usize, self.order);
+            if bit == 1 {
+                bytes[byte - start] |= mask;
+            } else {
+                bytes[byte - start] &= !mask;
+            }
+        }
+        self.position += width as usize;
+        Ok(())
+    }
+    /// Writes a single bit
+    #[inline]
+    pub fn write_bit(&mut self, bit: bool) -> error::Result<()> {
+        self.write_bits(u64::from(bit), 1)
+    }
+    /// Fills the rest of the current byte with `fill_bit`, so that the next bit starts a byte; does nothing when it
+    /// already does
+    #[inline]
+    pub fn align_to_byte(&mut self, fill_bit: bool) -> error::Result<()> {
+        let pad = ((8 - self.position % 8) % 8) as u32;
+        self.write_bits(if fill_bit { (1 << pad) - 1 } else { 0 }, pad)
+    }
+    /// Writes `value` with the given `ctx` at the byte position, returning the number of bytes it took; the stream must
+    /// be aligned, or it is an `Error::Invalid`
+    pub fn gwrite_with<N, Ctx>(&mut self, value: N, ctx: Ctx) -> error::Result<usize>
+        where N: TryIntoCtx<Ctx, Error = error::Error> + SizeWith<Ctx>,
+              Ctx: Copy,
+    {
+        let size = N::size_with(&ctx);
+        let written = value.try_into_ctx(self.aligned_bytes(size)?, ctx)?;
+        self.position += written * 8;
+        Ok(written)
+    }
+    /// Writes `bytes` as they are at the byte position; the stream must be aligned, or it is an `Error::Invalid`
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> error::Result<()> {
+        self.aligned_bytes(bytes.len())?.copy_from_slice(bytes);
+        self.position += bytes.len() * 8;
+        Ok(())
+    }
+    /// Zeroes the unused bits of the last byte, if it is partial, and returns the number of bytes the stream takes up
+    pub fn finish(self) -> usize {
+        let len = self.position.div_ceil(8);
+        if !self.is_aligned() {
+            // the byte was written already, so there is room for it
+            if let Ok(bytes) = self.sink.bytes_at(len - 1, 1) {
+                for i in self.position..len * 8 {
+                    bytes[0] &= !self.order.mask(i);
+                }
+            }
+        }
+        len
+    }
+}
+
+/// Reads values a given number of bits wide from bytes packed as a [BitWriter](struct.BitWriter.html) writes them, in
+/// the same order
+///
+/// Once [aligned](#method.align_to_byte), whole values can be read with [gread_with](#method.gread_with), which borrow
+/// from the bytes as a `gread_with` does. Reading past the end is an `Error::Incomplete` with the number of bytes
+/// missing, and reads nothing.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx::BitOrder, BitCursor};
+///
+/// let mut cursor = BitCursor::new(&[0b1011_1111, 0x80], BitOrder::MsbFirst);
+/// assert_eq!(cursor.read_bits(3).unwrap(), 0b101);
+/// assert_eq!(cursor.read_bits(6).unwrap(), 0b11111_1);
+/// assert!(!cursor.is_aligned());
+/// assert!(cursor.read_bits(8).is_err());
+/// assert_eq!(cursor.bit_position(), 9);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BitCursor<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    order: BitOrder,
+}
+
+impl<'a> BitCursor<'a> {
+    /// Reads bits in `order` from the start of `bytes`
+    #[inline]
+    pub fn new(bytes: &'a [u8], order: BitOrder) -> Self {
+        BitCursor { bytes, position: 0, order }
+    }
+    /// The number of bits read
+    #[inline]
+    pub fn bit_position(&self) -> usize {
+        self.position
+    }
+    /// Whether the next bit starts a byte
+    #[inline]
+    pub fn is_aligned(&self) -> bool {
+        self.position.is_multiple_of(8)
+    }
+    /// Reads `width` bits, up to 64 of them, as the low bits of a `u64`; a `width` over 64 is an `Error::BadInput`
+    pub fn read_bits(&mut self, width: u32) -> error::Result<u64> {
+        if width > 64 {
+            return Err(error::Error::BadInput { size: width as usize, msg: "a bit field is at most 64 bits wide" });
+        }
+        let end = (self.position + width as usize).div_ceil(8);
+        if end > self.bytes.len() {
+            return Err(error::Error::Incomplete { needed: end - self.bytes.len() });
+        }
+        let mut value = 0;
+        for i in 0..width {
+            let (byte, mask) = locate(self.position + i as usize, self.order);
+            let bit = u64::from(self.bytes[byte] & mask != 0);
+            value |= match self.order {
+                BitOrder::LsbFirst => bit << i,
+                BitOrder::MsbFirst => bit << (width - 1 - i),
+            };
+        }
+        self.position += width as usize;
+        Ok(value)
+    }
+    /// Reads a single bit
+    #[inline]
+    pub fn read_bit(&mut self) -> error::Result<bool> {
+        self.read_bits(1).map(|bit| bit == 1)
+    }
+    /// Skips the rest of the current byte, so that the next bit starts a byte; does nothing when it already does
+    #[inline]
+    pub fn align_to_byte(&mut self) {
+        self.position = self.position.div_ceil(8) * 8;
+    }
+    /// Reads a value with the given `ctx` at the byte position; the stream must be aligned, or it is an `Error::Invalid`
+    pub fn gread_with<N, Ctx>(&mut self, ctx: Ctx) -> error::Result<N>
+        where N: TryFromCtx<'a, Ctx, Error = error::Error>,
+              Ctx: Copy,
+    {
+        if !self.is_aligned() {
+            return Err(error::Error::Invalid { offset: self.position / 8, msg: "bit stream is not byte aligned" });
+        }
+        let bytes = self.bytes.get(self.position / 8..).ok_or(error::Error::Incomplete { needed: 1 })?;
+        let (value, size) = N::try_from_ctx(bytes, ctx)?;
+        self.position += size * 8;
+        Ok(value)
+    }
+}
@@ -0,0 +1,735 @@
+use core::ops::{Index, IndexMut, RangeFrom};
+#[cfg(feature = "alloc")]
+use core::ops::Range;
+#[cfg(feature = "alloc")]
+use alloc::borrow::Cow;
+#[cfg(feature = "alloc")]
+use alloc::alloc::{alloc_zeroed, dealloc, Layout};
+#[cfg(feature = "alloc")]
+use core::{fmt, ptr::NonNull, slice};
+
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::ctx::TryIntoCtx;
+use crate::error;
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use crate::prelude::*;
+
+/// A fixed capacity buffer of `N` bytes, stored inline, which values are appended to; for serializing small packets
+/// without an allocator
+///
+/// Only the bytes written so far are valid: they are what `as_slice` returns, and what the buffer can be read from or
+/// overwritten through [Pread](trait.Pread.html) and [Pwrite](trait.Pwrite.html). A value which doesn't fit in the rest
+/// of the buffer is an `Error::TooBig` whose `size` is the capacity the buffer would have needed, and isn't appended.
+///
+/// # Example
+/// ```rust
+/// use scroll::{SmallBuffer, Pread, Pwrite, BE};
+///
+/// let mut packet = SmallBuffer::<8>::new();
+/// packet.gwrite_with(0x01u8, BE).unwrap();
+/// let len = packet.len();
+/// packet.gwrite_with(0u16, BE).unwrap();
+/// packet.gwrite_with(0xdeadbeefu32, BE).unwrap();
+/// // patch the length in
+/// packet.pwrite_with(4u16, len, BE).unwrap();
+/// assert_eq!(packet.as_slice(), [0x01, 0x00, 0x04, 0xde, 0xad, 0xbe, 0xef]);
+/// assert_eq!(packet.pread_with::<u16>(1, BE).unwrap(), 4);
+/// assert!(packet.gwrite_with(0u16, BE).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SmallBuffer<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> Default for SmallBuffer<N> {
+    #[inline]
+    fn default() -> Self {
+        SmallBuffer::new()
+    }
+}
+
+impl<const N: usize> SmallBuffer<N> {
+    #[inline]
+    pub const fn new() -> Self {
+        SmallBuffer { bytes: [0; N], len: 0 }
+    }
+    /// The number of bytes written so far
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// The most bytes the buffer can hold, `N`
+    #[inline]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+    /// The bytes written so far
+    #[inline]
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+    /// The bytes written so far, mutably
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        &mut self.bytes[..self.len]
+    }
+    /// Empties the buffer
+    #[inline]
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+    /// Shortens the buffer to its first `len` bytes; does nothing if it isn't longer than that
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+    /// Removes the bytes in `range`, moving those after it down, and returns them, like `Vec::drain`; requires the `alloc`
+    /// feature
+    ///
+    /// # Panics
+    /// If the range is reversed, or ends past the bytes written.
+    ///
+    /// # Example
+    /// ```rust
+    /// use scroll::{SmallBuffer, LE};
+    /// let mut buffer = SmallBuffer::<8>::new();
+    /// buffer.gwrite_with(0x0403_0201u32, LE).unwrap();
+    /// assert_eq!(buffer.drain(0..3), [1, 2, 3]);
+    /// assert_eq!(buffer.as_slice(), [4]);
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn drain(&mut self, range: Range<usize>) -> Vec<u8> {
+        let drained = self.as_slice()[range.clone()].to_vec();
+        self.bytes.copy_within(range.end..self.len, range.start);
+        self.len -= range.len();
+        drained
+    }
+    /// Appends `n`, written with the given `ctx`, returning the number of bytes it took
+    pub fn gwrite_with<T, Ctx>(&mut self, n: T, ctx: Ctx) -> error::Result<usize>
+        where T: TryIntoCtx<Ctx, Error = error::Error>,
+              Ctx: Copy,
+    {
+        match n.try_into_ctx(&mut self.bytes[self.len..], ctx) {
+            Ok(size) => {
+                self.len += size;
+                Ok(size)
+            },
+            Err(error::Error::TooBig { size, .. }) => Err(error::Error::TooBig { size: self.len + size, len: N }),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for SmallBuffer<N> {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl<const N: usize> AsMut<[u8]> for SmallBuffer<N> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut [u8] {
+        self.as_mut_slice()
+    }
+}
+
+impl<const N: usize> Index<usize> for SmallBuffer<N> {
+    type Output = u8;
+    #[inline]
+    fn index(&self, index: usize) -> &u8 {
+        &self.as_slice()[index]
+    }
+}
+
+impl<const N: usize> Index<RangeFrom<usize>> for SmallBuffer<N> {
+    type Output = [u8];
+    #[inline]
+    fn index(&self, range: RangeFrom<usize>) -> &[u8] {
+        &self.as_slice()[range]
+    }
+}
+
+impl<const N: usize> IndexMut<RangeFrom<usize>> for SmallBuffer<N> {
+    #[inline]
+    fn index_mut(&mut self, range: RangeFrom<usize>) -> &mut [u8] {
+        &mut self.as_mut_slice()[range]
+    }
+}
+
+/// Bytes which are borrowed until they're first written to, and then copied; for sources which are mostly read, and
+/// only occasionally patched; requires the `alloc` feature
+///
+/// Reads, through [Pread](trait.Pread.html), never copy. The first write, through [Pwrite](trait.Pwrite.html) or
+/// `as_mut_slice`, copies the bytes once, and writes after it go to that copy. Writes don't change the length, so
+/// offsets stay valid across the copy; [prepend](#method.prepend) and [append_buf](#method.append_buf) do, and copy too.
+///
+/// # Example
+/// ```rust
+/// use scroll::{CowBuffer, Pread, Pwrite, LE};
+///
+/// let bytes = [0x01, 0x00, 0x02, 0x00];
+/// let mut buffer = CowBuffer::new(&bytes[..]);
+/// assert_eq!(buffer.pread_with::<u16>(2, LE).unwrap(), 2);
+/// assert!(!buffer.is_owned());
+/// buffer.pwrite_with(3u16, 2, LE).unwrap();
+/// assert!(buffer.is_owned());
+/// assert_eq!(buffer.into_owned(), [0x01, 0x00, 0x03, 0x00]);
+/// assert_eq!(bytes, [0x01, 0x00, 0x02, 0x00]);
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CowBuffer<'a> {
+    bytes: Cow<'a, [u8]>,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> CowBuffer<'a> {
+    #[inline]
+    pub fn new(bytes: impl Into<Cow<'a, [u8]>>) -> Self {
+        CowBuffer { bytes: bytes.into() }
+    }
+    /// Whether the bytes have been copied, by a write or by being owned from the start
+    #[inline]
+    pub fn is_owned(&self) -> bool {
+        matches!(self.bytes, Cow::Owned(_))
+    }
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+    #[inline]
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bytes
+    }
+    /// The bytes, mutably; this copies them if they're still borrowed
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        self.bytes.to_mut()
+    }
+    /// The bytes, copied if they're still borrowed
+    #[inline]
+    pub fn into_owned(self) -> Vec<u8> {
+        self.bytes.into_owned()
+    }
+    /// Concatenates `bufs`, in order, into a single new buffer, e.g. a packet's header, payload and trailer
+    ///
+    /// # Example
+    /// ```rust
+    /// use scroll::CowBuffer;
+    ///
+    /// let header = CowBuffer::new(&[0x01, 0x02][..]);
+    /// let payload = CowBuffer::from(vec![0xaa; 3]);
+    /// let packet = CowBuffer::concat_all(vec![header, payload, CowBuffer::new(&[0xff][..])]);
+    /// assert_eq!(packet.as_slice(), [0x01, 0x02, 0xaa, 0xaa, 0xaa, 0xff]);
+    /// ```
+    pub fn concat_all<'b>(bufs: impl IntoIterator<Item = CowBuffer<'b>>) -> CowBuffer<'static> {
+        let mut bytes = Vec::new();
+        for buf in bufs {
+            bytes.extend_from_slice(buf.as_slice());
+        }
+        CowBuffer::from(bytes)
+    }
+    /// Inserts `prefix` before the bytes, copying them if they're still borrowed; offsets into the buffer move up by
+    /// `prefix.len()`
+    pub fn prepend(&mut self, prefix: &[u8]) {
+        let bytes = self.bytes.to_mut();
+        bytes.splice(0..0, prefix.iter().copied());
+    }
+    /// Appends the bytes of `suffix`, copying these ones if they're still borrowed
+    pub fn append_buf(&mut self, suffix: &CowBuffer) {
+        self.bytes.to_mut().extend_from_slice(suffix.as_slice());
+    }
+    /// Allocates `len` zeroed bytes, or returns an `Error::AllocFailed` rather than aborting if they can't be, e.g., for
+    /// a size declared by untrusted input
+    ///
+    /// # Example
+    /// ```rust
+    /// use scroll::{CowBuffer, Error};
+    ///
+    /// assert_eq!(CowBuffer::try_zeroed(4).unwrap().as_slice(), [0; 4]);
+    /// assert!(matches!(CowBuffer::try_zeroed(usize::MAX), Err(Error::AllocFailed { size: usize::MAX })));
+    /// ```
+    pub fn try_zeroed(len: usize) -> error::Result<CowBuffer<'static>> {
+        let mut bytes = try_vec(len)?;
+        bytes.resize(len, 0);
+        Ok(CowBuffer::from(bytes))
+    }
+    /// An empty, owned buffer with room for `capacity` bytes, or an `Error::AllocFailed` if they can't be allocated
+    pub fn try_with_capacity(capacity: usize) -> error::Result<CowBuffer<'static>> {
+        try_vec(capacity).map(CowBuffer::from)
+    }
+    /// Makes room for at least `additional` more bytes, copying the bytes if they're still borrowed; an allocation
+    /// which fails is an `Error::AllocFailed`, and leaves the buffer as it was
+    pub fn try_reserve(&mut self, additional: usize) -> error::Result<()> {
+        match self.bytes {
+            Cow::Owned(ref mut bytes) => try_reserve(bytes, additional),
+            Cow::Borrowed(borrowed) => {
+                let mut bytes = try_vec(borrowed.len().saturating_add(additional))?;
+                bytes.extend_from_slice(borrowed);
+                self.bytes = Cow::Owned(bytes);
+                Ok(())
+            },
+        }
+    }
+    /// How many bytes the buffer can hold without reallocating; just its length while the bytes are borrowed
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        match self.bytes {
+            Cow::Owned(ref bytes) => bytes.capacity(),
+            Cow::Borrowed(bytes) => bytes.len(),
+        }
+    }
+}
+
+/// Makes room in `vec` for at least `additional` more elements, or returns an `Error::AllocFailed` with the number of
+/// bytes that needed
+#[cfg(feature = "alloc")]
+pub(crate) fn try_reserve<T>(vec: &mut Vec<T>, additional: usize) -> error::Result<()> {
+    vec.try_reserve(additional)
+        .map_err(|_| error::Error::AllocFailed { size: additional.saturating_mul(core::mem::size_of::<T>()) })
+}
+
+/// An empty `Vec` with room for `capacity` elements, or an `Error::AllocFailed`
+#[cfg(feature = "alloc")]
+pub(crate) fn try_vec<T>(capacity: usize) -> error::Result<Vec<T>> {
+    let mut vec = Vec::new();
+    try_reserve(&mut vec, capacity)?;
+    Ok(vec)
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> From<&'a [u8]> for CowBuffer<'a> {
+    #[inline]
+    fn from(bytes: &'a [u8]) -> Self {
+        CowBuffer::new(bytes)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<Vec<u8>> for CowBuffer<'static> {
+    #[inline]
+    fn from(bytes: Vec<u8>) -> Self {
+        CowBuffer::new(bytes)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> From<Cow<'a, [u8]>> for CowBuffer<'a> {
+    #[inline]
+    fn from(bytes: Cow<'a, [u8]>) -> Self {
+        CowBuffer { bytes }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> AsRef<[u8]> for CowBuffer<'a> {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> AsMut<[u8]> for CowBuffer<'a> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut [u8] {
+        self.as_mut_slice()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> Index<usize> for CowBuffer<'a> {
+    type Output = u8;
+    #[inline]
+    fn index(&self, index: usize) -> &u8 {
+        &self.as_slice()[index]
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> Index<RangeFrom<usize>> for CowBuffer<'a> {
+    type Output = [u8];
+    #[inline]
+    fn index(&self, range: RangeFrom<usize>) -> &[u8] {
+        &self.as_slice()[range]
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> IndexMut<RangeFrom<usize>> for CowBuffer<'a> {
+    #[inline]
+    fn index_mut(&mut self, range: RangeFrom<usize>) -> &mut [u8] {
+        &mut self.as_mut_slice()[range]
+    }
+}
+
+/// A heap allocated, fixed length buffer whose bytes start at a multiple of a requested alignment, e.g. 16 for SIMD
+/// views or 4096 for direct IO; requires the `alloc` feature
+///
+/// The bytes are zeroed when allocated: handing out uninitialized memory as a `&[u8]` would be undefined behavior, so
+/// there is no uninitialized variant. Otherwise it is read and written like any other byte buffer, through
+/// [Pread](trait.Pread.html) and [Pwrite](trait.Pwrite.html).
+///
+/// # Example
+/// ```rust
+/// use scroll::{AlignedBuffer, Pread, Pwrite, LE};
+///
+/// let mut buffer = AlignedBuffer::with_alignment(64, 4096).unwrap();
+/// assert_eq!(buffer.as_slice().as_ptr() as usize % 4096, 0);
+/// assert_eq!(buffer.alignment(), 4096);
+/// buffer.pwrite_with(0xdeadbeefu32, 8, LE).unwrap();
+/// assert_eq!(buffer.pread_with::<u32>(8, LE).unwrap(), 0xdeadbeef);
+/// assert!(AlignedBuffer::with_alignment(64, 24).is_err());
+/// ```
+#[cfg(feature = "alloc")]
+pub struct AlignedBuffer {
+    ptr: NonNull<u8>,
+    layout: Layout,
+}
+
+// the buffer uniquely owns its allocation, like a `Box<[u8]>`
+#[cfg(feature = "alloc")]
+unsafe impl Send for AlignedBuffer {}
+#[cfg(feature = "alloc")]
+unsafe impl Sync for AlignedBuffer {}
+
+#[cfg(feature = "alloc")]
+impl AlignedBuffer {
+    /// Allocates `len` zeroed bytes, starting at a multiple of `align`; an `align` which isn't a power of two, or a
+    /// `len` too large to allocate with it, is an `Error::BadInput`, and an allocation which fails is an
+    /// `Error::AllocFailed`
+    pub fn with_alignment(len: usize, align: usize) -> error::Result<Self> {
+        let layout = Layout::from_size_align(len, align)
+            .map_err(|_| error::Error::BadInput { size: align, msg: "alignment must be a power of two, and the length allocatable with it" })?;
+        let ptr = if len == 0 {
+            // a zero sized allocation isn't allowed; a dangling, aligned pointer is a valid empty slice
+            NonNull::new(core::ptr::without_provenance_mut(align)).expect("alignments are nonzero")
+        } else {
+            // SAFETY: the layout has a nonzero size
+            match NonNull::new(unsafe { alloc_zeroed(layout) }) {
+                Some(ptr) => ptr,
+                None => return Err(error::Error::AllocFailed { size: len }),
+            }
+        };
+        Ok(AlignedBuffer { ptr, layout })
+    }
+    /// Allocates a copy of `bytes`, starting at a multiple of `align`
+    pub fn from_slice_with_alignment(bytes: &[u8], align: usize) -> error::Result<Self> {
+        let mut buffer = AlignedBuffer::with_alignment(bytes.len(), align)?;
+        buffer.as_mut_slice().copy_from_slice(bytes);
+        Ok(buffer)
+    }
+    /// The alignment the bytes are guaranteed to start at
+    #[inline]
+    pub fn alignment(&self) -> usize {
+        self.layout.align()
+    }
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.layout.size()
+    }
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.layout.size() == 0
+    }
+    #[inline]
+    pub fn as_slice(&self) -> &[u8] {
+        // SAFETY: `ptr` is valid for `len` initialized bytes, or dangling and aligned for an empty slice
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.layout.size()) }
+    }
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        // SAFETY: as in `as_slice`, and `&mut self` makes the borrow unique
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.layout.size()) }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Drop for AlignedBuffer {
+    fn drop(&mut self) {
+        if self.layout.size() != 0 {
+            // SAFETY: `ptr` was allocated with exactly this layout
+            unsafe { dealloc(self.ptr.as_ptr(), self.layout) }
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Clone for AlignedBuffer {
+    fn clone(&self) -> Self {
+        AlignedBuffer::from_slice_with_alignment(self.as_slice(), self.alignment()).expect("failed to allocate a copy of an AlignedBuffer")
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Debug for AlignedBuffer {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("AlignedBuffer").field("alignment", &self.alignment()).field("bytes", &self.as_slice()).finish()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl PartialEq for AlignedBuffer {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Eq for AlignedBuffer {}
+
+#[cfg(feature = "alloc")]
+impl AsRef<[u8]> for AlignedBuffer {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl AsMut<[u8]> for AlignedBuffer {
+    #[inline]
+    fn as_mut(&mut self) -> &mut [u8] {
+        self.as_mut_slice()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Index<usize> for AlignedBuffer {
+    type Output = u8;
+    #[inline]
+    fn index(&self, index: usize) -> &u8 {
+        &self.as_slice()[index]
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Index<RangeFrom<usize>> for AlignedBuffer {
+    type Output = [u8];
+    #[inline]
+    fn index(&self, range: RangeFrom<usize>) -> &[u8] {
+        &self.as_slice()[range]
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl IndexMut<RangeFrom<usize>> for AlignedBuffer {
+    #[inline]
+    fn index_mut(&mut self, range: RangeFrom<usize>) -> &mut [u8] {
+        &mut self.as_mut_slice()[range]
+    }
+}
+
+/// An owned, growable buffer for key material, credentials and other secrets, which wipes its bytes when it's dropped;
+/// requires the `zeroize` feature
+///
+/// The bytes are wiped with the `zeroize` crate, whose writes the optimizer can't elide, and all of the allocation is
+/// wiped, not just the bytes in use. The buffer never lets its allocation be reallocated, which could leave a copy of
+/// the bytes behind: growing it copies them to a larger allocation and wipes the old one before releasing it. Values
+/// are appended with [gwrite_with](#method.gwrite_with), and read or overwritten through [Pread](trait.Pread.html) and
+/// [Pwrite](trait.Pwrite.html); `Debug` shows only the length.
+///
+/// # Example
+/// ```rust
+/// use scroll::{SecretBuffer, Pread, BE};
+///
+/// let blob = [0x00, 0x04, 0xde, 0xad, 0xbe, 0xef];
+/// let len: u16 = blob.pread_with(0, BE).unwrap();
+/// let mut key = SecretBuffer::new();
+/// key.extend_from_slice(blob.pread_with::<&[u8]>(2, len as usize).unwrap()).unwrap();
+/// key.gwrite_with(0x0102u16, BE).unwrap();
+/// assert_eq!(key.as_slice(), [0xde, 0xad, 0xbe, 0xef, 0x01, 0x02]);
+/// assert_eq!(format!("{:?}", key), "SecretBuffer { len: 6, .. }");
+/// ```
+#[cfg(feature = "zeroize")]
+#[derive(Default)]
+pub struct SecretBuffer {
+    bytes: Vec<u8>,
+}
+
+#[cfg(feature = "zeroize")]
+impl SecretBuffer {
+    #[inline]
+    pub fn new() -> Self {
+        SecretBuffer { bytes: Vec::new() }
+    }
+    /// An empty buffer with room for `capacity` bytes, or an `Error::AllocFailed` if they can't be allocated
+    pub fn try_with_capacity(capacity: usize) -> error::Result<Self> {
+        try_vec(capacity).map(|bytes| SecretBuffer { bytes })
+    }
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+    /// How many bytes the buffer can hold before it has to grow
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.bytes.capacity()
+    }
+    #[inline]
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bytes
+    }
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        &mut self.bytes
+    }
+    /// Wipes the bytes, and empties the buffer
+    #[inline]
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+    /// Shortens the buffer to its first `len` bytes, wiping the rest; does nothing if it isn't longer than that
+    pub fn truncate(&mut self, len: usize) {
+        if let Some(rest) = self.bytes.get_mut(len..) {
+            rest.zeroize();
+            self.bytes.truncate(len);
+        }
+    }
+    /// Makes room for at least `additional` more bytes; a larger allocation is made, the bytes are copied to it, and
+    /// the old one is wiped before it's released. An allocation which fails is an `Error::AllocFailed`, and leaves the
+    /// buffer as it was.
+    pub fn reserve(&mut self, additional: usize) -> error::Result<()> {
+        if self.bytes.capacity() - self.bytes.len() >= additional {
+            return Ok(());
+        }
+        let capacity = self.bytes.len().checked_add(additional)
+            .ok_or(error::Error::AllocFailed { size: usize::MAX })?
+            .max(self.bytes.capacity().saturating_mul(2));
+        let mut bytes = try_vec(capacity)?;
+        bytes.extend_from_slice(&self.bytes);
+        // wipes the whole of the old allocation, spare capacity included
+        self.bytes.zeroize();
+        self.bytes = bytes;
+        Ok(())
+    }
+    /// Appends `bytes`, growing the buffer as [reserve](#method.reserve) does
+    pub fn extend_from_slice(&mut self, bytes: &[u8]) -> error::Result<()> {
+        self.reserve(bytes.len())?;
+        self.bytes.extend_from_slice(bytes);
+        Ok(())
+    }
+    /// Appends `n`, written with the given `ctx`, growing the buffer until it fits, and returns the number of bytes it
+    /// took; a value which fails to write isn't appended
+    ///
+    /// `n` is written straight into the buffer, never into a scratch buffer, so it must be `Clone` to be written again
+    /// when it doesn't fit; each attempt is given twice the room of the last, as for [to_vec](fn.to_vec.html), up to
+    /// `Counter::DEFAULT_LIMIT` bytes, and a larger value is an `Error::LimitExceeded("size")`.
+    pub fn gwrite_with<T, Ctx>(&mut self, n: T, ctx: Ctx) -> error::Result<usize>
+        where T: TryIntoCtx<Ctx, Error = error::Error> + Clone,
+              Ctx: Copy,
+    {
+        let len = self.bytes.len();
+        let mut room = 64;
+        let mut last_too_big = None;
+        loop {
+            self.reserve(room)?;
+            // within the capacity, so this doesn't reallocate
+            self.bytes.resize(len + room, 0);
+            let written = n.clone().try_into_ctx(&mut self.bytes[len..], ctx);
+            match written {
+                Ok(size) => {
+                    self.truncate(len + size);
+                    return Ok(size);
+                },
+                Err(e) => {
+                    self.truncate(len);
+                    room = crate::counter::grown_len(e, room, crate::Counter::DEFAULT_LIMIT, &mut last_too_big)?;
+                },
+            }
+        }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for SecretBuffer {
+    fn drop(&mut self) {
+        self.bytes.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Zeroize for SecretBuffer {
+    #[inline]
+    fn zeroize(&mut self) {
+        self.bytes.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl ZeroizeOnDrop for SecretBuffer {}
+
+#[cfg(feature = "zeroize")]
+impl From<Vec<u8>> for SecretBuffer {
+    /// Takes over `bytes`, and their allocation, which is wiped when the buffer is dropped
+    #[inline]
+    fn from(bytes: Vec<u8>) -> Self {
+        SecretBuffer { bytes }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl fmt::Debug for SecretBuffer {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("SecretBuffer").field("len", &self.len()).finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl AsRef<[u8]> for SecretBuffer {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl AsMut<[u8]> for SecretBuffer {
+    #[inline]
+    fn as_mut(&mut self) -> &mut [u8] {
+        self.as_mut_slice()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Index<usize> for SecretBuffer {
+    type Output = u8;
+    #[inline]
+    fn index(&self, index: usize) -> &u8 {
+        &self.as_slice()[index]
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Index<RangeFrom<usize>> for SecretBuffer {
+    type Output = [u8];
+    #[inline]
+    fn index(&self, range: RangeFrom<usize>) -> &[u8] {
+        &self.as_slice()[range]
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl IndexMut<RangeFrom<usize>> for SecretBuffer {
+    #[inline]
+    fn index_mut(&mut self, range: RangeFrom<usize>) -> &mut [u8] {
+        &mut self.as_mut_slice()[range]
+    }
+}
@@ -0,0 +1,112 @@
+//! `const fn` reads of the primitive numbers, for pulling fields out of embedded data (e.g. `include_bytes!`) at compile time
+//!
+//! Each function reads its type at `offset` in the given byte order, and panics if the bytes aren't there; in a `const`
+//! item, that panic is a compile error.
+//!
+//! # Example
+//! ```rust
+//! use scroll::const_read;
+//!
+//! const BLOB: &[u8] = &[0x7f, b'E', b'L', b'F', 0x02, 0x00, 0x00, 0x00];
+//! const VERSION: u32 = const_read::u32_le(BLOB, 4);
+//! const _: () = assert!(VERSION == 2);
+//! ```
+
+/// Copies the `N` bytes at `offset` out of `bytes`, panicking if there aren't that many
+#[inline]
+const fn bytes_at<const N: usize>(bytes: &[u8], offset: usize) -> [u8; N] {
+    assert!(offset <= bytes.len() && bytes.len() - offset >= N, "const_read out of bounds");
+    let mut out = [0u8; N];
+    let mut i = 0;
+    while i < N {
+        out[i] = bytes[offset + i];
+        i += 1;
+    }
+    out
+}
+
+/// Reads the `u8` at `offset`
+#[inline]
+pub const fn u8(bytes: &[u8], offset: usize) -> u8 {
+    bytes_at::<1>(bytes, offset)[0]
+}
+
+/// Reads the `i8` at `offset`
+#[inline]
+pub const fn i8(bytes: &[u8], offset: usize) -> i8 {
+    bytes_at::<1>(bytes, offset)[0] as i8
+}
+
+macro_rules! const_read_impl {
+    ($typ:ty, $le:ident, $be:ident) => {
+        #[doc = concat!("Reads the little endian `", stringify!($typ), "` at `offset`")]
+        #[inline]
+        pub const fn $le(bytes: &[u8], offset: usize) -> $typ {
+            <$typ>::from_le_bytes(bytes_at(bytes, offset))
+        }
+        #[doc = concat!("Reads the big endian `", stringify!($typ), "` at `offset`")]
+        #[inline]
+        pub const fn $be(bytes: &[u8], offset: usize) -> $typ {
+            <$typ>::from_be_bytes(bytes_at(bytes, offset))
+        }
+    }
+}
+
+const_read_impl!(u16, u16_le, u16_be);
+const_read_impl!(i16, i16_le, i16_be);
+const_read_impl!(u32, u32_le, u32_be);
+const_read_impl!(i32, i32_le, i32_be);
+const_read_impl!(u64, u64_le, u64_be);
+const_read_impl!(i64, i64_le, i64_be);
+const_read_impl!(u128, u128_le, u128_be);
+const_read_impl!(i128, i128_le, i128_be);
+
+/// Reads the little endian `f32` at `offset`
+#[inline]
+pub const fn f32_le(bytes: &[u8], offset: usize) -> f32 {
+    f32::from_bits(u32_le(bytes, offset))
+}
+
+/// Reads the big endian `f32` at `offset`
+#[inline]
+pub const fn f32_be(bytes: &[u8], offset: usize) -> f32 {
+    f32::from_bits(u32_be(bytes, offset))
+}
+
+/// Reads the little endian `f64` at `offset`
+#[inline]
+pub const fn f64_le(bytes: &[u8], offset: usize) -> f64 {
+    f64::from_bits(u64_le(bytes, offset))
+}
+
+/// Reads the big endian `f64` at `offset`
+#[inline]
+pub const fn f64_be(bytes: &[u8], offset: usize) -> f64 {
+    f64::from_bits(u64_be(bytes, offset))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Pread, BE, LE};
+
+    const BLOB: &[u8] = &[0xde, 0xad, 0xbe, 0xef, 0x01, 0x02, 0x03, 0x04, 0x00, 0x00, 0xc0, 0x3f];
+    const MAGIC: u32 = u32_be(BLOB, 0);
+    const _: () = assert!(MAGIC == 0xdeadbeef);
+    const _: () = assert!(u16_le(BLOB, 4) == 0x0201);
+    const _: () = assert!(i8(BLOB, 0) == -0x22);
+
+    #[test]
+    fn const_read_matches_pread() {
+        assert_eq!(u64_le(BLOB, 2), BLOB.pread_with::<u64>(2, LE).unwrap());
+        assert_eq!(i64_be(BLOB, 2), BLOB.pread_with::<i64>(2, BE).unwrap());
+        assert_eq!(f32_le(BLOB, 8), 1.5);
+        assert_eq!(u32_le(BLOB, 8), 0x3fc0_0000);
+    }
+
+    #[test]
+    #[should_panic(expected = "const_read out of bounds")]
+    fn const_read_out_of_bounds() {
+        u32_le(BLOB, 9);
+    }
+}
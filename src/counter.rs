@@ -0,0 +1,219 @@
+use core::result;
+
+use crate::ctx::{SizeWith, TryIntoCtx};
+use crate::error;
+use crate::offset;
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use crate::prelude::*;
+
+/// A sink which only tallies how many bytes the values written into it take, e.g. to size an output buffer exactly before allocating it
+///
+/// Values are written after one another with [gwrite_with](#method.gwrite_with), at given offsets with
+/// [pwrite_with](#method.pwrite_with), or, with the `std` feature, as bytes through `std::io::Write`.
+/// Unlike [SizeWith](ctx/trait.SizeWith.html), this accounts for sizes which depend on the value, like those of strings and variable length integers.
+/// Each value is written into a scratch buffer which is reused, and grown whenever a value doesn't fit (a `TooBig` or `BadOffset` error
+/// which shows a lack of room), up to the counter's limit; the bytes themselves are discarded.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx::{Str, StrCtx}, Counter, BE};
+///
+/// let mut counter = Counter::new();
+/// counter.gwrite_with(0x1234u16, BE).unwrap();
+/// counter.gwrite_with(Str("hi"), StrCtx::Delimiter(0)).unwrap();
+/// assert_eq!(counter.total(), 5);
+/// counter.gwrite_align(4).unwrap();
+/// assert_eq!(counter.total(), 8);
+/// counter.pwrite_with(0u64, 0x10, BE).unwrap();
+/// assert_eq!(counter.total(), 0x18);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Counter {
+    scratch: Vec<u8>,
+    total: usize,
+    limit: usize,
+}
+
+impl Default for Counter {
+    #[inline]
+    fn default() -> Self {
+        Counter::new()
+    }
+}
+
+impl Counter {
+    /// The largest value a counter sizes by default, 16 MiB
+    pub const DEFAULT_LIMIT: usize = 16 << 20;
+    #[inline]
+    pub fn new() -> Self {
+        Counter::with_limit(Self::DEFAULT_LIMIT)
+    }
+    /// Creates a counter which can size values of at most `limit` bytes; larger ones are an `Error::LimitExceeded("size")`
+    #[inline]
+    pub fn with_limit(limit: usize) -> Self {
+        Counter { scratch: Vec::new(), total: 0, limit }
+    }
+    /// The number of bytes written so far
+    #[inline]
+    pub fn total(&self) -> usize {
+        self.total
+    }
+    /// "Writes" `n` with the given `ctx`, adding the number of bytes it takes to the total, and returning it
+    pub fn gwrite_with<N, Ctx>(&mut self, n: N, ctx: Ctx) -> error::Result<usize>
+        where N: TryIntoCtx<Ctx, Error = error::Error> + Clone,
+              Ctx: Copy,
+    {
+        let size = write_growing(&mut self.scratch, n, ctx, self.limit)?;
+        self.total = self.total.checked_add(size).ok_or(error::Error::BadOffset(self.total))?;
+        Ok(size)
+    }
+    /// "Writes" `n` with the given `ctx` at `offset`, as [Pwrite::pwrite_with](trait.Pwrite.html#method.pwrite_with)
+    /// does, returning the number of bytes it takes; the total becomes the end of the furthest value written, so values
+    /// can be placed at fixed offsets, in any order
+    pub fn pwrite_with<N, Ctx>(&mut self, n: N, offset: usize, ctx: Ctx) -> error::Result<usize>
+        where N: TryIntoCtx<Ctx, Error = error::Error> + Clone,
+              Ctx: Copy,
+    {
+        let size = write_growing(&mut self.scratch, n, ctx, self.limit)?;
+        let end = offset.checked_add(size).ok_or(error::Error::BadOffset(offset))?;
+        self.total = self.total.max(end);
+        Ok(size)
+    }
+    /// "Writes" the padding up to the next multiple of `align`, which must be a power of two, adding it to the total, and returning it
+    pub fn gwrite_align(&mut self, align: usize) -> error::Result<usize> {
+        let aligned = offset::align_up(self.total, align)?;
+        let padding = aligned - self.total;
+        self.total = aligned;
+        Ok(padding)
+    }
+}
+
+/// Tallies the bytes written through `std::io::Write`, so that a counter can size the output of encoders which write
+/// into a `Write`, and values written with [IOwrite](trait.IOwrite.html)
+#[cfg(feature = "std")]
+impl std::io::Write for Counter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.total = self.total.checked_add(buf.len())
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, error::Error::BadOffset(self.total)))?;
+        Ok(buf.len())
+    }
+    #[inline]
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Returns the number of bytes `n` takes when written with the given `ctx`; see [Counter](struct.Counter.html)
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx::{Str, StrCtx}, size_of_value, Pwrite};
+///
+/// let name = Str("scroll");
+/// let size = size_of_value(&name, StrCtx::Delimiter(0)).unwrap();
+/// assert_eq!(size, 7);
+/// let mut bytes = vec![0u8; size];
+/// bytes.pwrite_with(name, 0, StrCtx::Delimiter(0)).unwrap();
+/// ```
+pub fn size_of_value<N, Ctx>(n: &N, ctx: Ctx) -> result::Result<usize, error::Error>
+    where N: TryIntoCtx<Ctx, Error = error::Error> + Clone,
+          Ctx: Copy,
+{
+    Counter::new().gwrite_with(n.clone(), ctx)
+}
+
+/// Writes `n` at the start of `buffer`, growing it whenever `n` doesn't fit, up to `limit` bytes; see
+/// [write_growing_with](fn.write_growing_with.html)
+fn write_growing<N, Ctx>(buffer: &mut Vec<u8>, n: N, ctx: Ctx, limit: usize) -> error::Result<usize>
+    where N: TryIntoCtx<Ctx, Error = error::Error> + Clone,
+          Ctx: Copy,
+{
+    write_growing_with(buffer, limit, |buffer| n.clone().try_into_ctx(buffer, ctx))
+}
+
+/// Calls `write` on `buffer`, growing it until what `write` writes fits; needing more than `limit` bytes is an
+/// `Error::LimitExceeded("size")`
+///
+/// Only errors which show a lack of room are retried: a `BadOffset` at or past the end of the buffer, or a `TooBig`
+/// which changes as the buffer grows. Any other error, like a `BadOffset` within the buffer, or the same `TooBig` twice
+/// running, is the value's own, and is returned as it is.
+fn write_growing_with(buffer: &mut Vec<u8>, limit: usize, mut write: impl FnMut(&mut [u8]) -> error::Result<usize>) -> error::Result<usize> {
+    if buffer.is_empty() {
+        buffer.resize(64.min(limit), 0);
+    }
+    let mut last_too_big = None;
+    loop {
+        let error = match write(buffer) {
+            Ok(size) => return Ok(size),
+            Err(e) => e,
+        };
+        let len = grown_len(error, buffer.len(), limit, &mut last_too_big)?;
+        crate::buffer::try_reserve(buffer, len - buffer.len())?;
+        buffer.resize(len, 0);
+    }
+}
+
+/// The length to grow a buffer of `len` bytes to, after a write into it failed with `error`, or the error to return if
+/// it didn't fail for a lack of room, as [write_growing_with](fn.write_growing_with.html) tells them apart;
+/// `last_too_big` holds the `TooBig` of the previous attempt
+pub(crate) fn grown_len(error: error::Error, len: usize, limit: usize, last_too_big: &mut Option<(usize, usize)>) -> error::Result<usize> {
+    let needed = match error {
+        error::Error::BadOffset(offset) if offset >= len => offset.saturating_add(1),
+        error::Error::TooBig { size, len: room } if *last_too_big != Some((size, room)) => {
+            *last_too_big = Some((size, room));
+            len.saturating_add(1)
+        },
+        e => return Err(e),
+    };
+    if len >= limit || needed > limit {
+        return Err(error::Error::LimitExceeded("size"));
+    }
+    Ok(len.saturating_mul(2).max(needed).min(limit))
+}
+
+/// Writes `n` with the given `ctx` into a new `Vec`, which is exactly as long as the bytes written
+///
+/// Values of any size can be written, variable length ones included: the `Vec` is grown, as a [Counter](struct.Counter.html)'s
+/// scratch buffer is, until `n` fits, up to `Counter::DEFAULT_LIMIT` bytes; a larger value is an `Error::LimitExceeded("size")`.
+/// For values whose size is known statically, [to_vec_sized](fn.to_vec_sized.html) allocates it once, and
+/// [to_array](fn.to_array.html) doesn't allocate.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx::{Str, StrCtx}, to_vec, Uleb128, Pread, BE};
+///
+/// assert_eq!(to_vec(0xdeadbeefu32, BE).unwrap(), [0xde, 0xad, 0xbe, 0xef]);
+/// assert_eq!(to_vec(Str("hi"), StrCtx::Delimiter(0)).unwrap(), b"hi\0");
+/// let n: Uleb128 = [0xe5, 0x8e, 0x26].pread(0).unwrap();
+/// assert_eq!(to_vec(n, ()).unwrap(), [0xe5, 0x8e, 0x26]);
+/// ```
+pub fn to_vec<N, Ctx>(n: N, ctx: Ctx) -> error::Result<Vec<u8>>
+    where N: TryIntoCtx<Ctx, Error = error::Error> + Clone,
+          Ctx: Copy,
+{
+    let mut bytes = Vec::new();
+    let size = write_growing(&mut bytes, n, ctx, Counter::DEFAULT_LIMIT)?;
+    bytes.truncate(size);
+    Ok(bytes)
+}
+
+/// Writes `n` with the given `ctx` into a new `Vec` of its [SizeWith](ctx/trait.SizeWith.html) size, allocated once,
+/// without the retries of [to_vec](fn.to_vec.html); `n` needn't be `Clone`
+///
+/// # Example
+/// ```rust
+/// use scroll::{to_vec_sized, LE};
+///
+/// assert_eq!(to_vec_sized(0xdeadbeefu32, LE).unwrap(), [0xef, 0xbe, 0xad, 0xde]);
+/// ```
+pub fn to_vec_sized<N, Ctx>(n: N, ctx: Ctx) -> error::Result<Vec<u8>>
+    where N: TryIntoCtx<Ctx, Error = error::Error> + SizeWith<Ctx>,
+          Ctx: Copy,
+{
+    let size = N::size_with(&ctx);
+    let mut bytes = crate::buffer::try_vec(size)?;
+    bytes.resize(size, 0);
+    let size = n.try_into_ctx(&mut bytes, ctx)?;
+    bytes.truncate(size);
+    Ok(bytes)
+}
@@ -44,13 +44,37 @@
 //! ```
 
 use core::ptr::copy_nonoverlapping;
-use core::mem::transmute;
 use core::mem::size_of;
 use core::str;
 use core::result;
+use core::marker::PhantomData;
+use core::fmt;
+use core::convert::TryFrom;
 
+#[cfg(feature = "alloc")]
+use core::ffi::CStr;
+#[cfg(feature = "alloc")]
+use alloc::ffi::CString;
 #[cfg(feature = "std")]
-use std::ffi::{CStr, CString};
+use std::ffi::{OsStr, OsString};
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "std")]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+#[cfg(feature = "alloc")]
+use alloc::rc::Rc;
+#[cfg(feature = "alloc")]
+use alloc::sync::Arc;
+#[cfg(feature = "alloc")]
+use alloc::collections::BTreeMap;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use core::hash::Hash;
+#[cfg(feature = "alloc")]
+use alloc::borrow::Cow;
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use crate::prelude::*;
 
 use crate::error;
 use crate::endian::Endian;
@@ -78,11 +102,20 @@ impl<Ctx, T: AsRef<[u8]>> MeasureWith<Ctx> for T {
 /// The parsing context for converting a byte sequence to a `&str`
 ///
 /// `StrCtx` specifies what byte delimiter to use, and defaults to C-style null terminators. Be careful.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum StrCtx {
     Delimiter(u8),
     DelimiterUntil(u8, usize),
     Length(usize),
+    /// A fixed-width field of `len` bytes, padded at the end with `pad` (e.g., tar or ISO9660 names)
+    ///
+    /// Reading always consumes the full `len` bytes and strips the trailing `pad` bytes; writing fills the rest of
+    /// the field with `pad`, and errors if the string does not fit.
+    Fixed { len: usize, pad: u8 },
+    /// The length of the string as an unsigned integer `width` bytes wide (1, 2, 4 or 8), followed by the string itself
+    ///
+    /// Writing errors if the length does not fit in the prefix.
+    Prefixed { width: usize, endian: Endian },
 }
 
 /// A C-style, null terminator based delimiter
@@ -106,12 +139,153 @@ impl StrCtx {
         match *self {
             StrCtx::Delimiter(_) |
             StrCtx::DelimiterUntil(_, _) => 1,
-            StrCtx::Length(_) => 0,
+            StrCtx::Prefixed { width, .. } => width,
+            StrCtx::Length(_) |
+            StrCtx::Fixed { .. } => 0,
         }
     }
 
     pub fn is_empty(&self) -> bool {
-        if let StrCtx::Length(_) = *self { true } else { false }
+        matches!(*self, StrCtx::Length(_) | StrCtx::Fixed { .. })
+    }
+
+    /// Splits the bytes of a string off the front of `src`, returning them along with the number of bytes consumed
+    pub(crate) fn frame(self, src: &[u8]) -> error::Result<(&[u8], usize)> {
+        let len = match self {
+            StrCtx::Fixed { len, pad } => {
+                if len > src.len() {
+                    return Err(error::Error::Incomplete{needed: len - src.len()});
+                };
+                let field = &src[..len];
+                let trimmed = field.iter().rposition(|c| *c != pad).map_or(0, |last| last + 1);
+                return Ok((&field[..trimmed], len));
+            },
+            StrCtx::Prefixed { width, endian } => {
+                let len = read_prefix(src, width, endian)?;
+                let rest = &src[width..];
+                if len > rest.len() as u64 {
+                    return Err(error::Error::Incomplete{needed: (len - rest.len() as u64) as usize});
+                };
+                let len = len as usize;
+                return Ok((&rest[..len], width + len));
+            },
+            StrCtx::Length(len) => len,
+            StrCtx::Delimiter(delimiter) => {
+                if src.is_empty() {
+                    return Err(error::Error::Incomplete{needed: 1});
+                };
+                src.iter().take_while(|c| **c != delimiter).count()
+            },
+            StrCtx::DelimiterUntil(delimiter, len) => {
+                if len > src.len() {
+                    return Err(error::Error::Incomplete{needed: len - src.len()});
+                };
+                src
+                    .iter()
+                    .take_while(|c| **c != delimiter)
+                    .take(len)
+                    .count()
+            }
+        };
+
+        if len > src.len() {
+            return Err(error::Error::Incomplete{needed: len - src.len()});
+        };
+        Ok((&src[..len], len + self.len()))
+    }
+
+    /// Writes the already encoded `bytes` of a string into `dst`, framed as this context describes
+    pub(crate) fn write_framed(self, bytes: &[u8], dst: &mut [u8]) -> error::Result<usize> {
+        match self {
+            StrCtx::Fixed { len, pad } => {
+                if bytes.len() > len {
+                    return Err(error::Error::TooBig{size: bytes.len(), len});
+                }
+                if len > dst.len() {
+                    return Err(error::Error::TooBig{size: len, len: dst.len()});
+                }
+                dst[..bytes.len()].copy_from_slice(bytes);
+                for b in &mut dst[bytes.len()..len] {
+                    *b = pad;
+                }
+                Ok(len)
+            },
+            StrCtx::Length(len) => {
+                if bytes.len() != len {
+                    return Err(error::Error::BadInput{size: bytes.len(), msg: "string length does not match StrCtx::Length"});
+                }
+                TryIntoCtx::try_into_ctx(bytes, dst, ())
+            },
+            StrCtx::Prefixed { width, endian } => {
+                if width + bytes.len() > dst.len() {
+                    return Err(error::Error::TooBig{size: width + bytes.len(), len: dst.len()});
+                }
+                write_prefix(dst, width, endian, bytes.len())?;
+                dst[width..width + bytes.len()].copy_from_slice(bytes);
+                Ok(width + bytes.len())
+            },
+            StrCtx::Delimiter(delimiter) |
+            StrCtx::DelimiterUntil(delimiter, _) => {
+                if let StrCtx::DelimiterUntil(_, len) = self {
+                    if bytes.len() > len {
+                        return Err(error::Error::TooBig{size: bytes.len(), len});
+                    }
+                }
+                // it would end the string early when read back
+                if bytes.contains(&delimiter) {
+                    return Err(error::Error::BadInput{size: bytes.len(), msg: "string contains its delimiter"});
+                }
+                if bytes.len() >= dst.len() {
+                    return Err(error::Error::TooBig{size: bytes.len() + 1, len: dst.len()});
+                }
+                dst[..bytes.len()].copy_from_slice(bytes);
+                dst[bytes.len()] = delimiter;
+                Ok(bytes.len() + 1)
+            },
+        }
+    }
+}
+
+/// Reads the length prefix of a `StrCtx::Prefixed` string
+fn read_prefix(src: &[u8], width: usize, endian: Endian) -> error::Result<u64> {
+    Ok(match width {
+        1 => u64::from(u8::try_from_ctx(src, endian)?.0),
+        2 => u64::from(u16::try_from_ctx(src, endian)?.0),
+        4 => u64::from(u32::try_from_ctx(src, endian)?.0),
+        8 => u64::try_from_ctx(src, endian)?.0,
+        _ => return Err(error::Error::BadInput{size: width, msg: "unsupported length prefix width"}),
+    })
+}
+
+/// Writes the length prefix of a `StrCtx::Prefixed` string
+fn write_prefix(dst: &mut [u8], width: usize, endian: Endian, len: usize) -> error::Result<usize> {
+    let too_long = || error::Error::BadInput{size: len, msg: "string length does not fit in its prefix"};
+    match width {
+        1 => u8::try_from(len).map_err(|_| too_long())?.try_into_ctx(dst, endian),
+        2 => u16::try_from(len).map_err(|_| too_long())?.try_into_ctx(dst, endian),
+        4 => u32::try_from(len).map_err(|_| too_long())?.try_into_ctx(dst, endian),
+        8 => (len as u64).try_into_ctx(dst, endian),
+        _ => Err(error::Error::BadInput{size: width, msg: "unsupported length prefix width"}),
+    }
+}
+
+/// How [gread_line_with](../trait.PreadExt.html#tymethod.gread_line_with) splits a line of text off the front of the
+/// source, e.g., a PPM header before its pixels
+///
+/// The default strips a `\r` before the `\n`, and returns a last line without a `\n` as it is.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LineCtx {
+    /// Whether a `\r` just before the `\n` is stripped too, for CRLF line endings
+    pub strip_cr: bool,
+    /// Whether a last line which ends with the source, rather than a `\n`, is returned; if not, it's an
+    /// `Error::Incomplete`
+    pub unterminated: bool,
+}
+
+impl Default for LineCtx {
+    #[inline]
+    fn default() -> Self {
+        LineCtx { strip_cr: true, unterminated: true }
     }
 }
 
@@ -126,6 +300,56 @@ pub trait TryFromCtx<'a, Ctx: Copy = (), This: ?Sized = [u8]> where Self: 'a + S
     fn try_from_ctx(from: &'a This, ctx: Ctx) -> Result<(Self, usize), Self::Error>;
 }
 
+/// Tries to read `This` using the context `Ctx` into an existing `Self`, returning the number of bytes consumed
+///
+/// Every `TryFromCtx` type gets this for free, by reading a new value and overwriting `self` with it. Types which
+/// own allocations, e.g., a record with a `Vec` or `String` field, can implement this _instead of_ `TryFromCtx`, and
+/// reuse those allocations across reads; see [Pread::pread_inout_struct](../trait.Pread.html#method.pread_inout_struct).
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx, Pread, Endian};
+///
+/// struct Record {
+///     name: Vec<u8>,
+/// }
+///
+/// impl<'a> ctx::TryFromCtxMut<'a, Endian> for Record {
+///     type Error = scroll::Error;
+///     fn try_update_from_ctx(&mut self, src: &'a [u8], le: Endian) -> Result<usize, Self::Error> {
+///         let offset = &mut 0;
+///         let len: u8 = src.gread_with(offset, le)?;
+///         let name: &[u8] = src.gread_with(offset, len as usize)?;
+///         self.name.clear();
+///         self.name.extend_from_slice(name);
+///         Ok(*offset)
+///     }
+/// }
+///
+/// let mut record = Record { name: Vec::with_capacity(16) };
+/// let bytes = [2, b'h', b'i', 3, b'y', b'o', b'u'];
+/// let offset = &mut 0;
+/// bytes.gread_inout_struct(offset, &mut record, scroll::LE).unwrap();
+/// assert_eq!(record.name, b"hi");
+/// bytes.gread_inout_struct(offset, &mut record, scroll::LE).unwrap();
+/// assert_eq!(record.name, b"you");
+/// assert_eq!(record.name.capacity(), 16);
+/// ```
+pub trait TryFromCtxMut<'a, Ctx: Copy = (), This: ?Sized = [u8]> {
+    type Error;
+    fn try_update_from_ctx(&mut self, from: &'a This, ctx: Ctx) -> Result<usize, Self::Error>;
+}
+
+impl<'a, T, Ctx: Copy, This: ?Sized> TryFromCtxMut<'a, Ctx, This> for T where T: TryFromCtx<'a, Ctx, This> {
+    type Error = T::Error;
+    #[inline]
+    fn try_update_from_ctx(&mut self, from: &'a This, ctx: Ctx) -> Result<usize, Self::Error> {
+        let (value, size) = T::try_from_ctx(from, ctx)?;
+        *self = value;
+        Ok(size)
+    }
+}
+
 /// Writes `Self` into `This` using the context `Ctx`
 pub trait IntoCtx<Ctx: Copy = (), This: ?Sized = [u8]>: Sized {
     fn into_ctx(self, _: &mut This, ctx: Ctx);
@@ -147,29 +371,233 @@ pub trait SizeWith<Ctx = ()> {
     fn size_with(ctx: &Ctx) -> usize;
 }
 
-macro_rules! signed_to_unsigned {
-    (i8) =>  {u8 };
-    (u8) =>  {u8 };
-    (i16) => {u16};
-    (u16) => {u16};
-    (i32) => {u32};
-    (u32) => {u32};
-    (i64) => {u64};
-    (u64) => {u64};
-    (i128) => {u128};
-    (u128) => {u128};
-    (f32) => {u32};
-    (f64) => {u64};
+/// A type whose encoded size is the same in every context, and known at compile time, e.g., to stride through a table
+/// of records or size a scratch buffer in a `const`
+///
+/// It is implemented for the fixed width numbers (but not `usize` and `isize`), arrays of `ConstSize` types, and the
+/// fixed size [types](../types/index.html); `#[derive(ConstSize)]` implements it for structs of them, along with an
+/// inherent `SIZE` constant.
+///
+/// # Example
+/// ```rust
+/// use scroll::ctx::ConstSize;
+///
+/// const _: () = assert!(<[u32; 4]>::SIZE == 16);
+/// let scratch = [0u8; <[u16; 3]>::SIZE];
+/// assert_eq!(scratch.len(), 6);
+/// ```
+pub trait ConstSize {
+    /// The number of bytes `Self` is encoded in
+    const SIZE: usize;
+}
+
+macro_rules! const_size_impl {
+    ($($ty:ty),*) => {
+        $(
+            impl ConstSize for $ty {
+                const SIZE: usize = core::mem::size_of::<$ty>();
+            }
+        )*
+    }
+}
+
+const_size_impl!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, f32, f64);
+
+impl<T: ConstSize, const N: usize> ConstSize for [T; N] {
+    const SIZE: usize = N * T::SIZE;
 }
 
-macro_rules! write_into {
-    ($typ:ty, $size:expr, $n:expr, $dst:expr, $endian:expr) => ({
-        unsafe {
-            assert!($dst.len() >= $size);
-            let bytes = transmute::<$typ, [u8; $size]>(if $endian.is_little() { $n.to_le() } else { $n.to_be() });
-            copy_nonoverlapping((&bytes).as_ptr(), $dst.as_mut_ptr(), $size);
+/// Checks the invariants of a freshly parsed value which its `TryFromCtx` can't, e.g. ones spanning several fields, like `header.size >= header.header_len`
+///
+/// Implementing it doesn't change `pread`; instead, [Pread::pread_validated](../trait.Pread.html#method.pread_validated) and
+/// `gread_validated` call it after a successful parse, as does reading a [Validated](struct.Validated.html) value.
+/// The failure message is reported as an `Error::Invalid`, along with the offset the value was parsed at.
+pub trait Validate<Ctx = ()> {
+    /// Returns a description of the first invariant `self` violates, if any
+    fn validate(&self, ctx: &Ctx) -> Result<(), &'static str>;
+}
+
+/// The context a type is read and written with when none is given, for
+/// [Pread::pread_into](../trait.Pread.html#method.pread_into) and friends
+///
+/// Unlike `Ctx: Default`, which `pread` relies on, this belongs to the value being read rather than to the context, so
+/// a type can pick its own, e.g., a big endian header, and the context type needn't implement `Default` at all.
+/// The numeric primitives default to `Endian::default()`, and `#[derive(Pread)]` structs opt in with
+/// `#[scroll(default_ctx = "BE")]`.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx, Pread, Endian, BE};
+///
+/// struct Header { magic: u16, len: u16 }
+///
+/// impl<'a> ctx::TryFromCtx<'a, Endian> for Header {
+///     type Error = scroll::Error;
+///     fn try_from_ctx(src: &'a [u8], endian: Endian) -> Result<(Self, usize), Self::Error> {
+///         let offset = &mut 0;
+///         Ok((Header { magic: src.gread_with(offset, endian)?, len: src.gread_with(offset, endian)? }, *offset))
+///     }
+/// }
+///
+/// impl ctx::DefaultCtx for Header {
+///     type Ctx = Endian;
+///     fn default_ctx() -> Endian { BE }
+/// }
+///
+/// let header: Header = [0xca, 0xfe, 0x00, 0x10].pread_into(0).unwrap();
+/// assert_eq!((header.magic, header.len), (0xcafe, 0x10));
+/// ```
+pub trait DefaultCtx {
+    type Ctx: Copy;
+    fn default_ctx() -> Self::Ctx;
+}
+
+/// A fixed width value whose bytes are those of a number in the given byte order, e.g., the primitive numbers, and
+/// transparent newtypes over them
+///
+/// The primitive contexts of the numbers are built on it, and the [endian_newtype](../macro.endian_newtype.html) macro
+/// gives a newtype the same impls, and this trait, from the number it wraps.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx::EndianConvert, BE, LE};
+///
+/// assert_eq!(0xdeadbeefu32.to_bytes(BE), [0xde, 0xad, 0xbe, 0xef]);
+/// assert_eq!(u16::from_bytes([0x01, 0x02], LE), 0x0201);
+/// assert!(matches!(u16::read_from(&[0x01], LE), Err(scroll::Error::Incomplete { needed: 1 })));
+/// ```
+pub trait EndianConvert: Copy {
+    /// The value's bytes, `[u8; N]` for an `N` byte value
+    type Bytes: AsRef<[u8]> + AsMut<[u8]> + Default + Copy;
+    /// The value whose bytes in the byte order `endian` are `bytes`
+    fn from_bytes(bytes: Self::Bytes, endian: Endian) -> Self;
+    /// The bytes of `self` in the byte order `endian`
+    fn to_bytes(self, endian: Endian) -> Self::Bytes;
+    /// Reads the value from the start of `src`, returning it and its size
+    #[inline]
+    fn read_from(src: &[u8], endian: Endian) -> error::Result<(Self, usize)> {
+        let mut bytes = Self::Bytes::default();
+        let size = bytes.as_ref().len();
+        if size > src.len() {
+            return Err(error::Error::Incomplete { needed: size - src.len() });
+        }
+        bytes.as_mut().copy_from_slice(&src[..size]);
+        Ok((Self::from_bytes(bytes, endian), size))
+    }
+    /// Writes the value to the start of `dst`, returning its size
+    #[inline]
+    fn write_to(self, dst: &mut [u8], endian: Endian) -> error::Result<usize> {
+        let bytes = self.to_bytes(endian);
+        let size = bytes.as_ref().len();
+        if size > dst.len() {
+            return Err(error::Error::TooBig { size, len: dst.len() });
         }
-    });
+        dst[..size].copy_from_slice(bytes.as_ref());
+        Ok(size)
+    }
+}
+
+/// Implements [EndianConvert](ctx/trait.EndianConvert.html), and reading and writing with an `Endian` context, for each
+/// transparent `Copy` newtype `Name(Number)` from the number it wraps, so it preads, pwrites, ioreads and iowrites
+/// exactly like the number
+///
+/// # Example
+/// ```rust
+/// use scroll::{Pread, Pwrite, BE, LE};
+///
+/// #[derive(Debug, Copy, Clone, PartialEq)]
+/// struct Rva(u32);
+/// #[derive(Debug, Copy, Clone, PartialEq)]
+/// struct FileOffset(u64);
+/// scroll::endian_newtype!(Rva(u32), FileOffset(u64));
+///
+/// let mut bytes = [0u8; 12];
+/// bytes.pwrite_with(Rva(0x1000), 0, BE).unwrap();
+/// bytes.pwrite_with(FileOffset(0x400), 4, LE).unwrap();
+/// assert_eq!(bytes.pread_with::<Rva>(0, BE).unwrap(), Rva(0x1000));
+/// assert_eq!(bytes.pread_with::<FileOffset>(4, LE).unwrap(), FileOffset(0x400));
+/// ```
+#[macro_export]
+macro_rules! endian_newtype {
+    ($($ty:ident($inner:ty)),* $(,)?) => {
+        $(
+            impl $crate::ctx::EndianConvert for $ty {
+                type Bytes = <$inner as $crate::ctx::EndianConvert>::Bytes;
+                #[inline]
+                fn from_bytes(bytes: Self::Bytes, endian: $crate::Endian) -> Self {
+                    $ty(<$inner as $crate::ctx::EndianConvert>::from_bytes(bytes, endian))
+                }
+                #[inline]
+                fn to_bytes(self, endian: $crate::Endian) -> Self::Bytes {
+                    $crate::ctx::EndianConvert::to_bytes(self.0, endian)
+                }
+            }
+
+            impl<'a> $crate::ctx::TryFromCtx<'a, $crate::Endian> for $ty {
+                type Error = $crate::Error;
+                #[inline]
+                fn try_from_ctx(src: &'a [u8], le: $crate::Endian) -> $crate::export::result::Result<(Self, usize), Self::Error> {
+                    <$ty as $crate::ctx::EndianConvert>::read_from(src, le)
+                }
+            }
+
+            impl $crate::ctx::FromCtx<$crate::Endian> for $ty {
+                #[inline]
+                fn from_ctx(src: &[u8], le: $crate::Endian) -> Self {
+                    $ty(<$inner as $crate::ctx::FromCtx<$crate::Endian>>::from_ctx(src, le))
+                }
+            }
+
+            impl $crate::ctx::TryIntoCtx<$crate::Endian> for $ty {
+                type Error = $crate::Error;
+                #[inline]
+                fn try_into_ctx(self, dst: &mut [u8], le: $crate::Endian) -> $crate::export::result::Result<usize, Self::Error> {
+                    $crate::ctx::EndianConvert::write_to(self, dst, le)
+                }
+            }
+
+            impl<'t> $crate::ctx::TryIntoCtx<$crate::Endian> for &'t $ty {
+                type Error = $crate::Error;
+                #[inline]
+                fn try_into_ctx(self, dst: &mut [u8], le: $crate::Endian) -> $crate::export::result::Result<usize, Self::Error> {
+                    $crate::ctx::EndianConvert::write_to(*self, dst, le)
+                }
+            }
+
+            impl $crate::ctx::IntoCtx<$crate::Endian> for $ty {
+                #[inline]
+                fn into_ctx(self, dst: &mut [u8], le: $crate::Endian) {
+                    $crate::ctx::IntoCtx::into_ctx(self.0, dst, le)
+                }
+            }
+
+            impl<'t> $crate::ctx::IntoCtx<$crate::Endian> for &'t $ty {
+                #[inline]
+                fn into_ctx(self, dst: &mut [u8], le: $crate::Endian) {
+                    $crate::ctx::IntoCtx::into_ctx(self.0, dst, le)
+                }
+            }
+
+            impl $crate::ctx::SizeWith<$crate::Endian> for $ty {
+                #[inline]
+                fn size_with(_ctx: &$crate::Endian) -> usize {
+                    $crate::export::mem::size_of::<$inner>()
+                }
+            }
+
+            impl $crate::ctx::ConstSize for $ty {
+                const SIZE: usize = <$inner as $crate::ctx::ConstSize>::SIZE;
+            }
+
+            impl $crate::ctx::DefaultCtx for $ty {
+                type Ctx = $crate::Endian;
+                #[inline]
+                fn default_ctx() -> $crate::Endian {
+                    <$inner as $crate::ctx::DefaultCtx>::default_ctx()
+                }
+            }
+        )*
+    }
 }
 
 macro_rules! into_ctx_impl {
@@ -178,7 +606,7 @@ macro_rules! into_ctx_impl {
             #[inline]
             fn into_ctx(self, dst: &mut [u8], le: Endian) {
                 assert!(dst.len() >= $size);
-                write_into!($typ, $size, self, dst, le);
+                dst[..$size].copy_from_slice(&self.to_bytes(le));
             }
         }
         impl<'a> IntoCtx<Endian> for &'a $typ {
@@ -191,12 +619,7 @@ macro_rules! into_ctx_impl {
             type Error = error::Error;
             #[inline]
             fn try_into_ctx(self, dst: &mut [u8], le: Endian) -> error::Result<usize> {
-                if $size > dst.len () {
-                    Err(error::Error::TooBig{size: $size, len: dst.len()})
-                } else {
-                    <$typ as IntoCtx<Endian>>::into_ctx(self, dst, le);
-                    Ok($size)
-                }
+                self.write_to(dst, le)
             }
         }
         impl<'a> TryIntoCtx<Endian> for &'a $typ {
@@ -215,14 +638,9 @@ macro_rules! from_ctx_impl {
             #[inline]
             fn from_ctx(src: &[u8], le: Endian) -> Self {
                 assert!(src.len() >= $size);
-                let mut data: signed_to_unsigned!($typ) = 0;
-                unsafe {
-                    copy_nonoverlapping(
-                        src.as_ptr(),
-                        &mut data as *mut signed_to_unsigned!($typ) as *mut u8,
-                        $size);
-                }
-                (if le.is_little() { data.to_le() } else { data.to_be() }) as $typ
+                let mut bytes = [0u8; $size];
+                bytes.copy_from_slice(&src[..$size]);
+                <$typ as EndianConvert>::from_bytes(bytes, le)
             }
         }
 
@@ -230,27 +648,20 @@ macro_rules! from_ctx_impl {
             type Error = error::Error;
             #[inline]
             fn try_from_ctx(src: &'a [u8], le: Endian) -> result::Result<(Self, usize), Self::Error> {
-                if $size > src.len () {
-                    Err(error::Error::TooBig{size: $size, len: src.len()})
-                } else {
-                    Ok((FromCtx::from_ctx(&src, le), $size))
-                }
+                <$typ as EndianConvert>::read_from(src, le)
             }
         }
+    };
+}
+
+macro_rules! ctx_impl {
+    ($typ:tt, $size:expr) => {
+        from_ctx_impl!($typ, $size);
         // as ref
         impl<'a, T> FromCtx<Endian, T> for $typ where T: AsRef<[u8]> {
             #[inline]
             fn from_ctx(src: &T, le: Endian) -> Self {
-                let src = src.as_ref();
-                assert!(src.len() >= $size);
-                let mut data: signed_to_unsigned!($typ) = 0;
-                unsafe {
-                    copy_nonoverlapping(
-                        src.as_ptr(),
-                        &mut data as *mut signed_to_unsigned!($typ) as *mut u8,
-                        $size);
-                }
-                (if le.is_little() { data.to_le() } else { data.to_be() }) as $typ
+                <$typ as FromCtx<Endian>>::from_ctx(src.as_ref(), le)
             }
         }
 
@@ -262,12 +673,6 @@ macro_rules! from_ctx_impl {
                 Self::try_from_ctx(src, le)
             }
         }
-    };
-}
-
-macro_rules! ctx_impl {
-    ($typ:tt, $size:expr) => {
-        from_ctx_impl!($typ, $size);
      };
 }
 
@@ -282,38 +687,8 @@ ctx_impl!(i64, 8);
 ctx_impl!(u128, 16);
 ctx_impl!(i128, 16);
 
-macro_rules! from_ctx_float_impl {
-    ($typ:tt, $size:expr) => {
-        impl<'a> FromCtx<Endian> for $typ {
-            #[inline]
-            fn from_ctx(src: &[u8], le: Endian) -> Self {
-                assert!(src.len() >= ::core::mem::size_of::<Self>());
-                let mut data: signed_to_unsigned!($typ) = 0;
-                unsafe {
-                    copy_nonoverlapping(
-                        src.as_ptr(),
-                        &mut data as *mut signed_to_unsigned!($typ) as *mut u8,
-                        $size);
-                    transmute(if le.is_little() { data.to_le() } else { data.to_be() })
-                }
-            }
-        }
-        impl<'a> TryFromCtx<'a, Endian> for $typ where $typ: FromCtx<Endian> {
-            type Error = error::Error;
-            #[inline]
-            fn try_from_ctx(src: &'a [u8], le: Endian) -> result::Result<(Self, usize), Self::Error> {
-                if $size > src.len () {
-                    Err(error::Error::TooBig{size: $size, len: src.len()})
-                } else {
-                    Ok((FromCtx::from_ctx(src, le), $size))
-                }
-            }
-        }
-    }
-}
-
-from_ctx_float_impl!(f32, 4);
-from_ctx_float_impl!(f64, 8);
+from_ctx_impl!(f32, 4);
+from_ctx_impl!(f64, 8);
 
 into_ctx_impl!(u8,  1);
 into_ctx_impl!(i8,  1);
@@ -325,73 +700,17 @@ into_ctx_impl!(u64, 8);
 into_ctx_impl!(i64, 8);
 into_ctx_impl!(u128, 16);
 into_ctx_impl!(i128, 16);
-
-macro_rules! into_ctx_float_impl {
-    ($typ:tt, $size:expr) => {
-        impl IntoCtx<Endian> for $typ {
-            #[inline]
-            fn into_ctx(self, dst: &mut [u8], le: Endian) {
-                assert!(dst.len() >= $size);
-                write_into!(signed_to_unsigned!($typ), $size, transmute::<$typ, signed_to_unsigned!($typ)>(self), dst, le);
-            }
-        }
-        impl<'a> IntoCtx<Endian> for &'a $typ {
-            #[inline]
-            fn into_ctx(self, dst: &mut [u8], le: Endian) {
-                (*self).into_ctx(dst, le)
-            }
-        }
-        impl TryIntoCtx<Endian> for $typ where $typ: IntoCtx<Endian> {
-            type Error = error::Error;
-            #[inline]
-            fn try_into_ctx(self, dst: &mut [u8], le: Endian) -> error::Result<usize> {
-                if $size > dst.len () {
-                    Err(error::Error::TooBig{size: $size, len: dst.len()})
-                } else {
-                    <$typ as IntoCtx<Endian>>::into_ctx(self, dst, le);
-                    Ok($size)
-                }
-            }
-        }
-        impl<'a> TryIntoCtx<Endian> for &'a $typ {
-            type Error = error::Error;
-            #[inline]
-            fn try_into_ctx(self, dst: &mut [u8], le: Endian) -> error::Result<usize> {
-                (*self).try_into_ctx(dst, le)
-            }
-        }
-    }
-}
-
-into_ctx_float_impl!(f32, 4);
-into_ctx_float_impl!(f64, 8);
+into_ctx_impl!(f32, 4);
+into_ctx_impl!(f64, 8);
 
 impl<'a> TryFromCtx<'a, StrCtx> for &'a str {
     type Error = error::Error;
     #[inline]
     /// Read a `&str` from `src` using `delimiter`
     fn try_from_ctx(src: &'a [u8], ctx: StrCtx) -> Result<(Self, usize), Self::Error> {
-        let len = match ctx {
-            StrCtx::Length(len) => len,
-            StrCtx::Delimiter(delimiter) => src.iter().take_while(|c| **c != delimiter).count(),
-            StrCtx::DelimiterUntil(delimiter, len) => {
-                if len > src.len() {
-                    return Err(error::Error::TooBig{size: len, len: src.len()});
-                };
-                src
-                    .iter()
-                    .take_while(|c| **c != delimiter)
-                    .take(len)
-                    .count()
-            }
-        };
-
-        if len > src.len() {
-            return Err(error::Error::TooBig{size: len, len: src.len()});
-        };
-
-        match str::from_utf8(&src[..len]) {
-            Ok(res) => Ok((res, len + ctx.len())),
+        let (bytes, size) = ctx.frame(src)?;
+        match str::from_utf8(bytes) {
+            Ok(res) => Ok((res, size)),
             Err(_) => Err(error::Error::BadInput{size: src.len(), msg: "invalid utf8"})
         }
     }
@@ -406,7 +725,7 @@ impl<'a, T> TryFromCtx<'a, StrCtx, T> for &'a str where T: AsRef<[u8]> {
     }
 }
 
-impl<'a> TryIntoCtx for &'a [u8] {
+impl TryIntoCtx for &[u8] {
     type Error = error::Error;
     #[inline]
     fn try_into_ctx(self, dst: &mut [u8], _ctx: ()) -> error::Result<usize> {
@@ -424,8 +743,7 @@ impl<'a> TryIntoCtx for &'a [u8] {
     }
 }
 
-// TODO: make TryIntoCtx use StrCtx for awesomeness
-impl<'a> TryIntoCtx for &'a str {
+impl TryIntoCtx for &str {
     type Error = error::Error;
     #[inline]
     fn try_into_ctx(self, dst: &mut [u8], _ctx: ()) -> error::Result<usize> {
@@ -434,6 +752,58 @@ impl<'a> TryIntoCtx for &'a str {
     }
 }
 
+/// A `&str` to be written with a [StrCtx](enum.StrCtx.html), the mirror image of reading a `&str` with one
+///
+/// `&str` itself only writes with the `()` context, so that `pwrite` without a context keeps inferring which impl to
+/// use; wrap it in `Str` to have the delimiter appended, or a fixed-width field padded.
+///
+/// # Example
+/// ```rust
+/// use scroll::{Pread, Pwrite, ctx::{Str, StrCtx}};
+///
+/// let mut bytes = [0xffu8; 8];
+/// let ctx = StrCtx::Fixed { len: 8, pad: 0 };
+/// assert_eq!(bytes.pwrite_with(Str("hello"), 0, ctx).unwrap(), 8);
+/// assert_eq!(&bytes, b"hello\0\0\0");
+/// assert_eq!(bytes.pread_with::<&str>(0, ctx).unwrap(), "hello");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Str<'a>(pub &'a str);
+
+impl<'a> From<&'a str> for Str<'a> {
+    #[inline]
+    fn from(s: &'a str) -> Self {
+        Str(s)
+    }
+}
+
+impl<'a> TryIntoCtx<StrCtx> for Str<'a> {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], ctx: StrCtx) -> error::Result<usize> {
+        ctx.write_framed(self.0.as_bytes(), dst)
+    }
+}
+
+/// Writes the string with the [StrCtx](enum.StrCtx.html), just like [Str](struct.Str.html) does
+#[cfg(feature = "alloc")]
+impl TryIntoCtx<StrCtx> for String {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], ctx: StrCtx) -> error::Result<usize> {
+        Str(&self).try_into_ctx(dst, ctx)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl TryIntoCtx<StrCtx> for &String {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], ctx: StrCtx) -> error::Result<usize> {
+        Str(self).try_into_ctx(dst, ctx)
+    }
+}
+
 // TODO: we can make this compile time without size_of call, but compiler probably does that anyway
 macro_rules! sizeof_impl {
     ($ty:ty) => {
@@ -461,6 +831,62 @@ sizeof_impl!(f64);
 sizeof_impl!(usize);
 sizeof_impl!(isize);
 
+macro_rules! default_ctx_impl {
+    ($($ty:ty),*) => {
+        $(
+            impl DefaultCtx for $ty {
+                type Ctx = Endian;
+                #[inline]
+                fn default_ctx() -> Endian {
+                    Endian::default()
+                }
+            }
+        )*
+    }
+}
+
+default_ctx_impl!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, f32, f64, usize, isize);
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// A fixed width number whose representation in memory is exactly its bytes in the machine's byte order, so that a slice
+/// of them can be written or read in bulk; see [PwriteExt::pwrite_slice_with](../trait.PwriteExt.html#tymethod.pwrite_slice_with)
+/// and [PreadExt::pread_inout_endian](../trait.PreadExt.html#tymethod.pread_inout_endian)
+///
+/// It is sealed, and implemented for the integers, except `usize` and `isize`, and the floats, which are also where
+/// [EndianConvert](trait.EndianConvert.html) is implemented for the numbers.
+pub trait Primitive: EndianConvert + SizeWith<Endian> + private::Sealed {
+    /// `self` with the order of its bytes reversed
+    #[inline]
+    fn swap_bytes(self) -> Self {
+        Self::from_bytes(self.to_bytes(Endian::Little), Endian::Big)
+    }
+}
+
+macro_rules! primitive_impl {
+    ($($ty:ty),*) => {
+        $(
+            impl EndianConvert for $ty {
+                type Bytes = [u8; size_of::<$ty>()];
+                #[inline]
+                fn from_bytes(bytes: Self::Bytes, endian: Endian) -> Self {
+                    if endian.is_little() { <$ty>::from_le_bytes(bytes) } else { <$ty>::from_be_bytes(bytes) }
+                }
+                #[inline]
+                fn to_bytes(self, endian: Endian) -> Self::Bytes {
+                    if endian.is_little() { self.to_le_bytes() } else { self.to_be_bytes() }
+                }
+            }
+            impl private::Sealed for $ty {}
+            impl Primitive for $ty {}
+        )*
+    }
+}
+
+primitive_impl!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, f32, f64);
+
 impl FromCtx<Endian> for usize {
     #[inline]
     fn from_ctx(src: &[u8], le: Endian) -> Self {
@@ -483,7 +909,7 @@ impl<'a> TryFromCtx<'a, Endian> for usize where usize: FromCtx<Endian> {
     fn try_from_ctx(src: &'a [u8], le: Endian) -> result::Result<(Self, usize), Self::Error> {
         let size = ::core::mem::size_of::<usize>();
         if size > src.len () {
-            Err(error::Error::TooBig{size, len: src.len()})
+            Err(error::Error::Incomplete{needed: size - src.len()})
         } else {
             Ok((FromCtx::from_ctx(src, le), size))
         }
@@ -495,7 +921,7 @@ impl<'a> TryFromCtx<'a, usize> for &'a[u8] {
     #[inline]
     fn try_from_ctx(src: &'a [u8], size: usize) -> result::Result<(Self, usize), Self::Error> {
         if size > src.len () {
-            Err(error::Error::TooBig{size, len: src.len()})
+            Err(error::Error::Incomplete{needed: size - src.len()})
         } else {
             Ok((&src[..size], size))
         }
@@ -529,7 +955,7 @@ impl TryIntoCtx<Endian> for usize where usize: IntoCtx<Endian> {
     }
 }
 
-#[cfg(feature = "std")]
+#[cfg(feature = "alloc")]
 impl<'a> TryFromCtx<'a> for &'a CStr {
     type Error = error::Error;
     #[inline]
@@ -547,7 +973,7 @@ impl<'a> TryFromCtx<'a> for &'a CStr {
     }
 }
 
-#[cfg(feature = "std")]
+#[cfg(feature = "alloc")]
 impl<'a> TryFromCtx<'a> for CString {
     type Error = error::Error;
     #[inline]
@@ -557,8 +983,8 @@ impl<'a> TryFromCtx<'a> for CString {
     }
 }
 
-#[cfg(feature = "std")]
-impl<'a> TryIntoCtx for &'a CStr {
+#[cfg(feature = "alloc")]
+impl TryIntoCtx for &CStr {
     type Error = error::Error;
     #[inline]
     fn try_into_ctx(self, dst: &mut [u8], _ctx: ()) -> error::Result<usize> {
@@ -579,7 +1005,7 @@ impl<'a> TryIntoCtx for &'a CStr {
     }
 }
 
-#[cfg(feature = "std")]
+#[cfg(feature = "alloc")]
 impl TryIntoCtx for CString {
     type Error = error::Error;
     #[inline]
@@ -588,7 +1014,2864 @@ impl TryIntoCtx for CString {
     }
 }
 
-
+/// The parsing context for a null terminated `OsString`, by how the string is encoded, or for the platform's bytes
+/// framed by a [StrCtx](enum.StrCtx.html); requires the `std` feature
+///
+/// The terminator is consumed, but isn't part of the string; a missing terminator is an `Error::Incomplete`. A
+/// `PathBuf` reads with the same contexts.
+///
+/// # Example
+/// ```rust
+/// use std::ffi::OsString;
+/// use scroll::{Pread, ctx::OsStringCtx};
+///
+/// let bytes = b"/tmp/scroll.sock\0";
+/// let path: OsString = bytes.pread_with(0, OsStringCtx::Null).unwrap();
+/// assert_eq!(path, "/tmp/scroll.sock");
+///
+/// let bytes = b"C\0:\0\\\0\0\0";
+/// let path: OsString = bytes.pread_with(0, OsStringCtx::Utf16LE).unwrap();
+/// assert_eq!(path, "C:\\");
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OsStringCtx {
+    /// UTF-8, terminated by a zero byte; invalid UTF-8 is an error on every platform
+    Utf8,
+    /// UTF-16 code units in little endian, terminated by a zero unit, as Windows stores paths; unpaired surrogates are kept
+    /// on Windows, as `OsStringExt::from_wide` does, and are an error elsewhere
+    Utf16LE,
+    /// The platform's native bytes, terminated by a zero byte; any bytes are kept on Unix, as `OsStringExt::from_vec` does,
+    /// and must be UTF-8 elsewhere
+    Null,
+    /// The platform's native bytes, framed by the `StrCtx`, e.g., a length prefixed file name in an archive; any bytes are
+    /// kept on Unix, and elsewhere invalid UTF-8 is replaced with U+FFFD, as `String::from_utf8_lossy` does
+    Framed(StrCtx),
+}
+
+#[cfg(all(feature = "std", unix))]
+fn os_string_from_bytes(bytes: &[u8]) -> error::Result<OsString> {
+    use std::os::unix::ffi::OsStringExt;
+    Ok(OsString::from_vec(bytes.to_vec()))
+}
+
+#[cfg(all(feature = "std", not(unix)))]
+fn os_string_from_bytes(bytes: &[u8]) -> error::Result<OsString> {
+    os_string_from_utf8(bytes)
+}
+
+#[cfg(feature = "std")]
+fn os_string_from_utf8(bytes: &[u8]) -> error::Result<OsString> {
+    match str::from_utf8(bytes) {
+        Ok(s) => Ok(OsString::from(s)),
+        Err(_) => Err(error::Error::BadInput{size: bytes.len(), msg: "invalid utf8"}),
+    }
+}
+
+#[cfg(all(feature = "std", windows))]
+fn os_string_from_wide(units: &[u16]) -> error::Result<OsString> {
+    use std::os::windows::ffi::OsStringExt;
+    Ok(OsString::from_wide(units))
+}
+
+#[cfg(all(feature = "std", not(windows)))]
+fn os_string_from_wide(units: &[u16]) -> error::Result<OsString> {
+    match String::from_utf16(units) {
+        Ok(s) => Ok(OsString::from(s)),
+        Err(_) => Err(error::Error::BadInput{size: units.len() * 2, msg: "invalid utf16"}),
+    }
+}
+
+#[cfg(all(feature = "std", unix))]
+fn os_str_from_bytes(bytes: &[u8]) -> error::Result<&OsStr> {
+    use std::os::unix::ffi::OsStrExt;
+    Ok(OsStr::from_bytes(bytes))
+}
+
+#[cfg(all(feature = "std", not(unix)))]
+fn os_str_from_bytes(bytes: &[u8]) -> error::Result<&OsStr> {
+    match str::from_utf8(bytes) {
+        Ok(s) => Ok(OsStr::new(s)),
+        Err(_) => Err(error::Error::BadInput{size: bytes.len(), msg: "invalid utf8"}),
+    }
+}
+
+#[cfg(all(feature = "std", unix))]
+fn os_string_from_bytes_lossy(bytes: &[u8]) -> OsString {
+    use std::os::unix::ffi::OsStringExt;
+    OsString::from_vec(bytes.to_vec())
+}
+
+#[cfg(all(feature = "std", not(unix)))]
+fn os_string_from_bytes_lossy(bytes: &[u8]) -> OsString {
+    OsString::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
+#[cfg(all(feature = "std", unix))]
+fn os_str_bytes(s: &OsStr) -> error::Result<&[u8]> {
+    use std::os::unix::ffi::OsStrExt;
+    Ok(s.as_bytes())
+}
+
+#[cfg(all(feature = "std", not(unix)))]
+fn os_str_bytes(s: &OsStr) -> error::Result<&[u8]> {
+    s.to_str().map(str::as_bytes).ok_or(error::Error::BadInput{size: s.len(), msg: "path is not valid unicode"})
+}
+
+#[cfg(feature = "std")]
+impl<'a> TryFromCtx<'a, OsStringCtx> for OsString {
+    type Error = error::Error;
+    fn try_from_ctx(src: &'a [u8], ctx: OsStringCtx) -> result::Result<(Self, usize), Self::Error> {
+        match ctx {
+            OsStringCtx::Framed(ctx) => {
+                let (bytes, size) = ctx.frame(src)?;
+                Ok((os_string_from_bytes_lossy(bytes), size))
+            },
+            OsStringCtx::Utf8 | OsStringCtx::Null => {
+                let len = src.iter().position(|b| *b == 0).ok_or(error::Error::Incomplete{needed: 1})?;
+                let s = if ctx == OsStringCtx::Utf8 { os_string_from_utf8(&src[..len])? } else { os_string_from_bytes(&src[..len])? };
+                Ok((s, len + 1))
+            },
+            OsStringCtx::Utf16LE => {
+                let units: Vec<u16> = src.chunks_exact(2)
+                    .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+                    .take_while(|unit| *unit != 0)
+                    .collect();
+                let size = units.len() * 2 + 2;
+                if size > src.len() {
+                    return Err(error::Error::Incomplete{needed: size - src.len()});
+                }
+                Ok((os_string_from_wide(&units)?, size))
+            },
+        }
+    }
+}
+
+/// Borrows a platform string framed by the [StrCtx](enum.StrCtx.html), e.g., a file name in an archive; requires the
+/// `std` feature
+///
+/// On Unix, any bytes are an `OsStr`, so a name which isn't UTF-8 reads, and writes back, unchanged. Elsewhere an
+/// `OsStr` can't be borrowed from arbitrary bytes, and they must be UTF-8; an owned `OsString` or `PathBuf`, read with
+/// [OsStringCtx::Framed](enum.OsStringCtx.html#variant.Framed), replaces invalid UTF-8 with U+FFFD there instead.
+/// Writing, owned or borrowed, is the reverse: on Unix the bytes are written as they are, and elsewhere a string which
+/// isn't valid unicode is an `Error::BadInput`.
+///
+/// # Example
+/// ```rust
+/// use std::path::{Path, PathBuf};
+/// use scroll::{ctx::{OsStringCtx, StrCtx}, Pread, Pwrite, LE};
+///
+/// let bytes = b"\x09\0docs/a.md";
+/// let ctx = StrCtx::Prefixed { width: 2, endian: LE };
+/// let name: &Path = bytes.pread_with(0, ctx).unwrap();
+/// assert_eq!(name, Path::new("docs/a.md"));
+/// let name: PathBuf = bytes.pread_with(0, OsStringCtx::Framed(ctx)).unwrap();
+///
+/// let mut out = [0u8; 16];
+/// let size = out.pwrite_with(name.with_extension("txt"), 0, StrCtx::Delimiter(0)).unwrap();
+/// assert_eq!(&out[..size], b"docs/a.txt\0");
+/// ```
+#[cfg(feature = "std")]
+impl<'a> TryFromCtx<'a, StrCtx> for &'a OsStr {
+    type Error = error::Error;
+    #[inline]
+    fn try_from_ctx(src: &'a [u8], ctx: StrCtx) -> error::Result<(Self, usize)> {
+        let (bytes, size) = ctx.frame(src)?;
+        Ok((os_str_from_bytes(bytes)?, size))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a> TryFromCtx<'a, StrCtx> for &'a Path {
+    type Error = error::Error;
+    #[inline]
+    fn try_from_ctx(src: &'a [u8], ctx: StrCtx) -> error::Result<(Self, usize)> {
+        let (s, size) = <&OsStr>::try_from_ctx(src, ctx)?;
+        Ok((Path::new(s), size))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a> TryFromCtx<'a, OsStringCtx> for PathBuf {
+    type Error = error::Error;
+    #[inline]
+    fn try_from_ctx(src: &'a [u8], ctx: OsStringCtx) -> error::Result<(Self, usize)> {
+        let (s, size) = OsString::try_from_ctx(src, ctx)?;
+        Ok((PathBuf::from(s), size))
+    }
+}
+
+// every platform string writes its bytes framed by the `StrCtx`, owned or borrowed
+macro_rules! os_str_into_ctx_impl {
+    ($($ty:ty),*) => {
+        $(
+            #[cfg(feature = "std")]
+            impl TryIntoCtx<StrCtx> for $ty {
+                type Error = error::Error;
+                #[inline]
+                fn try_into_ctx(self, dst: &mut [u8], ctx: StrCtx) -> error::Result<usize> {
+                    ctx.write_framed(os_str_bytes(AsRef::<OsStr>::as_ref(&self))?, dst)
+                }
+            }
+        )*
+    }
+}
+
+os_str_into_ctx_impl!(&OsStr, &OsString, OsString, &Path, &PathBuf, PathBuf);
+
+// smart pointers parse a `T` and wrap it, and write through to the `T`, so that e.g. recursive types can hold a `Box<Self>`
+macro_rules! shared_pointer_impl {
+    ($ptr:ident) => {
+        #[cfg(feature = "alloc")]
+        impl<'a, T, Ctx: Copy> TryFromCtx<'a, Ctx> for $ptr<T> where T: TryFromCtx<'a, Ctx> {
+            type Error = T::Error;
+            #[inline]
+            fn try_from_ctx(src: &'a [u8], ctx: Ctx) -> result::Result<(Self, usize), Self::Error> {
+                let (value, size) = T::try_from_ctx(src, ctx)?;
+                Ok(($ptr::new(value), size))
+            }
+        }
+
+        // a shared `T` can't be moved out of, so it is written by reference
+        #[cfg(feature = "alloc")]
+        impl<T, Ctx: Copy, E> TryIntoCtx<Ctx> for $ptr<T> where for<'b> &'b T: TryIntoCtx<Ctx, Error = E> {
+            type Error = E;
+            #[inline]
+            fn try_into_ctx(self, dst: &mut [u8], ctx: Ctx) -> result::Result<usize, Self::Error> {
+                (&*self).try_into_ctx(dst, ctx)
+            }
+        }
+
+        #[cfg(feature = "alloc")]
+        impl<T: SizeWith<Ctx>, Ctx> SizeWith<Ctx> for $ptr<T> {
+            #[inline]
+            fn size_with(ctx: &Ctx) -> usize {
+                T::size_with(ctx)
+            }
+        }
+    }
+}
+
+shared_pointer_impl!(Rc);
+shared_pointer_impl!(Arc);
+
+// `Box` is `#[fundamental]`, so an impl for every context would overlap with the `Tag` impls (a `Box<T>` may be a `Tag`);
+// it forwards the crate's default contexts instead, which are the ones derived types are read and written with
+macro_rules! box_impl {
+    ($ctx:ty) => {
+        #[cfg(feature = "alloc")]
+        impl<'a, T> TryFromCtx<'a, $ctx> for Box<T> where T: TryFromCtx<'a, $ctx> {
+            type Error = T::Error;
+            #[inline]
+            fn try_from_ctx(src: &'a [u8], ctx: $ctx) -> result::Result<(Self, usize), Self::Error> {
+                let (value, size) = T::try_from_ctx(src, ctx)?;
+                Ok((Box::new(value), size))
+            }
+        }
+
+        #[cfg(feature = "alloc")]
+        impl<T> TryIntoCtx<$ctx> for Box<T> where T: TryIntoCtx<$ctx> {
+            type Error = T::Error;
+            #[inline]
+            fn try_into_ctx(self, dst: &mut [u8], ctx: $ctx) -> result::Result<usize, Self::Error> {
+                (*self).try_into_ctx(dst, ctx)
+            }
+        }
+
+        #[cfg(feature = "alloc")]
+        impl<T: SizeWith<$ctx>> SizeWith<$ctx> for Box<T> {
+            #[inline]
+            fn size_with(ctx: &$ctx) -> usize {
+                T::size_with(ctx)
+            }
+        }
+    }
+}
+
+box_impl!(Endian);
+box_impl!(());
+
+
+/// The parsing context for lazily reading `T`s with the context `Ctx`, until the source is exhausted
+///
+/// Reading with an `IterCtx` yields a [TryFromCtxIter](struct.TryFromCtxIter.html) instead of a collection, so nothing is
+/// parsed (or allocated) until the caller drives the iterator.
+///
+/// # Example
+/// ```rust
+/// use scroll::{Pread, LE};
+/// use scroll::ctx::{IterCtx, TryFromCtxIter};
+///
+/// let bytes = [0xef, 0xbe, 0xad, 0xde, 0x01, 0x00];
+/// let iter: TryFromCtxIter<u16, _> = bytes.pread_with(0, IterCtx(LE)).unwrap();
+/// let nums = iter.collect::<Result<Vec<u16>, _>>().unwrap();
+/// assert_eq!(nums, [0xbeef, 0xdead, 0x1]);
+/// ```
+#[derive(Debug, Copy, Clone, Default)]
+pub struct IterCtx<Ctx>(pub Ctx);
+
+/// A lazy iterator which parses one `T` out of its source, with the context `Ctx`, per call to `next`
+///
+/// The iterator returns `None` once the source is exhausted; after a parse error, or after an element which consumed no bytes, it is fused.
+#[derive(Debug, Clone)]
+pub struct TryFromCtxIter<'a, T, Ctx> {
+    src: &'a [u8],
+    offset: usize,
+    ctx: Ctx,
+    _marker: PhantomData<T>,
+}
+
+impl<'a, T, Ctx> TryFromCtxIter<'a, T, Ctx> {
+    /// How many bytes have been consumed so far
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<'a, T, Ctx> Iterator for TryFromCtxIter<'a, T, Ctx> where T: TryFromCtx<'a, Ctx>, Ctx: Copy {
+    type Item = Result<T, T::Error>;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset >= self.src.len() {
+            return None;
+        }
+        match T::try_from_ctx(&self.src[self.offset..], self.ctx) {
+            Ok((t, 0)) => {
+                self.offset = self.src.len();
+                Some(Ok(t))
+            },
+            Ok((t, size)) => {
+                self.offset += size;
+                Some(Ok(t))
+            },
+            Err(e) => {
+                self.offset = self.src.len();
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl<'a, T, Ctx> TryFromCtx<'a, IterCtx<Ctx>> for TryFromCtxIter<'a, T, Ctx> where T: TryFromCtx<'a, Ctx>, Ctx: Copy + 'a {
+    type Error = error::Error;
+    #[inline]
+    /// Returns the (unparsed) iterator; since it runs until the end of `src`, the whole of `src` is reported as consumed
+    fn try_from_ctx(src: &'a [u8], ctx: IterCtx<Ctx>) -> result::Result<(Self, usize), Self::Error> {
+        Ok((TryFromCtxIter { src, offset: 0, ctx: ctx.0, _marker: PhantomData }, src.len()))
+    }
+}
+
+/// The parsing context for reading `T`s with the context `ctx`, until one matching `is_terminal` is read, e.g. a table ended by an all-zero entry
+///
+/// At most `max` elements are read before the terminator, which bounds the damage from corrupt input. The terminator itself is always consumed,
+/// and only kept with the elements if `keep_terminator` is set. Running out of source before the terminator is an `Error::Incomplete`,
+/// whereas exceeding `max` is an `Error::BadInput`.
+///
+/// Used by [Pread::gread_terminated](../trait.Pread.html#method.gread_terminated), and lazily by [TerminatedIter](struct.TerminatedIter.html).
+#[derive(Debug, Copy, Clone)]
+pub struct TerminatedCtx<Ctx, F> {
+    /// The context each element is read with
+    pub ctx: Ctx,
+    /// Whether an element is the terminator
+    pub is_terminal: F,
+    /// The maximum number of elements before the terminator
+    pub max: usize,
+    /// Whether the terminator is yielded along with the elements
+    pub keep_terminator: bool,
+}
+
+impl<Ctx, F> TerminatedCtx<Ctx, F> {
+    /// Creates a context which reads at most `max` elements before the terminator, and drops the terminator
+    #[inline]
+    pub fn new(ctx: Ctx, is_terminal: F, max: usize) -> Self {
+        TerminatedCtx { ctx, is_terminal, max, keep_terminator: false }
+    }
+}
+
+#[inline]
+pub(crate) fn too_many_elements(max: usize) -> error::Error {
+    error::Error::BadInput { size: max, msg: "too many elements before the terminator" }
+}
+
+/// A lazy iterator which parses `T`s out of its source until the terminator of its [TerminatedCtx](struct.TerminatedCtx.html)
+///
+/// The iterator returns `None` once the terminator has been read; after an error it is fused.
+///
+/// # Example
+/// ```rust
+/// use scroll::LE;
+/// use scroll::ctx::{TerminatedCtx, TerminatedIter};
+///
+/// let bytes = [0x01, 0x00, 0x02, 0x00, 0x00, 0x00, 0xff, 0xff];
+/// let mut iter: TerminatedIter<u16, _, _> = TerminatedIter::new(&bytes, TerminatedCtx::new(LE, |n: &u16| *n == 0, 16));
+/// assert_eq!(iter.next().unwrap().unwrap(), 1);
+/// assert_eq!(iter.next().unwrap().unwrap(), 2);
+/// assert!(iter.next().is_none());
+/// assert_eq!(iter.offset(), 6);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TerminatedIter<'a, T, Ctx, F> {
+    src: &'a [u8],
+    offset: usize,
+    count: usize,
+    ctx: TerminatedCtx<Ctx, F>,
+    done: bool,
+    _marker: PhantomData<T>,
+}
+
+impl<'a, T, Ctx, F> TerminatedIter<'a, T, Ctx, F> {
+    /// Creates an iterator reading from the start of `src`
+    #[inline]
+    pub fn new(src: &'a [u8], ctx: TerminatedCtx<Ctx, F>) -> Self {
+        TerminatedIter { src, offset: 0, count: 0, ctx, done: false, _marker: PhantomData }
+    }
+    /// How many bytes have been consumed so far, including the terminator once it has been read
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<'a, T, Ctx, F> Iterator for TerminatedIter<'a, T, Ctx, F>
+    where T: TryFromCtx<'a, Ctx>,
+          T::Error: From<error::Error>,
+          Ctx: Copy,
+          F: Fn(&T) -> bool,
+{
+    type Item = Result<T, T::Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let src = &self.src[self.offset..];
+            let (t, size) = match T::try_from_ctx(src, self.ctx.ctx) {
+                Ok(res) => res,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            };
+            self.offset += size;
+            if (self.ctx.is_terminal)(&t) {
+                self.done = true;
+                if self.ctx.keep_terminator {
+                    return Some(Ok(t));
+                }
+            } else if self.count == self.ctx.max {
+                self.done = true;
+                return Some(Err(too_many_elements(self.ctx.max).into()));
+            } else {
+                self.count += 1;
+                return Some(Ok(t));
+            }
+        }
+        None
+    }
+}
+
+impl<'a, T, Ctx, F> core::iter::FusedIterator for TerminatedIter<'a, T, Ctx, F>
+    where T: TryFromCtx<'a, Ctx>,
+          T::Error: From<error::Error>,
+          Ctx: Copy,
+          F: Fn(&T) -> bool,
+{}
+
+/// The parsing context for reading timestamps as a `SystemTime` or `Duration`; selects how the timestamp is encoded, and with what endianness
+///
+/// When reading a `Duration`, it is the time elapsed since the encoding's epoch, so negative timestamps are an error.
+/// Values which cannot be represented by the target type, or by the encoding when writing, are an error rather than a panic.
+/// Writing a `SystemTime` truncates it to the precision of the encoding, rounding towards the past.
+///
+/// # Example
+/// ```rust
+/// use std::time::{Duration, UNIX_EPOCH, SystemTime};
+/// use scroll::{Pread, Pwrite, BE};
+/// use scroll::ctx::TimeCtx;
+///
+/// let bytes = [0x3b, 0x9a, 0xca, 0x00];
+/// let time: SystemTime = bytes.pread_with(0, TimeCtx::UnixSeconds32(BE)).unwrap();
+/// assert_eq!(time, UNIX_EPOCH + Duration::from_secs(1_000_000_000));
+///
+/// let mut filetime = [0u8; 8];
+/// filetime.pwrite_with(time, 0, TimeCtx::WindowsFiletime(BE)).unwrap();
+/// assert_eq!(filetime.pread_with::<SystemTime>(0, TimeCtx::WindowsFiletime(BE)).unwrap(), time);
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TimeCtx {
+    /// A signed 32-bit count of seconds since the Unix epoch, i.e., a classic `time_t`
+    UnixSeconds32(Endian),
+    /// A signed 64-bit count of seconds since the Unix epoch
+    UnixSeconds64(Endian),
+    /// A signed 64-bit count of milliseconds since the Unix epoch
+    UnixMillis64(Endian),
+    /// An unsigned 64-bit count of 100 nanosecond intervals since January 1, 1601 (UTC)
+    WindowsFiletime(Endian),
+}
+
+#[cfg(feature = "std")]
+const NANOS_PER_SEC: i128 = 1_000_000_000;
+/// Nanoseconds between the Windows epoch (1601-01-01) and the Unix epoch
+#[cfg(feature = "std")]
+const FILETIME_UNIX_OFFSET: i128 = 11_644_473_600 * NANOS_PER_SEC;
+
+#[cfg(feature = "std")]
+impl TimeCtx {
+    #[inline]
+    fn size(self) -> usize {
+        match self {
+            TimeCtx::UnixSeconds32(_) => 4,
+            _ => 8,
+        }
+    }
+    #[inline]
+    fn nanos_per_tick(self) -> i128 {
+        match self {
+            TimeCtx::UnixSeconds32(_) | TimeCtx::UnixSeconds64(_) => NANOS_PER_SEC,
+            TimeCtx::UnixMillis64(_) => 1_000_000,
+            TimeCtx::WindowsFiletime(_) => 100,
+        }
+    }
+    /// Nanoseconds from the Unix epoch to this encoding's epoch
+    #[inline]
+    fn epoch(self) -> i128 {
+        match self {
+            TimeCtx::WindowsFiletime(_) => -FILETIME_UNIX_OFFSET,
+            _ => 0,
+        }
+    }
+    /// Reads the raw timestamp, in nanoseconds since this encoding's epoch
+    fn read_nanos(self, src: &[u8]) -> error::Result<i128> {
+        let ticks = match self {
+            TimeCtx::UnixSeconds32(le) => i128::from(i32::try_from_ctx(src, le)?.0),
+            TimeCtx::UnixSeconds64(le) |
+            TimeCtx::UnixMillis64(le) => i128::from(i64::try_from_ctx(src, le)?.0),
+            TimeCtx::WindowsFiletime(le) => i128::from(u64::try_from_ctx(src, le)?.0),
+        };
+        Ok(ticks * self.nanos_per_tick())
+    }
+    /// Writes `nanos`, since this encoding's epoch, as a raw timestamp
+    fn write_nanos(self, nanos: i128, dst: &mut [u8]) -> error::Result<usize> {
+        let ticks = nanos.div_euclid(self.nanos_per_tick());
+        let out_of_range = || error::Error::BadInput { size: self.size(), msg: "timestamp out of range for its encoding" };
+        match self {
+            TimeCtx::UnixSeconds32(le) => i32::try_from(ticks).map_err(|_| out_of_range())?.try_into_ctx(dst, le),
+            TimeCtx::UnixSeconds64(le) |
+            TimeCtx::UnixMillis64(le) => i64::try_from(ticks).map_err(|_| out_of_range())?.try_into_ctx(dst, le),
+            TimeCtx::WindowsFiletime(le) => u64::try_from(ticks).map_err(|_| out_of_range())?.try_into_ctx(dst, le),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[inline]
+fn nanos_to_duration(nanos: i128) -> Option<Duration> {
+    if nanos < 0 {
+        return None;
+    }
+    let secs = u64::try_from(nanos / NANOS_PER_SEC).ok()?;
+    Some(Duration::new(secs, (nanos % NANOS_PER_SEC) as u32))
+}
+
+#[cfg(feature = "std")]
+impl<'a> TryFromCtx<'a, TimeCtx> for SystemTime {
+    type Error = error::Error;
+    #[inline]
+    fn try_from_ctx(src: &'a [u8], ctx: TimeCtx) -> result::Result<(Self, usize), Self::Error> {
+        let nanos = ctx.read_nanos(src)? + ctx.epoch();
+        let time = if nanos >= 0 {
+            nanos_to_duration(nanos).and_then(|d| UNIX_EPOCH.checked_add(d))
+        } else {
+            nanos_to_duration(-nanos).and_then(|d| UNIX_EPOCH.checked_sub(d))
+        };
+        match time {
+            Some(time) => Ok((time, ctx.size())),
+            None => Err(error::Error::BadInput { size: ctx.size(), msg: "timestamp is not representable as a SystemTime" }),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a> TryFromCtx<'a, TimeCtx> for Duration {
+    type Error = error::Error;
+    #[inline]
+    fn try_from_ctx(src: &'a [u8], ctx: TimeCtx) -> result::Result<(Self, usize), Self::Error> {
+        match nanos_to_duration(ctx.read_nanos(src)?) {
+            Some(duration) => Ok((duration, ctx.size())),
+            None => Err(error::Error::BadInput { size: ctx.size(), msg: "negative timestamp is not representable as a Duration" }),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl TryIntoCtx<TimeCtx> for SystemTime {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], ctx: TimeCtx) -> error::Result<usize> {
+        let nanos = match self.duration_since(UNIX_EPOCH) {
+            Ok(after) => after.as_nanos() as i128,
+            Err(before) => -(before.duration().as_nanos() as i128),
+        };
+        ctx.write_nanos(nanos - ctx.epoch(), dst)
+    }
+}
+
+#[cfg(feature = "std")]
+impl TryIntoCtx<TimeCtx> for Duration {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], ctx: TimeCtx) -> error::Result<usize> {
+        ctx.write_nanos(self.as_nanos() as i128, dst)
+    }
+}
+
+/// The parsing context for two interleaved streams, e.g., left and right audio channels stored as `L, R, L, R, ...`
+///
+/// Reading a `(Vec<T>, Vec<U>)` with an `InterleavedCtx` alternately parses a `T` with `inner_a` and a `U` with `inner_b`, until the source is exhausted;
+/// running out of bytes in the middle of a pair is an error.
+///
+/// # Example
+/// ```rust
+/// use scroll::{Pread, LE};
+/// use scroll::ctx::InterleavedCtx;
+///
+/// let bytes = [0x01, 0x00, 0xff, 0xff, 0x02, 0x00, 0xfe, 0xff];
+/// let (left, right): (Vec<i16>, Vec<i16>) = bytes.pread_with(0, InterleavedCtx { inner_a: LE, inner_b: LE }).unwrap();
+/// assert_eq!(left, [1, 2]);
+/// assert_eq!(right, [-1, -2]);
+/// ```
+#[derive(Debug, Copy, Clone, Default)]
+pub struct InterleavedCtx<A, B> {
+    pub inner_a: A,
+    pub inner_b: B,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, T, U, A, B, E> TryFromCtx<'a, InterleavedCtx<A, B>> for (Vec<T>, Vec<U>)
+    where T: TryFromCtx<'a, A, Error = E>,
+          U: TryFromCtx<'a, B, Error = E>,
+          E: From<error::Error>,
+          A: Copy,
+          B: Copy,
+{
+    type Error = E;
+    fn try_from_ctx(src: &'a [u8], ctx: InterleavedCtx<A, B>) -> result::Result<(Self, usize), Self::Error> {
+        let mut offset = 0;
+        let mut a = Vec::new();
+        let mut b = Vec::new();
+        while offset < src.len() {
+            let start = offset;
+            let (t, size) = T::try_from_ctx(&src[offset..], ctx.inner_a)?;
+            offset += size;
+            let (u, size) = U::try_from_ctx(&src[offset..], ctx.inner_b)?;
+            offset += size;
+            a.push(t);
+            b.push(u);
+            // a pair which consumes nothing would never exhaust the source
+            if offset == start {
+                break;
+            }
+        }
+        Ok(((a, b), offset))
+    }
+}
+
+/// The parsing context for three interleaved channels of `count` samples each, e.g., the `R, G, B, R, G, B, ...` of a pixel
+/// buffer
+///
+/// Reading a `(Vec<T>, Vec<T>, Vec<T>)` with an `Interleaved3Ctx` parses `count` triples of `T`s with `inner`, and
+/// deinterleaves them into one `Vec` per channel.
+///
+/// # Example
+/// ```rust
+/// use scroll::{Pread, LE};
+/// use scroll::ctx::Interleaved3Ctx;
+///
+/// let pixels = [0xff, 0x80, 0x00, 0x10, 0x20, 0x30];
+/// let (r, g, b): (Vec<u8>, Vec<u8>, Vec<u8>) = pixels.pread_with(0, Interleaved3Ctx { count: 2, inner: LE }).unwrap();
+/// assert_eq!(r, [0xff, 0x10]);
+/// assert_eq!(g, [0x80, 0x20]);
+/// assert_eq!(b, [0x00, 0x30]);
+/// ```
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Interleaved3Ctx<Ctx> {
+    pub count: usize,
+    pub inner: Ctx,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, T, Ctx, E> TryFromCtx<'a, Interleaved3Ctx<Ctx>> for (Vec<T>, Vec<T>, Vec<T>)
+    where T: TryFromCtx<'a, Ctx, Error = E>,
+          E: From<error::Error>,
+          Ctx: Copy,
+{
+    type Error = E;
+    fn try_from_ctx(src: &'a [u8], ctx: Interleaved3Ctx<Ctx>) -> result::Result<(Self, usize), Self::Error> {
+        check_count(ctx.count, src)?;
+        // each of the `count` frames takes up three elements
+        let capacity = initial_capacity::<[T; 3]>(ctx.count, src);
+        let mut channels = (Vec::with_capacity(capacity), Vec::with_capacity(capacity), Vec::with_capacity(capacity));
+        let mut offset = 0;
+        for _ in 0..ctx.count {
+            for channel in [&mut channels.0, &mut channels.1, &mut channels.2] {
+                let (t, size) = T::try_from_ctx(src.get(offset..).ok_or(error::Error::BadOffset(offset))?, ctx.inner)?;
+                offset += size;
+                channel.push(t);
+            }
+        }
+        Ok((channels, offset))
+    }
+}
+
+/// The parsing context for two values stored back to back, each with its own context, e.g., a header followed by its
+/// extension
+///
+/// Reading a `(T, U)` with a `ConcatCtx` parses a `T` with `inner_a`, and then a `U` with `inner_b` right where the `T`
+/// ended; writing a `(T, U)` does the same in reverse.
+///
+/// # Example
+/// ```rust
+/// use scroll::{Pread, Pwrite, BE, LE};
+/// use scroll::ctx::ConcatCtx;
+///
+/// let ctx = ConcatCtx { inner_a: BE, inner_b: LE };
+/// let bytes = [0x12, 0x34, 0x78, 0x56, 0x34, 0x12];
+/// let (a, b): (u16, u32) = bytes.pread_with(0, ctx).unwrap();
+/// assert_eq!(a, 0x1234);
+/// assert_eq!(b, 0x12345678);
+///
+/// let mut out = [0u8; 6];
+/// assert_eq!(out.pwrite_with((a, b), 0, ctx).unwrap(), 6);
+/// assert_eq!(out, bytes);
+/// ```
+#[derive(Debug, Copy, Clone, Default)]
+pub struct ConcatCtx<A, B> {
+    pub inner_a: A,
+    pub inner_b: B,
+}
+
+impl<'a, T, U, A, B, E> TryFromCtx<'a, ConcatCtx<A, B>> for (T, U)
+    where T: TryFromCtx<'a, A, Error = E>,
+          U: TryFromCtx<'a, B, Error = E>,
+          E: From<error::Error>,
+          A: Copy,
+          B: Copy,
+{
+    type Error = E;
+    #[inline]
+    fn try_from_ctx(src: &'a [u8], ctx: ConcatCtx<A, B>) -> result::Result<(Self, usize), Self::Error> {
+        let (t, size_a) = T::try_from_ctx(src, ctx.inner_a)?;
+        let rest = src.get(size_a..).ok_or(error::Error::BadOffset(size_a))?;
+        let (u, size_b) = U::try_from_ctx(rest, ctx.inner_b)?;
+        Ok(((t, u), size_a + size_b))
+    }
+}
+
+impl<T, U, A, B, E> TryIntoCtx<ConcatCtx<A, B>> for (T, U)
+    where T: TryIntoCtx<A, Error = E>,
+          U: TryIntoCtx<B, Error = E>,
+          E: From<error::Error>,
+          A: Copy,
+          B: Copy,
+{
+    type Error = E;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], ctx: ConcatCtx<A, B>) -> result::Result<usize, Self::Error> {
+        let size_a = self.0.try_into_ctx(dst, ctx.inner_a)?;
+        let rest = dst.get_mut(size_a..).ok_or(error::Error::BadOffset(size_a))?;
+        let size_b = self.1.try_into_ctx(rest, ctx.inner_b)?;
+        Ok(size_a + size_b)
+    }
+}
+
+/// What reading a map does when a key appears more than once
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Duplicates {
+    /// A repeated key is an `Error::Invalid`, carrying the offset of its pair
+    Reject,
+    /// The value of the last pair with the key is kept
+    LastWins,
+}
+
+/// The context for reading a `HashMap` or a `BTreeMap` out of `count` consecutive key/value pairs, each key and value
+/// being parsed with `ctx`; writing takes the same context, and writes the pairs without the count
+///
+/// The count usually comes from the source itself, so before allocating anything it is sanity checked against the
+/// remaining bytes, assuming each pair takes up at least one.
+///
+/// Writing a `BTreeMap` writes its pairs in key order; a `HashMap` has its pairs sorted by key first, so that its output
+/// is deterministic. Either way, `count` must match the number of pairs.
+///
+/// # Example
+/// ```rust
+/// use std::collections::BTreeMap;
+/// use scroll::{Pread, Pwrite, LE};
+/// use scroll::ctx::{Duplicates, MapCtx};
+///
+/// let bytes = [0x02, 0x01, 0x00, 0x10, 0x00, 0x02, 0x00, 0x20, 0x00];
+/// let offset = &mut 0;
+/// let count: u8 = bytes.gread_with(offset, LE).unwrap();
+/// let ctx = MapCtx::new(count as usize, LE, Duplicates::Reject);
+/// let map: BTreeMap<u16, u16> = bytes.gread_with(offset, ctx).unwrap();
+/// assert_eq!(map[&1], 0x10);
+/// assert_eq!(map[&2], 0x20);
+///
+/// let mut out = [0u8; 8];
+/// assert_eq!(out.pwrite_with(&map, 0, ctx).unwrap(), 8);
+/// assert_eq!(out, bytes[1..]);
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Debug, Copy, Clone)]
+pub struct MapCtx<Ctx> {
+    /// The number of key/value pairs
+    pub count: usize,
+    /// The context each key and value is read and written with
+    pub ctx: Ctx,
+    /// Whether a repeated key is an error
+    pub duplicates: Duplicates,
+}
+
+#[cfg(feature = "alloc")]
+impl<Ctx> MapCtx<Ctx> {
+    pub fn new(count: usize, ctx: Ctx, duplicates: Duplicates) -> Self {
+        MapCtx { count, ctx, duplicates }
+    }
+
+}
+
+/// Rejects an element count which `src` cannot possibly hold, assuming every element takes up at least a byte, before
+/// anything is allocated for them
+#[cfg(feature = "alloc")]
+pub(crate) fn check_count(count: usize, src: &[u8]) -> error::Result<()> {
+    if count > src.len() {
+        return Err(error::Error::BadInput { size: count, msg: "element count exceeds the remaining bytes" });
+    }
+    Ok(())
+}
+
+/// The capacity to start a collection of `count` `T`s read from `src` with: no more of them than fit in as many bytes as
+/// `src` has, so that a count which passes `check_count` can't preallocate far more than the input's size
+#[cfg(feature = "alloc")]
+pub(crate) fn initial_capacity<T>(count: usize, src: &[u8]) -> usize {
+    count.min(src.len() / size_of::<T>().max(1))
+}
+
+/// Reads `count` pairs from `src`, handing each to `insert`, which returns whether the key was already present
+#[cfg(feature = "alloc")]
+fn read_pairs<'a, K, V, KC, VC, E>(src: &'a [u8], count: usize, key_ctx: KC, val_ctx: VC, duplicates: Duplicates,
+                                   mut insert: impl FnMut(K, V) -> bool) -> result::Result<usize, E>
+    where K: TryFromCtx<'a, KC, Error = E>,
+          V: TryFromCtx<'a, VC, Error = E>,
+          E: From<error::Error>,
+          KC: Copy,
+          VC: Copy,
+{
+    let mut offset = 0;
+    for _ in 0..count {
+        let start = offset;
+        let (k, size) = K::try_from_ctx(src.get(offset..).ok_or(error::Error::BadOffset(offset))?, key_ctx)?;
+        offset += size;
+        let (v, size) = V::try_from_ctx(src.get(offset..).ok_or(error::Error::BadOffset(offset))?, val_ctx)?;
+        offset += size;
+        if insert(k, v) && duplicates == Duplicates::Reject {
+            return Err(error::Error::Invalid { offset: start, msg: "duplicate map key" }.into());
+        }
+    }
+    Ok(offset)
+}
+
+/// Writes `pairs`, of which there must be `count`, into `dst`
+#[cfg(feature = "alloc")]
+fn write_pairs<'m, K, V, KC, VC, E>(dst: &mut [u8], count: usize, key_ctx: KC, val_ctx: VC, len: usize,
+                                    pairs: impl Iterator<Item = (&'m K, &'m V)>) -> result::Result<usize, E>
+    where &'m K: TryIntoCtx<KC, Error = E>,
+          &'m V: TryIntoCtx<VC, Error = E>,
+          K: 'm,
+          V: 'm,
+          E: From<error::Error>,
+          KC: Copy,
+          VC: Copy,
+{
+    if len != count {
+        return Err(error::Error::BadInput { size: len, msg: "map length does not match the context's count" }.into());
+    }
+    let mut offset = 0;
+    for (k, v) in pairs {
+        offset += k.try_into_ctx(dst.get_mut(offset..).ok_or(error::Error::BadOffset(offset))?, key_ctx)?;
+        offset += v.try_into_ctx(dst.get_mut(offset..).ok_or(error::Error::BadOffset(offset))?, val_ctx)?;
+    }
+    Ok(offset)
+}
+
+#[cfg(feature = "std")]
+impl<'a, K, V, Ctx, E> TryFromCtx<'a, MapCtx<Ctx>> for HashMap<K, V>
+    where K: TryFromCtx<'a, Ctx, Error = E> + Eq + Hash,
+          V: TryFromCtx<'a, Ctx, Error = E>,
+          E: From<error::Error>,
+          Ctx: Copy,
+{
+    type Error = E;
+    fn try_from_ctx(src: &'a [u8], ctx: MapCtx<Ctx>) -> result::Result<(Self, usize), Self::Error> {
+        check_count(ctx.count, src)?;
+        let mut map = HashMap::with_capacity(initial_capacity::<(K, V)>(ctx.count, src));
+        let size = read_pairs::<K, V, Ctx, Ctx, E>(src, ctx.count, ctx.ctx, ctx.ctx, ctx.duplicates, |k, v| map.insert(k, v).is_some())?;
+        Ok((map, size))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, K, V, Ctx, E> TryFromCtx<'a, MapCtx<Ctx>> for BTreeMap<K, V>
+    where K: TryFromCtx<'a, Ctx, Error = E> + Ord,
+          V: TryFromCtx<'a, Ctx, Error = E>,
+          E: From<error::Error>,
+          Ctx: Copy,
+{
+    type Error = E;
+    fn try_from_ctx(src: &'a [u8], ctx: MapCtx<Ctx>) -> result::Result<(Self, usize), Self::Error> {
+        check_count(ctx.count, src)?;
+        let mut map = BTreeMap::new();
+        let size = read_pairs::<K, V, Ctx, Ctx, E>(src, ctx.count, ctx.ctx, ctx.ctx, ctx.duplicates, |k, v| map.insert(k, v).is_some())?;
+        Ok((map, size))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'m, K, V, Ctx, E> TryIntoCtx<MapCtx<Ctx>> for &'m HashMap<K, V>
+    where &'m K: TryIntoCtx<Ctx, Error = E>,
+          &'m V: TryIntoCtx<Ctx, Error = E>,
+          K: Ord,
+          E: From<error::Error>,
+          Ctx: Copy,
+{
+    type Error = E;
+    fn try_into_ctx(self, dst: &mut [u8], ctx: MapCtx<Ctx>) -> result::Result<usize, Self::Error> {
+        let mut pairs: Vec<_> = self.iter().collect();
+        pairs.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        write_pairs::<K, V, Ctx, Ctx, E>(dst, ctx.count, ctx.ctx, ctx.ctx, self.len(), pairs.into_iter())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'m, K, V, Ctx, E> TryIntoCtx<MapCtx<Ctx>> for &'m BTreeMap<K, V>
+    where &'m K: TryIntoCtx<Ctx, Error = E>,
+          &'m V: TryIntoCtx<Ctx, Error = E>,
+          E: From<error::Error>,
+          Ctx: Copy,
+{
+    type Error = E;
+    fn try_into_ctx(self, dst: &mut [u8], ctx: MapCtx<Ctx>) -> result::Result<usize, Self::Error> {
+        write_pairs::<K, V, Ctx, Ctx, E>(dst, ctx.count, ctx.ctx, ctx.ctx, self.len(), self.iter())
+    }
+}
+
+/// The context for reading a `BTreeMap` out of `count` consecutive key/value pairs, like a [MapCtx](struct.MapCtx.html),
+/// but with separate contexts for the keys and the values; writing takes the same context, and writes the pairs in key
+/// order without the count
+///
+/// # Example
+/// ```rust
+/// use std::collections::BTreeMap;
+/// use scroll::{Pread, Pwrite, LE};
+/// use scroll::ctx::{BTreeMapCtx, Duplicates, StrCtx};
+///
+/// // NUL terminated names, mapped to little endian numbers
+/// let bytes = b"b\0\x02\0a\0\x01\0";
+/// let ctx = BTreeMapCtx::new(2, StrCtx::Delimiter(0), LE, Duplicates::Reject);
+/// let map: BTreeMap<&str, u16> = bytes.pread_with(0, ctx).unwrap();
+/// assert_eq!(map.into_iter().collect::<Vec<_>>(), [("a", 1), ("b", 2)]);
+///
+/// let mut out = [0u8; 8];
+/// let map: BTreeMap<String, u16> = vec![("b".to_string(), 2), ("a".to_string(), 1)].into_iter().collect();
+/// assert_eq!(out.pwrite_with(&map, 0, ctx).unwrap(), 8);
+/// assert_eq!(&out, b"a\0\x01\0b\0\x02\0");
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Debug, Copy, Clone)]
+pub struct BTreeMapCtx<KC, VC> {
+    /// The number of key/value pairs
+    pub count: usize,
+    /// The context each key is read and written with
+    pub key_ctx: KC,
+    /// The context each value is read and written with
+    pub val_ctx: VC,
+    /// Whether a repeated key is an error
+    pub duplicates: Duplicates,
+}
+
+#[cfg(feature = "alloc")]
+impl<KC, VC> BTreeMapCtx<KC, VC> {
+    pub fn new(count: usize, key_ctx: KC, val_ctx: VC, duplicates: Duplicates) -> Self {
+        BTreeMapCtx { count, key_ctx, val_ctx, duplicates }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, K, V, KC, VC, E> TryFromCtx<'a, BTreeMapCtx<KC, VC>> for BTreeMap<K, V>
+    where K: TryFromCtx<'a, KC, Error = E> + Ord,
+          V: TryFromCtx<'a, VC, Error = E>,
+          E: From<error::Error>,
+          KC: Copy,
+          VC: Copy,
+{
+    type Error = E;
+    fn try_from_ctx(src: &'a [u8], ctx: BTreeMapCtx<KC, VC>) -> result::Result<(Self, usize), Self::Error> {
+        check_count(ctx.count, src)?;
+        let mut map = BTreeMap::new();
+        let size = read_pairs::<K, V, KC, VC, E>(src, ctx.count, ctx.key_ctx, ctx.val_ctx, ctx.duplicates, |k, v| map.insert(k, v).is_some())?;
+        Ok((map, size))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'m, K, V, KC, VC, E> TryIntoCtx<BTreeMapCtx<KC, VC>> for &'m BTreeMap<K, V>
+    where &'m K: TryIntoCtx<KC, Error = E>,
+          &'m V: TryIntoCtx<VC, Error = E>,
+          E: From<error::Error>,
+          KC: Copy,
+          VC: Copy,
+{
+    type Error = E;
+    fn try_into_ctx(self, dst: &mut [u8], ctx: BTreeMapCtx<KC, VC>) -> result::Result<usize, Self::Error> {
+        write_pairs::<K, V, KC, VC, E>(dst, ctx.count, ctx.key_ctx, ctx.val_ctx, self.len(), self.iter())
+    }
+}
+
+/// The context of a collection with a known number of elements: the count, and the context each element is read and
+/// written with
+///
+/// A `Vec<T>` reads and writes with a `Count`, rather than a bare `(usize, Ctx)`, which a
+/// [SplitCtx](struct.SplitCtx.html) payload reads with as its length in bytes; nesting one in the other can't mistake
+/// a count for a length.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Count<Ctx>(pub usize, pub Ctx);
+
+/// Reads exactly `count` `T`s back to back, each with `ctx`
+///
+/// The count is sanity checked against the remaining bytes before allocating, assuming every element takes up at least
+/// one. A failing element is reported as an `Error::Element`, with its index and its offset from the start of the `Vec`.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx::Count, Pread, Pwrite, BE};
+///
+/// let bytes = [0x00, 0x01, 0x00, 0x02, 0x00, 0x03];
+/// let offset = &mut 0;
+/// let entries: Vec<u16> = bytes.gread_with(offset, Count(3, BE)).unwrap();
+/// assert_eq!(entries, [1, 2, 3]);
+/// assert_eq!(*offset, 6);
+///
+/// let mut out = [0u8; 6];
+/// assert_eq!(out.pwrite_with(&entries, 0, Count(3, BE)).unwrap(), 6);
+/// assert_eq!(out, bytes);
+/// ```
+#[cfg(feature = "alloc")]
+impl<'a, T, Ctx> TryFromCtx<'a, Count<Ctx>> for Vec<T>
+    where T: TryFromCtx<'a, Ctx, Error = error::Error>,
+          Ctx: Copy,
+{
+    type Error = error::Error;
+    fn try_from_ctx(src: &'a [u8], Count(count, ctx): Count<Ctx>) -> error::Result<(Self, usize)> {
+        check_count(count, src)?;
+        read_elements(src, count, ctx, Vec::with_capacity(initial_capacity::<T>(count, src)))
+    }
+}
+
+/// Reads `count` elements back to back from `src` into `elements`
+#[cfg(feature = "alloc")]
+fn read_elements<'a, T, Ctx>(src: &'a [u8], count: usize, ctx: Ctx, mut elements: Vec<T>) -> error::Result<(Vec<T>, usize)>
+    where T: TryFromCtx<'a, Ctx, Error = error::Error>,
+          Ctx: Copy,
+{
+    let mut offset = 0;
+    for index in 0..count {
+        let (element, size) = src.get(offset..)
+            .ok_or(error::Error::BadOffset(offset))
+            .and_then(|src| T::try_from_ctx(src, ctx))
+            .map_err(|err| error::Error::Element { index, offset, source: Box::new(err) })?;
+        elements.push(element);
+        offset += size;
+    }
+    Ok((elements, offset))
+}
+
+/// Reads an owned `Vec` or `String` with the wrapped context, but allocates it fallibly: an allocation which fails is an
+/// `Error::AllocFailed`, which a service parsing untrusted input can turn into a rejection, rather than an abort
+///
+/// `Vec<T>` reads with `TryAlloc<Count<Ctx>>`, like it does with `Count<Ctx>`, and `String` reads with
+/// `TryAlloc<StrCtx>`, copying the `&str` that context reads. Values that read fine are the same either way.
+///
+/// # Example
+/// ```rust
+/// use scroll::{Pread, BE, ctx::{Count, StrCtx, TryAlloc}};
+///
+/// let bytes = [0x00, 0x01, 0x00, 0x02, b'h', b'i', 0x00];
+/// let lens: Vec<u16> = bytes.pread_with(0, TryAlloc(Count(2, BE))).unwrap();
+/// assert_eq!(lens, [1, 2]);
+/// let name: String = bytes.pread_with(4, TryAlloc(StrCtx::Delimiter(0))).unwrap();
+/// assert_eq!(name, "hi");
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct TryAlloc<Ctx>(pub Ctx);
+
+#[cfg(feature = "alloc")]
+impl<'a, T, Ctx> TryFromCtx<'a, TryAlloc<Count<Ctx>>> for Vec<T>
+    where T: TryFromCtx<'a, Ctx, Error = error::Error>,
+          Ctx: Copy,
+{
+    type Error = error::Error;
+    fn try_from_ctx(src: &'a [u8], TryAlloc(Count(count, ctx)): TryAlloc<Count<Ctx>>) -> error::Result<(Self, usize)> {
+        check_count(count, src)?;
+        read_elements(src, count, ctx, crate::buffer::try_vec(count)?)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> TryFromCtx<'a, TryAlloc<StrCtx>> for String {
+    type Error = error::Error;
+    fn try_from_ctx(src: &'a [u8], TryAlloc(ctx): TryAlloc<StrCtx>) -> error::Result<(Self, usize)> {
+        let (s, size) = <&str>::try_from_ctx(src, ctx)?;
+        let mut string = String::new();
+        string.try_reserve(s.len()).map_err(|_| error::Error::AllocFailed { size: s.len() })?;
+        string.push_str(s);
+        Ok((string, size))
+    }
+}
+
+/// How many bytes reading `Self` with `Ctx` allocates up front, as far as the context alone tells, e.g., room for the
+/// `count` elements of a `Vec<T>`; it is what [AllocLimit](struct.AllocLimit.html) checks before reading
+pub trait AllocSize<Ctx> {
+    /// The number of bytes reading with `ctx` allocates up front, saturating at `usize::MAX`
+    fn alloc_size(ctx: &Ctx) -> usize;
+}
+
+#[cfg(feature = "alloc")]
+impl<T, Ctx> AllocSize<Count<Ctx>> for Vec<T> {
+    #[inline]
+    fn alloc_size(&Count(count, _): &Count<Ctx>) -> usize {
+        count.saturating_mul(size_of::<T>())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, Ctx> AllocSize<TryAlloc<Count<Ctx>>> for Vec<T> {
+    #[inline]
+    fn alloc_size(ctx: &TryAlloc<Count<Ctx>>) -> usize {
+        <Vec<T> as AllocSize<Count<Ctx>>>::alloc_size(&ctx.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, V, Ctx> AllocSize<MapCtx<Ctx>> for HashMap<K, V> {
+    #[inline]
+    fn alloc_size(ctx: &MapCtx<Ctx>) -> usize {
+        ctx.count.saturating_mul(size_of::<(K, V)>())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V, Ctx> AllocSize<MapCtx<Ctx>> for BTreeMap<K, V> {
+    #[inline]
+    fn alloc_size(ctx: &MapCtx<Ctx>) -> usize {
+        ctx.count.saturating_mul(size_of::<(K, V)>())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V, KC, VC> AllocSize<BTreeMapCtx<KC, VC>> for BTreeMap<K, V> {
+    #[inline]
+    fn alloc_size(ctx: &BTreeMapCtx<KC, VC>) -> usize {
+        ctx.count.saturating_mul(size_of::<(K, V)>())
+    }
+}
+
+/// Caps how many bytes reading an owned collection with the context `ctx` may allocate: a read which would allocate
+/// more than `max_bytes` is an `Error::LimitExceeded("alloc")`, returned before anything is allocated
+///
+/// The remaining bytes of the source bound most counted reads, but not ones whose elements are smaller in the source
+/// than in memory, nor a count taken from elsewhere. It wraps the counted contexts of `Vec` (plain or
+/// [TryAlloc](struct.TryAlloc.html)), `HashMap` and `BTreeMap`, whose [AllocSize](trait.AllocSize.html) is the room for
+/// `count` elements. A `String` reads with `AllocLimit<Ctx>` whenever a `&str` reads with
+/// `Ctx`, and the limit is checked before the string is copied.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx::{AllocLimit, Count, StrCtx}, Error, Pread, BE};
+///
+/// let bytes = [0x00, 0x01, 0x00, 0x02, b'h', b'i', 0x00];
+/// let lens: Vec<u16> = bytes.pread_with(0, AllocLimit::new(4, Count(2, BE))).unwrap();
+/// assert_eq!(lens, [1, 2]);
+/// let err = bytes.pread_with::<Vec<u16>>(0, AllocLimit::new(3, Count(2, BE)));
+/// assert!(matches!(err, Err(Error::LimitExceeded("alloc"))));
+/// let name: String = bytes.pread_with(4, AllocLimit::new(2, StrCtx::Delimiter(0))).unwrap();
+/// assert_eq!(name, "hi");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct AllocLimit<Ctx> {
+    /// The most bytes the read may allocate
+    pub max_bytes: usize,
+    /// The context proper
+    pub ctx: Ctx,
+}
+
+impl<Ctx> AllocLimit<Ctx> {
+    /// Reads with `ctx`, allocating at most `max_bytes` bytes
+    #[inline]
+    pub fn new(max_bytes: usize, ctx: Ctx) -> Self {
+        AllocLimit { max_bytes, ctx }
+    }
+    /// Errors if allocating `size` bytes would exceed the limit
+    #[inline]
+    pub fn check(&self, size: usize) -> error::Result<()> {
+        if size > self.max_bytes {
+            Err(error::Error::LimitExceeded("alloc"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+// one impl per counted context rather than a blanket one over every `Ctx`, which sends inference of an unannotated
+// `Vec::try_from_ctx` chasing `AllocLimit<AllocLimit<..>>` forever
+macro_rules! alloc_limit_impl {
+    ($feature:literal, $ty:ty, $ctx:ty, $($param:ident),*) => {
+        #[cfg(feature = $feature)]
+        impl<'a, $($param),*> TryFromCtx<'a, AllocLimit<$ctx>> for $ty
+            where $ty: TryFromCtx<'a, $ctx> + AllocSize<$ctx>,
+                  <$ty as TryFromCtx<'a, $ctx>>::Error: From<error::Error>,
+                  $ctx: Copy,
+        {
+            type Error = <$ty as TryFromCtx<'a, $ctx>>::Error;
+            #[inline]
+            fn try_from_ctx(src: &'a [u8], ctx: AllocLimit<$ctx>) -> result::Result<(Self, usize), Self::Error> {
+                ctx.check(<$ty as AllocSize<$ctx>>::alloc_size(&ctx.ctx))?;
+                <$ty as TryFromCtx<'a, $ctx>>::try_from_ctx(src, ctx.ctx)
+            }
+        }
+    }
+}
+
+alloc_limit_impl!("alloc", Vec<T>, Count<Ctx>, T, Ctx);
+alloc_limit_impl!("alloc", Vec<T>, TryAlloc<Count<Ctx>>, T, Ctx);
+alloc_limit_impl!("std", HashMap<K, V>, MapCtx<Ctx>, K, V, Ctx);
+alloc_limit_impl!("alloc", BTreeMap<K, V>, MapCtx<Ctx>, K, V, Ctx);
+alloc_limit_impl!("alloc", BTreeMap<K, V>, BTreeMapCtx<KC, VC>, K, V, KC, VC);
+
+#[cfg(feature = "alloc")]
+impl<'a, Ctx: Copy> TryFromCtx<'a, AllocLimit<Ctx>> for String
+    where &'a str: TryFromCtx<'a, Ctx, Error = error::Error>,
+{
+    type Error = error::Error;
+    fn try_from_ctx(src: &'a [u8], ctx: AllocLimit<Ctx>) -> error::Result<(Self, usize)> {
+        let (s, size) = <&str>::try_from_ctx(src, ctx.ctx)?;
+        ctx.check(s.len())?;
+        Ok((s.to_owned(), size))
+    }
+}
+
+/// The error for element `index` of a sequence, `offset` bytes into it, failing with `err`; without `alloc`, there is
+/// nowhere to box it, so it's `err` itself
+#[cfg(feature = "alloc")]
+pub(crate) fn element_error(index: usize, offset: usize, err: error::Error) -> error::Error {
+    error::Error::Element { index, offset, source: Box::new(err) }
+}
+
+#[cfg(all(feature = "heapless", not(feature = "alloc")))]
+pub(crate) fn element_error(_index: usize, _offset: usize, err: error::Error) -> error::Error {
+    err
+}
+
+/// Writes the `len` `elements`, of which there must be `count`, back to back into `dst`
+#[cfg(any(feature = "alloc", feature = "heapless"))]
+pub(crate) fn write_elements<T, Ctx>(dst: &mut [u8], count: usize, ctx: Ctx, len: usize, elements: impl Iterator<Item = T>) -> error::Result<usize>
+    where T: TryIntoCtx<Ctx, Error = error::Error>,
+          Ctx: Copy,
+{
+    if len != count {
+        return Err(error::Error::BadInput { size: len, msg: "number of elements does not match the count" });
+    }
+    let mut offset = 0;
+    for (index, element) in elements.enumerate() {
+        offset += dst.get_mut(offset..)
+            .ok_or(error::Error::BadOffset(offset))
+            .and_then(|dst| element.try_into_ctx(dst, ctx))
+            .map_err(|err| element_error(index, offset, err))?;
+    }
+    Ok(offset)
+}
+
+/// Writes the elements back to back, each with `ctx`; the count must match the `Vec`'s length
+#[cfg(feature = "alloc")]
+impl<T, Ctx> TryIntoCtx<Count<Ctx>> for Vec<T>
+    where T: TryIntoCtx<Ctx, Error = error::Error>,
+          Ctx: Copy,
+{
+    type Error = error::Error;
+    fn try_into_ctx(self, dst: &mut [u8], Count(count, ctx): Count<Ctx>) -> error::Result<usize> {
+        write_elements(dst, count, ctx, self.len(), self.into_iter())
+    }
+}
+
+/// Writes the elements back to back, each with `ctx`; the count must match the `Vec`'s length
+#[cfg(feature = "alloc")]
+impl<'v, T, Ctx> TryIntoCtx<Count<Ctx>> for &'v Vec<T>
+    where &'v T: TryIntoCtx<Ctx, Error = error::Error>,
+          Ctx: Copy,
+{
+    type Error = error::Error;
+    fn try_into_ctx(self, dst: &mut [u8], Count(count, ctx): Count<Ctx>) -> error::Result<usize> {
+        write_elements(dst, count, ctx, self.len(), self.iter())
+    }
+}
+
+/// A slice of elements to write back to back with a [Count](struct.Count.html) context, as a `Vec` of them writes
+///
+/// `&[u8]` itself only writes with the `()` context, so that `pwrite` without a context keeps inferring which impl to
+/// use; wrap a slice in `Slice` to write its elements each with `ctx`.
+///
+/// # Example
+/// ```rust
+/// use scroll::{Pwrite, BE, ctx::{Count, Slice}};
+///
+/// let entries = [1u16, 2, 3];
+/// let mut bytes = [0u8; 4];
+/// assert_eq!(bytes.pwrite_with(Slice(&entries[1..]), 0, Count(2, BE)).unwrap(), 4);
+/// assert_eq!(bytes, [0x00, 0x02, 0x00, 0x03]);
+/// assert!(bytes.pwrite_with(Slice(&entries), 0, Count(2, BE)).is_err());
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Slice<'a, T>(pub &'a [T]);
+
+/// Writes the elements back to back, each with `ctx`; the count must match the slice's length
+#[cfg(feature = "alloc")]
+impl<'v, T, Ctx> TryIntoCtx<Count<Ctx>> for Slice<'v, T>
+    where &'v T: TryIntoCtx<Ctx, Error = error::Error>,
+          Ctx: Copy,
+{
+    type Error = error::Error;
+    fn try_into_ctx(self, dst: &mut [u8], Count(count, ctx): Count<Ctx>) -> error::Result<usize> {
+        write_elements(dst, count, ctx, self.0.len(), self.0.iter())
+    }
+}
+
+/// The order in which the bits of a byte hold the bools of a bitmap, or the bits of a bit stream
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum BitOrder {
+    /// The first bool is the least significant bit of the first byte, e.g., x86 feature masks
+    LsbFirst,
+    /// The first bool is the most significant bit of the first byte, e.g., network presence bitmaps
+    MsbFirst,
+}
+
+impl BitOrder {
+    /// The mask of the `i`th bit of a byte in this order
+    #[inline]
+    pub(crate) fn mask(self, i: usize) -> u8 {
+        match self {
+            BitOrder::LsbFirst => 1 << (i % 8),
+            BitOrder::MsbFirst => 0x80 >> (i % 8),
+        }
+    }
+}
+
+/// The number of bytes a bitmap of `count` bools is packed in
+#[inline]
+const fn bitmap_len(count: usize) -> usize {
+    count.div_ceil(8)
+}
+
+/// Unpacks the `bools.len()` bools at the start of `src`; the unused trailing bits of the last byte are ignored
+fn read_bits(src: &[u8], bools: &mut [bool], order: BitOrder) -> error::Result<usize> {
+    let size = bitmap_len(bools.len());
+    if size > src.len() {
+        return Err(error::Error::Incomplete { needed: size - src.len() });
+    }
+    for (i, bool) in bools.iter_mut().enumerate() {
+        *bool = src[i / 8] & order.mask(i) != 0;
+    }
+    Ok(size)
+}
+
+/// Packs `bools` into the start of `dst`, with the unused trailing bits of the last byte zeroed
+fn write_bits(dst: &mut [u8], bools: &[bool], order: BitOrder) -> error::Result<usize> {
+    let size = bitmap_len(bools.len());
+    if size > dst.len() {
+        return Err(error::Error::TooBig { size, len: dst.len() });
+    }
+    let dst = &mut dst[..size];
+    dst.fill(0);
+    for (i, _) in bools.iter().enumerate().filter(|(_, bool)| **bool) {
+        dst[i / 8] |= order.mask(i);
+    }
+    Ok(size)
+}
+
+/// The parsing context for a bitmap of `count` bools read into a `Vec<bool>`, packed 8 to a byte in the given bit `order`
+///
+/// It takes `ceil(count / 8)` bytes; the unused trailing bits of the last byte are ignored when reading, and written
+/// as zeroes. A `[bool; N]` reads with just the [BitOrder](enum.BitOrder.html), and bools write with it from an array,
+/// a slice or a `Vec`.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx::{BitOrder, BitsCtx}, Pread, Pwrite};
+///
+/// let bytes = [0b1000_0101, 0b0000_0010];
+/// let lsb: [bool; 4] = bytes.pread_with(0, BitOrder::LsbFirst).unwrap();
+/// assert_eq!(lsb, [true, false, true, false]);
+/// let msb: Vec<bool> = bytes.pread_with(0, BitsCtx::new(10, BitOrder::MsbFirst)).unwrap();
+/// assert_eq!(msb, [true, false, false, false, false, true, false, true, false, false]);
+///
+/// let mut out = [0xff; 2];
+/// assert_eq!(out.pwrite_with(&msb[..], 0, BitOrder::MsbFirst).unwrap(), 2);
+/// assert_eq!(out, [0b1000_0101, 0]);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct BitsCtx {
+    /// The number of bools
+    pub count: usize,
+    /// The order of the bits in each byte
+    pub order: BitOrder,
+}
+
+impl BitsCtx {
+    #[inline]
+    pub fn new(count: usize, order: BitOrder) -> Self {
+        BitsCtx { count, order }
+    }
+}
+
+impl<'a, const N: usize> TryFromCtx<'a, BitOrder> for [bool; N] {
+    type Error = error::Error;
+    #[inline]
+    fn try_from_ctx(src: &'a [u8], order: BitOrder) -> error::Result<(Self, usize)> {
+        let mut bools = [false; N];
+        let size = read_bits(src, &mut bools, order)?;
+        Ok((bools, size))
+    }
+}
+
+impl<const N: usize> SizeWith<BitOrder> for [bool; N] {
+    #[inline]
+    fn size_with(_: &BitOrder) -> usize {
+        bitmap_len(N)
+    }
+}
+
+impl<const N: usize> TryIntoCtx<BitOrder> for [bool; N] {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], order: BitOrder) -> error::Result<usize> {
+        write_bits(dst, &self, order)
+    }
+}
+
+impl<const N: usize> TryIntoCtx<BitOrder> for &[bool; N] {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], order: BitOrder) -> error::Result<usize> {
+        write_bits(dst, self, order)
+    }
+}
+
+impl TryIntoCtx<BitOrder> for &[bool] {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], order: BitOrder) -> error::Result<usize> {
+        write_bits(dst, self, order)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> TryFromCtx<'a, BitsCtx> for Vec<bool> {
+    type Error = error::Error;
+    fn try_from_ctx(src: &'a [u8], ctx: BitsCtx) -> error::Result<(Self, usize)> {
+        // check before allocating, since the count needn't be anywhere near the source's length
+        let size = bitmap_len(ctx.count);
+        if size > src.len() {
+            return Err(error::Error::Incomplete { needed: size - src.len() });
+        }
+        let mut bools = vec![false; ctx.count];
+        read_bits(src, &mut bools, ctx.order)?;
+        Ok((bools, size))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl TryIntoCtx<BitOrder> for Vec<bool> {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], order: BitOrder) -> error::Result<usize> {
+        write_bits(dst, &self, order)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl TryIntoCtx<BitOrder> for &Vec<bool> {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], order: BitOrder) -> error::Result<usize> {
+        write_bits(dst, self, order)
+    }
+}
+
+/// How an [AsciiNum](struct.AsciiNum.html) field is padded out to its full width
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AsciiPad {
+    /// Left-padded with `'0'`, and terminated by a NUL in the last byte of the field, as in tar headers
+    Zero,
+    /// Left-padded with spaces, filling the whole field
+    Space,
+    /// Left-padded with NULs, filling the whole field
+    Nul,
+}
+
+/// The parsing context for a number stored as fixed-width ASCII text in the given `radix`, e.g., the octal size field of a tar header
+///
+/// Reading ignores leading and trailing spaces and NULs regardless of the `pad` mode, and an entirely blank field is `0`;
+/// the `pad` mode decides how the number is laid out when writing. Digits above 9 are written in uppercase.
+///
+/// The number is read and written through the [Ascii](struct.Ascii.html) wrapper.
+///
+/// # Example
+/// ```rust
+/// use scroll::{Pread, Pwrite, ctx::{Ascii, AsciiNum, AsciiPad}};
+///
+/// let size = AsciiNum { radix: 8, len: 12, pad: AsciiPad::Zero };
+/// let mut field = [0u8; 12];
+/// field.pwrite_with(Ascii(1000u64), 0, size).unwrap();
+/// assert_eq!(&field, b"00000001750\0");
+/// let Ascii(n) = field.pread_with::<Ascii<u64>>(0, size).unwrap();
+/// assert_eq!(n, 1000);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct AsciiNum {
+    pub radix: u32,
+    pub len: usize,
+    pub pad: AsciiPad,
+}
+
+/// A `u64` or `i64` stored as ASCII text, read and written with an [AsciiNum](struct.AsciiNum.html)
+///
+/// The integers themselves only implement the binary [Endian](../enum.Endian.html) contexts, so that `pread` without a context keeps inferring one.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Ascii<T>(pub T);
+
+impl AsciiNum {
+    fn check_radix(&self) -> error::Result<()> {
+        if self.radix < 2 || self.radix > 36 {
+            Err(error::Error::BadInput { size: self.radix as usize, msg: "radix must be between 2 and 36" })
+        } else {
+            Ok(())
+        }
+    }
+    /// Returns the trimmed text of the field, and where it starts within the field
+    fn text<'a>(&self, src: &'a [u8]) -> error::Result<(&'a str, usize)> {
+        self.check_radix()?;
+        if self.len > src.len() {
+            return Err(error::Error::TooBig { size: self.len, len: src.len() });
+        }
+        let field = &src[..self.len];
+        let is_pad = |c: &u8| *c == NULL || *c == SPACE;
+        let start = field.iter().position(|c| !is_pad(c)).unwrap_or(self.len);
+        let end = field.iter().rposition(|c| !is_pad(c)).map_or(start, |last| last + 1);
+        match str::from_utf8(&field[start..end]) {
+            Ok(text) => Ok((text, start)),
+            Err(e) => Err(self.bad_number(field, start + e.valid_up_to())),
+        }
+    }
+    #[cfg(feature = "alloc")]
+    fn bad_number(&self, field: &[u8], at: usize) -> error::Error {
+        error::Error::Custom(format!("invalid base {} number {:?} at offset {} of a {} byte field",
+                                     self.radix, String::from_utf8_lossy(field), at, self.len))
+    }
+    #[cfg(not(feature = "alloc"))]
+    fn bad_number(&self, _field: &[u8], _at: usize) -> error::Error {
+        error::Error::BadInput { size: self.len, msg: "invalid ascii number" }
+    }
+    /// The offset of the first byte in the text that cannot be part of a number in this radix
+    fn first_invalid(&self, text: &str) -> usize {
+        text.char_indices()
+            .position(|(i, c)| !(c.is_digit(self.radix) || (i == 0 && (c == '-' || c == '+'))))
+            .unwrap_or(0)
+    }
+    fn write(&self, negative: bool, mut magnitude: u64, dst: &mut [u8]) -> error::Result<usize> {
+        self.check_radix()?;
+        if self.len > dst.len() {
+            return Err(error::Error::TooBig { size: self.len, len: dst.len() });
+        }
+        // 64 binary digits and a sign
+        let mut digits = [0u8; 65];
+        let mut start = digits.len();
+        loop {
+            let digit = (magnitude % u64::from(self.radix)) as u8;
+            start -= 1;
+            digits[start] = if digit < 10 { b'0' + digit } else { b'A' + digit - 10 };
+            magnitude /= u64::from(self.radix);
+            if magnitude == 0 { break }
+        }
+        let digits = &digits[start..];
+        let width = match self.pad {
+            AsciiPad::Zero => self.len.saturating_sub(1),
+            AsciiPad::Space | AsciiPad::Nul => self.len,
+        };
+        let size = digits.len() + negative as usize;
+        if size > width {
+            return Err(error::Error::TooBig { size, len: width });
+        }
+        let field = &mut dst[..self.len];
+        let (fill, mut at) = match self.pad {
+            AsciiPad::Zero => {
+                field[width..].iter_mut().for_each(|c| *c = NULL);
+                if negative { field[0] = b'-' }
+                (b'0', negative as usize)
+            },
+            AsciiPad::Space => (SPACE, 0),
+            AsciiPad::Nul => (NULL, 0),
+        };
+        let digits_start = width - digits.len();
+        while at < digits_start {
+            field[at] = fill;
+            at += 1;
+        }
+        if negative && self.pad != AsciiPad::Zero {
+            field[digits_start - 1] = b'-';
+        }
+        field[digits_start..width].copy_from_slice(digits);
+        Ok(self.len)
+    }
+}
+
+impl<'a> TryFromCtx<'a, AsciiNum> for Ascii<u64> {
+    type Error = error::Error;
+    fn try_from_ctx(src: &'a [u8], ctx: AsciiNum) -> result::Result<(Self, usize), Self::Error> {
+        let (text, start) = ctx.text(src)?;
+        if text.is_empty() {
+            return Ok((Ascii(0), ctx.len));
+        }
+        match u64::from_str_radix(text, ctx.radix) {
+            Ok(n) => Ok((Ascii(n), ctx.len)),
+            Err(_) => Err(ctx.bad_number(&src[..ctx.len], start + ctx.first_invalid(text))),
+        }
+    }
+}
+
+impl<'a> TryFromCtx<'a, AsciiNum> for Ascii<i64> {
+    type Error = error::Error;
+    fn try_from_ctx(src: &'a [u8], ctx: AsciiNum) -> result::Result<(Self, usize), Self::Error> {
+        let (text, start) = ctx.text(src)?;
+        if text.is_empty() {
+            return Ok((Ascii(0), ctx.len));
+        }
+        match i64::from_str_radix(text, ctx.radix) {
+            Ok(n) => Ok((Ascii(n), ctx.len)),
+            Err(_) => Err(ctx.bad_number(&src[..ctx.len], start + ctx.first_invalid(text))),
+        }
+    }
+}
+
+impl TryIntoCtx<AsciiNum> for Ascii<u64> {
+    type Error = error::Error;
+    fn try_into_ctx(self, dst: &mut [u8], ctx: AsciiNum) -> error::Result<usize> {
+        ctx.write(false, self.0, dst)
+    }
+}
+
+impl TryIntoCtx<AsciiNum> for Ascii<i64> {
+    type Error = error::Error;
+    fn try_into_ctx(self, dst: &mut [u8], ctx: AsciiNum) -> error::Result<usize> {
+        ctx.write(self.0 < 0, self.0.unsigned_abs(), dst)
+    }
+}
+
+/// The parsing context for a block-compressed payload, stored as a `u32` compressed size followed by that many compressed bytes
+///
+/// Reading a `Vec<u8>` with an `LzCtx` hands the compressed bytes to `decompress` (e.g., an LZ4 or Snappy block decoder), and
+/// returns whatever it produced; the whole compressed block is consumed.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx::LzCtx, Pread, LE};
+///
+/// // a "decompressor" which just repeats each byte
+/// fn double(compressed: &[u8]) -> scroll::Result<Vec<u8>> {
+///     Ok(compressed.iter().flat_map(|b| vec![*b, *b]).collect())
+/// }
+/// let bytes = [2, 0, 0, 0, 0xa, 0xb, 0xff];
+/// let ctx = LzCtx { endian: LE, decompress: double };
+/// let offset = &mut 0;
+/// let data: Vec<u8> = bytes.gread_with(offset, ctx).unwrap();
+/// assert_eq!(data, [0xa, 0xa, 0xb, 0xb]);
+/// assert_eq!(*offset, 6);
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Debug, Copy, Clone)]
+pub struct LzCtx<D = fn(&[u8]) -> error::Result<Vec<u8>>> {
+    /// The byte order of the compressed size
+    pub endian: Endian,
+    /// Decompresses a block, erroring if it is malformed
+    pub decompress: D,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, D> TryFromCtx<'a, LzCtx<D>> for Vec<u8>
+    where D: Fn(&[u8]) -> error::Result<Vec<u8>> + Copy
+{
+    type Error = error::Error;
+    fn try_from_ctx(src: &'a [u8], ctx: LzCtx<D>) -> result::Result<(Self, usize), Self::Error> {
+        use crate::Pread;
+        let offset = &mut 0;
+        let size = src.gread_with::<u32>(offset, ctx.endian)? as usize;
+        let compressed = src.gread_with::<&[u8]>(offset, size)?;
+        let data = (ctx.decompress)(compressed)?;
+        Ok((data, *offset))
+    }
+}
+
+/// The parsing context for an encrypted, or obfuscated, field of `len` bytes: the bytes are copied out, decrypted in place by
+/// `cipher`, and the plaintext is then parsed with `inner`
+///
+/// The value is read through the [Encrypted](struct.Encrypted.html) wrapper, and all `len` bytes are consumed, whether or
+/// not the value's own parse used every one of them. As the source is immutable, every read allocates a buffer of `len`
+/// bytes for the plaintext, which also means the value cannot borrow from it.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx::{Encrypted, EncryptedCtx}, Pread, LE};
+///
+/// let xor = |bytes: &mut [u8]| bytes.iter_mut().for_each(|b| *b ^= 0x42);
+/// let bytes = [0x42 ^ 0xef, 0x42 ^ 0xbe, 0x42 ^ 0xad, 0x42 ^ 0xde];
+/// let Encrypted(n) = bytes.pread_with::<Encrypted<u32>>(0, EncryptedCtx { len: 4, cipher: xor, inner: LE }).unwrap();
+/// assert_eq!(n, 0xdeadbeef);
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Debug, Copy, Clone)]
+pub struct EncryptedCtx<C, Inner> {
+    /// The length of the encrypted field
+    pub len: usize,
+    /// Decrypts the field in place
+    pub cipher: C,
+    /// The context the plaintext is parsed with
+    pub inner: Inner,
+}
+
+/// A value which is stored encrypted; see [EncryptedCtx](struct.EncryptedCtx.html)
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Encrypted<T>(pub T);
+
+#[cfg(feature = "alloc")]
+impl<'a, T, C, Inner, E> TryFromCtx<'a, EncryptedCtx<C, Inner>> for Encrypted<T>
+    where T: for<'b> TryFromCtx<'b, Inner, Error = E>,
+          C: Fn(&mut [u8]) + Copy,
+          Inner: Copy,
+          E: From<error::Error>,
+{
+    type Error = E;
+    fn try_from_ctx(src: &'a [u8], ctx: EncryptedCtx<C, Inner>) -> result::Result<(Self, usize), Self::Error> {
+        if ctx.len > src.len() {
+            return Err(error::Error::Incomplete { needed: ctx.len - src.len() }.into());
+        }
+        let mut plaintext = src[..ctx.len].to_vec();
+        (ctx.cipher)(&mut plaintext);
+        let (value, _) = T::try_from_ctx(&plaintext, ctx.inner)?;
+        Ok((Encrypted(value), ctx.len))
+    }
+}
+
+/// A type, usually a field-less enum, which is stored as a raw integer tag of type `Repr`
+///
+/// Implementing `Tag` lets the type be read and written directly with a [TagCtx](struct.TagCtx.html).
+pub trait Tag: Sized {
+    /// The raw integer the tag is stored as, e.g., `u16`
+    type Repr;
+    /// Maps the raw tag onto `Self`, or `None` if the tag is unknown
+    fn from_repr(repr: Self::Repr) -> Option<Self>;
+    /// The raw tag for `self`
+    fn to_repr(&self) -> Self::Repr;
+}
+
+/// The parsing context for a [Tag](trait.Tag.html): reads a `Repr` with the given `endian`, and maps it onto the tag type
+///
+/// Unknown tags are an error which reports the raw value.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx::{Tag, TagCtx}, Pread, Pwrite, LE};
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Compression {
+///     None,
+///     Deflate,
+/// }
+///
+/// impl Tag for Compression {
+///     type Repr = u16;
+///     fn from_repr(repr: u16) -> Option<Self> {
+///         match repr {
+///             0 => Some(Compression::None),
+///             8 => Some(Compression::Deflate),
+///             _ => None,
+///         }
+///     }
+///     fn to_repr(&self) -> u16 {
+///         match *self {
+///             Compression::None => 0,
+///             Compression::Deflate => 8,
+///         }
+///     }
+/// }
+///
+/// let ctx = TagCtx::<u16>::new(LE);
+/// let bytes = [0x08, 0x00, 0x0c, 0x00];
+/// assert_eq!(bytes.pread_with::<Compression>(0, ctx).unwrap(), Compression::Deflate);
+/// assert!(bytes.pread_with::<Compression>(2, ctx).is_err());
+///
+/// let mut out = [0xffu8; 2];
+/// out.pwrite_with(Compression::None, 0, ctx).unwrap();
+/// assert_eq!(out, [0, 0]);
+/// ```
+pub struct TagCtx<R> {
+    pub endian: Endian,
+    repr: PhantomData<R>,
+}
+
+impl<R> TagCtx<R> {
+    #[inline]
+    pub fn new(endian: Endian) -> Self {
+        TagCtx { endian, repr: PhantomData }
+    }
+}
+
+impl<R> From<Endian> for TagCtx<R> {
+    #[inline]
+    fn from(endian: Endian) -> Self {
+        TagCtx::new(endian)
+    }
+}
+
+impl<R> Default for TagCtx<R> {
+    #[inline]
+    fn default() -> Self {
+        TagCtx::new(Endian::default())
+    }
+}
+
+// manual impls, so that `R` need not be `Copy` or `Debug` itself
+impl<R> Copy for TagCtx<R> {}
+
+impl<R> Clone for TagCtx<R> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<R> fmt::Debug for TagCtx<R> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("TagCtx").field("endian", &self.endian).finish()
+    }
+}
+
+#[cfg(feature = "alloc")]
+fn unknown_tag<T, R: fmt::Debug>(repr: R) -> error::Error {
+    error::Error::Custom(format!("unknown {} tag {:?}", core::any::type_name::<T>(), repr))
+}
+
+#[cfg(not(feature = "alloc"))]
+#[allow(clippy::extra_unused_type_parameters)]
+fn unknown_tag<T, R: fmt::Debug>(_repr: R) -> error::Error {
+    error::Error::BadInput { size: size_of::<R>(), msg: "unknown tag" }
+}
+
+impl<'a, T> TryFromCtx<'a, TagCtx<T::Repr>> for T
+    where T: Tag + 'a,
+          T::Repr: TryFromCtx<'a, Endian, Error = error::Error> + Copy + fmt::Debug,
+{
+    type Error = error::Error;
+    #[inline]
+    fn try_from_ctx(src: &'a [u8], ctx: TagCtx<T::Repr>) -> result::Result<(Self, usize), Self::Error> {
+        let (repr, size) = T::Repr::try_from_ctx(src, ctx.endian)?;
+        match T::from_repr(repr) {
+            Some(tag) => Ok((tag, size)),
+            None => Err(unknown_tag::<T, _>(repr)),
+        }
+    }
+}
+
+impl<T> TryIntoCtx<TagCtx<T::Repr>> for T
+    where T: Tag,
+          T::Repr: TryIntoCtx<Endian, Error = error::Error>,
+{
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], ctx: TagCtx<T::Repr>) -> error::Result<usize> {
+        self.to_repr().try_into_ctx(dst, ctx.endian)
+    }
+}
+
+/// A field which is only present from some version of a format onwards, read and written with a [VersionedFieldCtx](struct.VersionedFieldCtx.html)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct VersionedField<T, V> {
+    /// The field's value, or `None` if the format version predates it
+    pub value: Option<T>,
+    phantom: PhantomData<V>,
+}
+
+impl<T, V> VersionedField<T, V> {
+    #[inline]
+    pub fn new(value: Option<T>) -> Self {
+        VersionedField { value, phantom: PhantomData }
+    }
+}
+
+impl<T, V> From<VersionedField<T, V>> for Option<T> {
+    #[inline]
+    fn from(field: VersionedField<T, V>) -> Self {
+        field.value
+    }
+}
+
+/// The parsing context for a [VersionedField](struct.VersionedField.html): the field is only parsed (with `inner`) if `version >= min_version`
+///
+/// Otherwise the field is `None`, and consumes no bytes, so an absent field may also sit at the very end of the source.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx::{VersionedField, VersionedFieldCtx}, Pread, LE};
+///
+/// let bytes = [0xef, 0xbe, 0xad, 0xde];
+/// let ctx = VersionedFieldCtx { version: 1, min_version: 2, inner: LE };
+/// let field: VersionedField<u32, u8> = bytes.pread_with(0, ctx).unwrap();
+/// assert_eq!(field.value, None);
+/// let ctx = VersionedFieldCtx { version: 2, ..ctx };
+/// let field: VersionedField<u32, u8> = bytes.pread_with(0, ctx).unwrap();
+/// assert_eq!(field.value, Some(0xdeadbeef));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct VersionedFieldCtx<V, Ctx> {
+    /// The version of the format being parsed
+    pub version: V,
+    /// The first version which has this field
+    pub min_version: V,
+    /// The context the field itself is parsed with
+    pub inner: Ctx,
+}
+
+impl<V: PartialOrd, Ctx> VersionedFieldCtx<V, Ctx> {
+    /// Is the field present in this version?
+    #[inline]
+    pub fn is_present(&self) -> bool {
+        self.version >= self.min_version
+    }
+}
+
+impl<'a, T, V, Ctx, E> TryFromCtx<'a, VersionedFieldCtx<V, Ctx>> for VersionedField<T, V>
+    where T: TryFromCtx<'a, Ctx, Error = E>,
+          V: PartialOrd + Copy + 'a,
+          Ctx: Copy,
+{
+    type Error = E;
+    #[inline]
+    fn try_from_ctx(src: &'a [u8], ctx: VersionedFieldCtx<V, Ctx>) -> result::Result<(Self, usize), Self::Error> {
+        if ctx.is_present() {
+            let (value, size) = T::try_from_ctx(src, ctx.inner)?;
+            Ok((VersionedField::new(Some(value)), size))
+        } else {
+            Ok((VersionedField::new(None), 0))
+        }
+    }
+}
+
+/// Writing a field which is missing in a version that has it, or which is present in a version that does not, is an error
+impl<T, V, Ctx, E> TryIntoCtx<VersionedFieldCtx<V, Ctx>> for VersionedField<T, V>
+    where T: TryIntoCtx<Ctx, Error = E>,
+          V: PartialOrd + Copy,
+          Ctx: Copy,
+          E: From<error::Error>,
+{
+    type Error = E;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], ctx: VersionedFieldCtx<V, Ctx>) -> result::Result<usize, Self::Error> {
+        match (self.value, ctx.is_present()) {
+            (Some(value), true) => value.try_into_ctx(dst, ctx.inner),
+            (None, false) => Ok(0),
+            (Some(_), false) => Err(error::Error::BadInput { size: 0, msg: "field is not present in this version" }.into()),
+            (None, true) => Err(error::Error::BadInput { size: 0, msg: "field is required in this version" }.into()),
+        }
+    }
+}
+
+/// The context for a value whose layout is chosen at runtime: `cond` is called each time the value is read or written,
+/// and it is read or written with `then_ctx` if it returns `true`, and with `else_ctx` otherwise
+///
+/// Both contexts produce the same type, e.g., an offset stored in 32 or 64 bits depending on the target, or a field
+/// whose encoding depends on a feature flag. The value is read and written through the [Chosen](struct.Chosen.html)
+/// wrapper.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx::{Chosen, CondCtx}, Pread, BE, LE};
+///
+/// let bytes = [0x12, 0x34];
+/// let big_endian = true;
+/// let ctx = CondCtx { cond: || big_endian, then_ctx: BE, else_ctx: LE };
+/// let Chosen(n) = bytes.pread_with::<Chosen<u16>>(0, ctx).unwrap();
+/// assert_eq!(n, 0x1234);
+/// let ctx = CondCtx { then_ctx: LE, else_ctx: BE, ..ctx };
+/// assert_eq!(bytes.pread_with::<Chosen<u16>>(0, ctx).unwrap(), Chosen(0x3412));
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct CondCtx<Cond, T, E> {
+    /// Whether to use `then_ctx`, asked for each value
+    pub cond: Cond,
+    /// The context when `cond` returns `true`
+    pub then_ctx: T,
+    /// The context when `cond` returns `false`
+    pub else_ctx: E,
+}
+
+/// A value read or written with a [CondCtx](struct.CondCtx.html)
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Chosen<N>(pub N);
+
+impl<'a, N, Cond, T, E, Error> TryFromCtx<'a, CondCtx<Cond, T, E>> for Chosen<N>
+    where N: TryFromCtx<'a, T, Error = Error> + TryFromCtx<'a, E, Error = Error>,
+          Cond: Fn() -> bool + Copy,
+          T: Copy,
+          E: Copy,
+{
+    type Error = Error;
+    #[inline]
+    fn try_from_ctx(src: &'a [u8], ctx: CondCtx<Cond, T, E>) -> result::Result<(Self, usize), Self::Error> {
+        let (n, size) = if (ctx.cond)() {
+            <N as TryFromCtx<'a, T>>::try_from_ctx(src, ctx.then_ctx)?
+        } else {
+            <N as TryFromCtx<'a, E>>::try_from_ctx(src, ctx.else_ctx)?
+        };
+        Ok((Chosen(n), size))
+    }
+}
+
+impl<N, Cond, T, E, Error> TryIntoCtx<CondCtx<Cond, T, E>> for Chosen<N>
+    where N: TryIntoCtx<T, Error = Error> + TryIntoCtx<E, Error = Error>,
+          Cond: Fn() -> bool + Copy,
+          T: Copy,
+          E: Copy,
+{
+    type Error = Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], ctx: CondCtx<Cond, T, E>) -> result::Result<usize, Self::Error> {
+        if (ctx.cond)() {
+            <N as TryIntoCtx<T>>::try_into_ctx(self.0, dst, ctx.then_ctx)
+        } else {
+            <N as TryIntoCtx<E>>::try_into_ctx(self.0, dst, ctx.else_ctx)
+        }
+    }
+}
+
+/// The parsing context for a byte string terminated by `sentinel`, in which `escape_byte` makes the byte following it a literal
+///
+/// Reading a `Vec<u8>` unescapes the bytes up to the sentinel, and consumes the sentinel too; a missing sentinel is a `BadOffset` error.
+/// Writing a `Vec<u8>` escapes every escape byte and sentinel in it, and appends the sentinel.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx::EscapedCtx, Pread, Pwrite};
+///
+/// let ctx = EscapedCtx { escape_byte: b'\\', sentinel: b'"' };
+/// let bytes = br#"say \"hi\"" rest"#;
+/// let offset = &mut 0;
+/// let unescaped: Vec<u8> = bytes.gread_with(offset, ctx).unwrap();
+/// assert_eq!(unescaped, br#"say "hi""#);
+/// assert_eq!(*offset, 11);
+///
+/// let mut out = [0u8; 11];
+/// assert_eq!(out.pwrite_with(unescaped, 0, ctx).unwrap(), 11);
+/// assert_eq!(&out, &bytes[..11]);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct EscapedCtx {
+    pub escape_byte: u8,
+    pub sentinel: u8,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> TryFromCtx<'a, EscapedCtx> for Vec<u8> {
+    type Error = error::Error;
+    fn try_from_ctx(src: &'a [u8], ctx: EscapedCtx) -> result::Result<(Self, usize), Self::Error> {
+        let mut bytes = Vec::new();
+        let mut iter = src.iter().enumerate();
+        while let Some((i, &byte)) = iter.next() {
+            if byte == ctx.escape_byte {
+                match iter.next() {
+                    Some((_, &literal)) => bytes.push(literal),
+                    None => break,
+                }
+            } else if byte == ctx.sentinel {
+                return Ok((bytes, i + 1));
+            } else {
+                bytes.push(byte);
+            }
+        }
+        Err(error::Error::BadOffset(src.len()))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl EscapedCtx {
+    fn escape(&self, bytes: &[u8], dst: &mut [u8]) -> error::Result<usize> {
+        let size = bytes.iter().filter(|b| **b == self.escape_byte || **b == self.sentinel).count() + bytes.len() + 1;
+        if size > dst.len() {
+            return Err(error::Error::TooBig { size, len: dst.len() });
+        }
+        let mut i = 0;
+        for &byte in bytes {
+            if byte == self.escape_byte || byte == self.sentinel {
+                dst[i] = self.escape_byte;
+                i += 1;
+            }
+            dst[i] = byte;
+            i += 1;
+        }
+        dst[i] = self.sentinel;
+        Ok(size)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl TryIntoCtx<EscapedCtx> for Vec<u8> {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], ctx: EscapedCtx) -> error::Result<usize> {
+        ctx.escape(&self, dst)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl TryIntoCtx<EscapedCtx> for &Vec<u8> {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], ctx: EscapedCtx) -> error::Result<usize> {
+        ctx.escape(self, dst)
+    }
+}
+
+/// The parsing context for a string framed by the [StrCtx](enum.StrCtx.html), whose invalid UTF-8 is replaced with U+FFFD
+/// rather than being an error, as with `String::from_utf8_lossy`
+///
+/// The frame is found first, so a multi-byte character cut off by its end is replaced too, rather than read past it.
+/// See the `Cow<str>` impls below for when the string is borrowed.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Utf8Lossy(pub StrCtx);
+
+/// Borrows the string from the source; every `StrCtx` mode borrows, including the trimmed `Fixed` field
+///
+/// # Example
+/// ```rust
+/// use std::borrow::Cow;
+/// use scroll::{ctx::{StrCtx, Utf8Lossy}, Pread};
+///
+/// let bytes = b"ok\0bad\xff\0";
+/// let ok: Cow<str> = bytes.pread_with(0, StrCtx::Delimiter(0)).unwrap();
+/// assert!(matches!(ok, Cow::Borrowed("ok")));
+/// // with Utf8Lossy, only a string which needed replacements is owned
+/// let ok: Cow<str> = bytes.pread_with(0, Utf8Lossy(StrCtx::Delimiter(0))).unwrap();
+/// assert!(matches!(ok, Cow::Borrowed("ok")));
+/// let bad: Cow<str> = bytes.pread_with(3, Utf8Lossy(StrCtx::Delimiter(0))).unwrap();
+/// assert!(matches!(bad, Cow::Owned(ref s) if s == "bad\u{fffd}"));
+/// ```
+#[cfg(feature = "alloc")]
+impl<'a> TryFromCtx<'a, StrCtx> for Cow<'a, str> {
+    type Error = error::Error;
+    #[inline]
+    fn try_from_ctx(src: &'a [u8], ctx: StrCtx) -> error::Result<(Self, usize)> {
+        let (s, size) = <&str>::try_from_ctx(src, ctx)?;
+        Ok((Cow::Borrowed(s), size))
+    }
+}
+
+/// Borrows the string from the source if it is valid UTF-8, and owns it if anything had to be replaced
+#[cfg(feature = "alloc")]
+impl<'a> TryFromCtx<'a, Utf8Lossy> for Cow<'a, str> {
+    type Error = error::Error;
+    #[inline]
+    fn try_from_ctx(src: &'a [u8], Utf8Lossy(ctx): Utf8Lossy) -> error::Result<(Self, usize)> {
+        let (bytes, size) = ctx.frame(src)?;
+        Ok((String::from_utf8_lossy(bytes), size))
+    }
+}
+
+/// Borrows the `len` bytes from the source
+#[cfg(feature = "alloc")]
+impl<'a> TryFromCtx<'a, usize> for Cow<'a, [u8]> {
+    type Error = error::Error;
+    #[inline]
+    fn try_from_ctx(src: &'a [u8], len: usize) -> error::Result<(Self, usize)> {
+        let (bytes, size) = <&[u8]>::try_from_ctx(src, len)?;
+        Ok((Cow::Borrowed(bytes), size))
+    }
+}
+
+/// Borrows the bytes from the source if there is nothing to unescape before the sentinel, and owns the unescaped bytes otherwise
+///
+/// # Example
+/// ```rust
+/// use std::borrow::Cow;
+/// use scroll::{ctx::EscapedCtx, Pread};
+///
+/// let ctx = EscapedCtx { escape_byte: b'\\', sentinel: b';' };
+/// let bytes = br"plain;esc\;aped;";
+/// let offset = &mut 0;
+/// let plain: Cow<[u8]> = bytes.gread_with(offset, ctx).unwrap();
+/// assert!(matches!(plain, Cow::Borrowed(b"plain")));
+/// let escaped: Cow<[u8]> = bytes.gread_with(offset, ctx).unwrap();
+/// assert!(matches!(escaped, Cow::Owned(ref bytes) if bytes == b"esc;aped"));
+/// ```
+#[cfg(feature = "alloc")]
+impl<'a> TryFromCtx<'a, EscapedCtx> for Cow<'a, [u8]> {
+    type Error = error::Error;
+    fn try_from_ctx(src: &'a [u8], ctx: EscapedCtx) -> error::Result<(Self, usize)> {
+        match src.iter().position(|b| *b == ctx.escape_byte || *b == ctx.sentinel) {
+            Some(i) if src[i] == ctx.sentinel => Ok((Cow::Borrowed(&src[..i]), i + 1)),
+            _ => {
+                let (bytes, size) = Vec::try_from_ctx(src, ctx)?;
+                Ok((Cow::Owned(bytes), size))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> TryIntoCtx<StrCtx> for Cow<'a, str> {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], ctx: StrCtx) -> error::Result<usize> {
+        Str(&self).try_into_ctx(dst, ctx)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> TryIntoCtx for Cow<'a, [u8]> {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], _ctx: ()) -> error::Result<usize> {
+        (&*self).try_into_ctx(dst, ())
+    }
+}
+
+/// The parsing context for a value whose bytes are transformed (e.g., XOR-obfuscated or byte-reversed) before being parsed with `inner`
+///
+/// Reading copies the value's bytes (as many as its [SizeWith](trait.SizeWith.html) says, at most 256) into a scratch buffer, applies `transform`
+/// to the copy, and parses it with `inner`; the source is never modified. Writing writes with `inner`, then applies `transform` to the written bytes.
+/// So `transform` decodes when reading and encodes when writing, and a transform which is its own inverse, like an XOR mask, round-trips with a single context.
+///
+/// The value is read and written through the [Transformed](struct.Transformed.html) wrapper.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx::{TransformCtx, Transformed}, Pread, Pwrite, BE};
+///
+/// let ctx = TransformCtx { inner: BE, transform: |bytes: &mut [u8]| bytes.reverse() };
+/// let bytes = [0xef, 0xbe, 0xad, 0xde];
+/// let Transformed(n) = bytes.pread_with::<Transformed<u32>>(0, ctx).unwrap();
+/// assert_eq!(n, 0xdeadbeef);
+/// let mut out = [0u8; 4];
+/// out.pwrite_with(Transformed(0xdeadbeef_u32), 0, ctx).unwrap();
+/// assert_eq!(out, bytes);
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct TransformCtx<Inner, F> {
+    pub inner: Inner,
+    pub transform: F,
+}
+
+/// A value read or written with a [TransformCtx](struct.TransformCtx.html)
+///
+/// A blanket impl for every `T` would make the contexts of ordinary reads impossible to infer, hence the wrapper.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Transformed<T>(pub T);
+
+impl<'a, T, Inner, F, E> TryFromCtx<'a, TransformCtx<Inner, F>> for Transformed<T>
+    where T: for<'b> TryFromCtx<'b, Inner, Error = E> + SizeWith<Inner> + 'a,
+          Inner: Copy,
+          F: Fn(&mut [u8]) + Copy,
+          E: From<error::Error>,
+{
+    type Error = E;
+    fn try_from_ctx(src: &'a [u8], ctx: TransformCtx<Inner, F>) -> result::Result<(Self, usize), Self::Error> {
+        let mut scratch = [0u8; 256];
+        let size = T::size_with(&ctx.inner);
+        if size > scratch.len() {
+            return Err(error::Error::BadInput { size, msg: "transformed value is larger than 256 bytes" }.into());
+        }
+        if size > src.len() {
+            return Err(error::Error::TooBig { size, len: src.len() }.into());
+        }
+        let buf = &mut scratch[..size];
+        buf.copy_from_slice(&src[..size]);
+        (ctx.transform)(buf);
+        let (t, _) = T::try_from_ctx(buf, ctx.inner)?;
+        Ok((Transformed(t), size))
+    }
+}
+
+impl<T, Inner, F, E> TryIntoCtx<TransformCtx<Inner, F>> for Transformed<T>
+    where T: TryIntoCtx<Inner, Error = E>,
+          Inner: Copy,
+          F: Fn(&mut [u8]) + Copy,
+{
+    type Error = E;
+    fn try_into_ctx(self, dst: &mut [u8], ctx: TransformCtx<Inner, F>) -> result::Result<usize, Self::Error> {
+        let size = self.0.try_into_ctx(dst, ctx.inner)?;
+        (ctx.transform)(&mut dst[..size]);
+        Ok(size)
+    }
+}
+
+/// A value which is checked with its [Validate](trait.Validate.html) implementation after being read, with the same context
+///
+/// Useful where a validated value is read generically, e.g. as an element of an [IterCtx](struct.IterCtx.html).
+/// As `try_from_ctx` doesn't know where its source starts, the offset of the `Error::Invalid` is `0`, i.e., relative to the value itself;
+/// prefer [Pread::pread_validated](../trait.Pread.html#method.pread_validated) where the absolute offset matters.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx::{self, Validate, Validated}, Endian, Error, Pread, LE};
+///
+/// struct Header { len: u16, header_len: u16 }
+///
+/// impl<'a> ctx::TryFromCtx<'a, Endian> for Header {
+///     type Error = Error;
+///     fn try_from_ctx(src: &'a [u8], endian: Endian) -> Result<(Self, usize), Self::Error> {
+///         let offset = &mut 0;
+///         Ok((Header { len: src.gread_with(offset, endian)?, header_len: src.gread_with(offset, endian)? }, *offset))
+///     }
+/// }
+///
+/// impl Validate<Endian> for Header {
+///     fn validate(&self, _: &Endian) -> Result<(), &'static str> {
+///         if self.len < self.header_len { Err("len is smaller than header_len") } else { Ok(()) }
+///     }
+/// }
+///
+/// let Validated(header) = [8, 0, 4, 0].pread_with::<Validated<Header>>(0, LE).unwrap();
+/// assert_eq!(header.len, 8);
+/// assert!(matches!([2, 0, 4, 0].pread_with::<Validated<Header>>(0, LE), Err(Error::Invalid { .. })));
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Validated<T>(pub T);
+
+impl<'a, T, Ctx> TryFromCtx<'a, Ctx> for Validated<T>
+    where T: TryFromCtx<'a, Ctx> + Validate<Ctx>,
+          T::Error: From<error::Error>,
+          Ctx: Copy,
+{
+    type Error = T::Error;
+    #[inline]
+    fn try_from_ctx(src: &'a [u8], ctx: Ctx) -> result::Result<(Self, usize), Self::Error> {
+        let (value, size) = T::try_from_ctx(src, ctx)?;
+        value.validate(&ctx).map_err(|msg| error::Error::Invalid { offset: 0, msg })?;
+        Ok((Validated(value), size))
+    }
+}
+
+/// A header which knows the length, in bytes, of the payload following it; see [SplitCtx](struct.SplitCtx.html)
+pub trait HasLen {
+    /// The length of the payload following this header
+    fn payload_len(&self) -> usize;
+}
+
+/// The parsing context for reading a header `H`, and then the payload `P` whose extent the header gives, as an `(H, P)` pair
+///
+/// The header is read with `ctx`, and the payload with `(len, ctx)`, from a window of exactly the `len` bytes following the header,
+/// where `len` is the header's [HasLen::payload_len](trait.HasLen.html#tymethod.payload_len). The pair consumes the header and all of the payload,
+/// whether or not the payload's own parse used every byte; a source which ends mid-payload is an `Error::Incomplete`.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx::{self, HasLen, SplitCtx}, Endian, Error, Pread, BE};
+///
+/// struct Header { kind: u8, len: u16 }
+///
+/// impl<'a> ctx::TryFromCtx<'a, Endian> for Header {
+///     type Error = Error;
+///     fn try_from_ctx(src: &'a [u8], endian: Endian) -> Result<(Self, usize), Self::Error> {
+///         let offset = &mut 0;
+///         Ok((Header { kind: src.gread(offset)?, len: src.gread_with(offset, endian)? }, *offset))
+///     }
+/// }
+///
+/// impl HasLen for Header {
+///     fn payload_len(&self) -> usize { self.len as usize }
+/// }
+///
+/// struct Payload<'a>(&'a [u8]);
+///
+/// impl<'a> ctx::TryFromCtx<'a, (usize, Endian)> for Payload<'a> {
+///     type Error = Error;
+///     fn try_from_ctx(src: &'a [u8], (len, _): (usize, Endian)) -> Result<(Self, usize), Self::Error> {
+///         Ok((Payload(src.pread_with(0, len)?), len))
+///     }
+/// }
+///
+/// let bytes = [0x01, 0x00, 0x02, 0xca, 0xfe, 0xff];
+/// let offset = &mut 0;
+/// let (header, payload): (Header, Payload) = bytes.gread_with(offset, SplitCtx::new(BE)).unwrap();
+/// assert_eq!(header.kind, 1);
+/// assert_eq!(payload.0, &[0xca, 0xfe]);
+/// assert_eq!(*offset, 5);
+/// ```
+pub struct SplitCtx<H, P, Ctx> {
+    /// The context the header, and then the payload, are read with
+    pub ctx: Ctx,
+    parts: PhantomData<fn() -> (H, P)>,
+}
+
+impl<H, P, Ctx> SplitCtx<H, P, Ctx> {
+    #[inline]
+    pub fn new(ctx: Ctx) -> Self {
+        SplitCtx { ctx, parts: PhantomData }
+    }
+}
+
+impl<H, P, Ctx> From<Ctx> for SplitCtx<H, P, Ctx> {
+    #[inline]
+    fn from(ctx: Ctx) -> Self {
+        SplitCtx::new(ctx)
+    }
+}
+
+// manual impls, so that `H` and `P` need not be `Copy` or `Debug` themselves
+impl<H, P, Ctx: Copy> Copy for SplitCtx<H, P, Ctx> {}
+
+impl<H, P, Ctx: Copy> Clone for SplitCtx<H, P, Ctx> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<H, P, Ctx: fmt::Debug> fmt::Debug for SplitCtx<H, P, Ctx> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("SplitCtx").field("ctx", &self.ctx).finish()
+    }
+}
+
+impl<'a, H, P, Ctx, E> TryFromCtx<'a, SplitCtx<H, P, Ctx>> for (H, P)
+    where H: TryFromCtx<'a, Ctx, Error = E> + HasLen,
+          P: TryFromCtx<'a, (usize, Ctx), Error = E>,
+          E: From<error::Error>,
+          Ctx: Copy,
+{
+    type Error = E;
+    fn try_from_ctx(src: &'a [u8], ctx: SplitCtx<H, P, Ctx>) -> result::Result<(Self, usize), Self::Error> {
+        let (header, header_size) = H::try_from_ctx(src, ctx.ctx)?;
+        let len = header.payload_len();
+        let rest = src.get(header_size..).ok_or(error::Error::BadOffset(header_size))?;
+        if len > rest.len() {
+            return Err(error::Error::Incomplete { needed: len - rest.len() }.into());
+        }
+        let (payload, _) = P::try_from_ctx(&rest[..len], (len, ctx.ctx))?;
+        Ok(((header, payload), header_size + len))
+    }
+}
+
+/// The parsing context for a value whose context depends on a tag at its start: the tag `K` is peeked with `tag_ctx`,
+/// `dispatch` maps it to the context `Ctx`, and the value is then parsed with that context from the same offset, tag included
+///
+/// The value is read through the [LookAhead](struct.LookAhead.html) wrapper.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx::{self, LookAhead, LookAheadCtx}, Error, Pread, LE};
+///
+/// #[derive(Copy, Clone)]
+/// enum Kind { Control, Data }
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Packet<'a> { Control(u16), Data(&'a [u8]) }
+///
+/// impl<'a> ctx::TryFromCtx<'a, Kind> for Packet<'a> {
+///     type Error = Error;
+///     fn try_from_ctx(src: &'a [u8], kind: Kind) -> Result<(Self, usize), Self::Error> {
+///         // the tag is still there to skip
+///         let offset = &mut 1;
+///         let packet = match kind {
+///             Kind::Control => Packet::Control(src.gread_with(offset, LE)?),
+///             Kind::Data => {
+///                 let len: u8 = src.gread(offset)?;
+///                 Packet::Data(src.gread_with(offset, len as usize)?)
+///             }
+///         };
+///         Ok((packet, *offset))
+///     }
+/// }
+///
+/// let ctx = LookAheadCtx::new(LE, |tag: u8| if tag == 0 { Kind::Control } else { Kind::Data });
+/// let LookAhead(packet) = [0x00, 0x01, 0x02].pread_with::<LookAhead<Packet>>(0, ctx).unwrap();
+/// assert_eq!(packet, Packet::Control(0x0201));
+/// ```
+pub struct LookAheadCtx<K, Ctx, KCtx = Endian> {
+    /// The context the tag is peeked with
+    pub tag_ctx: KCtx,
+    /// Maps the tag to the context the value is parsed with
+    pub dispatch: fn(K) -> Ctx,
+}
+
+impl<K, Ctx, KCtx> LookAheadCtx<K, Ctx, KCtx> {
+    #[inline]
+    pub fn new(tag_ctx: KCtx, dispatch: fn(K) -> Ctx) -> Self {
+        LookAheadCtx { tag_ctx, dispatch }
+    }
+}
+
+// manual impls, so that `K` and `Ctx` need not be `Copy` or `Debug` themselves
+impl<K, Ctx, KCtx: Copy> Copy for LookAheadCtx<K, Ctx, KCtx> {}
+
+impl<K, Ctx, KCtx: Copy> Clone for LookAheadCtx<K, Ctx, KCtx> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K, Ctx, KCtx: fmt::Debug> fmt::Debug for LookAheadCtx<K, Ctx, KCtx> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("LookAheadCtx").field("tag_ctx", &self.tag_ctx).finish()
+    }
+}
+
+/// A value read with a [LookAheadCtx](struct.LookAheadCtx.html)
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct LookAhead<T>(pub T);
+
+impl<'a, T, K, Ctx, KCtx, E> TryFromCtx<'a, LookAheadCtx<K, Ctx, KCtx>> for LookAhead<T>
+    where T: TryFromCtx<'a, Ctx, Error = E>,
+          K: TryFromCtx<'a, KCtx, Error = E>,
+          Ctx: Copy,
+          KCtx: Copy,
+{
+    type Error = E;
+    #[inline]
+    fn try_from_ctx(src: &'a [u8], ctx: LookAheadCtx<K, Ctx, KCtx>) -> result::Result<(Self, usize), Self::Error> {
+        let (tag, _) = K::try_from_ctx(src, ctx.tag_ctx)?;
+        let (value, size) = T::try_from_ctx(src, (ctx.dispatch)(tag))?;
+        Ok((LookAhead(value), size))
+    }
+}
+
+/// A context for tables whose fields sit at explicit offsets, rather than one after another: field `i` is read with `inner`
+/// at `offsets[i]`, relative to the start of the value
+///
+/// Tuples of two to four fields read with it directly; a struct reads its fields with [field](#method.field). The size of
+/// the value is the end of whichever field ends last, so padding between fields, or bytes skipped by them, is consumed too.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx::StrideCtx, Pread, LE};
+///
+/// // a u16 at 0, a u32 at 4 past two reserved bytes, and a u8 at 12
+/// const OFFSETS: &[usize] = &[0, 4, 12];
+/// let bytes = [0x01, 0x00, 0xff, 0xff, 0x02, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0x03];
+/// let offset = &mut 0;
+/// let (a, b, c): (u16, u32, u8) = bytes.gread_with(offset, StrideCtx::new(OFFSETS, LE)).unwrap();
+/// assert_eq!((a, b, c), (1, 2, 3));
+/// assert_eq!(*offset, 13);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct StrideCtx<Ctx> {
+    /// The offset of each field, in order
+    pub offsets: &'static [usize],
+    /// The context every field is read with
+    pub inner: Ctx,
+}
+
+impl<Ctx: Copy> StrideCtx<Ctx> {
+    #[inline]
+    pub fn new(offsets: &'static [usize], inner: Ctx) -> Self {
+        StrideCtx { offsets, inner }
+    }
+    /// Reads field `index` of the value starting at `src`, returning it and the offset its bytes end at
+    pub fn field<'a, T, E>(&self, src: &'a [u8], index: usize) -> result::Result<(T, usize), E>
+        where T: TryFromCtx<'a, Ctx, Error = E>,
+              E: From<error::Error>,
+    {
+        let offset = *self.offsets.get(index)
+            .ok_or(error::Error::BadInput { size: index, msg: "StrideCtx has no offset for this field" })?;
+        let rest = src.get(offset..).ok_or(error::Error::BadOffset(offset))?;
+        let (t, size) = T::try_from_ctx(rest, self.inner)?;
+        Ok((t, offset + size))
+    }
+}
+
+macro_rules! stride_tuple_impl {
+    ($($t:ident $i:tt),+) => {
+        impl<'a, $($t,)+ Ctx, E> TryFromCtx<'a, StrideCtx<Ctx>> for ($($t,)+)
+            where $($t: TryFromCtx<'a, Ctx, Error = E>,)+
+                  E: From<error::Error>,
+                  Ctx: Copy,
+        {
+            type Error = E;
+            #[inline]
+            fn try_from_ctx(src: &'a [u8], ctx: StrideCtx<Ctx>) -> result::Result<(Self, usize), Self::Error> {
+                let mut end = 0;
+                let value = ($({
+                    let (t, field_end) = ctx.field::<$t, E>(src, $i)?;
+                    end = end.max(field_end);
+                    t
+                },)+);
+                Ok((value, end))
+            }
+        }
+    }
+}
+
+stride_tuple_impl!(A 0, B 1);
+stride_tuple_impl!(A 0, B 1, C 2);
+stride_tuple_impl!(A 0, B 1, C 2, D 3);
+
+/// A context for reading the same bytes twice, as the pair of their old and new interpretations: the first value is
+/// read with `old` and the second with `new`, e.g. a field whose byte order or layout a format revision changed
+///
+/// The size is that of whichever read took more bytes. To compare the values at an offset of two different sources,
+/// see [PreadExt::pread_diff](../trait.PreadExt.html#tymethod.pread_diff).
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx::DiffCtx, Pread, BE, LE};
+///
+/// let bytes = [0x00, 0x01];
+/// let (old, new): (u16, u16) = bytes.pread_with(0, DiffCtx { old: LE, new: BE }).unwrap();
+/// assert_eq!((old, new), (0x0100, 0x0001));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DiffCtx<Ctx> {
+    /// The context the old value is read with
+    pub old: Ctx,
+    /// The context the new value is read with
+    pub new: Ctx,
+}
+
+impl<'a, T, Ctx, E> TryFromCtx<'a, DiffCtx<Ctx>> for (T, T)
+    where T: TryFromCtx<'a, Ctx, Error = E>,
+          Ctx: Copy,
+{
+    type Error = E;
+    #[inline]
+    fn try_from_ctx(src: &'a [u8], ctx: DiffCtx<Ctx>) -> result::Result<(Self, usize), Self::Error> {
+        let (old, old_size) = T::try_from_ctx(src, ctx.old)?;
+        let (new, new_size) = T::try_from_ctx(src, ctx.new)?;
+        Ok(((old, new), old_size.max(new_size)))
+    }
+}
+
+/// A budget for parsing untrusted, recursive structures: how many more levels may be descended into, and how many bytes any one value may claim
+///
+/// Exceeding either is an `Error::LimitExceeded`, rather than a stack overflow or a huge allocation. A parser embeds `Limits` in its
+/// context (or uses [Limited](struct.Limited.html)), calls [descend](#method.descend) before parsing a nested value with the child limits it returns,
+/// and calls [check_bytes](#method.check_bytes) on untrusted lengths before acting on them.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Limits {
+    /// How many more levels may be descended into
+    pub max_depth: usize,
+    /// The most bytes any one value may claim
+    pub max_bytes: usize,
+}
+
+impl Limits {
+    #[inline]
+    pub fn new(max_depth: usize, max_bytes: usize) -> Self {
+        Limits { max_depth, max_bytes }
+    }
+    /// Returns the limits for a nested value, one level deeper, or an error if no levels are left
+    #[inline]
+    pub fn descend(self) -> error::Result<Self> {
+        match self.max_depth.checked_sub(1) {
+            Some(max_depth) => Ok(Limits { max_depth, ..self }),
+            None => Err(error::Error::LimitExceeded("depth")),
+        }
+    }
+    /// Errors if `len` bytes are more than a value may claim
+    #[inline]
+    pub fn check_bytes(&self, len: usize) -> error::Result<()> {
+        if len > self.max_bytes {
+            Err(error::Error::LimitExceeded("bytes"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// A context `ctx` together with the [Limits](struct.Limits.html) it is parsed under
+///
+/// A `Box<T>` read with a `Limited` context descends one level before reading its `T`, and a `Vec<T>` read with a
+/// `Limited<Count<Ctx>>` before reading its elements, so the depth of recursive types holding a `Box<Self>` or a
+/// `Vec<Self>` is bounded without any further work.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx::{Limited, Limits, TryFromCtx}, Endian, Error, Pread, LE};
+///
+/// // a tree: a count of children, then each child
+/// struct Tree(Vec<Box<Tree>>);
+///
+/// impl<'a> TryFromCtx<'a, Limited<Endian>> for Tree {
+///     type Error = Error;
+///     fn try_from_ctx(src: &'a [u8], ctx: Limited<Endian>) -> Result<(Self, usize), Self::Error> {
+///         let offset = &mut 0;
+///         let count: u8 = src.gread(offset)?;
+///         let mut children = Vec::new();
+///         for _ in 0..count {
+///             children.push(src.gread_with::<Box<Tree>>(offset, ctx)?);
+///         }
+///         Ok((Tree(children), *offset))
+///     }
+/// }
+///
+/// let ctx = Limited::new(LE, Limits::new(2, 1024));
+/// assert!([1u8, 1, 0].pread_with::<Tree>(0, ctx).is_ok());
+/// assert!(matches!([1u8, 1, 1, 0].pread_with::<Tree>(0, ctx), Err(Error::LimitExceeded("depth"))));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Limited<Ctx> {
+    /// The context proper
+    pub ctx: Ctx,
+    /// The limits it is parsed under
+    pub limits: Limits,
+}
+
+impl<Ctx> Limited<Ctx> {
+    #[inline]
+    pub fn new(ctx: Ctx, limits: Limits) -> Self {
+        Limited { ctx, limits }
+    }
+    /// Returns the context for a nested value, one level deeper, or an error if no levels are left
+    #[inline]
+    pub fn descend(self) -> error::Result<Self> {
+        Ok(Limited { ctx: self.ctx, limits: self.limits.descend()? })
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, T, Ctx: Copy> TryFromCtx<'a, Limited<Ctx>> for Box<T>
+    where T: TryFromCtx<'a, Limited<Ctx>>,
+          T::Error: From<error::Error>,
+{
+    type Error = T::Error;
+    #[inline]
+    fn try_from_ctx(src: &'a [u8], ctx: Limited<Ctx>) -> result::Result<(Self, usize), Self::Error> {
+        let (value, size) = T::try_from_ctx(src, ctx.descend()?)?;
+        Ok((Box::new(value), size))
+    }
+}
+
+/// Reads `count` `T`s as a `Vec` does with a [Count](struct.Count.html), but one level deeper, each with the child limits
+///
+/// Room for the elements is checked against the limits' `max_bytes` before anything is allocated.
+#[cfg(feature = "alloc")]
+impl<'a, T, Ctx: Copy> TryFromCtx<'a, Limited<Count<Ctx>>> for Vec<T>
+    where T: TryFromCtx<'a, Limited<Ctx>, Error = error::Error>,
+{
+    type Error = error::Error;
+    fn try_from_ctx(src: &'a [u8], Limited { ctx: Count(count, ctx), limits }: Limited<Count<Ctx>>) -> error::Result<(Self, usize)> {
+        limits.check_bytes(count.saturating_mul(size_of::<T>()))?;
+        let ctx = Limited::new(ctx, limits.descend()?);
+        check_count(count, src)?;
+        read_elements(src, count, ctx, Vec::with_capacity(initial_capacity::<T>(count, src)))
+    }
+}
+
+/// The parsing context for a value preceded by its length in bytes, stored as an `L` with `endian`; the value itself is read and written with `inner`
+///
+/// Reading parses the value from exactly the `len` bytes following the length, and consumes all of them; writing writes the value just past
+/// where the length goes, then the length itself, so no temporary buffer is needed. The value is read and written through the
+/// [EncodedLen](struct.EncodedLen.html) wrapper.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx::{EncodedLen, EncodedLenCtx, StrCtx}, Pread, Pwrite, BE};
+///
+/// let mut bytes = [0u8; 8];
+/// assert_eq!(bytes.pwrite_with(EncodedLen("hello"), 0, EncodedLenCtx::<u16>::new(BE, ())).unwrap(), 7);
+/// assert_eq!(&bytes[..7], b"\x00\x05hello");
+/// // the string runs to the end of its bytes, as there is no delimiter in them
+/// let ctx = EncodedLenCtx::<u16, _>::new(BE, StrCtx::default());
+/// let EncodedLen(s) = bytes.pread_with::<EncodedLen<&str>>(0, ctx).unwrap();
+/// assert_eq!(s, "hello");
+/// ```
+pub struct EncodedLenCtx<L, Inner = ()> {
+    /// The endianness of the length
+    pub endian: Endian,
+    /// The context the value is read and written with
+    pub inner: Inner,
+    len: PhantomData<fn() -> L>,
+}
+
+impl<L, Inner> EncodedLenCtx<L, Inner> {
+    #[inline]
+    pub fn new(endian: Endian, inner: Inner) -> Self {
+        EncodedLenCtx { endian, inner, len: PhantomData }
+    }
+}
+
+// manual impls, so that `L` need not be `Copy` or `Debug` itself
+impl<L, Inner: Copy> Copy for EncodedLenCtx<L, Inner> {}
+
+impl<L, Inner: Copy> Clone for EncodedLenCtx<L, Inner> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<L, Inner: fmt::Debug> fmt::Debug for EncodedLenCtx<L, Inner> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("EncodedLenCtx").field("endian", &self.endian).field("inner", &self.inner).finish()
+    }
+}
+
+/// A value read or written with an [EncodedLenCtx](struct.EncodedLenCtx.html)
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct EncodedLen<T>(pub T);
+
+impl<'a, T, L, Inner, E> TryFromCtx<'a, EncodedLenCtx<L, Inner>> for EncodedLen<T>
+    where T: TryFromCtx<'a, Inner, Error = E>,
+          L: TryFromCtx<'a, Endian, Error = error::Error>,
+          usize: TryFrom<L>,
+          Inner: Copy,
+          E: From<error::Error>,
+{
+    type Error = E;
+    fn try_from_ctx(src: &'a [u8], ctx: EncodedLenCtx<L, Inner>) -> result::Result<(Self, usize), Self::Error> {
+        let (len, size) = L::try_from_ctx(src, ctx.endian)?;
+        let len = usize::try_from(len).map_err(|_| error::Error::BadInput { size, msg: "length does not fit in a usize" })?;
+        let rest = &src[size..];
+        if len > rest.len() {
+            return Err(error::Error::Incomplete { needed: len - rest.len() }.into());
+        }
+        let (value, _) = T::try_from_ctx(&rest[..len], ctx.inner)?;
+        Ok((EncodedLen(value), size + len))
+    }
+}
+
+impl<T, L, Inner, E> TryIntoCtx<EncodedLenCtx<L, Inner>> for EncodedLen<T>
+    where T: TryIntoCtx<Inner, Error = E>,
+          L: TryFrom<usize> + TryIntoCtx<Endian, Error = error::Error> + SizeWith<Endian>,
+          Inner: Copy,
+          E: From<error::Error>,
+{
+    type Error = E;
+    fn try_into_ctx(self, dst: &mut [u8], ctx: EncodedLenCtx<L, Inner>) -> result::Result<usize, Self::Error> {
+        let size = L::size_with(&ctx.endian);
+        if size > dst.len() {
+            return Err(error::Error::TooBig { size, len: dst.len() }.into());
+        }
+        let len = self.0.try_into_ctx(&mut dst[size..], ctx.inner)?;
+        let encoded = L::try_from(len).map_err(|_| error::Error::BadInput { size: len, msg: "length does not fit in the length field" })?;
+        encoded.try_into_ctx(dst, ctx.endian)?;
+        Ok(size + len)
+    }
+}
+
 // example of marshalling to bytes, let's wait until const is an option
 // impl FromCtx for [u8; 10] {
 //     fn from_ctx(bytes: &[u8], _ctx: Endian) -> Self {
@@ -606,7 +3889,7 @@ mod tests {
     use super::*;
 
     #[test]
-    #[cfg(feature = "std")]
+    #[cfg(feature = "alloc")]
     fn parse_a_cstr() {
         let src = CString::new("Hello World").unwrap();
         let as_bytes = src.as_bytes_with_nul();
@@ -619,6 +3902,58 @@ mod tests {
 
     #[test]
     #[cfg(feature = "std")]
+    fn parse_an_os_string() {
+        let (got, bytes_read) = OsString::try_from_ctx(b"/var/run/app.sock\0rest", OsStringCtx::Null).unwrap();
+        assert_eq!(got, "/var/run/app.sock");
+        assert_eq!(bytes_read, 18);
+        let (got, bytes_read) = OsString::try_from_ctx(b"/var/run\0", OsStringCtx::Utf8).unwrap();
+        assert_eq!((got.to_str(), bytes_read), (Some("/var/run"), 9));
+
+        let wide: Vec<u8> = "C:\\Temp\0".encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let (got, bytes_read) = OsString::try_from_ctx(&wide, OsStringCtx::Utf16LE).unwrap();
+        assert_eq!(got, "C:\\Temp");
+        assert_eq!(bytes_read, 16);
+
+        assert!(matches!(OsString::try_from_ctx(b"abc", OsStringCtx::Null), Err(error::Error::Incomplete{needed: 1})));
+        assert!(matches!(OsString::try_from_ctx(&wide[..15], OsStringCtx::Utf16LE), Err(error::Error::Incomplete{needed: 1})));
+        assert!(OsString::try_from_ctx(b"\xff\0", OsStringCtx::Utf8).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn os_str_and_path_fields() {
+        let ctx = StrCtx::Prefixed { width: 1, endian: Endian::Little };
+        let (name, size) = <&Path>::try_from_ctx(b"\x06bin/sh rest", ctx).unwrap();
+        assert_eq!((name, size), (Path::new("bin/sh"), 7));
+        let (name, size) = PathBuf::try_from_ctx(b"bin/sh\0", OsStringCtx::Framed(StrCtx::Delimiter(0))).unwrap();
+        assert_eq!((name.as_path(), size), (Path::new("bin/sh"), 7));
+        let (name, size) = OsString::try_from_ctx(b"\x06bin/sh", OsStringCtx::Framed(ctx)).unwrap();
+        assert_eq!((name.to_str(), size), (Some("bin/sh"), 7));
+        let mut dst = [0u8; 8];
+        assert_eq!(Path::new("bin/sh").try_into_ctx(&mut dst, ctx).unwrap(), 7);
+        assert_eq!(&dst[..7], b"\x06bin/sh");
+        assert!(matches!(OsStr::new("a\0b").try_into_ctx(&mut dst, StrCtx::Delimiter(0)), Err(error::Error::BadInput{..})));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            // not UTF-8, and read and written unchanged
+            let bytes = b"caf\xe9/\xff\xfe.txt";
+            let fixed = StrCtx::Fixed { len: 16, pad: 0 };
+            let mut field = [0u8; 16];
+            assert_eq!(OsStr::from_bytes(bytes).try_into_ctx(&mut field, fixed).unwrap(), 16);
+            let (name, size) = <&OsStr>::try_from_ctx(&field, fixed).unwrap();
+            assert_eq!((name.as_bytes(), size), (&bytes[..], 16));
+            let (path, _) = PathBuf::try_from_ctx(&field, OsStringCtx::Framed(fixed)).unwrap();
+            assert_eq!(path.as_os_str().as_bytes(), bytes);
+            let mut again = [0xffu8; 16];
+            path.try_into_ctx(&mut again, fixed).unwrap();
+            assert_eq!(again, field);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
     fn round_trip_a_c_str() {
         let src = CString::new("Hello World").unwrap();
         let src = src.as_c_str();
@@ -633,6 +3968,497 @@ mod tests {
         assert_eq!(bytes_read, as_bytes.len());
         assert_eq!(got, src);
     }
-}
 
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn iter_ctx() {
+        use crate::{Pread, LE};
+        let bytes = [1u8, 2, 3, 4, 5];
+        let offset = &mut 1;
+        let iter: TryFromCtxIter<u8, _> = bytes.gread_with(offset, IterCtx(LE)).unwrap();
+        assert_eq!(*offset, bytes.len());
+        assert_eq!(iter.collect::<Result<Vec<u8>, _>>().unwrap(), [2, 3, 4, 5]);
+
+        let mut iter: TryFromCtxIter<u16, _> = bytes.pread_with(0, IterCtx(LE)).unwrap();
+        assert_eq!(iter.next().unwrap().unwrap(), 0x201);
+        assert_eq!(iter.next().unwrap().unwrap(), 0x403);
+        assert_eq!(iter.offset(), 4);
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn timestamps() {
+        use crate::{Pread, Pwrite, LE, BE};
+        use std::time::{Duration, SystemTime, UNIX_EPOCH};
+        // 2009-02-13 23:31:30 UTC
+        let time = UNIX_EPOCH + Duration::from_secs(1_234_567_890);
+        let mut bytes = [0u8; 8];
+        for &ctx in &[TimeCtx::UnixSeconds32(LE), TimeCtx::UnixSeconds64(BE), TimeCtx::UnixMillis64(LE), TimeCtx::WindowsFiletime(BE)] {
+            let size = bytes.pwrite_with(time, 0, ctx).unwrap();
+            assert_eq!(bytes.pread_with::<SystemTime>(0, ctx).unwrap(), time);
+            assert_eq!(size, if let TimeCtx::UnixSeconds32(_) = ctx { 4 } else { 8 });
+        }
+        bytes.pwrite_with(time, 0, TimeCtx::UnixSeconds32(BE)).unwrap();
+        assert_eq!(bytes.pread_with::<u32>(0, BE).unwrap(), 1_234_567_890);
+        bytes.pwrite_with(time, 0, TimeCtx::UnixMillis64(LE)).unwrap();
+        assert_eq!(bytes.pread_with::<u64>(0, LE).unwrap(), 1_234_567_890_000);
+
+        // the unix epoch as a FILETIME
+        bytes.pwrite_with(116_444_736_000_000_000u64, 0, LE).unwrap();
+        assert_eq!(bytes.pread_with::<SystemTime>(0, TimeCtx::WindowsFiletime(LE)).unwrap(), UNIX_EPOCH);
+        bytes.pwrite_with(0u64, 0, LE).unwrap();
+        let windows_epoch = bytes.pread_with::<SystemTime>(0, TimeCtx::WindowsFiletime(LE)).unwrap();
+        assert_eq!(UNIX_EPOCH.duration_since(windows_epoch).unwrap(), Duration::from_secs(11_644_473_600));
+
+        // 1969-07-20 20:17:40 UTC
+        let moon = UNIX_EPOCH - Duration::from_secs(14_182_940);
+        bytes.pwrite_with(-14_182_940i32, 0, LE).unwrap();
+        assert_eq!(bytes.pread_with::<SystemTime>(0, TimeCtx::UnixSeconds32(LE)).unwrap(), moon);
+        for &ctx in &[TimeCtx::UnixSeconds32(BE), TimeCtx::UnixSeconds64(LE), TimeCtx::UnixMillis64(BE), TimeCtx::WindowsFiletime(LE)] {
+            bytes.pwrite_with(moon, 0, ctx).unwrap();
+            assert_eq!(bytes.pread_with::<SystemTime>(0, ctx).unwrap(), moon);
+        }
+        // sub-second precision rounds towards the past, even before the epoch
+        bytes.pwrite_with(UNIX_EPOCH - Duration::from_millis(1500), 0, TimeCtx::UnixSeconds64(LE)).unwrap();
+        assert_eq!(bytes.pread_with::<i64>(0, LE).unwrap(), -2);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn timestamps_out_of_range() {
+        use crate::{Pread, Pwrite, LE};
+        use std::time::{Duration, UNIX_EPOCH};
+        let mut bytes = [0u8; 8];
+        let far_future = UNIX_EPOCH + Duration::from_secs(1 << 40);
+        assert!(bytes.pwrite_with(far_future, 0, TimeCtx::UnixSeconds32(LE)).is_err());
+        assert!(bytes.pwrite_with(UNIX_EPOCH - Duration::from_secs(11_644_473_601), 0, TimeCtx::WindowsFiletime(LE)).is_err());
+        bytes.pwrite_with(-1i64, 0, LE).unwrap();
+        assert!(bytes.pread_with::<Duration>(0, TimeCtx::UnixSeconds64(LE)).is_err());
+        assert_eq!(bytes.pread_with::<Duration>(0, TimeCtx::WindowsFiletime(LE)).unwrap(), Duration::new(1_844_674_407_370, 955_161_500));
+        bytes.pwrite_with(Duration::from_millis(1500), 0, TimeCtx::UnixMillis64(LE)).unwrap();
+        assert_eq!(bytes.pread_with::<Duration>(0, TimeCtx::UnixMillis64(LE)).unwrap(), Duration::from_millis(1500));
+        // i64::MAX seconds overflows what a SystemTime can hold on most platforms, but must not panic
+        bytes.pwrite_with(i64::MAX, 0, LE).unwrap();
+        let _ = bytes.pread_with::<std::time::SystemTime>(0, TimeCtx::UnixSeconds64(LE));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn interleaved_ctx() {
+        use crate::{Pread, Pwrite, LE, BE};
+        let mut bytes = [0u8; 32];
+        let offset = &mut 0;
+        for i in 0..8i16 {
+            bytes.gwrite_with(i * 100, offset, LE).unwrap();
+            bytes.gwrite_with(-i, offset, LE).unwrap();
+        }
+        let ctx = InterleavedCtx { inner_a: LE, inner_b: LE };
+        let (left, right): (Vec<i16>, Vec<i16>) = bytes.pread_with(0, ctx).unwrap();
+        assert_eq!(left, [0, 100, 200, 300, 400, 500, 600, 700]);
+        assert_eq!(right, [0, -1, -2, -3, -4, -5, -6, -7]);
+
+        // mixed types and contexts
+        let ctx = InterleavedCtx { inner_a: BE, inner_b: LE };
+        let (a, b): (Vec<u8>, Vec<u16>) = [1u8, 2, 0, 3, 4, 0].pread_with(0, ctx).unwrap();
+        assert_eq!(a, [1, 3]);
+        assert_eq!(b, [2, 4]);
+
+        // the source ends in the middle of a pair
+        assert!(bytes[..31].pread_with::<(Vec<i16>, Vec<i16>)>(0, InterleavedCtx { inner_a: LE, inner_b: LE }).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn interleaved3_ctx() {
+        use crate::{Pread, LE, BE};
+        let pixels: Vec<u8> = (0..12u8).flat_map(|i| vec![i, 0x80 | i, 0xff - i]).collect();
+        assert_eq!(pixels.len(), 36);
+        let ctx = Interleaved3Ctx { count: 12, inner: LE };
+        let offset = &mut 0;
+        let (r, g, b): (Vec<u8>, Vec<u8>, Vec<u8>) = pixels.gread_with(offset, ctx).unwrap();
+        assert_eq!(*offset, 36);
+        assert_eq!(r, (0..12).collect::<Vec<u8>>());
+        assert_eq!(g, (0x80..0x8c).collect::<Vec<u8>>());
+        assert_eq!(b, (0xf4..=0xff).rev().collect::<Vec<u8>>());
+
+        // wider samples, and a source with more than `count` triples
+        let (r, g, b): (Vec<u16>, Vec<u16>, Vec<u16>) = pixels.pread_with(0, Interleaved3Ctx { count: 1, inner: BE }).unwrap();
+        assert_eq!((r, g, b), (vec![0x0080], vec![0xff01], vec![0x81fe]));
+
+        // the source ends in the middle of a triple
+        assert!(pixels[..35].pread_with::<(Vec<u8>, Vec<u8>, Vec<u8>)>(0, ctx).is_err());
+    }
+
+    #[test]
+    fn fixed_str() {
+        use crate::{Pread, Pwrite};
+        let ctx = StrCtx::Fixed { len: 8, pad: 0 };
+
+        // an empty field
+        let bytes = [0u8; 8];
+        let mut offset = 0;
+        assert_eq!(bytes.gread_with::<&str>(&mut offset, ctx).unwrap(), "");
+        assert_eq!(offset, 8);
+        let mut out = [0xffu8; 8];
+        assert_eq!(out.pwrite_with(Str(""), 0, ctx).unwrap(), 8);
+        assert_eq!(out, bytes);
+
+        // an exactly full field
+        let bytes = b"abcdefgh";
+        assert_eq!(bytes.pread_with::<&str>(0, ctx).unwrap(), "abcdefgh");
+        let mut out = [0u8; 8];
+        assert_eq!(out.pwrite_with(Str("abcdefgh"), 0, ctx).unwrap(), 8);
+        assert_eq!(&out, bytes);
+        assert!(out.pwrite_with(Str("abcdefghi"), 0, ctx).is_err());
+
+        // an embedded NUL is kept, only the trailing padding is stripped
+        let bytes = b"ab\0cd\0\0\0rest";
+        let mut offset = 0;
+        assert_eq!(bytes.gread_with::<&str>(&mut offset, ctx).unwrap(), "ab\0cd");
+        assert_eq!(offset, 8);
+        assert_eq!(bytes.gread_with::<&str>(&mut offset, StrCtx::Length(4)).unwrap(), "rest");
+
+        // space padding, and a field running past the end of the source
+        let ctx = StrCtx::Fixed { len: 6, pad: SPACE };
+        assert_eq!(b"ustar ".pread_with::<&str>(0, ctx).unwrap(), "ustar");
+        assert!(b"ustar".pread_with::<&str>(0, ctx).is_err());
+        let mut out = [0u8; 6];
+        out.pwrite_with(Str("tar"), 0, ctx).unwrap();
+        assert_eq!(&out, b"tar   ");
+    }
+
+    #[test]
+    fn str_write_delimiter() {
+        use crate::Pwrite;
+        let mut out = [0xffu8; 6];
+        let mut offset = 0;
+        out.gwrite_with(Str("ab"), &mut offset, StrCtx::Delimiter(NULL)).unwrap();
+        out.gwrite_with(Str("cd"), &mut offset, StrCtx::Length(2)).unwrap();
+        assert_eq!(offset, 5);
+        assert_eq!(&out, b"ab\0cd\xff");
+        assert!(out.pwrite_with(Str("abc"), 0, StrCtx::Length(2)).is_err());
+        assert!(out.pwrite_with(Str("abcdef"), 0, StrCtx::Delimiter(NULL)).is_err());
+    }
+
+    #[test]
+    fn ascii_num_tar_header() {
+        use crate::{Pread, Pwrite};
+        // the leading fields of a ustar header for a 1000 byte `hello.txt`, as written by GNU tar
+        let mut header = [0u8; 148];
+        header[..9].copy_from_slice(b"hello.txt");
+        header[100..108].copy_from_slice(b"0000644\0");
+        header[108..116].copy_from_slice(b"0001750\0");
+        header[116..124].copy_from_slice(b"0001750\0");
+        header[124..136].copy_from_slice(b"00000001750\0");
+        header[136..148].copy_from_slice(b"14371573231\0");
+        let octal = |len| AsciiNum { radix: 8, len, pad: AsciiPad::Zero };
+
+        let mut offset = 100;
+        let Ascii(mode): Ascii<u64> = header.gread_with(&mut offset, octal(8)).unwrap();
+        let Ascii(uid): Ascii<u64> = header.gread_with(&mut offset, octal(8)).unwrap();
+        let Ascii(_gid): Ascii<u64> = header.gread_with(&mut offset, octal(8)).unwrap();
+        let Ascii(size): Ascii<u64> = header.gread_with(&mut offset, octal(12)).unwrap();
+        let Ascii(mtime): Ascii<u64> = header.gread_with(&mut offset, octal(12)).unwrap();
+        assert_eq!(offset, 148);
+        assert_eq!(mode, 0o644);
+        assert_eq!(uid, 1000);
+        assert_eq!(size, 1000);
+        assert_eq!(mtime, 1_676_080_793);
+
+        // older tars space-pad and space-terminate, and leave unused fields blank
+        assert_eq!(b"    1750 \0\0\0".pread_with::<Ascii<u64>>(0, octal(12)).unwrap(), Ascii(1000));
+        assert_eq!(b"\0\0\0\0\0\0\0\0".pread_with::<Ascii<u64>>(0, octal(8)).unwrap(), Ascii(0));
+
+        let mut written = [0xffu8; 148];
+        let mut offset = 124;
+        written.gwrite_with(Ascii(size), &mut offset, octal(12)).unwrap();
+        written.gwrite_with(Ascii(mtime), &mut offset, octal(12)).unwrap();
+        assert_eq!(&written[124..148], &header[124..148]);
+        // 8^11 no longer fits in the 11 digits of a size field
+        assert!(written.pwrite_with(Ascii(1u64 << 33), 124, octal(12)).is_err());
+    }
+
+    #[test]
+    fn ascii_num_radix_and_errors() {
+        use crate::{Pread, Pwrite};
+        let hex = AsciiNum { radix: 16, len: 4, pad: AsciiPad::Nul };
+        assert_eq!(b"\0\0fF".pread_with::<Ascii<u64>>(0, hex).unwrap(), Ascii(0xff));
+        let mut out = [0xffu8; 4];
+        out.pwrite_with(Ascii(0xabu64), 0, hex).unwrap();
+        assert_eq!(&out, b"\0\0AB");
+
+        let dec = AsciiNum { radix: 10, len: 6, pad: AsciiPad::Space };
+        out.pwrite_with(Ascii(-42i64), 0, AsciiNum { radix: 10, len: 4, pad: AsciiPad::Zero }).unwrap();
+        assert_eq!(&out, b"-42\0");
+        assert_eq!(out.pread_with::<Ascii<i64>>(0, AsciiNum { radix: 10, len: 4, pad: AsciiPad::Zero }).unwrap(), Ascii(-42));
+        let mut out = [0u8; 6];
+        out.pwrite_with(Ascii(-42i64), 0, dec).unwrap();
+        assert_eq!(&out, b"   -42");
+        assert_eq!(out.pread_with::<Ascii<i64>>(0, dec).unwrap(), Ascii(-42));
+        assert!(out.pwrite_with(Ascii(-123_456i64), 0, dec).is_err());
+
+        assert!(b"12 4".pread_with::<Ascii<u64>>(0, hex).is_err());
+        assert!(b"0009".pread_with::<Ascii<u64>>(0, AsciiNum { radix: 8, len: 4, pad: AsciiPad::Zero }).is_err());
+        assert!(b"1".pread_with::<Ascii<u64>>(0, AsciiNum { radix: 1, len: 1, pad: AsciiPad::Zero }).is_err());
+        assert!(b"12".pread_with::<Ascii<u64>>(0, hex).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn ascii_num_error_message() {
+        use crate::Pread;
+        let err = b"  17x9\0\0".pread_with::<Ascii<u64>>(0, AsciiNum { radix: 8, len: 8, pad: AsciiPad::Zero }).unwrap_err();
+        assert_eq!(format!("{}", err), r#"invalid base 8 number "  17x9\0\0" at offset 4 of a 8 byte field"#);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn lz_ctx() {
+        use crate::{Pread, Pwrite, LE, BE};
+        fn xor(block: &[u8]) -> error::Result<Vec<u8>> {
+            Ok(block.iter().map(|b| b ^ 0x5a).collect())
+        }
+        let original: Vec<u8> = (0..64).collect();
+        let mut bytes = [0u8; 70];
+        let offset = &mut 0;
+        bytes.gwrite_with(64u32, offset, BE).unwrap();
+        for b in &original {
+            bytes.gwrite(b ^ 0x5a, offset).unwrap();
+        }
+        bytes.gwrite_with(0xbeefu16, offset, LE).unwrap();
+
+        let ctx = LzCtx { endian: BE, decompress: xor };
+        let offset = &mut 0;
+        let data: Vec<u8> = bytes.gread_with(offset, ctx).unwrap();
+        assert_eq!(data, original);
+        assert_eq!(*offset, 68);
+        assert_eq!(bytes.gread_with::<u16>(offset, LE).unwrap(), 0xbeef);
+
+        // the compressed size runs past the end of the source
+        assert!(bytes[..60].pread_with::<Vec<u8>>(0, ctx).is_err());
+        // the decompressor's errors are passed through
+        let ctx = LzCtx { endian: BE, decompress: |_: &[u8]| Err(error::Error::BadInput { size: 0, msg: "corrupt block" }) };
+        assert!(bytes.pread_with::<Vec<u8>>(0, ctx).is_err());
+    }
+
+    #[test]
+    fn tag_ctx() {
+        use crate::{Pread, Pwrite, BE, LE};
+        #[derive(Debug, PartialEq, Copy, Clone)]
+        enum Kind {
+            Data,
+            Eof,
+        }
+        impl Tag for Kind {
+            type Repr = u8;
+            fn from_repr(repr: u8) -> Option<Self> {
+                match repr {
+                    0 => Some(Kind::Data),
+                    1 => Some(Kind::Eof),
+                    _ => None,
+                }
+            }
+            fn to_repr(&self) -> u8 {
+                *self as u8
+            }
+        }
+        #[derive(Debug, PartialEq, Copy, Clone)]
+        enum Machine {
+            X86,
+            Arm64,
+        }
+        impl Tag for Machine {
+            type Repr = u32;
+            fn from_repr(repr: u32) -> Option<Self> {
+                match repr {
+                    0x14c => Some(Machine::X86),
+                    0xaa64 => Some(Machine::Arm64),
+                    _ => None,
+                }
+            }
+            fn to_repr(&self) -> u32 {
+                match *self {
+                    Machine::X86 => 0x14c,
+                    Machine::Arm64 => 0xaa64,
+                }
+            }
+        }
+
+        let bytes = [0x01, 0x00, 0x00, 0xaa, 0x64, 0x02];
+        let offset = &mut 0;
+        assert_eq!(bytes.gread_with::<Kind>(offset, TagCtx::new(LE)).unwrap(), Kind::Eof);
+        assert_eq!(bytes.gread_with::<Machine>(offset, TagCtx::new(BE)).unwrap(), Machine::Arm64);
+        assert_eq!(*offset, 5);
+        assert!(bytes.gread_with::<Kind>(offset, TagCtx::new(LE)).is_err());
+        assert_eq!(*offset, 5);
+        // ctx-less reads of the underlying integers still infer
+        assert_eq!(bytes.pread::<u8>(0).unwrap(), 1);
+
+        let mut out = [0u8; 5];
+        let offset = &mut 0;
+        out.gwrite_with(Kind::Eof, offset, TagCtx::new(LE)).unwrap();
+        out.gwrite_with(Machine::Arm64, offset, TagCtx::new(BE)).unwrap();
+        assert_eq!(out, bytes[..5]);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn tag_ctx_unknown() {
+        use crate::{Pread, LE};
+        #[derive(Debug)]
+        struct Version;
+        impl Tag for Version {
+            type Repr = u16;
+            fn from_repr(repr: u16) -> Option<Self> {
+                if repr == 2 { Some(Version) } else { None }
+            }
+            fn to_repr(&self) -> u16 {
+                2
+            }
+        }
+        let err = [3u8, 0].pread_with::<Version>(0, TagCtx::new(LE)).unwrap_err();
+        assert!(format!("{}", err).ends_with("Version tag 3"), "{}", err);
+    }
+
+    #[test]
+    fn versioned_field() {
+        use crate::{Endian, Pread, Pwrite, LE};
+        #[derive(Debug, PartialEq)]
+        struct Header {
+            version: u16,
+            flags: u32,
+            extended: Option<u64>,
+        }
+        impl<'a> TryFromCtx<'a, Endian> for Header {
+            type Error = error::Error;
+            fn try_from_ctx(src: &'a [u8], endian: Endian) -> result::Result<(Self, usize), Self::Error> {
+                let offset = &mut 0;
+                let version = src.gread_with(offset, endian)?;
+                let flags = src.gread_with(offset, endian)?;
+                let ctx = VersionedFieldCtx { version, min_version: 2, inner: endian };
+                let extended: VersionedField<u64, u16> = src.gread_with(offset, ctx)?;
+                Ok((Header { version, flags, extended: extended.into() }, *offset))
+            }
+        }
+
+        let v1 = [1, 0, 0xaa, 0, 0, 0];
+        let header: Header = v1.pread_with(0, LE).unwrap();
+        assert_eq!(header, Header { version: 1, flags: 0xaa, extended: None });
+        let offset = &mut 0;
+        let _: Header = v1.gread_with(offset, LE).unwrap();
+        assert_eq!(*offset, 6);
+
+        let v2 = [2, 0, 0xaa, 0, 0, 0, 8, 7, 6, 5, 4, 3, 2, 1];
+        let offset = &mut 0;
+        let header: Header = v2.gread_with(offset, LE).unwrap();
+        assert_eq!(header, Header { version: 2, flags: 0xaa, extended: Some(0x0102030405060708) });
+        assert_eq!(*offset, 14);
+        // the field is required once the version has it
+        assert!(v2[..6].pread_with::<Header>(0, LE).is_err());
+
+        let mut out = [0u8; 8];
+        let ctx = VersionedFieldCtx { version: 3u16, min_version: 2, inner: LE };
+        assert_eq!(out.pwrite_with(VersionedField::<u64, u16>::new(Some(0x0102030405060708)), 0, ctx).unwrap(), 8);
+        assert_eq!(out, v2[6..]);
+        assert!(out.pwrite_with(VersionedField::<u64, u16>::new(None), 0, ctx).is_err());
+        let ctx = VersionedFieldCtx { version: 1, ..ctx };
+        assert_eq!(out.pwrite_with(VersionedField::<u64, u16>::new(None), 0, ctx).unwrap(), 0);
+        assert!(out.pwrite_with(VersionedField::<u64, u16>::new(Some(1)), 0, ctx).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn escaped_ctx() {
+        use crate::{Pread, Pwrite};
+        let ctx = EscapedCtx { escape_byte: 0x5c, sentinel: 0x00 };
+        let bytes = b"hell\x5Co\x00";
+        let offset = &mut 0;
+        let hello: Vec<u8> = bytes.gread_with(offset, ctx).unwrap();
+        assert_eq!(hello, b"hello");
+        assert_eq!(*offset, 7);
+
+        // escaped escapes and sentinels
+        let bytes = b"a\x5c\x5cb\x5c\x00c\x00d";
+        let offset = &mut 0;
+        let decoded: Vec<u8> = bytes.gread_with(offset, ctx).unwrap();
+        assert_eq!(decoded, b"a\x5cb\x00c");
+        assert_eq!(*offset, 8);
+        let mut out = [0xffu8; 8];
+        assert_eq!(out.pwrite_with(&decoded, 0, ctx).unwrap(), 8);
+        assert_eq!(&out, &bytes[..8]);
+        assert!(out[..7].pwrite_with(&decoded, 0, ctx).is_err());
+
+        // an empty string is just the sentinel
+        let mut out = [0xffu8; 1];
+        assert_eq!(out.pwrite_with(Vec::new(), 0, ctx).unwrap(), 1);
+        assert_eq!(out.pread_with::<Vec<u8>>(0, ctx).unwrap(), b"");
+
+        // no sentinel, or an escape with nothing following it
+        assert!(matches!(b"hello".pread_with::<Vec<u8>>(0, ctx), Err(error::Error::BadOffset(5))));
+        assert!(matches!(b"hell\x5c".pread_with::<Vec<u8>>(0, ctx), Err(error::Error::BadOffset(5))));
+    }
+
+    #[test]
+    fn transform_ctx() {
+        use crate::{Pread, Pwrite, LE};
+        let xor = |bytes: &mut [u8]| bytes.iter_mut().for_each(|x| *x ^= 0xAA);
+        let ctx = TransformCtx { inner: LE, transform: xor };
+        let plain = 0x1234_5678u32;
+        let bytes = [0x78 ^ 0xAA, 0x56 ^ 0xAA, 0x34 ^ 0xAA, 0x12 ^ 0xAA, 0xff];
+        let offset = &mut 0;
+        assert_eq!(bytes.gread_with::<Transformed<u32>>(offset, ctx).unwrap(), Transformed(plain));
+        assert_eq!(*offset, 4);
+        // the source itself is untouched
+        assert_eq!(bytes[0], 0x78 ^ 0xAA);
+
+        let mut out = [0xffu8; 5];
+        assert_eq!(out.pwrite_with(Transformed(plain), 0, ctx).unwrap(), 4);
+        assert_eq!(out, bytes);
+
+        assert!(bytes[..3].pread_with::<Transformed<u32>>(0, ctx).is_err());
+        // ctx-less reads still infer
+        assert_eq!(bytes.pread::<u8>(4).unwrap(), 0xff);
+    }
+
+    #[test]
+    fn stride_ctx() {
+        use crate::{Pread, BE};
+
+        #[derive(Debug, PartialEq)]
+        struct Entry {
+            a: u16,
+            b: u32,
+            c: u8,
+        }
 
+        impl<'a> TryFromCtx<'a, StrideCtx<Endian>> for Entry {
+            type Error = error::Error;
+            fn try_from_ctx(src: &'a [u8], ctx: StrideCtx<Endian>) -> error::Result<(Self, usize)> {
+                let (a, _) = ctx.field(src, 0)?;
+                let (b, _) = ctx.field(src, 1)?;
+                let (c, end) = ctx.field(src, 2)?;
+                Ok((Entry { a, b, c }, end))
+            }
+        }
+
+        const OFFSETS: &[usize] = &[0, 4, 12];
+        let ctx = StrideCtx::new(OFFSETS, BE);
+        let bytes = [0xbe, 0xef, 0xaa, 0xaa, 0xde, 0xad, 0xbe, 0xef, 0xaa, 0xaa, 0xaa, 0xaa, 0x7f, 0xaa];
+        let offset = &mut 0;
+        assert_eq!(bytes.gread_with::<Entry>(offset, ctx).unwrap(), Entry { a: 0xbeef, b: 0xdeadbeef, c: 0x7f });
+        assert_eq!(*offset, 13);
+        assert_eq!(bytes.pread_with::<(u16, u32, u8)>(0, ctx).unwrap(), (0xbeef, 0xdeadbeef, 0x7f));
+
+        // fields may be out of order, and the size is that of the last to end
+        let ctx = StrideCtx::new(&[4, 0], BE);
+        assert_eq!(bytes.pread_with::<(u32, u16)>(0, ctx).unwrap(), (0xdeadbeef, 0xbeef));
+        let offset = &mut 0;
+        bytes.gread_with::<(u32, u16)>(offset, ctx).unwrap();
+        assert_eq!(*offset, 8);
+
+        assert!(matches!(bytes[..11].pread_with::<Entry>(0, StrideCtx::new(OFFSETS, BE)), Err(error::Error::BadOffset(12))));
+        assert!(matches!(bytes.pread_with::<(u8, u8, u8)>(0, StrideCtx::new(&[0, 1], BE)), Err(error::Error::BadInput { size: 2, .. })));
+    }
+}
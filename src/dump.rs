@@ -0,0 +1,226 @@
+use core::fmt;
+use core::marker::PhantomData;
+
+use crate::ctx::TryFromCtx;
+use crate::error;
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use crate::prelude::*;
+
+/// How a value is shown on its line of an offset dump: integers in hex, strings and characters escaped, and sequences
+/// element by element
+pub trait DumpValue {
+    /// Shows the value
+    fn dump_value(&self, f: &mut fmt::Formatter) -> fmt::Result;
+}
+
+macro_rules! dump_unsigned {
+    ($($ty:ty),*) => {$(
+        impl DumpValue for $ty {
+            #[inline]
+            fn dump_value(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{:#x}", self)
+            }
+        }
+    )*}
+}
+
+macro_rules! dump_signed {
+    ($($ty:ty),*) => {$(
+        impl DumpValue for $ty {
+            #[inline]
+            fn dump_value(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                if *self < 0 {
+                    write!(f, "-{:#x}", self.unsigned_abs())
+                } else {
+                    write!(f, "{:#x}", self)
+                }
+            }
+        }
+    )*}
+}
+
+macro_rules! dump_debug {
+    ($($ty:ty),*) => {$(
+        impl DumpValue for $ty {
+            #[inline]
+            fn dump_value(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{:?}", self)
+            }
+        }
+    )*}
+}
+
+dump_unsigned!(u8, u16, u32, u64, u128, usize);
+dump_signed!(i8, i16, i32, i64, i128, isize);
+dump_debug!(bool, char, f32, f64, str, String);
+
+impl<T: DumpValue> DumpValue for [T] {
+    fn dump_value(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("[")?;
+        for (i, element) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            element.dump_value(f)?;
+        }
+        f.write_str("]")
+    }
+}
+
+impl<T: DumpValue, const N: usize> DumpValue for [T; N] {
+    #[inline]
+    fn dump_value(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self[..].dump_value(f)
+    }
+}
+
+impl<T: DumpValue> DumpValue for Vec<T> {
+    #[inline]
+    fn dump_value(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self[..].dump_value(f)
+    }
+}
+
+impl<T: DumpValue> DumpValue for Option<T> {
+    #[inline]
+    fn dump_value(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Some(ref value) => value.dump_value(f),
+            None => f.write_str("None"),
+        }
+    }
+}
+
+impl<T: DumpValue + ?Sized> DumpValue for &T {
+    #[inline]
+    fn dump_value(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (**self).dump_value(f)
+    }
+}
+
+/// A value which can be shown in an offset dump, read with `Ctx`
+///
+/// Values with a [DumpValue](trait.DumpValue.html) are shown on their field's line; a struct deriving `Pread` with
+/// `#[scroll(debug_offsets)]` reads itself again from the bytes it was read from, showing its own fields, indented
+/// below it.
+pub trait DumpWith<'a, Ctx> {
+    /// Shows the value, which was read from `src`, the bytes it took, at `offset`, with `ctx`
+    fn dump_with(&self, dumper: &mut Dumper, src: &'a [u8], offset: usize, ctx: Ctx);
+    /// Reads a value from the start of `src` with `ctx`, and shows it, as being at `offset`; a read which fails is
+    /// shown, and returned
+    fn dump_from(src: &'a [u8], dumper: &mut Dumper, offset: usize, ctx: Ctx) -> error::Result<(Self, usize)>
+        where Self: Sized + TryFromCtx<'a, Ctx, Error = error::Error>,
+              Ctx: Copy,
+    {
+        match Self::try_from_ctx(src, ctx) {
+            Ok((value, size)) => {
+                value.dump_with(dumper, src.get(..size).unwrap_or(src), offset, ctx);
+                Ok((value, size))
+            },
+            Err(e) => {
+                dumper.error(&e);
+                Err(e)
+            },
+        }
+    }
+}
+
+impl<'a, T: DumpValue + ?Sized, Ctx> DumpWith<'a, Ctx> for T {
+    #[inline]
+    fn dump_with(&self, dumper: &mut Dumper, _src: &'a [u8], _offset: usize, _ctx: Ctx) {
+        dumper.value(self)
+    }
+}
+
+/// Writes the lines of an offset dump, one per field: its offset in hex, its size in bytes, and its name and value,
+/// indented by how deeply the field is nested
+///
+/// Once writing fails, nothing more is written, and the dump returns the error.
+pub struct Dumper<'a, 'b> {
+    f: &'a mut fmt::Formatter<'b>,
+    depth: usize,
+    lines: usize,
+    result: fmt::Result,
+}
+
+impl<'a, 'b> Dumper<'a, 'b> {
+    /// Starts a line, after the ones before it
+    fn line(&mut self) -> bool {
+        if self.result.is_ok() && self.lines > 0 {
+            self.result = self.f.write_str("\n");
+        }
+        self.lines += 1;
+        self.result.is_ok()
+    }
+    /// Starts the line of the field `name`, which takes `size` bytes at `offset`
+    pub fn field(&mut self, name: &str, offset: usize, size: usize) {
+        if self.line() {
+            self.result = write!(self.f, "{:#06x} {:>4}  {:indent$}{}:", offset, size, "", name, indent = self.depth * 2);
+        }
+    }
+    /// Shows `value` on the current line
+    pub fn value<T: DumpValue + ?Sized>(&mut self, value: &T) {
+        if self.result.is_ok() && self.lines > 0 {
+            self.result = self.f.write_str(" ");
+        }
+        if self.result.is_ok() {
+            self.result = value.dump_value(self.f);
+        }
+    }
+    /// Shows the fields which `fields` starts the lines of as those of the current field, indented below it
+    pub fn nested(&mut self, fields: impl FnOnce(&mut Self)) {
+        self.depth += 1;
+        fields(self);
+        self.depth -= 1;
+    }
+    /// Shows the error a read failed with, on a line of its own
+    pub fn error(&mut self, error: &error::Error) {
+        if self.line() {
+            self.result = write!(self.f, "{:13}{:indent$}<{}>", "", "", error, indent = self.depth * 2);
+        }
+    }
+}
+
+/// Displays a value read from a source as an offset dump: a line for each of its fields, with its offset in the
+/// source, its size, and its value; returned by the `dump` function derived with `#[scroll(debug_offsets)]`
+///
+/// The value is read each time it is displayed, and a read which fails is shown along with what was read before it.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx::StrCtx, OffsetDump, LE};
+///
+/// let bytes = [0x00, 0x2a, 0x00, b'a', b'\n', 0x00];
+/// assert_eq!(OffsetDump::<u16, _>::new(&bytes, 1, LE).to_string(), "0x2a");
+/// assert_eq!(OffsetDump::<&str, _>::new(&bytes, 3, StrCtx::Delimiter(0)).to_string(), "\"a\\n\"");
+/// assert_eq!(OffsetDump::<u32, _>::new(&bytes, 3, LE).to_string(), "             <incomplete input, 1 more bytes needed>");
+/// ```
+pub struct OffsetDump<'a, T: ?Sized, Ctx> {
+    src: &'a [u8],
+    offset: usize,
+    ctx: Ctx,
+    value: PhantomData<fn() -> T>,
+}
+
+impl<'a, T: ?Sized, Ctx> OffsetDump<'a, T, Ctx> {
+    /// Dumps the `T` at `offset` in `src`, read with `ctx`
+    #[inline]
+    pub fn new(src: &'a [u8], offset: usize, ctx: Ctx) -> Self {
+        OffsetDump { src, offset, ctx, value: PhantomData }
+    }
+}
+
+impl<'a, T, Ctx: Copy> fmt::Display for OffsetDump<'a, T, Ctx>
+    where T: DumpWith<'a, Ctx> + TryFromCtx<'a, Ctx, Error = error::Error>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut dumper = Dumper { f, depth: 0, lines: 0, result: Ok(()) };
+        match self.src.get(self.offset..) {
+            Some(src) => {
+                let _ = T::dump_from(src, &mut dumper, self.offset, self.ctx);
+            },
+            None => dumper.error(&error::Error::BadOffset(self.offset)),
+        }
+        dumper.result
+    }
+}
@@ -0,0 +1,208 @@
+use std::borrow::Cow;
+
+use encoding_rs::{DecoderResult, Encoding, EncoderResult};
+
+use crate::ctx::{Str, StrCtx, TryFromCtx, TryIntoCtx};
+use crate::error;
+
+/// What to do with bytes, or characters, which the charset cannot represent
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EncodingMode {
+    /// Fail with an `Error::Invalid` carrying the offset of the first malformed byte sequence when reading, or of the
+    /// first unmappable character of the string when writing
+    Strict,
+    /// Decode malformed sequences to U+FFFD, and encode unmappable characters as HTML numeric character references,
+    /// as `encoding_rs` does
+    Lossy,
+}
+
+/// The context for reading and writing strings in a charset other than UTF-8, e.g., Windows-1252 or Shift-JIS;
+/// requires the `encoding` feature
+///
+/// The string is framed by a [StrCtx](ctx/enum.StrCtx.html), exactly like a `&str` is. The framing is byte oriented, so
+/// the delimiter and padding must be single bytes which the charset never uses inside of a character; this holds
+/// for the single byte charsets and the ASCII compatible multi-byte ones, but not for UTF-16.
+///
+/// Strings are read as a `Cow<str>`, which borrows from the source whenever the bytes are already valid UTF-8 (e.g.,
+/// ASCII text), or as a `String`, and are written by wrapping them in a [Str](ctx/struct.Str.html).
+///
+/// # Example
+/// ```rust
+/// use std::borrow::Cow;
+/// use scroll::{Pread, Pwrite, EncodingCtx, EncodingMode, ctx::{Str, StrCtx}};
+///
+/// let ctx = EncodingCtx::new(encoding_rs::WINDOWS_1252, StrCtx::Delimiter(0), EncodingMode::Strict);
+/// let bytes = b"caf\xe9\0";
+/// let name: Cow<str> = bytes.pread_with(0, ctx).unwrap();
+/// assert_eq!(name, "café");
+///
+/// let mut out = [0u8; 5];
+/// assert_eq!(out.pwrite_with(Str("café"), 0, ctx).unwrap(), 5);
+/// assert_eq!(&out, bytes);
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct EncodingCtx {
+    /// The charset of the bytes
+    pub encoding: &'static Encoding,
+    /// How the string is delimited
+    pub framing: StrCtx,
+    /// Whether malformed or unmappable input is an error, or is replaced
+    pub mode: EncodingMode,
+}
+
+impl EncodingCtx {
+    pub fn new(encoding: &'static Encoding, framing: StrCtx, mode: EncodingMode) -> Self {
+        EncodingCtx { encoding, framing, mode }
+    }
+
+    fn decode<'a>(&self, bytes: &'a [u8]) -> error::Result<Cow<'a, str>> {
+        if self.mode == EncodingMode::Lossy {
+            return Ok(self.encoding.decode_without_bom_handling(bytes).0);
+        }
+        if let Some(s) = self.encoding.decode_without_bom_handling_and_without_replacement(bytes) {
+            return Ok(s);
+        }
+        // decode once more, incrementally, to find out where the malformed sequence starts
+        let mut decoder = self.encoding.new_decoder_without_bom_handling();
+        let mut s = String::with_capacity(decoder.max_utf8_buffer_length_without_replacement(bytes.len()).unwrap_or(bytes.len()));
+        let mut offset = 0;
+        loop {
+            let (result, read) = decoder.decode_to_string_without_replacement(&bytes[offset..], &mut s, true);
+            offset += read;
+            match result {
+                DecoderResult::InputEmpty => return Ok(Cow::Owned(s)),
+                DecoderResult::OutputFull => s.reserve(bytes.len() - offset + 16),
+                DecoderResult::Malformed(bad, after) => {
+                    return Err(error::Error::Invalid { offset: offset - bad as usize - after as usize, msg: "malformed byte sequence" })
+                }
+            }
+        }
+    }
+
+    fn encode<'a>(&self, s: &'a str) -> error::Result<Cow<'a, [u8]>> {
+        if self.encoding.output_encoding() != self.encoding {
+            return Err(error::Error::BadInput { size: s.len(), msg: "strings cannot be encoded in this charset" });
+        }
+        if self.mode == EncodingMode::Lossy {
+            return Ok(self.encoding.encode(s).0);
+        }
+        let mut encoder = self.encoding.new_encoder();
+        let mut bytes = Vec::with_capacity(encoder.max_buffer_length_from_utf8_without_replacement(s.len()).unwrap_or(s.len()));
+        let mut offset = 0;
+        loop {
+            let (result, read) = encoder.encode_from_utf8_to_vec_without_replacement(&s[offset..], &mut bytes, true);
+            offset += read;
+            match result {
+                EncoderResult::InputEmpty => return Ok(Cow::Owned(bytes)),
+                EncoderResult::OutputFull => bytes.reserve(s.len() - offset + 16),
+                EncoderResult::Unmappable(c) => {
+                    return Err(error::Error::Invalid { offset: offset - c.len_utf8(), msg: "unmappable character" })
+                }
+            }
+        }
+    }
+}
+
+impl<'a> TryFromCtx<'a, EncodingCtx> for Cow<'a, str> {
+    type Error = error::Error;
+    #[inline]
+    fn try_from_ctx(src: &'a [u8], ctx: EncodingCtx) -> error::Result<(Self, usize)> {
+        let (bytes, size) = ctx.framing.frame(src)?;
+        Ok((ctx.decode(bytes)?, size))
+    }
+}
+
+impl<'a> TryFromCtx<'a, EncodingCtx> for String {
+    type Error = error::Error;
+    #[inline]
+    fn try_from_ctx(src: &'a [u8], ctx: EncodingCtx) -> error::Result<(Self, usize)> {
+        let (s, size) = Cow::<str>::try_from_ctx(src, ctx)?;
+        Ok((s.into_owned(), size))
+    }
+}
+
+impl<'a> TryIntoCtx<EncodingCtx> for Str<'a> {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], ctx: EncodingCtx) -> error::Result<usize> {
+        let bytes = ctx.encode(self.0)?;
+        ctx.framing.write_framed(&bytes, dst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use encoding_rs::{SHIFT_JIS, UTF_16LE, WINDOWS_1252};
+    use crate::{Pread, Pwrite, Error};
+    use crate::ctx::{Str, StrCtx};
+    use super::{EncodingCtx, EncodingMode};
+
+    // "テスト" followed by a NUL terminator
+    const SHIFT_JIS_TEST: &[u8] = b"\x83\x65\x83\x58\x83\x67\0";
+    // "Ærø €5" in an 8 byte, space padded field
+    const WINDOWS_1252_FIELD: &[u8] = b"\xc6r\xf8 \x805  ";
+
+    #[test]
+    fn shift_jis_round_trip() {
+        for &mode in &[EncodingMode::Strict, EncodingMode::Lossy] {
+            let ctx = EncodingCtx::new(SHIFT_JIS, StrCtx::Delimiter(0), mode);
+            let mut offset = 0;
+            let s: String = SHIFT_JIS_TEST.gread_with(&mut offset, ctx).unwrap();
+            assert_eq!(s, "テスト");
+            assert_eq!(offset, SHIFT_JIS_TEST.len());
+
+            let mut bytes = [0xffu8; 7];
+            assert_eq!(bytes.pwrite_with(Str(&s), 0, ctx).unwrap(), 7);
+            assert_eq!(&bytes[..], SHIFT_JIS_TEST);
+        }
+    }
+
+    #[test]
+    fn windows_1252_round_trip() {
+        for &mode in &[EncodingMode::Strict, EncodingMode::Lossy] {
+            let ctx = EncodingCtx::new(WINDOWS_1252, StrCtx::Fixed { len: 8, pad: b' ' }, mode);
+            let s: Cow<str> = WINDOWS_1252_FIELD.pread_with(0, ctx).unwrap();
+            assert_eq!(s, "Ærø €5");
+
+            let mut bytes = [0u8; 8];
+            assert_eq!(bytes.pwrite_with(Str(&s), 0, ctx).unwrap(), 8);
+            assert_eq!(&bytes[..], WINDOWS_1252_FIELD);
+        }
+    }
+
+    #[test]
+    fn ascii_borrows() {
+        let ctx = EncodingCtx::new(WINDOWS_1252, StrCtx::Length(5), EncodingMode::Strict);
+        let s: Cow<str> = b"hello world".pread_with(0, ctx).unwrap();
+        assert!(matches!(s, Cow::Borrowed("hello")));
+    }
+
+    #[test]
+    fn malformed() {
+        // a lead byte followed by a byte which cannot trail it
+        let bytes = b"\x83\x65ab\x83\x7f\0";
+        let strict = EncodingCtx::new(SHIFT_JIS, StrCtx::Delimiter(0), EncodingMode::Strict);
+        let err = bytes.pread_with::<String>(0, strict).unwrap_err();
+        assert!(matches!(err, Error::Invalid { offset: 4, .. }), "{:?}", err);
+
+        let lossy = EncodingCtx { mode: EncodingMode::Lossy, ..strict };
+        let s: String = bytes.pread_with(0, lossy).unwrap();
+        assert_eq!(s, "テab\u{fffd}\u{7f}");
+    }
+
+    #[test]
+    fn unmappable() {
+        let strict = EncodingCtx::new(WINDOWS_1252, StrCtx::Delimiter(0), EncodingMode::Strict);
+        let mut bytes = [0u8; 16];
+        let err = bytes.pwrite_with(Str("ab日c"), 0, strict).unwrap_err();
+        assert!(matches!(err, Error::Invalid { offset: 2, .. }), "{:?}", err);
+
+        let lossy = EncodingCtx { mode: EncodingMode::Lossy, ..strict };
+        assert_eq!(bytes.pwrite_with(Str("ab日c"), 0, lossy).unwrap(), 12);
+        assert_eq!(&bytes[..12], b"ab&#26085;c\0");
+
+        let utf16 = EncodingCtx { encoding: UTF_16LE, ..lossy };
+        assert!(bytes.pwrite_with(Str("ab"), 0, utf16).is_err());
+    }
+}
@@ -1,3 +1,10 @@
+#[cfg(feature = "default-endian")]
+use core::cell::Cell;
+#[cfg(feature = "default-endian")]
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::error;
+
 #[derive(PartialEq, Eq, Copy, Debug, Clone)]
 /// The endianness (byte order) of a stream of bytes
 pub enum Endian {
@@ -18,11 +25,96 @@ pub const NATIVE: Endian = LE;
 /// The machine's native byte order
 pub const NATIVE: Endian = BE;
 
+#[cfg(feature = "default-endian")]
+std::thread_local! {
+    static DEFAULT: Cell<Endian> = const { Cell::new(NATIVE) };
+}
+
+/// Whether any thread has ever set a default other than the machine's byte order; until one does, every read of the
+/// default can skip the thread local
+#[cfg(feature = "default-endian")]
+static OVERRIDDEN: AtomicBool = AtomicBool::new(false);
+
+/// The byte order the calls which don't give one use: the machine's, unless, with the `default-endian` feature, this
+/// thread has set another with [set_default_endian](fn.set_default_endian.html)
 impl Default for Endian {
     #[inline]
     fn default() -> Self {
-        NATIVE
+        #[cfg(feature = "default-endian")]
+        {
+            if !OVERRIDDEN.load(Ordering::Relaxed) {
+                return NATIVE;
+            }
+            DEFAULT.try_with(Cell::get).unwrap_or(NATIVE)
+        }
+        #[cfg(not(feature = "default-endian"))]
+        {
+            NATIVE
+        }
+    }
+}
+
+/// Sets the byte order that the calls on this thread which don't give one use, returning the previous one; requires the
+/// `default-endian` feature
+///
+/// This is what `Endian::default()` returns, so it's what `pread`, `gread`, `pwrite`, `cread`, `ioread` and the other
+/// methods without a context read and write the primitives with, and what `pread_into` uses for them. It never affects
+/// a call given a context, nor a type with a [DefaultCtx](ctx/trait.DefaultCtx.html) of its own, like a
+/// `#[scroll(default_ctx = "BE")]` struct: those say what they mean, and win. It only applies to the current thread;
+/// other threads keep theirs, starting with the machine's byte order.
+///
+/// Prefer [with_default_endian](fn.with_default_endian.html), which puts the previous byte order back.
+///
+/// # Example
+/// ```rust
+/// use scroll::{set_default_endian, Pread, BE, LE, NATIVE};
+///
+/// let bytes = [0xde, 0xad, 0xbe, 0xef];
+/// let previous = set_default_endian(BE);
+/// assert_eq!(previous, NATIVE);
+/// assert_eq!(bytes.pread::<u32>(0).unwrap(), 0xdeadbeef);
+/// // an explicit byte order is unaffected
+/// assert_eq!(bytes.pread_with::<u32>(0, LE).unwrap(), 0xefbeadde);
+/// set_default_endian(previous);
+/// ```
+#[cfg(feature = "default-endian")]
+pub fn set_default_endian(endian: Endian) -> Endian {
+    if endian != NATIVE {
+        // this thread's own later reads see it, as the store happens before them
+        OVERRIDDEN.store(true, Ordering::Relaxed);
+    }
+    DEFAULT.with(|default| default.replace(endian))
+}
+
+/// Runs `f` with `endian` as the byte order of the calls on this thread which don't give one, as
+/// [set_default_endian](fn.set_default_endian.html) sets it, and then restores the previous one, even if `f` panics;
+/// requires the `default-endian` feature
+///
+/// # Example
+/// ```rust
+/// use scroll::{with_default_endian, Pread, Pwrite, NETWORK};
+///
+/// let mut packet = [0u8; 4];
+/// let port = with_default_endian(NETWORK, || {
+///     packet.pwrite(8080u16, 0).unwrap();
+///     packet.pread::<u16>(0).unwrap()
+/// });
+/// assert_eq!(port, 8080);
+/// assert_eq!(packet[..2], [0x1f, 0x90]);
+/// ```
+#[cfg(feature = "default-endian")]
+pub fn with_default_endian<R>(endian: Endian, f: impl FnOnce() -> R) -> R {
+    /// Puts the previous byte order back when dropped, on a return or an unwind alike
+    struct Restore(Endian);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            set_default_endian(self.0);
+        }
     }
+
+    let _restore = Restore(set_default_endian(endian));
+    f()
 }
 
 impl From<bool> for Endian {
@@ -39,9 +131,66 @@ impl Endian {
     }
     #[inline]
     pub fn is_little(&self) -> bool {
-        match *self {
-            LE => true,
-            _ => false,
+        matches!(*self, LE)
+    }
+    /// Detects the byte order of `src` from the mark at `offset`, which is either `le_magic` or `be_magic`, returning it and
+    /// the length of the mark
+    ///
+    /// Neither mark matching is an `Error::UnknownByteOrder`, carrying the bytes found where the longer mark would be; the
+    /// marks are at most 8 bytes long. If `src` ends before the shorter mark would, it is an `Error::Incomplete`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use scroll::{Endian, Error, Pread, LE};
+    ///
+    /// // a TIFF header: the byte order, 42, and the offset of the first IFD
+    /// let bytes = [b'I', b'I', 0x2a, 0x00, 0x08, 0x00, 0x00, 0x00];
+    /// let (endian, offset) = Endian::detect(&bytes, 0, b"II", b"MM").unwrap();
+    /// assert_eq!(endian, LE);
+    /// assert_eq!(bytes.pread_with::<u16>(offset, endian).unwrap(), 42);
+    /// assert_eq!(bytes.pread_with::<u32>(offset + 2, endian).unwrap(), 8);
+    ///
+    /// let err = Endian::detect(b"IM*\0", 0, b"II", b"MM").unwrap_err();
+    /// assert_eq!(err.to_string(), "unknown byte order at offset 0: found 0x494d");
+    /// ```
+    pub fn detect(src: &[u8], offset: usize, le_magic: &[u8], be_magic: &[u8]) -> error::Result<(Endian, usize)> {
+        let len = le_magic.len().max(be_magic.len());
+        if len > 8 {
+            return Err(error::Error::BadInput { size: len, msg: "byte order mark is longer than 8 bytes" });
+        }
+        let rest = src.get(offset..).ok_or(error::Error::BadOffset(offset))?;
+        if rest.starts_with(le_magic) {
+            return Ok((LE, le_magic.len()));
+        }
+        if rest.starts_with(be_magic) {
+            return Ok((BE, be_magic.len()));
+        }
+        let shortest = le_magic.len().min(be_magic.len());
+        if rest.len() < shortest {
+            return Err(error::Error::Incomplete { needed: shortest - rest.len() });
+        }
+        let found = rest[..len.min(rest.len())].iter().fold(0u64, |n, b| n << 8 | u64::from(*b));
+        Err(error::Error::UnknownByteOrder { offset, found })
+    }
+    /// Detects the byte order of `src` from the `width` byte mark at `offset`, which is either `le_magic` or `be_magic`, like
+    /// `detect`; the marks are the bytes read as big endian numbers
+    ///
+    /// # Example
+    /// ```rust
+    /// use scroll::{Endian, BE, LE};
+    ///
+    /// // ELF's EI_DATA byte: 1 is little endian, 2 is big endian
+    /// let ident = [0x7f, b'E', b'L', b'F', 2, 2, 1];
+    /// assert_eq!(Endian::detect_int(&ident, 5, 1, 1, 2).unwrap(), (BE, 1));
+    /// // a Mach-O magic, stored in the file's byte order
+    /// let bytes = [0xce, 0xfa, 0xed, 0xfe];
+    /// assert_eq!(Endian::detect_int(&bytes, 0, 4, 0xcefaedfe, 0xfeedface).unwrap(), (LE, 4));
+    /// ```
+    pub fn detect_int(src: &[u8], offset: usize, width: usize, le_magic: u64, be_magic: u64) -> error::Result<(Endian, usize)> {
+        if width == 0 || width > 8 {
+            return Err(error::Error::BadInput { size: width, msg: "byte order mark is not 1 to 8 bytes wide" });
         }
+        let (le, be) = (le_magic.to_be_bytes(), be_magic.to_be_bytes());
+        Endian::detect(src, offset, &le[8 - width..], &be[8 - width..])
     }
 }
@@ -0,0 +1,152 @@
+use crate::ctx::{SizeWith, TryFromCtx};
+use crate::error;
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use crate::prelude::*;
+
+/// Values at most this large are read through a buffer on the stack; larger ones need the `alloc` feature
+const SCRATCH: usize = 64;
+
+/// An object safe byte source, for handing "something to read from" to trait objects, like format plugins, which can't be
+/// generic over a [Pread](trait.Pread.html) source
+///
+/// Every `AsRef<[u8]>` source implements it. A `dyn ErasedPread` reads fixed size values with
+/// [pread_with](#method.pread_with), [gread_with](#method.gread_with) and [gread_inout_with](#method.gread_inout_with),
+/// which copy the value's bytes out with `read_bytes` and parse the copy; so values can't borrow from the source, and
+/// only the erased path pays for the dynamic dispatch.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ErasedPread, LE};
+///
+/// fn magic(src: &dyn ErasedPread) -> scroll::Result<u32> {
+///     src.pread_with(0, LE)
+/// }
+///
+/// let bytes = vec![0xef, 0xbe, 0xad, 0xde];
+/// assert_eq!(magic(&bytes).unwrap(), 0xdeadbeef);
+/// // a slice is unsized, so it is erased behind a reference to it
+/// assert_eq!(magic(&&bytes[..]).unwrap(), 0xdeadbeef);
+/// assert!(magic(&&bytes[..3]).is_err());
+/// ```
+pub trait ErasedPread {
+    /// Copies `out.len()` bytes at `offset` into `out`
+    fn read_bytes(&self, offset: usize, out: &mut [u8]) -> error::Result<()>;
+    /// The number of bytes in the source
+    fn len(&self) -> usize;
+    /// Whether the source has no bytes at all
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: AsRef<[u8]> + ?Sized> ErasedPread for T {
+    fn read_bytes(&self, offset: usize, out: &mut [u8]) -> error::Result<()> {
+        let src = self.as_ref();
+        if offset > src.len() {
+            return Err(error::Error::BadOffset(offset));
+        }
+        let src = &src[offset..];
+        if out.len() > src.len() {
+            return Err(error::Error::TooBig { size: out.len(), len: src.len() });
+        }
+        out.copy_from_slice(&src[..out.len()]);
+        Ok(())
+    }
+    #[inline]
+    fn len(&self) -> usize {
+        self.as_ref().len()
+    }
+}
+
+impl<'e> dyn ErasedPread + 'e {
+    /// Reads a value at `offset` with the given `ctx`
+    #[inline]
+    pub fn pread_with<N, Ctx>(&self, offset: usize, ctx: Ctx) -> error::Result<N>
+        where N: for<'b> TryFromCtx<'b, Ctx, Error = error::Error> + SizeWith<Ctx>,
+              Ctx: Copy,
+    {
+        self.gread_with(&mut { offset }, ctx)
+    }
+    /// Reads a value at `offset` with the given `ctx`, and advances the offset past it
+    pub fn gread_with<N, Ctx>(&self, offset: &mut usize, ctx: Ctx) -> error::Result<N>
+        where N: for<'b> TryFromCtx<'b, Ctx, Error = error::Error> + SizeWith<Ctx>,
+              Ctx: Copy,
+    {
+        let size = N::size_with(&ctx);
+        let (n, read) = if size <= SCRATCH {
+            let mut scratch = [0u8; SCRATCH];
+            self.read_bytes(*offset, &mut scratch[..size])?;
+            N::try_from_ctx(&scratch[..size], ctx)?
+        } else {
+            self.read_large(*offset, size, ctx)?
+        };
+        *offset += read;
+        Ok(n)
+    }
+    /// Reads values into all of `inout` with the given `ctx`, starting at `offset`, and advances the offset past them
+    pub fn gread_inout_with<N, Ctx>(&self, offset: &mut usize, inout: &mut [N], ctx: Ctx) -> error::Result<()>
+        where N: for<'b> TryFromCtx<'b, Ctx, Error = error::Error> + SizeWith<Ctx>,
+              Ctx: Copy,
+    {
+        for slot in inout.iter_mut() {
+            *slot = self.gread_with(offset, ctx)?;
+        }
+        Ok(())
+    }
+    #[cfg(feature = "alloc")]
+    fn read_large<N, Ctx>(&self, offset: usize, size: usize, ctx: Ctx) -> error::Result<(N, usize)>
+        where N: for<'b> TryFromCtx<'b, Ctx, Error = error::Error>,
+              Ctx: Copy,
+    {
+        // a size from the context can be anything; check it against the source before allocating for it
+        let len = self.len().checked_sub(offset).ok_or(error::Error::BadOffset(offset))?;
+        if size > len {
+            return Err(error::Error::TooBig { size, len });
+        }
+        let mut scratch = vec![0u8; size];
+        self.read_bytes(offset, &mut scratch)?;
+        N::try_from_ctx(&scratch, ctx)
+    }
+    #[cfg(not(feature = "alloc"))]
+    fn read_large<N, Ctx>(&self, _offset: usize, size: usize, _ctx: Ctx) -> error::Result<(N, usize)>
+        where N: for<'b> TryFromCtx<'b, Ctx, Error = error::Error>,
+              Ctx: Copy,
+    {
+        Err(error::Error::BadInput { size, msg: "value is too large to read from an erased source without alloc" })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BE;
+
+    struct Block([u8; 80]);
+
+    impl<'a> TryFromCtx<'a> for Block {
+        type Error = error::Error;
+        fn try_from_ctx(src: &'a [u8], _: ()) -> error::Result<(Self, usize)> {
+            let mut block = [0u8; 80];
+            block.copy_from_slice(&src[..80]);
+            Ok((Block(block), 80))
+        }
+    }
+
+    impl SizeWith for Block {
+        fn size_with(_: &()) -> usize {
+            80
+        }
+    }
+
+    #[test]
+    fn erased_large_and_short() {
+        let bytes: [u8; 100] = core::array::from_fn(|i| i as u8);
+        let src: &dyn ErasedPread = &bytes;
+        let offset = &mut 10;
+        let block: Block = src.gread_with(offset, ()).unwrap();
+        assert_eq!(block.0[..], bytes[10..90]);
+        assert_eq!(*offset, 90);
+        assert!(matches!(src.pread_with::<Block, _>(30, ()), Err(error::Error::TooBig { size: 80, len: 70 })));
+        assert!(matches!(src.pread_with::<u16, _>(101, BE), Err(error::Error::BadOffset(101))));
+    }
+}
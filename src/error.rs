@@ -5,6 +5,8 @@ use core::result;
 use std::io;
 #[cfg(feature = "std")]
 use std::error;
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use crate::prelude::*;
 
 #[derive(Debug)]
 /// A custom Scroll error
@@ -14,12 +16,256 @@ pub enum Error {
     /// The requested offset to read/write at is invalid
     BadOffset(usize),
     BadInput{ size: usize, msg: &'static str },
-    #[cfg(feature = "std")]
+    /// The requested range `start..end` is reversed, or reaches past `len`
+    BadRange { start: usize, end: usize, len: usize },
+    /// The source ended before the value did, and at least `needed` more bytes past its end are required to read it
+    ///
+    /// Unlike the other errors, this one is not permanent: a streaming parser can wait for more input, and retry at the same offset.
+    Incomplete { needed: usize },
+    /// The value parsed at `offset` violates an invariant, described by `msg`; see `ctx::Validate`
+    Invalid { offset: usize, msg: &'static str },
+    /// The magic at `offset` was `found` rather than `expected`; byte string magics are stored as big endian numbers
+    BadMagic { offset: usize, expected: u64, found: u64 },
+    /// The bytes at `offset`, `found` as a big endian number, are neither of the byte order marks; see `Endian::detect`
+    UnknownByteOrder { offset: usize, found: u64 },
+    /// A limit of the parse's `ctx::Limits` budget, named by the message (`"depth"` or `"bytes"`), the `ctx::AllocLimit`
+    /// of a read (`"alloc"`), or the most a `Counter` or `to_vec` grows its buffer to (`"size"`), was exceeded
+    LimitExceeded(&'static str),
+    /// Allocating `size` bytes failed; returned instead of aborting by the fallible allocation paths, like
+    /// `CowBuffer::try_zeroed` and `ctx::TryAlloc`
+    AllocFailed { size: usize },
+    #[cfg(feature = "alloc")]
+    /// Reading or writing element `index` of a sequence, which started `offset` bytes into it, failed with `source`
+    Element { index: usize, offset: usize, source: Box<Error> },
+    #[cfg(feature = "alloc")]
     /// A custom Scroll error for reporting messages to clients
     Custom(String),
     #[cfg(feature = "std")]
     /// Returned when IO based errors are encountered
     IO(io::Error),
+    #[cfg(feature = "std")]
+    /// An error assembled with an `ErrorBuilder`, with an optional offset and underlying `source`
+    Detailed(Box<DetailedError>),
+    #[cfg(feature = "alloc")]
+    /// Reading or writing the field `name` failed with `source`; see `ResultExt::field`
+    Field { name: &'static str, source: Box<Error> },
+    #[cfg(feature = "alloc")]
+    /// `source` happened while doing what `msg` describes; see `ResultExt::context`
+    Context { msg: String, source: Box<Error> },
+    #[cfg(feature = "location-tracking")]
+    /// `source` was returned at line `line` of the source file `file`; see `Error::at_location` and `pread_here!`
+    Located { file: &'static str, line: u32, source: Box<Error> },
+}
+
+#[cfg(feature = "std")]
+impl Error {
+    /// Starts building a `Error::Detailed`
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::error::Error as _;
+    /// use scroll::Error;
+    ///
+    /// let io = std::io::Error::new(std::io::ErrorKind::Other, "disk on fire");
+    /// let err = Error::builder().with_offset(8).with_message("bad header").with_source(io).build();
+    /// assert_eq!(err.to_string(), "bad header at offset 8: disk on fire");
+    /// assert_eq!(err.offset(), Some(8));
+    /// assert!(err.source().is_some());
+    /// ```
+    pub fn builder() -> ErrorBuilder {
+        ErrorBuilder::default()
+    }
+}
+
+/// The bytes shown on either side of the failing offset by `Error::display_with_source`
+const REPORT_WIDTH: usize = 16;
+
+struct SourceReport<'a> {
+    err: &'a Error,
+    src: &'a [u8],
+}
+
+impl<'a> Display for SourceReport<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.err)?;
+        let offset = match self.err.offset() {
+            Some(offset) => offset,
+            None => return Ok(()),
+        };
+        let len = self.src.len();
+        if len == 0 {
+            return write!(fmt, "\n  (the input is empty)");
+        }
+        // center the failing byte, keeping the window inside of the input
+        let start = offset.saturating_sub(REPORT_WIDTH / 2).min(len.saturating_sub(REPORT_WIDTH));
+        let end = (start + REPORT_WIDTH).min(len);
+        write!(fmt, "\n  {:08x}:", start)?;
+        for byte in &self.src[start..end] {
+            write!(fmt, " {:02x}", byte)?;
+        }
+        if offset < end {
+            write!(fmt, "\n  {:width$}^^", "", width = 10 + 3 * (offset - start))
+        } else {
+            write!(fmt, "\n  (offset {} is past the end of the input, which is {} bytes)", offset, len)
+        }
+    }
+}
+
+impl Error {
+    /// The offset this error occurred at, if it carries one
+    pub fn offset(&self) -> Option<usize> {
+        match *self {
+            Error::BadOffset(offset) => Some(offset),
+            Error::Invalid{ offset, .. } => Some(offset),
+            Error::BadMagic{ offset, .. } => Some(offset),
+            Error::UnknownByteOrder{ offset, .. } => Some(offset),
+            #[cfg(feature = "alloc")]
+            Error::Element{ offset, .. } => Some(offset),
+            #[cfg(feature = "std")]
+            Error::Detailed(ref detail) => detail.offset,
+            #[cfg(feature = "alloc")]
+            Error::Field{ ref source, .. } | Error::Context{ ref source, .. } => source.offset(),
+            #[cfg(feature = "location-tracking")]
+            Error::Located{ ref source, .. } => source.offset(),
+            _ => None,
+        }
+    }
+
+    /// Displays this error followed, when it carries an offset, by a hexdump of the (at most 16) bytes of `src` around
+    /// that offset, with a caret under the failing byte
+    ///
+    /// # Example
+    /// ```rust
+    /// use scroll::Error;
+    ///
+    /// let bytes = [0x7fu8, b'E', b'L', b'F', 0x02];
+    /// let err = Error::Invalid { offset: 4, msg: "bad class" };
+    /// assert_eq!(err.display_with_source(&bytes).to_string(), "\
+    /// invalid value at offset 4: bad class
+    ///   00000000: 7f 45 4c 46 02
+    ///                         ^^");
+    /// ```
+    pub fn display_with_source<'a>(&'a self, src: &'a [u8]) -> impl Display + 'a {
+        SourceReport { err: self, src }
+    }
+}
+
+impl Error {
+    /// Adds `base` to the offset of this error, if it carries one, for an error from reading a subslice which starts `base`
+    /// bytes into a larger source
+    pub(crate) fn rebase(self, base: usize) -> Error {
+        match self {
+            Error::BadOffset(offset) => Error::BadOffset(base.saturating_add(offset)),
+            Error::Invalid{ offset, msg } => Error::Invalid{ offset: base.saturating_add(offset), msg },
+            Error::BadMagic{ offset, expected, found } => Error::BadMagic{ offset: base.saturating_add(offset), expected, found },
+            Error::UnknownByteOrder{ offset, found } => Error::UnknownByteOrder{ offset: base.saturating_add(offset), found },
+            #[cfg(feature = "alloc")]
+            Error::Element{ index, offset, source } => Error::Element{ index, offset: base.saturating_add(offset), source },
+            #[cfg(feature = "std")]
+            Error::Detailed(mut detail) => {
+                detail.offset = detail.offset.map(|offset| base.saturating_add(offset));
+                Error::Detailed(detail)
+            },
+            #[cfg(feature = "alloc")]
+            Error::Field{ name, source } => Error::Field{ name, source: Box::new(source.rebase(base)) },
+            #[cfg(feature = "alloc")]
+            Error::Context{ msg, source } => Error::Context{ msg, source: Box::new(source.rebase(base)) },
+            #[cfg(feature = "location-tracking")]
+            Error::Located{ file, line, source } => Error::Located{ file, line, source: Box::new(source.rebase(base)) },
+            err => err,
+        }
+    }
+}
+
+impl Error {
+    /// Records that this error was returned at line `line` of `file`, wrapping it in an `Error::Located`; usually
+    /// called through [pread_here](macro.pread_here.html)
+    ///
+    /// Without the `location-tracking` feature this returns the error unchanged, so call sites cost nothing.
+    #[cfg(feature = "location-tracking")]
+    #[inline]
+    pub fn at_location(self, file: &'static str, line: u32) -> Error {
+        Error::Located { file, line, source: Box::new(self) }
+    }
+    /// Records that this error was returned at line `line` of `file`, wrapping it in an `Error::Located`; usually
+    /// called through [pread_here](macro.pread_here.html)
+    ///
+    /// Without the `location-tracking` feature this returns the error unchanged, so call sites cost nothing.
+    #[cfg(not(feature = "location-tracking"))]
+    #[inline]
+    pub fn at_location(self, _file: &'static str, _line: u32) -> Error {
+        self
+    }
+}
+
+/// Reads like `pread`, or `pread_with` when given a `ctx`, recording where the macro was invoked in the error; with
+/// the `location-tracking` feature, the error is an `Error::Located` which displays as `file:line: error`
+///
+/// # Example
+/// ```rust
+/// use scroll::{pread_here, LE};
+///
+/// let bytes = [0x01, 0x02];
+/// assert_eq!(pread_here!(bytes, 0, LE).map(|n: u16| n).unwrap(), 0x0201);
+/// let err = pread_here!(bytes, 1, LE).map(|n: u16| n).unwrap_err();
+/// if cfg!(feature = "location-tracking") {
+///     assert!(err.to_string().ends_with(": incomplete input, 1 more bytes needed"));
+/// }
+/// ```
+#[macro_export]
+macro_rules! pread_here {
+    ($src:expr, $offset:expr) => {{
+        use $crate::Pread as _;
+        $src.pread($offset).map_err(|err: $crate::Error| err.at_location(file!(), line!()))
+    }};
+    ($src:expr, $offset:expr, $ctx:expr) => {{
+        use $crate::Pread as _;
+        $src.pread_with($offset, $ctx).map_err(|err: $crate::Error| err.at_location(file!(), line!()))
+    }};
+}
+
+/// What an `Error::Detailed` holds, boxed so that it doesn't make every `Error` larger
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct DetailedError {
+    /// The offset the error occurred at, if known
+    pub offset: Option<usize>,
+    /// The message describing the error
+    pub msg: String,
+    /// The underlying error, returned by `source()`
+    pub source: Option<Box<dyn error::Error + Send + Sync>>,
+}
+
+/// A builder for an `Error::Detailed`, for errors which don't fit any of the other variants; see `Error::builder`
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+pub struct ErrorBuilder {
+    offset: Option<usize>,
+    msg: String,
+    source: Option<Box<dyn error::Error + Send + Sync>>,
+}
+
+#[cfg(feature = "std")]
+impl ErrorBuilder {
+    /// The offset the error occurred at
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+    /// The message describing the error
+    pub fn with_message(mut self, msg: impl Display) -> Self {
+        self.msg = msg.to_string();
+        self
+    }
+    /// The underlying error, returned by `source()`
+    pub fn with_source(mut self, source: impl error::Error + Send + Sync + 'static) -> Self {
+        self.source = Some(Box::new(source));
+        self
+    }
+    /// The `Error::Detailed` with the offset, message and source given so far
+    pub fn build(self) -> Error {
+        Error::Detailed(Box::new(DetailedError { offset: self.offset, msg: self.msg, source: self.source }))
+    }
 }
 
 #[cfg(feature = "std")]
@@ -29,17 +275,43 @@ impl error::Error for Error {
             Error::TooBig{ .. } => { "TooBig" }
             Error::BadOffset(_) => { "BadOffset" }
             Error::BadInput{ .. } => { "BadInput" }
+            Error::BadRange{ .. } => { "BadRange" }
+            Error::Incomplete{ .. } => { "Incomplete" }
+            Error::Invalid{ .. } => { "Invalid" }
+            Error::BadMagic{ .. } => { "BadMagic" }
+            Error::UnknownByteOrder{ .. } => { "UnknownByteOrder" }
+            Error::LimitExceeded(_) => { "LimitExceeded" }
+            Error::AllocFailed{ .. } => { "AllocFailed" }
+            Error::Element{ .. } => { "Element" }
             Error::Custom(_) => { "Custom" }
             Error::IO(_) => { "IO" }
+            Error::Detailed(_) => { "Detailed" }
+            Error::Field{ .. } => { "Field" }
+            Error::Context{ .. } => { "Context" }
+            #[cfg(feature = "location-tracking")]
+            Error::Located{ .. } => { "Located" }
         }
     }
-    fn cause(&self) -> Option<&error::Error> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match *self {
             Error::TooBig{ .. } => { None }
             Error::BadOffset(_) => { None }
             Error::BadInput{ .. } => { None }
+            Error::BadRange{ .. } => { None }
+            Error::Incomplete{ .. } => { None }
+            Error::Invalid{ .. } => { None }
+            Error::BadMagic{ .. } => { None }
+            Error::UnknownByteOrder{ .. } => { None }
+            Error::LimitExceeded(_) => { None }
+            Error::AllocFailed{ .. } => { None }
+            Error::Element{ ref source, .. } => { Some(&**source) }
             Error::Custom(_) => { None }
-            Error::IO(ref io) => { io.cause() }
+            Error::IO(ref io) => { io.source() }
+            Error::Detailed(ref detail) => { detail.source.as_ref().map(|source| &**source as &(dyn error::Error + 'static)) }
+            Error::Field{ ref source, .. } => { Some(&**source) }
+            Error::Context{ ref source, .. } => { Some(&**source) }
+            #[cfg(feature = "location-tracking")]
+            Error::Located{ ref source, .. } => { Some(&**source) }
         }
     }
 }
@@ -51,18 +323,204 @@ impl From<io::Error> for Error {
     }
 }
 
+#[cfg(feature = "std")]
+impl Error {
+    /// The kind of `io::Error` this error converts to: running out of input is an `UnexpectedEof`, input which fails to
+    /// validate is `InvalidData`, and a failed allocation is `OutOfMemory`; the wrappers take the kind of what they wrap
+    ///
+    /// This is the kind for an error from reading; see [io_write_kind](#method.io_write_kind) for one from writing.
+    pub fn io_kind(&self) -> io::ErrorKind {
+        match *self {
+            Error::TooBig{ .. } | Error::BadOffset(_) | Error::BadRange{ .. } | Error::Incomplete{ .. } => io::ErrorKind::UnexpectedEof,
+            Error::AllocFailed{ .. } => io::ErrorKind::OutOfMemory,
+            Error::IO(ref err) => err.kind(),
+            Error::Element{ ref source, .. } | Error::Field{ ref source, .. } | Error::Context{ ref source, .. } => source.io_kind(),
+            #[cfg(feature = "location-tracking")]
+            Error::Located{ ref source, .. } => source.io_kind(),
+            Error::Detailed(ref detail) => match detail.source {
+                Some(ref source) => if let Some(err) = source.downcast_ref::<Error>() {
+                    err.io_kind()
+                } else if let Some(err) = source.downcast_ref::<io::Error>() {
+                    err.kind()
+                } else {
+                    io::ErrorKind::InvalidData
+                },
+                None => io::ErrorKind::InvalidData,
+            },
+            _ => io::ErrorKind::InvalidData,
+        }
+    }
+    /// The kind of `io::Error` for this error when it comes from writing: running out of room in the destination is a
+    /// `WriteZero`, as it is for an `io::Write`, and anything else is the [io_kind](#method.io_kind)
+    pub fn io_write_kind(&self) -> io::ErrorKind {
+        match *self {
+            Error::TooBig{ .. } | Error::BadOffset(_) | Error::BadRange{ .. } => io::ErrorKind::WriteZero,
+            Error::Element{ ref source, .. } | Error::Field{ ref source, .. } | Error::Context{ ref source, .. } => source.io_write_kind(),
+            #[cfg(feature = "location-tracking")]
+            Error::Located{ ref source, .. } => source.io_write_kind(),
+            Error::Detailed(ref detail) => match detail.source.as_ref().and_then(|source| source.downcast_ref::<Error>()) {
+                Some(err) => err.io_write_kind(),
+                None => self.io_kind(),
+            },
+            _ => self.io_kind(),
+        }
+    }
+    /// Converts an error from writing to an `io::Error` of the [io_write_kind](#method.io_write_kind), wrapping the
+    /// error, as the `From` conversion does for one from reading
+    ///
+    /// # Example
+    /// ```rust
+    /// use scroll::{Pwrite, LE};
+    ///
+    /// fn header(bytes: &mut [u8]) -> std::io::Result<usize> {
+    ///     bytes.pwrite_with(0x7f45_4c46u32, 0, LE).map_err(scroll::Error::into_io_write_error)
+    /// }
+    ///
+    /// assert_eq!(header(&mut [0; 2]).unwrap_err().kind(), std::io::ErrorKind::WriteZero);
+    /// ```
+    pub fn into_io_write_error(self) -> io::Error {
+        match self {
+            Error::IO(err) => err,
+            err => io::Error::new(err.io_write_kind(), err),
+        }
+    }
+}
+
+/// Converts to an `io::Error` of the [io_kind](enum.Error.html#method.io_kind), wrapping the error, so that `get_ref`
+/// downcasts back to it; an `Error::IO` converts to the `io::Error` it holds
+///
+/// # Example
+/// ```rust
+/// use scroll::{Pread, LE};
+///
+/// fn magic(bytes: &[u8]) -> std::io::Result<u32> {
+///     Ok(bytes.pread_with(0, LE)?)
+/// }
+///
+/// let err = magic(&[0x7f, 0x45]).unwrap_err();
+/// assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+/// let inner = err.get_ref().unwrap().downcast_ref::<scroll::Error>().unwrap();
+/// assert!(matches!(inner, scroll::Error::Incomplete { needed: 2 }));
+/// ```
+#[cfg(feature = "std")]
+impl From<Error> for io::Error {
+    fn from(err: Error) -> io::Error {
+        match err {
+            Error::IO(err) => err,
+            err => io::Error::new(err.io_kind(), err),
+        }
+    }
+}
+
 impl Display for Error {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             Error::TooBig{ ref size, ref len } => { write! (fmt, "type is too big ({}) for {}", size, len) },
             Error::BadOffset(ref offset) => { write! (fmt, "bad offset {}", offset) },
             Error::BadInput{ ref msg, ref size } => { write! (fmt, "bad input {} ({})", msg, size) },
-            #[cfg(feature = "std")]
+            Error::BadRange{ ref start, ref end, ref len } => { write! (fmt, "bad range {}..{} for {}", start, end, len) },
+            Error::Incomplete{ ref needed } => { write! (fmt, "incomplete input, {} more bytes needed", needed) },
+            Error::Invalid{ ref offset, ref msg } => { write! (fmt, "invalid value at offset {}: {}", offset, msg) },
+            Error::BadMagic{ ref offset, ref expected, ref found } => { write! (fmt, "bad magic at offset {}: expected {:#x}, found {:#x}", offset, expected, found) },
+            Error::UnknownByteOrder{ ref offset, ref found } => { write! (fmt, "unknown byte order at offset {}: found {:#x}", offset, found) },
+            Error::LimitExceeded(ref limit) => { write! (fmt, "{} limit exceeded", limit) },
+            Error::AllocFailed{ ref size } => { write! (fmt, "failed to allocate {} bytes", size) },
+            #[cfg(feature = "alloc")]
+            Error::Element{ ref index, ref offset, ref source } => { write! (fmt, "element {} at offset {}: {}", index, offset, source) },
+            #[cfg(feature = "alloc")]
             Error::Custom(ref msg) => { write! (fmt, "{}", msg) },
             #[cfg(feature = "std")]
             Error::IO(ref err) => { write!(fmt, "{}", err) },
+            #[cfg(feature = "std")]
+            Error::Detailed(ref detail) => {
+                write!(fmt, "{}", detail.msg)?;
+                if let Some(offset) = detail.offset {
+                    write!(fmt, " at offset {}", offset)?;
+                }
+                if let Some(ref source) = detail.source {
+                    write!(fmt, ": {}", source)?;
+                }
+                Ok(())
+            },
+            // nested fields make a path, e.g., `header.e_phoff: bad offset 64`
+            #[cfg(feature = "alloc")]
+            Error::Field{ ref name, ref source } => match **source {
+                Error::Field{ .. } => { write!(fmt, "{}.{}", name, source) },
+                _ => { write!(fmt, "{}: {}", name, source) },
+            },
+            #[cfg(feature = "alloc")]
+            Error::Context{ ref msg, ref source } => { write!(fmt, "{}: {}", msg, source) },
+            #[cfg(feature = "location-tracking")]
+            Error::Located{ ref file, ref line, ref source } => { write!(fmt, "{}:{}: {}", file, line, source) },
         }
     }
 }
 
 pub type Result<T> = result::Result<T, Error>;
+
+/// Labels the error of a result with what was being read or written when it happened, keeping the original error as
+/// its `source`
+///
+/// Nothing is allocated unless the result is an error.
+///
+/// # Example
+/// ```rust
+/// use scroll::{Pread, LE, ResultExt};
+///
+/// fn phoff(bytes: &[u8]) -> scroll::Result<u64> {
+///     bytes.pread_with::<u64>(0x20, LE).field("e_phoff")
+/// }
+///
+/// let err = phoff(&[0; 0x24]).field("header").unwrap_err();
+/// assert_eq!(err.to_string(), "header.e_phoff: incomplete input, 4 more bytes needed");
+/// ```
+#[cfg(feature = "alloc")]
+pub trait ResultExt<T> {
+    /// Wraps the error in an `Error::Field` named `name`; fields wrapping fields display as a dotted path
+    fn field(self, name: &'static str) -> Result<T>;
+    /// Wraps the error in an `Error::Context` describing what was being done
+    fn context<C: Display>(self, context: C) -> Result<T>;
+}
+
+#[cfg(feature = "alloc")]
+impl<T, E: Into<Error>> ResultExt<T> for result::Result<T, E> {
+    #[inline]
+    fn field(self, name: &'static str) -> Result<T> {
+        self.map_err(|err| Error::Field { name, source: Box::new(err.into()) })
+    }
+    #[inline]
+    fn context<C: Display>(self, context: C) -> Result<T> {
+        self.map_err(|err| Error::Context { msg: context.to_string(), source: Box::new(err.into()) })
+    }
+}
+
+/// The error of reading a value and then converting it, as with `Pread::pread_try_into`: either step can fail
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CombinedError<P, C> {
+    /// Reading the intermediate value failed
+    Parse(P),
+    /// Converting the intermediate value failed
+    Convert(C),
+}
+
+impl<P: Display, C: Display> Display for CombinedError<P, C> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CombinedError::Parse(ref err) => { write! (fmt, "{}", err) },
+            CombinedError::Convert(ref err) => { write! (fmt, "conversion failed: {}", err) },
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<P, C> error::Error for CombinedError<P, C>
+    where P: error::Error + 'static,
+          C: error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            CombinedError::Parse(ref err) => { Some(err) }
+            CombinedError::Convert(ref err) => { Some(err) }
+        }
+    }
+}
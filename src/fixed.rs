@@ -0,0 +1,199 @@
+use core::result;
+
+use crate::ctx::{TryFromCtx, TryIntoCtx, SizeWith};
+use crate::endian::Endian;
+use crate::error;
+
+/// A binary fixed-point number, stored as the integer `I` with `FRAC` fractional bits (Q-format)
+///
+/// E.g., `Fixed<i32, 16>` is a signed Q16.16 number, as used for the rate of an MP4 `mvhd` box, and `Fixed<u32, 30>`
+/// an unsigned Q2.30 number. Reading and writing honors the `Endian` context like the underlying integer does.
+///
+/// # Example
+/// ```rust
+/// use scroll::{Fixed, Pread, BE};
+///
+/// let bytes = [0x00, 0x01, 0x80, 0x00];
+/// let rate: Fixed<i32, 16> = bytes.pread_with(0, BE).unwrap();
+/// assert_eq!(rate.to_f64(), 1.5);
+/// assert_eq!(rate.to_bits(), 0x0001_8000);
+/// assert_eq!(Fixed::<i32, 16>::from_f64(1.5), Some(rate));
+/// ```
+///
+/// `FRAC` is at most the number of bits of `I`; converting a number with more fractional bits fails to compile:
+/// ```compile_fail
+/// let _ = scroll::Fixed::<u8, 9>::from_bits(1).to_f64();
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fixed<I, const FRAC: u32>(I);
+
+impl<I, const FRAC: u32> Fixed<I, FRAC> {
+    /// Creates a fixed-point number from its raw, stored bits
+    #[inline]
+    pub fn from_bits(bits: I) -> Self {
+        Fixed(bits)
+    }
+    /// The raw, stored bits of this fixed-point number
+    #[inline]
+    pub fn to_bits(self) -> I {
+        self.0
+    }
+}
+
+#[inline]
+fn scale(frac: u32) -> f64 {
+    (1u128 << frac) as f64
+}
+
+macro_rules! fixed_impl {
+    ($($ty:ty),*) => {
+        $(
+        impl<const FRAC: u32> Fixed<$ty, FRAC> {
+            // referenced by the conversions, so that a `FRAC` wider than the integer is a compile error where it's used
+            const FRAC_FITS: () = assert!(FRAC <= <$ty>::BITS, "a fixed-point number has at most as many fractional bits as its integer");
+            /// Converts this fixed-point number to an `f64`, which is exact as long as the stored integer fits in 53 bits
+            #[inline]
+            pub fn to_f64(self) -> f64 {
+                let () = Self::FRAC_FITS;
+                self.0 as f64 / scale(FRAC)
+            }
+            /// Converts `value` to the nearest fixed-point number, rounding halfway cases away from zero
+            ///
+            /// Returns `None` if `value` is NaN, or out of the range of this fixed-point type.
+            pub fn from_f64(value: f64) -> Option<Self> {
+                let () = Self::FRAC_FITS;
+                if value.is_nan() {
+                    return None;
+                }
+                let scaled = value * scale(FRAC);
+                // `as` saturates, which the range check below then rejects
+                let truncated = scaled as i128;
+                let fraction = scaled - truncated as f64;
+                let rounded = if fraction >= 0.5 {
+                    truncated.checked_add(1)?
+                } else if fraction <= -0.5 {
+                    truncated.checked_sub(1)?
+                } else {
+                    truncated
+                };
+                if rounded < <$ty>::MIN as i128 || rounded > <$ty>::MAX as i128 {
+                    None
+                } else {
+                    Some(Fixed(rounded as $ty))
+                }
+            }
+        }
+        )*
+    }
+}
+
+fixed_impl!(i8, u8, i16, u16, i32, u32, i64, u64);
+
+impl<'a, I, const FRAC: u32> TryFromCtx<'a, Endian> for Fixed<I, FRAC>
+    where I: TryFromCtx<'a, Endian, Error = error::Error>
+{
+    type Error = error::Error;
+    #[inline]
+    fn try_from_ctx(src: &'a [u8], ctx: Endian) -> result::Result<(Self, usize), Self::Error> {
+        let (bits, size) = I::try_from_ctx(src, ctx)?;
+        Ok((Fixed(bits), size))
+    }
+}
+
+impl<I, const FRAC: u32> TryIntoCtx<Endian> for Fixed<I, FRAC>
+    where I: TryIntoCtx<Endian, Error = error::Error>
+{
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], ctx: Endian) -> error::Result<usize> {
+        self.0.try_into_ctx(dst, ctx)
+    }
+}
+
+impl<I: SizeWith<Endian>, const FRAC: u32> SizeWith<Endian> for Fixed<I, FRAC> {
+    #[inline]
+    fn size_with(ctx: &Endian) -> usize {
+        I::size_with(ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Fixed;
+    use crate::{Pread, Pwrite, BE, LE};
+
+    #[test]
+    fn mvhd_fields() {
+        // rate (Q16.16), volume (Q8.8) and the first matrix entries (Q16.16 and Q2.30) of an MP4 `mvhd` box
+        let bytes = [0x00, 0x01, 0x00, 0x00,
+                     0x01, 0x00,
+                     0x00, 0x01, 0x00, 0x00,
+                     0x40, 0x00, 0x00, 0x00];
+        let offset = &mut 0;
+        let rate: Fixed<i32, 16> = bytes.gread_with(offset, BE).unwrap();
+        let volume: Fixed<i16, 8> = bytes.gread_with(offset, BE).unwrap();
+        let a: Fixed<i32, 16> = bytes.gread_with(offset, BE).unwrap();
+        let w: Fixed<i32, 30> = bytes.gread_with(offset, BE).unwrap();
+        assert_eq!(*offset, 14);
+        assert_eq!(rate.to_f64(), 1.0);
+        assert_eq!(volume.to_f64(), 1.0);
+        assert_eq!(a.to_f64(), 1.0);
+        assert_eq!(w.to_f64(), 1.0);
+
+        let mut out = [0u8; 14];
+        let offset = &mut 0;
+        out.gwrite_with(Fixed::<i32, 16>::from_f64(1.0).unwrap(), offset, BE).unwrap();
+        out.gwrite_with(Fixed::<i16, 8>::from_f64(1.0).unwrap(), offset, BE).unwrap();
+        out.gwrite_with(a, offset, BE).unwrap();
+        out.gwrite_with(Fixed::<i32, 30>::from_f64(1.0).unwrap(), offset, BE).unwrap();
+        assert_eq!(out, bytes);
+    }
+
+    #[test]
+    fn signed_and_unsigned() {
+        // -1.5 in Q16.16 is sign extended, not read as a large positive number
+        let bytes = [0x00, 0x80, 0xfe, 0xff];
+        let signed: Fixed<i32, 16> = bytes.pread_with(0, LE).unwrap();
+        assert_eq!(signed.to_f64(), -1.5);
+        assert_eq!(signed.to_bits(), -0x18000);
+        let unsigned: Fixed<u32, 16> = bytes.pread_with(0, LE).unwrap();
+        assert_eq!(unsigned.to_f64(), 65534.5);
+        assert_eq!(Fixed::<i32, 16>::from_f64(-1.5), Some(signed));
+        assert_eq!(Fixed::<u32, 16>::from_f64(65534.5), Some(unsigned));
+        // Q2.30
+        assert_eq!(Fixed::<i32, 30>::from_bits(-0x4000_0000).to_f64(), -1.0);
+        assert_eq!(Fixed::<u32, 30>::from_bits(0xc000_0000).to_f64(), 3.0);
+    }
+
+    #[test]
+    fn from_f64_rounding_and_range() {
+        type Q8 = Fixed<i16, 8>;
+        // halfway cases round away from zero
+        assert_eq!(Q8::from_f64(0.5 / 256.0).unwrap().to_bits(), 1);
+        assert_eq!(Q8::from_f64(-0.5 / 256.0).unwrap().to_bits(), -1);
+        assert_eq!(Q8::from_f64(0.49 / 256.0).unwrap().to_bits(), 0);
+        assert_eq!(Q8::from_f64(1.0 / 3.0).unwrap().to_bits(), 85);
+        // the extremes of the range
+        assert_eq!(Q8::from_f64(-128.0).unwrap().to_bits(), i16::MIN);
+        assert_eq!(Q8::from_f64(127.99609375).unwrap().to_bits(), i16::MAX);
+        assert_eq!(Q8::from_f64(128.0), None);
+        assert_eq!(Q8::from_f64(-128.01), None);
+        assert_eq!(Fixed::<u16, 8>::from_f64(-0.01), None);
+        assert_eq!(Q8::from_f64(f64::NAN), None);
+        assert_eq!(Q8::from_f64(f64::INFINITY), None);
+        assert_eq!(Fixed::<u64, 0>::from_f64(u64::MAX as f64), None);
+        assert_eq!(Fixed::<u64, 0>::from_f64(1e19).unwrap().to_bits(), 10_000_000_000_000_000_000);
+    }
+
+    #[test]
+    fn all_bits_fractional() {
+        // as many fractional bits as the integer has, which covers [0, 1) unsigned and [-0.5, 0.5) signed
+        assert_eq!(Fixed::<u8, 8>::from_bits(0x80).to_f64(), 0.5);
+        assert_eq!(Fixed::<i8, 8>::from_bits(i8::MIN).to_f64(), -0.5);
+        assert_eq!(Fixed::<u8, 8>::from_f64(0.99609375).unwrap().to_bits(), u8::MAX);
+        assert_eq!(Fixed::<u8, 8>::from_f64(1.0), None);
+        assert_eq!(Fixed::<u64, 64>::from_bits(1 << 63).to_f64(), 0.5);
+        assert_eq!(Fixed::<u64, 64>::from_f64(0.25).unwrap().to_bits(), 1 << 62);
+        assert_eq!(Fixed::<i64, 64>::from_f64(0.5), None);
+    }
+}
@@ -0,0 +1,181 @@
+use core::fmt::LowerHex;
+
+use bitflags::{Bits, Flags};
+
+use crate::ctx::{TryFromCtx, TryIntoCtx};
+use crate::endian::Endian;
+use crate::error;
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use crate::prelude::*;
+
+/// What reading a flag word does with bits which don't correspond to any of its flags
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum UnknownBits {
+    /// Fail with an error naming the unknown bits, located at the flag word
+    Reject,
+    /// Drop them, like `from_bits_truncate`
+    Truncate,
+}
+
+/// The context for reading and writing a [bitflags](https://docs.rs/bitflags) type as its underlying integer, in the
+/// given byte order; requires the `bitflags` feature
+///
+/// A flags type opts in with the [flags_ctx](macro.flags_ctx.html) macro. Writing always emits `.bits()`, unknown bits
+/// included.
+///
+/// # Example
+/// ```rust
+/// use scroll::{Pread, Pwrite, FlagsCtx, LE};
+///
+/// bitflags::bitflags! {
+///     #[derive(Debug, PartialEq, Eq)]
+///     struct Mode: u16 {
+///         const READ = 0x1;
+///         const WRITE = 0x2;
+///     }
+/// }
+/// scroll::flags_ctx!(Mode);
+///
+/// let bytes = [0x03, 0x80];
+/// assert!(bytes.pread_with::<Mode>(0, FlagsCtx::strict(LE)).is_err());
+/// assert_eq!(bytes.pread_with::<Mode>(0, FlagsCtx::truncating(LE)).unwrap(), Mode::READ | Mode::WRITE);
+///
+/// let mut out = [0u8; 2];
+/// out.pwrite_with(Mode::WRITE, 0, FlagsCtx::strict(LE)).unwrap();
+/// assert_eq!(out, [0x02, 0x00]);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FlagsCtx {
+    /// The byte order of the flag word
+    pub endian: Endian,
+    /// What reading does with unknown bits
+    pub unknown: UnknownBits,
+    /// The offset of the flag word in its source, which the error for unknown bits reports; see [at](#method.at)
+    pub offset: usize,
+}
+
+impl FlagsCtx {
+    /// Unknown bits are an error
+    pub fn strict(endian: Endian) -> Self {
+        FlagsCtx { endian, unknown: UnknownBits::Reject, offset: 0 }
+    }
+    /// Unknown bits are dropped
+    pub fn truncating(endian: Endian) -> Self {
+        FlagsCtx { endian, unknown: UnknownBits::Truncate, offset: 0 }
+    }
+    /// The same context, for a flag word `offset` bytes into its source, as `pread_with_ctx_fn` passes it
+    ///
+    /// A value only sees its own bytes, so without this the error for unknown bits is at offset 0. Reads through an
+    /// [OffsetView](offset/struct.OffsetView.html), whose errors already carry absolute offsets, don't need it.
+    ///
+    /// # Example
+    /// ```rust
+    /// use scroll::{Error, Pread, FlagsCtx, LE};
+    ///
+    /// bitflags::bitflags! {
+    ///     struct Mode: u8 {
+    ///         const READ = 0x1;
+    ///     }
+    /// }
+    /// scroll::flags_ctx!(Mode);
+    ///
+    /// let bytes = [0x01, 0x01, 0x81];
+    /// let err = bytes.pread_with_ctx_fn::<Mode, _>(2, |at| FlagsCtx::strict(LE).at(at)).err().unwrap();
+    /// assert_eq!(err.offset(), Some(2));
+    /// ```
+    #[inline]
+    pub fn at(self, offset: usize) -> Self {
+        FlagsCtx { offset, ..self }
+    }
+}
+
+#[cfg(feature = "alloc")]
+fn unknown_bits<B: LowerHex>(bits: B, offset: usize) -> error::Error {
+    error::Error::Context {
+        msg: format!("unknown flag bits {:#x}", bits),
+        source: Box::new(error::Error::Invalid { offset, msg: "flag word has bits outside of its flags" }),
+    }
+}
+
+#[cfg(not(feature = "alloc"))]
+fn unknown_bits<B: LowerHex>(_bits: B, offset: usize) -> error::Error {
+    error::Error::Invalid { offset, msg: "flag word has bits outside of its flags" }
+}
+
+/// Reads the flags type `F` for the impls [flags_ctx](macro.flags_ctx.html) generates
+#[doc(hidden)]
+pub fn read_flags<'a, F>(src: &'a [u8], ctx: FlagsCtx) -> error::Result<(F, usize)>
+    where F: Flags,
+          F::Bits: TryFromCtx<'a, Endian, Error = error::Error> + LowerHex,
+{
+    let (bits, size) = F::Bits::try_from_ctx(src, ctx.endian)?;
+    let flags = match ctx.unknown {
+        UnknownBits::Reject => {
+            let unknown = bits & !F::all().bits();
+            if unknown != F::Bits::EMPTY {
+                return Err(unknown_bits(unknown, ctx.offset));
+            }
+            F::from_bits_retain(bits)
+        },
+        UnknownBits::Truncate => F::from_bits_truncate(bits),
+    };
+    Ok((flags, size))
+}
+
+/// Writes the flags value `flags` for the impls [flags_ctx](macro.flags_ctx.html) generates
+#[doc(hidden)]
+pub fn write_flags<F>(flags: &F, dst: &mut [u8], ctx: FlagsCtx) -> error::Result<usize>
+    where F: Flags,
+          F::Bits: TryIntoCtx<Endian, Error = error::Error>,
+{
+    flags.bits().try_into_ctx(dst, ctx.endian)
+}
+
+/// Implements reading and writing a [bitflags](https://docs.rs/bitflags) type with a [FlagsCtx](struct.FlagsCtx.html),
+/// for the type and references to it; requires the `bitflags` feature
+///
+/// # Example
+/// ```rust
+/// bitflags::bitflags! {
+///     struct Access: u32 {
+///         const READ = 1 << 0;
+///         const EXECUTE = 1 << 4;
+///     }
+/// }
+/// scroll::flags_ctx!(Access);
+/// ```
+#[macro_export]
+macro_rules! flags_ctx {
+    ($ty:ty) => {
+        impl<'a> $crate::ctx::TryFromCtx<'a, $crate::FlagsCtx> for $ty {
+            type Error = $crate::Error;
+            #[inline]
+            fn try_from_ctx(src: &'a [u8], ctx: $crate::FlagsCtx) -> $crate::export::result::Result<(Self, usize), Self::Error> {
+                $crate::read_flags(src, ctx)
+            }
+        }
+
+        impl<'f> $crate::ctx::TryIntoCtx<$crate::FlagsCtx> for &'f $ty {
+            type Error = $crate::Error;
+            #[inline]
+            fn try_into_ctx(self, dst: &mut [u8], ctx: $crate::FlagsCtx) -> $crate::export::result::Result<usize, Self::Error> {
+                $crate::write_flags(self, dst, ctx)
+            }
+        }
+
+        impl $crate::ctx::TryIntoCtx<$crate::FlagsCtx> for $ty {
+            type Error = $crate::Error;
+            #[inline]
+            fn try_into_ctx(self, dst: &mut [u8], ctx: $crate::FlagsCtx) -> $crate::export::result::Result<usize, Self::Error> {
+                $crate::write_flags(&self, dst, ctx)
+            }
+        }
+
+        impl $crate::ctx::SizeWith<$crate::FlagsCtx> for $ty {
+            #[inline]
+            fn size_with(_ctx: &$crate::FlagsCtx) -> usize {
+                $crate::export::mem::size_of::<<$ty as $crate::export::bitflags::Flags>::Bits>()
+            }
+        }
+    }
+}
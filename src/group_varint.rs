@@ -0,0 +1,362 @@
+//! Group varint encoded `u32`s, four to a group, as used by search indexes
+//!
+//! A group is a tag byte followed by four little endian values, each in as few bytes as it fits in, between 1 and 4.
+//! The tag holds the width of each value, less one, in two bits, the first value's in the most significant bits: the
+//! group `1, 15, 511, 131071` is the tag `0b00_00_01_10`, then `01`, `0f`, `ff 01` and `ff ff 01`. Decoding a group
+//! takes a single bounds check, where [LEB128](../leb128/index.html) needs one per byte, and no branching on the bytes.
+//!
+//! A run whose count isn't a multiple of four ends in a partial group, which is only as long as the values left: the tag
+//! has the widths of those, and zeroes for the rest, and no bytes follow for the missing values. As the group doesn't
+//! say how many values it holds, the count comes from elsewhere, e.g., a header.
+//!
+//! [GroupVarint](struct.GroupVarint.html) is also exported at the crate root.
+
+use crate::ctx::{MeasureWith, TryFromCtx, TryIntoCtx};
+use crate::error;
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use crate::prelude::*;
+
+/// A group of up to four group varint encoded `u32`s, four unless it ends a run
+///
+/// It reads a full group with the `()` context, as in a `pread`; [read_partial](#method.read_partial) reads one
+/// with fewer values.
+///
+/// # Example
+/// ```rust
+/// use scroll::{GroupVarint, Pread, Pwrite};
+///
+/// let bytes = [0x06, 0x01, 0x0f, 0xff, 0x01, 0xff, 0xff, 0x01];
+/// let group: GroupVarint = bytes.pread(0).unwrap();
+/// assert_eq!(group.values(), [1, 15, 511, 131071]);
+/// assert_eq!(group.size(), 8);
+///
+/// let mut out = [0u8; 8];
+/// assert_eq!(out.pwrite(group, 0).unwrap(), 8);
+/// assert_eq!(out, bytes);
+/// ```
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone, Hash)]
+pub struct GroupVarint {
+    values: [u32; 4],
+    count: usize,
+}
+
+/// The number of bytes `value` is encoded in, between 1 and 4
+#[inline]
+fn width(value: u32) -> usize {
+    4 - (value.leading_zeros() as usize / 8).min(3)
+}
+
+/// Decodes a group of `count` values, between 1 and 4, from the front of `src`, which starts `offset` bytes into its
+/// source, returning them and the group's size
+#[inline]
+fn decode(src: &[u8], offset: usize, count: usize) -> error::Result<([u32; 4], usize)> {
+    let (&tag, payload) = src.split_first().ok_or(error::Error::Incomplete { needed: 1 })?;
+    let mut widths = [0; 4];
+    let mut size = 0;
+    for (i, width) in widths.iter_mut().enumerate() {
+        let field = (tag >> (6 - 2 * i)) & 0b11;
+        if i < count {
+            *width = usize::from(field) + 1;
+            size += *width;
+        } else if field != 0 {
+            return Err(error::Error::Invalid { offset, msg: "group varint tag has widths past the end of the group" });
+        }
+    }
+    // the tag says how long the group is, before any of it is read
+    if size > payload.len() {
+        return Err(error::Error::Incomplete { needed: size - payload.len() });
+    }
+    let mut values = [0; 4];
+    let mut at = 0;
+    for (value, &width) in values.iter_mut().zip(&widths[..count]) {
+        let mut bytes = [0; 4];
+        bytes[..width].copy_from_slice(&payload[at..at + width]);
+        *value = u32::from_le_bytes(bytes);
+        at += width;
+    }
+    Ok((values, 1 + size))
+}
+
+impl GroupVarint {
+    /// A full group of four values
+    #[inline]
+    pub fn new(values: [u32; 4]) -> Self {
+        GroupVarint { values, count: 4 }
+    }
+    /// A group of the 1 to 4 `values`; any other number of them is an `Error::BadInput`
+    pub fn partial(values: &[u32]) -> error::Result<Self> {
+        if values.is_empty() || values.len() > 4 {
+            return Err(error::Error::BadInput { size: values.len(), msg: "a group holds between 1 and 4 values" });
+        }
+        let mut group = GroupVarint { values: [0; 4], count: values.len() };
+        group.values[..values.len()].copy_from_slice(values);
+        Ok(group)
+    }
+    /// The values of the group
+    #[inline]
+    pub fn values(&self) -> &[u32] {
+        &self.values[..self.count]
+    }
+    /// The number of values in the group
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.count
+    }
+    /// The number of bytes the group is encoded in, its tag included
+    #[inline]
+    pub fn size(&self) -> usize {
+        1 + self.values().iter().map(|&value| width(value)).sum::<usize>()
+    }
+    /// Reads a full group from `bytes` at `offset`, and advances the offset past it
+    ///
+    /// # Example
+    /// ```rust
+    /// use scroll::GroupVarint;
+    ///
+    /// let bytes = [0xff, 0b00_00_00_01, 0x01, 0x02, 0x03, 0x00, 0x01];
+    /// let offset = &mut 1;
+    /// assert_eq!(GroupVarint::read(&bytes, offset).unwrap(), [1, 2, 3, 256]);
+    /// assert_eq!(*offset, 7);
+    /// assert!(GroupVarint::read(&bytes[..6], &mut 1).is_err());
+    /// ```
+    #[inline]
+    pub fn read(bytes: &[u8], offset: &mut usize) -> error::Result<[u32; 4]> {
+        let src = bytes.get(*offset..).ok_or(error::Error::BadOffset(*offset))?;
+        let (values, size) = decode(src, *offset, 4)?;
+        *offset += size;
+        Ok(values)
+    }
+    /// Reads a group of `count` values, between 1 and 4, from `bytes` at `offset`, and advances the offset past it;
+    /// the tag must have zeroes for the values past the count
+    ///
+    /// # Example
+    /// ```rust
+    /// use scroll::GroupVarint;
+    ///
+    /// let bytes = [0b01_00_00_00, 0x2c, 0x01, 0x07];
+    /// let offset = &mut 0;
+    /// assert_eq!(GroupVarint::read_partial(&bytes, offset, 2).unwrap().values(), [300, 7]);
+    /// assert_eq!(*offset, 4);
+    /// // a width for a third value means the count is wrong
+    /// assert!(GroupVarint::read_partial(&[0b00_00_01_00, 0, 0, 0, 0], &mut 0, 2).is_err());
+    /// ```
+    pub fn read_partial(bytes: &[u8], offset: &mut usize, count: usize) -> error::Result<GroupVarint> {
+        if count == 0 || count > 4 {
+            return Err(error::Error::BadInput { size: count, msg: "a group holds between 1 and 4 values" });
+        }
+        let src = bytes.get(*offset..).ok_or(error::Error::BadOffset(*offset))?;
+        let (values, size) = decode(src, *offset, count)?;
+        *offset += size;
+        Ok(GroupVarint { values, count })
+    }
+    /// Writes the 1 to 4 `values` into `bytes` at `offset` as a group, each in as few bytes as it fits in, and advances
+    /// the offset past it, returning the group's size
+    ///
+    /// # Example
+    /// ```rust
+    /// use scroll::GroupVarint;
+    ///
+    /// let mut bytes = [0u8; 8];
+    /// let offset = &mut 0;
+    /// assert_eq!(GroupVarint::write(&mut bytes, offset, &[300, 7]).unwrap(), 4);
+    /// assert_eq!(&bytes[..*offset], [0b01_00_00_00, 0x2c, 0x01, 0x07]);
+    /// ```
+    pub fn write(bytes: &mut [u8], offset: &mut usize, values: &[u32]) -> error::Result<usize> {
+        use crate::Pwrite;
+        let size = bytes.pwrite(GroupVarint::partial(values)?, *offset)?;
+        *offset += size;
+        Ok(size)
+    }
+}
+
+impl<'a> TryFromCtx<'a> for GroupVarint {
+    type Error = error::Error;
+    #[inline]
+    fn try_from_ctx(src: &'a [u8], _ctx: ()) -> error::Result<(Self, usize)> {
+        let (values, size) = decode(src, 0, 4)?;
+        Ok((GroupVarint { values, count: 4 }, size))
+    }
+}
+
+/// Writes the group's values each in as few bytes as they fit in
+impl TryIntoCtx for GroupVarint {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], ctx: ()) -> error::Result<usize> {
+        (&self).try_into_ctx(dst, ctx)
+    }
+}
+
+impl TryIntoCtx for &GroupVarint {
+    type Error = error::Error;
+    fn try_into_ctx(self, dst: &mut [u8], _ctx: ()) -> error::Result<usize> {
+        let size = self.size();
+        if size > dst.len() {
+            return Err(error::Error::TooBig { size, len: dst.len() });
+        }
+        let mut tag = 0;
+        let mut at = 1;
+        for (i, &value) in self.values().iter().enumerate() {
+            let width = width(value);
+            tag |= ((width - 1) as u8) << (6 - 2 * i);
+            dst[at..at + width].copy_from_slice(&value.to_le_bytes()[..width]);
+            at += width;
+        }
+        dst[0] = tag;
+        Ok(size)
+    }
+}
+
+/// Measures the minimal encoding of the group
+impl MeasureWith<()> for GroupVarint {
+    #[inline]
+    fn measure_with(&self, _ctx: &()) -> usize {
+        self.size()
+    }
+}
+
+/// Decodes a run of `count` group varint encoded values in `src` at `offset`, in full groups and then a partial one for
+/// the rest, returning them and the number of bytes they took; requires the `alloc` feature
+///
+/// A group which is malformed, or which the region ends in the middle of, is an `Error::Element` with the index of its
+/// first value and the offset it starts at; a count larger than the bytes left, as each value takes at least one, is an
+/// `Error::BadInput`, checked before anything is allocated.
+///
+/// # Example
+/// ```rust
+/// use scroll::group_varint::{decode_group_varint_n, encode_group_varint};
+///
+/// let values = [1, 15, 511, 131071, 1 << 30, 0];
+/// let mut bytes = [0u8; 32];
+/// let size = encode_group_varint(&mut bytes, &mut 0, &values).unwrap();
+/// assert_eq!(size, 8 + 1 + 4 + 1);
+/// assert_eq!(decode_group_varint_n(&bytes, 0, 6).unwrap(), (values.to_vec(), size));
+/// assert!(decode_group_varint_n(&bytes[..size - 1], 0, 6).is_err());
+/// ```
+#[cfg(feature = "alloc")]
+pub fn decode_group_varint_n(src: &[u8], offset: usize, count: usize) -> error::Result<(Vec<u32>, usize)> {
+    let bytes = src.get(offset..).ok_or(error::Error::BadOffset(offset))?;
+    crate::ctx::check_count(count, bytes)?;
+    let mut values = Vec::with_capacity(crate::ctx::initial_capacity::<u32>(count, bytes));
+    let mut pos = 0;
+    while values.len() < count {
+        let group = (count - values.len()).min(4);
+        let (decoded, size) = decode(&bytes[pos..], offset + pos, group)
+            .map_err(|err| error::Error::Element { index: values.len(), offset: offset + pos, source: Box::new(err) })?;
+        values.extend_from_slice(&decoded[..group]);
+        pos += size;
+    }
+    Ok((values, pos))
+}
+
+/// Writes `values` into `dst` at `offset` as a run of groups, the last of them partial if their count isn't a multiple
+/// of four, and advances the offset past them, returning the number of bytes written
+///
+/// Nothing is written when the groups don't all fit, which is an `Error::TooBig`.
+pub fn encode_group_varint(dst: &mut [u8], offset: &mut usize, values: &[u32]) -> error::Result<usize> {
+    let rest = dst.get_mut(*offset..).ok_or(error::Error::BadOffset(*offset))?;
+    let size: usize = values.len().div_ceil(4) + values.iter().map(|&value| width(value)).sum::<usize>();
+    if size > rest.len() {
+        return Err(error::Error::TooBig { size, len: rest.len() });
+    }
+    let mut at = 0;
+    for group in values.chunks(4) {
+        at += GroupVarint::partial(group)?.try_into_ctx(&mut rest[at..], ())?;
+    }
+    *offset += at;
+    Ok(at)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Error, Pread, Pwrite};
+
+    #[test]
+    fn canonical_groups() {
+        // from Dean, "Challenges in Building Large-Scale Information Retrieval Systems", WSDM 2009
+        let bytes = [0x06, 0x01, 0x0f, 0xff, 0x01, 0xff, 0xff, 0x01];
+        assert_eq!(GroupVarint::read(&bytes, &mut 0).unwrap(), [1, 15, 511, 131071]);
+        let mut out = [0u8; 8];
+        assert_eq!(GroupVarint::write(&mut out, &mut 0, &[1, 15, 511, 131071]).unwrap(), 8);
+        assert_eq!(out, bytes);
+
+        let zeroes = [0x00, 0, 0, 0, 0];
+        assert_eq!(zeroes.pread::<GroupVarint>(0).unwrap(), GroupVarint::new([0; 4]));
+        let widest = GroupVarint::new([u32::MAX, 1 << 24, (1 << 24) - 1, 1 << 8]);
+        let mut out = [0u8; 17];
+        assert_eq!(out.pwrite(widest, 0).unwrap(), 1 + 4 + 4 + 3 + 2);
+        assert_eq!(out[0], 0b11_11_10_01);
+        assert_eq!(out.pread::<GroupVarint>(0).unwrap(), widest);
+    }
+
+    #[test]
+    fn partial_groups() {
+        for count in 1..=4 {
+            let values = [70000, 1, 300, 9];
+            let mut out = [0u8; 16];
+            let size = GroupVarint::write(&mut out, &mut 0, &values[..count]).unwrap();
+            let offset = &mut 0;
+            let group = GroupVarint::read_partial(&out, offset, count).unwrap();
+            assert_eq!((group.values(), group.count(), group.size(), *offset), (&values[..count], count, size, size));
+        }
+        assert!(matches!(GroupVarint::partial(&[]), Err(Error::BadInput { size: 0, .. })));
+        assert!(matches!(GroupVarint::partial(&[0; 5]), Err(Error::BadInput { size: 5, .. })));
+        assert!(matches!(GroupVarint::read_partial(&[0; 8], &mut 0, 0), Err(Error::BadInput { size: 0, .. })));
+        // the tag of a full group read as a partial one has widths past its count
+        let full = [0xff, 0xff, 0b00_00_00_01, 0, 0, 0, 0, 0];
+        assert!(matches!(GroupVarint::read_partial(&full, &mut 2, 3), Err(Error::Invalid { offset: 2, .. })));
+        #[cfg(feature = "alloc")]
+        match decode_group_varint_n(&full, 2, 3) {
+            Err(Error::Element { index: 0, offset: 2, source }) => assert!(matches!(*source, Error::Invalid { offset: 2, .. })),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn bounds_before_payload() {
+        // the tag alone says four 4 byte values follow; one byte short is rejected up front
+        let mut bytes = [0xffu8; 17];
+        assert!(matches!(GroupVarint::read(&bytes[..16], &mut 0), Err(Error::Incomplete { needed: 1 })));
+        assert!(matches!(GroupVarint::read(&bytes[..1], &mut 0), Err(Error::Incomplete { needed: 16 })));
+        assert!(matches!(GroupVarint::read(&bytes, &mut 17), Err(Error::Incomplete { needed: 1 })));
+        assert!(matches!(GroupVarint::read(&bytes, &mut 18), Err(Error::BadOffset(18))));
+        assert_eq!(GroupVarint::read(&bytes, &mut 0).unwrap(), [u32::MAX; 4]);
+
+        // writes which don't fit leave the buffer alone
+        let group = GroupVarint::new([u32::MAX; 4]);
+        assert!(matches!(bytes[..16].pwrite(group, 0), Err(Error::TooBig { size: 17, len: 16 })));
+        assert_eq!(encode_group_varint(&mut bytes, &mut 2, &[1; 12]).unwrap(), 15);
+        bytes = [0xff; 17];
+        assert!(matches!(encode_group_varint(&mut bytes, &mut 3, &[1; 12]), Err(Error::TooBig { size: 15, len: 14 })));
+        assert_eq!(bytes, [0xff; 17]);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn random_round_trips() {
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for _ in 0..200 {
+            let count = next() as usize % 40;
+            // values of every width, by shifting random bits down to random sizes
+            let values: Vec<u32> = (0..count).map(|_| (next() as u32) >> (next() % 32)).collect();
+            let mut bytes = vec![0u8; 1 + 5 * count];
+            let offset = &mut 1;
+            let size = encode_group_varint(&mut bytes, offset, &values).unwrap();
+            assert_eq!(*offset, 1 + size);
+            let expected = count.div_ceil(4) + values.iter().map(|&value| width(value)).sum::<usize>();
+            assert_eq!(size, expected);
+            assert_eq!(decode_group_varint_n(&bytes, 1, count).unwrap(), (values.clone(), size));
+            if count > 0 {
+                let err = decode_group_varint_n(&bytes[..size], 1, count).unwrap_err();
+                assert!(matches!(err, Error::Element { index, .. } if index == (count - 1) / 4 * 4));
+            }
+        }
+    }
+}
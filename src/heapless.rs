@@ -0,0 +1,120 @@
+use crate::ctx::{element_error, write_elements, Count, Str, StrCtx, TryFromCtx, TryIntoCtx};
+use crate::error;
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use crate::prelude::*;
+
+/// Reads `count` elements back to back, each with `ctx`, into a [heapless](https://docs.rs/heapless) `Vec` holding at
+/// most `N`; requires the `heapless` feature
+///
+/// A count larger than `N` is an error, never truncated, so a record table stays intact or isn't read at all.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx::Count, Pread, Pwrite, BE};
+///
+/// let bytes = [0x00, 0x01, 0x00, 0x02, 0x00, 0x03];
+/// let offset = &mut 0;
+/// let entries: heapless::Vec<u16, 4> = bytes.gread_with(offset, Count(3, BE)).unwrap();
+/// assert_eq!(entries, [1, 2, 3]);
+/// assert_eq!(*offset, 6);
+/// assert!(bytes.pread_with::<heapless::Vec<u16, 2>>(0, Count(3, BE)).is_err());
+///
+/// let mut out = [0u8; 6];
+/// assert_eq!(out.pwrite_with(&entries, 0, Count(3, BE)).unwrap(), 6);
+/// assert_eq!(out, bytes);
+/// ```
+impl<'a, T, Ctx, const N: usize> TryFromCtx<'a, Count<Ctx>> for ::heapless::Vec<T, N>
+    where T: TryFromCtx<'a, Ctx, Error = error::Error>,
+          Ctx: Copy,
+{
+    type Error = error::Error;
+    fn try_from_ctx(src: &'a [u8], Count(count, ctx): Count<Ctx>) -> error::Result<(Self, usize)> {
+        if count > N {
+            return Err(error::Error::BadInput { size: count, msg: "element count exceeds the capacity" });
+        }
+        let mut elements = ::heapless::Vec::new();
+        let mut offset = 0;
+        for index in 0..count {
+            let (value, size) = src.get(offset..)
+                .ok_or(error::Error::BadOffset(offset))
+                .and_then(|src| T::try_from_ctx(src, ctx))
+                .map_err(|err| element_error(index, offset, err))?;
+            // there is room, as the count was checked
+            if elements.push(value).is_err() {
+                return Err(error::Error::BadInput { size: count, msg: "element count exceeds the capacity" });
+            }
+            offset += size;
+        }
+        Ok((elements, offset))
+    }
+}
+
+/// Writes the elements back to back, each with `ctx`; the count must match the `Vec`'s length
+impl<T, Ctx, const N: usize> TryIntoCtx<Count<Ctx>> for ::heapless::Vec<T, N>
+    where T: TryIntoCtx<Ctx, Error = error::Error>,
+          Ctx: Copy,
+{
+    type Error = error::Error;
+    fn try_into_ctx(self, dst: &mut [u8], Count(count, ctx): Count<Ctx>) -> error::Result<usize> {
+        write_elements(dst, count, ctx, self.len(), self.into_iter())
+    }
+}
+
+/// Writes the elements back to back, each with `ctx`; the count must match the `Vec`'s length
+impl<'v, T, Ctx, const N: usize> TryIntoCtx<Count<Ctx>> for &'v ::heapless::Vec<T, N>
+    where &'v T: TryIntoCtx<Ctx, Error = error::Error>,
+          Ctx: Copy,
+{
+    type Error = error::Error;
+    fn try_into_ctx(self, dst: &mut [u8], Count(count, ctx): Count<Ctx>) -> error::Result<usize> {
+        write_elements(dst, count, ctx, self.len(), self.iter())
+    }
+}
+
+/// Reads the string framed by the [StrCtx](ctx/enum.StrCtx.html) into a [heapless](https://docs.rs/heapless) `String`
+/// holding at most `N` bytes, validating it as a `&str` does; requires the `heapless` feature
+///
+/// A longer string is an error, never truncated.
+///
+/// # Example
+/// ```rust
+/// use scroll::{Pread, Pwrite, ctx::StrCtx};
+///
+/// let bytes = b"eth0\0wlan0\0";
+/// let offset = &mut 0;
+/// let name: heapless::String<8> = bytes.gread_with(offset, StrCtx::Delimiter(0)).unwrap();
+/// assert_eq!(name, "eth0");
+/// assert_eq!(*offset, 5);
+/// assert!(bytes.pread_with::<heapless::String<4>>(5, StrCtx::Delimiter(0)).is_err());
+///
+/// let mut out = [0xffu8; 8];
+/// assert_eq!(out.pwrite_with(&name, 0, StrCtx::Fixed { len: 8, pad: 0 }).unwrap(), 8);
+/// assert_eq!(&out, b"eth0\0\0\0\0");
+/// ```
+impl<'a, const N: usize> TryFromCtx<'a, StrCtx> for ::heapless::String<N> {
+    type Error = error::Error;
+    #[inline]
+    fn try_from_ctx(src: &'a [u8], ctx: StrCtx) -> error::Result<(Self, usize)> {
+        let (s, size) = <&str>::try_from_ctx(src, ctx)?;
+        let mut string = ::heapless::String::new();
+        string.push_str(s).map_err(|_| error::Error::BadInput { size: s.len(), msg: "string exceeds the capacity" })?;
+        Ok((string, size))
+    }
+}
+
+/// Writes the string with the [StrCtx](ctx/enum.StrCtx.html), just like [Str](ctx/struct.Str.html) does
+impl<const N: usize> TryIntoCtx<StrCtx> for ::heapless::String<N> {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], ctx: StrCtx) -> error::Result<usize> {
+        Str(&self).try_into_ctx(dst, ctx)
+    }
+}
+
+impl<const N: usize> TryIntoCtx<StrCtx> for &::heapless::String<N> {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], ctx: StrCtx) -> error::Result<usize> {
+        Str(self).try_into_ctx(dst, ctx)
+    }
+}
@@ -1,9 +1,17 @@
-use core::u8;
-use core::convert::{From, AsRef};
+//! Variable length LEB128 integers, as used by DWARF and WebAssembly
+//!
+//! [Uleb128](struct.Uleb128.html) and [Sleb128](struct.Sleb128.html) are also exported at the crate root.
+
+use core::cmp::Ordering;
+use core::convert::{From, AsRef, TryFrom};
+use core::fmt;
+use core::num::TryFromIntError;
 use core::result;
 use crate::Pread;
-use crate::ctx::TryFromCtx;
+use crate::ctx::{MeasureWith, TryFromCtx, TryIntoCtx};
 use crate::error;
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use crate::prelude::*;
 
 #[derive(Debug, PartialEq, Copy, Clone)]
 /// An unsigned leb128 integer
@@ -19,12 +27,83 @@ impl Uleb128 {
         self.count
     }
     #[inline]
+    /// Return the decoded value
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+    #[inline]
     /// Read a variable length u64 from `bytes` at `offset`
     pub fn read(bytes: &[u8], offset: &mut usize) -> error::Result<u64> {
         let tmp = bytes.pread::<Uleb128>(*offset)?;
         *offset += tmp.size();
         Ok(tmp.into())
     }
+    /// Read a variable length u128 from `bytes` at `offset`, for encodings too wide for a `u64`
+    ///
+    /// The encoding may be at most 19 bytes long, and its last byte may only carry the two remaining high bits.
+    ///
+    /// # Example
+    /// ```rust
+    /// use scroll::Uleb128;
+    /// let bytes = [0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x02];
+    /// let offset = &mut 0;
+    /// assert_eq!(Uleb128::read_u128(&bytes, offset).unwrap(), 1 << 64);
+    /// assert_eq!(*offset, 10);
+    /// ```
+    pub fn read_u128(bytes: &[u8], offset: &mut usize) -> error::Result<u128> {
+        let mut result = 0;
+        let mut shift = 0;
+        let mut count = 0;
+        loop {
+            let byte: u8 = match bytes.get(*offset + count) {
+                Some(byte) => *byte,
+                None if *offset + count == bytes.len() => return Err(error::Error::Incomplete{ needed: 1 }),
+                None => return Err(error::Error::BadOffset(*offset)),
+            };
+
+            if shift == 126 && byte > 0x03 {
+                return Err(error::Error::BadInput{ size: bytes.len(), msg: "failed to parse"})
+            }
+
+            let low_bits = u128::from(mask_continuation(byte));
+            result |= low_bits << shift;
+
+            count += 1;
+            shift += 7;
+
+            if byte & CONTINUATION_BIT == 0 {
+                *offset += count;
+                return Ok(result);
+            }
+        }
+    }
+    /// Write `value` into `bytes` at `offset` as a minimally encoded, variable length u128, returning the number of bytes written
+    ///
+    /// # Example
+    /// ```rust
+    /// use scroll::Uleb128;
+    /// let mut bytes = [0u8; 19];
+    /// let offset = &mut 0;
+    /// assert_eq!(Uleb128::write_u128(&mut bytes, offset, 624485).unwrap(), 3);
+    /// assert_eq!(&bytes[..3], &[0xe5, 0x8e, 0x26]);
+    /// ```
+    pub fn write_u128(bytes: &mut [u8], offset: &mut usize, mut value: u128) -> error::Result<usize> {
+        use crate::Pwrite;
+        let mut count = 0;
+        loop {
+            let mut byte = (value as u8) & !CONTINUATION_BIT;
+            value >>= 7;
+            if value != 0 {
+                byte |= CONTINUATION_BIT;
+            }
+            bytes.pwrite(byte, *offset + count)?;
+            count += 1;
+            if value == 0 {
+                *offset += count;
+                return Ok(count);
+            }
+        }
+    }
 }
 
 impl AsRef<u64> for Uleb128 {
@@ -54,6 +133,11 @@ impl Sleb128 {
         self.count
     }
     #[inline]
+    /// Return the decoded value
+    pub fn value(&self) -> i64 {
+        self.value
+    }
+    #[inline]
     /// Read a variable length i64 from `bytes` at `offset`
     pub fn read(bytes: &[u8], offset: &mut usize) -> error::Result<i64> {
         use crate::Pread;
@@ -61,6 +145,34 @@ impl Sleb128 {
         *offset += tmp.size();
         Ok(tmp.into())
     }
+    /// Writes `value` as a variable length signed integer into `bytes` at `offset`, and returns how many bytes it took
+    ///
+    /// # Example
+    /// ```rust
+    /// use scroll::Sleb128;
+    /// let mut bytes = [0u8; 10];
+    /// let offset = &mut 0;
+    /// assert_eq!(Sleb128::write(&mut bytes, offset, -123456).unwrap(), 3);
+    /// assert_eq!(&bytes[..3], &[0xc0, 0xbb, 0x78]);
+    /// ```
+    pub fn write(bytes: &mut [u8], offset: &mut usize, mut value: i64) -> error::Result<usize> {
+        use crate::Pwrite;
+        let mut count = 0;
+        loop {
+            let mut byte = (value as u8) & !CONTINUATION_BIT;
+            value >>= 7;
+            let done = (value == 0 && byte & SIGN_BIT == 0) || (value == -1 && byte & SIGN_BIT != 0);
+            if !done {
+                byte |= CONTINUATION_BIT;
+            }
+            bytes.pwrite(byte, *offset + count)?;
+            count += 1;
+            if done {
+                *offset += count;
+                return Ok(count);
+            }
+        }
+    }
 }
 
 impl AsRef<i64> for Sleb128 {
@@ -76,6 +188,198 @@ impl From<Sleb128> for i64 {
     }
 }
 
+// checked conversions to the narrower primitives, and comparisons against all of them; the comparisons only go one
+// way, with the leb128 on the left, so that `130u64 == num.into()` keeps inferring its conversion
+macro_rules! leb128_impl {
+    ($leb:ty, $wide:ty, $($narrow:ty),*; $($cmp:ty),*) => {
+        $(
+        impl TryFrom<$leb> for $narrow {
+            type Error = TryFromIntError;
+            #[inline]
+            fn try_from(leb128: $leb) -> result::Result<Self, Self::Error> {
+                <$narrow>::try_from(leb128.value)
+            }
+        }
+        )*
+
+        $(
+        impl PartialEq<$cmp> for $leb {
+            #[inline]
+            fn eq(&self, other: &$cmp) -> bool {
+                <$wide>::from(self.value) == *other as $wide
+            }
+        }
+
+        impl PartialOrd<$cmp> for $leb {
+            #[inline]
+            fn partial_cmp(&self, other: &$cmp) -> Option<Ordering> {
+                <$wide>::from(self.value).partial_cmp(&(*other as $wide))
+            }
+        }
+        )*
+
+        impl fmt::Display for $leb {
+            fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+                fmt::Display::fmt(&self.value, fmt)
+            }
+        }
+
+        impl fmt::LowerHex for $leb {
+            fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+                fmt::LowerHex::fmt(&self.value, fmt)
+            }
+        }
+    }
+}
+
+leb128_impl!(Uleb128, u128, usize, u32, u16, u8; u8, u16, u32, u64, u128, usize);
+leb128_impl!(Sleb128, i128, isize, i32, i16, i8; i8, i16, i32, i64, i128, isize);
+
+/// The number of bytes the minimal unsigned LEB128 encoding of `value` takes up, between 1 and 10
+///
+/// # Example
+/// ```rust
+/// use scroll::leb128::encoded_uleb128_len;
+/// assert_eq!(encoded_uleb128_len(127), 1);
+/// assert_eq!(encoded_uleb128_len(128), 2);
+/// ```
+#[inline]
+pub fn encoded_uleb128_len(value: u64) -> usize {
+    let bits = 64 - value.leading_zeros() as usize;
+    core::cmp::max(1, bits.div_ceil(7))
+}
+
+/// The number of bytes the minimal signed LEB128 encoding of `value` takes up, between 1 and 10
+///
+/// # Example
+/// ```rust
+/// use scroll::leb128::encoded_sleb128_len;
+/// assert_eq!(encoded_sleb128_len(-64), 1);
+/// assert_eq!(encoded_sleb128_len(64), 2);
+/// ```
+#[inline]
+pub fn encoded_sleb128_len(value: i64) -> usize {
+    // the magnitude bits, plus the sign bit
+    let bits = 64 - (value ^ (value >> 63)).leading_zeros() as usize + 1;
+    bits.div_ceil(7)
+}
+
+/// The longest an unsigned LEB128 encoding of a `u64` can be
+#[cfg(feature = "alloc")]
+const MAX_ULEB128_LEN: usize = 10;
+
+/// Decodes the unsigned LEB128 values packed back to back in `src` from `offset` to its end, returning them and the
+/// number of bytes they took; requires the `alloc` feature
+///
+/// This is for long runs of them, as in columnar and index formats: while at least 10 bytes remain, a value is decoded
+/// with a single bounds check, and a one byte value without any further work. The rules are those of a single
+/// [Uleb128](struct.Uleb128.html). A value which is too large for a `u64`, or which the region ends in the middle of,
+/// is an `Error::Element` with the value's index and the offset it starts at, wrapping the error reading it alone gives.
+///
+/// # Example
+/// ```rust
+/// use scroll::{leb128::decode_uleb128_all, Error};
+///
+/// let bytes = [0xff, 0x02, 0x7f, 0xe5, 0x8e, 0x26, 0x00];
+/// assert_eq!(decode_uleb128_all(&bytes, 1).unwrap(), (vec![2, 127, 624485, 0], 6));
+/// let err = decode_uleb128_all(&bytes[..5], 1).unwrap_err();
+/// assert!(matches!(err, Error::Element { index: 2, offset: 3, .. }));
+/// ```
+#[cfg(feature = "alloc")]
+pub fn decode_uleb128_all(src: &[u8], offset: usize) -> error::Result<(Vec<u64>, usize)> {
+    let bytes = src.get(offset..).ok_or(error::Error::BadOffset(offset))?;
+    decode_uleb128_run(bytes, offset, Vec::new(), |_, pos| pos < bytes.len())
+}
+
+/// Decodes `count` unsigned LEB128 values packed back to back in `src` at `offset`, returning them and the number of
+/// bytes they took, as [decode_uleb128_all](fn.decode_uleb128_all.html) does; requires the `alloc` feature
+///
+/// A region which ends before the last of them is an `Error::Element`, as is one ending in the middle of a value; a
+/// count larger than the bytes left, as each value takes at least one, is an `Error::BadInput`, checked before
+/// anything is allocated.
+///
+/// # Example
+/// ```rust
+/// use scroll::leb128::decode_uleb128_n;
+///
+/// let bytes = [0x02, 0x80, 0x01, 0x03];
+/// assert_eq!(decode_uleb128_n(&bytes, 0, 2).unwrap(), (vec![2, 128], 3));
+/// assert!(decode_uleb128_n(&bytes, 2, 3).is_err());
+/// ```
+#[cfg(feature = "alloc")]
+pub fn decode_uleb128_n(src: &[u8], offset: usize, count: usize) -> error::Result<(Vec<u64>, usize)> {
+    let bytes = src.get(offset..).ok_or(error::Error::BadOffset(offset))?;
+    crate::ctx::check_count(count, bytes)?;
+    let values = Vec::with_capacity(crate::ctx::initial_capacity::<u64>(count, bytes));
+    decode_uleb128_run(bytes, offset, values, |values, _| values.len() < count)
+}
+
+/// Decodes values from the front of `bytes`, which start `offset` bytes into their source, into `values` for as long
+/// as `more` says to
+#[cfg(feature = "alloc")]
+#[inline]
+fn decode_uleb128_run(bytes: &[u8], offset: usize, mut values: Vec<u64>, more: impl Fn(&Vec<u64>, usize) -> bool)
+    -> error::Result<(Vec<u64>, usize)>
+{
+    let mut pos = 0;
+    while more(&values, pos) {
+        let rest = &bytes[pos..];
+        let decoded = match rest.first_chunk::<MAX_ULEB128_LEN>() {
+            Some(&[byte, ..]) if byte & CONTINUATION_BIT == 0 => Some((u64::from(byte), 1)),
+            Some(chunk) => decode_uleb128_chunk(chunk),
+            // near the end, the bytes are checked one at a time
+            None => rest.pread::<Uleb128>(0).ok().map(|uleb| (uleb.value, uleb.count)),
+        };
+        match decoded {
+            Some((value, size)) => {
+                values.push(value);
+                pos += size;
+            },
+            None => {
+                // decode it again the slow way, for the error
+                let source = rest.pread::<Uleb128>(0).err().unwrap_or(error::Error::Incomplete { needed: 1 });
+                return Err(error::Error::Element { index: values.len(), offset: offset + pos, source: Box::new(source) });
+            },
+        }
+    }
+    Ok((values, pos))
+}
+
+/// Decodes the value at the front of `chunk`, which holds the longest encoding there can be, or `None` if it is too
+/// large for a `u64`
+#[cfg(feature = "alloc")]
+#[inline(always)]
+fn decode_uleb128_chunk(chunk: &[u8; MAX_ULEB128_LEN]) -> Option<(u64, usize)> {
+    let mut result = 0;
+    for (i, &byte) in chunk.iter().enumerate() {
+        // the tenth byte only has room for the top bit
+        if i == MAX_ULEB128_LEN - 1 && byte > 0x01 {
+            return None;
+        }
+        result |= u64::from(mask_continuation(byte)) << (7 * i);
+        if byte & CONTINUATION_BIT == 0 {
+            return Some((result, i + 1));
+        }
+    }
+    None
+}
+
+/// Measures the minimal encoding of the value, which may be shorter than the encoding it was read from
+impl MeasureWith<()> for Uleb128 {
+    #[inline]
+    fn measure_with(&self, _ctx: &()) -> usize {
+        encoded_uleb128_len(self.value)
+    }
+}
+
+/// Measures the minimal encoding of the value, which may be shorter than the encoding it was read from
+impl MeasureWith<()> for Sleb128 {
+    #[inline]
+    fn measure_with(&self, _ctx: &()) -> usize {
+        encoded_sleb128_len(self.value)
+    }
+}
+
 // Below implementation heavily adapted from: https://github.com/fitzgen/leb128
 const CONTINUATION_BIT: u8 = 1 << 7;
 const SIGN_BIT: u8 = 1 << 6;
@@ -95,12 +399,12 @@ impl<'a> TryFromCtx<'a> for Uleb128 {
     type Error = error::Error;
     #[inline]
     fn try_from_ctx(src: &'a [u8], _ctx: ()) -> result::Result<(Self, usize), Self::Error> {
-        use crate::pread::Pread;
         let mut result = 0;
         let mut shift = 0;
         let mut count = 0;
         loop {
-            let byte: u8 = src.pread(count)?;
+            // the encoding runs past the end; at least one more byte is needed to tell how long it is
+            let byte: u8 = *src.get(count).ok_or(error::Error::Incomplete{ needed: 1 })?;
 
             if shift == 63 && byte != 0x00 && byte != 0x01 {
                 return Err(error::Error::BadInput{ size: src.len(), msg: "failed to parse"})
@@ -130,7 +434,8 @@ impl<'a> TryFromCtx<'a> for Sleb128 {
         let size = 64;
         let mut byte: u8;
         loop {
-            byte = src.gread(offset)?;
+            byte = *src.get(*offset).ok_or(error::Error::Incomplete{ needed: 1 })?;
+            *offset += 1;
 
             if shift == 63 && byte != 0x00 && byte != 0x7f {
                 return Err(error::Error::BadInput{size: src.len(), msg: "failed to parse"})
@@ -154,6 +459,39 @@ impl<'a> TryFromCtx<'a> for Sleb128 {
     }
 }
 
+/// Writes the minimal encoding of the value, which may be shorter than the encoding it was read from
+impl TryIntoCtx for Uleb128 {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], _ctx: ()) -> result::Result<usize, Self::Error> {
+        Uleb128::write_u128(dst, &mut 0, u128::from(self.value))
+    }
+}
+
+impl TryIntoCtx for &Uleb128 {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], ctx: ()) -> result::Result<usize, Self::Error> {
+        (*self).try_into_ctx(dst, ctx)
+    }
+}
+
+/// Writes the minimal encoding of the value, which may be shorter than the encoding it was read from
+impl TryIntoCtx for Sleb128 {
+    type Error = error::Error;
+    fn try_into_ctx(self, dst: &mut [u8], _ctx: ()) -> result::Result<usize, Self::Error> {
+        Sleb128::write(dst, &mut 0, self.value)
+    }
+}
+
+impl TryIntoCtx for &Sleb128 {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], ctx: ()) -> result::Result<usize, Self::Error> {
+        (*self).try_into_ctx(dst, ctx)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{Uleb128, Sleb128};
@@ -222,4 +560,180 @@ mod tests {
         let num: i64 = bytes.pread::<Sleb128>(0).expect("Should read Sleb128").into();
         assert_eq!(-129, num);
     }
+
+    #[test]
+    fn leb128_write() {
+        use super::super::{Pread, Pwrite};
+        let mut buf = [0u8; 10];
+        for bytes in [&[0x00][..], &[0x7f], &[0xe5, 0x8e, 0x26], &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]] {
+            let n: Uleb128 = bytes.pread(0).unwrap();
+            assert_eq!(buf.pwrite(n, 0).unwrap(), bytes.len());
+            assert_eq!(&buf[..bytes.len()], bytes);
+        }
+        for bytes in [&[0x00][..], &[0xc0, 0x00], &[0x40], &[0x3f], &[0xff, 0x7e], &[0x80, 0x7f], &[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x7f]] {
+            let n: Sleb128 = bytes.pread(0).unwrap();
+            assert_eq!(buf.pwrite(&n, 0).unwrap(), bytes.len());
+            assert_eq!(&buf[..bytes.len()], bytes);
+        }
+        // a redundant encoding is written minimally
+        let n: Uleb128 = [0x80u8, 0x00].pread(0).unwrap();
+        assert_eq!(buf.pwrite(n, 0).unwrap(), 1);
+        assert!(buf[..2].pwrite(n, 2).is_err());
+    }
+
+    #[test]
+    fn uleb128_u128() {
+        let mut buf = [0u8; 19];
+        for &value in &[0, 1, 127, 128, u128::from(u64::MAX), u128::from(u64::MAX) + 1, u128::MAX] {
+            let offset = &mut 0;
+            let size = Uleb128::write_u128(&mut buf, offset, value).unwrap();
+            assert_eq!(*offset, size);
+            let offset = &mut 0;
+            assert_eq!(Uleb128::read_u128(&buf, offset).unwrap(), value);
+            assert_eq!(*offset, size);
+        }
+
+        let offset = &mut 0;
+        assert_eq!(Uleb128::write_u128(&mut buf, offset, u128::from(u64::MAX) + 1).unwrap(), 10);
+        assert_eq!(&buf[..10], &[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x02]);
+        let offset = &mut 0;
+        assert_eq!(Uleb128::write_u128(&mut buf, offset, u128::MAX).unwrap(), 19);
+        assert!(buf[..18].iter().all(|b| *b == 0xff));
+        assert_eq!(buf[18], 0x03);
+        // and the 64-bit path still rejects it
+        assert!(Uleb128::read(&buf, &mut 0).is_err());
+        // too small a buffer
+        assert!(Uleb128::write_u128(&mut buf[..18], &mut 0, u128::MAX).is_err());
+    }
+
+    #[test]
+    fn uleb128_u128_overflow() {
+        // the 19th byte carries more than the 2 remaining bits
+        let mut buf = [0xffu8; 19];
+        buf[18] = 0x07;
+        assert!(Uleb128::read_u128(&buf, &mut 0).is_err());
+        // a 20 byte encoding
+        let mut buf = [0x80u8; 20];
+        buf[19] = 0x00;
+        assert!(Uleb128::read_u128(&buf, &mut 0).is_err());
+        // a truncated encoding
+        assert!(Uleb128::read_u128(&[0x80, 0x80], &mut 0).is_err());
+    }
+
+    #[test]
+    fn uleb128_conversions() {
+        use core::convert::TryFrom;
+        use super::super::Pread;
+        // 65535, then 65536
+        let bytes = [0xff, 0xff, 0x03, 0x80, 0x80, 0x04];
+        let max = bytes.pread::<Uleb128>(0).unwrap();
+        let over = bytes.pread::<Uleb128>(3).unwrap();
+        assert_eq!(max.value(), 65535);
+        assert_eq!(u16::try_from(max), Ok(u16::MAX));
+        assert!(u16::try_from(over).is_err());
+        assert!(u8::try_from(max).is_err());
+        assert_eq!(u32::try_from(over), Ok(65536));
+        assert_eq!(usize::try_from(over), Ok(65536));
+
+        assert!(max == u16::MAX);
+        assert!(max < 65536u32);
+        assert!(over > u16::MAX);
+        assert!(over != 0u8);
+        assert!(over >= 65536usize);
+        assert_eq!(format!("{}", over), "65536");
+        assert_eq!(format!("{:#x}", max), "0xffff");
+    }
+
+    #[test]
+    fn sleb128_conversions() {
+        use core::convert::TryFrom;
+        use super::super::Pread;
+        // -128, then -129
+        let bytes = [0x80, 0x7f, 0xff, 0x7e];
+        let min = bytes.pread::<Sleb128>(0).unwrap();
+        let under = bytes.pread::<Sleb128>(2).unwrap();
+        assert_eq!(min.value(), -128);
+        assert_eq!(i8::try_from(min), Ok(i8::MIN));
+        assert!(i8::try_from(under).is_err());
+        assert_eq!(i16::try_from(under), Ok(-129));
+        assert_eq!(isize::try_from(under), Ok(-129));
+
+        assert!(min == i8::MIN);
+        assert!(under < i8::MIN);
+        assert!(under > -130i64);
+        assert_eq!(format!("{}", under), "-129");
+        assert_eq!(format!("{:x}", min), "ffffffffffffff80");
+    }
+
+    #[test]
+    fn encoded_len() {
+        use super::super::ctx::MeasureWith;
+        use super::super::Pread;
+        use super::{encoded_sleb128_len, encoded_uleb128_len};
+        for &(value, len) in &[(0, 1), (127, 1), (128, 2), (16383, 2), (16384, 3), (u64::MAX, 10)] {
+            assert_eq!(encoded_uleb128_len(value), len, "{}", value);
+            let mut buf = [0u8; 19];
+            assert_eq!(Uleb128::write_u128(&mut buf, &mut 0, u128::from(value)).unwrap(), len);
+        }
+        for &(value, len) in &[(0, 1), (-1, 1), (63, 1), (-64, 1), (64, 2), (-65, 2), (8191, 2), (8192, 3), (i64::MAX, 10), (i64::MIN, 10)] {
+            assert_eq!(encoded_sleb128_len(value), len, "{}", value);
+        }
+        // a padded encoding measures as its minimal one
+        let num = [0x81u8, 0x80, 0x00].pread::<Uleb128>(0).unwrap();
+        assert_eq!(num.size(), 3);
+        assert_eq!(num.measure_with(&()), 1);
+        let num = [0x7fu8].pread::<Sleb128>(0).unwrap();
+        assert_eq!(num.measure_with(&()), 1);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn decode_uleb128_bulk() {
+        use super::super::{Error, Pread};
+        use super::{decode_uleb128_all, decode_uleb128_n};
+        // values of every length, with some padded encodings, so runs cross from the fast path to the checked one
+        let mut bytes = vec![0xaa];
+        let mut buf = [0u8; 19];
+        for i in 0..300u32 {
+            let value = u64::from(i).wrapping_mul(0x9e37_79b9_7f4a_7c15) >> (i % 64);
+            let len = Uleb128::write_u128(&mut buf, &mut 0, u128::from(value)).unwrap();
+            bytes.extend_from_slice(&buf[..len]);
+            if i % 7 == 0 {
+                bytes.extend_from_slice(&[0x80, 0x80, 0x00]);
+            }
+        }
+        let mut expected = Vec::new();
+        let offset = &mut 1;
+        while *offset < bytes.len() {
+            expected.push(bytes.gread::<Uleb128>(offset).unwrap().value());
+        }
+        let (values, size) = decode_uleb128_all(&bytes, 1).unwrap();
+        assert_eq!(values, expected);
+        assert_eq!(size, bytes.len() - 1);
+        assert_eq!(decode_uleb128_n(&bytes, 1, 5).unwrap().0, expected[..5]);
+        assert_eq!(decode_uleb128_all(&bytes, bytes.len()).unwrap(), (vec![], 0));
+        assert!(matches!(decode_uleb128_all(&bytes, bytes.len() + 1), Err(Error::BadOffset(_))));
+
+        // a region ending mid-varint errors with the value's index and offset
+        let bytes = [0x01, 0x80, 0x01, 0xff, 0xff];
+        let err = decode_uleb128_all(&bytes, 0).unwrap_err();
+        match err {
+            Error::Element { index: 2, offset: 3, source } => assert!(matches!(*source, Error::Incomplete { needed: 1 })),
+            err => panic!("unexpected error: {}", err),
+        }
+        assert!(matches!(decode_uleb128_n(&bytes, 0, 2), Ok((_, 3))));
+        assert!(matches!(decode_uleb128_n(&bytes, 0, 3), Err(Error::Element { index: 2, offset: 3, .. })));
+        assert!(matches!(decode_uleb128_n(&bytes[..3], 0, 3), Err(Error::Element { index: 2, offset: 3, .. })));
+        assert!(matches!(decode_uleb128_n(&bytes, 0, 6), Err(Error::BadInput { .. })));
+
+        // too large for a u64, with room to spare after it, and at the end
+        let mut bytes = vec![0x05];
+        bytes.extend_from_slice(&[0xff; 9]);
+        bytes.push(0x02);
+        bytes.extend_from_slice(&[0; 12]);
+        assert!(matches!(decode_uleb128_all(&bytes, 0), Err(Error::Element { index: 1, offset: 1, .. })));
+        assert!(matches!(decode_uleb128_all(&bytes[..11], 0), Err(Error::Element { index: 1, offset: 1, .. })));
+        bytes[10] = 0x01;
+        assert_eq!(decode_uleb128_all(&bytes, 0).unwrap().0[..2], [5, u64::MAX]);
+    }
 }
@@ -1,5 +1,9 @@
-use std::io::{Result, Read, Write};
-use crate::ctx::{FromCtx, IntoCtx, SizeWith};
+use core::convert::TryFrom;
+use core::ops::Range;
+use std::io::{self, BufRead, ErrorKind, Result, Read, Seek, SeekFrom, Write};
+use std::sync::{Mutex, PoisonError};
+use crate::ctx::{FromCtx, IntoCtx, SizeWith, TryFromCtx};
+use crate::ErasedPread;
 
 /// An extension trait to `std::io::Read` streams; this only deserializes simple types, like `u8`, `i32`, `f32`, `usize`, etc.
 ///
@@ -74,7 +78,7 @@ pub trait IOread<Ctx: Copy> : Read
     }
 
     /// Reads the type `N` from `Self`, with the parsing context `ctx`.
-    /// **NB**: this will panic if the type you're reading has a size greater than 256. Plans are to have this allocate in larger cases.
+    /// **NB**: a type with a size greater than 256 is an `InvalidInput` error, wrapping an `Error::TooBig`.
     ///
     /// For the primitive numeric types, this will be at the host machine's endianness.
     ///
@@ -93,16 +97,81 @@ pub trait IOread<Ctx: Copy> : Read
     /// let feeddead = bytes.ioread_with::<u32>(BE).unwrap();
     /// assert_eq!(0xfeeddead, feeddead);
     /// ```
+    ///
+    /// Like `read_exact`, it keeps reading until it has the whole value, however little each read returns, and retries
+    /// reads which are `Interrupted`. A stream which ends first is an `UnexpectedEof` error, whether or not it ended in
+    /// the middle of the value; use [try_ioread_with](#method.try_ioread_with) to tell the two apart.
     #[inline]
     fn ioread_with<N: FromCtx<Ctx> + SizeWith<Ctx>>(&mut self, ctx: Ctx) -> Result<N> {
-        let mut scratch = [0u8; 256];
         let size = N::size_with(&ctx);
-        let mut buf = &mut scratch[0..size];
-        self.read_exact(&mut buf)?;
-        Ok(N::from_ctx(buf, ctx))
+        self.try_ioread_with(ctx)?.ok_or_else(|| eof(size))
+    }
+
+    /// Reads the type `N` from `Self`, with the parsing context `ctx`, or returns `None` if the stream has already ended
+    ///
+    /// This is for streams of messages, where ending between two messages is how the stream finishes, but ending in the
+    /// middle of one is still an `UnexpectedEof` error, whose inner error is the
+    /// [Error::Incomplete](enum.Error.html#variant.Incomplete) with the number of bytes missing.
+    ///
+    /// # Example
+    /// ```rust
+    /// use scroll::{IOread, BE};
+    /// use std::io::{Cursor, ErrorKind};
+    ///
+    /// let mut stream = Cursor::new([0x00, 0x01, 0x00, 0x02]);
+    /// let mut lens = Vec::new();
+    /// while let Some(len) = stream.try_ioread_with::<u16>(BE).unwrap() {
+    ///     lens.push(len);
+    /// }
+    /// assert_eq!(lens, [1, 2]);
+    ///
+    /// let mut torn = Cursor::new([0x00, 0x01, 0x00]);
+    /// let error = torn.try_ioread_with::<u32>(BE).unwrap_err();
+    /// assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+    /// assert!(matches!(error.into_inner().unwrap().downcast_ref(), Some(scroll::Error::Incomplete { needed: 1 })));
+    /// ```
+    fn try_ioread_with<N: FromCtx<Ctx> + SizeWith<Ctx>>(&mut self, ctx: Ctx) -> Result<Option<N>> {
+        let mut scratch = [0u8; SCRATCH];
+        let size = N::size_with(&ctx);
+        let buf = scratch_for(&mut scratch, size)?;
+        match read_full(self, buf)? {
+            read if read == size => Ok(Some(N::from_ctx(buf, ctx))),
+            0 => Ok(None),
+            read => Err(eof(size - read)),
+        }
     }
 }
 
+/// Values at most this large are read and written through a buffer on the stack
+const SCRATCH: usize = 256;
+
+/// The first `size` bytes of `scratch`, or an `InvalidInput` error if a value of that size doesn't fit in it
+fn scratch_for(scratch: &mut [u8; SCRATCH], size: usize) -> Result<&mut [u8]> {
+    scratch.get_mut(..size).ok_or_else(|| {
+        io::Error::new(ErrorKind::InvalidInput, crate::Error::TooBig { size, len: SCRATCH })
+    })
+}
+
+/// Fills `buf` from `reader` like `read_exact`, retrying reads which are `Interrupted`, but returns how many bytes it read
+/// if the reader ends first
+fn read_full<R: Read + ?Sized>(reader: &mut R, buf: &mut [u8]) -> Result<usize> {
+    let mut read = 0;
+    while read < buf.len() {
+        match reader.read(&mut buf[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(ref e) if e.kind() == ErrorKind::Interrupted => {},
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(read)
+}
+
+/// The error for a stream which ended `needed` bytes short of a value
+fn eof(needed: usize) -> io::Error {
+    io::Error::new(ErrorKind::UnexpectedEof, crate::Error::Incomplete { needed })
+}
+
 /// Types that implement `Read` get methods defined in `IOread`
 /// for free.
 impl<Ctx: Copy, R: Read + ?Sized> IOread<Ctx> for R {}
@@ -113,7 +182,7 @@ impl<Ctx: Copy, R: Read + ?Sized> IOread<Ctx> for R {}
 pub trait IOwrite<Ctx: Copy>: Write
 {
     /// Writes the type `N` into `Self`, with the parsing context `ctx`.
-    /// **NB**: this will panic if the type you're writing has a size greater than 256. Plans are to have this allocate in larger cases.
+    /// **NB**: a type with a size greater than 256 is an `InvalidInput` error, wrapping an `Error::TooBig`.
     ///
     /// For the primitive numeric types, this will be at the host machine's endianness.
     ///
@@ -138,7 +207,7 @@ pub trait IOwrite<Ctx: Copy>: Write
     }
 
     /// Writes the type `N` into `Self`, with the parsing context `ctx`.
-    /// **NB**: this will panic if the type you're writing has a size greater than 256. Plans are to have this allocate in larger cases.
+    /// **NB**: a type with a size greater than 256 is an `InvalidInput` error, wrapping an `Error::TooBig`.
     ///
     /// For the primitive numeric types, this will be at the host machine's endianness.
     ///
@@ -155,9 +224,8 @@ pub trait IOwrite<Ctx: Copy>: Write
     /// ```
     #[inline]
     fn iowrite_with<N: SizeWith<Ctx> + IntoCtx<Ctx>>(&mut self, n: N, ctx: Ctx) -> Result<()> {
-        let mut buf = [0u8; 256];
-        let size = N::size_with(&ctx);
-        let buf = &mut buf[0..size];
+        let mut scratch = [0u8; SCRATCH];
+        let buf = scratch_for(&mut scratch, N::size_with(&ctx))?;
         n.into_ctx(buf, ctx);
         self.write_all(buf)?;
         Ok(())
@@ -167,3 +235,426 @@ pub trait IOwrite<Ctx: Copy>: Write
 /// Types that implement `Write` get methods defined in `IOwrite`
 /// for free.
 impl<Ctx: Copy, W: Write + ?Sized> IOwrite<Ctx> for W {}
+
+/// A `std::io::Write` over a region of a byte slice, starting at an offset; for handing a destination managed with
+/// scroll offsets to encoders which only emit into a `Write`
+///
+/// Each `write` copies in as many bytes as fit, and advances the [position](#method.position). Once the slice is full
+/// a `write` writes nothing, which `write_all` and `write!` report as an `io::ErrorKind::WriteZero`. Flushing does
+/// nothing.
+///
+/// # Example
+/// ```rust
+/// use std::io::Write;
+/// use scroll::{Pwrite, ScrollWriter, BE};
+///
+/// let mut bytes = [0u8; 8];
+/// let mut writer = ScrollWriter::new(&mut bytes, 2);
+/// write!(writer, "hi").unwrap();
+/// let offset = writer.position();
+/// bytes.pwrite_with(0xffffu16, offset, BE).unwrap();
+/// assert_eq!(bytes, [0, 0, b'h', b'i', 0xff, 0xff, 0, 0]);
+/// ```
+#[derive(Debug)]
+pub struct ScrollWriter<'a> {
+    dst: &'a mut [u8],
+    offset: usize,
+}
+
+impl<'a> ScrollWriter<'a> {
+    /// Creates a writer which writes into `dst` from `offset` on
+    #[inline]
+    pub fn new(dst: &'a mut [u8], offset: usize) -> Self {
+        ScrollWriter { dst, offset }
+    }
+    /// The offset the next write goes to, in `dst`
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<'a> Write for ScrollWriter<'a> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let start = self.offset.min(self.dst.len());
+        let len = buf.len().min(self.dst.len() - start);
+        self.dst[start..start + len].copy_from_slice(&buf[..len]);
+        self.offset += len;
+        Ok(len)
+    }
+    #[inline]
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A `std::io::Read` over a region of a byte source, for handing bytes found with scroll offsets to decompressors,
+/// hashers, and the like which only read from a `Read`
+///
+/// The region is checked once, when the reader is created. Reads advance through it, and return 0 at its end. The
+/// reader borrows the source, so it's also a `BufRead`, lending the rest of the region without copying.
+///
+/// # Example
+/// ```rust
+/// use std::io::Read;
+/// use scroll::ScrollReader;
+///
+/// let bytes = [0x00, b'h', b'e', b'l', b'l', b'o', 0xff];
+/// let mut reader = ScrollReader::new(&bytes, 1..6).unwrap();
+/// let mut text = String::new();
+/// reader.read_to_string(&mut text).unwrap();
+/// assert_eq!(text, "hello");
+/// assert!(ScrollReader::new(&bytes, 4..8).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScrollReader<'a> {
+    region: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ScrollReader<'a> {
+    /// Creates a reader over `range` of `src`; a reversed range, or one which reaches past the end of `src`, is an
+    /// `Error::BadRange`
+    pub fn new<S: AsRef<[u8]> + ?Sized>(src: &'a S, range: Range<usize>) -> crate::Result<Self> {
+        let src = src.as_ref();
+        match src.get(range.clone()) {
+            Some(region) => Ok(ScrollReader { region, pos: 0 }),
+            None => Err(crate::Error::BadRange { start: range.start, end: range.end, len: src.len() }),
+        }
+    }
+    /// How far into the region the next read starts
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+    /// The rest of the region, which hasn't been read yet
+    #[inline]
+    pub fn remaining(&self) -> &'a [u8] {
+        &self.region[self.pos..]
+    }
+}
+
+impl<'a> Read for ScrollReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let rest = self.remaining();
+        let len = buf.len().min(rest.len());
+        buf[..len].copy_from_slice(&rest[..len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+impl<'a> BufRead for ScrollReader<'a> {
+    #[inline]
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        Ok(self.remaining())
+    }
+    #[inline]
+    fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.region.len());
+    }
+}
+
+/// Positional reads over any `Read + Seek` source, e.g., a file, or a boxed reader over an archive member, for reading
+/// it at offsets as though it were a byte slice
+///
+/// It is an [ErasedPread](trait.ErasedPread.html), so it reads values with [pread_with](#method.pread_with) and
+/// [gread_with](#method.gread_with) through a shared reference: each read seeks to its offset and fills the value's
+/// bytes with `read_exact`, while holding a lock on the source, so reads from several places, or threads, don't
+/// interleave their seeks. The reader owns the source's position, which is left wherever the last read ended; it's
+/// nothing to rely on after [into_inner](#method.into_inner).
+///
+/// The source's length is taken when the reader is created, and bounds the reads as a slice's length does: an offset
+/// past it is an `Error::BadOffset`, and a value running past it an `Error::TooBig`. A seek or read which fails, e.g.,
+/// because the source shrank, is an `Error::IO`.
+///
+/// # Example
+/// ```rust
+/// use std::io::{Cursor, Read, Seek};
+/// use scroll::{SeekReader, BE, LE};
+///
+/// trait Member: Read + Seek {}
+/// impl<T: Read + Seek> Member for T {}
+///
+/// let archive: Box<dyn Member> = Box::new(Cursor::new([0xde, 0xad, 0xbe, 0xef, 0x01, 0x00]));
+/// let reader = SeekReader::new(archive).unwrap();
+/// assert_eq!(reader.pread_with::<u16, _>(4, LE).unwrap(), 1);
+/// assert_eq!(reader.pread_with::<u32, _>(0, BE).unwrap(), 0xdeadbeef);
+/// let offset = &mut 2;
+/// assert_eq!(reader.gread_with::<u16, _>(offset, BE).unwrap(), 0xbeef);
+/// assert_eq!(*offset, 4);
+/// assert!(reader.pread_with::<u32, _>(4, LE).is_err());
+/// ```
+#[derive(Debug)]
+pub struct SeekReader<R> {
+    inner: Mutex<R>,
+    len: usize,
+}
+
+impl<R: Read + Seek> SeekReader<R> {
+    /// Wraps `inner`, seeking to its end to find its length
+    pub fn new(mut inner: R) -> Result<Self> {
+        let len = inner.seek(SeekFrom::End(0))?;
+        let len = usize::try_from(len).map_err(|_| io::Error::new(ErrorKind::InvalidInput, crate::Error::BadInput {
+            size: usize::MAX,
+            msg: "source is too long for its offsets to fit in a usize",
+        }))?;
+        Ok(SeekReader { inner: Mutex::new(inner), len })
+    }
+    /// Reads a value at `offset` with the given `ctx`
+    #[inline]
+    pub fn pread_with<N, Ctx>(&self, offset: usize, ctx: Ctx) -> crate::Result<N>
+        where N: for<'b> TryFromCtx<'b, Ctx, Error = crate::Error> + SizeWith<Ctx>,
+              Ctx: Copy,
+    {
+        (self as &dyn ErasedPread).pread_with(offset, ctx)
+    }
+    /// Reads a value at `offset` with the given `ctx`, and advances the offset past it
+    #[inline]
+    pub fn gread_with<N, Ctx>(&self, offset: &mut usize, ctx: Ctx) -> crate::Result<N>
+        where N: for<'b> TryFromCtx<'b, Ctx, Error = crate::Error> + SizeWith<Ctx>,
+              Ctx: Copy,
+    {
+        (self as &dyn ErasedPread).gread_with(offset, ctx)
+    }
+}
+
+impl<R> SeekReader<R> {
+    /// The source's length, as measured when the reader was created
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Whether the source was empty when the reader was created
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Unwraps the source, whose position is wherever the last read left it
+    pub fn into_inner(self) -> R {
+        self.inner.into_inner().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<R: Read + Seek> ErasedPread for SeekReader<R> {
+    fn read_bytes(&self, offset: usize, out: &mut [u8]) -> crate::Result<()> {
+        let len = self.len.checked_sub(offset).ok_or(crate::Error::BadOffset(offset))?;
+        if out.len() > len {
+            return Err(crate::Error::TooBig { size: out.len(), len });
+        }
+        // a read which panicked left nothing behind but the position, which is sought anyway
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        inner.seek(SeekFrom::Start(offset as u64))?;
+        inner.read_exact(out)?;
+        Ok(())
+    }
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// The error `err` of a stream read or write of a value which started `position` bytes into the stream, as an
+/// [Error::Detailed](enum.Error.html#variant.Detailed) with that offset, of the same kind
+fn at_position(err: io::Error, position: u64, what: &str) -> io::Error {
+    let offset = usize::try_from(position).unwrap_or(usize::MAX);
+    let kind = err.kind();
+    io::Error::new(kind, crate::Error::builder().with_offset(offset).with_message(what).with_source(err).build())
+}
+
+/// A `std::io::Read` which counts the bytes read through it, for knowing how far into a stream a value is, e.g., for
+/// diagnostics, or for formats which refer to absolute offsets
+///
+/// Its [ioread_with](#method.ioread_with) and [try_ioread_with](#method.try_ioread_with) (and `ioread`) add the position
+/// the value started at to their errors, as an [Error::Detailed](enum.Error.html#variant.Detailed) with that offset
+/// inside the `io::Error`, of the same kind. Seeking, if the inner reader can, moves the position to where it lands.
+///
+/// # Example
+/// ```rust
+/// use std::io::Cursor;
+/// use scroll::{CountingReader, LE};
+///
+/// let mut reader = CountingReader::new(Cursor::new([0x01, 0x00, 0x02, 0x00, 0x00, 0x00, 0xff]));
+/// let version: u16 = reader.ioread_with(LE).unwrap();
+/// let count: u32 = reader.ioread_with(LE).unwrap();
+/// assert_eq!((version, count, reader.position()), (1, 2, 6));
+///
+/// let error = reader.ioread_with::<u32, _>(LE).unwrap_err();
+/// let error = error.into_inner().unwrap().downcast::<scroll::Error>().unwrap();
+/// assert_eq!(error.offset(), Some(6));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CountingReader<R> {
+    inner: R,
+    position: u64,
+}
+
+impl<R> CountingReader<R> {
+    /// Wraps `inner`, counting from 0
+    #[inline]
+    pub fn new(inner: R) -> Self {
+        CountingReader { inner, position: 0 }
+    }
+    /// Wraps `inner`, which is already `position` bytes into its stream
+    #[inline]
+    pub fn with_position(inner: R, position: u64) -> Self {
+        CountingReader { inner, position }
+    }
+    /// The number of bytes read so far, i.e., how far into the stream the next read starts
+    #[inline]
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+    /// The wrapped reader
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+    /// The wrapped reader; reading from it directly isn't counted
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+    /// Unwraps the reader
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> CountingReader<R> {
+    /// Like [IOread::ioread](trait.IOread.html#method.ioread), but an error says where in the stream the value started
+    #[inline]
+    pub fn ioread<N: FromCtx<Ctx> + SizeWith<Ctx>, Ctx: Copy + Default>(&mut self) -> Result<N> {
+        self.ioread_with(Ctx::default())
+    }
+    /// Like [IOread::ioread_with](trait.IOread.html#method.ioread_with), but an error says where in the stream the
+    /// value started
+    pub fn ioread_with<N: FromCtx<Ctx> + SizeWith<Ctx>, Ctx: Copy>(&mut self, ctx: Ctx) -> Result<N> {
+        let start = self.position;
+        IOread::ioread_with(self, ctx).map_err(|err| at_position(err, start, "stream read failed"))
+    }
+    /// Like [IOread::try_ioread_with](trait.IOread.html#method.try_ioread_with), but an error says where in the stream
+    /// the value started
+    pub fn try_ioread_with<N: FromCtx<Ctx> + SizeWith<Ctx>, Ctx: Copy>(&mut self, ctx: Ctx) -> Result<Option<N>> {
+        let start = self.position;
+        IOread::try_ioread_with(self, ctx).map_err(|err| at_position(err, start, "stream read failed"))
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl<R: BufRead> BufRead for CountingReader<R> {
+    #[inline]
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+    #[inline]
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt);
+        self.position += amt as u64;
+    }
+}
+
+impl<R: Seek> Seek for CountingReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        self.position = self.inner.seek(pos)?;
+        Ok(self.position)
+    }
+}
+
+/// A `std::io::Write` which counts the bytes written through it, like [CountingReader](struct.CountingReader.html)
+///
+/// Its [iowrite_with](#method.iowrite_with) (and `iowrite`) add the position the value started at to their errors, and
+/// seeking, if the inner writer can, moves the position to where it lands.
+///
+/// # Example
+/// ```rust
+/// use scroll::{CountingWriter, BE};
+///
+/// let mut buf = [0u8; 5];
+/// let mut writer = CountingWriter::new(&mut buf[..]);
+/// writer.iowrite_with(0xdeadbeef_u32, BE).unwrap();
+/// assert_eq!(writer.position(), 4);
+/// let error = writer.iowrite_with(0xcafe_u16, BE).unwrap_err();
+/// let error = error.into_inner().unwrap().downcast::<scroll::Error>().unwrap();
+/// assert_eq!(error.offset(), Some(4));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CountingWriter<W> {
+    inner: W,
+    position: u64,
+}
+
+impl<W> CountingWriter<W> {
+    /// Wraps `inner`, counting from 0
+    #[inline]
+    pub fn new(inner: W) -> Self {
+        CountingWriter { inner, position: 0 }
+    }
+    /// Wraps `inner`, which is already `position` bytes into its stream
+    #[inline]
+    pub fn with_position(inner: W, position: u64) -> Self {
+        CountingWriter { inner, position }
+    }
+    /// The number of bytes written so far, i.e., how far into the stream the next write starts
+    #[inline]
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+    /// The wrapped writer
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+    /// The wrapped writer; writing to it directly isn't counted
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+    /// Unwraps the writer
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> CountingWriter<W> {
+    /// Like [IOwrite::iowrite](trait.IOwrite.html#method.iowrite), but an error says where in the stream the value
+    /// started
+    #[inline]
+    pub fn iowrite<N: SizeWith<Ctx> + IntoCtx<Ctx>, Ctx: Copy + Default>(&mut self, n: N) -> Result<()> {
+        self.iowrite_with(n, Ctx::default())
+    }
+    /// Like [IOwrite::iowrite_with](trait.IOwrite.html#method.iowrite_with), but an error says where in the stream the
+    /// value started
+    pub fn iowrite_with<N: SizeWith<Ctx> + IntoCtx<Ctx>, Ctx: Copy>(&mut self, n: N, ctx: Ctx) -> Result<()> {
+        let start = self.position;
+        IOwrite::iowrite_with(self, n, ctx).map_err(|err| at_position(err, start, "stream write failed"))
+    }
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.position += written as u64;
+        Ok(written)
+    }
+    #[inline]
+    fn flush(&mut self) -> Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Seek> Seek for CountingWriter<W> {
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        self.position = self.inner.seek(pos)?;
+        Ok(self.position)
+    }
+}
@@ -12,7 +12,7 @@
 //!
 //! ```
 //!
-//! Scroll is a library for efficiently and easily reading/writing types from byte arrays. All the builtin types are supported, e.g., `u32`, `i8`, etc., where the type is specified as a type parameter, or type inferred when possible. In addition, it supports zero-copy reading of string slices, or any other kind of slice.  The library can be used in a no_std context as well; the [Error](enum.Error.html) type only has the `IO` and `String` variants if the default features are used, and is `no_std` safe when compiled without default features.
+//! Scroll is a library for efficiently and easily reading/writing types from byte arrays. All the builtin types are supported, e.g., `u32`, `i8`, etc., where the type is specified as a type parameter, or type inferred when possible. In addition, it supports zero-copy reading of string slices, or any other kind of slice.  The library can be used in a no_std context as well, by compiling without default features; the `alloc` feature then brings back the owned types (`Vec`, `String`, `CString`, the maps, [CowBuffer](struct.CowBuffer.html)) and the allocating [Error](enum.Error.html) variants, for targets which have an allocator but no `std`. Only the `std::io` readers and writers, `OsString` and the paths, `SystemTime` and `HashMap` need `std` itself.
 //!
 //! There are 3 traits for reading that you can import:
 //!
@@ -25,6 +25,7 @@
 //! Most familiar will likely be the `Pread` trait (inspired from the C function), which in our case takes an immutable reference to self, an immutable offset to read at, (and _optionally_ a parsing context, more on that later), and then returns the deserialized value.
 //!
 //! Because self is immutable, _**all** reads can be performed in parallel_ and hence are trivially parallelizable.
+//! Shared buffers like `Arc<[u8]>` or `Arc<Vec<u8>>` dereference to their contents, so clones of one can be handed to several threads and `pread` from concurrently.
 //!
 //! For most usecases, you can use [scroll_derive](https://docs.rs/scroll_derive) to annotate your types with `derive(Pread, Pwrite, IOread, IOwrite, SizeWith)` to automatically add sensible derive defaults, and you should be ready to roll.  For more complex usescases, you can implement the conversion traits yourself, see the [context module](ctx/index.html) for more information.
 //!
@@ -48,8 +49,10 @@
 //! let be_number: u32 = bytes.pread_with(0, scroll::BE).unwrap();
 //! // or a u16 - specify the type either on the variable or with the beloved turbofish
 //! let be_number2 = bytes.pread_with::<u16>(2, scroll::BE).unwrap();
+//! // `pread_be` and `pread_le` are shorthands for the two fixed byte orders
+//! let be_number3 = bytes.pread_be::<u16>(2).unwrap();
 //!
-//! // Scroll has core friendly errors (no allocation). This will have the type `scroll::Error::BadOffset` because it tried to read beyond the bound
+//! // Scroll has core friendly errors (no allocation). This will have the type `scroll::Error::Incomplete` because it tried to read beyond the bound
 //! let byte: scroll::Result<i64> = bytes.pread(0);
 //!
 //! // Scroll is extensible: as long as the type implements `TryWithCtx`, then you can read your type out of the byte array!
@@ -65,6 +68,10 @@
 //! let spaces: &[u8] = b"hello world some junk";
 //! let world: &str = spaces.pread_with(6, StrCtx::Delimiter(SPACE)).unwrap();
 //! assert_eq!("world", world);
+//!
+//! // byte slices are read the same way, with their length as the context
+//! let some: &[u8] = spaces.pread_with(12, 4).unwrap();
+//! assert_eq!(b"some", some);
 //! ```
 //!
 //! # `std::io` API
@@ -96,6 +103,20 @@
 //! assert_eq!(cursor.into_inner(), [0x68, 0x65, 0x6c, 0x6c, 0x6f, 0xde, 0xad, 0xbe, 0xef, 0x0]);
 //! ```
 //!
+//! # Panics
+//!
+//! The fallible API doesn't panic on its input: whatever the bytes, offsets, lengths and counts, the `pread`, `gread`,
+//! `pwrite` and `gwrite` families, the `ioread` and `iowrite` ones, and the library's own contexts return an
+//! [Error](enum.Error.html) rather than index out of bounds, overflow or allocate for a length they haven't checked
+//! against the source. Writes into a buffer which grows to fit, as [to_vec](fn.to_vec.html), [Counter](struct.Counter.html)
+//! and `SecretBuffer::gwrite_with` make, have no source to check against: they grow only while the value lacks room, and
+//! stop at a limit, `Counter::DEFAULT_LIMIT` unless given another, with an `Error::LimitExceeded`. `tests/no_panic.rs`
+//! holds them all to it, with adversarial offsets and lengths, and overflow checks on.
+//!
+//! The exceptions are the infallible traits, [FromCtx](ctx/trait.FromCtx.html) and [IntoCtx](ctx/trait.IntoCtx.html),
+//! and the [Cread](trait.Cread.html) and [Cwrite](trait.Cwrite.html) families built on them, which panic on a short
+//! buffer by design. A context impl of your own is trusted to return a size no larger than the bytes it was given.
+//!
 //! # Advanced Uses
 //!
 //! Scroll is designed to be highly configurable - it allows you to implement various context (`Ctx`) sensitive traits, which then grants the implementor _automatic_ uses of the `Pread` and/or `Pwrite` traits.
@@ -131,6 +152,11 @@
 //! let data = bytes.pread_with::<Data>(0, BE).unwrap();
 //! assert_eq!(data.id, 0x01020304);
 //! assert_eq!(data.name.to_string(), "UserName".to_string());
+//!
+//! // greedily too; the name borrows from `bytes`, not from the offset
+//! let offset = &mut 0;
+//! let data: Data = bytes.gread_with(offset, BE).unwrap();
+//! assert_eq!((data.name, *offset), ("UserName", bytes.len()));
 //! ```
 //!
 //! Please see the [Pread documentation examples](trait.Pread.html#implementing-your-own-reader)
@@ -139,20 +165,60 @@
 
 #[cfg(feature = "derive")]
 #[allow(unused_imports)]
-use scroll_derive::{Pread, Pwrite, SizeWith, IOread, IOwrite};
+use scroll_derive::{Pread, Pwrite, SizeWith, ConstSize, PreadView, IOread, IOwrite};
 
 #[cfg(feature = "std")]
 extern crate core;
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+/// What std's prelude provides for the `alloc` feature, when it is used without `std`
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+mod prelude {
+    pub use alloc::borrow::ToOwned;
+    pub use alloc::boxed::Box;
+    pub use alloc::format;
+    pub use alloc::string::{String, ToString};
+    pub use alloc::vec;
+    pub use alloc::vec::Vec;
+}
+
 pub mod ctx;
+pub mod offset;
+pub mod types;
 mod pread;
 mod pwrite;
 mod greater;
 mod error;
 mod endian;
-mod leb128;
+pub mod leb128;
+pub mod group_varint;
+pub mod const_read;
+mod fixed;
+mod strtab;
+mod buffer;
+mod erased;
+mod paged;
+mod bits;
+#[cfg(feature = "bytemuck")]
+mod pod;
+#[cfg(feature = "encoding")]
+mod encoding;
+#[cfg(feature = "bigint")]
+mod bigint;
+#[cfg(feature = "bitflags")]
+mod flags;
+#[cfg(feature = "volatile")]
+mod volatile;
+#[cfg(feature = "heapless")]
+mod heapless;
 #[cfg(feature = "std")]
 mod lesser;
+#[cfg(feature = "alloc")]
+mod counter;
+#[cfg(feature = "alloc")]
+mod dump;
 
 pub use crate::endian::*;
 pub use crate::pread::*;
@@ -160,18 +226,129 @@ pub use crate::pwrite::*;
 pub use crate::greater::*;
 pub use crate::error::*;
 pub use crate::leb128::*;
+pub use crate::group_varint::GroupVarint;
+pub use crate::fixed::*;
+pub use crate::strtab::*;
+pub use crate::buffer::*;
+pub use crate::erased::*;
+pub use crate::paged::*;
+pub use crate::bits::*;
+#[cfg(feature = "bytemuck")]
+pub use crate::pod::*;
+#[cfg(feature = "encoding")]
+pub use crate::encoding::*;
+#[cfg(feature = "bigint")]
+pub use crate::bigint::*;
+#[cfg(feature = "bitflags")]
+pub use crate::flags::*;
+#[cfg(feature = "volatile")]
+pub use crate::volatile::*;
 #[cfg(feature = "std")]
 pub use crate::lesser::*;
+#[cfg(feature = "alloc")]
+pub use crate::counter::*;
+#[cfg(feature = "alloc")]
+pub use crate::dump::*;
 
 #[doc(hidden)]
 pub mod export {
     pub use ::core::result;
     pub use ::core::mem;
+    pub use ::core::fmt;
+    #[cfg(feature = "bitflags")]
+    pub use ::bitflags;
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use crate::prelude::*;
+
+    /// The error for a derived struct whose `field`, at `offset` from the start of the struct, doesn't meet its
+    /// `#[scroll(..)]` attribute; `expected` is the source text of the attribute's value
+    #[cfg(feature = "alloc")]
+    pub fn field_mismatch(field: &str, expected: &str, found: &dyn ::core::fmt::Debug, offset: usize) -> crate::Error {
+        crate::Error::Custom(format!("field `{}` at offset {}: expected {}, found {:?}", field, offset, expected, found))
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    pub fn field_mismatch(_field: &str, _expected: &str, _found: &dyn ::core::fmt::Debug, offset: usize) -> crate::Error {
+        crate::Error::Invalid { offset, msg: "field does not meet its scroll attribute" }
+    }
+
+    /// Reads a field of a derived struct with the struct's custom context `Ctx`; naming `Ctx` makes a field which can't be
+    /// read with it an unsatisfied `TryFromCtx<Ctx>` bound on the field's type, rather than a mismatch with its contexts
+    #[inline]
+    pub fn gread_field<'a, N, Ctx: Copy>(src: &'a [u8], offset: &mut usize, ctx: Ctx) -> crate::Result<N>
+        where N: crate::ctx::TryFromCtx<'a, Ctx, Error = crate::Error>
+    {
+        use crate::Pread;
+        src.gread_with(offset, ctx)
+    }
+
+    /// Writes a field of a derived struct with the struct's custom context `Ctx`, like [gread_field](fn.gread_field.html)
+    #[inline]
+    pub fn gwrite_field<N, Ctx: Copy>(dst: &mut [u8], field: N, offset: &mut usize, ctx: Ctx) -> crate::Result<usize>
+        where N: crate::ctx::TryIntoCtx<Ctx, Error = crate::Error>
+    {
+        use crate::Pwrite;
+        dst.gwrite_with(field, offset, ctx)
+    }
+
+    /// Reads the unsigned leb128 field of a derived struct at `offset`, as its integer type `T`
+    pub fn read_uleb128<T: ::core::convert::TryFrom<u128>>(src: &[u8], offset: &mut usize) -> crate::Result<T> {
+        let at = *offset;
+        let value = crate::Uleb128::read_u128(src, offset)?;
+        T::try_from(value).map_err(|_| crate::Error::Invalid { offset: at, msg: "leb128 value out of range for its field" })
+    }
+
+    /// Reads the signed leb128 field of a derived struct at `offset`, as its integer type `T`
+    pub fn read_sleb128<T: ::core::convert::TryFrom<i64>>(src: &[u8], offset: &mut usize) -> crate::Result<T> {
+        let at = *offset;
+        let value = crate::Sleb128::read(src, offset)?;
+        T::try_from(value).map_err(|_| crate::Error::Invalid { offset: at, msg: "leb128 value out of range for its field" })
+    }
+
+    /// Writes the unsigned leb128 field `value` of a derived struct at `offset`
+    pub fn write_uleb128<T: ::core::convert::TryInto<u128>>(dst: &mut [u8], offset: &mut usize, value: T) -> crate::Result<usize> {
+        let value = value.try_into().map_err(|_| crate::Error::Invalid { offset: *offset, msg: "leb128 field is too large to write" })?;
+        crate::Uleb128::write_u128(dst, offset, value)
+    }
+
+    /// Writes the signed leb128 field `value` of a derived struct at `offset`
+    pub fn write_sleb128<T: ::core::convert::TryInto<i64>>(dst: &mut [u8], offset: &mut usize, value: T) -> crate::Result<usize> {
+        let value = value.try_into().map_err(|_| crate::Error::Invalid { offset: *offset, msg: "leb128 field is too large to write" })?;
+        crate::Sleb128::write(dst, offset, value)
+    }
+
+    /// Moves the offset of a derived struct being read forward to `to`, the explicit offset of a field or the struct's
+    /// size, past the gap before it
+    pub fn read_gap(src: &[u8], offset: &mut usize, to: usize) -> crate::Result<()> {
+        if *offset > to {
+            return Err(crate::Error::Invalid { offset: to, msg: "fields run past an explicit offset or the struct's size" });
+        }
+        if to > src.len() {
+            return Err(crate::Error::Incomplete { needed: to - src.len() });
+        }
+        *offset = to;
+        Ok(())
+    }
+
+    /// Moves the offset of a derived struct being written forward to `to`, like [read_gap](fn.read_gap.html), zeroing
+    /// the gap if `zero`, and otherwise leaving it as it is
+    pub fn write_gap(dst: &mut [u8], offset: &mut usize, to: usize, zero: bool) -> crate::Result<()> {
+        if *offset > to {
+            return Err(crate::Error::Invalid { offset: to, msg: "fields run past an explicit offset or the struct's size" });
+        }
+        if to > dst.len() {
+            return Err(crate::Error::TooBig { size: to, len: dst.len() });
+        }
+        if zero {
+            dst[*offset..to].fill(0);
+        }
+        *offset = to;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    #[allow(overflowing_literals)]
     use super::{LE};
 
     #[test]
@@ -245,11 +422,10 @@ mod tests {
         let b = &bytes[..];
         let iserr: Result<&str, _>  = b.pread_with(0, StrCtx::Length(3));
         assert!(iserr.is_err());
-        // let bytes2: &[u8]  = b.pread_with(0, 2).unwrap();
-        // assert_eq!(bytes2.len(), bytes[..].len());
-        // for i in 0..bytes2.len() {
-        //     assert_eq!(bytes2[i], bytes[i])
-        // }
+        let bytes2: &[u8]  = b.pread_with(0, 2).unwrap();
+        assert_eq!(bytes2, &bytes[..]);
+        let iserr: Result<&[u8], _> = b.pread_with(1, 2);
+        assert!(iserr.is_err());
     }
 
     #[test]
@@ -282,7 +458,7 @@ mod tests {
         let bytes: &[u8] = b"";
         let hello_world = bytes.pread_with::<&str>(0, StrCtx::Delimiter(NULL));
         println!("1 {:?}", &hello_world);
-        assert_eq!(hello_world.is_err(), true);
+        assert!(hello_world.is_err());
         let error = bytes.pread_with::<&str>(7, StrCtx::Delimiter(SPACE));
         println!("2 {:?}", &error);
         assert!(error.is_err());
@@ -327,15 +503,13 @@ mod tests {
         fn description(&self) -> &str {
             "ExternalError"
         }
-        fn cause(&self) -> Option<&error::Error> { None}
+        fn cause(&self) -> Option<&dyn error::Error> { None}
     }
 
     impl From<super::Error> for ExternalError {
         fn from(err: super::Error) -> Self {
-            //use super::Error::*;
-            match err {
-                _ => ExternalError{},
-            }
+            let _ = err;
+            ExternalError {}
         }
     }
 
@@ -346,7 +520,7 @@ mod tests {
         type Error = ExternalError;
         fn try_into_ctx(self, this: &mut [u8], le: super::Endian) -> Result<usize, Self::Error> {
             use super::Pwrite;
-            if this.len() < 2 { return Err((ExternalError {}).into()) }
+            if this.len() < 2 { return Err(ExternalError {}) }
             this.pwrite_with(self.0, 0, le)?;
             Ok(2)
         }
@@ -356,12 +530,22 @@ mod tests {
         type Error = ExternalError;
         fn try_from_ctx(this: &'a [u8], le: super::Endian) -> Result<(Self, usize), Self::Error> {
             use super::Pread;
-            if this.len() > 2 { return Err((ExternalError {}).into()) }
+            if this.len() > 2 { return Err(ExternalError {}) }
             let n = this.pread_with(0, le)?;
             Ok((Foo(n), 2))
         }
     }
 
+    #[test]
+    fn external_error() {
+        use super::{Pread, Pwrite, LE};
+        let mut bytes = [0u8; 2];
+        bytes.pwrite_with(Foo(0xbeef), 0, LE).unwrap();
+        assert_eq!(bytes.pread_with::<Foo>(0, LE).unwrap(), Foo(0xbeef));
+        assert!(bytes[..1].pwrite_with(Foo(1), 0, LE).is_err());
+        assert!([0u8; 3].pread_with::<Foo>(0, LE).is_err());
+    }
+
     #[test]
     fn pread_with_iter_bytes() {
         use super::{Pread};
@@ -369,8 +553,8 @@ mod tests {
         let bytes_from: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
         let bytes_to = &mut bytes_to[..];
         let bytes_from = &bytes_from[..];
-        for i in 0..bytes_from.len() {
-            bytes_to[i] = bytes_from.pread(i).unwrap();
+        for (i, byte) in bytes_to.iter_mut().enumerate() {
+            *byte = bytes_from.pread(i).unwrap();
         }
         assert_eq!(bytes_to, bytes_from);
     }
@@ -463,9 +647,9 @@ mod tests {
         let bytes_from: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
         let bytes_to = &mut bytes_to[..];
         let bytes_from = &bytes_from[..];
-        let mut offset = &mut 0;
-        for i in 0..bytes_from.len() {
-            bytes_to[i] = bytes_from.gread(&mut offset).unwrap();
+        let offset = &mut 0;
+        for byte in bytes_to.iter_mut() {
+            *byte = bytes_from.gread(offset).unwrap();
         }
         assert_eq!(bytes_to, bytes_from);
         assert_eq!(*offset, bytes_to.len());
@@ -504,11 +688,11 @@ mod tests {
         let res = b.gread_with::<&str>(offset, StrCtx::Length(3));
         assert!(res.is_err());
         *offset = 0;
-        let astring: [u8; 3] = [0x45, 042, 0x44];
+        let astring: [u8; 3] = [0x45, 42, 0x44];
         let string = astring.gread_with::<&str>(offset, StrCtx::Length(2));
-        match &string {
-            &Ok(_) => {},
-            &Err(ref err) => {println!("{}", &err); panic!();}
+        match string {
+            Ok(_) => {},
+            Err(ref err) => {println!("{}", &err); panic!();}
         }
         assert_eq!(string.unwrap(), "E*");
         *offset = 0;
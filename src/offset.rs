@@ -0,0 +1,427 @@
+//! Alignment helpers for byte offsets, an optional typed [Offset](struct.Offset.html), an
+//! [OffsetView](struct.OffsetView.html) of a section which reports the offsets of the whole file, and
+//! [transactions](fn.gread_txn.html) for speculative reads
+//!
+//! All of the helpers validate that the alignment is a power of two and use checked arithmetic, so an offset
+//! near `usize::MAX` returns an error instead of silently wrapping around.
+//!
+//! # Example
+//! ```rust
+//! use scroll::offset::{self, Offset};
+//! use scroll::{Pread, LE};
+//!
+//! assert_eq!(offset::align_up(5, 4).unwrap(), 8);
+//! assert_eq!(offset::align_down(5, 4).unwrap(), 4);
+//! assert!(offset::align_up(5, 3).is_err());
+//!
+//! let bytes = [0xff, 0, 0, 0, 0xef, 0xbe, 0xad, 0xde];
+//! let mut off = Offset::new(1);
+//! off = off.align_up(4).unwrap();
+//! // `Offset` converts into a plain `usize` for positional reads...
+//! assert_eq!(bytes.pread_with::<u32>(off.into(), LE).unwrap(), 0xdeadbeef);
+//! // ...and derefs to one for greedy reads
+//! let _: u32 = bytes.gread_with(&mut off, LE).unwrap();
+//! assert_eq!(off, Offset::new(8));
+//! ```
+
+use core::ops::{Deref, DerefMut};
+
+use crate::ctx::TryFromCtx;
+use crate::error;
+use crate::pread::PreadExt;
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use crate::prelude::*;
+
+#[inline]
+fn check_align(align: usize) -> error::Result<()> {
+    if align.is_power_of_two() {
+        Ok(())
+    } else {
+        Err(error::Error::BadInput { size: align, msg: "alignment must be a power of two" })
+    }
+}
+
+/// Rounds `offset` up to the next multiple of `align`, which must be a power of two
+#[inline]
+pub fn align_up(offset: usize, align: usize) -> error::Result<usize> {
+    check_align(align)?;
+    let mask = align - 1;
+    match offset.checked_add(mask) {
+        Some(offset) => Ok(offset & !mask),
+        None => Err(error::Error::BadOffset(offset)),
+    }
+}
+
+/// Rounds `offset` down to the previous multiple of `align`, which must be a power of two
+#[inline]
+pub fn align_down(offset: usize, align: usize) -> error::Result<usize> {
+    check_align(align)?;
+    Ok(offset & !(align - 1))
+}
+
+/// Is `offset` a multiple of `align`? Always `false` if `align` is not a power of two
+#[inline]
+pub fn is_aligned(offset: usize, align: usize) -> bool {
+    align.is_power_of_two() && offset & (align - 1) == 0
+}
+
+/// A byte offset, kept distinct from lengths and sizes
+///
+/// `Offset` is entirely opt-in: it converts into a `usize` for `pread` and friends, and dereferences to one,
+/// so `&mut Offset` can be passed wherever `gread` expects a `&mut usize`.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Offset(pub usize);
+
+impl Offset {
+    #[inline]
+    pub fn new(offset: usize) -> Self {
+        Offset(offset)
+    }
+    /// Rounds this offset up to the next multiple of `align`
+    #[inline]
+    pub fn align_up(self, align: usize) -> error::Result<Self> {
+        align_up(self.0, align).map(Offset)
+    }
+    /// Rounds this offset down to the previous multiple of `align`
+    #[inline]
+    pub fn align_down(self, align: usize) -> error::Result<Self> {
+        align_down(self.0, align).map(Offset)
+    }
+    /// Is this offset a multiple of `align`?
+    #[inline]
+    pub fn is_aligned(self, align: usize) -> bool {
+        is_aligned(self.0, align)
+    }
+    /// Advances this offset by `len` bytes, erroring on overflow
+    #[inline]
+    pub fn checked_add(self, len: usize) -> error::Result<Self> {
+        self.0.checked_add(len).map(Offset).ok_or(error::Error::BadOffset(self.0))
+    }
+    /// Moves this offset back by `len` bytes, erroring on underflow
+    #[inline]
+    pub fn checked_sub(self, len: usize) -> error::Result<Self> {
+        self.0.checked_sub(len).map(Offset).ok_or(error::Error::BadOffset(self.0))
+    }
+}
+
+impl From<usize> for Offset {
+    #[inline]
+    fn from(offset: usize) -> Self {
+        Offset(offset)
+    }
+}
+
+impl From<Offset> for usize {
+    #[inline]
+    fn from(offset: Offset) -> usize {
+        offset.0
+    }
+}
+
+impl Deref for Offset {
+    type Target = usize;
+    #[inline]
+    fn deref(&self) -> &usize {
+        &self.0
+    }
+}
+
+impl DerefMut for Offset {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut usize {
+        &mut self.0
+    }
+}
+
+/// A section of a larger source, starting `base` bytes into it, whose errors report offsets into the whole source
+///
+/// Offsets passed to a view are relative to the section, as they would be for a plain subslice, but the errors of its
+/// reads carry absolute offsets: those which have an offset get `base` added to it, and, under the `alloc` feature,
+/// those which don't are wrapped in an `Error::Context` naming the absolute offset of the value. A view of a view adds
+/// the bases together.
+///
+/// # Example
+/// ```rust
+/// use scroll::{offset::OffsetView, Error, LE};
+///
+/// let file = vec![0u8; 0x4012];
+/// let section = OffsetView::new(&file[0x4000..], 0x4000);
+/// assert_eq!(section.pread_with::<u16, _>(0x10, LE).unwrap(), 0);
+/// assert!(matches!(section.pread_with::<u8, _>(0x20, LE), Err(Error::BadOffset(0x4020))));
+/// let err = section.pread_with::<u32, _>(0x10, LE).unwrap_err();
+/// assert_eq!(err.to_string(), "at offset 0x4010: incomplete input, 2 more bytes needed");
+/// assert_eq!(section.to_absolute(0x10), 0x4010);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct OffsetView<'a> {
+    bytes: &'a [u8],
+    base: usize,
+}
+
+impl<'a> OffsetView<'a> {
+    /// A view of `bytes`, which start `base` bytes into their source
+    #[inline]
+    pub fn new(bytes: &'a [u8], base: usize) -> Self {
+        OffsetView { bytes, base }
+    }
+    /// The offset of the view in its source
+    #[inline]
+    pub fn base(&self) -> usize {
+        self.base
+    }
+    /// The offset in the source of the byte `offset` bytes into the view
+    #[inline]
+    pub fn to_absolute(&self, offset: usize) -> usize {
+        self.base.saturating_add(offset)
+    }
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.bytes
+    }
+    /// A view of the `len` bytes at `offset` into this one; see [PreadExt::pread_window](../trait.PreadExt.html#tymethod.pread_window)
+    #[inline]
+    pub fn window(&self, offset: usize, len: usize) -> error::Result<OffsetView<'a>> {
+        match self.bytes.pread_window(offset, len) {
+            Ok(bytes) => Ok(OffsetView::new(bytes, self.to_absolute(offset))),
+            Err(err @ error::Error::BadOffset(_)) => Err(self.locate(0, err)),
+            Err(err) => Err(self.locate(offset, err)),
+        }
+    }
+    /// Reads a value at `offset` into the view with the given `ctx`
+    #[inline]
+    pub fn pread_with<N, Ctx: Copy>(&self, offset: usize, ctx: Ctx) -> error::Result<N>
+        where N: TryFromCtx<'a, Ctx, Error = error::Error>
+    {
+        self.gread_with(&mut { offset }, ctx)
+    }
+    /// Reads a value at `offset` into the view with a default `Ctx`
+    #[inline]
+    pub fn pread<N, Ctx: Copy + Default>(&self, offset: usize) -> error::Result<N>
+        where N: TryFromCtx<'a, Ctx, Error = error::Error>
+    {
+        self.pread_with(offset, Ctx::default())
+    }
+    /// Reads a value at `offset` into the view with the given `ctx`, and advances the offset past it
+    pub fn gread_with<N, Ctx: Copy>(&self, offset: &mut usize, ctx: Ctx) -> error::Result<N>
+        where N: TryFromCtx<'a, Ctx, Error = error::Error>
+    {
+        let src = self.bytes.get(*offset..).ok_or_else(|| self.locate(0, error::Error::BadOffset(*offset)))?;
+        let (n, size) = N::try_from_ctx(src, ctx).map_err(|err| self.locate(*offset, err))?;
+        *offset += size;
+        Ok(n)
+    }
+    /// Reads a value at `offset` into the view with a default `Ctx`, and advances the offset past it
+    #[inline]
+    pub fn gread<N, Ctx: Copy + Default>(&self, offset: &mut usize) -> error::Result<N>
+        where N: TryFromCtx<'a, Ctx, Error = error::Error>
+    {
+        self.gread_with(offset, Ctx::default())
+    }
+    /// Makes `err`, from reading a value `offset` bytes into the view, report absolute offsets
+    fn locate(&self, offset: usize, err: error::Error) -> error::Error {
+        let at = self.to_absolute(offset);
+        if err.offset().is_some() {
+            return err.rebase(at);
+        }
+        #[cfg(feature = "alloc")]
+        return error::Error::Context { msg: format!("at offset {:#x}", at), source: Box::new(err) };
+        #[cfg(not(feature = "alloc"))]
+        err
+    }
+}
+
+/// Runs `f`, a sequence of greedy reads, on a copy of `offset`, and commits the copy to `offset` only if `f` succeeds;
+/// this is the way to look ahead, e.g. to try one format's header and then another's at the same spot
+///
+/// A failing `f` leaves `offset` exactly where it was, however far it got. Transactions nest, and cost nothing but a
+/// copy of the offset. For a single value, see [Pread::gread_with_rollback](../trait.Pread.html#method.gread_with_rollback).
+///
+/// # Example
+/// ```rust
+/// use scroll::{offset::gread_txn, Pread, BE};
+///
+/// let bytes = [0x01, 0x02, 0xff, 0x00];
+/// let offset = &mut 0;
+/// // a u16 count, then that many u16s: runs off the end
+/// let wide = gread_txn(offset, |offset| -> scroll::Result<Vec<u16>> {
+///     let count: u16 = bytes.gread_with(offset, BE)?;
+///     (0..count).map(|_| bytes.gread_with(offset, BE)).collect()
+/// });
+/// assert!(wide.is_err());
+/// assert_eq!(*offset, 0);
+/// // a u8 count, then that many u16s
+/// let narrow = gread_txn(offset, |offset| -> scroll::Result<Vec<u16>> {
+///     let count: u8 = bytes.gread_with(offset, BE)?;
+///     (0..count).map(|_| bytes.gread_with(offset, BE)).collect()
+/// });
+/// assert_eq!(narrow.unwrap(), [0x02ff]);
+/// assert_eq!(*offset, 3);
+/// ```
+#[inline]
+pub fn gread_txn<T, E, F>(offset: &mut usize, f: F) -> Result<T, E>
+    where F: FnOnce(&mut usize) -> Result<T, E>,
+{
+    let mut scoped = *offset;
+    let value = f(&mut scoped)?;
+    *offset = scoped;
+    Ok(value)
+}
+
+/// A saved offset, to [restore](#method.restore) after a speculative sequence of reads fails; the explicit form of
+/// [gread_txn](fn.gread_txn.html), for control flow a closure doesn't suit
+///
+/// # Example
+/// ```rust
+/// use scroll::{offset::Checkpoint, Pread, LE};
+///
+/// let bytes = [0x01, 0x02, 0x03];
+/// let offset = &mut 1;
+/// let checkpoint = Checkpoint::new(offset);
+/// let _: u8 = bytes.gread(offset).unwrap();
+/// if bytes.gread_with::<u16>(offset, LE).is_err() {
+///     checkpoint.restore(offset);
+/// }
+/// assert_eq!(*offset, 1);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Checkpoint(usize);
+
+impl Checkpoint {
+    /// Saves `offset`
+    #[inline]
+    pub fn new(offset: &usize) -> Self {
+        Checkpoint(*offset)
+    }
+    /// The saved offset
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.0
+    }
+    /// Moves `offset` back to the saved one
+    #[inline]
+    pub fn restore(&self, offset: &mut usize) {
+        *offset = self.0;
+    }
+    /// How far `offset` has moved past the saved one
+    #[inline]
+    pub fn consumed(&self, offset: &usize) -> usize {
+        offset.saturating_sub(self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn align() {
+        assert_eq!(align_up(0, 8).unwrap(), 0);
+        assert_eq!(align_up(1, 8).unwrap(), 8);
+        assert_eq!(align_up(8, 8).unwrap(), 8);
+        assert_eq!(align_up(9, 1).unwrap(), 9);
+        assert_eq!(align_down(15, 8).unwrap(), 8);
+        assert_eq!(align_down(16, 8).unwrap(), 16);
+        assert!(is_aligned(16, 16));
+        assert!(!is_aligned(17, 16));
+    }
+
+    #[test]
+    fn align_overflow() {
+        assert!(align_up(usize::MAX, 2).is_err());
+        assert!(align_up(usize::MAX - 6, 8).is_err());
+        assert_eq!(align_up(usize::MAX - 7, 8).unwrap(), usize::MAX - 7);
+        assert_eq!(align_down(usize::MAX, 8).unwrap(), usize::MAX - 7);
+    }
+
+    #[test]
+    fn align_not_power_of_two() {
+        assert!(align_up(4, 0).is_err());
+        assert!(align_up(4, 3).is_err());
+        assert!(align_down(4, 12).is_err());
+        assert!(!is_aligned(0, 0));
+        assert!(!is_aligned(6, 6));
+    }
+
+    #[test]
+    fn typed_offset() {
+        let off = Offset::new(3);
+        assert_eq!(off.align_up(4).unwrap(), Offset(4));
+        assert_eq!(off.align_down(4).unwrap(), Offset(0));
+        assert_eq!(off.checked_add(5).unwrap(), Offset(8));
+        assert!(off.checked_sub(4).is_err());
+        assert!(Offset(usize::MAX).checked_add(1).is_err());
+        assert_eq!(usize::from(off), 3);
+    }
+
+    #[test]
+    fn offset_view() {
+        use crate::{Error, LE};
+        let mut file = vec![0u8; 0x4020];
+        file[0x4008] = 0xff;
+        let section = OffsetView::new(&file[0x4000..0x4012], 0x4000);
+        // a read at relative 0x10 runs off the end of the section
+        let err = section.pread_with::<u32, _>(0x10, LE).unwrap_err();
+        assert!(err.to_string().contains("0x4010"), "{}", err);
+        assert!(matches!(section.pread_with::<u8, _>(0x13, LE), Err(Error::BadOffset(0x4013))));
+        assert_eq!(section.to_absolute(0x10), 0x4010);
+
+        // views nest, adding their bases
+        let header = section.window(0x8, 0x8).unwrap();
+        assert_eq!(header.base(), 0x4008);
+        let offset = &mut 0;
+        assert_eq!(header.gread::<u8, _>(offset).unwrap(), 0xff);
+        assert_eq!(*offset, 1);
+        assert!(matches!(header.gread_with::<u8, _>(&mut 9, LE), Err(Error::BadOffset(0x4011))));
+        assert_eq!(section.window(0x10, 4).unwrap_err().to_string(), "at offset 0x4010: type is too big (4) for 2");
+        assert!(matches!(section.window(0x13, 0), Err(Error::BadOffset(0x4013))));
+
+        // errors with an offset relative to the value are rebased to it
+        let bad = Error::Invalid { offset: 1, msg: "bad" };
+        assert!(matches!(header.locate(2, bad), Error::Invalid { offset: 0x400b, .. }));
+    }
+
+    #[test]
+    fn gread_txn_nests() {
+        use crate::{Error, Pread, BE};
+        let bytes = [0xca, 0xfe, 0x00, 0x04, 0xde, 0xad];
+        let offset = &mut 0;
+
+        // the inner transaction fails, and only it is undone
+        let magic = gread_txn(offset, |offset| -> error::Result<u16> {
+            let magic = bytes.gread_with(offset, BE)?;
+            let inner = gread_txn(offset, |offset| -> error::Result<u64> { bytes.gread_with(offset, BE) });
+            assert!(inner.is_err());
+            assert_eq!(*offset, 2);
+            Ok(magic)
+        }).unwrap();
+        assert_eq!(magic, 0xcafe);
+        assert_eq!(*offset, 2);
+
+        // a failing outer transaction undoes the inner one it committed
+        let failed = gread_txn(offset, |offset| -> error::Result<()> {
+            let _: u16 = gread_txn(offset, |offset| bytes.gread_with(offset, BE))?;
+            assert_eq!(*offset, 4);
+            Err(Error::BadInput { size: 0, msg: "not this format" })
+        });
+        assert!(failed.is_err());
+        assert_eq!(*offset, 2);
+
+        // a successful one commits exactly what it consumed
+        let checkpoint = Checkpoint::new(offset);
+        let (len, tail): (u16, u16) = gread_txn(offset, |offset| -> error::Result<_> {
+            Ok((bytes.gread_with(offset, BE)?, bytes.gread_with(offset, BE)?))
+        }).unwrap();
+        assert_eq!((len, tail), (4, 0xdead));
+        assert_eq!(checkpoint.consumed(offset), 4);
+        checkpoint.restore(offset);
+        assert_eq!(*offset, checkpoint.offset());
+    }
+}
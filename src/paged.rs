@@ -0,0 +1,178 @@
+#[cfg(feature = "alloc")]
+use alloc::borrow::Cow;
+use core::convert::TryFrom;
+
+use crate::ctx::{SizeWith, TryFromCtx};
+use crate::erased::ErasedPread;
+use crate::error;
+
+/// Hands out the pages of a source too large, or too slow, to have in memory at once, e.g., the sectors of a disk image
+/// behind a cache, for a [PagedSource](struct.PagedSource.html) to read from
+pub trait PageProvider {
+    /// The number of bytes in a page; every page but the last is this long
+    fn page_size(&self) -> usize;
+    /// The number of bytes in the source
+    fn total_len(&self) -> u64;
+    /// The bytes of the page `page_index`, which start `page_index * page_size` bytes into the source
+    ///
+    /// A page may be longer than the page size, e.g., a last sector padded out to full size, and only its first bytes
+    /// are used; a shorter one is an error. Failing to fetch it is an error too, which the read fails with.
+    fn page(&self, page_index: u64) -> error::Result<&[u8]>;
+}
+
+/// Positional reads over a source fetched a page at a time from a [PageProvider](trait.PageProvider.html), as though
+/// it were a byte slice
+///
+/// It is an [ErasedPread](trait.ErasedPread.html), so it reads values with [pread_with](#method.pread_with) and
+/// [gread_with](#method.gread_with): a value's bytes are gathered into a buffer on the stack from however many pages
+/// it spans, and parsed from there, so values can't borrow from the source. `read_bytes` copies any range into a
+/// buffer of the caller's.
+///
+/// A range can only be borrowed as it is when it lies within a single page: [pread_slice](#method.pread_slice) is an
+/// `Error::Invalid` at the boundary otherwise, and [pread_cow](#method.pread_cow) copies it instead. Offsets past the
+/// source's length are an `Error::BadOffset`, and values running past it an `Error::TooBig`, as for a slice.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ErasedPread, PageProvider, PagedSource, BE};
+///
+/// /// A disk image in 4 byte sectors
+/// struct Sectors(Vec<[u8; 4]>);
+///
+/// impl PageProvider for Sectors {
+///     fn page_size(&self) -> usize { 4 }
+///     fn total_len(&self) -> u64 { 4 * self.0.len() as u64 }
+///     fn page(&self, index: u64) -> scroll::Result<&[u8]> {
+///         self.0.get(index as usize).map(|sector| &sector[..]).ok_or(scroll::Error::BadOffset(index as usize))
+///     }
+/// }
+///
+/// let source = PagedSource::new(Sectors(vec![[0xde, 0xad, 0xbe, 0xef], [0x01, 0x02, 0x03, 0x04]])).unwrap();
+/// // straddles the sectors
+/// assert_eq!(source.pread_with::<u32, _>(2, BE).unwrap(), 0xbeef_0102);
+/// let mut buffer = [0u8; 5];
+/// source.read_bytes(1, &mut buffer).unwrap();
+/// assert_eq!(buffer, [0xad, 0xbe, 0xef, 0x01, 0x02]);
+/// assert_eq!(source.pread_slice(4, 3).unwrap(), [0x01, 0x02, 0x03]);
+/// assert!(source.pread_slice(3, 2).is_err());
+/// assert_eq!(source.pread_cow(3, 2).unwrap(), &[0xef, 0x01][..]);
+/// ```
+#[derive(Debug)]
+pub struct PagedSource<P> {
+    provider: P,
+    page_size: usize,
+    len: usize,
+}
+
+impl<P: PageProvider> PagedSource<P> {
+    /// Reads from `provider`, taking its page size and length now; a page size of 0 is an `Error::BadInput`, as is a
+    /// length whose offsets don't fit in a `usize`
+    pub fn new(provider: P) -> error::Result<Self> {
+        let page_size = provider.page_size();
+        if page_size == 0 {
+            return Err(error::Error::BadInput { size: 0, msg: "page size must not be 0" });
+        }
+        let len = usize::try_from(provider.total_len()).map_err(|_| error::Error::BadInput {
+            size: usize::MAX,
+            msg: "source is too long for its offsets to fit in a usize",
+        })?;
+        Ok(PagedSource { provider, page_size, len })
+    }
+    /// The bytes of the source which page `index` holds, fetched from the provider
+    fn page(&self, index: usize) -> error::Result<&[u8]> {
+        let start = index * self.page_size;
+        let len = self.page_size.min(self.len - start);
+        match self.provider.page(index as u64)? {
+            page if page.len() >= len => Ok(&page[..len]),
+            _ => Err(error::Error::Invalid { offset: start, msg: "page is shorter than the page size" }),
+        }
+    }
+    /// Checks that `size` bytes at `offset` are in the source
+    fn check(&self, offset: usize, size: usize) -> error::Result<()> {
+        let len = self.len.checked_sub(offset).ok_or(error::Error::BadOffset(offset))?;
+        if size > len {
+            return Err(error::Error::TooBig { size, len });
+        }
+        Ok(())
+    }
+    /// Reads a value at `offset` with the given `ctx`
+    #[inline]
+    pub fn pread_with<N, Ctx>(&self, offset: usize, ctx: Ctx) -> error::Result<N>
+        where N: for<'b> TryFromCtx<'b, Ctx, Error = error::Error> + SizeWith<Ctx>,
+              Ctx: Copy,
+    {
+        (self as &dyn ErasedPread).pread_with(offset, ctx)
+    }
+    /// Reads a value at `offset` with the given `ctx`, and advances the offset past it
+    #[inline]
+    pub fn gread_with<N, Ctx>(&self, offset: &mut usize, ctx: Ctx) -> error::Result<N>
+        where N: for<'b> TryFromCtx<'b, Ctx, Error = error::Error> + SizeWith<Ctx>,
+              Ctx: Copy,
+    {
+        (self as &dyn ErasedPread).gread_with(offset, ctx)
+    }
+    /// Borrows the `len` bytes at `offset` from the page holding them; a range spanning pages is an `Error::Invalid` at
+    /// the boundary it crosses
+    pub fn pread_slice(&self, offset: usize, len: usize) -> error::Result<&[u8]> {
+        self.check(offset, len)?;
+        if len == 0 {
+            return Ok(&[]);
+        }
+        let within = offset % self.page_size;
+        if len > self.page_size - within {
+            return Err(error::Error::Invalid { offset: offset - within + self.page_size, msg: "range spans a page boundary" });
+        }
+        let page = self.page(offset / self.page_size)?;
+        Ok(&page[within..within + len])
+    }
+    /// The `len` bytes at `offset`, borrowed from their page if they lie within one, and copied out of the pages they
+    /// span otherwise
+    #[cfg(feature = "alloc")]
+    pub fn pread_cow(&self, offset: usize, len: usize) -> error::Result<Cow<'_, [u8]>> {
+        self.check(offset, len)?;
+        if offset % self.page_size + len <= self.page_size {
+            return self.pread_slice(offset, len).map(Cow::Borrowed);
+        }
+        let mut bytes = crate::buffer::try_vec(len)?;
+        bytes.resize(len, 0);
+        self.read_bytes(offset, &mut bytes)?;
+        Ok(Cow::Owned(bytes))
+    }
+}
+
+impl<P> PagedSource<P> {
+    /// The source's length, as the provider gave it when the source was created
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Whether the source is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Unwraps the provider
+    pub fn into_inner(self) -> P {
+        self.provider
+    }
+}
+
+impl<P: PageProvider> ErasedPread for PagedSource<P> {
+    fn read_bytes(&self, offset: usize, out: &mut [u8]) -> error::Result<()> {
+        self.check(offset, out.len())?;
+        let mut done = 0;
+        while done < out.len() {
+            let at = offset + done;
+            let page = self.page(at / self.page_size)?;
+            let page = &page[at % self.page_size..];
+            let len = page.len().min(out.len() - done);
+            out[done..done + len].copy_from_slice(&page[..len]);
+            done += len;
+        }
+        Ok(())
+    }
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+}
@@ -0,0 +1,194 @@
+use core::mem::{align_of, size_of};
+
+use bytemuck::{Pod, PodCastError};
+
+use crate::error;
+use crate::pread::PreadExt;
+
+/// Reads [bytemuck::Pod](https://docs.rs/bytemuck/*/bytemuck/trait.Pod.html) types straight out of a byte buffer; requires the `bytemuck` feature
+///
+/// Any bit pattern is a valid `Pod`, so the bytes are simply copied into a `T`, whatever the alignment of the offset.
+/// No byte swapping happens, so the user is responsible for the `Pod` type matching the endianness of the data.
+///
+/// # Example
+/// ```rust
+/// use scroll::PreadPod;
+///
+/// let bytes = [0xff, 0x01, 0x00, 0x02, 0x00];
+/// let pair: [u16; 2] = bytes.pread_pod(1).unwrap();
+/// #[cfg(target_endian = "little")]
+/// assert_eq!(pair, [1, 2]);
+/// ```
+pub trait PreadPod {
+    /// Reads a `T` from `self` at `offset`
+    fn pread_pod<T: Pod>(&self, offset: usize) -> error::Result<T>;
+    /// Reads a `T` from `self` at `offset`, and updates the offset
+    fn gread_pod<T: Pod>(&self, offset: &mut usize) -> error::Result<T> {
+        let t = self.pread_pod(*offset)?;
+        *offset += size_of::<T>();
+        Ok(t)
+    }
+    /// Borrows `count` `T`s from `self` at `offset`, without copying them
+    ///
+    /// Unlike `pread_pod`, the bytes at `offset` must be suitably aligned for a `T`, or an `Error::BadInput` is
+    /// returned; note this is the alignment of the address in memory, and not merely of `offset`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use scroll::PreadPod;
+    ///
+    /// let words = [1u32, 2, 3];
+    /// let bytes: &[u8] = bytemuck::cast_slice(&words);
+    /// assert_eq!(bytes.pread_slice_aligned::<u32>(4, 2).unwrap(), &[2, 3]);
+    /// assert!(bytes.pread_slice_aligned::<u32>(2, 1).is_err());
+    /// ```
+    fn pread_slice_aligned<T: Pod>(&self, offset: usize, count: usize) -> error::Result<&[T]>;
+    /// Reads a `T` from `self` at `offset`, which must be suitably aligned for a `T`, as for `pread_slice_aligned`
+    ///
+    /// This is for layouts whose alignment is part of their contract, e.g. a `#[repr(C, align(8))]` header; a
+    /// misaligned offset is an `Error::BadInput` rather than a panic or a silent unaligned copy.
+    ///
+    /// # Example
+    /// ```rust
+    /// use scroll::PreadPod;
+    ///
+    /// let words = [1u32, 2];
+    /// let bytes: &[u8] = bytemuck::cast_slice(&words);
+    /// assert_eq!(bytes.pread_into_struct_at_aligned::<u32>(4).unwrap(), 2);
+    /// assert!(bytes.pread_into_struct_at_aligned::<u32>(1).is_err());
+    /// ```
+    fn pread_into_struct_at_aligned<T: Pod>(&self, offset: usize) -> error::Result<T>;
+}
+
+impl<B: AsRef<[u8]> + ?Sized> PreadPod for B {
+    #[inline]
+    fn pread_pod<T: Pod>(&self, offset: usize) -> error::Result<T> {
+        let bytes = self.as_ref();
+        if offset >= bytes.len() {
+            return Err(error::Error::BadOffset(offset));
+        }
+        let bytes = &bytes[offset..];
+        let size = size_of::<T>();
+        if size > bytes.len() {
+            return Err(error::Error::TooBig { size, len: bytes.len() });
+        }
+        Ok(bytemuck::pod_read_unaligned(&bytes[..size]))
+    }
+
+    fn pread_slice_aligned<T: Pod>(&self, offset: usize, count: usize) -> error::Result<&[T]> {
+        let size = self.pread_checked_size_product(offset, count, size_of::<T>())?;
+        let bytes = &self.as_ref()[offset..];
+        if bytes.as_ptr().align_offset(align_of::<T>()) != 0 {
+            return Err(error::Error::BadInput { size: offset, msg: "offset is misaligned for the type" });
+        }
+        Ok(bytemuck::cast_slice(&bytes[..size]))
+    }
+
+    fn pread_into_struct_at_aligned<T: Pod>(&self, offset: usize) -> error::Result<T> {
+        let bytes = self.as_ref();
+        if offset >= bytes.len() {
+            return Err(error::Error::BadOffset(offset));
+        }
+        let bytes = &bytes[offset..];
+        let size = size_of::<T>();
+        if size > bytes.len() {
+            return Err(error::Error::TooBig { size, len: bytes.len() });
+        }
+        match bytemuck::try_from_bytes(&bytes[..size]) {
+            Ok(t) => Ok(*t),
+            Err(PodCastError::TargetAlignmentGreaterAndInputNotAligned) => {
+                Err(error::Error::BadInput { size: offset, msg: "offset is misaligned for the type" })
+            },
+            Err(_) => Err(error::Error::BadInput { size, msg: "bytes cannot be cast to the type" }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytemuck::{Pod, Zeroable};
+    use super::PreadPod;
+    use crate::Error;
+
+    #[repr(C)]
+    #[derive(Debug, Copy, Clone, PartialEq, Pod, Zeroable)]
+    struct Header {
+        magic: u32,
+        version: u16,
+        flags: u16,
+        size: u64,
+    }
+
+    #[test]
+    #[cfg(target_endian = "little")]
+    fn pread_pod() {
+        let mut bytes = vec![0xaa];
+        bytes.extend_from_slice(&[0x7f, b'E', b'L', b'F', 2, 0, 0x10, 0x80, 0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01]);
+        // an unaligned offset is fine
+        let header: Header = bytes.pread_pod(1).unwrap();
+        assert_eq!(header, Header { magic: 0x464c457f, version: 2, flags: 0x8010, size: 0x0102030405060708 });
+
+        let offset = &mut 1;
+        let magic: [u8; 4] = bytes.gread_pod(offset).unwrap();
+        assert_eq!(&magic, b"\x7fELF");
+        let version: u16 = bytes.gread_pod(offset).unwrap();
+        assert_eq!(version, 2);
+        assert_eq!(*offset, 7);
+    }
+
+    #[test]
+    fn pread_pod_bounds() {
+        let bytes = [0u8; 16];
+        assert!(bytes.pread_pod::<Header>(0).is_ok());
+        assert!(bytes.pread_pod::<Header>(1).is_err());
+        assert!(bytes.pread_pod::<u8>(16).is_err());
+        let offset = &mut 12;
+        assert!(bytes.gread_pod::<u64>(offset).is_err());
+        assert_eq!(*offset, 12);
+    }
+
+    #[test]
+    fn pread_slice_aligned() {
+        #[repr(C, align(16))]
+        struct Aligned([u8; 32]);
+
+        let mut buffer = Aligned([0; 32]);
+        for (i, b) in buffer.0.iter_mut().enumerate() {
+            *b = i as u8;
+        }
+        let bytes = &buffer.0[..];
+        let words: &[u32] = bytes.pread_slice_aligned(16, 4).unwrap();
+        for (i, word) in words.iter().enumerate() {
+            let b = 16 + 4 * i as u32;
+            assert_eq!(*word, u32::from_ne_bytes([b as u8, b as u8 + 1, b as u8 + 2, b as u8 + 3]));
+        }
+        assert_eq!(bytes.pread_slice_aligned::<u32>(32, 0).unwrap().len(), 0);
+        assert!(matches!(bytes.pread_slice_aligned::<u32>(6, 1), Err(Error::BadInput { .. })));
+        assert!(matches!(bytes.pread_slice_aligned::<u32>(20, 4), Err(Error::TooBig { .. })));
+    }
+
+    #[test]
+    fn pread_into_struct_at_aligned() {
+        #[repr(C, align(4))]
+        #[derive(Debug, Copy, Clone, PartialEq, Pod, Zeroable)]
+        struct Entry {
+            id: u16,
+            kind: u8,
+            flags: u8,
+        }
+        #[repr(C, align(16))]
+        struct Aligned([u8; 16]);
+
+        let mut buffer = Aligned([0; 16]);
+        buffer.0[4..8].copy_from_slice(&[0x01, 0x00, 0x02, 0x03]);
+        let bytes = &buffer.0[..];
+        let entry: Entry = bytes.pread_into_struct_at_aligned(4).unwrap();
+        assert_eq!(entry, Entry { id: u16::from_ne_bytes([0x01, 0x00]), kind: 2, flags: 3 });
+        assert!(bytes.pread_into_struct_at_aligned::<Entry>(12).is_ok());
+        for offset in [1, 2, 3, 5, 6, 7] {
+            assert!(matches!(bytes.pread_into_struct_at_aligned::<Entry>(offset), Err(Error::BadInput { size, .. }) if size == offset));
+        }
+        assert!(matches!(bytes.pread_into_struct_at_aligned::<Entry>(14), Err(Error::TooBig { size: 4, len: 2 })));
+        assert!(matches!(bytes.pread_into_struct_at_aligned::<Entry>(16), Err(Error::BadOffset(16))));
+    }
+}
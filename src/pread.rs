@@ -1,8 +1,19 @@
+#[cfg(feature = "alloc")]
+use core::borrow::Borrow;
+use core::convert::TryFrom;
+use core::fmt::Debug;
+use core::iter::{FromIterator, FusedIterator};
+use core::marker::PhantomData;
 use core::result;
-use core::ops::{Index, RangeFrom};
+use core::ops::{Index, Range, RangeBounds, RangeFrom};
 
-use crate::ctx::{TryFromCtx, MeasureWith};
+use crate::ctx::{TryFromCtx, TryFromCtxMut, MeasureWith, SizeWith, Validate, DefaultCtx, Primitive, LineCtx};
+#[cfg(feature = "alloc")]
+use crate::ctx::StrCtx;
+use crate::endian::{Endian, BE, LE, NATIVE};
 use crate::error;
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use crate::prelude::*;
 
 /// A very generic, contextual pread interface in Rust. Allows completely parallelized reads, as `Self` is immutable
 ///
@@ -103,10 +114,44 @@ pub trait Pread<Ctx, E> : Index<usize> + Index<RangeFrom<usize>> + MeasureWith<C
     /// assert_eq!(dead, 0xdeadu16);
     fn pread_with<'a, N: TryFromCtx<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>>(&'a self, offset: usize, ctx: Ctx) -> result::Result<N, E> where <Self as Index<RangeFrom<usize>>>::Output: 'a {
         let len = self.measure_with(&ctx);
-        if offset >= len {
+        if offset > len {
             return Err(error::Error::BadOffset(offset).into())
         }
-        N::try_from_ctx(&self[offset..], ctx).and_then(|(n, _)| Ok(n))
+        N::try_from_ctx(&self[offset..], ctx).map(|(n, _)| n)
+    }
+    /// Reads a value from `self` at `offset`, with the context `ctx_fn` returns for that offset, for formats whose
+    /// context depends on where a value is
+    /// # Example
+    /// ```rust
+    /// use scroll::{Pread, BE, LE};
+    /// let bytes: [u8; 3] = [0xef, 0xbe, 0xef];
+    /// let ctx_fn = |offset: usize| if offset & 1 == 0 { LE } else { BE };
+    /// assert_eq!(bytes.pread_with_ctx_fn::<u16, _>(0, ctx_fn).unwrap(), 0xbeef);
+    /// assert_eq!(bytes.pread_with_ctx_fn::<u16, _>(1, ctx_fn).unwrap(), 0xbeef);
+    /// ```
+    #[inline]
+    fn pread_with_ctx_fn<'a, N: TryFromCtx<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>, F: FnOnce(usize) -> Ctx>(&'a self, offset: usize, ctx_fn: F) -> result::Result<N, E> where <Self as Index<RangeFrom<usize>>>::Output: 'a {
+        self.pread_with(offset, ctx_fn(offset))
+    }
+    /// Reads a value from `self` at `offset` with the given `ctx`, or returns `None` if `offset` is at or past the end of
+    /// `self`; for trailing fields which only newer versions of a format have
+    ///
+    /// Only a field which is entirely absent is `None`: one which starts but is cut short, or fails to parse, is still an error.
+    /// # Example
+    /// ```rust
+    /// use scroll::{Pread, LE};
+    /// let v1 = [0x01, 0x00];
+    /// let v2 = [0x02, 0x00, 0xff, 0x00, 0x00, 0x00];
+    /// assert!(v1.pread_optional_at::<u32>(2, LE).is_none());
+    /// assert_eq!(v2.pread_optional_at::<u32>(2, LE).unwrap().unwrap(), 0xff);
+    /// assert!(v2[..5].pread_optional_at::<u32>(2, LE).unwrap().is_err());
+    /// ```
+    #[inline]
+    fn pread_optional_at<'a, N: TryFromCtx<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>>(&'a self, offset: usize, ctx: Ctx) -> Option<result::Result<N, E>> where <Self as Index<RangeFrom<usize>>>::Output: 'a {
+        if offset >= self.measure_with(&ctx) {
+            return None;
+        }
+        Some(self.pread_with(offset, ctx))
     }
     #[inline]
     /// Reads a value from `self` at `offset` with a default `Ctx`. For the primitive numeric values, this will read at the machine's endianness. Updates the offset
@@ -142,14 +187,571 @@ pub trait Pread<Ctx, E> : Index<usize> + Index<RangeFrom<usize>> + MeasureWith<C
         //     Ok(n)
         // })
         let len = self.measure_with(&ctx);
-        if o >= len {
+        if o > len {
             return Err(error::Error::BadOffset(o).into())
         }
-        N::try_from_ctx(&self[o..], ctx).and_then(|(n, size)| {
+        N::try_from_ctx(&self[o..], ctx).map(|(n, size)| {
             *offset += size;
-            Ok(n)
+            n
         })
     }
+    /// Reads a value from `self` at `offset` as little endian; shorthand for `pread_with(offset, LE)`
+    /// # Example
+    /// ```rust
+    /// use scroll::Pread;
+    /// let bytes = [0xef, 0xbe, 0xad, 0xde];
+    /// assert_eq!(bytes.pread_le::<u32>(0).unwrap(), 0xdeadbeef);
+    /// ```
+    #[inline]
+    fn pread_le<'a, N: TryFromCtx<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>>(&'a self, offset: usize) -> result::Result<N, E> where Ctx: From<Endian>, <Self as Index<RangeFrom<usize>>>::Output: 'a {
+        self.pread_with(offset, Ctx::from(LE))
+    }
+    /// Reads a value from `self` at `offset` as big endian; shorthand for `pread_with(offset, BE)`
+    /// # Example
+    /// ```rust
+    /// use scroll::Pread;
+    /// let bytes = [0xde, 0xad, 0xbe, 0xef];
+    /// assert_eq!(bytes.pread_be::<u32>(0).unwrap(), 0xdeadbeef);
+    /// ```
+    #[inline]
+    fn pread_be<'a, N: TryFromCtx<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>>(&'a self, offset: usize) -> result::Result<N, E> where Ctx: From<Endian>, <Self as Index<RangeFrom<usize>>>::Output: 'a {
+        self.pread_with(offset, Ctx::from(BE))
+    }
+    /// Reads a value from `self` at `offset` as little endian, and updates the offset; shorthand for `gread_with(offset, LE)`
+    /// # Example
+    /// ```rust
+    /// use scroll::Pread;
+    /// let bytes = [0xad, 0xde, 0xef, 0xbe];
+    /// let offset = &mut 0;
+    /// assert_eq!(bytes.gread_le::<u16>(offset).unwrap(), 0xdead);
+    /// assert_eq!(bytes.gread_le::<u16>(offset).unwrap(), 0xbeef);
+    /// assert_eq!(*offset, 4);
+    /// ```
+    #[inline]
+    fn gread_le<'a, N: TryFromCtx<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>>(&'a self, offset: &mut usize) -> result::Result<N, E> where Ctx: From<Endian>, <Self as Index<RangeFrom<usize>>>::Output: 'a {
+        self.gread_with(offset, Ctx::from(LE))
+    }
+    /// Reads a value from `self` at `offset` as big endian, and updates the offset; shorthand for `gread_with(offset, BE)`
+    /// # Example
+    /// ```rust
+    /// use scroll::Pread;
+    /// let bytes = [0xde, 0xad, 0xbe, 0xef];
+    /// let offset = &mut 0;
+    /// assert_eq!(bytes.gread_be::<u16>(offset).unwrap(), 0xdead);
+    /// assert_eq!(bytes.gread_be::<u16>(offset).unwrap(), 0xbeef);
+    /// assert_eq!(*offset, 4);
+    /// ```
+    #[inline]
+    fn gread_be<'a, N: TryFromCtx<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>>(&'a self, offset: &mut usize) -> result::Result<N, E> where Ctx: From<Endian>, <Self as Index<RangeFrom<usize>>>::Output: 'a {
+        self.gread_with(offset, Ctx::from(BE))
+    }
+    /// Reads a value from `self` at `offset` with its [DefaultCtx](ctx/trait.DefaultCtx.html)
+    /// # Example
+    /// ```rust
+    /// use scroll::Pread;
+    /// let bytes = 0xdeadbeef_u32.to_ne_bytes();
+    /// assert_eq!(bytes.pread_into::<u32>(0).unwrap(), 0xdeadbeef);
+    /// ```
+    ///
+    /// Types without a default context still need one to be given:
+    /// ```compile_fail
+    /// use scroll::{ctx, Pread, Endian};
+    /// struct Flags(u8);
+    /// impl<'a> ctx::TryFromCtx<'a, Endian> for Flags {
+    ///     type Error = scroll::Error;
+    ///     fn try_from_ctx(src: &'a [u8], _: Endian) -> Result<(Self, usize), Self::Error> {
+    ///         Ok((Flags(src.pread(0)?), 1))
+    ///     }
+    /// }
+    /// let flags: Flags = [1u8].pread_into(0).unwrap();
+    /// ```
+    #[inline]
+    fn pread_into<'a, N>(&'a self, offset: usize) -> result::Result<N, E>
+        where N: DefaultCtx<Ctx = Ctx> + TryFromCtx<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>,
+              <Self as Index<RangeFrom<usize>>>::Output: 'a
+    {
+        self.pread_with(offset, N::default_ctx())
+    }
+    /// Reads a value from `self` at `offset` with its [DefaultCtx](ctx/trait.DefaultCtx.html), and updates the offset
+    /// # Example
+    /// ```rust
+    /// use scroll::Pread;
+    /// let bytes = [0x01u8, 0x02];
+    /// let offset = &mut 0;
+    /// assert_eq!(bytes.gread_into::<u8>(offset).unwrap(), 1);
+    /// assert_eq!(bytes.gread_into::<u8>(offset).unwrap(), 2);
+    /// assert_eq!(*offset, 2);
+    /// ```
+    #[inline]
+    fn gread_into<'a, N>(&'a self, offset: &mut usize) -> result::Result<N, E>
+        where N: DefaultCtx<Ctx = Ctx> + TryFromCtx<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>,
+              <Self as Index<RangeFrom<usize>>>::Output: 'a
+    {
+        self.gread_with(offset, N::default_ctx())
+    }
+    /// Reads an `N` from `self` at `offset` with the given `ctx`, then converts it to a `U` with `TryFrom`
+    ///
+    /// Either step failing is reported as the corresponding variant of a [CombinedError](enum.CombinedError.html).
+    /// # Example
+    /// ```rust
+    /// use std::convert::TryFrom;
+    /// use scroll::{CombinedError, Pread, LE};
+    /// #[derive(Debug, PartialEq)]
+    /// enum Class { Elf32, Elf64 }
+    /// impl TryFrom<u8> for Class {
+    ///     type Error = u8;
+    ///     fn try_from(n: u8) -> Result<Self, u8> {
+    ///         match n { 1 => Ok(Class::Elf32), 2 => Ok(Class::Elf64), n => Err(n) }
+    ///     }
+    /// }
+    /// let bytes = [0x02, 0x07];
+    /// assert_eq!(bytes.pread_try_into::<u8, Class>(0, LE).unwrap(), Class::Elf64);
+    /// assert!(matches!(bytes.pread_try_into::<u8, Class>(1, LE), Err(CombinedError::Convert(7))));
+    #[inline]
+    fn pread_try_into<'a, N, U>(&'a self, offset: usize, ctx: Ctx) -> result::Result<U, error::CombinedError<E, U::Error>>
+        where N: TryFromCtx<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>,
+              U: TryFrom<N>,
+              <Self as Index<RangeFrom<usize>>>::Output: 'a
+    {
+        self.gread_try_into(&mut { offset }, ctx)
+    }
+    /// Reads an `N` from `self` at `offset` with the given `ctx`, then converts it to a `U` with `TryFrom`, and updates the offset
+    ///
+    /// The offset is only updated if both steps succeed.
+    #[inline]
+    fn gread_try_into<'a, N, U>(&'a self, offset: &mut usize, ctx: Ctx) -> result::Result<U, error::CombinedError<E, U::Error>>
+        where N: TryFromCtx<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>,
+              U: TryFrom<N>,
+              <Self as Index<RangeFrom<usize>>>::Output: 'a
+    {
+        let mut o = *offset;
+        let n: N = self.gread_with(&mut o, ctx).map_err(error::CombinedError::Parse)?;
+        let u = U::try_from(n).map_err(error::CombinedError::Convert)?;
+        *offset = o;
+        Ok(u)
+    }
+    /// Reads a value from `self` at `offset` with the given `ctx`, then checks it with its [Validate](ctx/trait.Validate.html) implementation
+    ///
+    /// A failed check is an `Error::Invalid` carrying `offset`.
+    /// # Example
+    /// ```rust
+    /// use scroll::{ctx::Validate, Error, Pread, BE};
+    /// #[derive(Debug)]
+    /// struct Version(u16);
+    /// impl<'a> scroll::ctx::TryFromCtx<'a, scroll::Endian> for Version {
+    ///     type Error = Error;
+    ///     fn try_from_ctx(src: &'a [u8], endian: scroll::Endian) -> Result<(Self, usize), Error> {
+    ///         Ok((Version(src.pread_with(0, endian)?), 2))
+    ///     }
+    /// }
+    /// impl Validate<scroll::Endian> for Version {
+    ///     fn validate(&self, _: &scroll::Endian) -> Result<(), &'static str> {
+    ///         if self.0 <= 3 { Ok(()) } else { Err("unsupported version") }
+    ///     }
+    /// }
+    /// let bytes = [0x00, 0x02, 0x00, 0x07];
+    /// assert_eq!(bytes.pread_validated::<Version>(0, BE).unwrap().0, 2);
+    /// let err = bytes.pread_validated::<Version>(2, BE).unwrap_err();
+    /// assert_eq!(err.to_string(), "invalid value at offset 2: unsupported version");
+    #[inline]
+    fn pread_validated<'a, N>(&'a self, offset: usize, ctx: Ctx) -> result::Result<N, E>
+        where N: TryFromCtx<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E> + Validate<Ctx>,
+              <Self as Index<RangeFrom<usize>>>::Output: 'a
+    {
+        self.gread_validated(&mut { offset }, ctx)
+    }
+    /// Reads a value from `self` at `offset` with the given `ctx`, then checks it with its [Validate](ctx/trait.Validate.html) implementation,
+    /// and updates the offset
+    ///
+    /// A failed check is an `Error::Invalid` carrying the offset the value started at, which is left untouched.
+    #[inline]
+    fn gread_validated<'a, N>(&'a self, offset: &mut usize, ctx: Ctx) -> result::Result<N, E>
+        where N: TryFromCtx<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E> + Validate<Ctx>,
+              <Self as Index<RangeFrom<usize>>>::Output: 'a
+    {
+        let start = *offset;
+        let mut o = start;
+        let n: N = self.gread_with(&mut o, ctx)?;
+        n.validate(&ctx).map_err(|msg| error::Error::Invalid { offset: start, msg })?;
+        *offset = o;
+        Ok(n)
+    }
+    /// Reads a value from `self` at `offset` with the given `ctx`, along with the range of bytes it was parsed from
+    ///
+    /// The range is exactly what the value's `TryFromCtx` reported consuming, so it holds for variable length values too.
+    /// # Example
+    /// ```rust
+    /// use scroll::{Pread, Uleb128};
+    /// let bytes = [0xaa, 0xe5, 0x8e, 0x26, 0xbb];
+    /// let (n, span) = bytes.pread_spanned::<Uleb128>(1, ()).unwrap();
+    /// assert_eq!(u64::from(n), 624485);
+    /// assert_eq!(span, 1..4);
+    #[inline]
+    fn pread_spanned<'a, N: TryFromCtx<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>>(&'a self, offset: usize, ctx: Ctx) -> result::Result<(N, Range<usize>), E> where <Self as Index<RangeFrom<usize>>>::Output: 'a {
+        self.gread_spanned(&mut { offset }, ctx)
+    }
+    /// Reads a value from `self` at `offset` with the given `ctx`, along with the range of bytes it was parsed from, and updates the offset
+    /// # Example
+    /// ```rust
+    /// use scroll::{Pread, BE};
+    /// let bytes = [0xde, 0xad, 0xbe, 0xef];
+    /// let offset = &mut 1;
+    /// let (n, span) = bytes.gread_spanned::<u16>(offset, BE).unwrap();
+    /// assert_eq!(n, 0xadbe);
+    /// assert_eq!(span, 1..3);
+    /// assert_eq!(*offset, 3);
+    #[inline]
+    fn gread_spanned<'a, N: TryFromCtx<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>>(&'a self, offset: &mut usize, ctx: Ctx) -> result::Result<(N, Range<usize>), E> where <Self as Index<RangeFrom<usize>>>::Output: 'a {
+        let start = *offset;
+        let n = self.gread_with(offset, ctx)?;
+        Ok((n, start..*offset))
+    }
+    /// Reads a value from `self` at `offset` with the given `ctx` into `dst`, returning the number of bytes it consumed
+    ///
+    /// `dst` is only overwritten on success, so a value can be reused across parses, e.g. in a loop.
+    /// # Example
+    /// ```rust
+    /// use scroll::{Pread, LE};
+    /// let bytes = [0xef, 0xbe, 0xad, 0xde];
+    /// let mut n = 0u32;
+    /// assert_eq!(bytes.pread_into_existing(0, &mut n, LE).unwrap(), 4);
+    /// assert_eq!(n, 0xdeadbeef);
+    /// assert!(bytes.pread_into_existing(2, &mut n, LE).is_err());
+    /// assert_eq!(n, 0xdeadbeef);
+    #[inline]
+    fn pread_into_existing<'a, N: TryFromCtx<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>>(&'a self, offset: usize, dst: &mut N, ctx: Ctx) -> result::Result<usize, E> where <Self as Index<RangeFrom<usize>>>::Output: 'a {
+        let o = &mut { offset };
+        *dst = self.gread_with(o, ctx)?;
+        Ok(*o - offset)
+    }
+    /// Reads a value from `self` at `offset` with the given `ctx` into the existing `dst`, via
+    /// [TryFromCtxMut](ctx/trait.TryFromCtxMut.html), returning the number of bytes it consumed
+    ///
+    /// Unlike `pread_into_existing`, types implementing `TryFromCtxMut` themselves can reuse their allocations; how
+    /// much of `dst` is overwritten on failure is up to that impl.
+    /// # Example
+    /// ```rust
+    /// use scroll::{Pread, BE};
+    /// let bytes = [0xde, 0xad, 0xbe, 0xef];
+    /// let mut n = 0u16;
+    /// assert_eq!(bytes.pread_inout_struct(2, &mut n, BE).unwrap(), 2);
+    /// assert_eq!(n, 0xbeef);
+    #[inline]
+    fn pread_inout_struct<'a, N: TryFromCtxMut<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>>(&'a self, offset: usize, dst: &mut N, ctx: Ctx) -> result::Result<usize, E> where <Self as Index<RangeFrom<usize>>>::Output: 'a {
+        let len = self.measure_with(&ctx);
+        if offset > len {
+            return Err(error::Error::BadOffset(offset).into())
+        }
+        dst.try_update_from_ctx(&self[offset..], ctx)
+    }
+    /// Reads a value from `self` at `offset` with the given `ctx` into the existing `dst`, via
+    /// [TryFromCtxMut](ctx/trait.TryFromCtxMut.html), and updates the offset
+    /// # Example
+    /// ```rust
+    /// use scroll::{Pread, LE};
+    /// let bytes = [0xef, 0xbe, 0xad, 0xde];
+    /// let offset = &mut 0;
+    /// let mut n = 0u16;
+    /// bytes.gread_inout_struct(offset, &mut n, LE).unwrap();
+    /// assert_eq!(n, 0xbeef);
+    /// bytes.gread_inout_struct(offset, &mut n, LE).unwrap();
+    /// assert_eq!(n, 0xdead);
+    /// assert_eq!(*offset, 4);
+    #[inline]
+    fn gread_inout_struct<'a, N: TryFromCtxMut<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>>(&'a self, offset: &mut usize, dst: &mut N, ctx: Ctx) -> result::Result<usize, E> where <Self as Index<RangeFrom<usize>>>::Output: 'a {
+        let size = self.pread_inout_struct(*offset, dst, ctx)?;
+        *offset += size;
+        Ok(size)
+    }
+    /// Reads a value from `self` at `offset` with the given `ctx`, adding the number of bytes it consumed to `counter` on success.
+    /// # Example
+    /// ```rust
+    /// use scroll::{Pread, LE};
+    /// let bytes: [u8; 6] = [0xde, 0xad, 0xbe, 0xef, 0xca, 0xfe];
+    /// let mut counter = 0;
+    /// let _: u32 = bytes.pread_counting(0, LE, &mut counter).unwrap();
+    /// let _: u16 = bytes.pread_counting(4, LE, &mut counter).unwrap();
+    /// assert_eq!(counter, 6);
+    #[inline]
+    fn pread_counting<'a, N: TryFromCtx<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>>(&'a self, offset: usize, ctx: Ctx, counter: &mut usize) -> result::Result<N, E> where <Self as Index<RangeFrom<usize>>>::Output: 'a {
+        let len = self.measure_with(&ctx);
+        if offset > len {
+            return Err(error::Error::BadOffset(offset).into())
+        }
+        N::try_from_ctx(&self[offset..], ctx).map(|(n, size)| {
+            *counter += size;
+            n
+        })
+    }
+    /// Reads a value from `self` at `offset` with the given `ctx`, and updates the offset, adding the number of bytes it consumed to `counter` on success.
+    /// # Example
+    /// ```rust
+    /// use scroll::{Pread, BE};
+    /// let bytes: [u8; 3] = [0xde, 0xad, 0xbe];
+    /// let offset = &mut 0;
+    /// let mut counter = 0;
+    /// let dead: u16 = bytes.gread_counting(offset, BE, &mut counter).unwrap();
+    /// let be: u8 = bytes.gread_counting(offset, BE, &mut counter).unwrap();
+    /// assert_eq!((dead, be), (0xdead, 0xbe));
+    /// assert_eq!(counter, 3);
+    #[inline]
+    fn gread_counting<'a, N: TryFromCtx<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>>(&'a self, offset: &mut usize, ctx: Ctx, counter: &mut usize) -> result::Result<N, E> where <Self as Index<RangeFrom<usize>>>::Output: 'a {
+        let o = *offset;
+        let n = self.gread_with(offset, ctx)?;
+        *counter += *offset - o;
+        Ok(n)
+    }
+
+    /// Reads a value from `self` at `offset` with the given `ctx`, erroring if it falls outside of `range`
+    ///
+    /// With `alloc`, the error reports the value, the allowed range and the offset.
+    /// # Example
+    /// ```rust
+    /// use scroll::{Pread, LE};
+    /// let bytes: [u8; 4] = [0x02, 0x00, 0x05, 0x00];
+    /// let version: u16 = bytes.pread_bounded(0, 2..=4, LE).unwrap();
+    /// assert_eq!(version, 2);
+    /// assert!(bytes.pread_bounded::<u16, _>(2, 2..=4, LE).is_err());
+    #[inline]
+    fn pread_bounded<'a, N, R>(&'a self, offset: usize, range: R, ctx: Ctx) -> result::Result<N, E>
+        where N: TryFromCtx<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E> + PartialOrd + Debug,
+              R: RangeBounds<N> + Debug,
+              <Self as Index<RangeFrom<usize>>>::Output: 'a
+    {
+        let n = self.pread_with(offset, ctx)?;
+        check_bounds(n, &range, offset)
+    }
+    /// Reads a value from `self` at `offset` with the given `ctx`, erroring if it falls outside of `range`, and updates the offset.
+    /// The offset is left untouched if the value is out of range.
+    /// # Example
+    /// ```rust
+    /// use scroll::{Pread, BE};
+    /// let bytes: [u8; 3] = [0x00, 0x10, 0xff];
+    /// let offset = &mut 0;
+    /// let count: u16 = bytes.gread_bounded(offset, ..=0xff, BE).unwrap();
+    /// assert_eq!(count, 0x10);
+    /// assert!(bytes.gread_bounded::<u8, _>(offset, 1..0x80, BE).is_err());
+    /// assert_eq!(*offset, 2);
+    #[inline]
+    fn gread_bounded<'a, N, R>(&'a self, offset: &mut usize, range: R, ctx: Ctx) -> result::Result<N, E>
+        where N: TryFromCtx<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E> + PartialOrd + Debug,
+              R: RangeBounds<N> + Debug,
+              <Self as Index<RangeFrom<usize>>>::Output: 'a
+    {
+        let o = *offset;
+        let mut end = o;
+        let n = self.gread_with(&mut end, ctx)?;
+        let n = check_bounds(n, &range, o)?;
+        *offset = end;
+        Ok(n)
+    }
+    /// Speculatively reads a value from `self` at `offset` with the given `ctx`, keeping it only if `accept` returns true.
+    /// The offset is updated when the value is accepted; otherwise it is rolled back and `Ok(None)` is returned.
+    /// # Example
+    /// ```rust
+    /// use scroll::{Pread, LE};
+    /// let bytes = 50000u32.to_le_bytes();
+    /// let offset = &mut 0;
+    /// assert_eq!(bytes.gread_with_rollback::<u32, _>(offset, LE, |n| *n < 1000).unwrap(), None);
+    /// assert_eq!(*offset, 0);
+    /// assert_eq!(bytes.gread_with_rollback::<u16, _>(offset, LE, |n| *n < 1000).unwrap(), None);
+    /// assert_eq!(bytes.gread_with_rollback::<u8, _>(offset, LE, |n| *n == 0x50).unwrap(), Some(0x50));
+    /// assert_eq!(*offset, 1);
+    /// ```
+    #[inline]
+    fn gread_with_rollback<'a, N, F>(&'a self, offset: &mut usize, ctx: Ctx, accept: F) -> result::Result<Option<N>, E>
+        where N: TryFromCtx<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>,
+              F: FnOnce(&N) -> bool,
+              <Self as Index<RangeFrom<usize>>>::Output: 'a
+    {
+        let mut end = *offset;
+        let n = self.gread_with(&mut end, ctx)?;
+        if !accept(&n) {
+            return Ok(None);
+        }
+        *offset = end;
+        Ok(Some(n))
+    }
+    /// Reads `count` values one after another from `self`, starting at `start`, with the given `ctx`, and collects them
+    /// into any `FromIterator` container; the first value which fails to read is the error
+    /// # Example
+    /// ```rust
+    /// use std::collections::BTreeSet;
+    /// use scroll::{Pread, BE};
+    /// let bytes = [0x00, 0x03, 0x00, 0x01, 0x00, 0x03];
+    /// let set: BTreeSet<u16> = bytes.pread_collect(0, 3, BE).unwrap();
+    /// assert_eq!(set.into_iter().collect::<Vec<_>>(), [1, 3]);
+    /// assert!(bytes.pread_collect::<u16, Vec<_>>(2, 3, BE).is_err());
+    /// ```
+    #[inline]
+    fn pread_collect<'a, N, C>(&'a self, start: usize, count: usize, ctx: Ctx) -> result::Result<C, E>
+        where N: TryFromCtx<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>,
+              C: FromIterator<N>,
+              <Self as Index<RangeFrom<usize>>>::Output: 'a
+    {
+        let mut offset = start;
+        (0..count).map(|_| self.gread_with(&mut offset, ctx)).collect()
+    }
+
+    /// Reads a value from `self` at `offset` with the given `ctx`, and errors unless it equals `expected`
+    ///
+    /// For required constant fields such as versions and reserved zeros; with `alloc`, the error reports both values and the offset.
+    /// # Example
+    /// ```rust
+    /// use scroll::{Pread, BE};
+    /// let bytes: [u8; 2] = [0x00, 0x01];
+    /// assert!(bytes.pread_assert_eq(0, 1u16, BE).is_ok());
+    /// assert!(bytes.pread_assert_eq(0, 2u16, BE).is_err());
+    #[inline]
+    fn pread_assert_eq<'a, N>(&'a self, offset: usize, expected: N, ctx: Ctx) -> result::Result<(), E>
+        where N: TryFromCtx<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E> + PartialEq + Debug,
+              <Self as Index<RangeFrom<usize>>>::Output: 'a
+    {
+        let n: N = self.pread_with(offset, ctx)?;
+        if n == expected {
+            Ok(())
+        } else {
+            Err(not_equal(&n, &expected, offset).into())
+        }
+    }
+    /// Reads a value from `self` at `offset` with the given `ctx`, errors unless it equals `expected`, and updates the offset.
+    /// The offset is left untouched if the value is not the expected one.
+    /// # Example
+    /// ```rust
+    /// use scroll::{Pread, LE};
+    /// let bytes: [u8; 4] = [0x7f, b'E', b'L', b'F'];
+    /// let offset = &mut 0;
+    /// bytes.gread_assert_eq(offset, 0x7fu8, LE).unwrap();
+    /// bytes.gread_assert_eq(offset, &b"ELF"[..], 3).unwrap();
+    /// assert_eq!(*offset, 4);
+    #[inline]
+    fn gread_assert_eq<'a, N>(&'a self, offset: &mut usize, expected: N, ctx: Ctx) -> result::Result<(), E>
+        where N: TryFromCtx<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E> + PartialEq + Debug,
+              <Self as Index<RangeFrom<usize>>>::Output: 'a
+    {
+        let o = *offset;
+        let mut end = o;
+        let n: N = self.gread_with(&mut end, ctx)?;
+        if n == expected {
+            *offset = end;
+            Ok(())
+        } else {
+            Err(not_equal(&n, &expected, o).into())
+        }
+    }
+    /// Repeatedly reads `N`s from `self` starting at `offset` with the given `ctx` until the end of `self`, and updates the offset
+    ///
+    /// Any error is returned and leaves the offset untouched; so does an element which consumes no bytes. A last element which
+    /// runs out of bytes, whether it is the first or has fewer bytes left than the element before it took, is taken to be cut
+    /// short, and is an `Error::BadOffset` at its offset; any other error is returned as it is.
+    /// # Example
+    /// ```rust
+    /// use scroll::{Pread, BE};
+    /// let bytes = [0x00, 0x01, 0x00, 0x02, 0x00, 0x03];
+    /// let offset = &mut 2;
+    /// let words: Vec<u16> = bytes.gread_into_vec_until_eof(offset, BE).unwrap();
+    /// assert_eq!(words, [2, 3]);
+    /// assert_eq!(*offset, 6);
+    /// assert!(matches!(bytes.gread_into_vec_until_eof::<u32>(&mut 0, BE), Err(scroll::Error::BadOffset(4))));
+    #[cfg(feature = "alloc")]
+    fn gread_into_vec_until_eof<'a, N>(&'a self, offset: &mut usize, ctx: Ctx) -> result::Result<Vec<N>, E>
+        where N: TryFromCtx<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>,
+              <Self as Index<RangeFrom<usize>>>::Output: 'a,
+              E: Borrow<error::Error>,
+    {
+        let len = self.measure_with(&ctx);
+        let mut o = *offset;
+        let mut elements = Vec::new();
+        let mut last_size = 0;
+        while o < len {
+            let start = o;
+            match self.gread_with(&mut o, ctx) {
+                Ok(element) => elements.push(element),
+                Err(ref err) if (last_size == 0 || len - start < last_size) && is_truncated(err.borrow()) => {
+                    return Err(error::Error::BadOffset(start).into())
+                },
+                Err(err) => return Err(err),
+            }
+            if o == start {
+                return Err(error::Error::BadInput { size: 0, msg: "element consumed no bytes" }.into());
+            }
+            last_size = o - start;
+        }
+        *offset = o;
+        Ok(elements)
+    }
+    /// Reads `N`s from `self` starting at `offset` until one matches the terminator of `ctx`, and updates the offset past the terminator
+    ///
+    /// Running out of `self` before the terminator is an `Error::Incomplete`, and reading more than `ctx.max` elements before it an
+    /// `Error::BadInput`; either way, or on any other error, the offset is left untouched. See
+    /// [TerminatedIter](ctx/struct.TerminatedIter.html) for reading the elements lazily.
+    /// # Example
+    /// ```rust
+    /// use scroll::{ctx::TerminatedCtx, Pread, LE};
+    /// // two (address, size) entries, ended by an all-zero one
+    /// let bytes = [0x10, 0x00, 0x04, 0x00, 0x20, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00];
+    /// let offset = &mut 0;
+    /// let entries: Vec<u32> = bytes.gread_terminated(offset, TerminatedCtx::new(LE, |e: &u32| *e == 0, 64)).unwrap();
+    /// assert_eq!(entries, [0x0004_0010, 0x0008_0020]);
+    /// assert_eq!(*offset, 12);
+    /// // the terminator can be kept too
+    /// let ctx = TerminatedCtx { keep_terminator: true, ..TerminatedCtx::new(LE, |e: &u32| *e == 0, 64) };
+    /// assert_eq!(bytes.gread_terminated::<u32, _>(&mut 0, ctx).unwrap().len(), 3);
+    #[cfg(feature = "alloc")]
+    fn gread_terminated<'a, N, F>(&'a self, offset: &mut usize, ctx: crate::ctx::TerminatedCtx<Ctx, F>) -> result::Result<Vec<N>, E>
+        where N: TryFromCtx<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>,
+              F: Fn(&N) -> bool,
+              <Self as Index<RangeFrom<usize>>>::Output: 'a
+    {
+        let mut o = *offset;
+        let mut elements = Vec::new();
+        loop {
+            let n: N = self.gread_with(&mut o, ctx.ctx)?;
+            if (ctx.is_terminal)(&n) {
+                if ctx.keep_terminator {
+                    elements.push(n);
+                }
+                break;
+            }
+            if elements.len() == ctx.max {
+                return Err(crate::ctx::too_many_elements(ctx.max).into());
+            }
+            elements.push(n);
+        }
+        *offset = o;
+        Ok(elements)
+    }
+    /// Reads the bits of the [bitflags](https://docs.rs/bitflags) type `F` from `self` at `offset` with the given `ctx`, and updates the offset;
+    /// requires the `bitflags` feature
+    ///
+    /// Bits which don't correspond to any of `F`'s flags are an `Error::BadInput`, and leave the offset untouched.
+    /// # Example
+    /// ```rust
+    /// use scroll::{Pread, BE};
+    /// bitflags::bitflags! {
+    ///     #[derive(Debug, PartialEq, Eq)]
+    ///     struct Mode: u16 {
+    ///         const READ = 0x100;
+    ///         const WRITE = 0x080;
+    ///     }
+    /// }
+    /// let bytes = [0x01, 0x80, 0x02, 0x00];
+    /// let offset = &mut 0;
+    /// assert_eq!(bytes.gread_into_bitflags::<Mode>(offset, BE).unwrap(), Mode::READ | Mode::WRITE);
+    /// assert!(bytes.gread_into_bitflags::<Mode>(offset, BE).is_err());
+    /// assert_eq!(*offset, 2);
+    #[cfg(feature = "bitflags")]
+    fn gread_into_bitflags<'a, F>(&'a self, offset: &mut usize, ctx: Ctx) -> result::Result<F, E>
+        where F: bitflags::Flags,
+              F::Bits: TryFromCtx<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>,
+              <Self as Index<RangeFrom<usize>>>::Output: 'a
+    {
+        let mut o = *offset;
+        let bits: F::Bits = self.gread_with(&mut o, ctx)?;
+        let flags = F::from_bits(bits).ok_or(error::Error::BadInput { size: o - *offset, msg: "unknown flag bits" })?;
+        *offset = o;
+        Ok(flags)
+    }
 
     /// Trys to write `inout.len()` `N`s into `inout` from `Self` starting at `offset`, using the default context for `N`, and updates the offset.
     /// # Example
@@ -195,9 +797,749 @@ pub trait Pread<Ctx, E> : Index<usize> + Index<RangeFrom<usize>> + MeasureWith<C
         }
         Ok(())
     }
+
+    /// Trys to write `inout.len()` `N`s into `inout` from `Self` starting at `offset`, reading them as big endian, and updates the offset.
+    /// # Example
+    /// ```rust
+    /// use scroll::Pread;
+    /// let mut words = [0u16; 2];
+    /// let offset = &mut 0;
+    /// let bytes: [u8; 4] = [0xde, 0xad, 0xbe, 0xef];
+    /// bytes.gread_inout_be(offset, &mut words).unwrap();
+    /// assert_eq!(words, [0xdead, 0xbeef]);
+    /// assert_eq!(*offset, 4);
+    #[inline]
+    fn gread_inout_be<'a, N>(&'a self, offset: &mut usize, inout: &mut [N]) -> result::Result<(), E>
+        where
+        N: TryFromCtx<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>,
+    Ctx: From<Endian>,
+    <Self as Index<RangeFrom<usize>>>::Output: 'a
+    {
+        self.gread_inout_with(offset, inout, Ctx::from(BE))
+    }
+
+    /// Trys to write `inout.len()` `N`s into `inout` from `Self` starting at `offset`, reading them as little endian, and updates the offset.
+    /// # Example
+    /// ```rust
+    /// use scroll::Pread;
+    /// let mut words = [0u16; 2];
+    /// let offset = &mut 0;
+    /// let bytes: [u8; 4] = [0xad, 0xde, 0xef, 0xbe];
+    /// bytes.gread_inout_le(offset, &mut words).unwrap();
+    /// assert_eq!(words, [0xdead, 0xbeef]);
+    /// assert_eq!(*offset, 4);
+    #[inline]
+    fn gread_inout_le<'a, N>(&'a self, offset: &mut usize, inout: &mut [N]) -> result::Result<(), E>
+        where
+        N: TryFromCtx<'a, Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>,
+    Ctx: From<Endian>,
+    <Self as Index<RangeFrom<usize>>>::Output: 'a
+    {
+        self.gread_inout_with(offset, inout, Ctx::from(LE))
+    }
 }
 
 impl<Ctx: Copy,
      E: From<error::Error>,
      R: ?Sized + Index<usize> + Index<RangeFrom<usize>> + MeasureWith<Ctx>>
     Pread<Ctx, E> for R {}
+
+/// Conveniences for sources which are plain bytes, complementing [Pread](trait.Pread.html)
+///
+/// These methods don't take a parsing context, so unlike the `Pread` methods they don't need one inferred;
+/// they are implemented for anything which is `AsRef<[u8]>`.
+pub trait PreadExt {
+    /// Returns everything from `offset` to the end of `self`, and advances the offset to the end
+    ///
+    /// An offset already at the end yields an empty slice; an offset past the end is an error.
+    /// # Example
+    /// ```rust
+    /// use scroll::{Pread, PreadExt, BE};
+    /// let bytes = [0x00, 0x02, 0xca, 0xfe];
+    /// let offset = &mut 0;
+    /// let kind: u16 = bytes.gread_with(offset, BE).unwrap();
+    /// assert_eq!(bytes.gread_remaining(offset).unwrap(), &[0xca, 0xfe]);
+    /// assert_eq!(*offset, 4);
+    /// assert!(bytes.gread_remaining(offset).unwrap().is_empty());
+    /// ```
+    fn gread_remaining(&self, offset: &mut usize) -> error::Result<&[u8]>;
+    /// Returns everything from `offset` to the end of `self`, without an offset to advance
+    ///
+    /// As with [gread_remaining](#tymethod.gread_remaining), an offset at the end yields an empty slice, and one past it is an error.
+    /// # Example
+    /// ```rust
+    /// use scroll::PreadExt;
+    /// let bytes = [0x00, 0x02, 0xca, 0xfe];
+    /// assert_eq!(bytes.pread_remaining(2).unwrap(), &[0xca, 0xfe]);
+    /// assert!(bytes.pread_remaining(4).unwrap().is_empty());
+    /// assert!(bytes.pread_remaining(5).is_err());
+    /// ```
+    fn pread_remaining(&self, offset: usize) -> error::Result<&[u8]>;
+    /// Returns everything from `offset` up to, but not including, `end`, and advances the offset to `end`
+    ///
+    /// Useful when "the rest" is bounded by a chunk boundary rather than the end of `self`.
+    /// # Example
+    /// ```rust
+    /// use scroll::PreadExt;
+    /// let bytes = [1, 2, 3, 4, 5];
+    /// let offset = &mut 1;
+    /// assert_eq!(bytes.gread_remaining_until(offset, 3).unwrap(), &[2, 3]);
+    /// assert_eq!(*offset, 3);
+    /// assert!(bytes.gread_remaining_until(offset, 6).is_err());
+    /// ```
+    fn gread_remaining_until(&self, offset: &mut usize, end: usize) -> error::Result<&[u8]>;
+    /// Returns the `len` bytes at `offset` as a window of their own, erroring if they don't all lie within `self`
+    ///
+    /// The window is a plain, borrowed `&[u8]`, so it can be read with `Pread` like any other source: its offsets start at zero,
+    /// and reading past its end fails even where `self` continues. Windows can be nested arbitrarily.
+    /// # Example
+    /// ```rust
+    /// use scroll::{Pread, PreadExt, LE};
+    /// let bytes = [0x02, 0x00, 0xaa, 0xbb, 0xcc, 0xdd];
+    /// let chunk = bytes.pread_window(2, 2).unwrap();
+    /// assert_eq!(chunk.pread_with::<u16>(0, LE).unwrap(), 0xbbaa);
+    /// // `bytes` has 4 bytes at offset 2, but the chunk only has 2
+    /// assert!(chunk.pread_with::<u32>(0, LE).is_err());
+    /// assert!(bytes.pread_window(4, 3).is_err());
+    /// ```
+    fn pread_window(&self, offset: usize, len: usize) -> error::Result<&[u8]>;
+    /// Returns the `len` bytes at `offset` as a window of their own, and advances the offset past them
+    /// # Example
+    /// ```rust
+    /// use scroll::{Pread, PreadExt, BE};
+    /// let bytes = [0x00, 0x02, 0xaa, 0xbb, 0xcc];
+    /// let offset = &mut 0;
+    /// let len: u16 = bytes.gread_with(offset, BE).unwrap();
+    /// let chunk = bytes.gread_window(offset, len as usize).unwrap();
+    /// assert_eq!(chunk, &[0xaa, 0xbb]);
+    /// assert_eq!(*offset, 4);
+    /// ```
+    fn gread_window(&self, offset: &mut usize, len: usize) -> error::Result<&[u8]>;
+    /// Copies the `count` bytes at `offset` into a `Vec`, which outlives `self`; requires the `alloc` feature
+    ///
+    /// Errors as [pread_window](#tymethod.pread_window) does.
+    /// # Example
+    /// ```rust
+    /// use scroll::PreadExt;
+    /// let owned = {
+    ///     let bytes = vec![0x00, 0xaa, 0xbb, 0xcc];
+    ///     bytes.pread_bytes_owned(1, 2).unwrap()
+    /// };
+    /// assert_eq!(owned, [0xaa, 0xbb]);
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn pread_bytes_owned(&self, offset: usize, count: usize) -> error::Result<Vec<u8>>;
+    /// Copies the `count` bytes at `offset` into a `Vec`, and advances the offset past them; requires the `alloc`
+    /// feature
+    #[cfg(feature = "alloc")]
+    fn gread_bytes_owned(&self, offset: &mut usize, count: usize) -> error::Result<Vec<u8>>;
+    /// Fills `dst` with numbers read from `self` at `offset`, in the `endian` byte order, returning the number of bytes read
+    ///
+    /// The result is the same as reading the numbers one by one with `gread_with`, but the bounds are checked once, and the
+    /// numbers are copied in one go when `endian` is the machine's byte order. If `self` is too short for all of them,
+    /// `dst` is left as it was.
+    /// # Example
+    /// ```
+    /// use scroll::{PreadExt, BE};
+    /// let bytes = [0, 1, 2, 3, 4, 5, 6, 7, 8];
+    /// let mut dst = [0u16; 4];
+    /// assert_eq!(bytes.pread_inout_endian(1, &mut dst, BE).unwrap(), 8);
+    /// assert_eq!(dst, [0x0102, 0x0304, 0x0506, 0x0708]);
+    /// assert!(bytes.pread_inout_endian(2, &mut dst, BE).is_err());
+    /// ```
+    fn pread_inout_endian<T: Primitive>(&self, offset: usize, dst: &mut [T], endian: Endian) -> error::Result<usize>;
+    /// Fills `dst` with numbers read from `self` at `offset`, in the `endian` byte order, and advances the offset past them;
+    /// on an error, neither the offset nor `dst` change
+    fn gread_inout_endian<T: Primitive>(&self, offset: &mut usize, dst: &mut [T], endian: Endian) -> error::Result<usize>;
+    /// Copies as many bytes at `offset` into `dst` as `self` has left, at most `dst.len()`, advances the offset past them,
+    /// and returns how many were copied
+    ///
+    /// Like `Read::read`, a source with fewer bytes left than `dst` fills just the front of it, and one with none left
+    /// copies nothing and returns zero; only an offset past the end is an error, an `Error::BadOffset`. For all or
+    /// nothing, use [gread_inout](trait.Pread.html#method.gread_inout).
+    /// # Example
+    /// ```rust
+    /// use scroll::PreadExt;
+    /// let bytes = [1, 2, 3, 4, 5];
+    /// let offset = &mut 0;
+    /// let mut chunk = [0u8; 3];
+    /// assert_eq!(bytes.gread_inout_partial(offset, &mut chunk).unwrap(), 3);
+    /// assert_eq!(bytes.gread_inout_partial(offset, &mut chunk).unwrap(), 2);
+    /// assert_eq!(chunk, [4, 5, 3]);
+    /// assert_eq!(bytes.gread_inout_partial(offset, &mut chunk).unwrap(), 0);
+    /// assert_eq!(*offset, 5);
+    /// ```
+    fn gread_inout_partial(&self, offset: &mut usize, dst: &mut [u8]) -> error::Result<usize>;
+    /// Returns exactly the `n` bytes at `offset`, and advances the offset past them
+    ///
+    /// Unlike [gread_window](#tymethod.gread_window), a source which ends too early is an `Error::Incomplete` saying how many more bytes are needed,
+    /// so it suits streaming input; an offset past the end is an `Error::BadOffset`. Either way the offset is left alone.
+    /// # Example
+    /// ```rust
+    /// use scroll::{Error, PreadExt};
+    /// let bytes = b"GET /index";
+    /// let offset = &mut 0;
+    /// assert_eq!(bytes.gread_exactly(offset, 3).unwrap(), b"GET");
+    /// assert_eq!(*offset, 3);
+    /// assert!(matches!(bytes.gread_exactly(offset, 10), Err(Error::Incomplete { needed: 3 })));
+    /// ```
+    fn gread_exactly(&self, offset: &mut usize, n: usize) -> error::Result<&[u8]>;
+    /// Returns the line of text at `offset`, without its `\n` or `\r\n`, and advances the offset past the line ending,
+    /// for the text embedded in binary data
+    ///
+    /// Reads with the default [LineCtx](ctx/struct.LineCtx.html): a last line without a `\n` is returned as it is, and
+    /// an offset at the end, with no line left, is an `Error::Incomplete`. A line which isn't UTF-8 is an
+    /// `Error::Invalid` at its first bad byte; on any error the offset is left alone.
+    /// # Example
+    /// ```rust
+    /// use scroll::{Pread, PreadExt};
+    /// let bytes = b"P5\r\n# a comment\n2 1\n255\n\x00\xff";
+    /// let offset = &mut 0;
+    /// assert_eq!(bytes.gread_line(offset).unwrap(), "P5");
+    /// assert_eq!(bytes.gread_line(offset).unwrap(), "# a comment");
+    /// assert_eq!(bytes.gread_line(offset).unwrap(), "2 1");
+    /// assert_eq!(bytes.gread_line(offset).unwrap(), "255");
+    /// assert_eq!(bytes.gread::<u8>(offset).unwrap(), 0x00);
+    /// ```
+    fn gread_line(&self, offset: &mut usize) -> error::Result<&str>;
+    /// Returns the line of text at `offset`, split off as `ctx` says, and advances the offset past the line ending
+    fn gread_line_with(&self, offset: &mut usize, ctx: LineCtx) -> error::Result<&str>;
+    /// Returns the bytes of the line at `offset`, split off as `ctx` says, and advances the offset past the line ending;
+    /// for lines which needn't be UTF-8
+    /// # Example
+    /// ```rust
+    /// use scroll::{ctx::LineCtx, Error, PreadExt};
+    /// let bytes = b"caf\xe9\r\nno newline";
+    /// let offset = &mut 0;
+    /// let ctx = LineCtx { strip_cr: false, unterminated: false };
+    /// assert_eq!(bytes.gread_line_bytes(offset, ctx).unwrap(), b"caf\xe9\r");
+    /// assert!(matches!(bytes.gread_line_bytes(offset, ctx), Err(Error::Incomplete { needed: 1 })));
+    /// assert_eq!(bytes.gread_line_bytes(offset, LineCtx::default()).unwrap(), b"no newline");
+    /// ```
+    fn gread_line_bytes(&self, offset: &mut usize, ctx: LineCtx) -> error::Result<&[u8]>;
+    /// Checks that the bytes at `offset` are the magic `expected`, which is at most 8 bytes long
+    ///
+    /// A mismatch is an `Error::BadMagic`, whose values are the bytes read as big endian numbers; a magic which would run past the end of
+    /// `self` is an `Error::BadOffset`.
+    /// # Example
+    /// ```rust
+    /// use scroll::{Error, PreadExt};
+    /// let bytes = b"\x7fELF\x02\x01";
+    /// assert!(bytes.pread_magic(0, b"\x7fELF").is_ok());
+    /// let err = bytes.pread_magic(0, b"\xfe\xed\xfa\xce").unwrap_err();
+    /// assert_eq!(err.to_string(), "bad magic at offset 0: expected 0xfeedface, found 0x7f454c46");
+    /// assert!(matches!(bytes.pread_magic(4, b"\x7fELF"), Err(Error::BadOffset(4))));
+    /// ```
+    fn pread_magic(&self, offset: usize, expected: &[u8]) -> error::Result<()>;
+    /// Checks that the bytes at `offset` are the magic `expected`, and advances the offset past them
+    fn gread_magic(&self, offset: &mut usize, expected: &[u8]) -> error::Result<()>;
+    /// Checks that the `u32` at `offset`, read with `endian`, is the magic `expected`
+    /// # Example
+    /// ```rust
+    /// use scroll::{PreadExt, BE, LE};
+    /// // a Mach-O magic, in either byte order
+    /// let bytes = [0xce, 0xfa, 0xed, 0xfe];
+    /// assert!(bytes.pread_magic_u32(0, 0xfeedface, LE).is_ok());
+    /// assert!(bytes.pread_magic_u32(0, 0xfeedface, BE).is_err());
+    /// ```
+    fn pread_magic_u32(&self, offset: usize, expected: u32, endian: Endian) -> error::Result<()>;
+    /// Checks that the `u32` at `offset`, read with `endian`, is the magic `expected`, and advances the offset past it
+    fn gread_magic_u32(&self, offset: &mut usize, expected: u32, endian: Endian) -> error::Result<()>;
+    /// Skips the padding up to the next multiple of `align`, which must be a power of two, returning how many bytes were skipped
+    ///
+    /// Errors, leaving the offset alone, if the aligned offset would be past the end of `self`.
+    /// # Example
+    /// ```rust
+    /// use scroll::{Pread, PreadExt, LE};
+    /// let bytes = [0x01, 0xff, 0xff, 0xff, 0xef, 0xbe, 0xad, 0xde];
+    /// let offset = &mut 0;
+    /// let _: u8 = bytes.gread(offset).unwrap();
+    /// assert_eq!(bytes.gread_align(offset, 4).unwrap(), 3);
+    /// assert_eq!(bytes.gread_with::<u32>(offset, LE).unwrap(), 0xdeadbeef);
+    /// ```
+    fn gread_align(&self, offset: &mut usize, align: usize) -> error::Result<usize>;
+    /// Returns the size of `count` elements of `elem_size` bytes each, checking that the multiplication doesn't overflow, and
+    /// that that many bytes remain in `self` from `base`
+    ///
+    /// For sizing tables whose count comes from an untrusted header: an overflow is an `Error::BadInput`, and a table
+    /// running past the end of `self` an `Error::TooBig`.
+    /// # Example
+    /// ```rust
+    /// use scroll::{Error, PreadExt};
+    /// let bytes = [0u8; 64];
+    /// assert_eq!(bytes.pread_checked_size_product(16, 6, 8).unwrap(), 48);
+    /// assert!(matches!(bytes.pread_checked_size_product(16, 7, 8), Err(Error::TooBig { .. })));
+    /// assert!(matches!(bytes.pread_checked_size_product(0, usize::MAX / 2, 4), Err(Error::BadInput { .. })));
+    /// ```
+    fn pread_checked_size_product(&self, base: usize, count: usize, elem_size: usize) -> error::Result<usize>;
+    /// Reads an unsigned LEB128 value which must fit in a `u32`, as WebAssembly's indices and counts do, and advances the
+    /// offset past it
+    ///
+    /// A faster equivalent of reading a [Uleb128](struct.Uleb128.html) and narrowing it: at most 5 bytes are decoded, and
+    /// an encoding of a value above `u32::MAX`, or one longer than 5 bytes, is an `Error::BadInput`.
+    /// # Example
+    /// ```rust
+    /// use scroll::PreadExt;
+    /// let bytes = [0xe5, 0x8e, 0x26, 0xff, 0xff, 0xff, 0xff, 0x1f];
+    /// let offset = &mut 0;
+    /// assert_eq!(bytes.gread_leb128_u32(offset).unwrap(), 624485);
+    /// assert_eq!(*offset, 3);
+    /// assert!(bytes.gread_leb128_u32(offset).is_err());
+    /// assert_eq!(*offset, 3);
+    /// ```
+    fn gread_leb128_u32(&self, offset: &mut usize) -> error::Result<u32>;
+    /// Reads a count of type `L`, followed by that many UTF-8 strings, each prefixed with its `u16` length, as in a Java
+    /// `.class` constant pool; requires the `alloc` feature
+    ///
+    /// A string which fails to parse is reported as an `Error::Element`, with its index and offset.
+    /// # Example
+    /// ```rust
+    /// use scroll::{PreadExt, BE};
+    /// let bytes = b"\x02\x00\x02hi\x00\x05there";
+    /// let strings = bytes.pread_string_list::<u8>(0, BE).unwrap();
+    /// assert_eq!(strings, ["hi", "there"]);
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn pread_string_list<'a, L>(&'a self, offset: usize, endian: Endian) -> error::Result<Vec<String>>
+        where L: TryFromCtx<'a, Endian, Error = error::Error>, usize: TryFrom<L>;
+    /// Reads a length of type `L`, followed by that many bytes of UTF-8, as a `String`, and advances the offset past
+    /// them; requires the `alloc` feature
+    ///
+    /// Invalid UTF-8 is an `Error::Invalid` at the offset of the first bad byte; the offset is only advanced on success.
+    /// # Example
+    /// ```rust
+    /// use scroll::{PreadExt, BE};
+    /// let bytes = [0x00, 0x05, 0x68, 0x65, 0x6c, 0x6c, 0x6f];
+    /// let offset = &mut 0;
+    /// assert_eq!(bytes.gread_n_bytes_to_string::<u16>(offset, BE).unwrap(), "hello");
+    /// assert_eq!(*offset, 7);
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn gread_n_bytes_to_string<'a, L>(&'a self, offset: &mut usize, endian: Endian) -> error::Result<String>
+        where L: TryFromCtx<'a, Endian, Error = error::Error>, usize: TryFrom<L>;
+    /// Reads a value at `offset` from both `self`, the old bytes, and `new`, with the given `ctx`, returning the old
+    /// and new values if the bytes they were read from differ, and `None` if they're the same; for finding what a patch
+    /// changed
+    /// # Example
+    /// ```rust
+    /// use scroll::{PreadExt, LE};
+    /// let old = [0x01, 0x00, 0x02, 0x00];
+    /// let new = [0x01, 0x00, 0x03, 0x00];
+    /// assert_eq!(old.pread_diff::<u16, _>(&new, 0, LE).unwrap(), None);
+    /// assert_eq!(old.pread_diff::<u16, _>(&new, 2, LE).unwrap(), Some((2, 3)));
+    /// ```
+    fn pread_diff<'a, N, Ctx>(&'a self, new: &'a [u8], offset: usize, ctx: Ctx) -> error::Result<Option<(N, N)>>
+        where N: TryFromCtx<'a, Ctx, Error = error::Error>, Ctx: Copy;
+    /// Returns an iterator reading the `count` values at `offset`, `offset + stride`, `offset + 2 * stride`, and so on,
+    /// with the given `ctx`; for every Nth value of interleaved data
+    ///
+    /// `stride` is the distance between the starts of consecutive values, and may be larger than a value, skipping the
+    /// bytes in between; it can't be zero, unless there's at most one value. The bounds are checked up front, against the end of the last value: a region running past the
+    /// end of `self` is an `Error::TooBig`, and one whose size overflows a `usize` an `Error::BadInput`. A value can still
+    /// fail to parse, so the items are results.
+    /// # Example
+    /// ```rust
+    /// use scroll::{PreadExt, LE};
+    /// // the second of four u16 channels
+    /// let bytes = [0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0];
+    /// let channel = bytes.pread_strided_iter::<u16, _>(2, 2, 8, LE).unwrap();
+    /// assert_eq!(channel.collect::<Result<Vec<_>, _>>().unwrap(), [1, 2]);
+    /// assert!(bytes.pread_strided_iter::<u16, _>(2, 3, 8, LE).is_err());
+    /// ```
+    fn pread_strided_iter<'a, N, Ctx>(&'a self, offset: usize, count: usize, stride: usize, ctx: Ctx) -> error::Result<StridedIter<'a, N, Ctx>>
+        where N: TryFromCtx<'a, Ctx, Error = error::Error> + SizeWith<Ctx>, Ctx: Copy;
+    /// Reads the `count` values at `offset`, `offset + stride`, `offset + 2 * stride`, and so on, with the given `ctx`, as
+    /// [pread_strided_iter](#tymethod.pread_strided_iter) does; requires the `alloc` feature
+    ///
+    /// A value which fails to parse is reported as an `Error::Element`, with its index and offset.
+    /// # Example
+    /// ```rust
+    /// use scroll::{PreadExt, BE};
+    /// // every row's first u16, of rows 6 bytes apart
+    /// let bytes = [0x00, 0x01, 0xff, 0xff, 0xff, 0xff, 0x00, 0x02, 0xff, 0xff, 0xff, 0xff, 0x00, 0x03];
+    /// assert_eq!(bytes.pread_strided::<u16, _>(0, 3, 6, BE).unwrap(), [1, 2, 3]);
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn pread_strided<'a, N, Ctx>(&'a self, offset: usize, count: usize, stride: usize, ctx: Ctx) -> error::Result<Vec<N>>
+        where N: TryFromCtx<'a, Ctx, Error = error::Error> + SizeWith<Ctx>, Ctx: Copy;
+    /// Splits the `count` frames at `offset`, each of `channels` values stored back to back, into a `Vec` of values per
+    /// channel, all read with the given `ctx`; requires the `alloc` feature
+    ///
+    /// Each channel is read as [pread_strided](#tymethod.pread_strided) reads it, with a stride of a frame.
+    /// # Example
+    /// ```rust
+    /// use scroll::{PreadExt, LE};
+    /// // stereo samples
+    /// let bytes = [0x01, 0x00, 0xff, 0xff, 0x02, 0x00, 0xfe, 0xff];
+    /// let channels = bytes.pread_deinterleaved::<i16, _>(0, 2, 2, LE).unwrap();
+    /// assert_eq!(channels, [[1, 2], [-1, -2]]);
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn pread_deinterleaved<'a, N, Ctx>(&'a self, offset: usize, channels: usize, count: usize, ctx: Ctx) -> error::Result<Vec<Vec<N>>>
+        where N: TryFromCtx<'a, Ctx, Error = error::Error> + SizeWith<Ctx>, Ctx: Copy;
+}
+
+impl<B: AsRef<[u8]> + ?Sized> PreadExt for B {
+    #[inline]
+    fn gread_remaining(&self, offset: &mut usize) -> error::Result<&[u8]> {
+        let len = self.as_ref().len();
+        self.gread_remaining_until(offset, len)
+    }
+    #[inline]
+    fn pread_remaining(&self, offset: usize) -> error::Result<&[u8]> {
+        self.gread_remaining(&mut { offset })
+    }
+    #[inline]
+    fn gread_remaining_until(&self, offset: &mut usize, end: usize) -> error::Result<&[u8]> {
+        let bytes = self.as_ref();
+        if end > bytes.len() {
+            return Err(error::Error::TooBig { size: end, len: bytes.len() });
+        }
+        if *offset > end {
+            return Err(error::Error::BadOffset(*offset));
+        }
+        let remaining = &bytes[*offset..end];
+        *offset = end;
+        Ok(remaining)
+    }
+    #[inline]
+    fn pread_window(&self, offset: usize, len: usize) -> error::Result<&[u8]> {
+        let bytes = self.as_ref();
+        if offset > bytes.len() {
+            return Err(error::Error::BadOffset(offset));
+        }
+        match offset.checked_add(len) {
+            Some(end) if end <= bytes.len() => Ok(&bytes[offset..end]),
+            _ => Err(error::Error::TooBig { size: len, len: bytes.len() - offset }),
+        }
+    }
+    #[inline]
+    fn gread_window(&self, offset: &mut usize, len: usize) -> error::Result<&[u8]> {
+        let window = self.pread_window(*offset, len)?;
+        *offset += len;
+        Ok(window)
+    }
+    #[cfg(feature = "alloc")]
+    #[inline]
+    fn pread_bytes_owned(&self, offset: usize, count: usize) -> error::Result<Vec<u8>> {
+        self.pread_window(offset, count).map(<[u8]>::to_vec)
+    }
+    #[cfg(feature = "alloc")]
+    #[inline]
+    fn gread_bytes_owned(&self, offset: &mut usize, count: usize) -> error::Result<Vec<u8>> {
+        self.gread_window(offset, count).map(<[u8]>::to_vec)
+    }
+    #[inline]
+    fn pread_inout_endian<T: Primitive>(&self, offset: usize, dst: &mut [T], endian: Endian) -> error::Result<usize> {
+        self.gread_inout_endian(&mut { offset }, dst, endian)
+    }
+    fn gread_inout_endian<T: Primitive>(&self, offset: &mut usize, dst: &mut [T], endian: Endian) -> error::Result<usize> {
+        let size = core::mem::size_of_val(dst);
+        let src = self.as_ref().get(*offset..).ok_or(error::Error::BadOffset(*offset))?;
+        if size > src.len() {
+            return Err(error::Error::TooBig { size, len: src.len() });
+        }
+        // the primitives have no padding, and any bytes are a valid one
+        let bytes = unsafe { core::slice::from_raw_parts_mut(dst.as_mut_ptr() as *mut u8, size) };
+        bytes.copy_from_slice(&src[..size]);
+        if endian != NATIVE {
+            dst.iter_mut().for_each(|value| *value = value.swap_bytes());
+        }
+        *offset += size;
+        Ok(size)
+    }
+    fn gread_inout_partial(&self, offset: &mut usize, dst: &mut [u8]) -> error::Result<usize> {
+        let src = self.as_ref().get(*offset..).ok_or(error::Error::BadOffset(*offset))?;
+        let size = dst.len().min(src.len());
+        dst[..size].copy_from_slice(&src[..size]);
+        *offset += size;
+        Ok(size)
+    }
+    fn gread_exactly(&self, offset: &mut usize, n: usize) -> error::Result<&[u8]> {
+        let bytes = self.as_ref();
+        let rest = bytes.get(*offset..).ok_or(error::Error::BadOffset(*offset))?;
+        if n > rest.len() {
+            return Err(error::Error::Incomplete { needed: n - rest.len() });
+        }
+        *offset += n;
+        Ok(&rest[..n])
+    }
+    #[inline]
+    fn gread_line(&self, offset: &mut usize) -> error::Result<&str> {
+        self.gread_line_with(offset, LineCtx::default())
+    }
+    fn gread_line_with(&self, offset: &mut usize, ctx: LineCtx) -> error::Result<&str> {
+        let start = &mut { *offset };
+        let line = self.gread_line_bytes(start, ctx)?;
+        let line = core::str::from_utf8(line)
+            .map_err(|e| error::Error::Invalid { offset: *offset + e.valid_up_to(), msg: "invalid utf8" })?;
+        *offset = *start;
+        Ok(line)
+    }
+    fn gread_line_bytes(&self, offset: &mut usize, ctx: LineCtx) -> error::Result<&[u8]> {
+        let rest = self.as_ref().get(*offset..).ok_or(error::Error::BadOffset(*offset))?;
+        let (line, size) = match rest.iter().position(|b| *b == b'\n') {
+            Some(end) => {
+                let line = &rest[..end];
+                let line = match line.split_last() {
+                    Some((b'\r', stripped)) if ctx.strip_cr => stripped,
+                    _ => line,
+                };
+                (line, end + 1)
+            },
+            None if rest.is_empty() || !ctx.unterminated => return Err(error::Error::Incomplete { needed: 1 }),
+            None => (rest, rest.len()),
+        };
+        *offset += size;
+        Ok(line)
+    }
+    #[inline]
+    fn pread_magic(&self, offset: usize, expected: &[u8]) -> error::Result<()> {
+        self.gread_magic(&mut { offset }, expected)
+    }
+    fn gread_magic(&self, offset: &mut usize, expected: &[u8]) -> error::Result<()> {
+        if expected.len() > 8 {
+            return Err(error::Error::BadInput { size: expected.len(), msg: "magic is longer than 8 bytes" });
+        }
+        let found = self.pread_window(*offset, expected.len()).map_err(|_| error::Error::BadOffset(*offset))?;
+        if found != expected {
+            let as_u64 = |bytes: &[u8]| bytes.iter().fold(0u64, |n, b| n << 8 | u64::from(*b));
+            return Err(error::Error::BadMagic { offset: *offset, expected: as_u64(expected), found: as_u64(found) });
+        }
+        *offset += expected.len();
+        Ok(())
+    }
+    #[inline]
+    fn pread_magic_u32(&self, offset: usize, expected: u32, endian: Endian) -> error::Result<()> {
+        self.gread_magic_u32(&mut { offset }, expected, endian)
+    }
+    fn gread_magic_u32(&self, offset: &mut usize, expected: u32, endian: Endian) -> error::Result<()> {
+        let found: u32 = self.pread_window(*offset, 4)
+            .and_then(|bytes| bytes.pread_with(0, endian))
+            .map_err(|_| error::Error::BadOffset(*offset))?;
+        if found != expected {
+            return Err(error::Error::BadMagic { offset: *offset, expected: expected.into(), found: found.into() });
+        }
+        *offset += 4;
+        Ok(())
+    }
+    fn gread_align(&self, offset: &mut usize, align: usize) -> error::Result<usize> {
+        let aligned = crate::offset::align_up(*offset, align)?;
+        if aligned > self.as_ref().len() {
+            return Err(error::Error::BadOffset(aligned));
+        }
+        let skipped = aligned - *offset;
+        *offset = aligned;
+        Ok(skipped)
+    }
+    fn pread_checked_size_product(&self, base: usize, count: usize, elem_size: usize) -> error::Result<usize> {
+        let size = count.checked_mul(elem_size).ok_or(error::Error::BadInput { size: count, msg: "count * element size overflows" })?;
+        let rest = self.as_ref().get(base..).ok_or(error::Error::BadOffset(base))?;
+        if size > rest.len() {
+            return Err(error::Error::TooBig { size, len: rest.len() });
+        }
+        Ok(size)
+    }
+    #[inline]
+    fn gread_leb128_u32(&self, offset: &mut usize) -> error::Result<u32> {
+        let src = self.as_ref().get(*offset..).ok_or(error::Error::BadOffset(*offset))?;
+        let mut result = 0u32;
+        for (count, &byte) in src.iter().take(5).enumerate() {
+            // the fifth byte only has room for the top 4 bits, and ends the encoding
+            if count == 4 && byte > 0x0f {
+                return Err(error::Error::BadInput { size: *offset, msg: "LEB128 value does not fit in a u32" });
+            }
+            result |= u32::from(byte & 0x7f) << (7 * count);
+            if byte & 0x80 == 0 {
+                *offset += count + 1;
+                return Ok(result);
+            }
+        }
+        Err(error::Error::Incomplete { needed: 1 })
+    }
+    #[cfg(feature = "alloc")]
+    fn pread_string_list<'a, L>(&'a self, offset: usize, endian: Endian) -> error::Result<Vec<String>>
+        where L: TryFromCtx<'a, Endian, Error = error::Error>, usize: TryFrom<L>
+    {
+        let src = self.as_ref();
+        let mut offset = offset;
+        let count = src.gread_with::<L>(&mut offset, endian)?;
+        let count = usize::try_from(count).map_err(|_| error::Error::BadInput { size: offset, msg: "string count does not fit in a usize" })?;
+        // every string takes at least the two bytes of its length
+        let mut strings = Vec::with_capacity(count.min(src.len().saturating_sub(offset) / 2));
+        let ctx = StrCtx::Prefixed { width: 2, endian };
+        for index in 0..count {
+            let at = offset;
+            let s: &str = src.gread_with(&mut offset, ctx)
+                .map_err(|err| error::Error::Element { index, offset: at, source: Box::new(err) })?;
+            strings.push(s.to_string());
+        }
+        Ok(strings)
+    }
+    #[cfg(feature = "alloc")]
+    fn gread_n_bytes_to_string<'a, L>(&'a self, offset: &mut usize, endian: Endian) -> error::Result<String>
+        where L: TryFromCtx<'a, Endian, Error = error::Error>, usize: TryFrom<L>
+    {
+        let src = self.as_ref();
+        let mut at = *offset;
+        let len = src.gread_with::<L>(&mut at, endian)?;
+        let len = usize::try_from(len).map_err(|_| error::Error::BadInput { size: at, msg: "string length does not fit in a usize" })?;
+        let bytes = src.pread_window(at, len)?;
+        let s = core::str::from_utf8(bytes).map_err(|err| error::Error::Invalid { offset: at + err.valid_up_to(), msg: "invalid utf8" })?;
+        *offset = at + len;
+        Ok(s.to_string())
+    }
+    fn pread_diff<'a, N, Ctx>(&'a self, new: &'a [u8], offset: usize, ctx: Ctx) -> error::Result<Option<(N, N)>>
+        where N: TryFromCtx<'a, Ctx, Error = error::Error>, Ctx: Copy
+    {
+        let old = self.as_ref();
+        let (mut old_end, mut new_end) = (offset, offset);
+        let old_n = old.gread_with(&mut old_end, ctx)?;
+        let new_n = new.gread_with(&mut new_end, ctx)?;
+        if old[offset..old_end] == new[offset..new_end] {
+            Ok(None)
+        } else {
+            Ok(Some((old_n, new_n)))
+        }
+    }
+    fn pread_strided_iter<'a, N, Ctx>(&'a self, offset: usize, count: usize, stride: usize, ctx: Ctx) -> error::Result<StridedIter<'a, N, Ctx>>
+        where N: TryFromCtx<'a, Ctx, Error = error::Error> + SizeWith<Ctx>, Ctx: Copy
+    {
+        let src = self.as_ref();
+        if offset > src.len() {
+            return Err(error::Error::BadOffset(offset));
+        }
+        if count > 1 && stride == 0 {
+            return Err(error::Error::BadInput { size: count, msg: "strided values can't all be at the same offset" });
+        }
+        if count > 0 {
+            let too_large = error::Error::BadInput { size: count, msg: "strided region is larger than a usize" };
+            let size = (count - 1).checked_mul(stride)
+                .and_then(|last| last.checked_add(N::size_with(&ctx)))
+                .ok_or(too_large)?;
+            if size > src.len() - offset {
+                return Err(error::Error::TooBig { size, len: src.len() - offset });
+            }
+        }
+        Ok(StridedIter { src, offset, remaining: count, stride, ctx, value: PhantomData })
+    }
+    #[cfg(feature = "alloc")]
+    fn pread_strided<'a, N, Ctx>(&'a self, offset: usize, count: usize, stride: usize, ctx: Ctx) -> error::Result<Vec<N>>
+        where N: TryFromCtx<'a, Ctx, Error = error::Error> + SizeWith<Ctx>, Ctx: Copy
+    {
+        let iter = self.pread_strided_iter(offset, count, stride, ctx)?;
+        let mut values = Vec::with_capacity(count);
+        for (index, value) in iter.enumerate() {
+            // within the region checked above
+            let at = offset + index * stride;
+            values.push(value.map_err(|source| error::Error::Element { index, offset: at, source: Box::new(source) })?);
+        }
+        Ok(values)
+    }
+    #[cfg(feature = "alloc")]
+    fn pread_deinterleaved<'a, N, Ctx>(&'a self, offset: usize, channels: usize, count: usize, ctx: Ctx) -> error::Result<Vec<Vec<N>>>
+        where N: TryFromCtx<'a, Ctx, Error = error::Error> + SizeWith<Ctx>, Ctx: Copy
+    {
+        let src = self.as_ref();
+        if offset > src.len() {
+            return Err(error::Error::BadOffset(offset));
+        }
+        let len = src.len() - offset;
+        let too_large = || error::Error::BadInput { size: channels, msg: "strided region is larger than a usize" };
+        let size = N::size_with(&ctx);
+        let frame = channels.checked_mul(size).ok_or_else(too_large)?;
+        if count > 0 {
+            let region = (count - 1).checked_mul(frame).and_then(|last| last.checked_add(frame)).ok_or_else(too_large)?;
+            if region > len {
+                return Err(error::Error::TooBig { size: region, len });
+            }
+        }
+        // with no frames, or values taking no bytes, the region doesn't bound the channels, so the bytes left do
+        if (count == 0 || size == 0) && channels > len {
+            return Err(error::Error::TooBig { size: channels, len });
+        }
+        let mut values = crate::buffer::try_vec(channels)?;
+        for channel in 0..channels {
+            if count == 0 {
+                values.push(Vec::new());
+            } else {
+                // within the region checked above
+                values.push(self.pread_strided(offset + channel * size, count, frame, ctx)?);
+            }
+        }
+        Ok(values)
+    }
+}
+
+/// An iterator reading values a fixed stride apart, from [pread_strided_iter](trait.PreadExt.html#tymethod.pread_strided_iter)
+///
+/// The bounds of every value were checked when it was made, so it yields exactly as many values as were asked for,
+/// though each can fail to parse.
+#[derive(Debug, Clone)]
+pub struct StridedIter<'a, N, Ctx> {
+    src: &'a [u8],
+    offset: usize,
+    remaining: usize,
+    stride: usize,
+    ctx: Ctx,
+    value: PhantomData<fn() -> N>,
+}
+
+impl<'a, N, Ctx> Iterator for StridedIter<'a, N, Ctx> where N: TryFromCtx<'a, Ctx, Error = error::Error>, Ctx: Copy {
+    type Item = error::Result<N>;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let value = self.src.pread_with(self.offset, self.ctx);
+        // the last value's offset was checked, but the one past it needn't fit a usize
+        self.offset = self.offset.wrapping_add(self.stride);
+        Some(value)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, N, Ctx> ExactSizeIterator for StridedIter<'a, N, Ctx> where N: TryFromCtx<'a, Ctx, Error = error::Error>, Ctx: Copy {}
+
+impl<'a, N, Ctx> FusedIterator for StridedIter<'a, N, Ctx> where N: TryFromCtx<'a, Ctx, Error = error::Error>, Ctx: Copy {}
+
+/// Whether `err` is from a read running out of bytes
+#[cfg(feature = "alloc")]
+#[inline]
+fn is_truncated(err: &error::Error) -> bool {
+    matches!(*err, error::Error::TooBig { .. } | error::Error::BadOffset(_) | error::Error::Incomplete { .. })
+}
+
+#[inline]
+fn check_bounds<N: PartialOrd + Debug, R: RangeBounds<N> + Debug, E: From<error::Error>>(n: N, range: &R, offset: usize) -> result::Result<N, E> {
+    if range.contains(&n) {
+        Ok(n)
+    } else {
+        Err(out_of_range(&n, range, offset).into())
+    }
+}
+
+#[cfg(feature = "alloc")]
+fn out_of_range<N: Debug, R: Debug>(n: &N, range: &R, offset: usize) -> error::Error {
+    error::Error::Custom(format!("value {:?} at offset {} is out of the range {:?}", n, offset, range))
+}
+
+#[cfg(not(feature = "alloc"))]
+fn out_of_range<N: Debug, R: Debug>(_n: &N, _range: &R, offset: usize) -> error::Error {
+    error::Error::BadInput { size: offset, msg: "value out of range" }
+}
+
+#[cfg(feature = "alloc")]
+fn not_equal<N: Debug>(n: &N, expected: &N, offset: usize) -> error::Error {
+    error::Error::Custom(format!("expected {:?} at offset {}, found {:?}", expected, offset, n))
+}
+
+#[cfg(not(feature = "alloc"))]
+fn not_equal<N: Debug>(_n: &N, _expected: &N, offset: usize) -> error::Error {
+    error::Error::BadInput { size: offset, msg: "value is not the expected one" }
+}
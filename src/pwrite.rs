@@ -1,7 +1,12 @@
+use core::convert::TryFrom;
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem::size_of;
 use core::result;
-use core::ops::{Index, IndexMut, RangeFrom};
+use core::ops::{Index, IndexMut, Range, RangeFrom};
 
-use crate::ctx::{TryIntoCtx, MeasureWith};
+use crate::ctx::{TryIntoCtx, MeasureWith, SizeWith, DefaultCtx, Primitive};
+use crate::endian::{Endian, LE, BE, NATIVE};
 use crate::error;
 
 /// Writes into `Self` at an offset of type `I` using a `Ctx`
@@ -71,9 +76,490 @@ pub trait Pwrite<Ctx, E> : Index<usize> + IndexMut<RangeFrom<usize>> + MeasureWi
             err => err
         }
     }
+    /// Writes `n` into `self` at `offset` as little endian; shorthand for `pwrite_with(n, offset, LE)`
+    /// # Example
+    /// ```
+    /// use scroll::Pwrite;
+    /// let mut bytes = [0u8; 4];
+    /// bytes.pwrite_le(0xdeadbeef_u32, 0).unwrap();
+    /// assert_eq!(bytes, [0xef, 0xbe, 0xad, 0xde]);
+    /// ```
+    #[inline]
+    fn pwrite_le<N: TryIntoCtx<Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>>(&mut self, n: N, offset: usize) -> result::Result<usize, E> where Ctx: From<Endian> {
+        self.pwrite_with(n, offset, Ctx::from(LE))
+    }
+    /// Writes `n` into `self` at `offset` as big endian; shorthand for `pwrite_with(n, offset, BE)`
+    /// # Example
+    /// ```
+    /// use scroll::Pwrite;
+    /// let mut bytes = [0u8; 4];
+    /// bytes.pwrite_be(0xdeadbeef_u32, 0).unwrap();
+    /// assert_eq!(bytes, [0xde, 0xad, 0xbe, 0xef]);
+    /// ```
+    #[inline]
+    fn pwrite_be<N: TryIntoCtx<Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>>(&mut self, n: N, offset: usize) -> result::Result<usize, E> where Ctx: From<Endian> {
+        self.pwrite_with(n, offset, Ctx::from(BE))
+    }
+    /// Writes `n` into `self` at `offset` as little endian, and updates the offset; shorthand for `gwrite_with(n, offset, LE)`
+    /// # Example
+    /// ```
+    /// use scroll::Pwrite;
+    /// let mut bytes = [0u8; 4];
+    /// let offset = &mut 0;
+    /// bytes.gwrite_le(0xdead_u16, offset).unwrap();
+    /// bytes.gwrite_le(0xbeef_u16, offset).unwrap();
+    /// assert_eq!(bytes, [0xad, 0xde, 0xef, 0xbe]);
+    /// assert_eq!(*offset, 4);
+    /// ```
+    #[inline]
+    fn gwrite_le<N: TryIntoCtx<Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>>(&mut self, n: N, offset: &mut usize) -> result::Result<usize, E> where Ctx: From<Endian> {
+        self.gwrite_with(n, offset, Ctx::from(LE))
+    }
+    /// Writes `n` into `self` at `offset` as big endian, and updates the offset; shorthand for `gwrite_with(n, offset, BE)`
+    /// # Example
+    /// ```
+    /// use scroll::Pwrite;
+    /// let mut bytes = [0u8; 4];
+    /// let offset = &mut 0;
+    /// bytes.gwrite_be(0xdead_u16, offset).unwrap();
+    /// bytes.gwrite_be(0xbeef_u16, offset).unwrap();
+    /// assert_eq!(bytes, [0xde, 0xad, 0xbe, 0xef]);
+    /// assert_eq!(*offset, 4);
+    /// ```
+    #[inline]
+    fn gwrite_be<N: TryIntoCtx<Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>>(&mut self, n: N, offset: &mut usize) -> result::Result<usize, E> where Ctx: From<Endian> {
+        self.gwrite_with(n, offset, Ctx::from(BE))
+    }
+    /// Writes `n` into `self` at `offset` with its [DefaultCtx](ctx/trait.DefaultCtx.html)
+    /// # Example
+    /// ```
+    /// use scroll::Pwrite;
+    /// let mut bytes = [0u8; 4];
+    /// bytes.pwrite_into(0xdeadbeef_u32, 0).unwrap();
+    /// assert_eq!(bytes, 0xdeadbeef_u32.to_ne_bytes());
+    /// ```
+    #[inline]
+    fn pwrite_into<N>(&mut self, n: N, offset: usize) -> result::Result<usize, E>
+        where N: DefaultCtx<Ctx = Ctx> + TryIntoCtx<Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>
+    {
+        self.pwrite_with(n, offset, N::default_ctx())
+    }
+    /// Writes `n` into `self` at `offset` with its [DefaultCtx](ctx/trait.DefaultCtx.html), and updates the offset
+    #[inline]
+    fn gwrite_into<N>(&mut self, n: N, offset: &mut usize) -> result::Result<usize, E>
+        where N: DefaultCtx<Ctx = Ctx> + TryIntoCtx<Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>
+    {
+        self.gwrite_with(n, offset, N::default_ctx())
+    }
+    /// Writes the bits of the [bitflags](https://docs.rs/bitflags) value `flags` into `self` at `offset` with the given `ctx`,
+    /// and updates the offset; requires the `bitflags` feature
+    /// # Example
+    /// ```rust
+    /// use scroll::{Pwrite, LE};
+    /// bitflags::bitflags! {
+    ///     struct Mode: u16 {
+    ///         const READ = 0x100;
+    ///         const WRITE = 0x080;
+    ///     }
+    /// }
+    /// let mut bytes = [0u8; 2];
+    /// let offset = &mut 0;
+    /// bytes.gwrite_bitflags(Mode::READ | Mode::WRITE, offset, LE).unwrap();
+    /// assert_eq!(bytes, [0x80, 0x01]);
+    /// assert_eq!(*offset, 2);
+    #[cfg(feature = "bitflags")]
+    fn gwrite_bitflags<F>(&mut self, flags: F, offset: &mut usize, ctx: Ctx) -> result::Result<usize, E>
+        where F: bitflags::Flags,
+              F::Bits: TryIntoCtx<Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>
+    {
+        self.gwrite_with(flags.bits(), offset, ctx)
+    }
+    /// Writes `n` at `data_offset` with the `ctx`, then back-patches the number of bytes it took, as an `L`, at `length_offset` with the `len_ctx`.
+    /// Returns the number of bytes `n` took.
+    ///
+    /// Errors if the length does not fit in an `L`; the data has been written by then.
+    /// # Example
+    /// ```
+    /// use scroll::{Pwrite, Pread, BE};
+    /// let mut record = [0u8; 8];
+    /// // a 2 byte length, followed by the payload
+    /// let size = record.pwrite_then_update_length::<_, u16, _>(0, 2, "hello", (), BE).unwrap();
+    /// assert_eq!(size, 5);
+    /// assert_eq!(record.pread_with::<u16>(0, BE).unwrap(), 5);
+    /// assert_eq!(&record[2..7], b"hello");
+    /// ```
+    fn pwrite_then_update_length<N, L, LenCtx>(&mut self, length_offset: usize, data_offset: usize, n: N, ctx: Ctx, len_ctx: LenCtx) -> result::Result<usize, E>
+        where N: TryIntoCtx<Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>,
+              L: TryFrom<usize> + TryIntoCtx<LenCtx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>,
+              LenCtx: Copy,
+              Self: Pwrite<LenCtx, E>,
+    {
+        let size = self.pwrite_with(n, data_offset, ctx)?;
+        let len = L::try_from(size).map_err(|_| error::Error::BadInput { size, msg: "length does not fit in the length field" })?;
+        self.pwrite_with(len, length_offset, len_ctx)?;
+        Ok(size)
+    }
+    /// Writes `n` with the `ctx`, preceded by the number of bytes it took as an `L` with the same `ctx`, and updates the offset past both.
+    /// Returns the number of bytes written, prefix included.
+    ///
+    /// The value is written first, just past where the prefix goes, so no temporary buffer is needed.
+    /// Errors, leaving the offset alone, if either doesn't fit, or if the length does not fit in an `L`.
+    /// # Example
+    /// ```
+    /// use scroll::{Pwrite, BE};
+    /// let mut bytes = [0u8; 5];
+    /// let offset = &mut 0;
+    /// assert_eq!(bytes.pwrite_with_length_prefix::<_, u8>(0xdeadbeef_u32, offset, BE).unwrap(), 5);
+    /// assert_eq!(bytes, [4, 0xde, 0xad, 0xbe, 0xef]);
+    /// assert_eq!(*offset, 5);
+    /// ```
+    fn pwrite_with_length_prefix<N, L>(&mut self, n: N, offset: &mut usize, ctx: Ctx) -> result::Result<usize, E>
+        where N: TryIntoCtx<Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>,
+              L: SizeWith<Ctx> + TryFrom<usize> + TryIntoCtx<Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>,
+    {
+        let o = *offset;
+        let prefix = L::size_with(&ctx);
+        let data_offset = o.checked_add(prefix).ok_or(error::Error::BadOffset(o))?;
+        let size = self.pwrite_then_update_length::<N, L, Ctx>(o, data_offset, n, ctx, ctx)?;
+        *offset = data_offset + size;
+        Ok(prefix + size)
+    }
+    /// Reserves room for an `N` at `offset`, zeroing it, and updates the offset; the returned [Slot](struct.Slot.html) writes the real value later
+    ///
+    /// Useful for length or offset fields whose value is only known once what follows them has been written.
+    /// # Example
+    /// ```
+    /// use scroll::{Pwrite, Pread, LE};
+    /// let mut bytes = [0xffu8; 8];
+    /// let offset = &mut 0;
+    /// let len = bytes.reserve::<u16>(offset, LE).unwrap();
+    /// let size = bytes.gwrite(&b"data"[..], offset).unwrap();
+    /// assert_eq!(bytes.pread_with::<u16>(0, LE).unwrap(), 0);
+    /// len.patch(&mut bytes, size as u16).unwrap();
+    /// assert_eq!(&bytes[..6], b"\x04\x00data");
+    /// ```
+    fn reserve<N>(&mut self, offset: &mut usize, ctx: Ctx) -> result::Result<Slot<N, Ctx>, E>
+        where N: SizeWith<Ctx> + TryIntoCtx<Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>,
+              <Self as Index<RangeFrom<usize>>>::Output: AsMut<[u8]>,
+    {
+        let o = *offset;
+        let len = self.measure_with(&ctx);
+        if o >= len {
+            return Err(error::Error::BadOffset(o).into())
+        }
+        let size = N::size_with(&ctx);
+        let dst = self[o..].as_mut();
+        if size > dst.len() {
+            return Err(error::Error::TooBig { size, len: dst.len() }.into())
+        }
+        for b in &mut dst[..size] {
+            *b = 0;
+        }
+        *offset += size;
+        Ok(Slot { offset: o, ctx, value: PhantomData })
+    }
+    /// Writes `count` copies of `n` into `self` at `offset`, with the `ctx`, returning the total number of bytes written.
+    /// Updates the offset only if every copy was written.
+    ///
+    /// The copies are checked to fit before any is written: if they don't, it is an `Error::TooBig`, and `self` is left as
+    /// it was.
+    ///
+    /// # Example
+    /// ```
+    /// use scroll::{Pwrite, BE};
+    /// let mut bytes = [0u8; 8];
+    /// let mut offset = 1;
+    /// assert_eq!(bytes.gwrite_repeated(0xbeefu16, 3, &mut offset, BE).unwrap(), 6);
+    /// assert_eq!(offset, 7);
+    /// assert_eq!(bytes, [0, 0xbe, 0xef, 0xbe, 0xef, 0xbe, 0xef, 0]);
+    /// assert!(bytes.gwrite_repeated(0xbeefu16, 4, &mut offset, BE).is_err());
+    /// assert_eq!(offset, 7);
+    /// ```
+    fn gwrite_repeated<N: Clone + SizeWith<Ctx> + TryIntoCtx<Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>>(&mut self, n: N, count: usize, offset: &mut usize, ctx: Ctx) -> result::Result<usize, E> {
+        check_repeated(self.measure_with(&ctx), *offset, N::size_with(&ctx), count)?;
+        let mut o = *offset;
+        for _ in 0..count {
+            self.gwrite_with(n.clone(), &mut o, ctx)?;
+        }
+        let size = o - *offset;
+        *offset = o;
+        Ok(size)
+    }
+    /// Writes `count` copies of `n` into `self` from `offset`, with the `ctx`, returning the total number of bytes written;
+    /// the non-greedy twin of [gwrite_repeated](#method.gwrite_repeated)
+    ///
+    /// # Example
+    /// ```
+    /// use scroll::{Pwrite, Error, LE};
+    /// // erased flash reads back as 0xff
+    /// let mut page = [0u8; 16];
+    /// assert_eq!(page.pwrite_repeat(0xffu8, 12, 4, LE).unwrap(), 12);
+    /// assert_eq!(&page[..4], &[0; 4]);
+    /// assert!(page[4..].iter().all(|b| *b == 0xff));
+    /// assert!(matches!(page.pwrite_repeat(0xffu8, 5, 12, LE), Err(Error::TooBig { size: 5, len: 4 })));
+    /// ```
+    #[inline]
+    fn pwrite_repeat<N: Clone + SizeWith<Ctx> + TryIntoCtx<Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>>(&mut self, n: N, count: usize, mut offset: usize, ctx: Ctx) -> result::Result<usize, E> {
+        self.gwrite_repeated(n, count, &mut offset, ctx)
+    }
+    /// Writes `n` into a slot of `stride` bytes at `offset`, with the `ctx`, zeroing the rest of the slot, and advances the
+    /// offset by exactly `stride`; returns `stride`
+    ///
+    /// For tables of fixed size slots, whose entries may be shorter than a slot. A slot which doesn't fit in `self` is an
+    /// `Error::TooBig`, and an `N` whose size is more than `stride` bytes is an `Error::BadInput`; either way, nothing is
+    /// written, and the offset is left alone.
+    /// # Example
+    /// ```
+    /// use scroll::{Pwrite, LE};
+    /// let mut table = [0xffu8; 8];
+    /// let offset = &mut 0;
+    /// assert_eq!(table.gwrite_struct_padded(0x0201u16, 4, offset, LE).unwrap(), 4);
+    /// table.gwrite_struct_padded(0x03u8, 4, offset, LE).unwrap();
+    /// assert_eq!(table, [0x01, 0x02, 0, 0, 0x03, 0, 0, 0]);
+    /// assert_eq!(*offset, 8);
+    /// ```
+    fn gwrite_struct_padded<N>(&mut self, n: N, stride: usize, offset: &mut usize, ctx: Ctx) -> result::Result<usize, E>
+        where N: TryIntoCtx<Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E> + SizeWith<Ctx>,
+              <Self as Index<RangeFrom<usize>>>::Output: AsMut<[u8]>,
+    {
+        let o = *offset;
+        let len = self.measure_with(&ctx);
+        if o > len {
+            return Err(error::Error::BadOffset(o).into())
+        }
+        if stride > len - o {
+            return Err(error::Error::TooBig { size: stride, len: len - o }.into())
+        }
+        let size = N::size_with(&ctx);
+        if size > stride {
+            return Err(error::Error::BadInput { size, msg: "value is larger than its stride" }.into())
+        }
+        let size = self.pwrite_with(n, o, ctx)?;
+        // a value which wrote more than its size said
+        if size > stride {
+            return Err(error::Error::BadInput { size, msg: "value is larger than its stride" }.into())
+        }
+        for b in &mut self[o + size..].as_mut()[..stride - size] {
+            *b = 0;
+        }
+        *offset = o + stride;
+        Ok(stride)
+    }
+    /// Writes the byte `pattern` `times` times into `self` at `offset`, returning the total number of bytes written.
+    /// Updates the offset only if every repetition was written, and, as with [gwrite_repeated](#method.gwrite_repeated),
+    /// writes nothing unless they all fit.
+    ///
+    /// # Example
+    /// ```
+    /// use scroll::Pwrite;
+    /// let mut bytes = [0u8; 7];
+    /// let mut offset = 0;
+    /// assert_eq!(bytes.gwrite_pattern(b"\xde\xad", 3, &mut offset).unwrap(), 6);
+    /// assert_eq!(bytes, [0xde, 0xad, 0xde, 0xad, 0xde, 0xad, 0]);
+    /// ```
+    #[inline]
+    fn gwrite_pattern<'b>(&mut self, pattern: &'b [u8], times: usize, offset: &mut usize) -> result::Result<usize, E>
+        where &'b [u8]: TryIntoCtx<Ctx, <Self as Index<RangeFrom<usize>>>::Output, Error = E>, Ctx: Default {
+        let ctx = Ctx::default();
+        check_repeated(self.measure_with(&ctx), *offset, pattern.len(), times)?;
+        let mut o = *offset;
+        for _ in 0..times {
+            self.gwrite_with(pattern, &mut o, ctx)?;
+        }
+        let size = o - *offset;
+        *offset = o;
+        Ok(size)
+    }
+}
+
+/// Checks that `count` values of `size` bytes fit in a destination of `len` bytes from `offset`, before any is written
+fn check_repeated(len: usize, offset: usize, size: usize, count: usize) -> error::Result<()> {
+    if count == 0 {
+        return Ok(())
+    }
+    if offset > len {
+        return Err(error::Error::BadOffset(offset))
+    }
+    let size = size.checked_mul(count)
+        .ok_or(error::Error::BadInput { size: count, msg: "repeated values are larger than a usize" })?;
+    if size > len - offset {
+        return Err(error::Error::TooBig { size, len: len - offset })
+    }
+    Ok(())
 }
 
 impl<Ctx: Copy,
      E: From<error::Error>,
      R: ?Sized + Index<usize> + IndexMut<RangeFrom<usize>> + MeasureWith<Ctx>>
     Pwrite<Ctx, E> for R {}
+
+/// Writes `n` with the given `ctx` into an array of `N` bytes, which it must fill exactly; for values whose size is known
+/// statically, without allocating
+///
+/// A value which takes fewer than `N` bytes is an `Error::BadInput`, and one which takes more is the error its `TryIntoCtx`
+/// returns, usually an `Error::TooBig`.
+///
+/// # Example
+/// ```rust
+/// use scroll::{to_array, LE};
+/// assert_eq!(to_array::<4, _, _>(0xdeadbeefu32, LE).unwrap(), [0xef, 0xbe, 0xad, 0xde]);
+/// assert!(to_array::<8, _, _>(0xdeadbeefu32, LE).is_err());
+/// assert!(to_array::<2, _, _>(0xdeadbeefu32, LE).is_err());
+/// ```
+pub fn to_array<const N: usize, T, Ctx>(n: T, ctx: Ctx) -> error::Result<[u8; N]>
+    where T: TryIntoCtx<Ctx, Error = error::Error>,
+          Ctx: Copy,
+{
+    let mut bytes = [0u8; N];
+    let size = n.try_into_ctx(&mut bytes, ctx)?;
+    if size != N {
+        return Err(error::Error::BadInput { size, msg: "value does not fill the array" });
+    }
+    Ok(bytes)
+}
+
+/// A placeholder for an `N`, reserved with [Pwrite::reserve](trait.Pwrite.html#method.reserve), to be written once its value is known
+///
+/// A `Slot` is plain data, recording the offset and the context to write with, so it can be stored and patched in any order.
+pub struct Slot<N, Ctx> {
+    offset: usize,
+    ctx: Ctx,
+    value: PhantomData<fn(N)>,
+}
+
+impl<N, Ctx: Copy> Slot<N, Ctx> {
+    /// The offset of the reserved room
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+    /// Writes `n` into the reserved room in `dst`, returning the number of bytes written
+    ///
+    /// The bounds are checked again, so patching a different, smaller buffer than the slot was reserved in is an error rather than a panic.
+    pub fn patch<W, E>(self, dst: &mut W, n: N) -> result::Result<usize, E>
+        where W: ?Sized + Pwrite<Ctx, E>,
+              N: TryIntoCtx<Ctx, <W as Index<RangeFrom<usize>>>::Output, Error = E>,
+              E: From<error::Error>,
+    {
+        dst.pwrite_with(n, self.offset, self.ctx)
+    }
+}
+
+impl<N, Ctx: Copy> Copy for Slot<N, Ctx> {}
+
+impl<N, Ctx: Copy> Clone for Slot<N, Ctx> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<N, Ctx: fmt::Debug> fmt::Debug for Slot<N, Ctx> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Slot").field("offset", &self.offset).field("ctx", &self.ctx).finish()
+    }
+}
+
+/// Conveniences for editing plain byte buffers in place, complementing [Pwrite](trait.Pwrite.html)
+///
+/// Like [PreadExt](trait.PreadExt.html), these don't take a parsing context; they are implemented for anything which is `AsMut<[u8]>`.
+pub trait PwriteExt {
+    /// Copies the bytes in `src` to `dest`, within `self`; the two may overlap, as with `slice::copy_within`
+    ///
+    /// Errors with `BadRange` instead of panicking if either range is out of bounds.
+    /// # Example
+    /// ```
+    /// use scroll::PwriteExt;
+    /// let mut bytes = *b"header.table....";
+    /// // move the table down 2 bytes, to make room for a longer header
+    /// bytes.pwrite_copy_within(7..12, 9).unwrap();
+    /// assert_eq!(&bytes, b"header.tatable..");
+    /// assert!(bytes.pwrite_copy_within(7..12, 12).is_err());
+    /// ```
+    fn pwrite_copy_within(&mut self, src: Range<usize>, dest: usize) -> error::Result<()>;
+    /// Writes `fill` bytes up to the next multiple of `align`, which must be a power of two, returning how many bytes were written
+    ///
+    /// Errors, leaving `self` and the offset alone, if the padding doesn't fit.
+    /// # Example
+    /// ```
+    /// use scroll::{Pwrite, PwriteExt};
+    /// let mut bytes = [0u8; 8];
+    /// let offset = &mut 0;
+    /// bytes.gwrite(0x01u8, offset).unwrap();
+    /// assert_eq!(bytes.gwrite_align(offset, 4, 0xff).unwrap(), 3);
+    /// assert_eq!(*offset, 4);
+    /// assert_eq!(bytes[..4], [0x01, 0xff, 0xff, 0xff]);
+    /// // already aligned
+    /// assert_eq!(bytes.gwrite_align(offset, 4, 0xff).unwrap(), 0);
+    /// ```
+    fn gwrite_align(&mut self, offset: &mut usize, align: usize, fill: u8) -> error::Result<usize>;
+    /// Writes every number in `values` into `self` at `offset`, in the `endian` byte order, returning the number of bytes written
+    ///
+    /// The result is the same as writing the numbers one by one with `gwrite_with`, but the bounds are checked once, and the
+    /// numbers are copied in one go when `endian` is the machine's byte order. If they don't all fit, nothing is written.
+    /// # Example
+    /// ```
+    /// use scroll::{PwriteExt, BE};
+    /// let mut bytes = [0u8; 9];
+    /// assert_eq!(bytes.pwrite_slice_with(1, &[0x0102u16, 0x0304, 0x0506, 0x0708], BE).unwrap(), 8);
+    /// assert_eq!(bytes, [0, 1, 2, 3, 4, 5, 6, 7, 8]);
+    /// assert!(bytes.pwrite_slice_with(2, &[0u16; 4], BE).is_err());
+    /// ```
+    fn pwrite_slice_with<T: Primitive>(&mut self, offset: usize, values: &[T], endian: Endian) -> error::Result<usize>;
+    /// Writes every number in `values` into `self` at `offset`, in the `endian` byte order, and advances the offset past them
+    fn gwrite_slice_with<T: Primitive>(&mut self, offset: &mut usize, values: &[T], endian: Endian) -> error::Result<usize>;
+}
+
+impl<B: AsMut<[u8]> + ?Sized> PwriteExt for B {
+    fn pwrite_copy_within(&mut self, src: Range<usize>, dest: usize) -> error::Result<()> {
+        let bytes = self.as_mut();
+        let len = bytes.len();
+        if src.start > src.end || src.end > len {
+            return Err(error::Error::BadRange { start: src.start, end: src.end, len });
+        }
+        let count = src.end - src.start;
+        match dest.checked_add(count) {
+            Some(end) if end <= len => {
+                bytes.copy_within(src, dest);
+                Ok(())
+            },
+            _ => Err(error::Error::BadRange { start: dest, end: dest.saturating_add(count), len }),
+        }
+    }
+    fn gwrite_align(&mut self, offset: &mut usize, align: usize, fill: u8) -> error::Result<usize> {
+        let aligned = crate::offset::align_up(*offset, align)?;
+        let bytes = self.as_mut();
+        if aligned > bytes.len() {
+            return Err(error::Error::TooBig { size: aligned - *offset, len: bytes.len().saturating_sub(*offset) });
+        }
+        let written = aligned - *offset;
+        bytes[*offset..aligned].iter_mut().for_each(|b| *b = fill);
+        *offset = aligned;
+        Ok(written)
+    }
+    #[inline]
+    fn pwrite_slice_with<T: Primitive>(&mut self, offset: usize, values: &[T], endian: Endian) -> error::Result<usize> {
+        self.gwrite_slice_with(&mut { offset }, values, endian)
+    }
+    fn gwrite_slice_with<T: Primitive>(&mut self, offset: &mut usize, values: &[T], endian: Endian) -> error::Result<usize> {
+        let width = size_of::<T>();
+        let size = values.len().checked_mul(width)
+            .ok_or(error::Error::BadInput { size: values.len(), msg: "slice is too large to write" })?;
+        let dst = self.as_mut().get_mut(*offset..).ok_or(error::Error::BadOffset(*offset))?;
+        if size > dst.len() {
+            return Err(error::Error::TooBig { size, len: dst.len() });
+        }
+        let dst = &mut dst[..size];
+        // the primitives have no padding, and every one of their bytes is initialized
+        let bytes_of = |values: &[T]| unsafe { core::slice::from_raw_parts(values.as_ptr() as *const u8, core::mem::size_of_val(values)) };
+        if endian == NATIVE {
+            dst.copy_from_slice(bytes_of(values));
+        } else {
+            for (chunk, value) in dst.chunks_exact_mut(width).zip(values) {
+                chunk.copy_from_slice(bytes_of(&[value.swap_bytes()]));
+            }
+        }
+        *offset += size;
+        Ok(size)
+    }
+}
@@ -0,0 +1,174 @@
+use core::ops::Range;
+use core::str;
+
+use crate::ctx::NULL;
+use crate::error;
+
+/// A string table: a run of NUL terminated strings, addressed by their byte offset into the table, as found in ELF and Mach-O binaries
+///
+/// # Example
+/// ```rust
+/// use scroll::StrTable;
+///
+/// let bytes = b"\x7fELF\0.text\0.data\0";
+/// let strtab = StrTable::from_range(&bytes[..], 5..bytes.len()).unwrap();
+/// assert_eq!(strtab.get(1).unwrap(), "text");
+/// assert_eq!(strtab.get(6).unwrap(), ".data");
+///
+/// let strings: Vec<(usize, &str)> = strtab.iter().collect::<Result<_, _>>().unwrap();
+/// assert_eq!(strings, [(0, ".text"), (6, ".data")]);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct StrTable<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> StrTable<'a> {
+    /// Creates a string table spanning all of `bytes`
+    #[inline]
+    pub fn new(bytes: &'a [u8]) -> Self {
+        StrTable { bytes }
+    }
+    /// Creates a string table spanning `range` of `src`, erroring if the range is out of bounds
+    pub fn from_range<S: AsRef<[u8]> + ?Sized>(src: &'a S, range: Range<usize>) -> error::Result<Self> {
+        let src = src.as_ref();
+        if range.start > range.end {
+            return Err(error::Error::BadOffset(range.start));
+        }
+        if range.end > src.len() {
+            return Err(error::Error::TooBig { size: range.end, len: src.len() });
+        }
+        Ok(StrTable { bytes: &src[range] })
+    }
+    /// The raw bytes of the table
+    #[inline]
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+    /// The size of the table, in bytes
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+    /// Returns the NUL terminated string starting at `offset` into the table
+    ///
+    /// Errors if `offset` is out of bounds, if the string runs off the end of the table without a terminator, or if it is not valid UTF-8.
+    pub fn get(&self, offset: usize) -> error::Result<&'a str> {
+        if offset >= self.bytes.len() {
+            return Err(error::Error::BadOffset(offset));
+        }
+        let rest = &self.bytes[offset..];
+        match rest.iter().position(|c| *c == NULL) {
+            Some(len) => str::from_utf8(&rest[..len])
+                .map_err(|_| error::Error::BadInput { size: len, msg: "invalid utf8" }),
+            None => Err(error::Error::BadInput { size: rest.len(), msg: "unterminated string" }),
+        }
+    }
+    /// Lazily iterates over every string in the table, along with its offset
+    ///
+    /// A string which is malformed (not NUL terminated, or not UTF-8) is yielded as an error, which ends the iteration.
+    #[inline]
+    pub fn iter(&self) -> StrTableIter<'a> {
+        StrTableIter { table: *self, offset: 0, done: false }
+    }
+}
+
+impl<'a> IntoIterator for StrTable<'a> {
+    type Item = error::Result<(usize, &'a str)>;
+    type IntoIter = StrTableIter<'a>;
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the `(offset, string)` pairs of a [StrTable](struct.StrTable.html)
+#[derive(Debug, Clone)]
+pub struct StrTableIter<'a> {
+    table: StrTable<'a>,
+    offset: usize,
+    done: bool,
+}
+
+impl<'a> Iterator for StrTableIter<'a> {
+    type Item = error::Result<(usize, &'a str)>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.offset >= self.table.len() {
+            return None;
+        }
+        let offset = self.offset;
+        match self.table.get(offset) {
+            Ok(s) => {
+                self.offset += s.len() + 1;
+                Some(Ok((offset, s)))
+            },
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl<'a> core::iter::FusedIterator for StrTableIter<'a> {}
+
+#[cfg(test)]
+mod tests {
+    use super::StrTable;
+
+    #[test]
+    fn get() {
+        let strtab = StrTable::new(b"\0libc.so.6\0printf\0");
+        assert_eq!(strtab.get(0).unwrap(), "");
+        assert_eq!(strtab.get(1).unwrap(), "libc.so.6");
+        // offsets into the middle of a string are fine, as the linker does for suffixes
+        assert_eq!(strtab.get(6).unwrap(), "so.6");
+        assert_eq!(strtab.get(11).unwrap(), "printf");
+        assert_eq!(strtab.get(17).unwrap(), "");
+        assert!(strtab.get(18).is_err());
+        assert!(StrTable::new(b"abc").get(0).is_err());
+        assert!(StrTable::new(b"\xff\0").get(0).is_err());
+    }
+
+    #[test]
+    fn from_range() {
+        let bytes = [0xffu8, b'a', 0, b'b', 0, 0xff];
+        let strtab = StrTable::from_range(&bytes, 1..5).unwrap();
+        assert_eq!(strtab.len(), 4);
+        assert_eq!(strtab.get(2).unwrap(), "b");
+        assert!(StrTable::from_range(&bytes, 1..7).is_err());
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = StrTable::from_range(&bytes, 3..1);
+        assert!(reversed.is_err());
+        assert!(StrTable::from_range(&bytes, 6..6).unwrap().is_empty());
+    }
+
+    #[test]
+    fn iter() {
+        let strtab = StrTable::new(b"\0.text\0\0.bss\0");
+        let mut iter = strtab.iter();
+        assert_eq!(iter.next().unwrap().unwrap(), (0, ""));
+        assert_eq!(iter.next().unwrap().unwrap(), (1, ".text"));
+        assert_eq!(iter.next().unwrap().unwrap(), (7, ""));
+        assert_eq!(iter.next().unwrap().unwrap(), (8, ".bss"));
+        assert!(iter.next().is_none());
+        assert_eq!(StrTable::new(b"").iter().count(), 0);
+    }
+
+    #[test]
+    fn iter_malformed() {
+        // no trailing NUL
+        let mut iter = StrTable::new(b"a\0bc").iter();
+        assert_eq!(iter.next().unwrap().unwrap(), (0, "a"));
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+        // invalid utf8 ends the iteration too
+        let mut iter = StrTable::new(b"\xc3\0ok\0").into_iter();
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+    }
+}
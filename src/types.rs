@@ -0,0 +1,525 @@
+//! Common wire-format types which aren't plain primitives
+//!
+//! # Example
+//! ```rust
+//! use scroll::Pread;
+//! use scroll::types::MacAddress;
+//!
+//! // the destination of an Ethernet frame
+//! let frame = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x08, 0x00];
+//! let dst: MacAddress = frame.pread(0).unwrap();
+//! assert!(dst.is_broadcast());
+//! assert_eq!(dst.to_string(), "FF:FF:FF:FF:FF:FF");
+//! ```
+
+use core::fmt;
+use core::result;
+
+use crate::ctx::{TryFromCtx, TryIntoCtx, SizeWith, ConstSize};
+use crate::endian::Endian;
+use crate::error;
+
+/// A 48-bit Ethernet MAC address, as its 6 bytes in transmission order
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MacAddress(pub [u8; 6]);
+
+impl MacAddress {
+    /// The broadcast address, `FF:FF:FF:FF:FF:FF`
+    pub const BROADCAST: MacAddress = MacAddress([0xff; 6]);
+    /// Is this the broadcast address?
+    #[inline]
+    pub fn is_broadcast(&self) -> bool {
+        *self == Self::BROADCAST
+    }
+    /// Is this a group address, i.e., is the least significant bit of its first byte set? The broadcast address is one too
+    #[inline]
+    pub fn is_multicast(&self) -> bool {
+        self.0[0] & 0x01 != 0
+    }
+    /// Is this an individual address, i.e., not a multicast one?
+    #[inline]
+    pub fn is_unicast(&self) -> bool {
+        !self.is_multicast()
+    }
+}
+
+impl From<[u8; 6]> for MacAddress {
+    #[inline]
+    fn from(bytes: [u8; 6]) -> Self {
+        MacAddress(bytes)
+    }
+}
+
+impl From<MacAddress> for [u8; 6] {
+    #[inline]
+    fn from(mac: MacAddress) -> Self {
+        mac.0
+    }
+}
+
+impl fmt::Display for MacAddress {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let [a, b, c, d, e, f] = self.0;
+        write!(fmt, "{:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X}", a, b, c, d, e, f)
+    }
+}
+
+impl<'a> TryFromCtx<'a> for MacAddress {
+    type Error = error::Error;
+    #[inline]
+    fn try_from_ctx(src: &'a [u8], _ctx: ()) -> result::Result<(Self, usize), Self::Error> {
+        if src.len() < 6 {
+            return Err(error::Error::Incomplete { needed: 6 - src.len() });
+        }
+        let mut bytes = [0u8; 6];
+        bytes.copy_from_slice(&src[..6]);
+        Ok((MacAddress(bytes), 6))
+    }
+}
+
+impl TryIntoCtx for MacAddress {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], _ctx: ()) -> error::Result<usize> {
+        if dst.len() < 6 {
+            return Err(error::Error::TooBig { size: 6, len: dst.len() });
+        }
+        dst[..6].copy_from_slice(&self.0);
+        Ok(6)
+    }
+}
+
+impl SizeWith for MacAddress {
+    #[inline]
+    fn size_with(_ctx: &()) -> usize {
+        6
+    }
+}
+
+impl ConstSize for MacAddress {
+    const SIZE: usize = 6;
+}
+
+/// `N` bytes of padding: reading skips them whatever they are, and writing emits `N` copies of `FILL`
+///
+/// It carries no data, so it documents a layout's gaps in the struct itself. Like the primitives, it is read and written with an `Endian`
+/// context, which it ignores, so it composes with the derive.
+///
+/// # Example
+/// ```rust
+/// use scroll::{types::Skip, Pread, Pwrite, LE};
+///
+/// let bytes = [0x01, 0xaa, 0xbb, 0xcc, 0x02];
+/// let offset = &mut 1;
+/// let _: Skip<3> = bytes.gread_with(offset, LE).unwrap();
+/// assert_eq!(bytes.gread_with::<u8>(offset, LE).unwrap(), 2);
+///
+/// let mut out = [0u8; 4];
+/// out.pwrite_with(Skip::<4, 0xff>, 0, LE).unwrap();
+/// assert_eq!(out, [0xff; 4]);
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Skip<const N: usize, const FILL: u8 = 0>;
+
+/// `N` reserved bytes which must be zero: reading errors if any of them isn't, and writing emits zeros
+///
+/// # Example
+/// ```rust
+/// use scroll::{types::ReservedZero, Pread, LE};
+///
+/// assert!([0u8, 0].pread_with::<ReservedZero<2>>(0, LE).is_ok());
+/// assert!([0u8, 1].pread_with::<ReservedZero<2>>(0, LE).is_err());
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ReservedZero<const N: usize>;
+
+#[inline]
+fn check_len(len: usize, n: usize) -> error::Result<()> {
+    if len < n {
+        Err(error::Error::Incomplete { needed: n - len })
+    } else {
+        Ok(())
+    }
+}
+
+impl<'a, const N: usize, const FILL: u8> TryFromCtx<'a, Endian> for Skip<N, FILL> {
+    type Error = error::Error;
+    #[inline]
+    fn try_from_ctx(src: &'a [u8], _ctx: Endian) -> result::Result<(Self, usize), Self::Error> {
+        check_len(src.len(), N)?;
+        Ok((Skip, N))
+    }
+}
+
+impl<const N: usize, const FILL: u8> TryIntoCtx<Endian> for Skip<N, FILL> {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], _ctx: Endian) -> error::Result<usize> {
+        if dst.len() < N {
+            return Err(error::Error::TooBig { size: N, len: dst.len() });
+        }
+        dst[..N].iter_mut().for_each(|b| *b = FILL);
+        Ok(N)
+    }
+}
+
+impl<const N: usize, const FILL: u8> SizeWith<Endian> for Skip<N, FILL> {
+    #[inline]
+    fn size_with(_ctx: &Endian) -> usize {
+        N
+    }
+}
+
+impl<const N: usize, const FILL: u8> ConstSize for Skip<N, FILL> {
+    const SIZE: usize = N;
+}
+
+impl<'a, const N: usize> TryFromCtx<'a, Endian> for ReservedZero<N> {
+    type Error = error::Error;
+    #[inline]
+    fn try_from_ctx(src: &'a [u8], _ctx: Endian) -> result::Result<(Self, usize), Self::Error> {
+        check_len(src.len(), N)?;
+        if src[..N].iter().any(|b| *b != 0) {
+            return Err(error::Error::BadInput { size: N, msg: "reserved field is not zero" });
+        }
+        Ok((ReservedZero, N))
+    }
+}
+
+impl<const N: usize> TryIntoCtx<Endian> for ReservedZero<N> {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], ctx: Endian) -> error::Result<usize> {
+        Skip::<N, 0>.try_into_ctx(dst, ctx)
+    }
+}
+
+impl<const N: usize> SizeWith<Endian> for ReservedZero<N> {
+    #[inline]
+    fn size_with(_ctx: &Endian) -> usize {
+        N
+    }
+}
+
+impl<const N: usize> ConstSize for ReservedZero<N> {
+    const SIZE: usize = N;
+}
+
+/// An x87 80-bit extended precision float, as stored by AIFF sample rates and x87 register dumps: a sign bit, a 15-bit
+/// exponent biased by 16383, and a 64-bit significand whose integer bit is explicit
+///
+/// It is read and written as 10 bytes with an `Endian` context: big endian puts the sign and exponent first, and little
+/// endian, the x87's layout in memory, puts the significand first.
+///
+/// Every `f64` converts exactly with `F80::from`; `to_f64` rounds to nearest even, loses the extra 11 bits of precision,
+/// and overflows to an infinity or underflows to a subnormal or zero. Encodings which are invalid on a 387 or later are
+/// read as the value the integer bit and exponent describe: a pseudo-denormal (zero exponent, integer bit set) is the
+/// normal value it equals, and an unnormal (integer bit clear) is its denormalized value. With the maximum exponent, only
+/// the set integer bit and a zero fraction is an infinity; anything else, including a pseudo-infinity, is a NaN.
+///
+/// # Example
+/// ```rust
+/// use scroll::{types::F80, Pread, Pwrite, BE};
+///
+/// // an AIFF COMM chunk's sample rate
+/// let bytes = [0x40, 0x0e, 0xac, 0x44, 0, 0, 0, 0, 0, 0];
+/// let rate: F80 = bytes.pread_with(0, BE).unwrap();
+/// assert_eq!(rate.to_f64(), 44100.0);
+///
+/// let mut out = [0u8; 10];
+/// out.pwrite_with(F80::from(44100.0), 0, BE).unwrap();
+/// assert_eq!(out, bytes);
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct F80 {
+    /// The sign, in the top bit, and the biased exponent
+    pub sign_exponent: u16,
+    /// The significand, with the integer bit at the top
+    pub significand: u64,
+}
+
+const F80_BIAS: i32 = 16383;
+const F80_INTEGER_BIT: u64 = 1 << 63;
+
+impl F80 {
+    /// Is the sign bit set?
+    #[inline]
+    pub fn is_sign_negative(self) -> bool {
+        self.sign_exponent & 0x8000 != 0
+    }
+    #[inline]
+    pub fn is_infinite(self) -> bool {
+        self.sign_exponent & 0x7fff == 0x7fff && self.significand == F80_INTEGER_BIT
+    }
+    #[inline]
+    pub fn is_nan(self) -> bool {
+        self.sign_exponent & 0x7fff == 0x7fff && self.significand != F80_INTEGER_BIT
+    }
+    /// The nearest `f64`, rounding ties to even
+    pub fn to_f64(self) -> f64 {
+        let sign = u64::from(self.sign_exponent >> 15) << 63;
+        let exponent = i32::from(self.sign_exponent & 0x7fff);
+        if exponent == 0x7fff {
+            if self.is_infinite() {
+                return f64::from_bits(sign | 0x7ff0_0000_0000_0000);
+            }
+            // keep the top of the payload, and make sure it stays a NaN
+            return f64::from_bits(sign | 0x7ff8_0000_0000_0000 | ((self.significand >> 11) & 0x000f_ffff_ffff_ffff));
+        }
+        if self.significand == 0 {
+            return f64::from_bits(sign);
+        }
+        // the value is significand * 2^(exponent - bias - 63), where a zero exponent means the same scale as 1
+        let shift = self.significand.leading_zeros();
+        let significand = self.significand << shift;
+        let exponent = exponent.max(1) - F80_BIAS - shift as i32;
+        // the number of low bits of the significand which don't fit, 11 for a normal f64, and more for a subnormal one
+        let dropped = if exponent < -1022 { (11 - 1022 - exponent).min(65) as u32 } else { 11 };
+        let wide = u128::from(significand);
+        let mut kept = (wide >> dropped) as u64;
+        let rest = wide & ((1u128 << dropped) - 1);
+        let half = 1u128 << (dropped - 1);
+        if rest > half || (rest == half && kept & 1 == 1) {
+            kept += 1;
+        }
+        if exponent < -1022 {
+            // a carry into bit 52 is the smallest normal, which the same bits encode
+            return f64::from_bits(sign | kept);
+        }
+        let (kept, exponent) = if kept == 1 << 53 { (kept >> 1, exponent + 1) } else { (kept, exponent) };
+        if exponent > 1023 {
+            return f64::from_bits(sign | 0x7ff0_0000_0000_0000);
+        }
+        f64::from_bits(sign | ((exponent + 1023) as u64) << 52 | (kept & 0x000f_ffff_ffff_ffff))
+    }
+}
+
+impl From<f64> for F80 {
+    fn from(f: f64) -> Self {
+        let bits = f.to_bits();
+        let sign = ((bits >> 48) as u16) & 0x8000;
+        let exponent = ((bits >> 52) & 0x7ff) as i32;
+        let fraction = bits & 0x000f_ffff_ffff_ffff;
+        let (exponent, significand) = match exponent {
+            0x7ff => (0x7fff, F80_INTEGER_BIT | fraction << 11),
+            0 if fraction == 0 => (0, 0),
+            // a subnormal f64 is normal here
+            0 => {
+                let shift = fraction.leading_zeros();
+                (1 - 1023 + F80_BIAS + 11 - shift as i32, fraction << shift)
+            },
+            _ => (exponent - 1023 + F80_BIAS, F80_INTEGER_BIT | fraction << 11),
+        };
+        F80 { sign_exponent: sign | exponent as u16, significand }
+    }
+}
+
+impl<'a> TryFromCtx<'a, Endian> for F80 {
+    type Error = error::Error;
+    #[inline]
+    fn try_from_ctx(src: &'a [u8], ctx: Endian) -> result::Result<(Self, usize), Self::Error> {
+        check_len(src.len(), 10)?;
+        let mut bytes = [0u8; 10];
+        bytes.copy_from_slice(&src[..10]);
+        if ctx.is_little() {
+            bytes.reverse();
+        }
+        let mut significand = [0u8; 8];
+        significand.copy_from_slice(&bytes[2..]);
+        let f = F80 { sign_exponent: u16::from_be_bytes([bytes[0], bytes[1]]), significand: u64::from_be_bytes(significand) };
+        Ok((f, 10))
+    }
+}
+
+impl TryIntoCtx<Endian> for F80 {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], ctx: Endian) -> error::Result<usize> {
+        if dst.len() < 10 {
+            return Err(error::Error::TooBig { size: 10, len: dst.len() });
+        }
+        let dst = &mut dst[..10];
+        dst[..2].copy_from_slice(&self.sign_exponent.to_be_bytes());
+        dst[2..].copy_from_slice(&self.significand.to_be_bytes());
+        if ctx.is_little() {
+            dst.reverse();
+        }
+        Ok(10)
+    }
+}
+
+impl SizeWith<Endian> for F80 {
+    #[inline]
+    fn size_with(_ctx: &Endian) -> usize {
+        10
+    }
+}
+
+impl ConstSize for F80 {
+    const SIZE: usize = 10;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{F80, MacAddress, ReservedZero, Skip};
+    use crate::{Pread, Pwrite};
+
+    #[test]
+    fn read_write_display() {
+        let bytes = [0x00, 0x1b, 0x21, 0x3a, 0x4f, 0xe0, 0xaa];
+        let offset = &mut 0;
+        let mac: MacAddress = bytes.gread(offset).unwrap();
+        assert_eq!(mac, MacAddress([0x00, 0x1b, 0x21, 0x3a, 0x4f, 0xe0]));
+        assert_eq!(*offset, 6);
+        assert_eq!(mac.to_string(), "00:1B:21:3A:4F:E0");
+        assert!(bytes.pread::<MacAddress>(2).is_err());
+
+        let mut out = [0u8; 7];
+        assert_eq!(out.pwrite(mac, 1).unwrap(), 6);
+        assert_eq!(out[1..], bytes[..6]);
+        assert!(out.pwrite(mac, 2).is_err());
+    }
+
+    #[test]
+    fn predicates() {
+        assert!(MacAddress::BROADCAST.is_broadcast());
+        assert!(MacAddress::BROADCAST.is_multicast());
+        // an IPv4 multicast MAC
+        let mcast = MacAddress([0x01, 0x00, 0x5e, 0x00, 0x00, 0xfb]);
+        assert!(mcast.is_multicast());
+        assert!(!mcast.is_broadcast());
+        assert!(!mcast.is_unicast());
+        let ucast = MacAddress([0x00, 0x1b, 0x21, 0x3a, 0x4f, 0xe0]);
+        assert!(ucast.is_unicast());
+        assert!(!ucast.is_multicast());
+        assert!(!ucast.is_broadcast());
+    }
+
+    #[test]
+    fn skip_and_reserved_round_trip() {
+        use crate::ctx::{TryFromCtx, TryIntoCtx};
+        use crate::{Endian, BE};
+        #[derive(Debug, PartialEq)]
+        struct Header {
+            kind: u8,
+            _pad: Skip<3, 0xff>,
+            len: u32,
+            _reserved: ReservedZero<4>,
+        }
+        impl<'a> TryFromCtx<'a, Endian> for Header {
+            type Error = crate::Error;
+            fn try_from_ctx(src: &'a [u8], ctx: Endian) -> Result<(Self, usize), Self::Error> {
+                let offset = &mut 0;
+                let header = Header {
+                    kind: src.gread_with(offset, ctx)?,
+                    _pad: src.gread_with(offset, ctx)?,
+                    len: src.gread_with(offset, ctx)?,
+                    _reserved: src.gread_with(offset, ctx)?,
+                };
+                Ok((header, *offset))
+            }
+        }
+
+        let mut bytes = [0u8; 12];
+        let offset = &mut 0;
+        bytes.gwrite_with(7u8, offset, BE).unwrap();
+        bytes.gwrite_with(Skip::<3, 0xff>, offset, BE).unwrap();
+        bytes.gwrite_with(0x1000u32, offset, BE).unwrap();
+        bytes.gwrite_with(ReservedZero::<4>, offset, BE).unwrap();
+        assert_eq!(*offset, 12);
+        assert_eq!(bytes, [7, 0xff, 0xff, 0xff, 0, 0, 0x10, 0, 0, 0, 0, 0]);
+        let header: Header = bytes.pread_with(0, BE).unwrap();
+        assert_eq!(header, Header { kind: 7, _pad: Skip, len: 0x1000, _reserved: ReservedZero });
+
+        // padding may hold anything, but reserved bytes must be zero
+        bytes[1] = 0x12;
+        assert!(bytes.pread_with::<Header>(0, BE).is_ok());
+        bytes[10] = 0x01;
+        assert!(bytes.pread_with::<Header>(0, BE).is_err());
+        assert!(bytes[..11].pread_with::<Header>(0, BE).is_err());
+        assert!(bytes[..10].pwrite_with(ReservedZero::<4>, 8, BE).is_err());
+        assert_eq!(Skip::<0>.try_into_ctx(&mut [], BE).unwrap(), 0);
+    }
+
+    #[test]
+    fn reserved_zero() {
+        use crate::{Error, LE};
+        let offset = &mut 0;
+        let _: ReservedZero<3> = [0u8, 0, 0, 1].gread_with(offset, LE).unwrap();
+        assert_eq!(*offset, 3);
+        for i in 0..3 {
+            let mut bytes = [0u8; 3];
+            bytes[i] = 0x80;
+            assert!(matches!(bytes.pread_with::<ReservedZero<3>>(0, LE), Err(Error::BadInput { size: 3, .. })));
+        }
+        // whatever was there before, writing a reserved field zeroes it
+        let mut bytes = [0xa5u8; 5];
+        assert_eq!(bytes.pwrite_with(ReservedZero::<3>, 1, LE).unwrap(), 3);
+        assert_eq!(bytes, [0xa5, 0, 0, 0, 0xa5]);
+        assert!(bytes.pread_with::<ReservedZero<3>>(1, LE).is_ok());
+    }
+
+    #[test]
+    fn f80_aiff_sample_rate() {
+        use crate::{BE, LE};
+        let bytes = [0x40, 0x0e, 0xac, 0x44, 0, 0, 0, 0, 0, 0];
+        let rate: F80 = bytes.pread_with(0, BE).unwrap();
+        assert_eq!(rate, F80 { sign_exponent: 0x400e, significand: 0xac44 << 48 });
+        assert_eq!(rate.to_f64(), 44100.0);
+        let mut out = [0u8; 11];
+        assert_eq!(out.pwrite_with(rate, 1, LE).unwrap(), 10);
+        assert!(out[1..].iter().eq(bytes.iter().rev()));
+        assert_eq!(out.pread_with::<F80>(1, LE).unwrap(), rate);
+        assert!(out.pwrite_with(rate, 2, LE).is_err());
+        assert!(bytes[..9].pread_with::<F80>(0, BE).is_err());
+    }
+
+    #[test]
+    fn f80_f64_round_trip() {
+        for &f in &[0.0, -0.0, 1.0, -2.5, 0.1, 1e300, -1e-300, f64::MAX, f64::MIN_POSITIVE, 5e-324, -2.2e-310,
+                    f64::INFINITY, f64::NEG_INFINITY] {
+            let f80 = F80::from(f);
+            assert_eq!(f80.to_f64().to_bits(), f.to_bits(), "{:e}", f);
+        }
+        assert_eq!(F80::from(1.0), F80 { sign_exponent: 0x3fff, significand: 1 << 63 });
+        // a subnormal f64 is normalized
+        assert_eq!(F80::from(5e-324), F80 { sign_exponent: 0x3fff - 1074, significand: 1 << 63 });
+        assert!(F80::from(f64::NAN).is_nan());
+        assert!(F80::from(f64::NAN).to_f64().is_nan());
+        assert!(F80::from(f64::INFINITY).is_infinite());
+    }
+
+    #[test]
+    fn f80_rounding_and_range() {
+        // 1 + 2^-53 is a tie, and rounds to the even 1.0; a bit more rounds up
+        let tie = F80 { sign_exponent: 0x3fff, significand: (1 << 63) | (1 << 10) };
+        assert_eq!(tie.to_f64(), 1.0);
+        let above = F80 { significand: tie.significand | 1, ..tie };
+        assert_eq!(above.to_f64(), 1.0 + f64::EPSILON);
+        // all ones rounds up to the next power of two
+        assert_eq!(F80 { sign_exponent: 0x3fff, significand: u64::MAX }.to_f64(), 2.0);
+        // out of range
+        assert_eq!(F80 { sign_exponent: 0x3fff + 1024, significand: 1 << 63 }.to_f64(), f64::INFINITY);
+        assert_eq!(F80 { sign_exponent: 0x8000 | (0x3fff - 1080), significand: 1 << 63 }.to_f64().to_bits(), (-0.0f64).to_bits());
+        assert_eq!(F80 { sign_exponent: 0x3fff - 1075, significand: (1 << 63) | 1 }.to_f64(), 5e-324);
+        assert_eq!(F80 { sign_exponent: 0x3fff - 1023, significand: u64::MAX }.to_f64(), f64::MIN_POSITIVE);
+        // the smallest f80 denormal
+        assert_eq!(F80 { sign_exponent: 0, significand: 1 }.to_f64(), 0.0);
+    }
+
+    #[test]
+    fn f80_invalid_encodings() {
+        // a pseudo-denormal is the normal value with the smallest exponent
+        let pseudo_denormal = F80 { sign_exponent: 0, significand: 1 << 63 };
+        assert_eq!(pseudo_denormal.to_f64(), F80 { sign_exponent: 1, significand: 1 << 63 }.to_f64());
+        // an unnormal is its denormalized value
+        assert_eq!(F80 { sign_exponent: 0x3fff, significand: 1 << 62 }.to_f64(), 0.5);
+        // a pseudo-infinity and a pseudo-NaN are NaNs
+        let pseudo_infinity = F80 { sign_exponent: 0x7fff, significand: 0 };
+        assert!(pseudo_infinity.is_nan() && !pseudo_infinity.is_infinite());
+        assert!(pseudo_infinity.to_f64().is_nan());
+        let nan = F80 { sign_exponent: 0xffff, significand: 0xc000_0000_0000_0000 };
+        assert!(nan.is_nan() && nan.is_sign_negative());
+        assert!(nan.to_f64().is_nan() && nan.to_f64().is_sign_negative());
+    }
+}
@@ -0,0 +1,182 @@
+use core::marker::PhantomData;
+use core::mem::size_of;
+use core::ptr;
+
+use crate::ctx::Primitive;
+use crate::endian::{Endian, NATIVE};
+use crate::error;
+
+/// How a [VolatileBytes](struct.VolatileBytes.html) loads and stores a primitive, exactly once and at its width
+///
+/// [Volatile](struct.Volatile.html) is the real thing; another implementation can add barriers around the accesses, or,
+/// in tests on a host, log them.
+pub trait Access {
+    /// Loads the value at `ptr`, which is in bounds and aligned
+    ///
+    /// # Safety
+    /// `ptr` must be valid for a read of a `T`, and aligned for it
+    unsafe fn load<T: Primitive>(ptr: *const T) -> T;
+    /// Stores `value` at `ptr`, which is in bounds and aligned
+    ///
+    /// # Safety
+    /// `ptr` must be valid for a write of a `T`, and aligned for it
+    unsafe fn store<T: Primitive>(ptr: *mut T, value: T);
+}
+
+/// Accesses memory with `read_volatile` and `write_volatile`
+#[derive(Debug, Copy, Clone)]
+pub struct Volatile;
+
+impl Access for Volatile {
+    #[inline]
+    unsafe fn load<T: Primitive>(ptr: *const T) -> T {
+        ptr::read_volatile(ptr)
+    }
+    #[inline]
+    unsafe fn store<T: Primitive>(ptr: *mut T, value: T) {
+        ptr::write_volatile(ptr, value)
+    }
+}
+
+/// A region of memory-mapped registers, read and written a primitive at a time with volatile accesses
+///
+/// Memory-mapped I/O can't be borrowed as a `&[u8]`, which would let the compiler merge, split, reorder or elide the
+/// accesses, and assume the bytes don't change behind its back; so, unlike the other sources, this one isn't
+/// [Pread](trait.Pread.html), and hands out no slices. Instead, [read](#method.read) and [write](#method.write) access
+/// a primitive at an offset exactly once, at its width, and must be aligned for it, as registers are; the byte order
+/// is applied to the value afterwards (or before), never by touching the memory byte by byte.
+///
+/// # Example
+/// ```rust
+/// use scroll::{VolatileBytes, BE, LE};
+///
+/// let mut registers = [0u32; 4];
+/// let mmio = unsafe { VolatileBytes::new(registers.as_mut_ptr() as *mut u8, 16) };
+/// mmio.write(4, 0xdead_beefu32, LE).unwrap();
+/// assert_eq!(mmio.read::<u32>(4, LE).unwrap(), 0xdead_beef);
+/// assert_eq!(mmio.read::<u32>(4, BE).unwrap(), 0xefbe_adde);
+/// // a misaligned access isn't split into smaller ones
+/// assert!(mmio.read::<u32>(2, LE).is_err());
+/// assert!(mmio.read::<u32>(16, LE).is_err());
+/// ```
+#[derive(Debug)]
+pub struct VolatileBytes<A: Access = Volatile> {
+    ptr: *mut u8,
+    len: usize,
+    access: PhantomData<A>,
+}
+
+impl VolatileBytes<Volatile> {
+    /// The `len` bytes of registers at `ptr`
+    ///
+    /// # Safety
+    /// `ptr` must be valid for volatile reads and writes of `len` bytes for as long as the `VolatileBytes` is used
+    #[inline]
+    pub unsafe fn new(ptr: *mut u8, len: usize) -> Self {
+        Self::with_access(ptr, len)
+    }
+}
+
+impl<A: Access> VolatileBytes<A> {
+    /// The `len` bytes of registers at `ptr`, accessed through `A`
+    ///
+    /// # Safety
+    /// As for [new](#method.new)
+    #[inline]
+    pub unsafe fn with_access(ptr: *mut u8, len: usize) -> Self {
+        VolatileBytes { ptr, len, access: PhantomData }
+    }
+    /// The number of bytes of registers
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Whether there are no registers at all
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// The pointer to the `T` at `offset`, if it is in bounds and aligned
+    fn at<T>(&self, offset: usize) -> error::Result<*mut T> {
+        let size = size_of::<T>();
+        if offset > self.len {
+            return Err(error::Error::BadOffset(offset));
+        }
+        if size > self.len - offset {
+            return Err(error::Error::TooBig { size, len: self.len - offset });
+        }
+        let ptr = self.ptr.wrapping_add(offset) as *mut T;
+        if !ptr.is_aligned() {
+            return Err(error::Error::Invalid { offset, msg: "misaligned volatile access" });
+        }
+        Ok(ptr)
+    }
+    /// Reads the `T` at `offset`, in the byte order `endian`, with a single load
+    #[inline]
+    pub fn read<T: Primitive>(&self, offset: usize, endian: Endian) -> error::Result<T> {
+        let ptr = self.at::<T>(offset)?;
+        // safe by the contract of `new`, with `at` checking the bounds and alignment
+        let value = unsafe { A::load(ptr) };
+        Ok(if endian == NATIVE { value } else { value.swap_bytes() })
+    }
+    /// Writes `value` at `offset`, in the byte order `endian`, with a single store
+    #[inline]
+    pub fn write<T: Primitive>(&self, offset: usize, value: T, endian: Endian) -> error::Result<()> {
+        let ptr = self.at::<T>(offset)?;
+        let value = if endian == NATIVE { value } else { value.swap_bytes() };
+        unsafe { A::store(ptr, value) };
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::{BE, LE};
+    use std::cell::RefCell;
+    use std::vec::Vec;
+
+    std::thread_local! {
+        static ACCESSES: RefCell<Vec<(&'static str, usize, usize)>> = const { RefCell::new(Vec::new()) };
+    }
+
+    /// Logs each access, as the kind, address and width, and then makes it
+    struct Logged;
+
+    impl Access for Logged {
+        unsafe fn load<T: Primitive>(ptr: *const T) -> T {
+            ACCESSES.with(|log| log.borrow_mut().push(("load", ptr as usize, size_of::<T>())));
+            Volatile::load(ptr)
+        }
+        unsafe fn store<T: Primitive>(ptr: *mut T, value: T) {
+            ACCESSES.with(|log| log.borrow_mut().push(("store", ptr as usize, size_of::<T>())));
+            Volatile::store(ptr, value)
+        }
+    }
+
+    fn accesses() -> Vec<(&'static str, usize, usize)> {
+        ACCESSES.with(|log| log.borrow_mut().drain(..).collect())
+    }
+
+    #[test]
+    fn volatile_accesses() {
+        let mut registers = [0u64; 2];
+        let base = registers.as_mut_ptr() as usize;
+        let mmio = unsafe { VolatileBytes::<Logged>::with_access(registers.as_mut_ptr() as *mut u8, 16) };
+
+        mmio.write(8, 0x0102_0304u32, BE).unwrap();
+        mmio.write(2, 0xbeefu16, LE).unwrap();
+        assert_eq!(accesses(), [("store", base + 8, 4), ("store", base + 2, 2)]);
+        assert_eq!(mmio.read::<u32>(8, BE).unwrap(), 0x0102_0304);
+        assert_eq!(mmio.read::<u8>(8, LE).unwrap(), 0x01);
+        assert_eq!(mmio.read::<u64>(0, LE).unwrap(), 0xbeef_0000);
+        assert_eq!(accesses(), [("load", base + 8, 4), ("load", base + 8, 1), ("load", base, 8)]);
+
+        // rejected accesses don't touch the memory at all
+        assert!(matches!(mmio.read::<u32>(6, LE), Err(error::Error::Invalid { offset: 6, .. })));
+        assert!(matches!(mmio.read::<u64>(16, LE), Err(error::Error::TooBig { size: 8, len: 0 })));
+        assert!(matches!(mmio.write(17, 0u8, LE), Err(error::Error::BadOffset(17))));
+        assert!(accesses().is_empty());
+        assert_eq!(registers[1].to_ne_bytes()[..4], 0x0102_0304u32.to_be_bytes());
+    }
+}
@@ -0,0 +1,83 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use scroll::ctx::{AllocLimit, Count, Duplicates, MapCtx, StrCtx, TryAlloc};
+use scroll::{Error, Pread, LE};
+
+/// Records the largest allocation made while `TRACKING` is set
+struct Tracking;
+
+static TRACKING: AtomicBool = AtomicBool::new(false);
+static LARGEST: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Tracking {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if TRACKING.load(Ordering::SeqCst) {
+            LARGEST.fetch_max(layout.size(), Ordering::SeqCst);
+        }
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if TRACKING.load(Ordering::SeqCst) {
+            LARGEST.fetch_max(new_size, Ordering::SeqCst);
+        }
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Tracking = Tracking;
+
+/// The largest allocation `f` makes; the only test in this file, so nothing else allocates meanwhile
+fn largest_allocation<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    LARGEST.store(0, Ordering::SeqCst);
+    TRACKING.store(true, Ordering::SeqCst);
+    let r = f();
+    TRACKING.store(false, Ordering::SeqCst);
+    (r, LARGEST.load(Ordering::SeqCst))
+}
+
+#[test]
+fn alloc_limit() {
+    const CAP: usize = 64;
+    let bytes: Vec<u8> = (0..=255).cycle().take(1024).map(|b| b % 26 + b'a').collect();
+
+    let (vec, largest) = largest_allocation(|| bytes.pread_with::<Vec<u8>>(0, AllocLimit::new(CAP, Count(CAP, LE))));
+    assert_eq!(vec.unwrap(), bytes[..CAP]);
+    assert!(largest <= CAP);
+    let (vec, largest) = largest_allocation(|| bytes.pread_with::<Vec<u8>>(0, AllocLimit::new(CAP, Count(CAP + 1, LE))));
+    assert!(matches!(vec, Err(Error::LimitExceeded("alloc"))));
+    assert_eq!(largest, 0);
+
+    // the count alone fits the source, but its elements are four times larger in memory
+    let vec = bytes.pread_with::<Vec<u32>>(0, AllocLimit::new(CAP, Count(CAP / 4, LE)));
+    assert_eq!(vec.unwrap().len(), CAP / 4);
+    let vec = bytes.pread_with::<Vec<u32>>(0, AllocLimit::new(CAP, Count(CAP / 4 + 1, LE)));
+    assert!(matches!(vec, Err(Error::LimitExceeded("alloc"))));
+
+    // a count from a corrupt header fails on the limit, not on the allocator
+    let (vec, largest) =
+        largest_allocation(|| bytes.pread_with::<Vec<u64>>(0, AllocLimit::new(CAP, TryAlloc(Count(0xFFFF_FFFF, LE)))));
+    assert!(matches!(vec, Err(Error::LimitExceeded("alloc"))));
+    assert_eq!(largest, 0);
+
+    let (s, largest) =
+        largest_allocation(|| bytes.pread_with::<String>(0, AllocLimit::new(CAP, StrCtx::Length(CAP))));
+    assert_eq!(s.unwrap().as_bytes(), &bytes[..CAP]);
+    assert!(largest <= CAP);
+    let (s, largest) =
+        largest_allocation(|| bytes.pread_with::<String>(0, AllocLimit::new(CAP, StrCtx::Length(CAP + 1))));
+    assert!(matches!(s, Err(Error::LimitExceeded("alloc"))));
+    assert_eq!(largest, 0);
+
+    let map_bytes = [1u8, 10, 2, 20];
+    let ctx = MapCtx::new(2, LE, Duplicates::Reject);
+    let map = map_bytes.pread_with::<BTreeMap<u8, u8>>(0, AllocLimit::new(4, ctx)).unwrap();
+    assert_eq!(map[&2], 20);
+    let map = map_bytes.pread_with::<BTreeMap<u8, u8>>(0, AllocLimit::new(3, ctx));
+    assert!(matches!(map, Err(Error::LimitExceeded("alloc"))));
+}
@@ -1,9 +1,5 @@
 // this exists primarily to test various API usages of scroll; e.g., must compile
 
-// guard against potential undefined behaviour when borrowing from
-// packed structs. See https://github.com/rust-lang/rust/issues/46043
-#![deny(safe_packed_borrows)]
-
 // #[macro_use] extern crate scroll_derive;
 
 use std::ops::{Deref,  DerefMut};
@@ -58,9 +54,20 @@ pub struct Section32 {
 
 impl<'a> ctx::TryFromCtx<'a, ()> for Section<'a> {
     type Error = scroll::Error;
-    fn try_from_ctx(_bytes: &'a [u8], _ctx: ()) -> ::std::result::Result<(Self, usize), Self::Error> {
+    fn try_from_ctx(bytes: &'a [u8], _ctx: ()) -> ::std::result::Result<(Self, usize), Self::Error> {
         //let section = Section::from_ctx(bytes, bytes.pread_with::<Section32>(offset, ctx)?);
-        let section = unsafe { ::std::mem::uninitialized::<Section>()};
+        let section = Section {
+            sectname: [0; 16],
+            segname: [0; 16],
+            addr: 0,
+            size: 0,
+            offset: 0,
+            align: 0,
+            reloff: 0,
+            nreloc: 0,
+            flags: 0,
+            data: bytes,
+        };
         Ok((section, ::std::mem::size_of::<Section>()))
     }
 }
@@ -84,7 +91,7 @@ pub struct Segment<'a> {
 
 impl<'a> Segment<'a> {
     pub fn name(&self) -> Result<&str> {
-        Ok(self.segname.pread::<&str>(0)?)
+        self.segname.pread::<&str>(0)
     }
     pub fn sections(&self) -> Result<Vec<Section<'a>>> {
         let nsects = self.nsects as usize;
@@ -124,6 +131,12 @@ impl<'a> DerefMut for Segments<'a> {
     }
 }
 
+impl<'a> Default for Segments<'a> {
+    fn default() -> Self {
+        Segments::new()
+    }
+}
+
 impl<'a> Segments<'a> {
     pub fn new() -> Self {
         Segments {
@@ -163,12 +176,92 @@ fn lifetime_passthrough_<'a>(segments: &Segments<'a>, section_name: &str) -> Opt
 #[test]
 fn lifetime_passthrough() {
     let segments = Segments::new();
-    let _res = lifetime_passthrough_(&segments, "__text");
-    assert!(true)
+    assert!(lifetime_passthrough_(&segments, "__text").is_none());
+}
+
+struct Data<'a> {
+    name: &'a str,
+    id: u32,
+}
+
+impl<'a> ctx::TryFromCtx<'a, scroll::Endian> for Data<'a> {
+    type Error = scroll::Error;
+    fn try_from_ctx(src: &'a [u8], endian: scroll::Endian) -> Result<(Self, usize)> {
+        let offset = &mut 0;
+        let name = src.gread::<&str>(offset)?;
+        let id = src.gread_with(offset, endian)?;
+        Ok((Data { name, id }, *offset))
+    }
+}
+
+/// A `Data` and the bytes after it, read greedily inside another `TryFromCtx`
+struct Record<'a> {
+    data: Data<'a>,
+    payload: &'a [u8],
+}
+
+impl<'a> ctx::TryFromCtx<'a, scroll::Endian> for Record<'a> {
+    type Error = scroll::Error;
+    fn try_from_ctx(src: &'a [u8], endian: scroll::Endian) -> Result<(Self, usize)> {
+        let offset = &mut 0;
+        let data = src.gread_with::<Data>(offset, endian)?;
+        let len = src.gread_with::<u8>(offset, endian)? as usize;
+        let payload = src.gread_with::<&[u8]>(offset, len)?;
+        Ok((Record { data, payload }, *offset))
+    }
+}
+
+/// Two `Record`s, so a `Data` is two levels of `gread` down
+struct Pair<'a>(Record<'a>, Record<'a>);
+
+impl<'a> ctx::TryFromCtx<'a, scroll::Endian> for Pair<'a> {
+    type Error = scroll::Error;
+    fn try_from_ctx(src: &'a [u8], endian: scroll::Endian) -> Result<(Self, usize)> {
+        let offset = &mut 0;
+        let first = src.gread_with(offset, endian)?;
+        let second = src.gread_with(offset, endian)?;
+        Ok((Pair(first, second), *offset))
+    }
+}
+
+/// The borrows outlive the offset, and the function, as they're tied to `src`
+fn names<'a>(src: &'a [u8], count: usize) -> Result<Vec<&'a str>> {
+    let mut offset = 0;
+    let mut names = Vec::new();
+    for _ in 0..count {
+        let data: Data<'a> = src.gread_with(&mut offset, scroll::BE)?;
+        names.push(data.name);
+    }
+    Ok(names)
+}
+
+#[test]
+fn borrowed_gread() {
+    let bytes = b"UserName\x00\x01\x02\x03\x04Admin\x00\x00\x00\x00\x2a".to_vec();
+    let offset = &mut 0;
+    let user: Data = bytes.gread_with(offset, scroll::BE).unwrap();
+    let admin = bytes.gread_with::<Data>(offset, scroll::BE).unwrap();
+    assert_eq!((user.name, user.id), ("UserName", 0x01020304));
+    assert_eq!((admin.name, admin.id), ("Admin", 42));
+    assert_eq!(*offset, bytes.len());
+    assert_eq!(names(&bytes, 2).unwrap(), ["UserName", "Admin"]);
+
+    let bytes = b"a\x00\x01\x00\x00\x00\x02hib\x00\x02\x00\x00\x00\x00rest";
+    let offset = &mut 0;
+    let pair: Pair = bytes.gread_with(offset, scroll::LE).unwrap();
+    assert_eq!((pair.0.data.name, pair.0.data.id, pair.0.payload), ("a", 1, &b"hi"[..]));
+    assert_eq!((pair.1.data.name, pair.1.data.id, pair.1.payload), ("b", 2, &b""[..]));
+    assert_eq!(&bytes[*offset..], b"rest");
+    // the borrows outlive the reads which made them, and the intermediate values
+    let payload: &[u8] = {
+        let record = bytes.pread_with::<Record>(0, scroll::LE).unwrap();
+        record.payload
+    };
+    assert_eq!(payload, b"hi");
 }
 
 #[derive(Default)]
-#[repr(packed)]
+#[repr(C, packed)]
 struct Foo {
     foo: i64,
     bar: u32,
@@ -204,7 +297,7 @@ fn ioread_api() {
     assert_eq!({foo_.bar}, bar);
 }
 
-#[repr(packed)]
+#[repr(C, packed)]
 struct Bar {
     foo: i32,
     bar: u32,
@@ -268,9 +361,3003 @@ fn cwrite_api_customtype() {
     use scroll::{Cwrite, Cread};
     let bar = Bar { foo: -1, bar: 0xdeadbeef };
     let mut bytes = [0x0; 16];
-    &bytes[..].cwrite::<Bar>(bar, 0);
+    bytes[..].cwrite::<Bar>(bar, 0);
     let bar = bytes.cread::<Bar>(0);
     assert_eq!({bar.foo}, -1);
     assert_eq!({bar.bar}, 0xdeadbeef);
 }
 
+
+#[test]
+fn arc_shared_buffer() {
+    use std::sync::Arc;
+    use std::thread;
+    use scroll::{LE, BE};
+    let bytes: Arc<[u8]> = Arc::from(&[0xde, 0xad, 0xbe, 0xef, 0x01, 0x02, 0x03, 0x04][..]);
+    let first = bytes.clone();
+    let second = bytes.clone();
+    let a = thread::spawn(move || first.pread_with::<u32>(0, BE).unwrap());
+    let b = thread::spawn(move || second.pread_with::<u32>(4, LE).unwrap());
+    assert_eq!(a.join().unwrap(), 0xdeadbeef);
+    assert_eq!(b.join().unwrap(), 0x04030201);
+    let offset = &mut 0;
+    assert_eq!(bytes.gread_with::<u16>(offset, BE).unwrap(), 0xdead);
+    assert_eq!(*offset, 2);
+
+    let vec: Arc<Vec<u8>> = Arc::new(bytes.to_vec());
+    let shared = vec.clone();
+    let c = thread::spawn(move || shared.pread_with::<u16>(6, BE).unwrap());
+    assert_eq!(c.join().unwrap(), 0x0304);
+    assert_eq!(vec.pread_with::<u16>(2, BE).unwrap(), 0xbeef);
+}
+
+#[test]
+fn gwrite_repeated_and_pattern() {
+    use scroll::{Pwrite, LE};
+    let mut bytes = [0u8; 104];
+    let mut offset = 2;
+    assert_eq!(bytes.gwrite_repeated(0xFFFF_u16, 50, &mut offset, LE).unwrap(), 100);
+    assert_eq!(offset, 102);
+    assert_eq!(&bytes[..2], &[0, 0]);
+    for i in 0..50 {
+        assert_eq!(bytes.pread_with::<u16>(2 + i * 2, LE).unwrap(), 0xFFFF);
+    }
+    assert_eq!(&bytes[102..], &[0, 0]);
+    // only 2 bytes left, so nothing is written, and the offset stays put
+    assert!(matches!(bytes.gwrite_repeated(0u16, 2, &mut offset, LE), Err(scroll::Error::TooBig { size: 4, len: 2 })));
+    assert_eq!(offset, 102);
+    assert_eq!(&bytes[100..], &[0xFF, 0xFF, 0, 0]);
+    assert_eq!(bytes.gwrite_repeated(0u16, 0, &mut offset, LE).unwrap(), 0);
+
+    let mut offset = 0;
+    assert_eq!(bytes.gwrite_pattern(b"\x00\xff\x01", 34, &mut offset).unwrap(), 102);
+    assert_eq!(&bytes[..6], b"\x00\xff\x01\x00\xff\x01");
+    assert!(matches!(bytes.gwrite_pattern(b"\x00\xff\x01", 1, &mut offset), Err(scroll::Error::TooBig { size: 3, len: 2 })));
+    assert_eq!(&bytes[102..], &[0, 0]);
+}
+
+#[test]
+fn pwrite_repeat() {
+    use scroll::{Pwrite, LE};
+    let mut buffer = vec![0u8; 512];
+    assert_eq!(buffer.pwrite_repeat(0xFF_u8, 512, 0, LE).unwrap(), 512);
+    assert_eq!((buffer[0], buffer[255], buffer[511]), (0xFF, 0xFF, 0xFF));
+    // the copies which would fit aren't written either
+    assert!(matches!(buffer.pwrite_repeat(0_u8, 2, 511, LE), Err(scroll::Error::TooBig { size: 2, len: 1 })));
+    assert_eq!(buffer[511], 0xFF);
+    assert!(matches!(buffer.pwrite_repeat(0_u8, 1, 513, LE), Err(scroll::Error::BadOffset(513))));
+    assert!(matches!(buffer.pwrite_repeat(0_u16, usize::MAX, 0, LE), Err(scroll::Error::BadInput { .. })));
+    assert!(buffer.iter().all(|b| *b == 0xFF));
+}
+
+#[test]
+fn pread_gread_counting() {
+    use scroll::{BE, LE};
+    let bytes = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08,
+                 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, 0x10,
+                 0x11, 0x12, 0x13];
+    let mut counter = 0;
+    let offset = &mut 0;
+    let _: u8 = bytes.gread_counting(offset, LE, &mut counter).unwrap();
+    let _: u16 = bytes.gread_counting(offset, BE, &mut counter).unwrap();
+    let _: i32 = bytes.gread_counting(offset, LE, &mut counter).unwrap();
+    let _: f64 = bytes.gread_counting(offset, BE, &mut counter).unwrap();
+    let _: i32 = bytes.gread_counting(offset, LE, &mut counter).unwrap();
+    assert_eq!(counter, 1 + 2 + 4 + 8 + 4);
+    assert_eq!(counter, *offset);
+    // failed reads aren't counted
+    assert!(bytes.gread_counting::<u64>(offset, LE, &mut counter).is_err());
+    assert_eq!(counter, 19);
+
+    let mut counter = 0;
+    let _: u64 = bytes.pread_counting(3, LE, &mut counter).unwrap();
+    let _: u16 = bytes.pread_counting(0, LE, &mut counter).unwrap();
+    assert_eq!(counter, 10);
+    assert!(bytes.pread_counting::<u32>(18, LE, &mut counter).is_err());
+    assert_eq!(counter, 10);
+}
+
+#[test]
+fn pread_gread_bounded() {
+    use scroll::{BE, LE};
+    let bytes = [1u8, 2, 4, 5, 0xff, 0xff];
+    // inclusive boundaries
+    assert!(bytes.pread_bounded::<u8, _>(0, 2..=4, LE).is_err());
+    assert_eq!(bytes.pread_bounded::<u8, _>(1, 2..=4, LE).unwrap(), 2);
+    assert_eq!(bytes.pread_bounded::<u8, _>(2, 2..=4, LE).unwrap(), 4);
+    assert!(bytes.pread_bounded::<u8, _>(3, 2..=4, LE).is_err());
+    // exclusive and open ranges
+    assert!(bytes.pread_bounded::<u8, _>(2, 2..4, LE).is_err());
+    assert_eq!(bytes.pread_bounded::<u16, _>(4, ..=0xffff, BE).unwrap(), 0xffff);
+    assert_eq!(bytes.pread_bounded::<i16, _>(4, ..0, BE).unwrap(), -1);
+    assert!(bytes.pread_bounded::<u16, _>(4, 1.., BE).is_ok());
+
+    let offset = &mut 1;
+    assert_eq!(bytes.gread_bounded::<u8, _>(offset, 2..=4, LE).unwrap(), 2);
+    assert_eq!(bytes.gread_bounded::<u8, _>(offset, 2..=4, LE).unwrap(), 4);
+    assert!(bytes.gread_bounded::<u8, _>(offset, 2..=4, LE).is_err());
+    assert_eq!(*offset, 3);
+}
+
+#[test]
+fn pread_bounded_error() {
+    use scroll::LE;
+    let bytes = [0u8, 0, 7, 0];
+    let err = bytes.pread_bounded::<u16, _>(2, 2..=4, LE).unwrap_err();
+    assert_eq!(err.to_string(), "value 7 at offset 2 is out of the range 2..=4");
+    let offset = &mut 2;
+    let err = bytes.gread_bounded::<u16, _>(offset, ..7, LE).unwrap_err();
+    assert_eq!(err.to_string(), "value 7 at offset 2 is out of the range ..7");
+}
+
+#[test]
+fn gread_inout_be_le() {
+    let bytes: Vec<u8> = (0..16).collect();
+    let mut be = [0u16; 8];
+    let mut le = [0u16; 8];
+    let offset = &mut 0;
+    bytes.gread_inout_be(offset, &mut be).unwrap();
+    assert_eq!(*offset, 16);
+    let offset = &mut 0;
+    bytes.gread_inout_le(offset, &mut le).unwrap();
+    assert_eq!(*offset, 16);
+    for i in 0..8 {
+        let manual = u16::from_ne_bytes([bytes[2 * i], bytes[2 * i + 1]]);
+        assert_eq!(be[i], u16::from_be(manual));
+        assert_eq!(le[i], u16::from_le(manual));
+        assert_eq!(be[i], le[i].swap_bytes());
+    }
+    let mut too_many = [0u32; 5];
+    assert!(bytes.gread_inout_be(&mut 0, &mut too_many).is_err());
+}
+
+#[test]
+fn gread_remaining() {
+    use scroll::PreadExt;
+    let bytes = [1u8, 2, 3, 4];
+    let offset = &mut 0;
+    assert_eq!(bytes.gread_remaining(offset).unwrap(), &[1, 2, 3, 4]);
+    assert_eq!(*offset, 4);
+    // exactly at the end is empty, not an error
+    assert_eq!(bytes.gread_remaining(offset).unwrap(), &[]);
+    assert_eq!(*offset, 4);
+    // one past the end is an error, and the offset is left alone
+    let offset = &mut 5;
+    assert!(bytes.gread_remaining(offset).is_err());
+    assert_eq!(*offset, 5);
+    let mut offset = usize::MAX;
+    assert!(bytes.gread_remaining(&mut offset).is_err());
+    assert!(bytes[..0].gread_remaining(&mut 0).unwrap().is_empty());
+}
+
+#[test]
+fn pread_remaining() {
+    use scroll::{PreadExt, BE};
+    let packet = [0x00u8, 0x01, 0x00, 0x03, 0xaa, 0xbb, 0xcc];
+    // a 4 byte header, then the payload
+    let offset = &mut 0;
+    let _kind: u16 = packet.gread_with(offset, BE).unwrap();
+    let len: u16 = packet.gread_with(offset, BE).unwrap();
+    assert_eq!(packet.pread_remaining(*offset).unwrap(), &[0xaa, 0xbb, 0xcc]);
+    assert_eq!(*offset, 4);
+    let payload = packet.gread_remaining(offset).unwrap();
+    assert_eq!(payload.len(), len as usize);
+    assert_eq!(*offset, packet.len());
+    assert!(packet.pread_remaining(packet.len()).unwrap().is_empty());
+    assert!(packet.pread_remaining(packet.len() + 1).is_err());
+}
+
+#[test]
+fn gread_inout_partial() {
+    use scroll::PreadExt;
+    let bytes = [1u8, 2, 3, 4, 5];
+    // a destination larger than what remains gets just the rest
+    let offset = &mut 3;
+    let mut dst = [0xffu8; 4];
+    assert_eq!(bytes.gread_inout_partial(offset, &mut dst).unwrap(), 2);
+    assert_eq!(dst, [4, 5, 0xff, 0xff]);
+    assert_eq!(*offset, 5);
+    // at the end, nothing is copied, and that's not an error
+    assert_eq!(bytes.gread_inout_partial(offset, &mut dst).unwrap(), 0);
+    assert_eq!(*offset, 5);
+    // exactly as many bytes as remain
+    let offset = &mut 1;
+    assert_eq!(bytes.gread_inout_partial(offset, &mut dst).unwrap(), 4);
+    assert_eq!(dst, [2, 3, 4, 5]);
+    assert_eq!(*offset, 5);
+    // an empty source
+    let offset = &mut 0;
+    assert_eq!(bytes[..0].gread_inout_partial(offset, &mut dst).unwrap(), 0);
+    assert_eq!(*offset, 0);
+    assert_eq!(dst, [2, 3, 4, 5]);
+    // past the end is still an error, and the offset is left alone
+    let offset = &mut 6;
+    assert!(matches!(bytes.gread_inout_partial(offset, &mut dst), Err(scroll::Error::BadOffset(6))));
+    assert_eq!(*offset, 6);
+}
+
+#[test]
+fn gread_line() {
+    use scroll::{ctx::LineCtx, Error, PreadExt};
+    let bytes = b"lf\ncrlf\r\n\n\r\nlone\rcr\nlast";
+    let offset = &mut 0;
+    assert_eq!(bytes.gread_line(offset).unwrap(), "lf");
+    assert_eq!(bytes.gread_line(offset).unwrap(), "crlf");
+    assert_eq!(*offset, 9);
+    // empty lines, either way
+    assert_eq!(bytes.gread_line(offset).unwrap(), "");
+    assert_eq!(bytes.gread_line(offset).unwrap(), "");
+    // only a \r right before the \n is stripped
+    assert_eq!(bytes.gread_line(offset).unwrap(), "lone\rcr");
+    // the last line ends with the source, unless that's not allowed
+    let strict = LineCtx { strip_cr: true, unterminated: false };
+    assert!(matches!(bytes.gread_line_with(offset, strict), Err(Error::Incomplete { needed: 1 })));
+    assert_eq!(*offset, 20);
+    assert_eq!(bytes.gread_line(offset).unwrap(), "last");
+    assert_eq!(*offset, bytes.len());
+    // with nothing left, there's no line to read
+    assert!(matches!(bytes.gread_line(offset), Err(Error::Incomplete { needed: 1 })));
+    assert!(matches!(bytes.gread_line(&mut 100), Err(Error::BadOffset(100))));
+
+    let keep_cr = LineCtx { strip_cr: false, unterminated: true };
+    assert_eq!(bytes.gread_line_with(&mut 3, keep_cr).unwrap(), "crlf\r");
+
+    // text which isn't UTF-8 is an error where it goes bad, unless read as bytes
+    let bytes = b"P6\nna\xefve\n\xff\x00";
+    let offset = &mut 3;
+    assert!(matches!(bytes.gread_line(offset), Err(Error::Invalid { offset: 5, .. })));
+    assert_eq!(*offset, 3);
+    assert_eq!(bytes.gread_line_bytes(offset, LineCtx::default()).unwrap(), b"na\xefve");
+    assert_eq!(bytes.gread_remaining(offset).unwrap(), b"\xff\x00");
+}
+
+#[test]
+fn gread_remaining_until() {
+    use scroll::PreadExt;
+    let bytes = [1u8, 2, 3, 4];
+    let offset = &mut 1;
+    assert_eq!(bytes.gread_remaining_until(offset, 1).unwrap(), &[]);
+    assert_eq!(*offset, 1);
+    assert_eq!(bytes.gread_remaining_until(offset, 3).unwrap(), &[2, 3]);
+    assert_eq!(*offset, 3);
+    assert_eq!(bytes.gread_remaining_until(offset, 4).unwrap(), &[4]);
+    assert_eq!(*offset, 4);
+    // an end before the offset, or past the source
+    let offset = &mut 3;
+    assert!(bytes.gread_remaining_until(offset, 2).is_err());
+    assert!(bytes.gread_remaining_until(offset, 5).is_err());
+    assert!(bytes.gread_remaining_until(offset, usize::MAX).is_err());
+    assert_eq!(*offset, 3);
+}
+
+#[test]
+fn pwrite_then_update_length() {
+    use scroll::{ctx, Endian, Pwrite, BE};
+    struct Handshake<'a> {
+        kind: u8,
+        body: &'a [u8],
+    }
+    impl<'a> ctx::TryIntoCtx<Endian> for Handshake<'a> {
+        type Error = scroll::Error;
+        fn try_into_ctx(self, dst: &mut [u8], endian: Endian) -> scroll::Result<usize> {
+            let offset = &mut 0;
+            dst.gwrite_with(self.kind, offset, endian)?;
+            dst.gwrite(self.body, offset)?;
+            Ok(*offset)
+        }
+    }
+
+    // a TLS-style record: content type, version, then a 2 byte length of the fragment which follows
+    let mut record = [0u8; 16];
+    let offset = &mut 0;
+    record.gwrite_with(0x16u8, offset, BE).unwrap();
+    record.gwrite_with(0x0303u16, offset, BE).unwrap();
+    let length_offset = *offset;
+    *offset += 2;
+    let size = record.pwrite_then_update_length::<_, u16, _>(length_offset, *offset, Handshake { kind: 1, body: b"hello" }, BE, BE).unwrap();
+    assert_eq!(size, 6);
+    assert_eq!(record.pread_with::<u16>(length_offset, BE).unwrap(), 6);
+    assert_eq!(&record[..11], b"\x16\x03\x03\x00\x06\x01hello");
+
+    // 300 bytes don't fit in a single byte length
+    let mut big = [0u8; 301];
+    assert!(big.pwrite_then_update_length::<_, u8, _>(0, 1, &[0xffu8; 300][..], (), BE).is_err());
+    assert!(big.pwrite_then_update_length::<_, u16, _>(0, 1, &[0xffu8; 300][..], (), BE).is_ok());
+    assert_eq!(big.pread_with::<u16>(0, BE).unwrap(), 300);
+}
+
+#[test]
+fn pread_window_rejects_lying_offsets() {
+    use scroll::{ctx, Endian, PreadExt, LE};
+    // a chunk whose header claims its payload is at some offset, with a length
+    #[derive(Debug)]
+    struct Chunk<'a> {
+        payload: &'a [u8],
+    }
+    impl<'a> ctx::TryFromCtx<'a, Endian> for Chunk<'a> {
+        type Error = scroll::Error;
+        fn try_from_ctx(src: &'a [u8], endian: Endian) -> scroll::Result<(Self, usize)> {
+            let offset: u16 = src.pread_with(0, endian)?;
+            let len: u16 = src.pread_with(2, endian)?;
+            let payload = src.pread_with::<&[u8]>(offset as usize, len as usize)?;
+            Ok((Chunk { payload }, src.len()))
+        }
+    }
+
+    // two 8 byte chunks back to back; the first one lies, pointing its 4 byte payload at offset 6
+    let bytes = [6, 0, 4, 0, 0xaa, 0xbb, 0xcc, 0xdd,
+                 4, 0, 4, 0, 0x11, 0x22, 0x33, 0x44];
+    // against the whole file, the lie "succeeds" and reads into the second chunk
+    let chunk: Chunk = bytes.pread_with(0, LE).unwrap();
+    assert_eq!(chunk.payload, &[0xcc, 0xdd, 4, 0]);
+    // but within its window, it is caught
+    let offset = &mut 0;
+    let first = bytes.gread_window(offset, 8).unwrap();
+    assert!(first.pread_with::<Chunk>(0, LE).is_err());
+    let second = bytes.gread_window(offset, 8).unwrap();
+    assert_eq!(second.pread_with::<Chunk>(0, LE).unwrap().payload, &[0x11, 0x22, 0x33, 0x44]);
+    assert_eq!(*offset, 16);
+
+    // windows nest, and their bounds are checked
+    let inner = second.pread_window(4, 4).unwrap().pread_window(2, 2).unwrap();
+    assert_eq!(inner, &[0x33, 0x44]);
+    assert!(inner.pread_window(0, 3).is_err());
+    assert!(inner.pread_window(3, 0).is_err());
+    assert!(inner.pread_window(2, 0).unwrap().is_empty());
+    assert!(bytes.pread_window(8, usize::MAX).is_err());
+    assert!(bytes.gread_window(offset, 1).is_err());
+    assert_eq!(*offset, 16);
+}
+
+#[test]
+fn gread_into_vec_until_eof() {
+    use scroll::{BE, LE};
+    let bytes: Vec<u8> = (1..=20).collect();
+    let offset = &mut 0;
+    let words: Vec<u32> = bytes.gread_into_vec_until_eof(offset, LE).unwrap();
+    assert_eq!(*offset, 20);
+    assert_eq!(words, [0x04030201, 0x08070605, 0x0c0b0a09, 0x100f0e0d, 0x14131211]);
+    // 20 bytes aren't a whole number of u64s, and the last 4 are cut short
+    let offset = &mut 0;
+    assert!(matches!(bytes.gread_into_vec_until_eof::<u64>(offset, BE), Err(scroll::Error::BadOffset(16))));
+    assert_eq!(*offset, 0);
+    assert!(matches!(bytes[..6].gread_into_vec_until_eof::<u32>(&mut 0, BE), Err(scroll::Error::BadOffset(4))));
+    // so is a buffer too short for even one
+    assert!(matches!(bytes[..3].gread_into_vec_until_eof::<u32>(&mut 0, BE), Err(scroll::Error::BadOffset(0))));
+    // a last element which fails for another reason keeps its error
+    let strings = b"abc\0\xff\0";
+    let err = strings.gread_into_vec_until_eof::<&str>(&mut 0, scroll::ctx::StrCtx::Delimiter(0)).unwrap_err();
+    assert!(matches!(err, scroll::Error::BadInput { msg: "invalid utf8", .. }));
+    // starting at the end is an empty vec
+    let offset = &mut 20;
+    assert!(bytes.gread_into_vec_until_eof::<u32>(offset, BE).unwrap().is_empty());
+}
+
+#[test]
+fn pwrite_copy_within() {
+    use scroll::{Error, PwriteExt};
+    // forward overlap: the destination is after the source
+    let mut bytes = [0u8, 1, 2, 3, 4, 5, 6, 7];
+    bytes.pwrite_copy_within(1..5, 3).unwrap();
+    assert_eq!(bytes, [0, 1, 2, 1, 2, 3, 4, 7]);
+    // backward overlap: the destination is before the source
+    let mut bytes = [0u8, 1, 2, 3, 4, 5, 6, 7];
+    bytes.pwrite_copy_within(3..8, 1).unwrap();
+    assert_eq!(bytes, [0, 3, 4, 5, 6, 7, 6, 7]);
+    // zero length, even at the very end
+    let mut bytes = [0u8, 1, 2, 3];
+    bytes.pwrite_copy_within(2..2, 4).unwrap();
+    bytes.pwrite_copy_within(4..4, 0).unwrap();
+    assert_eq!(bytes, [0, 1, 2, 3]);
+    // a destination running past the end
+    match bytes.pwrite_copy_within(0..2, 3) {
+        Err(Error::BadRange { start: 3, end: 5, len: 4 }) => (),
+        res => panic!("unexpected {:?}", res),
+    }
+    assert!(bytes.pwrite_copy_within(0..2, usize::MAX).is_err());
+    // bad sources
+    assert!(bytes.pwrite_copy_within(2..5, 0).is_err());
+    #[allow(clippy::reversed_empty_ranges)]
+    let reversed = bytes.pwrite_copy_within(3..1, 0);
+    assert!(reversed.is_err());
+    assert_eq!(bytes, [0, 1, 2, 3]);
+    let mut vec = vec![1u8, 2, 3];
+    vec.pwrite_copy_within(0..1, 2).unwrap();
+    assert_eq!(vec, [1, 2, 1]);
+}
+
+#[test]
+fn reserve_and_patch() {
+    use scroll::{ctx, Endian, Pwrite, Slot, BE};
+    // a RIFF-style chunk: a 4 byte id, a 4 byte length, and the payload
+    #[derive(Debug, PartialEq)]
+    struct Chunk<'a> {
+        id: &'a [u8],
+        data: &'a [u8],
+    }
+    impl<'a> ctx::TryFromCtx<'a, Endian> for Chunk<'a> {
+        type Error = scroll::Error;
+        fn try_from_ctx(src: &'a [u8], endian: Endian) -> scroll::Result<(Self, usize)> {
+            let offset = &mut 0;
+            let id = src.gread_with::<&[u8]>(offset, 4)?;
+            let len: u32 = src.gread_with(offset, endian)?;
+            let data = src.gread_with::<&[u8]>(offset, len as usize)?;
+            Ok((Chunk { id, data }, *offset))
+        }
+    }
+
+    let mut bytes = [0xffu8; 32];
+    let offset = &mut 0;
+    let mut slots: Vec<(Slot<u32, Endian>, usize)> = Vec::new();
+    for (id, data) in [(b"fmt ", &b"abc"[..]), (b"data", &b"0123456789"[..])].iter() {
+        bytes.gwrite(&id[..], offset).unwrap();
+        let len = bytes.reserve::<u32>(offset, BE).unwrap();
+        assert_eq!(bytes.pread_with::<u32>(len.offset(), BE).unwrap(), 0);
+        let start = *offset;
+        bytes.gwrite(*data, offset).unwrap();
+        slots.push((len, *offset - start));
+    }
+    assert_eq!(*offset, 29);
+    // patch them out of order, from the stored handles
+    for &(slot, len) in slots.iter().rev() {
+        assert_eq!(slot.patch(&mut bytes, len as u32).unwrap(), 4);
+    }
+
+    let offset = &mut 0;
+    assert_eq!(bytes.gread_with::<Chunk>(offset, BE).unwrap(), Chunk { id: b"fmt ", data: b"abc" });
+    assert_eq!(bytes.gread_with::<Chunk>(offset, BE).unwrap(), Chunk { id: b"data", data: b"0123456789" });
+    assert_eq!(*offset, 29);
+
+    // the bounds are checked when patching too
+    let (slot, _) = slots[1];
+    assert!(slot.patch(&mut bytes[..16], 0).is_err());
+    // and when reserving
+    assert!(bytes.reserve::<u64>(&mut 28, BE).is_err());
+    assert!(bytes.reserve::<u8>(&mut 32, BE).is_err());
+}
+
+#[test]
+fn pread_assert_eq() {
+    use scroll::{BE, LE};
+    let header = [0xca, 0xfe, 0x00, 0x01, 0x00, 0x02];
+    assert!(header.pread_assert_eq(2, 0x0001u16, BE).is_ok());
+    let err = header.pread_assert_eq(4, 0x0001u16, BE).unwrap_err();
+    assert_eq!(err.to_string(), "expected 1 at offset 4, found 2");
+    assert!(header.pread_assert_eq(4, 0x0002u16, BE).is_ok());
+    // the read itself failing
+    assert!(header.pread_assert_eq(5, 0x0002u16, BE).is_err());
+
+    let offset = &mut 0;
+    header.gread_assert_eq(offset, 0xfecau16, LE).unwrap();
+    header.gread_assert_eq(offset, 0x0001u16, BE).unwrap();
+    assert!(header.gread_assert_eq(offset, 0x0001u16, BE).is_err());
+    assert_eq!(*offset, 4);
+}
+
+#[test]
+fn incomplete() {
+    use scroll::{ctx::StrCtx, Error, Uleb128, BE, LE};
+    // a truncated u64 reports how much of it is missing, however far into it the source ends
+    let bytes = [1u8, 2, 3];
+    assert!(matches!(bytes.pread_with::<u64>(0, LE), Err(Error::Incomplete { needed: 5 })));
+    assert!(matches!(bytes.pread_with::<u64>(3, LE), Err(Error::Incomplete { needed: 8 })));
+    // past the end is still a bad offset
+    assert!(matches!(bytes.pread_with::<u64>(4, LE), Err(Error::BadOffset(4))));
+
+    // a ULEB128 cut mid-encoding needs at least one more byte
+    let bytes = [0x80u8, 0x80];
+    assert!(matches!(bytes.pread::<Uleb128>(0), Err(Error::Incomplete { needed: 1 })));
+    assert!(matches!(Uleb128::read_u128(&bytes, &mut 0), Err(Error::Incomplete { needed: 1 })));
+
+    // a length prefixed string cut mid-payload; the offset is left alone, so the read can be retried once more input arrives
+    let mut stream = vec![0u8, 5, b'h', b'e'];
+    let offset = &mut 0;
+    let len: u16 = stream.gread_with(offset, BE).unwrap();
+    let res = stream.gread_with::<&str>(offset, StrCtx::Length(len as usize));
+    assert!(matches!(res, Err(Error::Incomplete { needed: 3 })));
+    assert_eq!(*offset, 2);
+    stream.extend_from_slice(b"llo");
+    assert_eq!(stream.gread_with::<&str>(offset, StrCtx::Length(len as usize)).unwrap(), "hello");
+    assert_eq!(*offset, 7);
+    // zero sized reads at the very end succeed
+    assert_eq!(stream.pread_with::<&[u8]>(7, 0).unwrap(), b"");
+    assert!(matches!(stream.pread_with::<&[u8]>(7, 2), Err(Error::Incomplete { needed: 2 })));
+}
+
+#[test]
+fn gread_terminated() {
+    use scroll::{ctx::{TerminatedCtx, TerminatedIter}, Error, LE};
+    let is_null = |e: &u16| *e == 0;
+    let bytes = [0x01, 0x00, 0x02, 0x00, 0x03, 0x00, 0x00, 0x00, 0xaa];
+    let offset = &mut 0;
+    let entries: Vec<u16> = bytes.gread_terminated(offset, TerminatedCtx::new(LE, is_null, 3)).unwrap();
+    assert_eq!(entries, [1, 2, 3]);
+    assert_eq!(*offset, 8);
+    let ctx = TerminatedCtx { keep_terminator: true, ..TerminatedCtx::new(LE, is_null, 3) };
+    assert_eq!(bytes.gread_terminated::<u16, _>(&mut 0, ctx).unwrap(), [1, 2, 3, 0]);
+
+    // the cap on the number of elements
+    let offset = &mut 0;
+    let res = bytes.gread_terminated::<u16, _>(offset, TerminatedCtx::new(LE, is_null, 2));
+    assert!(matches!(res, Err(Error::BadInput { .. })));
+    assert_eq!(*offset, 0);
+    // running out of source before the terminator, whether on an element boundary or not
+    let res = bytes[..6].gread_terminated::<u16, _>(&mut 0, TerminatedCtx::new(LE, is_null, 8));
+    assert!(matches!(res, Err(Error::Incomplete { needed: 2 })));
+    let res = bytes[..5].gread_terminated::<u16, _>(&mut 0, TerminatedCtx::new(LE, is_null, 8));
+    assert!(matches!(res, Err(Error::Incomplete { needed: 1 })));
+
+    // and lazily
+    let iter: TerminatedIter<u16, _, _> = TerminatedIter::new(&bytes, TerminatedCtx::new(LE, is_null, 3));
+    assert_eq!(iter.collect::<Result<Vec<_>>>().unwrap(), [1, 2, 3]);
+    let mut iter: TerminatedIter<u16, _, _> = TerminatedIter::new(&bytes, TerminatedCtx::new(LE, is_null, 1));
+    assert_eq!(iter.next().unwrap().unwrap(), 1);
+    assert!(matches!(iter.next(), Some(Err(Error::BadInput { .. }))));
+    assert!(iter.next().is_none());
+    let mut iter: TerminatedIter<u16, _, _> = TerminatedIter::new(&bytes[..3], TerminatedCtx::new(LE, is_null, 8));
+    assert_eq!(iter.next().unwrap().unwrap(), 1);
+    assert!(matches!(iter.next(), Some(Err(Error::Incomplete { needed: 1 }))));
+    assert!(iter.next().is_none());
+    assert_eq!(iter.offset(), 2);
+}
+
+#[test]
+fn pread_gread_spanned() {
+    use scroll::{ctx::TryFromCtx, Endian, Uleb128, LE};
+    // a variable length value
+    let bytes = [0x7f, 0x80, 0x01, 0x00];
+    let offset = &mut 0;
+    let (a, span) = bytes.gread_spanned::<Uleb128>(offset, ()).unwrap();
+    assert_eq!((u64::from(a), span), (0x7f, 0..1));
+    let (b, span) = bytes.gread_spanned::<Uleb128>(offset, ()).unwrap();
+    assert_eq!((u64::from(b), span), (0x80, 1..3));
+    assert_eq!(*offset, 3);
+    assert_eq!(bytes.pread_spanned::<Uleb128>(3, ()).unwrap().1, 3..4);
+
+    // a nested struct, whose fields report their own spans
+    #[derive(Debug, PartialEq)]
+    struct Entry {
+        tag: u8,
+        value: u32,
+    }
+    impl<'a> TryFromCtx<'a, Endian> for Entry {
+        type Error = scroll::Error;
+        fn try_from_ctx(src: &'a [u8], endian: Endian) -> Result<(Self, usize)> {
+            let offset = &mut 0;
+            let tag = src.gread(offset)?;
+            let (value, span) = src.gread_spanned(offset, endian)?;
+            assert_eq!(span, 1..5);
+            Ok((Entry { tag, value }, *offset))
+        }
+    }
+    let bytes = [0xff, 0xff, 0x01, 0x78, 0x56, 0x34, 0x12, 0xff];
+    let (entry, span) = bytes.pread_spanned::<Entry>(2, LE).unwrap();
+    assert_eq!(entry, Entry { tag: 1, value: 0x1234_5678 });
+    assert_eq!(span, 2..7);
+    // and nothing is consumed on error
+    let offset = &mut 4;
+    assert!(bytes.gread_spanned::<Entry>(offset, LE).is_err());
+    assert_eq!(*offset, 4);
+}
+
+#[test]
+fn cond_ctx() {
+    use std::cell::Cell;
+    use std::convert::TryFrom;
+    use scroll::{ctx::{Chosen, CondCtx, TryFromCtx, TryIntoCtx}, Endian, Error, Pwrite, BE, LE};
+
+    /// A file offset, stored in 32 or 64 bits depending on the file's class
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    struct FileOffset(u64);
+    #[derive(Copy, Clone)]
+    struct Narrow(Endian);
+    #[derive(Copy, Clone)]
+    struct Wide(Endian);
+    impl<'a> TryFromCtx<'a, Narrow> for FileOffset {
+        type Error = Error;
+        fn try_from_ctx(src: &'a [u8], Narrow(endian): Narrow) -> Result<(Self, usize)> {
+            Ok((FileOffset(u64::from(src.pread_with::<u32>(0, endian)?)), 4))
+        }
+    }
+    impl<'a> TryFromCtx<'a, Wide> for FileOffset {
+        type Error = Error;
+        fn try_from_ctx(src: &'a [u8], Wide(endian): Wide) -> Result<(Self, usize)> {
+            Ok((FileOffset(src.pread_with(0, endian)?), 8))
+        }
+    }
+    impl TryIntoCtx<Narrow> for FileOffset {
+        type Error = Error;
+        fn try_into_ctx(self, dst: &mut [u8], Narrow(endian): Narrow) -> Result<usize> {
+            let narrow = u32::try_from(self.0).map_err(|_| Error::BadInput { size: 8, msg: "offset needs 64 bits" })?;
+            dst.pwrite_with(narrow, 0, endian)
+        }
+    }
+    impl TryIntoCtx<Wide> for FileOffset {
+        type Error = Error;
+        fn try_into_ctx(self, dst: &mut [u8], Wide(endian): Wide) -> Result<usize> {
+            dst.pwrite_with(self.0, 0, endian)
+        }
+    }
+
+    // the flag is read each time, so the same context follows it as it changes
+    let is_64 = Cell::new(false);
+    let ctx = CondCtx { cond: || is_64.get(), then_ctx: Wide(LE), else_ctx: Narrow(LE) };
+    let bytes = [0x78, 0x56, 0x34, 0x12, 0xf0, 0xde, 0xbc, 0x9a];
+    let offset = &mut 0;
+    assert_eq!(bytes.gread_with::<Chosen<FileOffset>>(offset, ctx).unwrap(), Chosen(FileOffset(0x1234_5678)));
+    assert_eq!(*offset, 4);
+    is_64.set(true);
+    let offset = &mut 0;
+    assert_eq!(bytes.gread_with::<Chosen<FileOffset>>(offset, ctx).unwrap(), Chosen(FileOffset(0x9abc_def0_1234_5678)));
+    assert_eq!(*offset, 8);
+    assert!(bytes.pread_with::<Chosen<FileOffset>>(1, ctx).is_err());
+    is_64.set(false);
+    assert_eq!(bytes.pread_with::<Chosen<FileOffset>>(4, ctx).unwrap(), Chosen(FileOffset(0x9abc_def0)));
+
+    let mut out = [0u8; 8];
+    assert_eq!(out.pwrite_with(Chosen(FileOffset(0x1234_5678)), 0, ctx).unwrap(), 4);
+    assert!(out.pwrite_with(Chosen(FileOffset(1 << 32)), 0, ctx).is_err());
+    is_64.set(true);
+    assert_eq!(out.pwrite_with(Chosen(FileOffset(1 << 32)), 0, CondCtx { then_ctx: Wide(BE), ..ctx }).unwrap(), 8);
+    assert_eq!(out, [0, 0, 0, 1, 0, 0, 0, 0]);
+}
+
+#[test]
+fn pread_gread_validated() {
+    use scroll::{ctx::{IterCtx, TryFromCtx, TryFromCtxIter, Validate, Validated}, Endian, Error, LE};
+    #[derive(Debug, PartialEq)]
+    struct Header {
+        size: u16,
+        header_len: u16,
+    }
+    impl<'a> TryFromCtx<'a, Endian> for Header {
+        type Error = scroll::Error;
+        fn try_from_ctx(src: &'a [u8], endian: Endian) -> Result<(Self, usize)> {
+            let offset = &mut 0;
+            let size = src.gread_with(offset, endian)?;
+            let header_len = src.gread_with(offset, endian)?;
+            Ok((Header { size, header_len }, *offset))
+        }
+    }
+    impl Validate<Endian> for Header {
+        fn validate(&self, _endian: &Endian) -> std::result::Result<(), &'static str> {
+            if self.size >= self.header_len {
+                Ok(())
+            } else {
+                Err("size is smaller than header_len")
+            }
+        }
+    }
+
+    let bytes = [0x08, 0x00, 0x04, 0x00, 0x02, 0x00, 0x04, 0x00];
+    let offset = &mut 0;
+    let header: Header = bytes.gread_validated(offset, LE).unwrap();
+    assert_eq!(header, Header { size: 8, header_len: 4 });
+    assert_eq!(*offset, 4);
+    // the error carries the offset the struct started at, and the offset isn't advanced
+    let res = bytes.gread_validated::<Header>(offset, LE);
+    assert!(matches!(res, Err(Error::Invalid { offset: 4, msg: "size is smaller than header_len" })));
+    assert_eq!(*offset, 4);
+    // parse errors come first
+    assert!(matches!(bytes.pread_validated::<Header>(6, LE), Err(Error::Incomplete { .. })));
+    // the plain read doesn't validate
+    assert!(bytes.pread_with::<Header>(4, LE).is_ok());
+
+    // the wrapper validates wherever it is read generically
+    let iter: TryFromCtxIter<Validated<Header>, _> = bytes.pread_with(0, IterCtx(LE)).unwrap();
+    let res: Vec<_> = iter.collect();
+    assert!(res[0].is_ok());
+    assert!(matches!(res[1], Err(Error::Invalid { offset: 0, .. })));
+}
+
+#[test]
+fn split_ctx_tlv() {
+    use scroll::{ctx::{HasLen, SplitCtx, TryFromCtx}, Endian, Error, LE};
+    // a type-length-value record, whose value is a list of u16s
+    #[derive(Debug, PartialEq)]
+    struct Tl {
+        tag: u8,
+        len: u8,
+    }
+    impl<'a> TryFromCtx<'a, Endian> for Tl {
+        type Error = scroll::Error;
+        fn try_from_ctx(src: &'a [u8], _: Endian) -> Result<(Self, usize)> {
+            Ok((Tl { tag: src.pread(0)?, len: src.pread(1)? }, 2))
+        }
+    }
+    impl HasLen for Tl {
+        fn payload_len(&self) -> usize {
+            self.len as usize
+        }
+    }
+    #[derive(Debug, PartialEq)]
+    struct Values(Vec<u16>);
+    impl<'a> TryFromCtx<'a, (usize, Endian)> for Values {
+        type Error = scroll::Error;
+        fn try_from_ctx(src: &'a [u8], (len, endian): (usize, Endian)) -> Result<(Self, usize)> {
+            assert_eq!(src.len(), len);
+            Ok((Values(src.gread_into_vec_until_eof(&mut 0, endian)?), len))
+        }
+    }
+
+    let bytes = [0x07, 0x04, 0x01, 0x00, 0x02, 0x00, 0x09, 0x02, 0xff, 0xff];
+    let offset = &mut 0;
+    let (tl, values): (Tl, Values) = bytes.gread_with(offset, SplitCtx::new(LE)).unwrap();
+    assert_eq!(tl, Tl { tag: 7, len: 4 });
+    assert_eq!(values, Values(vec![1, 2]));
+    assert_eq!(*offset, 6);
+    let (tl, values): (Tl, Values) = bytes.gread_with(offset, SplitCtx::new(LE)).unwrap();
+    assert_eq!((tl.tag, values), (9, Values(vec![0xffff])));
+    assert_eq!(*offset, 10);
+
+    // the payload runs past the end of the source
+    let res = bytes[..5].pread_with::<(Tl, Values)>(0, SplitCtx::new(LE));
+    assert!(matches!(res, Err(Error::Incomplete { needed: 1 })));
+    // the payload can't read beyond the extent the header gives it
+    let bytes = [0x07, 0x03, 0x01, 0x00, 0x02, 0x00];
+    assert!(bytes.pread_with::<(Tl, Values)>(0, SplitCtx::new(LE)).is_err());
+}
+
+#[test]
+fn pread_gread_magic() {
+    use scroll::{Error, PreadExt, BE, LE};
+    let bytes = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR";
+    let offset = &mut 0;
+    bytes.gread_magic(offset, b"\x89PNG\r\n\x1a\n").unwrap();
+    assert_eq!(*offset, 8);
+    bytes.gread_magic_u32(offset, 13, BE).unwrap();
+    assert_eq!(*offset, 12);
+
+    // a mismatch leaves the offset alone
+    let res = bytes.gread_magic(offset, b"IHDX");
+    match res {
+        Err(Error::BadMagic { offset: 12, expected: 0x4948_4458, found: 0x4948_4452 }) => (),
+        res => panic!("unexpected {:?}", res),
+    }
+    assert_eq!(*offset, 12);
+    match bytes.pread_magic_u32(8, 13, LE) {
+        Err(Error::BadMagic { offset: 8, expected: 13, found: 0x0d00_0000 }) => (),
+        res => panic!("unexpected {:?}", res),
+    }
+
+    // a magic running past the end is a bad offset, not a bad magic
+    assert!(matches!(bytes.pread_magic(14, b"HDR!"), Err(Error::BadOffset(14))));
+    assert!(matches!(bytes.pread_magic_u32(14, 0, LE), Err(Error::BadOffset(14))));
+    assert!(matches!(bytes.pread_magic(usize::MAX, b"I"), Err(Error::BadOffset(_))));
+    // magics are at most 8 bytes
+    assert!(matches!(bytes.pread_magic(0, &bytes[..9]), Err(Error::BadInput { .. })));
+}
+
+#[test]
+fn pread_gread_try_into() {
+    use std::convert::TryFrom;
+    use scroll::{CombinedError, Error, BE, LE};
+    #[derive(Debug, PartialEq)]
+    enum Opcode {
+        Nop,
+        Jump,
+    }
+    impl TryFrom<u8> for Opcode {
+        type Error = &'static str;
+        fn try_from(n: u8) -> std::result::Result<Self, Self::Error> {
+            match n {
+                0x90 => Ok(Opcode::Nop),
+                0xeb => Ok(Opcode::Jump),
+                _ => Err("unknown opcode"),
+            }
+        }
+    }
+
+    let bytes = [0x90u8, 0xeb, 0x0f];
+    let offset = &mut 0;
+    assert_eq!(bytes.gread_try_into::<u8, Opcode>(offset, LE).unwrap(), Opcode::Nop);
+    assert_eq!(bytes.gread_try_into::<u8, Opcode>(offset, LE).unwrap(), Opcode::Jump);
+    assert_eq!(*offset, 2);
+    // the conversion failing, which leaves the offset alone
+    assert!(matches!(bytes.gread_try_into::<u8, Opcode>(offset, LE), Err(CombinedError::Convert("unknown opcode"))));
+    assert_eq!(*offset, 2);
+    // the parse failing
+    assert!(matches!(bytes.pread_try_into::<u8, Opcode>(3, LE), Err(CombinedError::Parse(Error::Incomplete { .. }))));
+    assert!(matches!(bytes.pread_try_into::<u8, Opcode>(4, LE), Err(CombinedError::Parse(Error::BadOffset(4)))));
+    // with std conversions, and a context
+    assert_eq!(bytes.pread_try_into::<u16, u8>(1, BE).unwrap_err().to_string(),
+               "conversion failed: out of range integral type conversion attempted");
+    assert_eq!(bytes.pread_try_into::<u16, i32>(1, BE).unwrap(), 0xeb0f);
+}
+
+#[test]
+fn gwrite_gread_align() {
+    use scroll::{PreadExt, Pwrite, PwriteExt, BE};
+    // records of a name, then a u32 aligned to 4, then 16 byte aligned blobs, as in a flash image padded with 0xff
+    let records: [(&str, u32, &[u8]); 3] = [("a", 1, b"xyz"), ("bcdef", 2, b""), ("gh", 3, b"0123456789abcdefg")];
+    let mut image = [0u8; 128];
+    let offset = &mut 0;
+    for &(name, n, blob) in records.iter() {
+        image.gwrite(name, offset).unwrap();
+        image.gwrite_align(offset, 4, 0xff).unwrap();
+        image.gwrite_with(n, offset, BE).unwrap();
+        image.gwrite_align(offset, 16, 0xa5).unwrap();
+        image.gwrite(blob, offset).unwrap();
+        image.gwrite_align(offset, 16, 0xff).unwrap();
+    }
+    let end = *offset;
+    assert_eq!(end, 96);
+    assert_eq!(image[1..4], [0xff; 3]);
+    assert_eq!(image[8..16], [0xa5; 8]);
+
+    let offset = &mut 0;
+    for &(name, n, blob) in records.iter() {
+        assert_eq!(image.gread_with::<&str>(offset, scroll::ctx::StrCtx::Length(name.len())).unwrap(), name);
+        let skipped = image.gread_align(offset, 4).unwrap();
+        assert!(image[*offset - skipped..*offset].iter().all(|b| *b == 0xff));
+        assert_eq!(image.gread_with::<u32>(offset, BE).unwrap(), n);
+        image.gread_align(offset, 16).unwrap();
+        assert_eq!(image.gread_with::<&[u8]>(offset, blob.len()).unwrap(), blob);
+        image.gread_align(offset, 16).unwrap();
+    }
+    assert_eq!(*offset, end);
+
+    // the padding must fit, and the alignment be a power of two
+    let offset = &mut 125;
+    assert!(image[..126].gwrite_align(offset, 8, 0).is_err());
+    assert_eq!(image[..126].gread_align(offset, 8).unwrap_err().to_string(), "bad offset 128");
+    assert_eq!(*offset, 125);
+    assert_eq!(image.gread_align(offset, 8).unwrap(), 3);
+    assert!(image.gwrite_align(offset, 3, 0).is_err());
+    assert!(image.gread_align(&mut { usize::MAX }, 4).is_err());
+    assert_eq!(image.gwrite_align(offset, 1, 0).unwrap(), 0);
+}
+
+#[test]
+fn look_ahead_ctx() {
+    use scroll::{ctx::{LookAhead, LookAheadCtx, TryFromCtx}, BE};
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    enum Kind {
+        Control,
+        Data,
+    }
+    #[derive(Debug, PartialEq)]
+    enum Packet<'a> {
+        Control { opcode: u8 },
+        Data { seq: u16, payload: &'a [u8] },
+    }
+    impl<'a> TryFromCtx<'a, Kind> for Packet<'a> {
+        type Error = scroll::Error;
+        fn try_from_ctx(src: &'a [u8], kind: Kind) -> Result<(Self, usize)> {
+            let offset = &mut 0;
+            let tag: u8 = src.gread(offset)?;
+            let packet = match kind {
+                Kind::Control => {
+                    assert_eq!(tag, 0xc0);
+                    Packet::Control { opcode: src.gread(offset)? }
+                },
+                Kind::Data => {
+                    let seq = src.gread_with(offset, BE)?;
+                    let len: u8 = src.gread(offset)?;
+                    Packet::Data { seq, payload: src.gread_with(offset, len as usize)? }
+                },
+            };
+            Ok((packet, *offset))
+        }
+    }
+
+    let ctx = LookAheadCtx::new(BE, |tag: u8| if tag & 0xc0 == 0xc0 { Kind::Control } else { Kind::Data });
+    let bytes = [0xc0, 0x07, 0x01, 0x00, 0x2a, 0x02, 0xaa, 0xbb, 0xc0];
+    let offset = &mut 0;
+    let LookAhead(control) = bytes.gread_with::<LookAhead<Packet>>(offset, ctx).unwrap();
+    assert_eq!(control, Packet::Control { opcode: 7 });
+    assert_eq!(*offset, 2);
+    let LookAhead(data) = bytes.gread_with::<LookAhead<Packet>>(offset, ctx).unwrap();
+    assert_eq!(data, Packet::Data { seq: 0x2a, payload: &[0xaa, 0xbb] });
+    assert_eq!(*offset, 8);
+    // the tag is there, but the rest of the packet isn't
+    assert!(bytes.gread_with::<LookAhead<Packet>>(offset, ctx).is_err());
+    assert_eq!(*offset, 8);
+}
+
+#[test]
+fn box_rc_arc() {
+    use std::rc::Rc;
+    use std::sync::Arc;
+    use scroll::{ctx::{TryFromCtx, TryIntoCtx}, Endian, Pwrite, BE, LE};
+    // a cons-list: a value, then a flag saying whether another node follows
+    #[derive(Debug, PartialEq)]
+    struct Node {
+        value: u32,
+        next: Option<Box<Node>>,
+    }
+    impl<'a> TryFromCtx<'a, Endian> for Node {
+        type Error = scroll::Error;
+        fn try_from_ctx(src: &'a [u8], ctx: Endian) -> Result<(Self, usize)> {
+            let offset = &mut 0;
+            let value = src.gread_with(offset, ctx)?;
+            let next = match src.gread::<u8>(offset)? {
+                0 => None,
+                _ => Some(src.gread_with::<Box<Node>>(offset, ctx)?),
+            };
+            Ok((Node { value, next }, *offset))
+        }
+    }
+    impl TryIntoCtx<Endian> for Node {
+        type Error = scroll::Error;
+        fn try_into_ctx(self, dst: &mut [u8], ctx: Endian) -> Result<usize> {
+            let offset = &mut 0;
+            dst.gwrite_with(self.value, offset, ctx)?;
+            dst.gwrite(self.next.is_some() as u8, offset)?;
+            if let Some(next) = self.next {
+                dst.gwrite_with(next, offset, ctx)?;
+            }
+            Ok(*offset)
+        }
+    }
+
+    let list = Node { value: 1, next: Some(Box::new(Node { value: 2, next: Some(Box::new(Node { value: 3, next: None })) })) };
+    let mut bytes = [0u8; 15];
+    assert_eq!(bytes.pwrite_with(list, 0, LE).unwrap(), 15);
+    assert_eq!(bytes, [1, 0, 0, 0, 1, 2, 0, 0, 0, 1, 3, 0, 0, 0, 0]);
+    let offset = &mut 0;
+    let node: Node = bytes.gread_with(offset, LE).unwrap();
+    assert_eq!(*offset, 15);
+    assert_eq!(node.next.as_ref().and_then(|n| n.next.as_ref()).map(|n| n.value), Some(3));
+    let boxed: Box<Node> = bytes.pread_with(5, LE).unwrap();
+    assert_eq!(boxed.value, 2);
+    // a truncated list
+    assert!(bytes[..14].pread_with::<Node>(0, LE).is_err());
+
+    // shared pointers read the same, and write by reference
+    let rc: Rc<u16> = bytes.pread_with(0, BE).unwrap();
+    assert_eq!(*rc, 0x0100);
+    let arc: Arc<u32> = bytes.pread_with(5, LE).unwrap();
+    assert_eq!(*arc, 2);
+    let mut out = [0u8; 6];
+    let offset = &mut 0;
+    out.gwrite_with(rc.clone(), offset, BE).unwrap();
+    out.gwrite_with(arc, offset, LE).unwrap();
+    assert_eq!(out, [1, 0, 2, 0, 0, 0]);
+    assert_eq!(Rc::strong_count(&rc), 1);
+}
+
+#[test]
+fn gread_exactly() {
+    use scroll::{Error, PreadExt};
+    let bytes = [1u8, 2, 3, 4, 5, 6, 7, 8, 9];
+    let offset = &mut 0;
+    assert_eq!(bytes.gread_exactly(offset, 2).unwrap(), &[1, 2]);
+    assert_eq!(*offset, 2);
+    assert_eq!(bytes.gread_exactly(offset, 4).unwrap(), &[3, 4, 5, 6]);
+    assert_eq!(*offset, 6);
+    assert_eq!(bytes.gread_exactly(offset, 3).unwrap(), &[7, 8, 9]);
+    assert_eq!(*offset, 9);
+    // zero bytes at the end are fine, but any more are incomplete
+    assert!(bytes.gread_exactly(offset, 0).unwrap().is_empty());
+    assert!(matches!(bytes.gread_exactly(offset, 1), Err(Error::Incomplete { needed: 1 })));
+    assert!(matches!(bytes.gread_exactly(&mut 7, usize::MAX), Err(Error::Incomplete { .. })));
+    assert!(matches!(bytes.gread_exactly(&mut 10, 0), Err(Error::BadOffset(10))));
+    assert_eq!(*offset, 9);
+}
+
+#[test]
+fn limits_depth() {
+    use scroll::{ctx::{Limited, Limits, TryFromCtx}, Endian, Error, LE};
+    // a chain of nodes, each giving the relative offset of the next one, or 0xff at the end of the chain
+    #[derive(Debug)]
+    struct Node {
+        value: u16,
+        next: Option<Box<Node>>,
+    }
+    impl<'a> TryFromCtx<'a, Limited<Endian>> for Node {
+        type Error = scroll::Error;
+        fn try_from_ctx(src: &'a [u8], ctx: Limited<Endian>) -> Result<(Self, usize)> {
+            let value = src.pread_with(0, ctx.ctx)?;
+            let next = match src.pread::<u8>(2)? {
+                0xff => None,
+                // reading a `Box` descends a level
+                delta => Some(src.pread_with::<Box<Node>>(delta as usize, ctx)?),
+            };
+            Ok((Node { value, next }, 3))
+        }
+    }
+    let ctx = Limited::new(LE, Limits::new(8, 1024));
+
+    let chain = [1, 0, 3, 2, 0, 3, 3, 0, 0xff];
+    let node: Node = chain.pread_with(0, ctx).unwrap();
+    let mut values = vec![node.value];
+    let mut next = &node.next;
+    while let Some(node) = next {
+        values.push(node.value);
+        next = &node.next;
+    }
+    assert_eq!(values, [1, 2, 3]);
+
+    // a node pointing at itself terminates only because of the depth limit
+    let cycle = [1, 0, 0];
+    assert!(matches!(cycle.pread_with::<Node>(0, ctx), Err(Error::LimitExceeded("depth"))));
+    // as does a chain deeper than the limit
+    assert!(chain.pread_with::<Node>(0, Limited::new(LE, Limits::new(1, 1024))).is_err());
+    assert!(chain.pread_with::<Node>(0, Limited::new(LE, Limits::new(2, 1024))).is_ok());
+
+    // untrusted lengths are checked against the byte budget
+    let limits = Limits::new(1, 16);
+    assert!(limits.check_bytes(16).is_ok());
+    assert_eq!(limits.check_bytes(17).unwrap_err().to_string(), "bytes limit exceeded");
+    assert!(limits.descend().unwrap().descend().is_err());
+}
+
+#[test]
+fn limits_vec() {
+    use scroll::{ctx::{Count, Limited, Limits, TryFromCtx}, Endian, Error, LE};
+    // a tree: a count of children, then each child
+    #[derive(Debug)]
+    struct Tree(Vec<Tree>);
+    impl<'a> TryFromCtx<'a, Limited<Endian>> for Tree {
+        type Error = scroll::Error;
+        fn try_from_ctx(src: &'a [u8], ctx: Limited<Endian>) -> Result<(Self, usize)> {
+            let offset = &mut 0;
+            let count: u8 = src.gread(offset)?;
+            // reading a `Vec` descends a level
+            let children = src.gread_with(offset, Limited::new(Count(count as usize, ctx.ctx), ctx.limits))?;
+            Ok((Tree(children), *offset))
+        }
+    }
+    // every tree reads its children, even none, a level deeper, so three levels of trees need a depth of three
+    let tree: Tree = [2u8, 1, 0, 0].pread_with(0, Limited::new(LE, Limits::new(3, 1024))).unwrap();
+    assert_eq!(tree.0.len(), 2);
+    assert_eq!(tree.0[0].0.len(), 1);
+    let err = [1u8, 1, 1, 0].pread_with::<Tree>(0, Limited::new(LE, Limits::new(3, 1024))).unwrap_err();
+    assert!(matches!(err, Error::Element { index: 0, offset: 0, ref source } if matches!(**source, Error::Element { .. })));
+    assert!(err.to_string().contains("depth limit exceeded"));
+    // room for the elements is checked against the byte budget before reading any
+    let ctx = Limited::new(Count(2, LE), Limits::new(2, 2 * std::mem::size_of::<Tree>() - 1));
+    assert!(matches!([0u8, 0].pread_with::<Vec<Tree>>(0, ctx), Err(Error::LimitExceeded("bytes"))));
+}
+
+#[test]
+fn pwrite_with_length_prefix() {
+    use scroll::{Pwrite, BE, LE};
+    let mut bytes = [0u8; 16];
+    let offset = &mut 0;
+    assert_eq!(bytes.pwrite_with_length_prefix::<_, u8>(0xdeadbeef_u32, offset, BE).unwrap(), 5);
+    assert_eq!(bytes[..5], [4, 0xde, 0xad, 0xbe, 0xef]);
+    assert_eq!(*offset, 5);
+    assert_eq!(bytes.pwrite_with_length_prefix::<_, u32>(0x0102_u16, offset, LE).unwrap(), 6);
+    assert_eq!(bytes[5..11], [2, 0, 0, 0, 0x02, 0x01]);
+    assert_eq!(*offset, 11);
+    // the value doesn't fit, and neither does the prefix
+    assert!(bytes.pwrite_with_length_prefix::<_, u16>(0u64, offset, LE).is_err());
+    assert!(bytes.pwrite_with_length_prefix::<_, u8>(0u8, &mut 16, LE).is_err());
+    assert!(bytes.pwrite_with_length_prefix::<_, u8>(0u8, &mut { usize::MAX }, LE).is_err());
+    assert_eq!(*offset, 11);
+}
+
+#[test]
+fn counter_size_of_value() {
+    use scroll::{ctx::{Str, StrCtx, TryIntoCtx}, size_of_value, Counter, Endian, Pwrite, Uleb128, LE};
+    #[derive(Clone)]
+    struct Entry<'a> {
+        id: u128,
+        name: &'a str,
+        flags: u16,
+    }
+    impl<'a> TryIntoCtx<Endian> for Entry<'a> {
+        type Error = scroll::Error;
+        fn try_into_ctx(self, dst: &mut [u8], ctx: Endian) -> Result<usize> {
+            let offset = &mut 0;
+            Uleb128::write_u128(dst, offset, self.id)?;
+            dst.gwrite_with(Str(self.name), offset, StrCtx::Delimiter(0))?;
+            dst.gwrite_with(self.flags, offset, ctx)?;
+            Ok(*offset)
+        }
+    }
+
+    let entries = [
+        Entry { id: 5, name: "a", flags: 1 },
+        Entry { id: 624485, name: "a somewhat longer name, longer than the initial scratch buffer of the counter", flags: 2 },
+        Entry { id: u128::MAX, name: "", flags: 3 },
+    ];
+    assert_eq!(size_of_value(&entries[0], LE).unwrap(), 1 + 2 + 2);
+    assert_eq!(size_of_value(&entries[2], LE).unwrap(), 19 + 1 + 2);
+
+    let mut counter = Counter::new();
+    for entry in entries.iter() {
+        counter.gwrite_with(entry.clone(), LE).unwrap();
+        counter.gwrite_align(4).unwrap();
+    }
+    let mut bytes = vec![0u8; counter.total()];
+    let offset = &mut 0;
+    for entry in entries.iter() {
+        bytes.gwrite_with(entry.clone(), offset, LE).unwrap();
+        *offset = scroll::offset::align_up(*offset, 4).unwrap();
+    }
+    assert_eq!(*offset, bytes.len());
+    assert_eq!(bytes.len(), 8 + 84 + 24);
+
+    // values beyond the limit, and genuine errors, are reported
+    assert!(matches!(Counter::with_limit(16).gwrite_with(entries[1].clone(), LE), Err(scroll::Error::LimitExceeded("size"))));
+    assert!(matches!(Counter::new().gwrite_with(Str("abc"), StrCtx::Length(2)), Err(scroll::Error::BadInput { .. })));
+    assert!(Counter::new().gwrite_align(3).is_err());
+
+    // at fixed offsets, and through std::io::Write
+    use scroll::IOwrite;
+    use std::io::Write;
+    let mut counter = Counter::new();
+    counter.pwrite_with(entries[0].clone(), 0x20, LE).unwrap();
+    assert_eq!(counter.pwrite_with(0u32, 0x4, LE).unwrap(), 4);
+    assert_eq!(counter.total(), 0x25);
+    counter.write_all(b"trailer").unwrap();
+    counter.iowrite_with(1u64, LE).unwrap();
+    assert_eq!(counter.total(), 0x25 + 7 + 8);
+}
+
+#[test]
+fn pread_into_existing() {
+    use scroll::{ctx::TryFromCtx, Endian, BE};
+    #[derive(Debug, Default, Clone, PartialEq)]
+    struct Record<'a> {
+        id: u32,
+        tag: &'a [u8],
+        checksum: u16,
+    }
+    impl<'a> TryFromCtx<'a, Endian> for Record<'a> {
+        type Error = scroll::Error;
+        fn try_from_ctx(src: &'a [u8], ctx: Endian) -> Result<(Self, usize)> {
+            let offset = &mut 0;
+            let id = src.gread_with(offset, ctx)?;
+            let tag = src.gread_with(offset, 3)?;
+            let checksum = src.gread_with(offset, ctx)?;
+            Ok((Record { id, tag, checksum }, *offset))
+        }
+    }
+
+    let bytes = [0x00, 0x00, 0x00, 0x2a, b'a', b'b', b'c', 0xca, 0xfe, 0xff];
+    let expected = bytes.pread_with::<Record>(0, BE).unwrap();
+    let mut record = Record::default();
+    for _ in 0..1000 {
+        assert_eq!(bytes.pread_into_existing(0, &mut record, BE).unwrap(), 9);
+        assert_eq!(record, expected);
+    }
+    // a failed parse leaves the existing value alone
+    assert!(bytes.pread_into_existing(2, &mut record, BE).is_err());
+    assert_eq!(record, expected);
+}
+
+#[test]
+fn encoded_len_ctx() {
+    use scroll::{ctx::{EncodedLen, EncodedLenCtx, StrCtx}, Error, Pwrite, BE, LE};
+    let name = String::from("scroll");
+    let ctx = EncodedLenCtx::<u16>::new(BE, ());
+    let mut bytes = [0u8; 16];
+    let offset = &mut 0;
+    bytes.gwrite_with(EncodedLen(name.as_str()), offset, ctx).unwrap();
+    assert_eq!(*offset, 8);
+    assert_eq!(&bytes[..8], b"\x00\x06scroll");
+    let offset = &mut 0;
+    let ctx = EncodedLenCtx::<u16, _>::new(BE, StrCtx::default());
+    let EncodedLen(read) = bytes.gread_with::<EncodedLen<&str>>(offset, ctx).unwrap();
+    assert_eq!(read.to_string(), name);
+    assert_eq!(*offset, 8);
+
+    // the value only sees its own bytes, and all of them are consumed whatever it parses
+    let ctx = EncodedLenCtx::<u8, _>::new(LE, StrCtx::Delimiter(b','));
+    let bytes = b"\x05ab,cdXYZ";
+    let offset = &mut 0;
+    let EncodedLen(first) = bytes.gread_with::<EncodedLen<&str>>(offset, ctx).unwrap();
+    assert_eq!(first, "ab");
+    assert_eq!(*offset, 6);
+    let ctx = EncodedLenCtx::<u32, _>::new(LE, LE);
+    assert!(matches!(b"\x02\x00\x00\x00\x01".pread_with::<EncodedLen<u16>>(0, ctx), Err(Error::Incomplete { needed: 1 })));
+    assert!(b"\x02\x00\x00\x00\x01\x02".pread_with::<EncodedLen<u32>>(0, ctx).is_err());
+
+    // the length doesn't fit its field
+    let long = "x".repeat(256);
+    let mut bytes = vec![0u8; 300];
+    assert!(bytes.pwrite_with(EncodedLen(long.as_str()), 0, EncodedLenCtx::<u8>::new(LE, ())).is_err());
+    assert_eq!(bytes.pwrite_with(EncodedLen(long.as_str()), 0, EncodedLenCtx::<u16>::new(LE, ())).unwrap(), 258);
+}
+
+#[test]
+fn pread_inout_struct() {
+    use scroll::{Endian, Pwrite, LE};
+
+    #[derive(Default)]
+    struct Record {
+        id: u32,
+        name: String,
+    }
+
+    impl<'a> ctx::TryFromCtxMut<'a, Endian> for Record {
+        type Error = scroll::Error;
+        fn try_update_from_ctx(&mut self, src: &'a [u8], le: Endian) -> Result<usize> {
+            let offset = &mut 0;
+            self.id = src.gread_with(offset, le)?;
+            let name: &str = src.gread_with(offset, ctx::StrCtx::Delimiter(0))?;
+            self.name.clear();
+            self.name.push_str(name);
+            Ok(*offset)
+        }
+    }
+
+    let mut bytes = vec![0u8; 1000 * 12];
+    let offset = &mut 0;
+    for id in 0..1000u32 {
+        bytes.gwrite_with(id, offset, LE).unwrap();
+        bytes.gwrite_with(ctx::Str(&format!("rec{:04}", id)), offset, ctx::StrCtx::Delimiter(0)).unwrap();
+    }
+
+    let mut record = Record::default();
+    record.name.reserve(16);
+    let buffer = record.name.as_ptr();
+    let offset = &mut 0;
+    for id in 0..1000u32 {
+        assert_eq!(bytes.gread_inout_struct(offset, &mut record, LE).unwrap(), 12);
+        assert_eq!(record.id, id);
+        assert_eq!(record.name, format!("rec{:04}", id));
+    }
+    assert_eq!(*offset, bytes.len());
+    // every record was parsed into the same allocation
+    assert_eq!(record.name.as_ptr(), buffer);
+    assert!(bytes.pread_inout_struct(bytes.len() + 1, &mut record, LE).is_err());
+}
+
+#[test]
+fn concat_ctx() {
+    use scroll::{ctx::ConcatCtx, Pwrite, BE, LE};
+    let bytes = [0xca, 0xfe, 0xef, 0xbe, 0xad, 0xde, 0xff];
+    let ctx = ConcatCtx { inner_a: BE, inner_b: LE };
+    let offset = &mut 0;
+    let (kind, value): (u16, u32) = bytes.gread_with(offset, ctx).unwrap();
+    assert_eq!(kind, 0xcafe);
+    assert_eq!(value, 0xdeadbeef);
+    assert_eq!(*offset, 6);
+    assert!(bytes.pread_with::<(u16, u32)>(2, ctx).is_err());
+
+    let mut out = [0u8; 6];
+    assert_eq!(out.pwrite_with((kind, value), 0, ctx).unwrap(), 6);
+    assert_eq!(out, bytes[..6]);
+    assert!(out.pwrite_with((kind, value), 1, ctx).is_err());
+}
+
+#[test]
+fn str_ctx_write_modes() {
+    use scroll::{ctx::{Str, StrCtx}, Error, Pwrite, BE, LE};
+    let modes = [
+        StrCtx::Delimiter(0),
+        StrCtx::Prefixed { width: 1, endian: LE },
+        StrCtx::Prefixed { width: 2, endian: BE },
+        StrCtx::Prefixed { width: 8, endian: LE },
+        StrCtx::Fixed { len: 16, pad: 0 },
+    ];
+    for &s in &["hello", "grüße, 世界", ""] {
+        for &ctx in &modes {
+            let mut bytes = [0xffu8; 32];
+            let offset = &mut 0;
+            let written = bytes.gwrite_with(s.to_string(), offset, ctx).unwrap();
+            assert_eq!(written, *offset);
+            let offset = &mut 0;
+            assert_eq!(bytes.gread_with::<&str>(offset, ctx).unwrap(), s);
+            assert_eq!(*offset, written, "{:?}", ctx);
+            assert_eq!(bytes.pwrite_with(Str(s), 0, ctx).unwrap(), written);
+        }
+    }
+
+    let mut bytes = [0u8; 300];
+    assert_eq!(bytes.pwrite_with(&String::from("abc"), 0, StrCtx::Prefixed { width: 2, endian: BE }).unwrap(), 5);
+    assert_eq!(&bytes[..5], b"\x00\x03abc");
+    // interior terminators, overlong strings, and lengths the prefix cannot hold
+    assert!(matches!(bytes.pwrite_with(Str("a\0b"), 0, StrCtx::Delimiter(0)), Err(Error::BadInput { .. })));
+    assert!(matches!(bytes.pwrite_with(Str("abc"), 0, StrCtx::Fixed { len: 2, pad: 0 }), Err(Error::TooBig { .. })));
+    let long = "x".repeat(256);
+    assert!(matches!(bytes.pwrite_with(Str(&long), 0, StrCtx::Prefixed { width: 1, endian: LE }), Err(Error::BadInput { .. })));
+    assert_eq!(bytes.pwrite_with(Str(&long), 0, StrCtx::Prefixed { width: 2, endian: LE }).unwrap(), 258);
+    assert!(bytes.pwrite_with(Str("abc"), 0, StrCtx::Prefixed { width: 3, endian: LE }).is_err());
+    // a prefix promising more than there is
+    assert!(matches!(b"\x05abc".pread_with::<&str>(0, StrCtx::Prefixed { width: 1, endian: LE }), Err(Error::Incomplete { needed: 2 })));
+}
+
+#[cfg(feature = "bitflags")]
+#[test]
+fn bitflags() {
+    use scroll::{Error, Pwrite, BE};
+    bitflags::bitflags! {
+        #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+        struct Permissions: u16 {
+            const OWNER_READ = 0o400;
+            const OWNER_WRITE = 0o200;
+            const OWNER_EXEC = 0o100;
+            const GROUP_READ = 0o040;
+            const OTHER_READ = 0o004;
+        }
+    }
+
+    let bytes = [0x01, 0xe4, 0x02, 0x00];
+    let offset = &mut 0;
+    let permissions: Permissions = bytes.gread_into_bitflags(offset, BE).unwrap();
+    assert_eq!(permissions, Permissions::OWNER_READ | Permissions::OWNER_WRITE | Permissions::OWNER_EXEC | Permissions::GROUP_READ | Permissions::OTHER_READ);
+    assert!(!permissions.is_empty());
+    assert_eq!(*offset, 2);
+    // 0o1000 is no permission
+    assert!(matches!(bytes.gread_into_bitflags::<Permissions>(offset, BE), Err(Error::BadInput { size: 2, .. })));
+    assert_eq!(*offset, 2);
+
+    let mut out = [0u8; 2];
+    let offset = &mut 0;
+    assert_eq!(out.gwrite_bitflags(permissions, offset, BE).unwrap(), 2);
+    assert_eq!(out, bytes[..2]);
+    assert_eq!(*offset, 2);
+}
+
+#[cfg(feature = "bitflags")]
+#[test]
+fn flags_ctx() {
+    use scroll::{ctx::SizeWith, offset::OffsetView, Error, FlagsCtx, Pwrite, BE, LE};
+    bitflags::bitflags! {
+        // bits 1, 3, 4 and 6..=31 are holes
+        #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+        struct Section: u32 {
+            const WRITE = 1 << 0;
+            const ALLOC = 1 << 2;
+            const EXEC = 1 << 5;
+        }
+    }
+    scroll::flags_ctx!(Section);
+
+    let bytes = [0x00, 0x00, 0x00, 0x25, 0x00, 0x00, 0x01, 0x0d];
+    let offset = &mut 0;
+    let flags: Section = bytes.gread_with(offset, FlagsCtx::strict(BE)).unwrap();
+    assert_eq!(flags, Section::all());
+    assert_eq!(*offset, 4);
+
+    // 0x10d is WRITE | ALLOC, and the holes 0x100 and 0x8
+    let err = bytes.gread_with::<Section>(offset, FlagsCtx::strict(BE)).unwrap_err();
+    assert_eq!(err.to_string(), "unknown flag bits 0x108: invalid value at offset 0: flag word has bits outside of its flags");
+    assert_eq!(*offset, 4);
+    let view = OffsetView::new(&bytes[4..], 4);
+    assert_eq!(view.pread_with::<Section, _>(0, FlagsCtx::strict(BE)).unwrap_err().offset(), Some(4));
+    assert!(matches!(err, Error::Context { .. }));
+    let err = bytes.pread_with_ctx_fn::<Section, _>(4, |at| FlagsCtx::strict(BE).at(at)).unwrap_err();
+    assert_eq!(err.to_string(), "unknown flag bits 0x108: invalid value at offset 4: flag word has bits outside of its flags");
+    assert_eq!(err.offset(), Some(4));
+
+    let flags: Section = bytes.gread_with(offset, FlagsCtx::truncating(BE)).unwrap();
+    assert_eq!(flags, Section::WRITE | Section::ALLOC);
+    assert_eq!(*offset, 8);
+
+    let mut out = [0xffu8; 4];
+    assert_eq!(out.pwrite_with(Section::EXEC | Section::WRITE, 0, FlagsCtx::strict(LE)).unwrap(), 4);
+    assert_eq!(out, [0x21, 0, 0, 0]);
+    // unknown bits kept with from_bits_retain are written back as they are
+    assert_eq!(out.pwrite_with(&Section::from_bits_retain(0x108), 0, FlagsCtx::truncating(BE)).unwrap(), 4);
+    assert_eq!(out, [0, 0, 0x01, 0x08]);
+    assert_eq!(Section::size_with(&FlagsCtx::strict(LE)), 4);
+}
+
+#[test]
+fn map_ctx() {
+    use std::collections::{BTreeMap, HashMap};
+    use scroll::{ctx::{Duplicates, MapCtx}, Error, Pwrite, BE};
+    let bytes = [0x00, 0x03, 0x10, 0x00, 0x01, 0x11, 0x00, 0x02, 0x12];
+    let ctx = MapCtx::new(3, BE, Duplicates::Reject);
+    let map: HashMap<u16, u8> = bytes.pread_with(0, ctx).unwrap();
+    assert_eq!(map.len(), 3);
+    assert_eq!(map[&3], 0x10);
+    assert_eq!(map[&1], 0x11);
+
+    // written back sorted by key, however the HashMap iterates
+    let mut out = [0u8; 9];
+    assert_eq!(out.pwrite_with(&map, 0, ctx).unwrap(), 9);
+    assert_eq!(out, [0x00, 0x01, 0x11, 0x00, 0x02, 0x12, 0x00, 0x03, 0x10]);
+    let tree: BTreeMap<u16, u8> = out.pread_with(0, ctx).unwrap();
+    assert!(tree.iter().map(|(k, v)| (*k, *v)).eq(vec![(1, 0x11), (2, 0x12), (3, 0x10)]));
+    let mut again = [0u8; 9];
+    assert_eq!(again.pwrite_with(&tree, 0, ctx).unwrap(), 9);
+    assert_eq!(again, out);
+    assert!(again.pwrite_with(&tree, 0, MapCtx::new(2, BE, Duplicates::Reject)).is_err());
+
+    // the same key twice
+    let bytes = [0x00, 0x01, 0xaa, 0x00, 0x01, 0xbb];
+    assert!(matches!(bytes.pread_with::<BTreeMap<u16, u8>>(0, MapCtx::new(2, BE, Duplicates::Reject)), Err(Error::Invalid { offset: 3, .. })));
+    let map: HashMap<u16, u8> = bytes.pread_with(0, MapCtx::new(2, BE, Duplicates::LastWins)).unwrap();
+    assert_eq!(map.len(), 1);
+    assert_eq!(map[&1], 0xbb);
+
+    // a corrupt count fails before anything is allocated, and a short one when the pairs run out
+    assert!(matches!(bytes.pread_with::<HashMap<u16, u8>>(0, MapCtx::new(usize::MAX, BE, Duplicates::Reject)), Err(Error::BadInput { .. })));
+    assert!(matches!(bytes.pread_with::<HashMap<u16, u8>>(0, MapCtx::new(3, BE, Duplicates::LastWins)), Err(Error::Incomplete { .. })));
+}
+
+#[test]
+fn btree_map_ctx() {
+    use std::collections::BTreeMap;
+    use scroll::{ctx::{BTreeMapCtx, Duplicates}, Error, Pwrite, BE, LE};
+    // u8 keys, and little endian u16 values
+    let bytes = [0x04, 0x40, 0x00, 0x01, 0x10, 0x00, 0x03, 0x30, 0x00, 0x02, 0x20, 0x00];
+    let ctx = BTreeMapCtx::new(4, BE, LE, Duplicates::Reject);
+    let offset = &mut 0;
+    let map: BTreeMap<u8, u16> = bytes.gread_with(offset, ctx).unwrap();
+    assert_eq!(*offset, 12);
+    assert!(map.into_iter().eq(vec![(1, 0x10), (2, 0x20), (3, 0x30), (4, 0x40)]));
+
+    // written back in ascending key order
+    let map: BTreeMap<u8, u16> = bytes.pread_with(0, ctx).unwrap();
+    let mut out = [0u8; 12];
+    assert_eq!(out.pwrite_with(&map, 0, ctx).unwrap(), 12);
+    assert_eq!(out, [0x01, 0x10, 0x00, 0x02, 0x20, 0x00, 0x03, 0x30, 0x00, 0x04, 0x40, 0x00]);
+    let ctx = BTreeMapCtx { val_ctx: BE, ..ctx };
+    assert_eq!(out.pwrite_with(&map, 0, ctx).unwrap(), 12);
+    assert_eq!(out[..3], [0x01, 0x00, 0x10]);
+    assert!(out.pwrite_with(&map, 0, BTreeMapCtx { count: 3, ..ctx }).is_err());
+
+    let bytes = [0x01, 0xaa, 0x00, 0x01, 0xbb, 0x00];
+    assert!(matches!(bytes.pread_with::<BTreeMap<u8, u16>>(0, BTreeMapCtx::new(2, LE, LE, Duplicates::Reject)), Err(Error::Invalid { offset: 3, .. })));
+    let map: BTreeMap<u8, u16> = bytes.pread_with(0, BTreeMapCtx::new(2, LE, LE, Duplicates::LastWins)).unwrap();
+    assert_eq!(map[&1], 0xbb);
+}
+
+#[test]
+fn encrypted_ctx() {
+    use scroll::{ctx::{Encrypted, EncryptedCtx}, Error, BE};
+    fn xor(bytes: &mut [u8]) {
+        for b in bytes {
+            *b ^= 0x42;
+        }
+    }
+    let plaintext = 0x1234_5678u32 ^ 0x4242_4242;
+    let mut bytes = plaintext.to_be_bytes().to_vec();
+    bytes.push(0xff);
+    let ctx = EncryptedCtx { len: 4, cipher: xor, inner: BE };
+    let offset = &mut 0;
+    let Encrypted(n) = bytes.gread_with::<Encrypted<u32>>(offset, ctx).unwrap();
+    assert_eq!(n, 0x1234_5678);
+    assert_eq!(*offset, 4);
+    // the source itself is untouched
+    assert_eq!(bytes.pread_with::<u32>(0, BE).unwrap(), plaintext);
+    // the whole field is consumed, even if the value is smaller
+    let Encrypted(n) = bytes.gread_with::<Encrypted<u16>>(&mut 0, EncryptedCtx { len: 4, ..ctx }).unwrap();
+    assert_eq!(n, 0x1234);
+    assert!(matches!(bytes.pread_with::<Encrypted<u32>>(2, ctx), Err(Error::Incomplete { needed: 1 })));
+    assert!(bytes.pread_with::<Encrypted<u32>>(0, EncryptedCtx { len: 2, ..ctx }).is_err());
+}
+
+#[test]
+fn vec_count_ctx() {
+    use scroll::{ctx::{Count, Slice, Str, StrCtx}, Error, Pwrite, BE, LE};
+    let bytes = [0x01, 0x00, 0x02, 0x00, 0x03, 0x00, 0xff];
+    let offset = &mut 0;
+    let v: Vec<u16> = bytes.gread_with(offset, Count(3, LE)).unwrap();
+    assert_eq!(v, [1, 2, 3]);
+    assert_eq!(*offset, 6);
+    let empty: Vec<u16> = bytes.pread_with(7, Count(0, LE)).unwrap();
+    assert!(empty.is_empty());
+    // nested, as a field type
+    let nested: Vec<Vec<u8>> = bytes.pread_with(0, Count(3, Count(2, LE))).unwrap();
+    assert_eq!(nested, [[1, 0], [2, 0], [3, 0]]);
+
+    // the count is checked up front, and a failing element reports where it is
+    assert!(matches!(bytes.pread_with::<Vec<u16>>(0, Count(usize::MAX, LE)), Err(Error::BadInput { .. })));
+    // elements larger in memory than in the bytes, which preallocate no more than the bytes take up
+    let empties: Vec<Vec<u16>> = bytes.pread_with(0, Count(bytes.len(), Count(0, LE))).unwrap();
+    assert_eq!(empties, vec![Vec::<u16>::new(); bytes.len()]);
+    match bytes.pread_with::<Vec<u16>>(2, Count(3, LE)) {
+        Err(Error::Element { index: 2, offset: 4, source }) => assert!(matches!(*source, Error::Incomplete { needed: 1 })),
+        other => panic!("unexpected {:?}", other),
+    }
+    let strs = b"ab\0\xff\xfe\0";
+    match strs.pread_with::<Vec<&str>>(0, Count(2, StrCtx::Delimiter(0))) {
+        Err(Error::Element { index: 1, offset: 3, .. }) => (),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    let mut out = [0u8; 6];
+    assert_eq!(out.pwrite_with(&v, 0, Count(3, BE)).unwrap(), 6);
+    assert_eq!(out, [0, 1, 0, 2, 0, 3]);
+    assert_eq!(out.pwrite_with(v.clone(), 0, Count(3, LE)).unwrap(), 6);
+    assert_eq!(out, bytes[..6]);
+    assert_eq!(out.pwrite_with(Slice(&v[1..]), 2, Count(2, BE)).unwrap(), 4);
+    assert_eq!(out, [1, 0, 0, 2, 0, 3]);
+    assert!(matches!(out.pwrite_with(Slice(&v), 0, Count(2, LE)), Err(Error::BadInput { .. })));
+    assert!(matches!(out.pwrite_with(&v, 0, Count(2, LE)), Err(Error::BadInput { .. })));
+    assert!(matches!(out.pwrite_with(&v, 2, Count(3, LE)), Err(Error::Element { index: 2, offset: 4, .. })));
+    let names = vec![Str("ab"), Str("c")];
+    assert_eq!(out.pwrite_with(names, 0, Count(2, StrCtx::Delimiter(0))).unwrap(), 5);
+    assert_eq!(&out[..5], b"ab\0c\0");
+}
+
+#[test]
+fn pread_checked_size_product() {
+    use scroll::{Error, PreadExt};
+    let bytes = [0u8; 32];
+    assert_eq!(bytes.pread_checked_size_product(0, 4, 8).unwrap(), 32);
+    assert_eq!(bytes.pread_checked_size_product(32, 0, 8).unwrap(), 0);
+    assert_eq!(bytes.pread_checked_size_product(8, usize::MAX, 0).unwrap(), 0);
+    // a count and element size whose product wraps around to something small
+    assert!(matches!(bytes.pread_checked_size_product(0, 1 << (usize::BITS - 1), 2), Err(Error::BadInput { .. })));
+    assert!(matches!(bytes.pread_checked_size_product(0, usize::MAX, usize::MAX), Err(Error::BadInput { .. })));
+    assert!(matches!(bytes.pread_checked_size_product(8, 4, 8), Err(Error::TooBig { size: 32, len: 24 })));
+    assert!(matches!(bytes.pread_checked_size_product(33, 0, 8), Err(Error::BadOffset(33))));
+}
+
+#[test]
+fn cow_borrows_when_possible() {
+    use std::borrow::Cow;
+    use scroll::{ctx::{EscapedCtx, StrCtx, Utf8Lossy}, Pwrite};
+    let bytes = b"name\0\xffx\0pad   ";
+
+    let name: Cow<str> = bytes.pread_with(0, StrCtx::Delimiter(0)).unwrap();
+    assert!(matches!(name, Cow::Borrowed(_)));
+    assert_eq!(name.as_ptr(), bytes.as_ptr());
+    let pad: Cow<str> = bytes.pread_with(8, StrCtx::Fixed { len: 6, pad: b' ' }).unwrap();
+    assert!(matches!(pad, Cow::Borrowed("pad")));
+    assert_eq!(pad.as_ptr(), bytes[8..].as_ptr());
+    assert!(bytes.pread_with::<Cow<str>>(5, StrCtx::Delimiter(0)).is_err());
+
+    let lossy: Cow<str> = bytes.pread_with(0, Utf8Lossy(StrCtx::Delimiter(0))).unwrap();
+    assert_eq!(lossy.as_ptr(), bytes.as_ptr());
+    let offset = &mut 5;
+    let lossy: Cow<str> = bytes.gread_with(offset, Utf8Lossy(StrCtx::Delimiter(0))).unwrap();
+    assert!(matches!(lossy, Cow::Owned(ref s) if s == "\u{fffd}x"));
+    assert_eq!(*offset, 8);
+
+    let raw: Cow<[u8]> = bytes.pread_with(1, 3usize).unwrap();
+    assert_eq!(raw.as_ptr(), bytes[1..].as_ptr());
+    let ctx = EscapedCtx { escape_byte: 0xff, sentinel: 0 };
+    let plain: Cow<[u8]> = bytes.pread_with(0, ctx).unwrap();
+    assert!(matches!(plain, Cow::Borrowed(b"name")));
+    let unescaped: Cow<[u8]> = bytes.pread_with(5, ctx).unwrap();
+    assert!(matches!(unescaped, Cow::Owned(ref b) if b == b"x"));
+
+    // either variant writes the same
+    let mut out = [0u8; 16];
+    assert_eq!(out.pwrite_with(name, 0, StrCtx::Delimiter(0)).unwrap(), 5);
+    assert_eq!(out.pwrite_with(lossy, 5, StrCtx::Length(4)).unwrap(), 4);
+    assert_eq!(&out[..9], "name\0\u{fffd}x".as_bytes());
+    assert_eq!(out.pwrite(unescaped, 9).unwrap(), 1);
+    assert_eq!(out.pwrite(raw, 10).unwrap(), 3);
+    assert_eq!(&out[9..13], b"xame");
+}
+
+#[test]
+fn utf8_lossy_strings() {
+    use std::borrow::Cow;
+    use scroll::ctx::{StrCtx, Utf8Lossy};
+
+    let valid = "añb€".as_bytes();
+    let s: Cow<str> = valid.pread_with(0, Utf8Lossy(StrCtx::Length(valid.len()))).unwrap();
+    assert!(matches!(s, Cow::Borrowed("añb€")));
+
+    // the replacement is where the bad byte was, and the rest of the string survives
+    let bytes = b"tit\xffle\0";
+    let offset = &mut 0;
+    let s: Cow<str> = bytes.gread_with(offset, Utf8Lossy(StrCtx::Delimiter(0))).unwrap();
+    assert!(matches!(s, Cow::Owned(_)));
+    assert_eq!(s.find('\u{fffd}'), Some(3));
+    assert_eq!(s, "tit\u{fffd}le");
+    assert_eq!(*offset, bytes.len());
+    assert!(bytes.pread_with::<&str>(0, StrCtx::Delimiter(0)).is_err());
+
+    // a field ending in the middle of "é" keeps its length, and the next field starts at its boundary
+    let bytes = b"caf\xc3\xa9";
+    let offset = &mut 0;
+    let s: Cow<str> = bytes.gread_with(offset, Utf8Lossy(StrCtx::Length(4))).unwrap();
+    assert_eq!(s, "caf\u{fffd}");
+    assert_eq!(*offset, 4);
+    let s: Cow<str> = bytes.gread_with(offset, Utf8Lossy(StrCtx::Length(1))).unwrap();
+    assert_eq!(s, "\u{fffd}");
+    assert!(bytes.pread_with::<&str>(0, StrCtx::Length(4)).is_err());
+    assert!(bytes.pread_with::<Cow<str>>(0, Utf8Lossy(StrCtx::Length(6))).is_err());
+}
+
+#[test]
+fn endian_shortcuts() {
+    use scroll::Pwrite;
+    let mut bytes = [0u8; 8];
+    let offset = &mut 0;
+    bytes.gwrite_be(0xdead_u16, offset).unwrap();
+    bytes.gwrite_le(0xbeef_u16, offset).unwrap();
+    bytes.pwrite_le(0x0102_0304_u32, *offset).unwrap();
+    assert_eq!(*offset, 4);
+    assert_eq!(bytes, [0xde, 0xad, 0xef, 0xbe, 0x04, 0x03, 0x02, 0x01]);
+    assert_eq!(bytes.pread_be::<u16>(0).unwrap(), 0xdead);
+    assert_eq!(bytes.pread_le::<u32>(4).unwrap(), 0x0102_0304);
+    assert_eq!(bytes.pread_be::<u32>(4).unwrap(), bytes.pread_with::<u32>(4, scroll::BE).unwrap());
+
+    let offset = &mut 2;
+    assert_eq!(bytes.gread_le::<u16>(offset).unwrap(), 0xbeef);
+    assert_eq!(bytes.gread_be::<u32>(offset).unwrap(), 0x0403_0201);
+    assert_eq!(*offset, 8);
+    assert!(bytes.gread_le::<u8>(offset).is_err());
+    assert!(bytes.pwrite_be(0u16, 7).is_err());
+}
+
+#[test]
+fn slices_are_try_from_ctx() {
+    use scroll::ctx::{StrCtx, TryFromCtx};
+    // a helper written against TryFromCtx works for slice targets as well as for numbers
+    fn field<'a, T: TryFromCtx<'a, C, Error = scroll::Error>, C: Copy>(src: &'a [u8], offset: &mut usize, ctx: C) -> Result<T> {
+        src.gread_with(offset, ctx)
+    }
+    let bytes = b"\x04\0magicname\0";
+    let offset = &mut 0;
+    let len: u16 = field(bytes, offset, scroll::LE).unwrap();
+    let magic: &[u8] = field(bytes, offset, len as usize).unwrap();
+    let name: &str = field(bytes, offset, StrCtx::Delimiter(0)).unwrap();
+    assert_eq!(magic, b"magi");
+    assert_eq!(name, "cname");
+    assert_eq!(*offset, bytes.len());
+    assert!(field::<&[u8], _>(bytes, &mut 8, 5usize).is_err());
+}
+
+#[test]
+fn gread_with_rollback() {
+    let mut bytes = [0u8; 8];
+    bytes[..4].copy_from_slice(&50000u32.to_le_bytes());
+    bytes[4..].copy_from_slice(&42u32.to_le_bytes());
+    let offset = &mut 0;
+    let small = |n: &u32| *n < 1000;
+    assert_eq!(bytes.gread_with_rollback(offset, scroll::LE, small).unwrap(), None);
+    assert_eq!(*offset, 0);
+    *offset = 4;
+    assert_eq!(bytes.gread_with_rollback(offset, scroll::LE, small).unwrap(), Some(42));
+    assert_eq!(*offset, 8);
+    assert!(bytes.gread_with_rollback(offset, scroll::LE, small).is_err());
+    assert_eq!(*offset, 8);
+}
+
+#[test]
+fn pread_collect() {
+    use std::collections::{BTreeSet, HashSet};
+    use scroll::{Pwrite, BE};
+    let mut bytes = [0xffu8; 22];
+    let offset = &mut 2;
+    for n in [7u32, 3, 0xdeadbeef, 3, 1] {
+        bytes.gwrite_with(n, offset, BE).unwrap();
+    }
+    let set: BTreeSet<u32> = bytes.pread_collect(2, 5, BE).unwrap();
+    assert_eq!(set.len(), 4);
+    assert!([1, 3, 7, 0xdeadbeef].iter().all(|n| set.contains(n)));
+    let hashed: HashSet<u32> = bytes.pread_collect(2, 5, BE).unwrap();
+    assert_eq!(hashed, set.iter().copied().collect());
+    let list: Vec<u32> = bytes.pread_collect(2, 5, BE).unwrap();
+    assert_eq!(list, [7, 3, 0xdeadbeef, 3, 1]);
+    assert!(bytes.pread_collect::<u32, Vec<_>>(2, 0, BE).unwrap().is_empty());
+    // the sixth value runs off the end
+    assert!(bytes.pread_collect::<u32, BTreeSet<_>>(2, 6, BE).is_err());
+}
+
+#[test]
+fn pread_diff() {
+    use scroll::{ctx::DiffCtx, Pwrite, PreadExt, BE, LE};
+    let old: Vec<u8> = (0..32).collect();
+    let mut new = old.clone();
+    new.pwrite_with(0xdeadbeefu32, 12, BE).unwrap();
+
+    let changed: Vec<(usize, (u32, u32))> = (0..old.len()).step_by(4)
+        .filter_map(|offset| old.pread_diff(&new, offset, BE).unwrap().map(|diff| (offset, diff)))
+        .collect();
+    assert_eq!(changed, [(12, (0x0c0d0e0f, 0xdeadbeef))]);
+    // values straddling the change see it too
+    assert_eq!(old.pread_diff::<u16, _>(&new, 15, LE).unwrap(), Some((0x100f, 0x10ef)));
+    assert_eq!(old.pread_diff::<u16, _>(&new, 16, LE).unwrap(), None);
+    assert!(old.pread_diff::<u32, _>(&new[..14], 12, BE).is_err());
+
+    let (le, be): (u32, u32) = new.pread_with(12, DiffCtx { old: LE, new: BE }).unwrap();
+    assert_eq!((le, be), (0xefbeadde, 0xdeadbeef));
+}
+
+#[test]
+fn pread_strided() {
+    use scroll::{Endian, Error, PreadExt, BE};
+
+    /// An odd byte; an even one fails to parse
+    #[derive(Debug, PartialEq)]
+    struct Odd(u8);
+
+    impl<'a> ctx::TryFromCtx<'a, Endian> for Odd {
+        type Error = scroll::Error;
+        fn try_from_ctx(src: &'a [u8], _: Endian) -> Result<(Self, usize)> {
+            match src.pread::<u8>(0)? {
+                b if b % 2 == 1 => Ok((Odd(b), 1)),
+                _ => Err(Error::Invalid { offset: 0, msg: "even" }),
+            }
+        }
+    }
+
+    impl ctx::SizeWith<Endian> for Odd {
+        fn size_with(_: &Endian) -> usize {
+            1
+        }
+    }
+
+    // three channels of u16 samples, after a two byte header; channel `c` of frame `f` is `f << 8 | c`
+    let mut bytes = vec![0xff, 0xff];
+    for frame in 0..4u16 {
+        for channel in 0..3u16 {
+            bytes.extend_from_slice(&(frame << 8 | channel).to_be_bytes());
+        }
+    }
+    assert_eq!(bytes.pread_strided::<u16, _>(2 + 2 * 2, 4, 6, BE).unwrap(), [0x0002, 0x0102, 0x0202, 0x0302]);
+    assert_eq!(bytes.pread_strided::<u16, _>(2, 2, 12, BE).unwrap(), [0x0000, 0x0200]);
+    let channels = bytes.pread_deinterleaved::<u16, _>(2, 3, 4, BE).unwrap();
+    for (c, channel) in channels.iter().enumerate() {
+        assert_eq!(*channel, (0..4).map(|f| f << 8 | c as u16).collect::<Vec<_>>());
+    }
+    // the low byte of each channel-1 sample, or values overlapping each other
+    assert_eq!(bytes.pread_strided::<u8, _>(5, 4, 6, BE).unwrap(), [1, 1, 1, 1]);
+    assert_eq!(bytes.pread_strided::<u16, _>(3, 3, 1, BE).unwrap(), [0x0000, 0x0001, 0x0100]);
+
+    let iter = bytes.pread_strided_iter::<u16, _>(4, 4, 6, BE).unwrap();
+    assert_eq!(iter.len(), 4);
+    assert_eq!(iter.map(|sample| sample.unwrap()).collect::<Vec<_>>(), [0x0001, 0x0101, 0x0201, 0x0301]);
+
+    // the last value must fit entirely, up to the end at 26
+    assert_eq!(bytes.len(), 26);
+    assert_eq!(bytes.pread_strided::<u32, _>(4, 4, 6, BE).unwrap()[3], 0x0301_0302);
+    assert!(matches!(bytes.pread_strided::<u32, _>(6, 4, 6, BE), Err(Error::TooBig { size: 22, len: 20 })));
+    assert!(matches!(bytes.pread_strided::<u16, _>(9, 4, 6, BE), Err(Error::TooBig { size: 20, len: 17 })));
+    assert!(matches!(bytes.pread_strided::<u16, _>(2, usize::MAX, 2, BE), Err(Error::BadInput { .. })));
+    assert!(matches!(bytes.pread_strided::<u16, _>(2, 2, usize::MAX, BE), Err(Error::BadInput { .. })));
+    assert!(matches!(bytes.pread_strided::<u16, _>(2, 2, 0, BE), Err(Error::BadInput { .. })));
+    assert!(matches!(bytes.pread_strided::<u16, _>(27, 0, 2, BE), Err(Error::BadOffset(27))));
+    assert!(bytes.pread_strided::<u16, _>(26, 0, 2, BE).unwrap().is_empty());
+    assert!(matches!(bytes.pread_deinterleaved::<u16, _>(2, 3, 5, BE), Err(Error::TooBig { .. })));
+    assert!(matches!(bytes.pread_deinterleaved::<u16, _>(2, usize::MAX, 1, BE), Err(Error::BadInput { .. })));
+    assert!(bytes.pread_deinterleaved::<u16, _>(2, 0, 4, BE).unwrap().is_empty());
+    // the whole region is checked before any channel is allocated, and the bytes left bound the channels of no frames
+    assert_eq!(bytes.pread_deinterleaved::<u16, _>(2, 4, 3, BE).unwrap().len(), 4);
+    assert!(matches!(bytes.pread_deinterleaved::<u16, _>(4, 4, 3, BE), Err(Error::TooBig { size: 24, len: 22 })));
+    assert!(matches!(bytes.pread_deinterleaved::<u16, _>(27, 1, 0, BE), Err(Error::BadOffset(27))));
+    assert_eq!(bytes.pread_deinterleaved::<u16, _>(2, 24, 0, BE).unwrap().len(), 24);
+    assert!(matches!(bytes.pread_deinterleaved::<u16, _>(2, usize::MAX / 2, 0, BE), Err(Error::TooBig { len: 24, .. })));
+
+    // a value which fails to parse is an element error, but doesn't stop the iterator
+    let odds = [1, 0, 3, 0, 4, 0, 5];
+    assert_eq!(odds.pread_strided::<Odd, _>(0, 2, 2, BE).unwrap(), [Odd(1), Odd(3)]);
+    match odds.pread_strided::<Odd, _>(0, 4, 2, BE) {
+        Err(Error::Element { index: 2, offset: 4, source }) => assert!(matches!(*source, Error::Invalid { .. })),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    let parsed: Vec<bool> = odds.pread_strided_iter::<Odd, _>(0, 4, 2, BE).unwrap().map(|odd| odd.is_ok()).collect();
+    assert_eq!(parsed, [true, true, false, true]);
+}
+
+#[test]
+fn gread_bytes_owned() {
+    use scroll::{Error, PreadExt};
+    let offset = &mut 1;
+    let (head, tail) = {
+        let bytes: Vec<u8> = (0..8).collect();
+        let source: &[u8] = &bytes;
+        let head = source.gread_bytes_owned(offset, 3).unwrap();
+        let tail = source.pread_bytes_owned(6, 2).unwrap();
+        assert!(matches!(source.gread_bytes_owned(offset, 5), Err(Error::TooBig { size: 5, len: 4 })));
+        assert!(matches!(source.pread_bytes_owned(9, 0), Err(Error::BadOffset(9))));
+        (head, tail)
+        // `bytes` is dropped here
+    };
+    assert_eq!(head, [1, 2, 3]);
+    assert_eq!(tail, [6, 7]);
+    assert_eq!(*offset, 4);
+}
+
+#[test]
+fn gread_inout_endian() {
+    use scroll::{Endian, Error, PreadExt, PwriteExt, BE, LE};
+
+    fn round_trip<T: scroll::ctx::Primitive + PartialEq + std::fmt::Debug + Default>(values: &[T], endian: Endian) {
+        let mut bytes = vec![0u8; 1 + std::mem::size_of_val(values)];
+        bytes.pwrite_slice_with(1, values, endian).unwrap();
+        // the destination exactly exhausts the source
+        let mut dst = vec![T::default(); values.len()];
+        let offset = &mut 1;
+        assert_eq!(bytes.gread_inout_endian(offset, &mut dst, endian).unwrap(), bytes.len() - 1);
+        assert_eq!(dst, values);
+        assert_eq!(*offset, bytes.len());
+        // one more is too many, and changes nothing
+        let mut dst = vec![T::default(); values.len() + 1];
+        let offset = &mut 1;
+        assert!(matches!(bytes.gread_inout_endian(offset, &mut dst, endian), Err(Error::TooBig { .. })));
+        assert_eq!(*offset, 1);
+        assert!(dst.iter().all(|value| *value == T::default()));
+    }
+
+    for &endian in &[LE, BE] {
+        round_trip(&[1u16, 0xbeef, 0xffff], endian);
+        round_trip(&[1u32, 0xdeadbeef], endian);
+        round_trip(&[u64::MAX - 1, 0x0102_0304_0506_0708], endian);
+        round_trip(&[1.5f32, -0.0, f32::MAX], endian);
+        round_trip(&[std::f64::consts::PI, -2.25], endian);
+    }
+
+    // the same as reading one by one
+    let bytes = [0x3f, 0xc0, 0x00, 0x00, 0x40, 0x20, 0x00, 0x00];
+    let mut dst = [0f32; 2];
+    bytes.pread_inout_endian(0, &mut dst, BE).unwrap();
+    assert_eq!(dst, [bytes.pread_with::<f32>(0, BE).unwrap(), bytes.pread_with::<f32>(4, BE).unwrap()]);
+    assert_eq!(dst, [1.5, 2.5]);
+    assert!(matches!(bytes.pread_inout_endian(9, &mut dst, LE), Err(Error::BadOffset(9))));
+}
+
+#[test]
+fn pread_string_list() {
+    use scroll::PreadExt;
+    let bytes = b"\xff\x00\x03\x00\x04Code\x00\x00\x00\x0eLineNumbers\xe2\x9c\x93";
+    let strings = bytes.pread_string_list::<u16>(1, scroll::BE).unwrap();
+    assert_eq!(strings, ["Code", "", "LineNumbers\u{2713}"]);
+
+    // one string short
+    let err = bytes[..bytes.len() - 1].pread_string_list::<u16>(1, scroll::BE).unwrap_err();
+    assert!(matches!(err, scroll::Error::Element { index: 2, offset: 11, .. }), "{:?}", err);
+    // a count far beyond the input doesn't allocate up front
+    assert!([0xffu8, 0xff, 0xff, 0xff].pread_string_list::<u32>(0, scroll::LE).is_err());
+}
+
+#[test]
+fn gread_leb128_u32() {
+    use scroll::{PreadExt, Uleb128};
+    let mut bytes = [0u8; 5];
+    for &value in &[0u32, 1, 0x7f, 0x80, 0x3fff, 0x4000, 624_485, 0x0fff_ffff, 0x1000_0000, u32::MAX] {
+        let offset = &mut 0;
+        let len = Uleb128::write_u128(&mut bytes, offset, u128::from(value)).unwrap();
+        let offset = &mut 0;
+        assert_eq!(bytes.gread_leb128_u32(offset).unwrap(), value);
+        assert_eq!(*offset, len);
+        assert_eq!(bytes.pread::<Uleb128>(0).unwrap().value(), u64::from(value));
+    }
+    // u32::MAX + 1, and a sixth byte
+    assert!(matches!([0x80u8, 0x80, 0x80, 0x80, 0x10].gread_leb128_u32(&mut 0), Err(scroll::Error::BadInput { .. })));
+    assert!(matches!([0xffu8, 0xff, 0xff, 0xff, 0x8f, 0x00].gread_leb128_u32(&mut 0), Err(scroll::Error::BadInput { .. })));
+    assert!(matches!([0x80u8, 0x80].gread_leb128_u32(&mut 0), Err(scroll::Error::Incomplete { needed: 1 })));
+    assert!(matches!([0x01u8].gread_leb128_u32(&mut 2), Err(scroll::Error::BadOffset(2))));
+}
+
+#[test]
+fn error_builder() {
+    use std::error::Error as _;
+    let source = std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "truncated");
+    let err = scroll::Error::builder().with_offset(0x40).with_message(format_args!("bad section {}", 3)).with_source(source).build();
+    assert_eq!(err.to_string(), "bad section 3 at offset 64: truncated");
+    assert_eq!(err.offset(), Some(0x40));
+    assert_eq!(err.source().unwrap().to_string(), "truncated");
+
+    let err = scroll::Error::builder().with_message("no offset").build();
+    assert_eq!(err.to_string(), "no offset");
+    assert_eq!(err.offset(), None);
+    assert!(err.source().is_none());
+    assert_eq!(scroll::Error::BadOffset(3).offset(), Some(3));
+    // the details are boxed, so they take up no more room in every `Result` than a `String` does; the file and line of
+    // an `Error::Located` take more
+    if !cfg!(feature = "location-tracking") {
+        assert!(std::mem::size_of::<scroll::Error>() <= 32);
+    }
+}
+
+#[test]
+fn error_display_with_source() {
+    use scroll::Error;
+    let bytes: Vec<u8> = (0..40u8).collect();
+    // centered
+    let err = Error::Invalid { offset: 20, msg: "bad" };
+    assert_eq!(err.display_with_source(&bytes).to_string(), "\
+invalid value at offset 20: bad
+  0000000c: 0c 0d 0e 0f 10 11 12 13 14 15 16 17 18 19 1a 1b
+                                    ^^");
+    // clamped at the start
+    let err = Error::BadOffset(1);
+    assert_eq!(err.display_with_source(&bytes).to_string(), "\
+bad offset 1
+  00000000: 00 01 02 03 04 05 06 07 08 09 0a 0b 0c 0d 0e 0f
+               ^^");
+    // clamped at the end
+    let err = Error::BadMagic { offset: 39, expected: 0x7f, found: 0x27 };
+    assert_eq!(err.display_with_source(&bytes).to_string(), "\
+bad magic at offset 39: expected 0x7f, found 0x27
+  00000018: 18 19 1a 1b 1c 1d 1e 1f 20 21 22 23 24 25 26 27
+                                                         ^^");
+    // past the end, and a short input
+    let err = Error::BadOffset(41);
+    assert_eq!(err.display_with_source(&bytes[..3]).to_string(), "\
+bad offset 41
+  00000000: 00 01 02
+  (offset 41 is past the end of the input, which is 3 bytes)");
+    assert_eq!(err.display_with_source(&[]).to_string(), "bad offset 41\n  (the input is empty)");
+    // no offset
+    let err = Error::Incomplete { needed: 2 };
+    assert_eq!(err.display_with_source(&bytes).to_string(), "incomplete input, 2 more bytes needed");
+}
+
+#[test]
+fn error_result_ext() {
+    use std::error::Error as _;
+    use scroll::{Error, ResultExt, LE};
+    let bytes = [0u8; 8];
+    let err = bytes.pread_with::<u64>(4, LE).field("e_phoff").field("header").unwrap_err();
+    assert_eq!(err.to_string(), "header.e_phoff: incomplete input, 4 more bytes needed");
+    assert_eq!(err.source().unwrap().to_string(), "e_phoff: incomplete input, 4 more bytes needed");
+
+    let err = bytes.pread_with::<u8>(9, LE).field("e_ident").context(format_args!("program header {}", 2)).field("elf").unwrap_err();
+    assert_eq!(err.to_string(), "elf: program header 2: e_ident: bad offset 9");
+    assert_eq!(err.offset(), Some(9));
+
+    // errors which convert into a scroll::Error
+    let io: std::result::Result<(), std::io::Error> = Err(std::io::Error::other("closed"));
+    assert_eq!(io.context("reading the header").unwrap_err().to_string(), "reading the header: closed");
+    assert_eq!(Ok::<_, Error>(1u8).field("unused").unwrap(), 1);
+}
+
+#[test]
+fn gread_n_bytes_to_string() {
+    use scroll::{PreadExt, BE, LE};
+    let bytes = [0x00, 0x05, 0x68, 0x65, 0x6C, 0x6C, 0x6F];
+    let offset = &mut 0;
+    assert_eq!(bytes.gread_n_bytes_to_string::<u16>(offset, BE).unwrap(), "hello");
+    assert_eq!(*offset, 7);
+
+    let bytes = b"\x02\0\0\0hi\x01h";
+    let offset = &mut 0;
+    assert_eq!(bytes.gread_n_bytes_to_string::<u32>(offset, LE).unwrap(), "hi");
+    assert_eq!(bytes.gread_n_bytes_to_string::<u8>(offset, LE).unwrap(), "h");
+    assert_eq!(*offset, 8);
+
+    // too short, and invalid UTF-8, leave the offset alone
+    let offset = &mut 0;
+    assert!(b"\x03ab".gread_n_bytes_to_string::<u8>(offset, LE).is_err());
+    assert!(matches!(b"\x03a\xffb".gread_n_bytes_to_string::<u8>(offset, LE), Err(scroll::Error::Invalid { offset: 2, .. })));
+    assert_eq!(*offset, 0);
+}
+
+#[test]
+fn small_buffer() {
+    use scroll::{Error, PreadExt, SmallBuffer, BE, LE};
+    let mut packet = SmallBuffer::<24>::new();
+    assert!(packet.is_empty());
+    assert_eq!(packet.gwrite_with(0xa5u8, LE).unwrap(), 1);
+    assert_eq!(packet.gwrite_with(0x0102u16, BE).unwrap(), 2);
+    assert_eq!(packet.gwrite_with(ctx::Str("id"), ctx::StrCtx::Delimiter(0)).unwrap(), 3);
+    assert_eq!(packet.gwrite_with(-2i64, LE).unwrap(), 8);
+    assert_eq!(packet.len(), 14);
+    assert_eq!(packet.capacity(), 24);
+
+    let offset = &mut 0;
+    assert_eq!(packet.gread::<u8>(offset).unwrap(), 0xa5);
+    assert_eq!(packet.gread_with::<u16>(offset, BE).unwrap(), 0x0102);
+    assert_eq!(packet.gread_with::<&str>(offset, ctx::StrCtx::Delimiter(0)).unwrap(), "id");
+    assert_eq!(packet.gread_with::<i64>(offset, LE).unwrap(), -2);
+    assert_eq!(packet.pread_remaining(*offset).unwrap(), &[]);
+    // reads stop at the bytes written
+    assert!(packet.pread::<u8>(14).is_err());
+
+    // an overflowing write reports the capacity it needed, and isn't appended
+    match packet.gwrite_with(0u128, LE) {
+        Err(Error::TooBig { size: 30, len: 24 }) => {},
+        other => panic!("{:?}", other),
+    }
+    assert_eq!(packet.len(), 14);
+    assert_eq!(packet.gwrite_with(0u64, LE).unwrap(), 8);
+    assert!(packet.gwrite_with(0u32, LE).is_err());
+    packet.as_mut_slice()[0] = 0x5a;
+    assert_eq!(packet.as_slice()[0], 0x5a);
+    packet.clear();
+    assert_eq!(packet.as_slice(), &[]);
+}
+
+#[test]
+fn cow_buffer() {
+    use scroll::{CowBuffer, Pwrite, BE};
+    let bytes = [0x7f, b'E', b'L', b'F', 0x02, 0x01, 0x01, 0x00];
+    let mut buffer = CowBuffer::from(&bytes[..]);
+    let offset = &mut 0;
+    assert_eq!(buffer.gread_with::<u32>(offset, BE).unwrap(), 0x7f454c46);
+    assert_eq!(buffer.gread::<u8>(offset).unwrap(), 2);
+    assert!(!buffer.is_owned());
+    assert!(std::ptr::eq(buffer.as_slice(), &bytes[..]));
+
+    // the offset taken before the copy still points at the same byte
+    buffer.pwrite(0x09u8, *offset).unwrap();
+    assert!(buffer.is_owned());
+    let copy = buffer.as_slice().as_ptr();
+    assert!(!std::ptr::eq(copy, bytes.as_ptr()));
+    assert_eq!(buffer.gread::<u8>(offset).unwrap(), 0x09);
+    buffer.pwrite(0xffu8, 7).unwrap();
+    assert_eq!(buffer.as_slice().as_ptr(), copy);
+    assert_eq!(buffer.into_owned(), [0x7f, b'E', b'L', b'F', 0x02, 0x09, 0x01, 0xff]);
+    assert_eq!(bytes[5], 0x01);
+
+    // a failed write doesn't copy
+    let mut buffer = CowBuffer::new(&bytes[..]);
+    assert!(buffer.pwrite(0u8, 8).is_err());
+    assert!(!buffer.is_owned());
+}
+
+#[test]
+fn cow_buffer_concat() {
+    use scroll::{CowBuffer, BE};
+    let header = [0xca, 0xfe, 0x00, 0x03];
+    let payload = vec![0x10, 0x20, 0x30];
+    let trailer = [0xde, 0xad];
+    let parts = vec![CowBuffer::new(&header[..]), CowBuffer::from(payload.clone()), CowBuffer::new(&trailer[..])];
+    let packet = CowBuffer::concat_all(parts);
+    assert_eq!(packet.len(), 9);
+    assert_eq!(packet.as_slice()[..4], header);
+    assert_eq!(packet.as_slice()[4..7], payload[..]);
+    assert_eq!(packet.as_slice()[7..], trailer);
+    assert_eq!(packet.pread_with::<u16>(2, BE).unwrap(), 3);
+    assert!(CowBuffer::concat_all(Vec::new()).is_empty());
+
+    let mut buffer = CowBuffer::new(&payload[..]);
+    buffer.prepend(&header);
+    assert!(buffer.is_owned());
+    buffer.append_buf(&CowBuffer::new(&trailer[..]));
+    assert_eq!(buffer, packet);
+    assert_eq!(payload, [0x10, 0x20, 0x30]);
+}
+
+#[test]
+fn aligned_buffer() {
+    use scroll::{AlignedBuffer, Error, Pwrite, LE};
+    for &align in &[1, 8, 16, 64, 4096] {
+        for &len in &[0, 1, 100, 5000] {
+            let mut buffer = AlignedBuffer::with_alignment(len, align).unwrap();
+            assert_eq!(buffer.as_slice().as_ptr() as usize % align, 0);
+            assert_eq!(buffer.alignment(), align);
+            assert_eq!(buffer.len(), len);
+            assert!(buffer.as_slice().iter().all(|&b| b == 0));
+            if len >= 8 {
+                buffer.pwrite_with(0x0123_4567_89ab_cdefu64, len - 8, LE).unwrap();
+                assert_eq!(buffer.pread_with::<u64>(len - 8, LE).unwrap(), 0x0123_4567_89ab_cdef);
+                let copy = buffer.clone();
+                assert_eq!(copy, buffer);
+                assert_eq!(copy.as_slice().as_ptr() as usize % align, 0);
+            }
+        }
+    }
+    let buffer = AlignedBuffer::from_slice_with_alignment(&[1, 2, 3], 32).unwrap();
+    assert_eq!(buffer.as_slice(), [1, 2, 3]);
+    assert!(buffer.pread::<u8>(3).is_err());
+    assert!(matches!(AlignedBuffer::with_alignment(8, 0), Err(Error::BadInput { size: 0, .. })));
+    assert!(matches!(AlignedBuffer::with_alignment(8, 12), Err(Error::BadInput { size: 12, .. })));
+    assert!(AlignedBuffer::with_alignment(usize::MAX, 8).is_err());
+}
+
+#[test]
+fn pread_with_ctx_fn() {
+    use scroll::{Endian, BE, LE};
+    #[derive(Debug, PartialEq)]
+    struct Pair { tag: u16, value: u32 }
+    // little endian at even offsets, big endian at odd ones
+    let ctx_fn = |offset: usize| -> Endian { if offset & 1 == 0 { LE } else { BE } };
+    let bytes = [0x34, 0x12, 0x00, 0xde, 0xad, 0xbe, 0xef];
+    let pair = Pair {
+        tag: bytes.pread_with_ctx_fn(0, ctx_fn).unwrap(),
+        value: bytes.pread_with_ctx_fn(3, ctx_fn).unwrap(),
+    };
+    assert_eq!(pair, Pair { tag: 0x1234, value: 0xdeadbeef });
+    let mut seen = None;
+    assert!(bytes.pread_with_ctx_fn::<u16, _>(8, |offset| { seen = Some(offset); LE }).is_err());
+    assert_eq!(seen, Some(8));
+}
+
+#[test]
+fn small_buffer_truncate_and_drain() {
+    use scroll::{SmallBuffer, BE};
+    let mut buffer = SmallBuffer::<16>::new();
+    for b in 0..10u8 {
+        buffer.gwrite_with(b, BE).unwrap();
+    }
+    assert_eq!(buffer.drain(0..4), [0, 1, 2, 3]);
+    assert_eq!(buffer.as_slice(), [4, 5, 6, 7, 8, 9]);
+    assert_eq!(buffer.pread_with::<u16>(0, BE).unwrap(), 0x0405);
+    // the space drained is free again
+    buffer.gwrite_with(0x0a0b_0c0du32, BE).unwrap();
+    assert_eq!(buffer.drain(2..5), [6, 7, 8]);
+    assert_eq!(buffer.as_slice(), [4, 5, 9, 0x0a, 0x0b, 0x0c, 0x0d]);
+    assert!(buffer.drain(3..3).is_empty());
+
+    buffer.truncate(8);
+    assert_eq!(buffer.len(), 7);
+    buffer.truncate(2);
+    assert_eq!(buffer.as_slice(), [4, 5]);
+    assert!(std::panic::catch_unwind(move || { buffer.drain(1..3); }).is_err());
+}
+
+#[test]
+fn endian_detect() {
+    use scroll::{Endian, Error, BE, LE};
+    let tiff = b"\0MM\0\x2a";
+    assert_eq!(Endian::detect(tiff, 1, b"II", b"MM").unwrap(), (BE, 2));
+    assert_eq!(tiff.pread_with::<u16>(3, BE).unwrap(), 42);
+    // found bytes are shown as far as the longer mark, or the end of the source
+    assert!(matches!(Endian::detect(tiff, 0, b"II", b"MM"), Err(Error::UnknownByteOrder { offset: 0, found: 0x004d })));
+    assert!(matches!(Endian::detect(b"\xca\xfe\xba", 0, b"\xff\xfe", b"\xfe\xff\x00\x00"), Err(Error::UnknownByteOrder { found: 0xcafeba, .. })));
+    assert!(matches!(Endian::detect(tiff, 4, b"II", b"MM"), Err(Error::Incomplete { needed: 1 })));
+    assert!(matches!(Endian::detect(tiff, 6, b"II", b"MM"), Err(Error::BadOffset(6))));
+    assert!(matches!(Endian::detect(tiff, 0, &[0; 9], b"MM"), Err(Error::BadInput { .. })));
+
+    // a UTF-32 byte order mark
+    let bom = [0xff, 0xfe, 0x00, 0x00];
+    assert_eq!(Endian::detect_int(&bom, 0, 4, 0xfffe0000, 0x0000feff).unwrap(), (LE, 4));
+    assert_eq!(Endian::detect_int(&[1], 0, 1, 1, 2).unwrap(), (LE, 1));
+    assert!(matches!(Endian::detect_int(&[3], 0, 1, 1, 2), Err(Error::UnknownByteOrder { offset: 0, found: 3 })));
+    assert!(Endian::detect_int(&bom, 0, 0, 1, 2).is_err());
+    assert!(Endian::detect_int(&bom, 0, 9, 1, 2).is_err());
+}
+
+#[test]
+#[cfg(feature = "default-endian")]
+fn default_endian() {
+    use std::panic::{self, AssertUnwindSafe};
+    use scroll::{set_default_endian, with_default_endian, Cread, Endian, IOread, Pwrite, BE, LE, NATIVE};
+    let bytes = [0x01, 0x02, 0x03, 0x04];
+    let other = if NATIVE == LE { BE } else { LE };
+    assert_eq!(Endian::default(), NATIVE);
+
+    let (read, cread, io) = with_default_endian(BE, || {
+        let mut out = [0u8; 4];
+        out.pwrite(0x0a0bu16, 0).unwrap();
+        assert_eq!(out[..2], [0x0a, 0x0b]);
+        // an explicit byte order always wins
+        assert_eq!(bytes.pread_with::<u16>(0, LE).unwrap(), 0x0201);
+        let io: u16 = std::io::Cursor::new(&bytes[2..]).ioread().unwrap();
+        (bytes.pread::<u32>(0).unwrap(), bytes.cread::<u16>(0), io)
+    });
+    assert_eq!((read, cread, io), (0x01020304, 0x0102, 0x0304));
+    assert_eq!(Endian::default(), NATIVE);
+
+    // scopes nest, and each puts back the one it replaced, even when unwinding
+    let unwound = panic::catch_unwind(AssertUnwindSafe(|| {
+        with_default_endian(other, || {
+            let inner = with_default_endian(NATIVE, Endian::default);
+            assert_eq!(inner, NATIVE);
+            assert_eq!(Endian::default(), other);
+            with_default_endian(LE, || panic!("unwinding through the scope"))
+        })
+    }));
+    assert!(unwound.is_err());
+    assert_eq!(Endian::default(), NATIVE);
+
+    // the default is per thread
+    assert_eq!(set_default_endian(other), NATIVE);
+    let elsewhere = std::thread::spawn(Endian::default).join().unwrap();
+    assert_eq!(elsewhere, NATIVE);
+    assert_eq!(Endian::default(), other);
+    assert_eq!(set_default_endian(NATIVE), other);
+}
+
+#[test]
+fn gwrite_struct_padded() {
+    use scroll::{Error, Pwrite, BE};
+    let mut table = vec![0xaau8; 10];
+    let offset = &mut 0;
+    assert_eq!(table.gwrite_struct_padded(0xbeefu16, 4, offset, BE).unwrap(), 4);
+    assert_eq!(*offset, 4);
+    assert_eq!(table[..4], [0xbe, 0xef, 0, 0]);
+    // an exact fit needs no padding
+    table.gwrite_struct_padded(0x0102_0304u32, 4, offset, BE).unwrap();
+    assert_eq!(table, [0xbe, 0xef, 0, 0, 1, 2, 3, 4, 0xaa, 0xaa]);
+
+    assert!(matches!(table.gwrite_struct_padded(1u16, 4, offset, BE), Err(Error::TooBig { size: 4, len: 2 })));
+    assert_eq!(*offset, 8);
+    let offset = &mut 4;
+    assert!(matches!(table.gwrite_struct_padded(0xffff_ffffu32, 2, offset, BE), Err(Error::BadInput { size: 4, .. })));
+    assert_eq!(*offset, 4);
+    // the value didn't fit its slot, so none of it was written
+    assert_eq!(table, [0xbe, 0xef, 0, 0, 1, 2, 3, 4, 0xaa, 0xaa]);
+    assert!(matches!(table.gwrite_struct_padded(1u8, 1, &mut 11, BE), Err(Error::BadOffset(11))));
+}
+
+#[test]
+fn pwrite_slice_with() {
+    use scroll::{ctx::Primitive, ctx::TryIntoCtx, Endian, Error, Pwrite, PwriteExt, BE, LE};
+    fn same_as_elementwise<T: Primitive + TryIntoCtx<Endian, Error = Error>>(values: &[T]) {
+        for &endian in &[LE, BE] {
+            let len = 1 + std::mem::size_of_val(values);
+            let (mut bulk, mut elementwise) = (vec![0xaau8; len], vec![0xaau8; len]);
+            let offset = &mut 1;
+            assert_eq!(bulk.gwrite_slice_with(offset, values, endian).unwrap(), len - 1);
+            assert_eq!(*offset, len);
+            let offset = &mut 1;
+            for value in values {
+                elementwise.gwrite_with(*value, offset, endian).unwrap();
+            }
+            assert_eq!(bulk, elementwise);
+        }
+    }
+    same_as_elementwise(&[0x12u8, 0x34]);
+    same_as_elementwise(&[-2i16, 0x1234]);
+    same_as_elementwise(&(0..1000u32).map(|i| i.wrapping_mul(0x9e37_79b9)).collect::<Vec<_>>());
+    same_as_elementwise(&[i64::MIN, -1, 0x0102_0304_0506_0708]);
+    same_as_elementwise(&[u128::MAX - 1, 7]);
+    same_as_elementwise(&[1.5f32, -0.0, f32::INFINITY]);
+    same_as_elementwise(&[std::f64::consts::PI, f64::MIN_POSITIVE]);
+    same_as_elementwise::<u32>(&[]);
+
+    let mut bytes = [0u8; 8];
+    assert!(matches!(bytes.pwrite_slice_with(2, &[1u32, 2], LE), Err(Error::TooBig { size: 8, len: 6 })));
+    assert_eq!(bytes, [0; 8]);
+    assert!(matches!(bytes.pwrite_slice_with(9, &[1u8], LE), Err(Error::BadOffset(9))));
+    assert_eq!(bytes.pwrite_slice_with(8, &[0u8; 0], LE).unwrap(), 0);
+}
+
+#[test]
+fn pread_optional_at() {
+    use scroll::{ctx::StrCtx, Error, BE};
+    // a header whose version 2 appends a u32 checksum
+    let v2 = [0x00, 0x02, 0xde, 0xad, 0xbe, 0xef];
+    assert_eq!(v2.pread_optional_at::<u32>(2, BE).unwrap().unwrap(), 0xdeadbeef);
+    let v1 = &v2[..2];
+    assert!(v1.pread_optional_at::<u32>(2, BE).is_none());
+    assert!(v1.pread_optional_at::<u32>(3, BE).is_none());
+    // one byte short of the checksum is a truncated field, not a missing one
+    assert!(matches!(v2[..5].pread_optional_at::<u32>(2, BE), Some(Err(Error::Incomplete { needed: 1 }))));
+    // parse errors still surface
+    let name = [b'a', 0xff, 0];
+    assert!(matches!(name.pread_optional_at::<&str>(0, StrCtx::Delimiter(0)), Some(Err(Error::BadInput { .. }))));
+    assert!(name.pread_optional_at::<&str>(3, StrCtx::Delimiter(0)).is_none());
+}
+
+#[test]
+fn to_vec_and_to_array() {
+    use scroll::{ctx::{self, Str, StrCtx}, to_array, to_vec, to_vec_sized, Endian, Error, Sleb128, Uleb128, LE};
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Entry {
+        id: u16,
+        name: String,
+    }
+    impl<'a> ctx::TryFromCtx<'a, Endian> for Entry {
+        type Error = Error;
+        fn try_from_ctx(src: &'a [u8], le: Endian) -> Result<(Self, usize)> {
+            let offset = &mut 0;
+            let id = src.gread_with(offset, le)?;
+            let name: &str = src.gread_with(offset, StrCtx::Delimiter(0))?;
+            Ok((Entry { id, name: name.to_string() }, *offset))
+        }
+    }
+    impl ctx::TryIntoCtx<Endian> for Entry {
+        type Error = Error;
+        fn try_into_ctx(self, dst: &mut [u8], le: Endian) -> Result<usize> {
+            use scroll::Pwrite;
+            let offset = &mut 0;
+            dst.gwrite_with(self.id, offset, le)?;
+            dst.gwrite_with(Str(&self.name), offset, StrCtx::Delimiter(0))?;
+            Ok(*offset)
+        }
+    }
+
+    // statically sized
+    let bytes = to_vec(-2i32, LE).unwrap();
+    assert_eq!(bytes, [0xfe, 0xff, 0xff, 0xff]);
+    assert_eq!(bytes.pread_with::<i32>(0, LE).unwrap(), -2);
+    assert_eq!(to_array::<4, _, _>(-2i32, LE).unwrap(), bytes[..]);
+
+    // variable sized, and longer than the first guess
+    let entry = Entry { id: 7, name: "x".repeat(300) };
+    let bytes = to_vec(entry.clone(), LE).unwrap();
+    assert_eq!(bytes.len(), 2 + 300 + 1);
+    assert_eq!(bytes.pread_with::<Entry>(0, LE).unwrap(), entry);
+    assert!(matches!(to_array::<8, _, _>(entry, LE), Err(Error::BadOffset(_)) | Err(Error::TooBig { .. })));
+
+    // variable length integers
+    let n: Uleb128 = [0xe5u8, 0x8e, 0x26].pread(0).unwrap();
+    let bytes = to_vec(n, ()).unwrap();
+    assert_eq!(bytes.pread::<Uleb128>(0).unwrap(), n);
+    let n: Sleb128 = [0x80u8, 0x7f].pread(0).unwrap();
+    assert_eq!(to_vec(&n, ()).unwrap(), [0x80, 0x7f]);
+    assert!(matches!(to_array::<3, _, _>(n, ()), Err(Error::BadInput { size: 2, .. })));
+
+    // errors which growing doesn't fix are returned, not retried until the limit
+    #[derive(Clone)]
+    struct Fails(fn() -> Error);
+    impl ctx::TryIntoCtx for Fails {
+        type Error = Error;
+        fn try_into_ctx(self, _: &mut [u8], _: ()) -> Result<usize> {
+            Err((self.0)())
+        }
+    }
+    assert!(matches!(to_vec(Fails(|| Error::BadOffset(3)), ()), Err(Error::BadOffset(3))));
+    assert!(matches!(to_vec(Fails(|| Error::TooBig { size: 9, len: 1 }), ()), Err(Error::TooBig { size: 9, len: 1 })));
+    assert!(matches!(to_vec(Fails(|| Error::BadOffset(usize::MAX)), ()), Err(Error::LimitExceeded("size"))));
+    assert!(matches!(to_vec(Str(&"x".repeat(17 << 20)), StrCtx::Length(17 << 20)), Err(Error::LimitExceeded("size"))));
+
+    // sized up front
+    assert_eq!(to_vec_sized(0x0102u16, scroll::BE).unwrap(), [0x01, 0x02]);
+    assert_eq!(to_vec_sized(-2i32, LE).unwrap(), to_vec(-2i32, LE).unwrap());
+}
+
+#[test]
+fn erased_pread() {
+    use scroll::{ctx::TryFromCtx, Endian, Error, ErasedPread, Pread, BE};
+
+    #[derive(Debug, PartialEq)]
+    struct Header {
+        magic: u32,
+        count: u16,
+    }
+
+    impl<'a> TryFromCtx<'a, Endian> for Header {
+        type Error = Error;
+        fn try_from_ctx(src: &'a [u8], endian: Endian) -> Result<(Self, usize)> {
+            let offset = &mut 0;
+            let magic = src.gread_with(offset, endian)?;
+            let count = src.gread_with(offset, endian)?;
+            Ok((Header { magic, count }, *offset))
+        }
+    }
+
+    impl SizeWith<Endian> for Header {
+        fn size_with(_: &Endian) -> usize {
+            6
+        }
+    }
+
+    // what a plugin sees
+    fn parse(src: &dyn ErasedPread) -> Result<(Header, [u16; 3])> {
+        let offset = &mut 0;
+        let header: Header = src.gread_with(offset, BE)?;
+        let mut entries = [0u16; 3];
+        src.gread_inout_with(offset, &mut entries, BE)?;
+        Ok((header, entries))
+    }
+
+    let bytes = vec![0xca, 0xfe, 0xba, 0xbe, 0x00, 0x03, 0x00, 0x01, 0x00, 0x02, 0x00, 0x03];
+    let offset = &mut 0;
+    let header: Header = bytes.gread_with(offset, BE).unwrap();
+    let mut entries = [0u16; 3];
+    bytes.gread_inout_with(offset, &mut entries, BE).unwrap();
+
+    let boxed: Box<dyn ErasedPread> = Box::new(bytes.clone());
+    assert_eq!(boxed.len(), 12);
+    assert_eq!(parse(&*boxed).unwrap(), (header, entries));
+    assert_eq!(parse(&&bytes[..]).unwrap(), parse(&bytes).unwrap());
+    assert!(matches!(parse(&&bytes[..11]), Err(Error::TooBig { size: 2, len: 1 })));
+}
+
+#[test]
+fn scroll_writer() {
+    use std::io::{ErrorKind, Write};
+    use scroll::{Pwrite, ScrollWriter, LE};
+
+    // what an encoder which only knows `Write` does
+    fn encode<W: Write>(w: &mut W, values: &[u16]) -> std::io::Result<()> {
+        for value in values {
+            w.write_all(&value.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    let mut bytes = [0xaau8; 16];
+    bytes.pwrite_with(0x1234u16, 0, LE).unwrap();
+    let mut writer = ScrollWriter::new(&mut bytes, 2);
+    write!(writer, "v{}", 7).unwrap();
+    encode(&mut writer, &[0xbeef, 0xcafe]).unwrap();
+    writer.flush().unwrap();
+    let offset = writer.position();
+    assert_eq!(offset, 8);
+    bytes.pwrite_with(0x5678u16, offset, LE).unwrap();
+    assert_eq!(bytes, [0x34, 0x12, b'v', b'7', 0xef, 0xbe, 0xfe, 0xca, 0x78, 0x56, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa]);
+
+    // a full destination takes what fits, then nothing
+    let mut writer = ScrollWriter::new(&mut bytes[..12], 10);
+    assert_eq!(writer.write(&[1, 2, 3]).unwrap(), 2);
+    assert_eq!(writer.write(&[3]).unwrap(), 0);
+    assert_eq!(encode(&mut writer, &[0]).unwrap_err().kind(), ErrorKind::WriteZero);
+    assert_eq!(writer.position(), 12);
+    assert_eq!(bytes[10..], [1, 2, 0xaa, 0xaa, 0xaa, 0xaa]);
+    assert_eq!(ScrollWriter::new(&mut bytes, 20).write(&[0]).unwrap(), 0);
+}
+
+#[cfg(feature = "location-tracking")]
+#[test]
+fn pread_here() {
+    use scroll::{pread_here, Error, LE};
+    let bytes = [0xef, 0xbe, 0xad, 0xde];
+    let n: u32 = pread_here!(bytes, 0, LE).unwrap();
+    assert_eq!(n, 0xdeadbeef);
+    let n: u8 = pread_here!(&bytes[..], 3).unwrap();
+    assert_eq!(n, 0xde);
+
+    let line = line!() + 1;
+    let err = pread_here!(bytes, 2, LE).map(|n: u32| n).unwrap_err();
+    match err {
+        Error::Located { file, line: at, ref source } => {
+            assert_eq!(file, file!());
+            assert_eq!(at, line);
+            assert!(matches!(**source, Error::Incomplete { needed: 2 }));
+        },
+        ref other => panic!("{:?}", other),
+    }
+    assert_eq!(err.to_string(), format!("{}:{}: incomplete input, 2 more bytes needed", file!(), line));
+    // the location is transparent to the error's offset
+    let err = Error::BadOffset(9).at_location("parser.rs", 12);
+    assert_eq!(err.offset(), Some(9));
+    assert_eq!(err.to_string(), "parser.rs:12: bad offset 9");
+}
+
+#[test]
+fn scroll_reader() {
+    use std::io::{self, BufRead, Read};
+    use scroll::{Error, ScrollReader};
+
+    let bytes: Vec<u8> = (0..=255).collect();
+    let mut reader = ScrollReader::new(&bytes, 16..200).unwrap();
+    let mut out = Vec::new();
+    assert_eq!(io::copy(&mut reader, &mut out).unwrap(), 184);
+    assert_eq!(out, bytes.pread_with::<&[u8]>(16, 184).unwrap());
+    assert_eq!(reader.position(), 184);
+    assert_eq!(reader.read(&mut [0u8; 4]).unwrap(), 0);
+
+    // the region is all a reader sees, and it lends rather than copies
+    let mut reader = ScrollReader::new(&bytes[..], 250..256).unwrap();
+    let mut head = [0u8; 2];
+    reader.read_exact(&mut head).unwrap();
+    assert_eq!(head, [250, 251]);
+    assert!(std::ptr::eq(reader.fill_buf().unwrap(), &bytes[252..]));
+    reader.consume(3);
+    assert_eq!(reader.remaining(), [255]);
+    assert!(reader.read_exact(&mut head).is_err());
+    assert!(ScrollReader::new(&bytes, 0..0).unwrap().fill_buf().unwrap().is_empty());
+
+    assert!(matches!(ScrollReader::new(&bytes, 200..257), Err(Error::BadRange { start: 200, end: 257, len: 256 })));
+    #[allow(clippy::reversed_empty_ranges)]
+    let reversed = ScrollReader::new(&bytes, 9..3);
+    assert!(matches!(reversed, Err(Error::BadRange { .. })));
+}
+
+#[test]
+fn ioread_short_reads() {
+    use std::io::{self, ErrorKind, Read};
+    use scroll::{Error, IOread, BE, LE};
+
+    /// Returns one byte per read, and an `Interrupted` error before every other one if `interrupt` is set
+    struct Trickle<'a> {
+        bytes: &'a [u8],
+        interrupt: bool,
+        interrupted: bool,
+    }
+
+    impl<'a> Read for Trickle<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.interrupt && !self.interrupted {
+                self.interrupted = true;
+                return Err(io::Error::from(ErrorKind::Interrupted));
+            }
+            self.interrupted = false;
+            match (self.bytes.split_first(), buf.first_mut()) {
+                (Some((&byte, rest)), Some(slot)) => {
+                    *slot = byte;
+                    self.bytes = rest;
+                    Ok(1)
+                },
+                _ => Ok(0),
+            }
+        }
+    }
+
+    let bytes = [0xde, 0xad, 0xbe, 0xef, 0x01, 0x02, 0x03, 0x04, 0x05];
+    for &interrupt in &[false, true] {
+        let mut reader = Trickle { bytes: &bytes, interrupt, interrupted: false };
+        assert_eq!(reader.ioread_with::<u32>(BE).unwrap(), 0xdeadbeef);
+        assert_eq!(reader.try_ioread_with::<u32>(LE).unwrap(), Some(0x04030201));
+        // a u32 torn after its first byte
+        let error = reader.try_ioread_with::<u32>(LE).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+        assert!(matches!(error.get_ref().and_then(|e| e.downcast_ref()), Some(Error::Incomplete { needed: 3 })));
+        // the stream ended cleanly between values
+        assert_eq!(reader.try_ioread_with::<u32>(LE).unwrap(), None);
+        let error = reader.ioread_with::<u16>(LE).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+        assert!(matches!(error.get_ref().and_then(|e| e.downcast_ref()), Some(Error::Incomplete { needed: 2 })));
+    }
+
+    /// Fails every read with something other than `Interrupted`
+    struct Broken;
+
+    impl Read for Broken {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::from(ErrorKind::ConnectionReset))
+        }
+    }
+
+    assert_eq!(Broken.try_ioread_with::<u32>(LE).unwrap_err().kind(), ErrorKind::ConnectionReset);
+}
+
+#[test]
+fn counting_reader() {
+    use std::io::{Cursor, ErrorKind, Read, Seek, SeekFrom};
+    use scroll::{CountingReader, CountingWriter, Error, BE};
+
+    #[derive(Debug, PartialEq)]
+    struct Header {
+        magic: u32,
+        version: u16,
+        count: u16,
+    }
+
+    impl ctx::FromCtx<scroll::Endian> for Header {
+        fn from_ctx(bytes: &[u8], ctx: scroll::Endian) -> Self {
+            Header { magic: bytes.cread_with(0, ctx), version: bytes.cread_with(4, ctx), count: bytes.cread_with(6, ctx) }
+        }
+    }
+
+    impl ctx::SizeWith<scroll::Endian> for Header {
+        fn size_with(_: &scroll::Endian) -> usize {
+            8
+        }
+    }
+
+    let mut out = CountingWriter::new(Vec::new());
+    out.iowrite_with(0x7f454c46u32, BE).unwrap();
+    out.iowrite_with(2u16, BE).unwrap();
+    out.iowrite_with(3u16, BE).unwrap();
+    out.iowrite_with(0xaau8, BE).unwrap();
+    assert_eq!(out.position(), 9);
+    let bytes = out.into_inner();
+
+    let mut reader = CountingReader::new(Cursor::new(&bytes));
+    let header: Header = reader.ioread_with(BE).unwrap();
+    assert_eq!(header, Header { magic: 0x7f454c46, version: 2, count: 3 });
+    assert_eq!(reader.position(), Header::size_with(&BE) as u64);
+
+    // the first entry is torn, and the error says where it started
+    let error = reader.ioread_with::<u32, _>(BE).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+    assert!(error.to_string().contains("offset 8"));
+    let error = error.into_inner().unwrap().downcast::<Error>().unwrap();
+    assert_eq!(error.offset(), Some(8));
+    assert_eq!(reader.position(), 9);
+
+    // seeking keeps the count where the inner reader is
+    assert_eq!(reader.seek(SeekFrom::Start(4)).unwrap(), 4);
+    assert_eq!(reader.position(), 4);
+    assert_eq!(reader.ioread_with::<u16, _>(BE).unwrap(), 2);
+    let mut rest = Vec::new();
+    reader.read_to_end(&mut rest).unwrap();
+    assert_eq!(reader.position(), 9);
+    assert_eq!(reader.get_ref().position(), 9);
+}
+
+#[test]
+fn fallible_allocation() {
+    use scroll::{CowBuffer, Error, Pwrite, BE, ctx::{Count, StrCtx, TryAlloc}};
+
+    // far more than any allocator can hand out, but an error rather than an abort
+    assert!(matches!(CowBuffer::try_zeroed(usize::MAX), Err(Error::AllocFailed { size: usize::MAX })));
+    assert!(matches!(CowBuffer::try_with_capacity(isize::MAX as usize), Err(Error::AllocFailed { .. })));
+    let bytes = [0x00, 0x01, 0x00, 0x02, b'o', b'k', 0x00];
+    let mut buffer = CowBuffer::new(&bytes[..]);
+    assert!(matches!(buffer.try_reserve(usize::MAX), Err(Error::AllocFailed { .. })));
+    assert!(!buffer.is_owned());
+
+    // normal sizes are unaffected
+    buffer.try_reserve(64).unwrap();
+    assert!(buffer.is_owned());
+    assert!(buffer.capacity() >= bytes.len() + 64);
+    assert_eq!(buffer.as_slice(), bytes);
+    let mut zeroed = CowBuffer::try_zeroed(4096).unwrap();
+    assert_eq!(zeroed.len(), 4096);
+    zeroed.pwrite_with(0xdeadbeefu32, 4092, BE).unwrap();
+    assert!(CowBuffer::try_with_capacity(16).unwrap().is_empty());
+
+    let lens: Vec<u16> = bytes.pread_with(0, TryAlloc(Count(2, BE))).unwrap();
+    assert_eq!(lens, bytes.pread_with::<Vec<u16>>(0, Count(2, BE)).unwrap());
+    let name: String = bytes.pread_with(4, TryAlloc(StrCtx::Delimiter(0))).unwrap();
+    assert_eq!(name, "ok");
+    assert!(bytes.pread_with::<Vec<u16>>(0, TryAlloc(Count(8, BE))).is_err());
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Rva(u32);
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Delta(i16);
+scroll::endian_newtype!(Rva(u32), Delta(i16));
+
+#[test]
+fn endian_newtype() {
+    use scroll::{Error, IOread, IOwrite, Pwrite, BE, LE, ctx::ConstSize};
+
+    let mut bytes = [0u8; 12];
+    let offset = &mut 0;
+    bytes.gwrite_with(Rva(0xdeadbeef), offset, LE).unwrap();
+    bytes.gwrite_with(&Rva(0xdeadbeef), offset, BE).unwrap();
+    bytes.gwrite_with(Delta(-2), offset, LE).unwrap();
+    bytes.gwrite_with(Delta(-2), offset, BE).unwrap();
+    assert_eq!(bytes, [0xef, 0xbe, 0xad, 0xde, 0xde, 0xad, 0xbe, 0xef, 0xfe, 0xff, 0xff, 0xfe]);
+
+    let offset = &mut 0;
+    assert_eq!(bytes.gread_with::<Rva>(offset, LE).unwrap(), Rva(0xdeadbeef));
+    assert_eq!(bytes.gread_with::<Rva>(offset, BE).unwrap(), Rva(0xdeadbeef));
+    assert_eq!(bytes.gread_with::<Delta>(offset, LE).unwrap(), Delta(-2));
+    assert_eq!(bytes.gread_with::<Delta>(offset, BE).unwrap(), Delta(-2));
+    assert_eq!(bytes.pread_with::<Rva>(4, LE).unwrap(), Rva(0xefbeadde));
+    assert_eq!(bytes.cread_with::<Rva>(0, LE), Rva(0xdeadbeef));
+    assert!(matches!(bytes.pread_with::<Rva>(10, LE), Err(Error::Incomplete { needed: 2 })));
+    assert!(matches!(bytes.pwrite_with(Rva(0), 10, LE), Err(Error::TooBig { size: 4, len: 2 })));
+    assert_eq!(Rva::size_with(&LE), 4);
+    assert_eq!(<Delta as ConstSize>::SIZE, 2);
+
+    let mut cursor = std::io::Cursor::new(Vec::new());
+    cursor.iowrite_with(Rva(0x1000), BE).unwrap();
+    cursor.set_position(0);
+    assert_eq!(cursor.ioread_with::<Rva>(BE).unwrap(), Rva(0x1000));
+}
+
+#[test]
+fn into_io_error() {
+    use std::io::{self, ErrorKind};
+    use scroll::{Error, LE};
+
+    fn kind(err: Error) -> ErrorKind {
+        io::Error::from(err).kind()
+    }
+    assert_eq!(kind(Error::BadOffset(8)), ErrorKind::UnexpectedEof);
+    assert_eq!(kind(Error::BadRange { start: 0, end: 8, len: 4 }), ErrorKind::UnexpectedEof);
+    assert_eq!(kind(Error::Incomplete { needed: 2 }), ErrorKind::UnexpectedEof);
+    assert_eq!(kind(Error::BadInput { size: 4, msg: "invalid utf8" }), ErrorKind::InvalidData);
+    assert_eq!(kind(Error::BadMagic { offset: 0, expected: 0x7f, found: 0 }), ErrorKind::InvalidData);
+    assert_eq!(kind(Error::AllocFailed { size: usize::MAX }), ErrorKind::OutOfMemory);
+    let element = Error::Element { index: 1, offset: 4, source: Box::new(Error::Incomplete { needed: 1 }) };
+    assert_eq!(kind(element), ErrorKind::UnexpectedEof);
+    let detailed = Error::builder().with_offset(4).with_source(Error::Invalid { offset: 4, msg: "bad" }).build();
+    assert_eq!(kind(detailed), ErrorKind::InvalidData);
+    // an io error comes back out as it went in
+    assert_eq!(kind(Error::IO(io::Error::new(ErrorKind::Interrupted, "again"))), ErrorKind::Interrupted);
+
+    // running out of room is a WriteZero from writing, not an UnexpectedEof
+    assert_eq!(Error::TooBig { size: 8, len: 4 }.io_kind(), ErrorKind::UnexpectedEof);
+    assert_eq!(Error::TooBig { size: 8, len: 4 }.into_io_write_error().kind(), ErrorKind::WriteZero);
+    assert_eq!(Error::BadOffset(8).io_write_kind(), ErrorKind::WriteZero);
+    let element = Error::Element { index: 1, offset: 4, source: Box::new(Error::TooBig { size: 2, len: 1 }) };
+    assert_eq!(element.io_write_kind(), ErrorKind::WriteZero);
+    assert_eq!(Error::BadInput { size: 4, msg: "too long" }.io_write_kind(), ErrorKind::InvalidData);
+    assert_eq!(Error::IO(io::Error::new(ErrorKind::Interrupted, "again")).into_io_write_error().kind(), ErrorKind::Interrupted);
+
+    fn read(bytes: &[u8]) -> io::Result<u64> {
+        Ok(bytes.pread_with(0, LE)?)
+    }
+    let err = read(&[1, 2, 3]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    let inner = err.get_ref().and_then(|err| err.downcast_ref::<Error>());
+    assert!(matches!(inner, Some(Error::Incomplete { needed: 5 })));
+    assert!(matches!(err.into_inner().unwrap().downcast::<Error>().map(|err| *err), Ok(Error::Incomplete { needed: 5 })));
+}
+
+#[test]
+fn bitmaps() {
+    use scroll::{Error, Pwrite, ctx::{BitOrder, BitsCtx}};
+
+    // 11 bools over two bytes, with the last 5 bits of the second unused, and set here
+    let bytes = [0b0110_0001, 0b1111_1101];
+    let lsb: [bool; 11] = bytes.pread_with(0, BitOrder::LsbFirst).unwrap();
+    assert_eq!(lsb, [true, false, false, false, false, true, true, false, true, false, true]);
+    let msb: [bool; 11] = bytes.pread_with(0, BitOrder::MsbFirst).unwrap();
+    assert_eq!(msb, [false, true, true, false, false, false, false, true, true, true, true]);
+    let vec: Vec<bool> = bytes.pread_with(0, BitsCtx::new(11, BitOrder::MsbFirst)).unwrap();
+    assert_eq!(vec, msb);
+    assert_eq!(<[bool; 11]>::size_with(&BitOrder::LsbFirst), 2);
+    assert_eq!(<[bool; 16]>::size_with(&BitOrder::LsbFirst), 2);
+
+    // writing zeroes the unused bits, so the bytes don't round trip, but the bools do
+    let mut out = [0xff; 3];
+    let offset = &mut 0;
+    out.gwrite_with(lsb, offset, BitOrder::LsbFirst).unwrap();
+    assert_eq!(*offset, 2);
+    assert_eq!(out, [0b0110_0001, 0b0000_0101, 0xff]);
+    out.pwrite_with(&vec, 0, BitOrder::MsbFirst).unwrap();
+    assert_eq!(out, [0b0110_0001, 0b1110_0000, 0xff]);
+    assert_eq!(out.pread_with::<[bool; 11]>(0, BitOrder::MsbFirst).unwrap(), msb);
+    out.pwrite_with(&[true; 3][..], 2, BitOrder::MsbFirst).unwrap();
+    assert_eq!(out[2], 0b1110_0000);
+
+    let empty: [bool; 0] = bytes.pread_with(2, BitOrder::LsbFirst).unwrap();
+    assert_eq!(empty, []);
+    assert!(matches!(bytes.pread_with::<[bool; 17]>(0, BitOrder::LsbFirst), Err(Error::Incomplete { needed: 1 })));
+    assert!(bytes.pread_with::<Vec<bool>>(0, BitsCtx::new(usize::MAX, BitOrder::LsbFirst)).is_err());
+    assert!(matches!(out.pwrite_with([true; 9], 2, BitOrder::LsbFirst), Err(Error::TooBig { size: 2, len: 1 })));
+}
+
+#[test]
+fn seek_reader() {
+    use std::io::{self, Cursor, ErrorKind, Read, Seek, SeekFrom};
+    use std::sync::Arc;
+    use scroll::{Error, SeekReader, BE, LE};
+
+    /// Reads at most `chunk` bytes at a time, and seeking past the bytes it holds is an error; measured, though, its
+    /// end is `claimed` bytes in, which can be past them, as for a source which shrank after being measured
+    struct Chunked {
+        bytes: Vec<u8>,
+        pos: u64,
+        chunk: usize,
+        claimed: usize,
+    }
+
+    impl Read for Chunked {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let rest = self.bytes.get(self.pos as usize..).unwrap_or(&[]);
+            let len = buf.len().min(rest.len()).min(self.chunk);
+            buf[..len].copy_from_slice(&rest[..len]);
+            self.pos += len as u64;
+            Ok(len)
+        }
+    }
+
+    impl Seek for Chunked {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            let pos = match pos {
+                SeekFrom::Start(pos) => pos as i64,
+                // only measuring is expected
+                SeekFrom::End(delta) => return Ok(self.claimed as u64 - delta as u64),
+                SeekFrom::Current(delta) => self.pos as i64 + delta,
+            };
+            if pos < 0 || pos as usize > self.bytes.len() {
+                return Err(io::Error::new(ErrorKind::InvalidInput, "seek out of bounds"));
+            }
+            self.pos = pos as u64;
+            Ok(self.pos)
+        }
+    }
+
+    let bytes: Vec<u8> = (0..=255).collect();
+    let chunked = Chunked { bytes: bytes.clone(), pos: 0, chunk: 3, claimed: 256 };
+    trait Source: Read + Seek {}
+    impl<T: Read + Seek> Source for T {}
+    let boxed: Box<dyn Source> = Box::new(Cursor::new(bytes.clone()));
+    let cursor = SeekReader::new(boxed).unwrap();
+    let chunked = SeekReader::new(chunked).unwrap();
+    for (offset, expected) in [(0, 0x0001_0203u32), (252, 0xfcfd_feff), (17, 0x1112_1314)] {
+        assert_eq!(cursor.pread_with::<u32, _>(offset, BE).unwrap(), expected);
+        assert_eq!(chunked.pread_with::<u32, _>(offset, BE).unwrap(), expected);
+    }
+    // a value larger than a chunk, read with the other's calls in between
+    let (a, b) = (&mut 100, &mut 200);
+    for _ in 0..4 {
+        let x: u64 = chunked.gread_with(a, LE).unwrap();
+        let y: u64 = chunked.gread_with(b, LE).unwrap();
+        assert_eq!(x.to_le_bytes()[0] as usize + 100, y.to_le_bytes()[0] as usize);
+    }
+    assert_eq!((*a, *b), (132, 232));
+    assert_eq!(chunked.into_inner().bytes, bytes);
+
+    // past the end, as a slice would be
+    assert!(matches!(cursor.pread_with::<u32, _>(256, LE), Err(Error::TooBig { size: 4, len: 0 })));
+    assert!(matches!(cursor.pread_with::<u32, _>(254, LE), Err(Error::TooBig { size: 4, len: 2 })));
+    assert!(matches!(cursor.pread_with::<u8, _>(257, LE), Err(Error::BadOffset(257))));
+    // the source failing to seek or read is an io error
+    let shrunk = Chunked { bytes: bytes[..250].to_vec(), pos: 0, chunk: 7, claimed: 256 };
+    let shrunk = SeekReader::new(shrunk).unwrap();
+    assert_eq!(shrunk.len(), 256);
+    match shrunk.pread_with::<u32, _>(252, LE) {
+        Err(Error::IO(err)) => assert_eq!(err.kind(), ErrorKind::InvalidInput),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    match shrunk.pread_with::<u32, _>(248, LE) {
+        Err(Error::IO(err)) => assert_eq!(err.kind(), ErrorKind::UnexpectedEof),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(shrunk.pread_with::<u16, _>(248, BE).unwrap(), 0xf8f9);
+
+    // reads from several threads don't interleave their seeks
+    let shared = Arc::new(SeekReader::new(Cursor::new(bytes.clone())).unwrap());
+    let threads: Vec<_> = (0..4).map(|t| {
+        let shared = Arc::clone(&shared);
+        std::thread::spawn(move || {
+            for i in 0..1000 {
+                let offset = (t * 61 + i * 7) % 250;
+                let value: u32 = shared.pread_with(offset, BE).unwrap();
+                assert_eq!(value.to_be_bytes(), [offset as u8, offset as u8 + 1, offset as u8 + 2, offset as u8 + 3]);
+            }
+        })
+    }).collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}
+
+#[test]
+fn paged_source() {
+    use std::borrow::Cow;
+    use std::cell::Cell;
+    use std::convert::TryInto;
+    use scroll::{ErasedPread, Error, PageProvider, PagedSource, BE, LE};
+
+    /// 58 bytes in 16 byte pages, the last one padded out, which counts the pages fetched, and can fail to fetch one, or
+    /// hand out one too short
+    struct Pages {
+        pages: Vec<[u8; 16]>,
+        fetched: Cell<usize>,
+        failing: Option<u64>,
+        short: Option<u64>,
+    }
+
+    impl PageProvider for Pages {
+        fn page_size(&self) -> usize {
+            16
+        }
+        fn total_len(&self) -> u64 {
+            58
+        }
+        fn page(&self, index: u64) -> Result<&[u8]> {
+            self.fetched.set(self.fetched.get() + 1);
+            if self.failing == Some(index) {
+                return Err(Error::BadInput { size: index as usize, msg: "sector unreadable" });
+            }
+            let page = &self.pages[index as usize][..];
+            Ok(if self.short == Some(index) { &page[..8] } else { page })
+        }
+    }
+
+    let bytes: Vec<u8> = (0..64).map(|i| i as u8 * 3).collect();
+    let pages = || Pages {
+        pages: bytes.chunks(16).map(|page| page.try_into().unwrap()).collect(),
+        fetched: Cell::new(0),
+        failing: None,
+        short: None,
+    };
+    let source = PagedSource::new(pages()).unwrap();
+    assert_eq!(source.len(), 58);
+
+    // every way a u32 splits across each boundary, 1/3, 2/2 and 3/1, and not at all on either side
+    for boundary in [16, 32, 48] {
+        for offset in boundary - 4..=boundary {
+            let expected = u32::from_be_bytes(bytes[offset..offset + 4].try_into().unwrap());
+            assert_eq!(source.pread_with::<u32, _>(offset, BE).unwrap(), expected, "at {}", offset);
+            assert_eq!(source.pread_with::<u32, _>(offset, LE).unwrap(), expected.swap_bytes());
+        }
+    }
+    let offset = &mut 1;
+    while *offset + 8 <= 58 {
+        let at = *offset;
+        let value: u64 = source.gread_with(offset, LE).unwrap();
+        assert_eq!(value, u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap()));
+    }
+    assert_eq!(*offset, 57);
+
+    // a copy spanning three pages fetches each once
+    let before = source.into_inner();
+    before.fetched.set(0);
+    let source = PagedSource::new(before).unwrap();
+    let mut buffer = [0u8; 40];
+    source.read_bytes(10, &mut buffer).unwrap();
+    assert_eq!(buffer[..], bytes[10..50]);
+    let fetched = source.into_inner().fetched.get();
+    assert_eq!(fetched, 4);
+
+    let source = PagedSource::new(pages()).unwrap();
+    let slice = source.pread_slice(20, 12).unwrap();
+    assert_eq!(slice, &bytes[20..32]);
+    assert!(matches!(source.pread_slice(30, 3), Err(Error::Invalid { offset: 32, .. })));
+    assert_eq!(source.pread_slice(58, 0).unwrap(), b"");
+    assert!(matches!(source.pread_cow(20, 12).unwrap(), Cow::Borrowed(b) if b.as_ptr() == slice.as_ptr()));
+    assert!(matches!(source.pread_cow(30, 20).unwrap(), Cow::Owned(ref b) if b[..] == bytes[30..50]));
+
+    // the padding past the end of the source isn't in it
+    assert!(matches!(source.pread_with::<u32, _>(56, LE), Err(Error::TooBig { size: 4, len: 2 })));
+    assert!(matches!(source.pread_with::<u16, _>(59, LE), Err(Error::BadOffset(59))));
+    assert!(matches!(source.pread_slice(50, 10), Err(Error::TooBig { size: 10, len: 8 })));
+    assert!(matches!(source.pread_cow(40, 20), Err(Error::TooBig { size: 20, len: 18 })));
+
+    let source = PagedSource::new(Pages { failing: Some(2), ..pages() }).unwrap();
+    assert!(source.pread_with::<u32, _>(28, LE).is_ok());
+    assert!(matches!(source.pread_with::<u32, _>(30, LE), Err(Error::BadInput { size: 2, msg: "sector unreadable" })));
+    assert!(source.pread_cow(30, 4).is_err());
+    let source = PagedSource::new(Pages { short: Some(1), ..pages() }).unwrap();
+    assert!(source.pread_with::<u64, _>(8, LE).is_ok());
+    assert!(matches!(source.pread_with::<u16, _>(15, LE), Err(Error::Invalid { offset: 16, .. })));
+    assert!(matches!(source.pread_slice(16, 2), Err(Error::Invalid { offset: 16, .. })));
+
+    struct Unpaged;
+    impl PageProvider for Unpaged {
+        fn page_size(&self) -> usize {
+            0
+        }
+        fn total_len(&self) -> u64 {
+            0
+        }
+        fn page(&self, _: u64) -> Result<&[u8]> {
+            Ok(&[])
+        }
+    }
+    assert!(matches!(PagedSource::new(Unpaged), Err(Error::BadInput { size: 0, .. })));
+}
+
+#[test]
+fn bit_writer() {
+    use scroll::ctx::BitOrder;
+    use scroll::{BitCursor, BitWriter, Error, BE, LE};
+
+    // pseudorandom (value, width) pairs, widths 0 through 64, read back as they were written, in both orders
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    let fields: Vec<(u64, u32)> = (0..2000)
+        .map(|_| {
+            let width = (next() % 65) as u32;
+            (if width == 64 { next() } else { next() & ((1 << width) - 1) }, width)
+        })
+        .collect();
+    let bits: usize = fields.iter().map(|&(_, width)| width as usize).sum();
+    for order in [BitOrder::LsbFirst, BitOrder::MsbFirst] {
+        let mut grown = Vec::new();
+        let mut writer = BitWriter::new(&mut grown, order);
+        for &(value, width) in &fields {
+            writer.write_bits(value, width).unwrap();
+        }
+        assert_eq!(writer.bit_position(), bits);
+        assert_eq!(writer.finish(), bits.div_ceil(8));
+        assert_eq!(grown.len(), bits.div_ceil(8));
+
+        // a slice sink, dirty to begin with, ends up with the same bytes
+        let mut fixed = vec![0xa5u8; grown.len()];
+        let mut writer = BitWriter::new(&mut fixed[..], order);
+        for &(value, width) in &fields {
+            writer.write_bits(value, width).unwrap();
+        }
+        assert_eq!(writer.finish(), grown.len());
+        assert_eq!(fixed, grown);
+
+        let mut cursor = BitCursor::new(&grown, order);
+        for &(value, width) in &fields {
+            assert_eq!(cursor.read_bits(width).unwrap(), value, "{:?} {} bits at {}", order, width, cursor.bit_position());
+        }
+        assert_eq!(cursor.bit_position(), bits);
+    }
+
+    // a final DEFLATE block with the fixed codes holding only its end of block code, and MSB first, a 3 and a 5 bit field
+    let mut bytes = [0xffu8; 2];
+    let mut writer = BitWriter::new(&mut bytes[..], BitOrder::LsbFirst);
+    writer.write_bit(true).unwrap();
+    writer.write_bits(0b01, 2).unwrap();
+    writer.write_bits(0, 7).unwrap();
+    assert_eq!(writer.finish(), 2);
+    assert_eq!(bytes, [0x03, 0x00]);
+    let mut byte = [0u8];
+    let mut writer = BitWriter::new(&mut byte[..], BitOrder::MsbFirst);
+    writer.write_bits(0b101, 3).unwrap();
+    writer.write_bits(0x1f, 5).unwrap();
+    assert_eq!(writer.finish(), 1);
+    assert_eq!(byte, [0xbf]);
+
+    // bits and whole values interleave at byte boundaries, the padding filled either way
+    let mut bytes = Vec::new();
+    let mut writer = BitWriter::new(&mut bytes, BitOrder::MsbFirst);
+    writer.write_bits(0b11, 2).unwrap();
+    assert!(matches!(writer.gwrite_with(7u16, LE), Err(Error::Invalid { offset: 0, .. })));
+    assert!(matches!(writer.write_bytes(b"ab"), Err(Error::Invalid { offset: 0, .. })));
+    writer.align_to_byte(true).unwrap();
+    assert_eq!(writer.gwrite_with(0x0102u16, BE).unwrap(), 2);
+    writer.write_bytes(b"ab").unwrap();
+    writer.write_bits(0b1, 1).unwrap();
+    writer.align_to_byte(false).unwrap();
+    writer.align_to_byte(true).unwrap();
+    assert_eq!(writer.bit_position(), 48);
+    writer.gwrite_with(0xdeadu16, LE).unwrap();
+    assert_eq!(writer.finish(), 8);
+    assert_eq!(bytes, [0xff, 0x01, 0x02, b'a', b'b', 0x80, 0xad, 0xde]);
+    let mut cursor = BitCursor::new(&bytes, BitOrder::MsbFirst);
+    assert_eq!(cursor.read_bits(2).unwrap(), 0b11);
+    assert!(matches!(cursor.gread_with::<u16, _>(BE), Err(Error::Invalid { offset: 0, .. })));
+    cursor.align_to_byte();
+    assert_eq!(cursor.gread_with::<u16, _>(BE).unwrap(), 0x0102);
+    assert_eq!(cursor.gread_with::<&str, _>(scroll::ctx::StrCtx::Length(2)).unwrap(), "ab");
+    assert!(cursor.read_bit().unwrap());
+    cursor.align_to_byte();
+    assert_eq!(cursor.gread_with::<u16, _>(LE).unwrap(), 0xdead);
+    assert!(matches!(cursor.read_bits(1), Err(Error::Incomplete { needed: 1 })));
+
+    // a full slice takes nothing more, and an over wide field or value is refused before anything is written
+    let mut bytes = [0u8; 2];
+    let mut writer = BitWriter::new(&mut bytes[..], BitOrder::LsbFirst);
+    writer.write_bits(0x1ff, 9).unwrap();
+    assert!(matches!(writer.write_bits(0xff, 8), Err(Error::TooBig { size: 2, len: 1 })));
+    assert!(matches!(writer.write_bits(0, 65), Err(Error::BadInput { size: 65, .. })));
+    assert!(matches!(writer.write_bits(4, 2), Err(Error::Invalid { .. })));
+    assert_eq!(writer.bit_position(), 9);
+    writer.write_bits(0x7f, 7).unwrap();
+    assert!(matches!(writer.gwrite_with(1u8, LE), Err(Error::TooBig { size: 1, len: 0 })));
+    assert_eq!(writer.finish(), 2);
+    assert_eq!(bytes, [0xff, 0xff]);
+    let mut cursor = BitCursor::new(&bytes, BitOrder::LsbFirst);
+    assert!(matches!(cursor.read_bits(65), Err(Error::BadInput { size: 65, .. })));
+    assert!(matches!(cursor.read_bits(24), Err(Error::Incomplete { needed: 1 })));
+    assert_eq!(cursor.bit_position(), 0);
+}
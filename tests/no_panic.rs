@@ -0,0 +1,254 @@
+//! Drives the fallible reading and writing API with adversarial offsets, lengths and counts, checking that each call
+//! returns, `Ok` or `Err`, rather than panicking; tests build with overflow checks, so arithmetic overflow panics too
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::ffi::CStr;
+use std::io::Cursor;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::{Duration, SystemTime};
+
+#[cfg(feature = "encoding")]
+use encoding_rs::WINDOWS_1252;
+#[cfg(feature = "bigint")]
+use num_bigint::{BigInt, BigUint};
+
+use scroll::ctx::{Ascii, AsciiNum, AsciiPad, BitOrder, BitsCtx, Count, Duplicates, EncodedLen, EncodedLenCtx, EscapedCtx, FromCtx, IntoCtx, LineCtx, MapCtx, SizeWith, Str, StrCtx, TimeCtx,
+                  TryAlloc, TryFromCtx, TryIntoCtx, Utf8Lossy};
+use scroll::group_varint::{decode_group_varint_n, encode_group_varint};
+use scroll::leb128::{decode_uleb128_all, decode_uleb128_n};
+use scroll::offset::{align_down, align_up, Offset, OffsetView};
+use scroll::types::{MacAddress, F80};
+use scroll::{to_vec, AlignedBuffer, BitCursor, BitWriter, Counter, Cread, CowBuffer, ErasedPread, Error, GroupVarint, IOread, IOwrite, PageProvider, PagedSource, PreadExt, PwriteExt, Pread, Pwrite, Sleb128, StrTable, Uleb128, BE, LE};
+#[cfg(feature = "bigint")]
+use scroll::BigIntCtx;
+#[cfg(feature = "encoding")]
+use scroll::{EncodingCtx, EncodingMode};
+#[cfg(feature = "zeroize")]
+use scroll::SecretBuffer;
+
+/// Offsets, lengths and counts at and around the interesting boundaries of a source of `len` bytes
+fn numbers(len: usize) -> Vec<usize> {
+    let mut numbers = vec![0, 1, 2, 3, 4, 7, 8, 9, 16, 255, 256, 65535, 65536, 1 << 31, 1 << 32];
+    numbers.extend([len / 2, len.saturating_sub(1), len, len + 1, len + 8]);
+    numbers.extend([isize::MAX as usize, isize::MAX as usize + 1, usize::MAX / 2, usize::MAX - 8, usize::MAX - 1, usize::MAX]);
+    numbers
+}
+
+/// Sources: empty, short, all zeroes, all ones (endless leb128s, no delimiters), and pseudo-random
+fn sources() -> Vec<Vec<u8>> {
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let random = (0..97).map(|_| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state as u8
+    }).collect();
+    vec![vec![], vec![0x80], vec![0; 33], vec![0xff; 64], random]
+}
+
+/// A value as large as its context says, as a user's type sized by a header field could be
+struct Blob;
+
+impl<'a> TryFromCtx<'a, usize> for Blob {
+    type Error = Error;
+    fn try_from_ctx(src: &'a [u8], len: usize) -> Result<(Self, usize), Error> {
+        src.pread_with::<&[u8]>(0, len).map(|_| (Blob, len))
+    }
+}
+
+impl FromCtx<usize> for Blob {
+    fn from_ctx(_: &[u8], _: usize) -> Self {
+        Blob
+    }
+}
+
+impl IntoCtx<usize> for Blob {
+    fn into_ctx(self, _: &mut [u8], _: usize) {}
+}
+
+impl SizeWith<usize> for Blob {
+    fn size_with(&len: &usize) -> usize {
+        len
+    }
+}
+
+/// A byte written at the offset it holds, as a user's type placing a field at an offset from a header could be
+#[derive(Clone, Copy)]
+struct At(usize);
+
+impl TryIntoCtx for At {
+    type Error = Error;
+    fn try_into_ctx(self, dst: &mut [u8], _: ()) -> Result<usize, Error> {
+        dst.pwrite(1u8, self.0).map(|_| self.0 + 1)
+    }
+}
+
+/// A source served in 7 byte pages
+struct Pages<'a>(&'a [u8]);
+
+impl<'a> PageProvider for Pages<'a> {
+    fn page_size(&self) -> usize {
+        7
+    }
+    fn total_len(&self) -> u64 {
+        self.0.len() as u64
+    }
+    fn page(&self, index: u64) -> Result<&[u8], Error> {
+        self.0.chunks(7).nth(index as usize).ok_or(Error::BadOffset(index as usize))
+    }
+}
+
+#[test]
+fn no_panics() {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let mut panicked = Vec::new();
+    macro_rules! check {
+        ($what:expr, $call:expr) => {
+            if panic::catch_unwind(AssertUnwindSafe(|| { let _ = $call; })).is_err() {
+                panicked.push($what);
+            }
+        };
+    }
+    for src in sources() {
+        let src = &src[..];
+        let len = src.len();
+        for &o in &numbers(len) {
+            let at = format!("len {} offset {}", len, o);
+            check!(format!("u64 {}", at), src.pread_with::<u64>(o, LE));
+            check!(format!("gread f64 {}", at), src.gread_with::<f64>(&mut { o }, LE));
+            check!(format!("i128 {}", at), src.pread_be::<i128>(o));
+            check!(format!("cstr {}", at), src.pread::<&CStr>(o));
+            check!(format!("uleb128 {}", at), src.pread::<Uleb128>(o));
+            check!(format!("sleb128 {}", at), src.pread::<Sleb128>(o));
+            check!(format!("leb128 u32 {}", at), src.gread_leb128_u32(&mut { o }));
+            check!(format!("bulk uleb128 {}", at), decode_uleb128_all(src, o));
+            check!(format!("delimited str {}", at), src.pread_with::<&str>(o, StrCtx::Delimiter(0)));
+            for width in 0..=9 {
+                check!(format!("prefixed str {} {}", width, at), src.pread_with::<&str>(o, StrCtx::Prefixed { width, endian: LE }));
+                let mut dst = src.to_vec();
+                check!(format!("write prefixed str {} {}", width, at), dst.pwrite_with(Str("abc"), o, StrCtx::Prefixed { width, endian: BE }));
+            }
+            check!(format!("bools {}", at), src.pread_with::<[bool; 13]>(o, BitOrder::MsbFirst));
+            check!(format!("remaining {}", at), src.gread_remaining(&mut { o }));
+            check!(format!("inout partial {}", at), src.gread_inout_partial(&mut { o }, &mut [0u8; 40]));
+            check!(format!("line {}", at), src.gread_line(&mut { o }));
+            check!(format!("group varint {}", at), GroupVarint::read(src, &mut { o }));
+            check!(format!("write group varint {}", at), encode_group_varint(&mut src.to_vec(), &mut { o }, &[u32::MAX, 1, 256, 7, 1 << 20]));
+            let width = o.min(u32::MAX as usize) as u32;
+            check!(format!("read bits {}", at), { let mut bits = BitCursor::new(src, BitOrder::MsbFirst); bits.read_bits(3).and_then(|_| bits.read_bits(width)) });
+            check!(format!("write bits {}", at), { let mut dst = src.to_vec(); let mut bits = BitWriter::new(&mut dst[..], BitOrder::LsbFirst); bits.write_bits(5, 3).and_then(|_| bits.write_bits(0, width)) });
+            check!(format!("write aligned bits {}", at), { let mut dst = src.to_vec(); let mut bits = BitWriter::new(&mut dst[..], BitOrder::MsbFirst); bits.write_bits(1, 8).and_then(|_| bits.gwrite_with(7u64, LE)).map(|_| bits.finish()) });
+            for (count, stride) in [(0, 0), (1, 0), (2, 0), (3, 7), (usize::MAX, 1), (2, usize::MAX), (usize::MAX / 2, 2), (0, usize::MAX / 64)] {
+                check!(format!("strided {} {} {}", count, stride, at), src.pread_strided::<u32, _>(o, count, stride, LE));
+                check!(format!("strided iter {} {} {}", count, stride, at), src.pread_strided_iter::<u16, _>(o, count, stride, BE).map(|iter| iter.take(4).count()));
+                check!(format!("deinterleaved {} {} {}", count, stride, at), src.pread_deinterleaved::<u16, _>(o, stride, count, BE));
+            }
+            check!(format!("line bytes {}", at), src.gread_line_bytes(&mut { o }, LineCtx { strip_cr: true, unterminated: false }));
+            check!(format!("magic {}", at), src.pread_magic(o, b"\xff\xff"));
+            check!(format!("magic u32 {}", at), src.pread_magic_u32(o, 0, LE));
+            check!(format!("inout {}", at), src.gread_inout_with(&mut { o }, &mut [0u32; 3], LE));
+            check!(format!("inout endian {}", at), src.pread_inout_endian(o, &mut [0u16; 5], LE));
+            check!(format!("string list {}", at), src.pread_string_list::<u32>(o, LE));
+            check!(format!("n bytes string {}", at), src.gread_n_bytes_to_string::<u64>(&mut { o }, LE));
+            check!(format!("diff {}", at), src.pread_diff::<u32, _>(&src[len / 2..], o, LE));
+            check!(format!("strtab {}", at), StrTable::new(src).get(o));
+            check!(format!("until eof {}", at), src.gread_into_vec_until_eof::<u16>(&mut { o }, LE));
+            for ctx in [TimeCtx::UnixSeconds32(LE), TimeCtx::UnixSeconds64(BE), TimeCtx::UnixMillis64(LE), TimeCtx::WindowsFiletime(BE)] {
+                check!(format!("time {:?} {}", ctx, at), src.pread_with::<SystemTime>(o, ctx));
+                check!(format!("duration {:?} {}", ctx, at), src.pread_with::<Duration>(o, ctx));
+                let mut dst = src.to_vec();
+                check!(format!("write time {:?} {}", ctx, at), dst.pwrite_with(SystemTime::now(), o, ctx));
+                check!(format!("write duration {:?} {}", ctx, at), dst.pwrite_with(Duration::MAX, o, ctx));
+            }
+            check!(format!("escaped {}", at), src.pread_with::<Vec<u8>>(o, EscapedCtx { escape_byte: 0xff, sentinel: 0 }));
+            check!(format!("lossy {}", at), src.pread_with::<Cow<str>>(o, Utf8Lossy(StrCtx::Delimiter(0))));
+            check!(format!("encoded len {}", at), src.pread_with::<EncodedLen<&str>>(o, EncodedLenCtx::<u64, _>::new(BE, StrCtx::default())));
+            check!(format!("encoded len str {}", at), src.pread_with::<EncodedLen<&str>>(o, EncodedLenCtx::<u32, _>::new(LE, StrCtx::default())));
+            check!(format!("f80 {}", at), src.pread_with::<F80>(o, LE));
+            check!(format!("mac {}", at), src.pread::<MacAddress>(o));
+
+            let mut dst = src.to_vec();
+            check!(format!("pwrite u32 {}", at), dst.pwrite_with(0xdead_beefu32, o, LE));
+            check!(format!("gwrite u128 {}", at), dst.gwrite_with(1u128, &mut { o }, LE));
+            check!(format!("pwrite bytes {}", at), dst.pwrite(&b"abc"[..], o));
+            check!(format!("pwrite str {}", at), dst.pwrite_with(String::from("abc"), o, StrCtx::Prefixed { width: 2, endian: LE }));
+            check!(format!("pwrite slice {}", at), dst.pwrite_slice_with(o, &[1u16, 2, 3], LE));
+            check!(format!("reserve {}", at), Pwrite::reserve::<u32>(&mut dst[..], &mut { o }, LE));
+            check!(format!("length prefix {}", at), dst.pwrite_with_length_prefix::<_, u16>(3u8, &mut { o }, LE));
+            check!(format!("to vec {}", at), to_vec(At(o), ()));
+            check!(format!("to vec fixed str {}", at), to_vec(Str("abc"), StrCtx::Fixed { len: o, pad: 0 }));
+            check!(format!("counter {}", at), Counter::new().pwrite_with(At(o), o, ()));
+            #[cfg(feature = "zeroize")]
+            check!(format!("secret buffer {}", at), SecretBuffer::from(src.to_vec()).gwrite_with(At(o), ()));
+
+            for &n in &numbers(len) {
+                let at = format!("{} n {}", at, n);
+                check!(format!("bytes {}", at), src.pread_with::<&[u8]>(o, n));
+                check!(format!("length str {}", at), src.pread_with::<&str>(o, StrCtx::Length(n)));
+                check!(format!("until str {}", at), src.pread_with::<&str>(o, StrCtx::DelimiterUntil(0, n)));
+                check!(format!("fixed str {}", at), src.pread_with::<&str>(o, StrCtx::Fixed { len: n, pad: b' ' }));
+                check!(format!("write fixed str {}", at), src.to_vec().pwrite_with(Str("abc"), o, StrCtx::Fixed { len: n, pad: 0 }));
+                check!(format!("vec {}", at), src.pread_with::<Vec<u16>>(o, Count(n, LE)));
+                check!(format!("string {}", at), src.pread_with::<String>(o, TryAlloc(StrCtx::Length(n))));
+                check!(format!("bitmap {}", at), src.pread_with::<Vec<bool>>(o, BitsCtx::new(n, BitOrder::LsbFirst)));
+                check!(format!("map {}", at), src.pread_with::<BTreeMap<u8, u16>>(o, MapCtx::new(n, LE, Duplicates::LastWins)));
+                check!(format!("bulk uleb128 n {}", at), decode_uleb128_n(src, o, n));
+                check!(format!("group varint n {}", at), decode_group_varint_n(src, o, n));
+                check!(format!("group varint partial {}", at), GroupVarint::read_partial(src, &mut { o }, n));
+                check!(format!("window {}", at), src.gread_window(&mut { o }, n));
+                check!(format!("bytes owned {}", at), src.pread_bytes_owned(o, n));
+                check!(format!("remaining until {}", at), src.gread_remaining_until(&mut { o }, n));
+                check!(format!("exactly {}", at), src.gread_exactly(&mut { o }, n));
+                check!(format!("align {}", at), src.gread_align(&mut { o }, n));
+                check!(format!("size product {}", at), src.pread_checked_size_product(o, n, n));
+                check!(format!("size product by 8 {}", at), src.pread_checked_size_product(o, n, 8));
+                check!(format!("collect {}", at), src.pread_collect::<u32, Vec<u32>>(o, n, LE));
+                check!(format!("strtab range {}", at), StrTable::from_range(src, o..n));
+                check!(format!("erased {}", at), (&src as &dyn ErasedPread).pread_with::<Blob, _>(o, n));
+                let paged = PagedSource::new(Pages(src)).unwrap();
+                check!(format!("paged {}", at), paged.pread_with::<Blob, _>(o, n));
+                check!(format!("paged slice {}", at), paged.pread_slice(o, n));
+                check!(format!("paged cow {}", at), paged.pread_cow(o, n));
+                check!(format!("ioread {}", at), Cursor::new(src).ioread_with::<Blob>(n));
+                check!(format!("iowrite {}", at), Cursor::new(src.to_vec()).iowrite_with(Blob, n));
+                for radix in [0, 1, 2, 10, 36, 37] {
+                    check!(format!("ascii radix {} {}", radix, at), src.pread_with::<Ascii<i64>>(o, AsciiNum { radix, len: n, pad: AsciiPad::Space }));
+                }
+                check!(format!("view {}", at), OffsetView::new(src, usize::MAX - n).window(o, n).map(|view| view.pread::<u32, _>(0)));
+                check!(format!("align up {}", at), align_up(o, n));
+                check!(format!("align down {}", at), align_down(o, n));
+                check!(format!("offset {}", at), Offset(o).checked_add(n).and_then(|o| o.checked_sub(n.wrapping_mul(3))));
+                #[cfg(feature = "bigint")]
+                check!(format!("bigint {}", at), src.pread_with::<BigInt>(o, BigIntCtx::new(n, BE)));
+                #[cfg(feature = "bigint")]
+                check!(format!("biguint {}", at), src.pread_with::<BigUint>(o, (n, LE)));
+                #[cfg(feature = "encoding")]
+                check!(format!("decoded {}", at), src.pread_with::<String>(o, EncodingCtx::new(WINDOWS_1252, StrCtx::DelimiterUntil(0, n), EncodingMode::Lossy)));
+                check!(format!("zeroed {}", at), CowBuffer::try_zeroed(n.min(1 << 20)));
+                check!(format!("aligned {}", at), AlignedBuffer::with_alignment(o.min(1 << 20), n));
+
+                let mut dst = src.to_vec();
+                check!(format!("repeat {}", at), dst.pwrite_repeat(7u16, n, o, LE));
+                check!(format!("padded {}", at), dst.gwrite_struct_padded(7u16, n, &mut { o }, LE));
+                check!(format!("pattern {}", at), dst.gwrite_pattern(b"xy", n, &mut { o }));
+                check!(format!("copy within {}", at), dst.pwrite_copy_within(o..n, n));
+                check!(format!("copy within reversed {}", at), dst.pwrite_copy_within(n..o, o));
+                check!(format!("write align {}", at), dst.gwrite_align(&mut { o }, n, 0));
+                check!(format!("update length {}", at), dst.pwrite_then_update_length::<_, u32, _>(o, n, 5u16, LE, LE));
+                check!(format!("write bitmap {}", at), dst.pwrite_with(&[true; 11][..], o, BitOrder::LsbFirst));
+                check!(format!("write vec {}", at), dst.pwrite_with(vec![1u16, 2], o, Count(n, LE)));
+                check!(format!("write ascii {}", at), dst.pwrite_with(Ascii(-9i64), o, AsciiNum { radix: 10, len: n, pad: AsciiPad::Zero }));
+                #[cfg(feature = "bigint")]
+                check!(format!("write bigint {}", at), dst.pwrite_with(BigInt::from(-300), o, BigIntCtx::new(n, LE)));
+                #[cfg(feature = "encoding")]
+                check!(format!("write encoded {}", at), dst.pwrite_with(Str("abc"), o, EncodingCtx::new(WINDOWS_1252, StrCtx::Length(n), EncodingMode::Strict)));
+            }
+        }
+    }
+    // the harness does catch one: `cread` panics on a short buffer by design
+    let caught = panic::catch_unwind(|| [0u8; 2].cread_with::<u32>(0, LE)).is_err();
+    panic::set_hook(hook);
+    assert!(caught);
+    assert!(panicked.is_empty(), "{} calls panicked, e.g.:\n{}", panicked.len(), panicked.iter().take(40).cloned().collect::<Vec<_>>().join("\n"));
+}
@@ -0,0 +1,113 @@
+//! Builds scroll without `std`, with only the `alloc` feature, and round trips the owned types through its buffers
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use scroll::ctx::{Count, Str, StrCtx};
+use scroll::{CowBuffer, Endian, Error, Pread, Pwrite, SmallBuffer};
+
+/// A record with a fixed header, a length prefixed payload, and a null terminated name
+#[derive(Debug, PartialEq, Eq)]
+pub struct Record {
+    pub id: u32,
+    pub payload: Vec<u16>,
+    pub name: String,
+}
+
+impl Record {
+    /// Appends the record to a fixed capacity buffer
+    pub fn write<const N: usize>(&self, buffer: &mut SmallBuffer<N>, endian: Endian) -> Result<usize, Error> {
+        let start = buffer.len();
+        buffer.gwrite_with(self.id, endian)?;
+        buffer.gwrite_with(self.payload.len() as u16, endian)?;
+        for &element in &self.payload {
+            buffer.gwrite_with(element, endian)?;
+        }
+        buffer.gwrite_with(Str(&self.name), StrCtx::Delimiter(0))?;
+        Ok(buffer.len() - start)
+    }
+
+    /// Reads a record written by `write`
+    pub fn read(bytes: &[u8], offset: &mut usize, endian: Endian) -> Result<Record, Error> {
+        let id = bytes.gread_with(offset, endian)?;
+        let len: u16 = bytes.gread_with(offset, endian)?;
+        let payload = bytes.gread_with(offset, Count(len as usize, endian))?;
+        let name: &str = bytes.gread_with(offset, StrCtx::Delimiter(0))?;
+        Ok(Record { id, payload, name: String::from(name) })
+    }
+}
+
+/// Copies `bytes` into a buffer which is borrowed until written, and overwrites the `u32` at `offset` with `id`
+pub fn patch_id(bytes: &[u8], offset: usize, id: u32, endian: Endian) -> Result<Vec<u8>, Error> {
+    let mut buffer = CowBuffer::new(bytes);
+    buffer.pwrite_with(id, offset, endian)?;
+    Ok(buffer.into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::ffi::CString;
+    use alloc::string::ToString;
+    use alloc::vec;
+    use scroll::{CowBuffer, Error, Pread, Pwrite, SmallBuffer, BE, LE};
+
+    use super::{patch_id, Record};
+
+    fn record() -> Record {
+        Record { id: 0xdead_beef, payload: vec![1, 2, 0x300], name: "kernel".to_string() }
+    }
+
+    #[test]
+    fn small_buffer_round_trip() {
+        for &endian in &[LE, BE] {
+            let mut buffer = SmallBuffer::<32>::new();
+            let size = record().write(&mut buffer, endian).unwrap();
+            assert_eq!(size, 4 + 2 + 6 + 7);
+            assert_eq!(buffer.len(), size);
+
+            let mut offset = 0;
+            assert_eq!(Record::read(buffer.as_slice(), &mut offset, endian).unwrap(), record());
+            assert_eq!(offset, size);
+        }
+    }
+
+    #[test]
+    fn small_buffer_overflow() {
+        let mut buffer = SmallBuffer::<8>::new();
+        assert!(matches!(record().write(&mut buffer, LE), Err(Error::TooBig { len: 8, .. })));
+    }
+
+    #[test]
+    fn cow_buffer_round_trip() {
+        let mut buffer = SmallBuffer::<32>::new();
+        record().write(&mut buffer, BE).unwrap();
+
+        let borrowed = CowBuffer::new(buffer.as_slice());
+        assert!(!borrowed.is_owned());
+        assert_eq!(borrowed.pread_with::<u32>(0, BE).unwrap(), 0xdead_beef);
+
+        let patched = patch_id(buffer.as_slice(), 0, 7, BE).unwrap();
+        assert_eq!(Record::read(&patched, &mut 0, BE).unwrap(), Record { id: 7, ..record() });
+        assert_eq!(buffer.pread_with::<u32>(0, BE).unwrap(), 0xdead_beef);
+    }
+
+    #[test]
+    fn c_string_round_trip() {
+        let mut bytes = vec![0u8; 8];
+        let name = CString::new("alloc").unwrap();
+        assert_eq!(bytes.pwrite(name.clone(), 0).unwrap(), 6);
+        assert_eq!(bytes.pread::<CString>(0).unwrap(), name);
+    }
+
+    #[test]
+    fn allocating_errors() {
+        let err = Error::Custom("bad record".to_string());
+        assert_eq!(err.to_string(), "bad record");
+        let err = Error::Field { name: "id", source: alloc::boxed::Box::new(Error::BadOffset(4)) };
+        assert_eq!(err.offset(), Some(4));
+    }
+}
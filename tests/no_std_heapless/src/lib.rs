@@ -0,0 +1,120 @@
+//! Builds scroll without `std` or `alloc`, with only the `heapless` feature, and round trips a packet whose records are
+//! collected into fixed capacity containers
+
+#![no_std]
+
+use heapless::{String, Vec};
+
+use scroll::ctx::{Count, StrCtx};
+use scroll::{Endian, Error, Pread, Pwrite};
+
+/// The most records a packet holds
+pub const MAX_RECORDS: usize = 4;
+
+/// A reading of a sensor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reading {
+    pub sensor: u8,
+    pub value: i16,
+}
+
+impl Reading {
+    /// The number of bytes a reading takes up
+    pub const SIZE: usize = 3;
+}
+
+impl<'a> scroll::ctx::TryFromCtx<'a, Endian> for Reading {
+    type Error = Error;
+    fn try_from_ctx(src: &'a [u8], endian: Endian) -> Result<(Self, usize), Error> {
+        let offset = &mut 0;
+        let reading = Reading { sensor: src.gread_with(offset, endian)?, value: src.gread_with(offset, endian)? };
+        Ok((reading, *offset))
+    }
+}
+
+impl scroll::ctx::TryIntoCtx<Endian> for &Reading {
+    type Error = Error;
+    fn try_into_ctx(self, dst: &mut [u8], endian: Endian) -> Result<usize, Error> {
+        let offset = &mut 0;
+        dst.gwrite_with(self.sensor, offset, endian)?;
+        dst.gwrite_with(self.value, offset, endian)?;
+        Ok(*offset)
+    }
+}
+
+/// A packet: the name of the station, null terminated, then a count of readings, and the readings
+#[derive(Debug, PartialEq, Eq)]
+pub struct Packet {
+    pub station: String<16>,
+    pub readings: Vec<Reading, MAX_RECORDS>,
+}
+
+impl Packet {
+    /// Writes the packet into `bytes`, returning how many bytes it took up
+    pub fn write(&self, bytes: &mut [u8], endian: Endian) -> Result<usize, Error> {
+        let offset = &mut 0;
+        bytes.gwrite_with(&self.station, offset, StrCtx::Delimiter(0))?;
+        bytes.gwrite_with(self.readings.len() as u8, offset, endian)?;
+        bytes.gwrite_with(&self.readings, offset, Count(self.readings.len(), endian))?;
+        Ok(*offset)
+    }
+
+    /// Reads a packet written by `write`
+    pub fn read(bytes: &[u8], offset: &mut usize, endian: Endian) -> Result<Packet, Error> {
+        let station = bytes.gread_with(offset, StrCtx::Delimiter(0))?;
+        let count: u8 = bytes.gread_with(offset, endian)?;
+        let readings = bytes.gread_with(offset, Count(count as usize, endian))?;
+        Ok(Packet { station, readings })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::fmt::Write;
+    use heapless::{String, Vec};
+    use scroll::{Error, Pread, BE, LE};
+
+    use super::{Packet, Reading, MAX_RECORDS};
+
+    fn packet() -> Packet {
+        let mut station = String::new();
+        station.push_str("north").unwrap();
+        let mut readings = Vec::new();
+        readings.push(Reading { sensor: 1, value: -40 }).unwrap();
+        readings.push(Reading { sensor: 7, value: 0x0123 }).unwrap();
+        Packet { station, readings }
+    }
+
+    #[test]
+    fn packet_round_trip() {
+        for &endian in &[LE, BE] {
+            let mut bytes = [0u8; 64];
+            let size = packet().write(&mut bytes, endian).unwrap();
+            assert_eq!(size, 6 + 1 + 2 * Reading::SIZE);
+
+            let offset = &mut 0;
+            assert_eq!(Packet::read(&bytes, offset, endian).unwrap(), packet());
+            assert_eq!(*offset, size);
+        }
+        let mut bytes = [0u8; 64];
+        packet().write(&mut bytes, BE).unwrap();
+        assert_eq!(&bytes[..13], b"north\0\x02\x01\xff\xd8\x07\x01\x23");
+    }
+
+    #[test]
+    fn capacity_is_never_truncated() {
+        let mut bytes = [0u8; 64];
+        let size = packet().write(&mut bytes, LE).unwrap();
+        bytes[6] = MAX_RECORDS as u8 + 1;
+        assert!(matches!(Packet::read(&bytes[..size], &mut 0, LE), Err(Error::BadInput { size: 5, .. })));
+        assert!(matches!(bytes.pread_with::<String<4>>(0, scroll::ctx::StrCtx::Delimiter(0)), Err(Error::BadInput { size: 5, .. })));
+
+        // a count within the capacity, but past the end of the input, fails on the missing record
+        bytes[6] = MAX_RECORDS as u8;
+        // with `alloc`, scroll says which record it was, and without, it can only pass the error on
+        let mut err = String::<128>::new();
+        write!(err, "{:?}", Packet::read(&bytes[..size], &mut 0, LE).unwrap_err()).unwrap();
+        assert!(err.contains("Incomplete { needed: 1 }"), "{}", err);
+        assert!(packet().write(&mut [0u8; 12], LE).is_err());
+    }
+}
@@ -12,10 +12,10 @@ fn readme_test() {
     let outdir = depdir.parent().unwrap();
     let extern_arg = format!("scroll={}", outdir.join("libscroll.rlib").to_string_lossy());
     let mut cmd = Command::new(rustdoc);
-    cmd.args(&["--verbose", "--test", "-L"])
-        .arg(&outdir)
+    cmd.args(["--verbose", "--test", "-L"])
+        .arg(outdir)
         .arg("-L")
-        .arg(&depdir)
+        .arg(depdir)
         .arg("--extern")
         .arg(&extern_arg)
         .arg(&readme);
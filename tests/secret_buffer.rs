@@ -0,0 +1,116 @@
+#![cfg(feature = "zeroize")]
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::slice;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use scroll::ctx::{Ascii, AsciiNum, AsciiPad, TryIntoCtx};
+use scroll::{Error, Pread, Pwrite, SecretBuffer, BE};
+
+/// Counts the allocations released while `TRACKING` is set, and how many of them still held a nonzero byte; a
+/// reallocation releases the old allocation, so it is counted too
+struct Inspecting;
+
+static TRACKING: AtomicBool = AtomicBool::new(false);
+static RELEASED: AtomicUsize = AtomicUsize::new(0);
+static UNWIPED: AtomicUsize = AtomicUsize::new(0);
+
+fn inspect(ptr: *mut u8, layout: Layout) {
+    if TRACKING.load(Ordering::SeqCst) {
+        RELEASED.fetch_add(1, Ordering::SeqCst);
+        // the allocation is still ours until it's handed back
+        if unsafe { slice::from_raw_parts(ptr, layout.size()) }.iter().any(|&b| b != 0) {
+            UNWIPED.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+}
+
+unsafe impl GlobalAlloc for Inspecting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        inspect(ptr, layout);
+        System.dealloc(ptr, layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        inspect(ptr, layout);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+/// A value which always fails to write, with the error `0` makes
+#[derive(Clone)]
+struct Fails(fn() -> Error);
+
+impl TryIntoCtx for Fails {
+    type Error = Error;
+    fn try_into_ctx(self, _: &mut [u8], _: ()) -> Result<usize, Error> {
+        Err((self.0)())
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Inspecting = Inspecting;
+
+/// The allocations `f` releases, and how many of those weren't wiped; the only test in this file, so nothing else
+/// allocates meanwhile
+fn releases(f: impl FnOnce()) -> (usize, usize) {
+    RELEASED.store(0, Ordering::SeqCst);
+    UNWIPED.store(0, Ordering::SeqCst);
+    TRACKING.store(true, Ordering::SeqCst);
+    f();
+    TRACKING.store(false, Ordering::SeqCst);
+    (RELEASED.load(Ordering::SeqCst), UNWIPED.load(Ordering::SeqCst))
+}
+
+#[test]
+fn secret_buffer_wipes() {
+    let secret = [0xa5u8; 24];
+
+    // the inspection does see secrets left behind by a plain `Vec`, on growth and on drop
+    let (released, unwiped) = releases(|| {
+        let mut bytes = Vec::with_capacity(4);
+        bytes.extend_from_slice(&secret[..4]);
+        bytes.extend_from_slice(&secret[4..]);
+    });
+    assert!(released >= 2);
+    assert_eq!(unwiped, released);
+
+    // every allocation a `SecretBuffer` releases, growing or dropped, has been wiped
+    let (released, unwiped) = releases(|| {
+        let mut key = SecretBuffer::try_with_capacity(4).unwrap();
+        key.extend_from_slice(&secret[..4]).unwrap();
+        key.extend_from_slice(&secret[4..]).unwrap();
+        assert!(key.capacity() >= 24);
+        for _ in 0..16 {
+            key.gwrite_with(0xa5a5_a5a5u32, BE).unwrap();
+        }
+        assert_eq!(key.len(), 24 + 64);
+        key.pwrite_with(0x0102u16, 0, BE).unwrap();
+        assert_eq!(key.pread_with::<u16>(0, BE).unwrap(), 0x0102);
+        key.truncate(8);
+        assert_eq!(key.as_slice(), [0x01, 0x02, 0xa5, 0xa5, 0xa5, 0xa5, 0xa5, 0xa5]);
+    });
+    assert!(released >= 3, "the buffer grew at least twice, and was dropped: {}", released);
+    assert_eq!(unwiped, 0);
+
+    // a value which doesn't fit grows the buffer, and one which fails to write isn't appended
+    let mut key = SecretBuffer::from(secret.to_vec());
+    assert!(key.gwrite_with(&[0xffu8; 100][..], ()).is_ok());
+    assert_eq!(key.len(), 124);
+    let bad_radix = AsciiNum { radix: 1, len: 4, pad: AsciiPad::Zero };
+    assert!(key.gwrite_with(Ascii(7u64), bad_radix).is_err());
+    assert_eq!(key.len(), 124);
+
+    // errors which more room doesn't fix are returned without growing, and the growth is bounded
+    let capacity = key.capacity();
+    assert!(matches!(key.gwrite_with(Fails(|| Error::BadOffset(3)), ()), Err(Error::BadOffset(3))));
+    assert_eq!(key.capacity(), capacity);
+    assert!(matches!(key.gwrite_with(Fails(|| Error::BadOffset(usize::MAX)), ()), Err(Error::LimitExceeded("size"))));
+    assert_eq!(key.len(), 124);
+    let (_, unwiped) = releases(|| drop(key));
+    assert_eq!(unwiped, 0);
+
+    let key = SecretBuffer::from(secret.to_vec());
+    assert_eq!(format!("{:?}", key), "SecretBuffer { len: 24, .. }");
+}